@@ -0,0 +1,409 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use prvw_core::cache;
+use prvw_core::codex::{self, AnalysisRequest};
+use prvw_core::config;
+use prvw_core::diff_parser;
+use prvw_core::gh::{self, run_gh};
+use prvw_core::guidelines;
+use prvw_core::history;
+use prvw_core::local_range;
+use prvw_core::provider::ProviderRegistry;
+use prvw_core::push_hook::FAIL_ON_LEVELS;
+use prvw_core::report::render_review_markdown;
+use prvw_core::session;
+use prvw_core::settings::{self, RunDefaults};
+use prvw_core::types::{AnalysisResponse, RepoContext, ReviewSession};
+
+use crate::mcp;
+
+/// Bundle identifier of the app; its data directory is shared with the CLI.
+const APP_IDENTIFIER: &str = "com.masakitakegami.prvw";
+
+const USAGE: &str = "Usage:
+  prvw-cli analyze <owner/repo#123 | PR URL> [options]
+      Analyze a GitHub PR and print the review report (Markdown, or JSON with --json).
+  prvw-cli check --repo <path> --range <base..head> [--fail-on low|medium|high] [options]
+      Analyze a local revision range; exits 1 when a group is at or above --fail-on (high).
+  prvw-cli mcp
+      Serve list_prs, get_pr_diff, parse_diff and analyze as MCP tools over stdio.
+
+Options:
+  --json              Print the analysis response as JSON instead of Markdown
+  --provider <name>   codex, claude or openai (default: from Settings)
+  --model <model>     Model passed to the provider (default: from Settings)
+  --lang <lang>       Output language (default: from Settings)
+  --guidelines        Include the repo's review guidelines (CONTRIBUTING.md, ...)
+  --force             Ignore cached analyses
+";
+
+/// Exit code of a `check` that found changes at or above `--fail-on`.
+const EXIT_FINDINGS: i32 = 1;
+/// Exit code of usage errors and failed runs.
+const EXIT_ERROR: i32 = 2;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    pub json: bool,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub lang: Option<String>,
+    pub guidelines: bool,
+    pub force: bool,
+    pub repo_path: Option<String>,
+    pub range: Option<String>,
+    pub fail_on: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Cli {
+    Help,
+    Analyze { target: String, options: Options },
+    Check { options: Options },
+    Mcp,
+}
+
+fn parse_args(args: &[String]) -> Result<Cli, String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Ok(Cli::Help);
+    };
+    let mut options = Options::default();
+    let mut positional = Vec::new();
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        let mut value = |name: &str| {
+            rest.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+        match arg.as_str() {
+            "--json" => options.json = true,
+            "--guidelines" => options.guidelines = true,
+            "--force" => options.force = true,
+            "--provider" => options.provider = Some(value(arg.as_str())?),
+            "--model" => options.model = Some(value(arg.as_str())?),
+            "--lang" => options.lang = Some(value(arg.as_str())?),
+            "--repo" => options.repo_path = Some(value(arg.as_str())?),
+            "--range" => options.range = Some(value(arg.as_str())?),
+            "--fail-on" => options.fail_on = Some(value(arg.as_str())?),
+            "-h" | "--help" => return Ok(Cli::Help),
+            flag if flag.starts_with('-') => return Err(format!("Unknown option {}", flag)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+    match command.as_str() {
+        "help" | "-h" | "--help" => Ok(Cli::Help),
+        "analyze" => match positional.as_slice() {
+            [target] => Ok(Cli::Analyze {
+                target: target.clone(),
+                options,
+            }),
+            _ => Err("analyze takes one PR (owner/repo#123 or a PR URL)".to_string()),
+        },
+        "mcp" => match positional.first() {
+            Some(arg) => Err(format!("Unexpected argument {}", arg)),
+            None => Ok(Cli::Mcp),
+        },
+        "check" => {
+            if !positional.is_empty() {
+                return Err(format!("Unexpected argument {}", positional[0]));
+            }
+            if options.range.is_none() {
+                return Err("check needs --range <base..head>".to_string());
+            }
+            if let Some(level) = options.fail_on.as_deref() {
+                if !FAIL_ON_LEVELS.contains(&level) {
+                    return Err(format!(
+                        "Unknown risk level '{}'. Use one of: {}.",
+                        level,
+                        FAIL_ON_LEVELS.join(", ")
+                    ));
+                }
+            }
+            Ok(Cli::Check { options })
+        }
+        other => Err(format!("Unknown command '{}'", other)),
+    }
+}
+
+/// Whether a group's `risk` is at or above `fail_on` (both low/medium/high).
+fn at_or_above(risk: &str, fail_on: &str) -> bool {
+    let rank = |level: &str| FAIL_ON_LEVELS.iter().position(|l| *l == level);
+    match (rank(risk), rank(fail_on)) {
+        (Some(risk), Some(fail_on)) => risk >= fail_on,
+        _ => false,
+    }
+}
+
+/// The app's data directory as Tauri resolves it, so the CLI shares its settings and caches.
+pub fn app_data_dir() -> Option<PathBuf> {
+    let home = || std::env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(target_os = "macos") {
+        home().map(|h| h.join("Library/Application Support"))
+    } else if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| home().map(|h| h.join(".local/share")))
+    };
+    base.map(|b| b.join(APP_IDENTIFIER))
+}
+
+/// The app's config directory as Tauri resolves it, where `prvw.toml` lives.
+pub fn app_config_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "macos") || cfg!(windows) {
+        return app_data_dir();
+    }
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+        .map(|b| b.join(APP_IDENTIFIER))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PrView {
+    #[serde(default)]
+    body: String,
+    updated_at: String,
+}
+
+/// Analyze a diff with the provider, model and language from `options` or the repo's settings.
+pub fn analyze_hunks(
+    app_data_dir: Option<&Path>,
+    options: &Options,
+    repo: Option<&str>,
+    diff: String,
+    pr_body: Option<String>,
+    cache_owner: Option<RepoContext>,
+) -> Result<AnalysisResponse, String> {
+    let parsed = diff_parser::parse(diff, repo.map(str::to_string))?;
+    if parsed.hunks.is_empty() {
+        return Err("No hunks to analyze (all changes are ignored by the repo settings).".into());
+    }
+    let hunks_json = serde_json::to_string(&parsed.hunks)
+        .map_err(|e| format!("Failed to serialize hunks: {}", e))?;
+    let defaults = settings::for_repo(repo);
+    let run = RunDefaults::from_settings(
+        &defaults,
+        options.model.clone(),
+        options.lang.clone(),
+        options.provider.clone(),
+        None,
+        None,
+    );
+    let provider = ProviderRegistry::default().resolve(&run.provider)?;
+    // Guidelines failures only lose the extra context, as in the app.
+    let guidelines = repo
+        .filter(|_| options.guidelines)
+        .and_then(|repo| guidelines::fetch_review_guidelines(repo).ok().flatten());
+    let guidelines = config::with_prompt(guidelines, &defaults, "analysis");
+    let response = codex::run_analysis(
+        app_data_dir,
+        provider.as_ref(),
+        AnalysisRequest {
+            hunks_json,
+            pr_body,
+            hunk_summaries: None,
+            guidelines,
+            repo_context: None,
+            cache_owner,
+            model: run.model,
+            lang: run.lang,
+            options: run.options,
+            force: options.force,
+            timeout_minutes: run.timeout_minutes,
+        },
+    )?;
+    Ok(response)
+}
+
+/// Print the analysis as JSON or as the review summary, with `session`'s order, checks and skips.
+fn print_response(
+    response: &AnalysisResponse,
+    session: &ReviewSession,
+    json: bool,
+) -> Result<(), String> {
+    if json {
+        let out = serde_json::to_string_pretty(response)
+            .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+        println!("{}", out);
+    } else {
+        print!("{}", render_review_markdown(&response.result, session));
+    }
+    Ok(())
+}
+
+/// Body and diff of a PR; the diff goes through the disk cache keyed by the PR's last update.
+pub fn fetch_pr(
+    app_data_dir: Option<&Path>,
+    repo: &str,
+    pr_number: u32,
+    force: bool,
+) -> Result<(String, String), String> {
+    let output = run_gh(
+        &[
+            "pr",
+            "view",
+            "-R",
+            repo,
+            &pr_number.to_string(),
+            "--json",
+            "body,updatedAt",
+        ],
+        "gh pr view",
+    )?;
+    let pr: PrView = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse PR metadata: {}", e))?;
+    let diff = gh::fetch_pr_diff(app_data_dir, repo, pr_number, Some(&pr.updated_at), force)?;
+    Ok((pr.body, diff))
+}
+
+/// Analyze a GitHub PR, using its body as context.
+pub fn analyze_pr(
+    app_data_dir: Option<&Path>,
+    repo: &str,
+    pr_number: u32,
+    options: &Options,
+) -> Result<AnalysisResponse, String> {
+    let (body, diff) = fetch_pr(app_data_dir, repo, pr_number, options.force)?;
+    eprintln!("prvw: analyzing {}#{}...", repo, pr_number);
+    analyze_hunks(
+        app_data_dir,
+        options,
+        Some(repo),
+        diff,
+        Some(body).filter(|b| !b.trim().is_empty()),
+        Some(RepoContext {
+            repo: repo.to_string(),
+            pr_number,
+        }),
+    )
+}
+
+fn analyze(app_data_dir: Option<&Path>, target: &str, options: &Options) -> Result<i32, String> {
+    let (repo, pr_number) = gh::parse_pr_ref(target)?;
+    let response = analyze_pr(app_data_dir, &repo, pr_number, options)?;
+    let session = app_data_dir
+        .map(|dir| session::load(dir, &repo, pr_number))
+        .unwrap_or_default();
+    print_response(&response, &session, options.json)?;
+    Ok(0)
+}
+
+fn check(app_data_dir: Option<&Path>, options: &Options) -> Result<i32, String> {
+    let repo_path = options.repo_path.as_deref().unwrap_or(".");
+    let range = options.range.as_deref().unwrap_or_default();
+    let fail_on = options.fail_on.as_deref().unwrap_or("high");
+    let (repo, _root) = local_range::open(repo_path)?;
+    let (diff, commits) = local_range::range_diff(&repo, range)?;
+    if diff.files.is_empty() {
+        eprintln!("prvw: no changes in {}", range.trim());
+        return Ok(0);
+    }
+    let remotes = local_range::clone_remotes(&repo);
+    eprintln!("prvw: analyzing {}...", range.trim());
+    let response = analyze_hunks(
+        app_data_dir,
+        options,
+        remotes.repo.as_deref(),
+        diff.patch,
+        Some(local_range::describe(range.trim(), &commits)),
+        None,
+    )?;
+    // A local range has no PR, so no review session either.
+    print_response(&response, &ReviewSession::default(), options.json)?;
+    let findings: Vec<_> = response
+        .result
+        .groups
+        .iter()
+        .filter(|g| at_or_above(&g.risk, fail_on))
+        .collect();
+    for group in &findings {
+        eprintln!("prvw: [{}] {}: {}", group.risk, group.id, group.title);
+    }
+    Ok(if findings.is_empty() {
+        0
+    } else {
+        EXIT_FINDINGS
+    })
+}
+
+/// Entry point of the `prvw-cli` binary: runs a command against the app's settings and caches
+/// and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let cli = match parse_args(args) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("prvw: {}\n\n{}", e, USAGE);
+            return EXIT_ERROR;
+        }
+    };
+    let app_data_dir = app_data_dir();
+    if let Some(dir) = &app_data_dir {
+        history::init(dir);
+        settings::init(dir);
+    }
+    if let Some(dir) = app_config_dir() {
+        config::load(&dir);
+    }
+    let result = match &cli {
+        Cli::Help => {
+            print!("{}", USAGE);
+            Ok(0)
+        }
+        Cli::Analyze { target, options } => analyze(app_data_dir.as_deref(), target, options),
+        Cli::Check { options } => check(app_data_dir.as_deref(), options),
+        Cli::Mcp => mcp::serve(app_data_dir.as_deref()).map(|()| 0),
+    };
+    // Cache writes happen in the background; let them land before exiting.
+    cache::flush();
+    result.unwrap_or_else(|e| {
+        eprintln!("prvw: {}", e);
+        EXIT_ERROR
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn parses_commands() {
+        match parse_args(&args(&["analyze", "o/r#1", "--json", "--model", "m"])).unwrap() {
+            Cli::Analyze { target, options } => {
+                assert_eq!(target, "o/r#1");
+                assert!(options.json);
+                assert_eq!(options.model.as_deref(), Some("m"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(
+            parse_args(&args(&["check", "--range", "a..b", "--fail-on", "medium"])).unwrap(),
+            Cli::Check { .. }
+        ));
+        assert!(parse_args(&args(&["check"])).is_err());
+        assert_eq!(parse_args(&args(&["mcp"])).unwrap(), Cli::Mcp);
+        assert!(parse_args(&args(&["mcp", "extra"])).is_err());
+        assert!(parse_args(&args(&["check", "--range", "a..b", "--fail-on", "severe"])).is_err());
+        assert!(parse_args(&args(&["analyze", "--model"])).is_err());
+        assert_eq!(parse_args(&[]).unwrap(), Cli::Help);
+    }
+
+    #[test]
+    fn compares_risk_levels() {
+        assert!(at_or_above("high", "medium"));
+        assert!(at_or_above("medium", "medium"));
+        assert!(!at_or_above("low", "medium"));
+        assert!(!at_or_above("unknown", "low"));
+    }
+}
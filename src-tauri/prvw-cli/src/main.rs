@@ -0,0 +1,9 @@
+//! Headless prvw: analyze a PR or a local range from a terminal or CI, without the app.
+
+mod cli;
+mod mcp;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(cli::run(&args));
+}
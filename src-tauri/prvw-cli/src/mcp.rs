@@ -0,0 +1,391 @@
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use prvw_core::diff_parser;
+use prvw_core::gh;
+
+use crate::cli::{self, Options};
+
+/// Protocol revisions this server speaks; the newest is offered when the client asks for
+/// another one.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListPrsArgs {
+    repo: String,
+    #[serde(default = "default_state")]
+    state: String,
+    #[serde(default = "default_limit")]
+    limit: u32,
+    search: Option<String>,
+}
+
+fn default_state() -> String {
+    "open".to_string()
+}
+
+fn default_limit() -> u32 {
+    30
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetPrDiffArgs {
+    repo: String,
+    pr_number: u32,
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ParseDiffArgs {
+    diff_text: String,
+    repo: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnalyzeArgs {
+    /// `owner/repo#123` or a PR URL; otherwise `diff_text` is analyzed.
+    pr: Option<String>,
+    diff_text: Option<String>,
+    repo: Option<String>,
+    provider: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    #[serde(default)]
+    guidelines: bool,
+    #[serde(default)]
+    force: bool,
+}
+
+/// Tool definitions for `tools/list`.
+fn tools() -> Value {
+    let repo = json!({"type": "string", "description": "GitHub repository as owner/repo"});
+    let force = json!({"type": "boolean", "description": "Bypass the disk cache"});
+    json!([
+        {
+            "name": "list_prs",
+            "description": "List pull requests of a GitHub repository.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "repo": repo,
+                    "state": {"type": "string", "enum": ["open", "closed", "merged", "all"]},
+                    "limit": {"type": "integer", "minimum": 1},
+                    "search": {"type": "string", "description": "GitHub search query"}
+                },
+                "required": ["repo"]
+            }
+        },
+        {
+            "name": "get_pr_diff",
+            "description": "Fetch the unified diff of a pull request.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "repo": repo,
+                    "prNumber": {"type": "integer", "minimum": 1},
+                    "force": force
+                },
+                "required": ["repo", "prNumber"]
+            }
+        },
+        {
+            "name": "parse_diff",
+            "description": "Split a unified diff into hunks with ids H1, H2, ... Files matching \
+                            the repo's ignore globs are dropped.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "diffText": {"type": "string"},
+                    "repo": repo
+                },
+                "required": ["diffText"]
+            }
+        },
+        {
+            "name": "analyze",
+            "description": "Group the changes of a pull request (pr) or a unified diff \
+                            (diffText) by intent, with a risk level per group. Provider, model \
+                            and language default to the app's settings.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "pr": {"type": "string", "description": "owner/repo#123 or a PR URL"},
+                    "diffText": {"type": "string"},
+                    "repo": repo,
+                    "provider": {"type": "string", "enum": ["codex", "claude", "openai"]},
+                    "model": {"type": "string"},
+                    "lang": {"type": "string"},
+                    "guidelines": {
+                        "type": "boolean",
+                        "description": "Include the repo's review guidelines"
+                    },
+                    "force": force
+                }
+            }
+        }
+    ])
+}
+
+fn args<T: DeserializeOwned>(arguments: Value) -> Result<T, String> {
+    let arguments = if arguments.is_null() {
+        json!({})
+    } else {
+        arguments
+    };
+    serde_json::from_value(arguments).map_err(|e| format!("Invalid arguments: {}", e))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize result: {}", e))
+}
+
+/// Run a tool; the text is the tool's result or, on `Err`, its error message.
+fn call_tool(app_data_dir: Option<&Path>, name: &str, arguments: Value) -> Result<String, String> {
+    match name {
+        "list_prs" => {
+            let a: ListPrsArgs = args(arguments)?;
+            to_json(&gh::fetch_pr_list(
+                a.repo.trim(),
+                a.limit,
+                &a.state,
+                a.search,
+            )?)
+        }
+        "get_pr_diff" => {
+            let a: GetPrDiffArgs = args(arguments)?;
+            cli::fetch_pr(app_data_dir, a.repo.trim(), a.pr_number, a.force).map(|(_, diff)| diff)
+        }
+        "parse_diff" => {
+            let a: ParseDiffArgs = args(arguments)?;
+            to_json(&diff_parser::parse(a.diff_text, a.repo)?.hunks)
+        }
+        "analyze" => {
+            let a: AnalyzeArgs = args(arguments)?;
+            let options = Options {
+                provider: a.provider,
+                model: a.model,
+                lang: a.lang,
+                guidelines: a.guidelines,
+                force: a.force,
+                ..Options::default()
+            };
+            let response = match (a.pr, a.diff_text) {
+                (Some(pr), _) => {
+                    let (repo, pr_number) = gh::parse_pr_ref(&pr)?;
+                    cli::analyze_pr(app_data_dir, &repo, pr_number, &options)?
+                }
+                (None, Some(diff)) => {
+                    cli::analyze_hunks(app_data_dir, &options, a.repo.as_deref(), diff, None, None)?
+                }
+                (None, None) => return Err("analyze needs either pr or diffText.".to_string()),
+            };
+            to_json(&response)
+        }
+        other => Err(format!("Unknown tool '{}'", other)),
+    }
+}
+
+fn is_tool(name: &str) -> bool {
+    tools()
+        .as_array()
+        .is_some_and(|tools| tools.iter().any(|tool| tool["name"] == name))
+}
+
+/// Result of one JSON-RPC method call, or its error code and message.
+fn dispatch(
+    app_data_dir: Option<&Path>,
+    method: &str,
+    params: &Value,
+) -> Result<Value, (i64, String)> {
+    match method {
+        "initialize" => {
+            let requested = params
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let version = PROTOCOL_VERSIONS
+                .iter()
+                .find(|v| **v == requested)
+                .unwrap_or(&PROTOCOL_VERSIONS[0]);
+            Ok(json!({
+                "protocolVersion": version,
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "prvw", "version": env!("CARGO_PKG_VERSION")}
+            }))
+        }
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => {
+            let name = params
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            if !is_tool(&name) {
+                return Err((INVALID_PARAMS, format!("Unknown tool '{}'", name)));
+            }
+            let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+            // Tool failures are results the model can read, not protocol errors.
+            let (text, is_error) = match call_tool(app_data_dir, &name, arguments) {
+                Ok(text) => (text, false),
+                Err(e) => (e, true),
+            };
+            Ok(json!({
+                "content": [{"type": "text", "text": text}],
+                "isError": is_error
+            }))
+        }
+        other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+    }
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+/// Response to one line of input; notifications (no `id`) get none.
+fn handle_line(app_data_dir: Option<&Path>, line: &str) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => {
+            let message = format!("Parse error: {}", e);
+            return Some(error_response(&Value::Null, PARSE_ERROR, &message));
+        }
+    };
+    let id = message.get("id").cloned();
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        // Responses to requests we never send are ignored.
+        return id
+            .filter(|_| message.get("result").is_none() && message.get("error").is_none())
+            .map(|id| error_response(&id, INVALID_REQUEST, "Missing method"));
+    };
+    let params = message.get("params").unwrap_or(&Value::Null);
+    let result = dispatch(app_data_dir, method, params);
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err((code, message)) => error_response(&id, code, &message),
+    })
+}
+
+/// Serve MCP over stdio until stdin closes: one JSON-RPC message per line in, one response per
+/// line out. Progress and diagnostics go to stderr so stdout carries only protocol messages.
+pub fn serve(app_data_dir: Option<&Path>) -> Result<(), String> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| format!("Failed to read stdin: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(app_data_dir, &line) {
+            writeln!(stdout, "{}", response)
+                .and_then(|()| stdout.flush())
+                .map_err(|e| format!("Failed to write stdout: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(line: &str) -> Value {
+        handle_line(None, line).unwrap()
+    }
+
+    fn request(id: u32, method: &str, params: Value) -> Value {
+        let mut line = json!({"jsonrpc": "2.0", "id": id, "method": method});
+        line["params"] = params;
+        call(&line.to_string())
+    }
+
+    #[test]
+    fn negotiates_protocol_version() {
+        let response = request(1, "initialize", json!({"protocolVersion": "2024-11-05"}));
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(response["result"]["serverInfo"]["name"], "prvw");
+        let response = request(2, "initialize", json!({"protocolVersion": "1999-01-01"}));
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+    }
+
+    #[test]
+    fn lists_tools() {
+        let response = call(r#"{"jsonrpc":"2.0","id":"a","method":"tools/list"}"#);
+        let names: Vec<&str> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["list_prs", "get_pr_diff", "parse_diff", "analyze"]);
+        assert!(is_tool("analyze") && !is_tool("merge"));
+    }
+
+    #[test]
+    fn ignores_notifications() {
+        let line = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        assert!(handle_line(None, line).is_none());
+    }
+
+    #[test]
+    fn reports_protocol_errors() {
+        assert_eq!(call("not json")["error"]["code"], PARSE_ERROR);
+        assert_eq!(
+            request(3, "resources/list", Value::Null)["error"]["code"],
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            request(4, "tools/call", json!({"name": "merge"}))["error"]["code"],
+            INVALID_PARAMS
+        );
+    }
+
+    #[test]
+    fn tool_failures_are_error_results() {
+        let response = request(5, "tools/call", json!({"name": "analyze", "arguments": {}}));
+        assert_eq!(response["result"]["isError"], true);
+        assert!(response["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("pr or diffText"));
+        let arguments = json!({"repo": "o/r"});
+        let response = request(
+            6,
+            "tools/call",
+            json!({"name": "get_pr_diff", "arguments": arguments}),
+        );
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[test]
+    fn parses_diffs() {
+        let diff = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-x\n+y";
+        let response = request(
+            7,
+            "tools/call",
+            json!({"name": "parse_diff", "arguments": {"diffText": diff}}),
+        );
+        assert_eq!(response["result"]["isError"], false);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        let hunks: Vec<Value> = serde_json::from_str(text).unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0]["id"], "H1");
+    }
+}
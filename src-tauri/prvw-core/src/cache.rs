@@ -0,0 +1,926 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::cache_crypto;
+use crate::time::now_secs;
+use crate::types::RepoContext;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+pub fn hash_key(input: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    input.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+/// Last-access index of `cache/` entries, kept inside the cache so clearing it resets the index.
+const INDEX_FILE: &str = "index.json";
+/// Size limit in MB, kept outside `cache/` so it survives clearing the cache.
+const LIMIT_FILE: &str = "cache_limit.json";
+pub const DEFAULT_MAX_MB: u64 = 500;
+/// Whether new entries are encrypted at rest, kept next to the size limit.
+const ENCRYPTION_FILE: &str = "cache_encryption.json";
+
+/// Serializes read-modify-write cycles of the index across concurrent commands.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Callback told about failed background writes; unset in tests and the CLI.
+static WRITE_ERROR_HOOK: OnceLock<WriteErrorHook> = OnceLock::new();
+
+type WriteErrorHook = Box<dyn Fn(&str) + Send + Sync>;
+/// Callback told about each lookup (namespace, hit); unset in tests and the CLI.
+static LOOKUP_HOOK: OnceLock<LookupHook> = OnceLock::new();
+
+type LookupHook = Box<dyn Fn(&str, bool) + Send + Sync>;
+/// Queue of the background writer thread, started on first write.
+static WRITER: OnceLock<mpsc::Sender<WriteJob>> = OnceLock::new();
+
+/// How often the background sweep removes expired entries.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// How long entries of a namespace stay valid. Diffs and the recent-PR list go stale with
+/// every push; model outputs are keyed by their full input, so they only age out to bound
+/// disk use. Namespaces outside `cache/` (e.g. chat history) never expire.
+fn ttl(subdir: &str) -> Option<Duration> {
+    match subdir {
+        "cache/diff" | "cache/related_prs" => Some(DAY),
+        s if s.starts_with("cache/") => Some(30 * DAY),
+        _ => None,
+    }
+}
+
+fn is_expired(path: &Path, ttl: Duration) -> bool {
+    path.metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > ttl)
+}
+
+/// Per-entry bookkeeping, keyed `<namespace>/<key>.json`.
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct CacheIndex {
+    /// Unix seconds of the last read or write.
+    #[serde(default)]
+    accessed: HashMap<String, u64>,
+    /// PR an entry was produced for (`owner/repo#123`), for `invalidate_cache`.
+    #[serde(default)]
+    owners: HashMap<String, String>,
+}
+
+fn owner_tag(repo: &str, pr_number: u32) -> String {
+    format!("{}#{}", repo, pr_number)
+}
+
+/// Index id of an entry; only namespaces under `cache/` are tracked.
+fn entry_id(subdir: &str, key: &str) -> Option<String> {
+    let namespace = subdir.strip_prefix("cache/")?;
+    Some(format!("{}/{}.json", namespace, key))
+}
+
+fn load_index(cache_dir: &Path) -> CacheIndex {
+    fs::read_to_string(cache_dir.join(INDEX_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(cache_dir: &Path, index: &CacheIndex) {
+    let path = cache_dir.join(INDEX_FILE);
+    match serde_json::to_string(index) {
+        Ok(json) => {
+            if let Err(e) = fs::write(&path, json) {
+                eprintln!("[cache] failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => eprintln!("[cache] failed to serialize index: {}", e),
+    }
+}
+
+/// Record an access to an entry; after a write, also record its owner and evict down to
+/// the size limit.
+fn touch(app_data_dir: &Path, subdir: &str, key: &str, written: bool, owner: Option<String>) {
+    let Some(id) = entry_id(subdir, key) else {
+        return;
+    };
+    let Ok(_guard) = INDEX_LOCK.lock() else {
+        return;
+    };
+    let cache_dir = app_data_dir.join("cache");
+    let mut index = load_index(&cache_dir);
+    index.accessed.insert(id.clone(), now_secs());
+    if let Some(owner) = owner {
+        index.owners.insert(id.clone(), owner);
+    }
+    if written {
+        let removed = evict_lru(
+            &cache_dir,
+            &mut index,
+            max_mb(app_data_dir).saturating_mul(1024 * 1024),
+            &id,
+        );
+        if removed > 0 {
+            eprintln!("[cache] evicted {} least recently used entries", removed);
+        }
+    }
+    save_index(&cache_dir, &index);
+}
+
+/// Whether new cache entries are encrypted with the key in the OS keyring. Existing entries
+/// stay readable either way.
+pub fn encryption_enabled(app_data_dir: &Path) -> bool {
+    fs::read_to_string(app_data_dir.join(ENCRYPTION_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or(false)
+}
+
+/// Configured cache size limit in MB; 0 means unlimited.
+pub fn max_mb(app_data_dir: &Path) -> u64 {
+    fs::read_to_string(app_data_dir.join(LIMIT_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or(DEFAULT_MAX_MB)
+}
+
+/// Delete least recently used entries until the cache fits in `max_bytes` (0 = unlimited),
+/// never deleting `keep`. Entries missing from the index count as accessed at their mtime.
+/// Also drops index ids whose files are gone. Returns how many entries were deleted.
+fn evict_lru(cache_dir: &Path, index: &mut CacheIndex, max_bytes: u64, keep: &str) -> usize {
+    let mut entries: Vec<(u64, String, PathBuf, u64)> = Vec::new();
+    if let Ok(namespaces) = fs::read_dir(cache_dir) {
+        for namespace in namespaces.flatten().filter(|n| n.path().is_dir()) {
+            let Ok(files) = fs::read_dir(namespace.path()) else {
+                continue;
+            };
+            for file in files.flatten() {
+                let Ok(meta) = file.metadata() else {
+                    continue;
+                };
+                let id = format!(
+                    "{}/{}",
+                    namespace.file_name().to_string_lossy(),
+                    file.file_name().to_string_lossy()
+                );
+                let accessed = index.accessed.get(&id).copied().unwrap_or_else(|| {
+                    meta.modified()
+                        .ok()
+                        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0)
+                });
+                entries.push((accessed, id, file.path(), meta.len()));
+            }
+        }
+    }
+    index
+        .accessed
+        .retain(|id, _| entries.iter().any(|(_, e, _, _)| e == id));
+    index
+        .owners
+        .retain(|id, _| entries.iter().any(|(_, e, _, _)| e == id));
+
+    let mut total: u64 = entries.iter().map(|(_, _, _, size)| size).sum();
+    if max_bytes == 0 || total <= max_bytes {
+        return 0;
+    }
+    entries.sort_by_key(|(accessed, _, _, _)| *accessed);
+    let mut removed = 0;
+    for (_, id, path, size) in entries {
+        if total <= max_bytes {
+            break;
+        }
+        if id == keep || fs::remove_file(&path).is_err() {
+            continue;
+        }
+        index.accessed.remove(&id);
+        index.owners.remove(&id);
+        total = total.saturating_sub(size);
+        removed += 1;
+    }
+    removed
+}
+
+/// Version of the value shape stored in a namespace. Bump it when a cached type changes in a
+/// way serde defaults would paper over, and teach `migrate_step` how to carry old entries
+/// forward (or leave them to miss and be recomputed).
+fn schema_version(subdir: &str) -> u32 {
+    SCHEMA_VERSIONS
+        .iter()
+        .find(|(namespace, _)| *namespace == subdir)
+        .map_or(1, |(_, version)| *version)
+}
+
+/// Namespaces whose schema moved past version 1.
+const SCHEMA_VERSIONS: &[(&str, u32)] = &[];
+
+/// Stored entries are `{"schemaVersion": N, "value": ...}`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope<'a, T> {
+    schema_version: u32,
+    value: &'a T,
+}
+
+/// Split a stored entry into its version and value. Entries written before versioning are
+/// the bare value, reported as version 0.
+fn unwrap_envelope(stored: serde_json::Value) -> (u32, serde_json::Value) {
+    if let serde_json::Value::Object(map) = &stored {
+        let version = map.get("schemaVersion").and_then(serde_json::Value::as_u64);
+        if let (Some(version), Some(value), 2) = (version, map.get("value"), map.len()) {
+            return (u32::try_from(version).unwrap_or(u32::MAX), value.clone());
+        }
+    }
+    (0, stored)
+}
+
+/// Migrate a value of `subdir` from `version` to `version + 1`, or `None` to drop it.
+fn migrate_step(subdir: &str, version: u32, value: serde_json::Value) -> Option<serde_json::Value> {
+    match (subdir, version) {
+        // Pre-versioning analyses lack the deterministic risky-pattern hits; recompute them.
+        ("cache/analysis" | "cache/adjust", 0) => None,
+        (_, 0) => Some(value),
+        _ => None,
+    }
+}
+
+/// Bring a stored value up to the namespace's current version. Entries from a newer version
+/// (written by a later build) are refused.
+fn upgrade(subdir: &str, version: u32, value: serde_json::Value) -> Option<serde_json::Value> {
+    let current = schema_version(subdir);
+    if version > current {
+        return None;
+    }
+    (version..current).try_fold(value, |value, v| migrate_step(subdir, v, value))
+}
+
+pub fn read_cache<T: DeserializeOwned>(app_data_dir: &Path, subdir: &str, key: &str) -> Option<T> {
+    let value = read_entry(app_data_dir, subdir, key);
+    if let Some(hook) = LOOKUP_HOOK.get() {
+        hook(subdir, value.is_some());
+    }
+    value
+}
+
+fn read_entry<T: DeserializeOwned>(app_data_dir: &Path, subdir: &str, key: &str) -> Option<T> {
+    let path = app_data_dir.join(subdir).join(format!("{}.json", key));
+    if ttl(subdir).is_some_and(|ttl| is_expired(&path, ttl)) {
+        let _ = fs::remove_file(&path);
+        return None;
+    }
+    let mut data = fs::read(&path).ok()?;
+    if cache_crypto::is_encrypted(&data) {
+        data = match cache_crypto::decrypt(&data) {
+            Ok(plain) => plain,
+            Err(e) => {
+                eprintln!("[cache] {:?}: {}", path, e);
+                return None;
+            }
+        };
+    }
+    let (version, value) = unwrap_envelope(serde_json::from_slice(&data).ok()?);
+    let Some(value) = upgrade(subdir, version, value) else {
+        eprintln!(
+            "[cache] {:?}: schema version {} does not match {}, ignoring",
+            path,
+            version,
+            schema_version(subdir)
+        );
+        return None;
+    };
+    let value = serde_json::from_value(value).ok()?;
+    touch(app_data_dir, subdir, key, false, None);
+    Some(value)
+}
+
+pub fn write_cache<T: Serialize>(app_data_dir: &Path, subdir: &str, key: &str, value: &T) {
+    write_cache_owned(app_data_dir, subdir, key, value, None);
+}
+
+/// `write_cache` for an entry produced for one PR, so `invalidate_cache` can find it.
+///
+/// Only serialization (and encryption) happens on the caller's thread; the file write, index
+/// update, and eviction are queued to a background writer so commands return without waiting
+/// on disk. Failures are logged and passed to the `on_write_error` hook.
+pub fn write_cache_owned<T: Serialize>(
+    app_data_dir: &Path,
+    subdir: &str,
+    key: &str,
+    value: &T,
+    owner: Option<&RepoContext>,
+) {
+    let job = match pending_write(app_data_dir, subdir, key, value, owner) {
+        Ok(job) => job,
+        Err(e) => {
+            report_write_error(&format!("not cached: {}", e));
+            return;
+        }
+    };
+    if let Err(mpsc::SendError(WriteJob::Write(job))) = writer().send(WriteJob::Write(job)) {
+        persist(job);
+    }
+}
+
+/// `write_cache` that writes on the caller's thread and reports failure, for user data whose
+/// read-modify-write cycles must never race a queued write (e.g. chat history).
+pub fn write_cache_now<T: Serialize>(
+    app_data_dir: &Path,
+    subdir: &str,
+    key: &str,
+    value: &T,
+) -> Result<(), String> {
+    let write = pending_write(app_data_dir, subdir, key, value, None)?;
+    write_entry(&write)?;
+    touch(&write.app_data_dir, &write.subdir, &write.key, true, None);
+    Ok(())
+}
+
+fn pending_write<T: Serialize>(
+    app_data_dir: &Path,
+    subdir: &str,
+    key: &str,
+    value: &T,
+    owner: Option<&RepoContext>,
+) -> Result<PendingWrite, String> {
+    let envelope = Envelope {
+        schema_version: schema_version(subdir),
+        value,
+    };
+    let json = serde_json::to_vec(&envelope)
+        .map_err(|e| format!("failed to serialize for key {}: {}", key, e))?;
+    // Never fall back to plaintext when encryption is on but fails.
+    let data = if encryption_enabled(app_data_dir) {
+        cache_crypto::encrypt(&json)?
+    } else {
+        json
+    };
+    Ok(PendingWrite {
+        app_data_dir: app_data_dir.to_path_buf(),
+        subdir: subdir.to_string(),
+        key: key.to_string(),
+        data,
+        owner: owner.map(|o| owner_tag(&o.repo, o.pr_number)),
+    })
+}
+
+struct PendingWrite {
+    app_data_dir: PathBuf,
+    subdir: String,
+    key: String,
+    data: Vec<u8>,
+    owner: Option<String>,
+}
+
+enum WriteJob {
+    Write(PendingWrite),
+    /// Acknowledged once every earlier job is done.
+    Flush(mpsc::Sender<()>),
+}
+
+fn writer() -> &'static mpsc::Sender<WriteJob> {
+    WRITER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<WriteJob>();
+        std::thread::spawn(move || {
+            for job in rx {
+                match job {
+                    WriteJob::Write(write) => persist(write),
+                    WriteJob::Flush(ack) => {
+                        let _ = ack.send(());
+                    }
+                }
+            }
+        });
+        tx
+    })
+}
+
+/// Write an entry via a temp file and rename, so concurrent readers never see it half
+/// written.
+fn write_entry(write: &PendingWrite) -> Result<(), String> {
+    let dir = write.app_data_dir.join(&write.subdir);
+    fs::create_dir_all(&dir).map_err(|e| format!("failed to create dir {:?}: {}", dir, e))?;
+    let path = dir.join(format!("{}.json", write.key));
+    let tmp = dir.join(format!("{}.json.tmp", write.key));
+    fs::write(&tmp, &write.data)
+        .and_then(|()| fs::rename(&tmp, &path))
+        .map_err(|e| {
+            let _ = fs::remove_file(&tmp);
+            format!("failed to write {:?}: {}", path, e)
+        })
+}
+
+/// Write a queued entry, then record it in the index.
+fn persist(write: PendingWrite) {
+    if let Err(e) = write_entry(&write) {
+        report_write_error(&e);
+        return;
+    }
+    touch(
+        &write.app_data_dir,
+        &write.subdir,
+        &write.key,
+        true,
+        write.owner,
+    );
+}
+
+fn report_write_error(message: &str) {
+    eprintln!("[cache] {}", message);
+    if let Some(hook) = WRITE_ERROR_HOOK.get() {
+        hook(message);
+    }
+}
+
+/// Wait until every queued write has landed.
+pub fn flush() {
+    let (ack, done) = mpsc::channel();
+    if writer().send(WriteJob::Flush(ack)).is_ok() {
+        let _ = done.recv();
+    }
+}
+
+/// `read_cache` on the blocking thread pool, so commands never wait on disk or decryption on
+/// the async runtime's threads.
+pub async fn read_cache_async<T: DeserializeOwned + Send + 'static>(
+    app_data_dir: &Path,
+    subdir: &str,
+    key: &str,
+) -> Option<T> {
+    let (dir, subdir, key) = (
+        app_data_dir.to_path_buf(),
+        subdir.to_string(),
+        key.to_string(),
+    );
+    tokio::task::spawn_blocking(move || read_cache(&dir, &subdir, &key))
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Let background writes report failures, e.g. to the frontend. Only the first hook is kept.
+pub fn on_write_error(hook: impl Fn(&str) + Send + Sync + 'static) {
+    let _ = WRITE_ERROR_HOOK.set(Box::new(hook));
+}
+
+/// Count cache hits and misses, e.g. for local metrics. Only the first hook is kept.
+pub fn on_lookup(hook: impl Fn(&str, bool) + Send + Sync + 'static) {
+    let _ = LOOKUP_HOOK.set(Box::new(hook));
+}
+
+/// Delete the entries owned by `repo` (only those of `pr_number` when given).
+/// Returns how many were deleted.
+fn remove_owned(
+    cache_dir: &Path,
+    index: &mut CacheIndex,
+    repo: &str,
+    pr_number: Option<u32>,
+) -> usize {
+    let repo_prefix = format!("{}#", repo);
+    let matches = |owner: &str| match pr_number {
+        Some(pr) => owner == owner_tag(repo, pr),
+        None => owner.starts_with(&repo_prefix),
+    };
+    let ids: Vec<String> = index
+        .owners
+        .iter()
+        .filter(|(_, owner)| matches(owner))
+        .map(|(id, _)| id.clone())
+        .collect();
+    let mut removed = 0;
+    for id in ids {
+        if fs::remove_file(cache_dir.join(&id)).is_ok() {
+            removed += 1;
+        }
+        index.owners.remove(&id);
+        index.accessed.remove(&id);
+    }
+    removed
+}
+
+/// Remove expired entries from every namespace under `cache/`. Returns how many were removed.
+pub fn sweep_expired(app_data_dir: &Path) -> usize {
+    let mut removed = 0;
+    let Ok(namespaces) = fs::read_dir(app_data_dir.join("cache")) else {
+        return 0;
+    };
+    for namespace in namespaces.flatten() {
+        let Some(ttl) = ttl(&format!(
+            "cache/{}",
+            namespace.file_name().to_string_lossy()
+        )) else {
+            continue;
+        };
+        let Ok(entries) = fs::read_dir(namespace.path()) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|e| e == "json")
+                && is_expired(&path, ttl)
+                && fs::remove_file(&path).is_ok()
+            {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
+
+/// Sweep expired entries now and then every `SWEEP_INTERVAL` for the life of the app.
+pub fn start_sweeper(app_data_dir: PathBuf) {
+    std::thread::spawn(move || loop {
+        let removed = sweep_expired(&app_data_dir);
+        if removed > 0 {
+            eprintln!("[cache] removed {} expired entries", removed);
+        }
+        std::thread::sleep(SWEEP_INTERVAL);
+    });
+}
+
+pub fn dir_size(path: &Path) -> u64 {
+    let mut total = 0;
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let p = entry.path();
+            if p.is_dir() {
+                total += dir_size(&p);
+            } else if let Ok(meta) = p.metadata() {
+                total += meta.len();
+            }
+        }
+    }
+    total
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// Human-readable size of the `cache/` directory.
+pub fn size(app_data_dir: &Path) -> String {
+    let cache_dir = app_data_dir.join("cache");
+    if !cache_dir.exists() {
+        return "0 B".to_string();
+    }
+    format_bytes(dir_size(&cache_dir))
+}
+
+/// Delete every entry under `cache/`, waiting for queued writes first.
+pub fn clear(app_data_dir: &Path) -> Result<(), String> {
+    let cache_dir = app_data_dir.join("cache");
+    flush();
+    if cache_dir.exists() {
+        fs::remove_dir_all(&cache_dir).map_err(|e| format!("Failed to clear cache: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Drop the entries produced for one PR, or for every PR in `repo` when `pr_number` is
+/// omitted. Returns how many entries were removed.
+pub fn invalidate(
+    app_data_dir: &Path,
+    repo: &str,
+    pr_number: Option<u32>,
+) -> Result<usize, String> {
+    let cache_dir = app_data_dir.join("cache");
+    flush();
+    if !cache_dir.exists() {
+        return Ok(0);
+    }
+    let _guard = INDEX_LOCK
+        .lock()
+        .map_err(|_| "Cache index lock poisoned".to_string())?;
+    let mut index = load_index(&cache_dir);
+    let removed = remove_owned(&cache_dir, &mut index, repo, pr_number);
+    save_index(&cache_dir, &index);
+    Ok(removed)
+}
+
+/// The most recently used entry of `subdir` produced for a PR, e.g. its latest analysis
+/// whatever model and language it was run with.
+pub fn latest_owned<T: DeserializeOwned>(
+    app_data_dir: &Path,
+    subdir: &str,
+    repo: &str,
+    pr_number: u32,
+) -> Option<T> {
+    let namespace = format!("{}/", subdir.strip_prefix("cache/")?);
+    let owner = owner_tag(repo, pr_number);
+    flush();
+    let key = {
+        let _guard = INDEX_LOCK.lock().ok()?;
+        let index = load_index(&app_data_dir.join("cache"));
+        index
+            .owners
+            .iter()
+            .filter(|(id, o)| **o == owner && id.starts_with(&namespace))
+            .max_by_key(|(id, _)| index.accessed.get(*id).copied().unwrap_or_default())
+            .and_then(|(id, _)| id.strip_prefix(&namespace)?.strip_suffix(".json"))
+            .map(str::to_string)
+    }?;
+    read_cache(app_data_dir, subdir, &key)
+}
+
+/// Turn encryption of new entries on or off. Enabling creates the keyring key first, so an
+/// unavailable keyring is reported here rather than silently skipping writes later.
+pub fn set_encryption(app_data_dir: &Path, enabled: bool) -> Result<(), String> {
+    if enabled {
+        cache_crypto::ensure_key()?;
+    }
+    fs::create_dir_all(app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    fs::write(app_data_dir.join(ENCRYPTION_FILE), enabled.to_string())
+        .map_err(|e| format!("Failed to save cache encryption setting: {}", e))
+}
+
+/// Save the cache size limit in MB (0 = unlimited) and evict down to it right away.
+pub fn set_limit(app_data_dir: &Path, max_mb: u64) -> Result<(), String> {
+    fs::create_dir_all(app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    fs::write(app_data_dir.join(LIMIT_FILE), max_mb.to_string())
+        .map_err(|e| format!("Failed to save cache limit: {}", e))?;
+
+    flush();
+    let _guard = INDEX_LOCK
+        .lock()
+        .map_err(|_| "Cache index lock poisoned".to_string())?;
+    let cache_dir = app_data_dir.join("cache");
+    if cache_dir.exists() {
+        let mut index = load_index(&cache_dir);
+        evict_lru(
+            &cache_dir,
+            &mut index,
+            max_mb.saturating_mul(1024 * 1024),
+            "",
+        );
+        save_index(&cache_dir, &index);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_key_is_deterministic() {
+        let a = hash_key("hello world");
+        let b = hash_key("hello world");
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn hash_key_different_inputs_differ() {
+        let a = hash_key("input_a");
+        let b = hash_key("input_b");
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn hash_key_is_16_hex_chars() {
+        let h = hash_key("test");
+        assert_eq!(h.len(), 16);
+        assert!(h.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn format_bytes_zero() {
+        assert_eq!(format_bytes(0), "0 B");
+    }
+
+    #[test]
+    fn format_bytes_small() {
+        assert_eq!(format_bytes(512), "512 B");
+    }
+
+    #[test]
+    fn format_bytes_kilobytes() {
+        assert_eq!(format_bytes(1024), "1.0 KB");
+    }
+
+    #[test]
+    fn format_bytes_megabytes() {
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+    }
+
+    #[test]
+    fn read_write_cache_roundtrip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let key = "test_key";
+        let value = serde_json::json!({"foo": "bar", "num": 42});
+        write_cache(tmp.path(), "sub", key, &value);
+        flush();
+        let read: Option<serde_json::Value> = read_cache(tmp.path(), "sub", key);
+        assert_eq!(read.unwrap(), value);
+    }
+
+    fn age(app_data_dir: &Path, subdir: &str, key: &str, by: Duration) {
+        let path = app_data_dir.join(subdir).join(format!("{}.json", key));
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(std::time::SystemTime::now() - by)
+            .unwrap();
+    }
+
+    #[test]
+    fn ttl_per_namespace() {
+        assert_eq!(ttl("cache/diff"), Some(DAY));
+        assert_eq!(ttl("cache/analysis"), Some(30 * DAY));
+        assert_eq!(ttl("chat"), None);
+    }
+
+    #[test]
+    fn expired_entries_are_dropped_on_read() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_cache(tmp.path(), "cache/diff", "old", &"diff");
+        write_cache(tmp.path(), "cache/analysis", "recent", &"analysis");
+        flush();
+        age(tmp.path(), "cache/diff", "old", 2 * DAY);
+        age(tmp.path(), "cache/analysis", "recent", 2 * DAY);
+        assert!(read_cache::<String>(tmp.path(), "cache/diff", "old").is_none());
+        assert!(!tmp.path().join("cache/diff/old.json").exists());
+        assert_eq!(
+            read_cache::<String>(tmp.path(), "cache/analysis", "recent").as_deref(),
+            Some("analysis")
+        );
+    }
+
+    #[test]
+    fn sweep_removes_only_expired_entries() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_cache(tmp.path(), "cache/diff", "old", &"diff");
+        write_cache(tmp.path(), "cache/diff", "new", &"diff");
+        write_cache(tmp.path(), "chat", "history", &"chat");
+        flush();
+        age(tmp.path(), "cache/diff", "old", 2 * DAY);
+        age(tmp.path(), "chat", "history", 365 * DAY);
+        assert_eq!(sweep_expired(tmp.path()), 1);
+        assert!(tmp.path().join("cache/diff/new.json").exists());
+        assert!(tmp.path().join("chat/history.json").exists());
+    }
+
+    #[test]
+    fn evicts_least_recently_used_entries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache_dir = tmp.path().join("cache");
+        for key in ["a", "b", "c"] {
+            write_cache(tmp.path(), "cache/analysis", key, &"x".repeat(100));
+        }
+        flush();
+        let mut index = CacheIndex::default();
+        index.accessed.insert("analysis/a.json".to_string(), 30);
+        index.accessed.insert("analysis/b.json".to_string(), 10);
+        index.accessed.insert("analysis/c.json".to_string(), 20);
+        index.accessed.insert("analysis/gone.json".to_string(), 5);
+        let size = fs::metadata(cache_dir.join("analysis/a.json"))
+            .unwrap()
+            .len();
+
+        assert_eq!(evict_lru(&cache_dir, &mut index, 0, ""), 0);
+        assert_eq!(evict_lru(&cache_dir, &mut index, size * 5 / 2, ""), 1);
+        assert!(!cache_dir.join("analysis/b.json").exists());
+        assert!(!index.accessed.contains_key("analysis/gone.json"));
+
+        assert_eq!(
+            evict_lru(&cache_dir, &mut index, size * 3 / 2, "analysis/c.json"),
+            1
+        );
+        assert!(!cache_dir.join("analysis/a.json").exists());
+        assert!(cache_dir.join("analysis/c.json").exists());
+    }
+
+    #[test]
+    fn reads_and_writes_update_the_index() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_cache(tmp.path(), "cache/diff", "k", &"diff");
+        write_cache(tmp.path(), "chat", "h", &"chat");
+        flush();
+        let index = load_index(&tmp.path().join("cache"));
+        assert!(index.accessed.contains_key("diff/k.json"));
+        assert_eq!(index.accessed.len(), 1);
+    }
+
+    #[test]
+    fn invalidates_entries_per_pr_and_per_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache_dir = tmp.path().join("cache");
+        let owner = |repo: &str, pr_number: u32| RepoContext {
+            repo: repo.to_string(),
+            pr_number,
+        };
+        write_cache_owned(
+            tmp.path(),
+            "cache/diff",
+            "d1",
+            &"diff",
+            Some(&owner("o/r", 1)),
+        );
+        write_cache_owned(
+            tmp.path(),
+            "cache/analysis",
+            "a1",
+            &"a",
+            Some(&owner("o/r", 1)),
+        );
+        write_cache_owned(
+            tmp.path(),
+            "cache/analysis",
+            "a2",
+            &"a",
+            Some(&owner("o/r", 2)),
+        );
+        write_cache_owned(
+            tmp.path(),
+            "cache/analysis",
+            "a3",
+            &"a",
+            Some(&owner("o/r2", 1)),
+        );
+        write_cache(tmp.path(), "cache/analysis", "untagged", &"a");
+        flush();
+
+        let mut index = load_index(&cache_dir);
+        assert_eq!(remove_owned(&cache_dir, &mut index, "o/r", Some(1)), 2);
+        assert!(!cache_dir.join("diff/d1.json").exists());
+        assert!(cache_dir.join("analysis/a2.json").exists());
+
+        assert_eq!(remove_owned(&cache_dir, &mut index, "o/r", None), 1);
+        assert!(cache_dir.join("analysis/a3.json").exists());
+        assert!(cache_dir.join("analysis/untagged.json").exists());
+    }
+
+    #[test]
+    fn finds_latest_entry_of_a_pr() {
+        let tmp = tempfile::tempdir().unwrap();
+        let cache_dir = tmp.path().join("cache");
+        let owner = RepoContext {
+            repo: "o/r".to_string(),
+            pr_number: 1,
+        };
+        for (key, value) in [("old", "old"), ("new", "new")] {
+            write_cache_owned(tmp.path(), "cache/analysis", key, &value, Some(&owner));
+        }
+        write_cache_owned(tmp.path(), "cache/diff", "d", &"diff", Some(&owner));
+        flush();
+        let mut index = load_index(&cache_dir);
+        index.accessed.insert("analysis/old.json".to_string(), 1);
+        save_index(&cache_dir, &index);
+
+        let latest: Option<String> = latest_owned(tmp.path(), "cache/analysis", "o/r", 1);
+        assert_eq!(latest.as_deref(), Some("new"));
+        let other: Option<String> = latest_owned(tmp.path(), "cache/analysis", "o/r", 2);
+        assert!(other.is_none());
+    }
+
+    #[test]
+    fn entries_carry_their_schema_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_cache(tmp.path(), "cache/diff", "k", &"diff");
+        flush();
+        let raw = fs::read_to_string(tmp.path().join("cache/diff/k.json")).unwrap();
+        assert_eq!(raw, r#"{"schemaVersion":1,"value":"diff"}"#);
+    }
+
+    #[test]
+    fn legacy_entries_migrate_or_miss() {
+        let tmp = tempfile::tempdir().unwrap();
+        for (subdir, value) in [("cache/diff", "\"diff\""), ("cache/analysis", "{}")] {
+            let dir = tmp.path().join(subdir);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("old.json"), value).unwrap();
+        }
+        assert_eq!(
+            read_cache::<String>(tmp.path(), "cache/diff", "old").as_deref(),
+            Some("diff")
+        );
+        let analysis: Option<serde_json::Value> = read_cache(tmp.path(), "cache/analysis", "old");
+        assert!(analysis.is_none());
+    }
+
+    #[test]
+    fn newer_schema_versions_are_refused() {
+        let value = serde_json::json!({"schemaVersion": 9, "value": "x"});
+        let (version, inner) = unwrap_envelope(value);
+        assert_eq!(version, 9);
+        assert!(upgrade("cache/diff", version, inner).is_none());
+        assert_eq!(unwrap_envelope(serde_json::json!({"value": 1})).0, 0);
+    }
+
+    #[test]
+    fn read_cache_miss_returns_none() {
+        let tmp = tempfile::tempdir().unwrap();
+        let read: Option<serde_json::Value> = read_cache(tmp.path(), "sub", "nonexistent");
+        assert!(read.is_none());
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::Mutex;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use crate::keystore;
+
+const KEYRING_USER: &str = "cache-encryption-key";
+
+/// Prefix of encrypted entries; anything else is read as plaintext JSON.
+const MAGIC: &[u8] = b"PRVWENC1";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// The key is read from the keyring once per process; keyring lookups can be slow or prompt.
+static KEY: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+/// The cache key from the OS keyring, generated and stored on first use when `create` is set.
+fn load_key(create: bool) -> Result<Vec<u8>, String> {
+    let mut cached = KEY
+        .lock()
+        .map_err(|_| "Cache key lock poisoned".to_string())?;
+    if let Some(key) = cached.as_ref() {
+        return Ok(key.clone());
+    }
+    let key = match keystore::get_secret(KEYRING_USER)
+        .map_err(|e| format!("Failed to read cache encryption key: {}", e))?
+    {
+        Some(key) if key.len() == KEY_LEN => key,
+        Some(_) => return Err("Cache encryption key in the keyring is malformed".to_string()),
+        None if create => {
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng).to_vec();
+            keystore::set_secret(KEYRING_USER, &key)
+                .map_err(|e| format!("Failed to save cache encryption key: {}", e))?;
+            key
+        }
+        None => return Err("No cache encryption key in the keyring".to_string()),
+    };
+    *cached = Some(key.clone());
+    Ok(key)
+}
+
+fn seal(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Failed to encrypt cache entry".to_string())?;
+    let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn open(key: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+    let body = data
+        .strip_prefix(MAGIC)
+        .filter(|b| b.len() >= NONCE_LEN)
+        .ok_or_else(|| "Not an encrypted cache entry".to_string())?;
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt cache entry (wrong key or corrupted)".to_string())
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Make sure a key exists, creating it if needed, so enabling encryption fails up front when
+/// the keyring is unavailable.
+pub fn ensure_key() -> Result<(), String> {
+    load_key(true).map(|_| ())
+}
+
+/// Encrypt a cache entry with ChaCha20-Poly1305: `MAGIC || nonce || ciphertext`.
+pub fn encrypt(plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    seal(&load_key(true)?, plaintext)
+}
+
+pub fn decrypt(data: &[u8]) -> Result<Vec<u8>, String> {
+    open(&load_key(false)?, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_open_roundtrip() {
+        let key = [7u8; KEY_LEN];
+        let sealed = seal(&key, b"{\"diff\":1}").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.windows(4).any(|w| w == b"diff"));
+        assert_eq!(open(&key, &sealed).unwrap(), b"{\"diff\":1}");
+    }
+
+    #[test]
+    fn open_rejects_wrong_key_and_tampering() {
+        let key = [7u8; KEY_LEN];
+        let mut sealed = seal(&key, b"secret").unwrap();
+        assert!(open(&[8u8; KEY_LEN], &sealed).is_err());
+        if let Some(last) = sealed.last_mut() {
+            *last ^= 1;
+        }
+        assert!(open(&key, &sealed).is_err());
+        assert!(open(&key, b"{\"plain\":true}").is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::json_store::{self, TemplateStore};
+use crate::settings;
+use crate::types::{ChecklistTemplate, IntentGroup};
+
+/// Saved templates, with ids of the form `T<n>`.
+static TEMPLATES: TemplateStore =
+    TemplateStore::new("checklist_templates.json", 'T', "checklist template");
+
+/// Trim the template, drop empty items, and check the name and repo.
+fn normalize(mut template: ChecklistTemplate) -> Result<ChecklistTemplate, PrvwError> {
+    template.name = template.name.trim().to_string();
+    if template.name.is_empty() {
+        return Err(PrvwError::InvalidInput(
+            "Checklist template name is empty.".to_string(),
+        ));
+    }
+    template.repo = template
+        .repo
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    if let Some(repo) = &template.repo {
+        validate_repo(repo)?;
+    }
+    template.items = template
+        .items
+        .iter()
+        .map(|i| i.trim().to_string())
+        .filter(|i| !i.is_empty())
+        .collect();
+    if template.items.is_empty() {
+        return Err(PrvwError::InvalidInput(
+            "Checklist template has no items.".to_string(),
+        ));
+    }
+    Ok(template)
+}
+
+/// Append the items of every template applying to `repo` to each group's checklist, skipping
+/// items the checklist already has (ignoring case), so re-applying is a no-op.
+pub fn merge_templates(
+    groups: &mut [IntentGroup],
+    templates: &[ChecklistTemplate],
+    repo: Option<&str>,
+) {
+    let items: Vec<&str> = templates
+        .iter()
+        .filter(|t| json_store::applies_to(t.repo.as_deref(), repo))
+        .flat_map(|t| t.items.iter().map(String::as_str))
+        .collect();
+    if items.is_empty() {
+        return;
+    }
+    for group in groups {
+        let mut present: HashSet<String> = group
+            .reviewer_checklist
+            .iter()
+            .map(|i| i.trim().to_lowercase())
+            .collect();
+        for item in &items {
+            if present.insert(item.to_lowercase()) {
+                group.reviewer_checklist.push(item.to_string());
+            }
+        }
+    }
+}
+
+/// Merge the saved templates for `repo` into `groups`, including templates the repo's settings
+/// pick. Applied to results on the way out, so cached analyses pick up template edits without
+/// re-running.
+pub fn apply_templates(
+    app_data_dir: Option<&Path>,
+    repo: Option<&str>,
+    groups: &mut [IntentGroup],
+) {
+    if let Some(dir) = app_data_dir {
+        let mut templates: Vec<ChecklistTemplate> = TEMPLATES.load(dir);
+        if let Some(repo) = repo {
+            let settings = settings::current();
+            let picked = settings::repo_settings(&settings, repo)
+                .map(|o| o.checklist_templates.as_slice())
+                .unwrap_or_default();
+            // A picked template applies to the repo as if it were scoped to it.
+            for template in templates.iter_mut().filter(|t| picked.contains(&t.id)) {
+                template.repo = None;
+            }
+        }
+        merge_templates(groups, &templates, repo);
+    }
+}
+
+/// Saved templates: all of them, or those applying to `repo` (global ones included).
+pub fn list(app_data_dir: &Path, repo: Option<&str>) -> Vec<ChecklistTemplate> {
+    TEMPLATES.list(app_data_dir, repo)
+}
+
+/// Create (empty `id`) or replace a template. Returns it as saved.
+pub fn save(
+    app_data_dir: &Path,
+    template: ChecklistTemplate,
+) -> Result<ChecklistTemplate, PrvwError> {
+    TEMPLATES.save(app_data_dir, normalize(template)?)
+}
+
+pub fn delete(app_data_dir: &Path, id: &str) -> Result<(), PrvwError> {
+    TEMPLATES.delete::<ChecklistTemplate>(app_data_dir, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(id: &str, repo: Option<&str>, items: &[&str]) -> ChecklistTemplate {
+        ChecklistTemplate {
+            id: id.to_string(),
+            name: id.to_string(),
+            repo: repo.map(str::to_string),
+            items: items.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    fn group(checklist: &[&str]) -> IntentGroup {
+        IntentGroup {
+            id: "G1".to_string(),
+            title: String::new(),
+            category: String::new(),
+            rationale: String::new(),
+            risk: "low".to_string(),
+            risk_score: 0,
+            risk_evidence: vec![],
+            hunk_ids: vec![],
+            reviewer_checklist: checklist.iter().map(ToString::to_string).collect(),
+            suggested_tests: vec![],
+        }
+    }
+
+    #[test]
+    fn merges_global_and_matching_repo_templates_once() {
+        let templates = vec![
+            template("T1", None, &["Security sign-off"]),
+            template("T2", Some("Org/App"), &["i18n strings extracted"]),
+            template("T3", Some("org/other"), &["a11y reviewed"]),
+        ];
+        let mut groups = vec![group(&["security sign-off", "Check error paths"])];
+        merge_templates(&mut groups, &templates, Some("org/app"));
+        merge_templates(&mut groups, &templates, Some("org/app"));
+        assert_eq!(
+            groups[0].reviewer_checklist,
+            vec![
+                "security sign-off",
+                "Check error paths",
+                "i18n strings extracted"
+            ]
+        );
+    }
+
+    #[test]
+    fn normalizes_and_numbers_templates() {
+        let t = normalize(template(" ", None, &["x"]));
+        assert!(t.is_err());
+        let t = normalize(ChecklistTemplate {
+            name: " Security ".to_string(),
+            repo: Some(" ".to_string()),
+            ..template("", None, &[" a ", ""])
+        })
+        .unwrap();
+        assert_eq!(
+            (t.name.as_str(), t.repo, t.items),
+            ("Security", None, vec!["a".to_string()])
+        );
+        assert_eq!(
+            TEMPLATES.next_id(&[template("T2", None, &[]), template("x", None, &[])]),
+            "T3"
+        );
+    }
+}
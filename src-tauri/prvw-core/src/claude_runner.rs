@@ -0,0 +1,274 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::codex_runner::{self, CodexOutput, RunError};
+use crate::i18n::{t, Msg};
+use crate::provider::{AnalysisProvider, Capabilities, Job};
+use crate::types::TokenUsage;
+
+/// Claude Code has no `--output-schema`, so the schema is enforced through the prompt
+/// and the JSON is extracted from the final message.
+const SCHEMA_INSTRUCTION: &str =
+    " Respond with ONLY a single JSON object that conforms to the JSON schema in schema.json \
+     (read it first). Do not wrap the JSON in markdown fences and do not add any other text.";
+
+/// Envelope printed by `claude -p --output-format json`.
+#[derive(Debug, Deserialize)]
+struct ClaudeEnvelope {
+    #[serde(default)]
+    is_error: bool,
+    #[serde(default)]
+    result: String,
+    #[serde(default)]
+    usage: Option<ClaudeUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+impl ClaudeUsage {
+    fn to_token_usage(&self) -> TokenUsage {
+        let input =
+            self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens;
+        TokenUsage {
+            input_tokens: Some(input),
+            output_tokens: Some(self.output_tokens),
+            total_tokens: input + self.output_tokens,
+        }
+    }
+}
+
+/// Build CLI arguments for `claude -p`. Only the Read tool is allowed, mirroring Codex's
+/// read-only sandbox. `add_dir` grants read access to a directory outside the working directory.
+pub fn build_args(model: &Option<String>, add_dir: Option<&str>, prompt: String) -> Vec<String> {
+    let mut args = vec![
+        "-p".to_string(),
+        "--output-format".to_string(),
+        "json".to_string(),
+        "--allowedTools".to_string(),
+        "Read".to_string(),
+    ];
+
+    if let Some(m) = model {
+        if !m.trim().is_empty() {
+            args.push("--model".to_string());
+            args.push(m.trim().to_string());
+        }
+    }
+
+    if let Some(dir) = add_dir {
+        args.push("--add-dir".to_string());
+        args.push(dir.to_string());
+    }
+
+    args.push(prompt);
+    args
+}
+
+/// Extract the outermost JSON object from a model message, tolerating fences or prose around it.
+fn extract_json(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let end = text.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    Some(&text[start..=end])
+}
+
+fn is_auth_error(text: &str) -> bool {
+    text.contains("/login") || text.contains("API key") || text.contains("authenticat")
+}
+
+/// Run Claude Code in `work_dir` and return its output plus the final message text.
+pub fn run(
+    work_dir: &Path,
+    args: &[String],
+    timeout: Duration,
+) -> Result<(CodexOutput, String), RunError> {
+    let model_used = args
+        .windows(2)
+        .find(|w| w[0] == "--model")
+        .map(|w| w[1].clone())
+        .unwrap_or_else(|| "(config default)".to_string());
+
+    let mut cmd = Command::new("claude");
+    cmd.args(args).current_dir(work_dir).env("NO_COLOR", "1");
+    let output = codex_runner::run_process(
+        &mut cmd,
+        "claude",
+        &t(Msg::ClaudeNotInstalled, &[]),
+        timeout,
+    )?;
+
+    let envelope: Option<ClaudeEnvelope> = serde_json::from_str(output.stdout.trim()).ok();
+    let failed = !output.status.success() || envelope.as_ref().is_some_and(|e| e.is_error);
+    if failed {
+        let detail = envelope
+            .as_ref()
+            .map(|e| e.result.clone())
+            .filter(|r| !r.is_empty())
+            .unwrap_or_else(|| output.stderr.clone());
+        if is_auth_error(&detail) || is_auth_error(&output.stderr) {
+            return Err(RunError::NotAuthenticated {
+                program: "claude",
+                hint: t(Msg::ClaudeNotAuthenticated, &[]),
+            });
+        }
+        if codex_runner::is_rate_limited(&detail) {
+            return Err(RunError::RateLimited {
+                program: "claude",
+                detail,
+            });
+        }
+        return Err(RunError::Unknown {
+            stderr: format!("Claude Code failed: {}", detail),
+        });
+    }
+
+    let envelope = envelope.ok_or_else(|| RunError::SchemaViolation {
+        program: "claude",
+        detail: "the CLI output is not a JSON envelope".to_string(),
+    })?;
+    let log = CodexOutput {
+        stdout: envelope.result.clone(),
+        stderr: output.stderr,
+        elapsed_secs: output.elapsed_secs,
+        model_used,
+        usage: envelope.usage.as_ref().map(ClaudeUsage::to_token_usage),
+        args: args.to_vec(),
+        run_id: None,
+    };
+    Ok((log, envelope.result))
+}
+
+/// Claude Code CLI backend: reads the temp dir with its Read tool; the schema is requested
+/// through the prompt and the JSON is extracted from the final message.
+pub struct ClaudeCodeProvider;
+
+impl AnalysisProvider for ClaudeCodeProvider {
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        &["claude-code"]
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            reads_files: true,
+            native_schema: false,
+            cli: Some("claude"),
+        }
+    }
+
+    fn prepare_input(&self, job: &mut Job) -> Result<(), RunError> {
+        job.prompt.push_str(SCHEMA_INSTRUCTION);
+        Ok(())
+    }
+
+    fn run(&self, job: &Job) -> Result<(CodexOutput, String), RunError> {
+        let add_dir = match job.repo_dir {
+            Some(_) => Some(
+                job.temp_path
+                    .to_str()
+                    .ok_or_else(|| RunError::unknown("Non-UTF-8 temp path"))?,
+            ),
+            None => None,
+        };
+        let args = build_args(job.model, add_dir, job.prompt.clone());
+        run(job.repo_dir.unwrap_or(job.temp_path), &args, job.timeout)
+    }
+
+    fn parse_output(&self, raw: &str) -> Result<String, RunError> {
+        extract_json(raw)
+            .map(str::to_string)
+            .ok_or_else(|| RunError::SchemaViolation {
+                program: "claude",
+                detail: "the final message contains no JSON object".to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RunOptions;
+
+    #[test]
+    fn build_args_print_mode_read_only() {
+        let args = build_args(&None, None, "prompt".to_string());
+        assert_eq!(args[0], "-p");
+        assert!(args.contains(&"json".to_string()));
+        let tools = args.iter().position(|a| a == "--allowedTools").unwrap();
+        assert_eq!(args[tools + 1], "Read");
+        assert!(!args.contains(&"--model".to_string()));
+        assert!(!args.contains(&"--add-dir".to_string()));
+        assert_eq!(args.last().unwrap(), "prompt");
+    }
+
+    #[test]
+    fn build_args_with_model() {
+        let args = build_args(&Some(" sonnet ".to_string()), None, "p".to_string());
+        let m = args.iter().position(|a| a == "--model").unwrap();
+        assert_eq!(args[m + 1], "sonnet");
+    }
+
+    #[test]
+    fn build_args_with_add_dir() {
+        let args = build_args(&None, Some("/tmp/in"), "p".to_string());
+        let d = args.iter().position(|a| a == "--add-dir").unwrap();
+        assert_eq!(args[d + 1], "/tmp/in");
+        assert_eq!(args.last().unwrap(), "p");
+    }
+
+    #[test]
+    fn extract_json_strips_fences() {
+        let text = "Here you go:\n```json\n{\"a\": {\"b\": 1}}\n```";
+        assert_eq!(extract_json(text), Some("{\"a\": {\"b\": 1}}"));
+    }
+
+    #[test]
+    fn extract_json_none_without_object() {
+        assert_eq!(extract_json("no json here"), None);
+        assert_eq!(extract_json("} backwards {"), None);
+    }
+
+    #[test]
+    fn prepare_input_adds_schema_instruction() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut job = Job {
+            temp_path: dir.path(),
+            schema_path: &dir.path().join("schema.json"),
+            output_path: &dir.path().join("result.json"),
+            repo_dir: None,
+            model: &None,
+            options: &RunOptions::default(),
+            prompt: "prompt".to_string(),
+            timeout: Duration::from_secs(1),
+        };
+        ClaudeCodeProvider.prepare_input(&mut job).unwrap();
+        assert!(job.prompt.starts_with("prompt"));
+        assert!(job.prompt.contains("schema.json"));
+    }
+
+    #[test]
+    fn envelope_parses_result() {
+        let env: ClaudeEnvelope =
+            serde_json::from_str(r#"{"type":"result","is_error":false,"result":"{}"}"#).unwrap();
+        assert!(!env.is_error);
+        assert_eq!(env.result, "{}");
+    }
+}
@@ -0,0 +1,2644 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::cache;
+use crate::checklists;
+use crate::codex_runner::{self, lang_suffix};
+use crate::config;
+use crate::constraints;
+use crate::error::PrvwError;
+use crate::guidelines::MAX_GUIDELINES_CHARS;
+use crate::history;
+use crate::i18n::{t, Msg};
+use crate::leftovers;
+use crate::manifest;
+use crate::operations::Op;
+use crate::provider::{self, AnalysisProvider, Job, ProviderRegistry};
+use crate::review_state::{self, ReviewStates};
+use crate::risky;
+use crate::settings::{self, RunDefaults};
+use crate::types::{
+    AnalysisConstraints, AnalysisPromptPreview, AnalysisResponse, AnalysisResult,
+    BreakingChangesResponse, BreakingChangesResult, ConsistencyResponse, ConsistencyResult,
+    DraftCommentsResponse, DraftCommentsResult, ExplainResponse, ExplainResult,
+    GuidedRefineResponse, Hunk, HunkSummary, IntentGroup, RefineResponse, RefineResult,
+    RepoContext, RunOptions, SplitPlanResponse, SplitPlanResult, SummarizeResponse,
+    SummarizeResult,
+};
+use crate::usage;
+use crate::validation::{
+    validate_analysis, validate_breaking_changes, validate_consistency, validate_draft_comments,
+    validate_guided_refine, validate_risk, validate_split_plan, validate_summaries,
+};
+use crate::workspace;
+
+const ANALYSIS_SCHEMA: &str = include_str!("../schemas/analysis.json");
+const REFINE_SCHEMA: &str = include_str!("../schemas/refine.json");
+const EXPLAIN_SCHEMA: &str = include_str!("../schemas/explain.json");
+const SUMMARIZE_SCHEMA: &str = include_str!("../schemas/summarize.json");
+const COMMENTS_SCHEMA: &str = include_str!("../schemas/comments.json");
+const SPLIT_PLAN_SCHEMA: &str = include_str!("../schemas/split_plan.json");
+const BREAKING_SCHEMA: &str = include_str!("../schemas/breaking.json");
+const CONSISTENCY_SCHEMA: &str = include_str!("../schemas/consistency.json");
+
+/// Upper bound on validation problems quoted back to the model in a repair prompt.
+const MAX_REPAIR_WARNINGS: usize = 30;
+
+const RISK_INSTRUCTIONS: &str = "Back every risk label with evidence: \
+     set riskScore to an integer from 0 to 100 consistent with risk (low 0-33, medium 34-66, high 67-100), \
+     and list in riskEvidence the specific hunk ids from that group with a short concrete reason each \
+     (e.g. \"changes token expiry check\"). Medium and high risk groups must have at least one evidence entry.";
+
+/// Cache key fragment for full-repo context; the hunks already pin the PR content.
+fn repo_context_key(ctx: &Option<RepoContext>) -> String {
+    ctx.as_ref()
+        .map(|c| format!("repo:{}#{}", c.repo, c.pr_number))
+        .unwrap_or_default()
+}
+
+/// Check out the PR head for full-repo context, if requested. Returns (checkout path, head sha).
+fn prepare_repo_context(
+    app_data_dir: Option<&Path>,
+    ctx: &Option<RepoContext>,
+) -> Result<Option<(PathBuf, String)>, String> {
+    let Some(ctx) = ctx else {
+        return Ok(None);
+    };
+    let dir = app_data_dir
+        .ok_or_else(|| "Failed to get app data dir for full-repo context".to_string())?;
+    workspace::prepare_pr_checkout(dir, &ctx.repo, ctx.pr_number).map(Some)
+}
+
+/// Prompt fragment injecting the target repo's condensed review guidelines, if any.
+fn guidelines_context(guidelines: &Option<String>) -> String {
+    match guidelines.as_deref() {
+        Some(g) if !g.trim().is_empty() => {
+            let end = g.floor_char_boundary(MAX_GUIDELINES_CHARS);
+            format!(
+                " The project's review guidelines (condensed) are:\n{}\n\
+                 Reflect these conventions in each group's reviewerChecklist and suggestedTests where relevant.",
+                &g[..end]
+            )
+        }
+        _ => String::new(),
+    }
+}
+
+fn build_analysis_prompt(
+    hunk_count: usize,
+    pr_body: &Option<String>,
+    has_summaries: bool,
+    guidelines: &Option<String>,
+    dependency_summary: &str,
+    risky_summary: &str,
+    lang: &Option<String>,
+) -> String {
+    let pr_context = match pr_body.as_deref() {
+        Some(body) if !body.trim().is_empty() => {
+            let truncated = if body.len() > 2000 {
+                let end = body.floor_char_boundary(2000);
+                &body[..end]
+            } else {
+                body
+            };
+            format!(" The PR description is: \"{}\".", truncated)
+        }
+        _ => String::new(),
+    };
+    let summaries_hint = if has_summaries {
+        " summaries.json contains a one-sentence summary per hunk id; use it as a hint when grouping."
+    } else {
+        ""
+    };
+    let dependency_context = if dependency_summary.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " Dependency changes parsed from the manifests in this PR \
+             (lockfile hunks, if any, are listed without their lines):\n{}\n\
+             Use this summary instead of inferring versions from lockfile hunks.",
+            dependency_summary
+        )
+    };
+    let risky_context = if risky_summary.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " A deterministic scan flagged these risky patterns on added lines:\n{}\n\
+             Treat each as a risk signal for the group containing its hunk: \
+             cite it in riskEvidence and raise the risk unless the code shows it is safe.",
+            risky_summary
+        )
+    };
+
+    format!(
+        "Read hunks.json which contains {} hunks and group ALL of them by change intent for PR review.{}{}{}{}{} \
+         Every single hunk must be assigned to exactly one group — do not leave any hunk unassigned. \
+         Use only existing hunk ids. Output must match the schema. Do not invent ids. \
+         Order the groups array by logical processing flow \
+         (e.g. data model / schema first, then business logic, then API / controller, then UI, then tests, then config). \
+         Give each group a clear, descriptive title that serves as a section heading for reviewers. \
+         Assign each group a category from: schema, logic, api, ui, test, config, docs, refactor, other. \
+         {} \
+         For overallSummary, write a structured reviewer-facing summary in this format: \
+         First line: a single sentence stating WHAT the PR changes and WHY (keep it short). \
+         Then a blank line (\"\\n\\n\"), followed by bullet points (each starting with \"- \") listing each key change, one per line. \
+         Rules for bullets: one change per bullet, keep each bullet under 80 characters, \
+         use noun phrases (no trailing verbs like \"〜している\"), \
+         include only 1-2 key identifiers per bullet (the most important function or type name) — do NOT exhaustively list every touched symbol. \
+         Do NOT mention hunks, hunks.json, grouping process, or analysis internals — write as if summarizing the PR itself. \
+         Also classify each hunk as substantive or non-substantive. \
+         Non-substantive changes are: formatting/whitespace-only changes, code moved to another file without modification, \
+         indentation changes, lock file updates, auto-generated code changes, snapshot updates. \
+         Note: variable/function renames and comment changes ARE substantive. \
+         List non-substantive hunk IDs in nonSubstantiveHunkIds.{}",
+        hunk_count,
+        pr_context,
+        summaries_hint,
+        guidelines_context(guidelines),
+        dependency_context,
+        risky_context,
+        RISK_INSTRUCTIONS,
+        lang_suffix(lang)
+    )
+}
+
+/// Hunks JSON for the model with lockfile hunk lines dropped: the dependency summary in the
+/// prompt carries their meaning, and the ids stay so every hunk can still be grouped.
+fn strip_lockfile_lines(hunks_json: &str, hunks: &[Hunk]) -> Result<String, String> {
+    if !hunks.iter().any(|h| manifest::is_lockfile(&h.file_path)) {
+        return Ok(hunks_json.to_string());
+    }
+    let stripped: Vec<Hunk> = hunks
+        .iter()
+        .map(|h| {
+            let mut h = h.clone();
+            if manifest::is_lockfile(&h.file_path) {
+                h.lines.clear();
+            }
+            h
+        })
+        .collect();
+    serde_json::to_string(&stripped).map_err(|e| format!("Failed to serialize hunks: {}", e))
+}
+
+/// The hunks.json payload and prompt of an analysis run. Shared with `preview_analysis_prompt`
+/// so the preview is exactly what `run_analysis` sends.
+fn analysis_input(
+    hunks_json: &str,
+    hunks: &[Hunk],
+    hunk_count: usize,
+    pr_body: &Option<String>,
+    has_summaries: bool,
+    guidelines: &Option<String>,
+    lang: &Option<String>,
+) -> Result<(String, String), String> {
+    let dependency_summary = manifest::render_summary(&manifest::dependency_changes(hunks));
+    let risky_summary = risky::render_summary(&risky::scan_hunks(hunks));
+    let model_hunks_json = strip_lockfile_lines(hunks_json, hunks)?;
+    let prompt = build_analysis_prompt(
+        hunk_count,
+        pr_body,
+        has_summaries,
+        guidelines,
+        &dependency_summary,
+        &risky_summary,
+        lang,
+    );
+    Ok((model_hunks_json, prompt))
+}
+
+/// Follow-up prompt asking the model to fix an analysis that failed ID validation.
+/// The previous answer is provided as previous.json next to hunks.json.
+fn build_repair_prompt(base_prompt: &str, warnings: &[String]) -> String {
+    let problems: Vec<&str> = warnings
+        .iter()
+        .take(MAX_REPAIR_WARNINGS)
+        .map(String::as_str)
+        .collect();
+    format!(
+        "{}\n\nprevious.json contains your previous answer to this task. \
+         It failed validation with these problems:\n- {}\n\
+         Return a complete corrected answer that fixes every problem: \
+         use only hunk ids that exist in hunks.json and assign every hunk to exactly one group.",
+        base_prompt,
+        problems.join("\n- ")
+    )
+}
+
+fn read_analysis_output(output_path: &Path) -> Result<(String, AnalysisResult), String> {
+    let analysis_str = std::fs::read_to_string(output_path).map_err(|e| {
+        format!(
+            "Failed to read analysis.json: {}. Codex may not have produced output.",
+            e
+        )
+    })?;
+    let result: AnalysisResult = serde_json::from_str(&analysis_str)
+        .map_err(|e| format!("Failed to parse analysis.json: {}", e))?;
+    Ok((analysis_str, result))
+}
+
+fn build_refine_prompt(
+    group_title: &str,
+    group_id: &str,
+    guidelines: &Option<String>,
+    lang: &Option<String>,
+) -> String {
+    format!(
+        "Read hunks.json. These hunks all belong to a single intent group titled \"{}\". \
+         Split them into smaller, more focused sub-groups by specific change purpose. \
+         Use only existing hunk ids from the input. Do not invent ids. \
+         Sub-group ids must be \"{}.1\", \"{}.2\", etc. \
+         Order sub-groups by logical processing flow. \
+         Give each sub-group a clear, descriptive title. \
+         Assign each sub-group a category from: schema, logic, api, ui, test, config, docs, refactor, other. \
+         {}{}{}",
+        group_title,
+        group_id,
+        group_id,
+        RISK_INSTRUCTIONS,
+        guidelines_context(guidelines),
+        lang_suffix(lang)
+    )
+}
+
+/// Upper bound on the reviewer's regrouping instructions quoted into the prompt.
+const MAX_GUIDANCE_CHARS: usize = 2000;
+
+fn build_guided_refine_prompt(
+    group_title: &str,
+    group_id: &str,
+    guidance: &str,
+    guidelines: &Option<String>,
+    lang: &Option<String>,
+) -> String {
+    let guidance = guidance.trim();
+    format!(
+        "Read hunks.json. These hunks all belong to a single intent group titled \"{}\". \
+         Regroup them into one or more sub-groups by specific change purpose, following the reviewer's instructions, \
+         which take precedence over your own judgement of how to group:\n\"{}\"\n\
+         Assign every hunk in hunks.json to exactly one sub-group. \
+         Use only existing hunk ids from the input. Do not invent ids. \
+         Sub-group ids must be \"{}.1\", \"{}.2\", etc. \
+         Order sub-groups by logical processing flow. \
+         Give each sub-group a clear, descriptive title. \
+         Assign each sub-group a category from: schema, logic, api, ui, test, config, docs, refactor, other. \
+         {}{}{}",
+        group_title,
+        &guidance[..guidance.floor_char_boundary(MAX_GUIDANCE_CHARS)],
+        group_id,
+        group_id,
+        RISK_INSTRUCTIONS,
+        guidelines_context(guidelines),
+        lang_suffix(lang)
+    )
+}
+
+/// Replace group `group_id` in `analysis` with `sub_groups`, keeping its position.
+fn merge_sub_groups(analysis: &mut AnalysisResult, group_id: &str, sub_groups: &[IntentGroup]) {
+    if let Some(pos) = analysis.groups.iter().position(|g| g.id == group_id) {
+        analysis
+            .groups
+            .splice(pos..=pos, sub_groups.iter().cloned());
+    }
+}
+
+/// Inputs of one intent analysis run, shared by the command and background pre-analysis.
+pub struct AnalysisRequest {
+    pub hunks_json: String,
+    pub pr_body: Option<String>,
+    pub hunk_summaries: Option<Vec<HunkSummary>>,
+    pub guidelines: Option<String>,
+    pub repo_context: Option<RepoContext>,
+    /// PR the hunks come from, recorded on the cache entry for `invalidate_cache`.
+    pub cache_owner: Option<RepoContext>,
+    pub model: Option<String>,
+    pub lang: Option<String>,
+    pub options: RunOptions,
+    pub force: bool,
+    pub timeout_minutes: Option<u64>,
+}
+
+/// Run (or load from cache) an intent analysis and cache the validated result. Checklist
+/// templates are merged into the returned groups, not the cached ones.
+pub fn run_analysis(
+    app_data_dir: Option<&Path>,
+    provider: &dyn AnalysisProvider,
+    request: AnalysisRequest,
+) -> Result<AnalysisResponse, PrvwError> {
+    let AnalysisRequest {
+        hunks_json,
+        pr_body,
+        hunk_summaries,
+        guidelines,
+        repo_context,
+        cache_owner,
+        model,
+        lang,
+        options,
+        force,
+        timeout_minutes,
+    } = request;
+    let template_repo = cache_owner
+        .as_ref()
+        .or(repo_context.as_ref())
+        .map(|c| c.repo.clone());
+
+    let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+    let valid_ids: HashSet<String> = hunks.iter().map(|h| h.id.clone()).collect();
+
+    if valid_ids.is_empty() {
+        return Err(t(Msg::NoHunksToAnalyze, &[]).into());
+    }
+
+    let model_str = format!(
+        "{}:{}:{}",
+        provider.name(),
+        model.as_deref().unwrap_or(""),
+        options.cache_key()
+    );
+    let lang_str = lang.as_deref().unwrap_or("");
+    let pr_body_str = pr_body.as_deref().unwrap_or("");
+    let summaries_json = match hunk_summaries {
+        Some(ref s) if !s.is_empty() => Some(
+            serde_json::to_string(s)
+                .map_err(|e| format!("Failed to serialize hunk summaries: {}", e))?,
+        ),
+        _ => None,
+    };
+    let cache_key = cache::hash_key(&format!(
+        "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+        hunks_json,
+        pr_body_str,
+        summaries_json.as_deref().unwrap_or(""),
+        guidelines.as_deref().unwrap_or(""),
+        repo_context_key(&repo_context),
+        model_str,
+        lang_str
+    ));
+
+    // Check cache (unless force)
+    if !force {
+        if let Some(dir) = app_data_dir {
+            if let Some(mut cached) =
+                cache::read_cache::<AnalysisResponse>(dir, "cache/analysis", &cache_key)
+            {
+                cached.from_cache = true;
+                checklists::apply_templates(
+                    Some(dir),
+                    template_repo.as_deref(),
+                    &mut cached.result.groups,
+                );
+                return Ok(cached);
+            }
+        }
+    }
+
+    let checkout = prepare_repo_context(app_data_dir, &repo_context)?;
+    let (model_hunks_json, prompt) = analysis_input(
+        &hunks_json,
+        &hunks,
+        valid_ids.len(),
+        &pr_body,
+        summaries_json.is_some(),
+        &guidelines,
+        &lang,
+    )?;
+    let (temp_dir, schema_path, output_path) =
+        codex_runner::prepare_temp_dir(&model_hunks_json, ANALYSIS_SCHEMA, "analysis.json")?;
+
+    if let Some(ref json) = summaries_json {
+        std::fs::write(temp_dir.path().join("summaries.json"), json)
+            .map_err(|e| format!("Failed to write summaries.json: {}", e))?;
+    }
+
+    let repo_dir = checkout.as_ref().map(|(path, _)| path.as_path());
+    let timeout = codex_runner::timeout_from_minutes(timeout_minutes);
+
+    let estimated_tokens =
+        usage::estimate_tokens(&model_hunks_json) + usage::estimate_tokens(&prompt);
+    let codex_output = provider::execute(
+        provider,
+        Job {
+            temp_path: temp_dir.path(),
+            schema_path: &schema_path,
+            output_path: &output_path,
+            repo_dir,
+            model: &model,
+            options: &options,
+            prompt: prompt.clone(),
+            timeout,
+        },
+    )?;
+    usage::record(
+        app_data_dir,
+        "analysis",
+        provider.name(),
+        estimated_tokens,
+        &codex_output,
+    );
+
+    let (analysis_str, result) = read_analysis_output(&output_path)?;
+    let mut validation = validate_analysis(&result, &valid_ids);
+    let mut accepted_run_id = codex_output.run_id.clone();
+
+    let mut log = codex_runner::build_log("analysis", &codex_output);
+    if let Some((_, sha)) = &checkout {
+        log.push_str(&format!("[analysis] repo context at {}\n", sha));
+    }
+
+    // One repair attempt when the output is badly broken; the cleaned first result is the fallback.
+    if validation.needs_repair(valid_ids.len()) {
+        log.push_str(&format!(
+            "[analysis] {} hunk id problem(s), retrying once with a repair prompt\n",
+            validation.id_errors
+        ));
+        std::fs::write(temp_dir.path().join("previous.json"), &analysis_str)
+            .map_err(|e| format!("Failed to write previous.json: {}", e))?;
+        let repaired = provider::execute(
+            provider,
+            Job {
+                temp_path: temp_dir.path(),
+                schema_path: &schema_path,
+                output_path: &output_path,
+                repo_dir,
+                model: &model,
+                options: &options,
+                prompt: build_repair_prompt(&prompt, &validation.warnings),
+                timeout,
+            },
+        )
+        .map_err(String::from)
+        .and_then(|output| Ok((output, read_analysis_output(&output_path)?.1)));
+        match repaired {
+            Ok((repair_output, repaired_result)) => {
+                usage::record(
+                    app_data_dir,
+                    "analysis-repair",
+                    provider.name(),
+                    estimated_tokens,
+                    &repair_output,
+                );
+                log.push_str(&codex_runner::build_log("analysis-repair", &repair_output));
+                let repaired_validation = validate_analysis(&repaired_result, &valid_ids);
+                if repaired_validation.id_errors < validation.id_errors {
+                    log.push_str(&format!(
+                        "[analysis] repair accepted: {} -> {} hunk id problem(s)\n",
+                        validation.id_errors, repaired_validation.id_errors
+                    ));
+                    validation = repaired_validation;
+                    accepted_run_id = repair_output.run_id.clone();
+                } else {
+                    log.push_str(
+                        "[analysis] repair did not help, keeping the cleaned first result\n",
+                    );
+                }
+            }
+            Err(e) => log.push_str(&format!(
+                "[analysis] repair failed, keeping the cleaned first result: {}\n",
+                e
+            )),
+        }
+    }
+
+    let leftovers = leftovers::scan_hunks(&hunks);
+    leftovers::annotate_checklists(&mut validation.cleaned, &leftovers);
+    let risky_patterns = risky::scan_hunks(&hunks);
+
+    log.push_str(&format!(
+        "[analysis] hunks={} groups={} leftovers={} risky={}\n",
+        valid_ids.len(),
+        validation.cleaned.groups.len(),
+        leftovers.len(),
+        risky_patterns.len()
+    ));
+    history::attach_warnings(accepted_run_id.as_deref(), &validation.warnings);
+    if !validation.warnings.is_empty() {
+        log.push_str("--- validation warnings ---\n");
+        for w in &validation.warnings {
+            log.push_str(w);
+            log.push('\n');
+        }
+    }
+
+    let mut response = AnalysisResponse {
+        result: validation.cleaned,
+        leftovers,
+        risky_patterns,
+        codex_log: log,
+        from_cache: false,
+    };
+
+    // Write cache
+    if let Some(dir) = app_data_dir {
+        cache::write_cache_owned(
+            dir,
+            "cache/analysis",
+            &cache_key,
+            &response,
+            cache_owner.as_ref(),
+        );
+    }
+    checklists::apply_templates(
+        app_data_dir,
+        template_repo.as_deref(),
+        &mut response.result.groups,
+    );
+
+    Ok(response)
+}
+
+/// Merge checklist templates into a guided refinement's analysis and new sub-groups.
+fn apply_guided_templates(
+    app_data_dir: Option<&Path>,
+    repo_context: &Option<RepoContext>,
+    response: &mut GuidedRefineResponse,
+) {
+    let repo = repo_context.as_ref().map(|c| c.repo.as_str());
+    checklists::apply_templates(app_data_dir, repo, &mut response.result.groups);
+    checklists::apply_templates(app_data_dir, repo, &mut response.sub_groups);
+}
+
+fn build_draft_comments_prompt(group_title: &str, lang: &Option<String>) -> String {
+    format!(
+        "Read hunks.json. These hunks all belong to the intent group titled \"{}\". \
+         Act as a careful code reviewer and propose inline review comments only where there is something \
+         worth saying (bugs, missing edge cases, unclear naming, missing tests, risky changes). \
+         Each comment must reference an existing hunk id and its filePath, and a line number that appears in that hunk: \
+         use side \"RIGHT\" with the newLine of an added or context line, or side \"LEFT\" with the oldLine of a removed line. \
+         Do not invent ids or lines. \
+         Set severity to one of: nit, suggestion, issue, blocker. \
+         Write each body as a concise, polite comment addressed to the PR author, in markdown. \
+         Return an empty comments array if nothing needs comment.{}",
+        group_title,
+        lang_suffix(lang)
+    )
+}
+
+fn build_split_plan_prompt(
+    hunk_count: usize,
+    pr_body: &Option<String>,
+    has_analysis: bool,
+    lang: &Option<String>,
+) -> String {
+    let pr_context = match pr_body {
+        Some(body) if !body.trim().is_empty() => {
+            format!(" The PR description is:\n---\n{}\n---\n", body.trim())
+        }
+        _ => String::new(),
+    };
+    let analysis_hint = if has_analysis {
+        " analysis.json contains an existing grouping of the hunks by intent; use it as a hint, \
+         but a proposed PR may combine or cut across intent groups."
+    } else {
+        ""
+    };
+    format!(
+        "Read hunks.json which contains {} hunks of a pull request that is too large to review comfortably.{}{} \
+         Propose how it could be split into smaller PRs that can each be reviewed and merged independently, \
+         listed in the order they should be merged. \
+         Each proposed PR gets a short title, a one-paragraph description of what it contains and why it stands alone, \
+         and the hunk ids it contains. Every hunk id should appear in exactly one proposed PR. \
+         Use only existing hunk ids from the input. Do not invent ids. \
+         In dependsOn list the 1-based positions of earlier proposed PRs that must be merged first; \
+         a PR may only depend on PRs listed before it. \
+         Prefer few, cohesive PRs; if the PR should not be split, return a single proposed PR and explain why in rationale. \
+         Summarize the splitting strategy in rationale.{}",
+        hunk_count,
+        pr_context,
+        analysis_hint,
+        lang_suffix(lang)
+    )
+}
+
+fn build_breaking_changes_prompt(lang: &Option<String>) -> String {
+    format!(
+        "Read hunks.json and analysis.json, which groups those hunks by intent. \
+         Look only for changes to public surfaces that other code or users depend on: \
+         exported or public symbols (functions, types, methods, constants, traits), HTTP/RPC endpoints, \
+         database or serialization schemas, configuration keys, and CLI flags or commands. \
+         Report each change that could break existing callers or data: removals, renames, signature or type changes, \
+         new required parameters or fields, changed defaults, and changed response or file formats. \
+         For each finding give the group id from analysis.json, the hunk ids from that group where the change happens, \
+         kind (symbol, endpoint, schema, config, cli, other), the affected name in subject, \
+         a one-sentence description of what breaks and for whom, \
+         and severity \"breaking\" when callers will certainly break or \"possibly-breaking\" otherwise. \
+         Ignore private or internal code and purely additive changes. \
+         Use only existing group and hunk ids. Do not invent ids. \
+         Return an empty findings array if nothing public changes incompatibly.{}",
+        lang_suffix(lang)
+    )
+}
+
+fn build_consistency_prompt(title: &str, body: &Option<String>, lang: &Option<String>) -> String {
+    let description = match body.as_deref() {
+        Some(b) if !b.trim().is_empty() => {
+            let end = b.floor_char_boundary(4000);
+            format!(" The PR description is: \"{}\".", &b[..end])
+        }
+        _ => " The PR has no description.".to_string(),
+    };
+    format!(
+        "The PR title is: \"{}\".{} \
+         Read analysis.json, which groups the PR's actual changes by intent. \
+         Compare what the title and description claim with what the groups show, and report each mismatch: \
+         undisclosed (a group makes a meaningful change the title and description do not mention or imply, \
+         e.g. the PR says \"fix typo\" but modifies auth middleware), \
+         unfulfilled (a claimed change that no group implements), \
+         or contradicted (a group does something at odds with a claim). \
+         Give the claim as a short quote or paraphrase (empty for undisclosed), the related group ids \
+         (empty for unfulfilled), a one-sentence description, and severity: \
+         high for undisclosed security, data, or behavior changes, medium for other functional gaps, \
+         low for minor omissions. Do not report tests, docs, formatting, or refactors that obviously \
+         support a described change. Use only existing group ids. Do not invent ids. \
+         In summary, write one sentence on how well the title and description match the changes. \
+         Return an empty mismatches array if they match.{}",
+        title.trim(),
+        description,
+        lang_suffix(lang)
+    )
+}
+
+fn build_summarize_prompt(hunk_count: usize, lang: &Option<String>) -> String {
+    format!(
+        "Read hunks.json which contains {} hunks. \
+         For EVERY hunk, write a single sentence (under 120 characters) explaining what the change does, \
+         written for a code reviewer skimming the diff. \
+         Return exactly one entry per hunk id. Use only existing hunk ids. Do not invent ids. \
+         Do not mention hunks or hunks.json in the sentences.{}",
+        hunk_count,
+        lang_suffix(lang)
+    )
+}
+
+fn build_explain_prompt(file_path: &str, lang: &Option<String>) -> String {
+    format!(
+        "Read hunk.json which contains a single code hunk from the file \"{}\". \
+         Explain this change for a code reviewer. Your explanation (in markdown) must cover: \
+         1. **What changed** — describe the concrete code change in a few sentences. \
+         2. **Intent** — why this change was likely made. \
+         3. **Impact** — what behavior or logic is affected, and any potential risks. \
+         Keep it concise and reviewer-focused. Use inline code (backticks) for identifiers.{}",
+        file_path,
+        lang_suffix(lang)
+    )
+}
+
+fn build_ask_prompt(
+    file_path: &str,
+    question: &str,
+    context: &str,
+    lang: &Option<String>,
+) -> String {
+    format!(
+        "Read hunk.json which contains a single code hunk from the file \"{}\". \
+         A reviewer has already received this analysis:\n\n{}\n\n\
+         The reviewer now asks: \"{}\"\n\n\
+         Answer the question concisely in markdown. Use inline code (backticks) for identifiers.{}",
+        file_path,
+        context,
+        question,
+        lang_suffix(lang)
+    )
+}
+
+/// Group a diff's hunks into intents. Pass the `session_id` of a review opened with
+/// `open_review_state` (with `hunk_ids` to analyze only those), or the hunks as `hunks_json`.
+#[allow(clippy::too_many_arguments)]
+pub async fn analyze_intents_with_codex(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    states: &ReviewStates,
+    session_id: Option<String>,
+    hunk_ids: Option<Vec<String>>,
+    hunks_json: Option<String>,
+    pr_body: Option<String>,
+    hunk_summaries: Option<Vec<HunkSummary>>,
+    guidelines: Option<String>,
+    repo_context: Option<RepoContext>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    cache_owner: Option<RepoContext>,
+    op_id: Option<String>,
+) -> Result<AnalysisResponse, PrvwError> {
+    let op = Op::start(op_id, "analyze_intents_with_codex");
+    let result: Result<AnalysisResponse, PrvwError> = op
+        .scope(async {
+            let defaults = settings::for_repo(
+                cache_owner
+                    .as_ref()
+                    .or(repo_context.as_ref())
+                    .map(|c| c.repo.as_str()),
+            );
+            let guidelines = config::with_prompt(guidelines, &defaults, "analysis");
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::from_settings(
+                &defaults,
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+            let hunks_json = review_state::resolve_hunks(
+                states,
+                session_id.as_deref(),
+                hunk_ids.as_deref(),
+                hunks_json,
+            )?;
+            op.progress(t(Msg::RunningAnalysis, &[&provider.name()]));
+            let response = run_analysis(
+                app_data_dir,
+                provider.as_ref(),
+                AnalysisRequest {
+                    hunks_json,
+                    pr_body,
+                    hunk_summaries,
+                    guidelines,
+                    repo_context,
+                    cache_owner,
+                    model,
+                    lang,
+                    options,
+                    force: force == Some(true),
+                    timeout_minutes,
+                },
+            )?;
+            // An analysis of a hunk selection doesn't replace the review's full one.
+            if let (Some(session_id), None) = (&session_id, &hunk_ids) {
+                states.set_analysis(session_id, &response.result)?;
+            }
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+/// Re-run grouping honoring the reviewer's corrections to `analysis`: pinned hunk-to-group
+/// assignments and group merges are applied first, given to the model as fixed constraints
+/// (with the corrected analysis as previous.json), and enforced again on its answer.
+#[allow(clippy::too_many_arguments)]
+pub async fn adjust_analysis(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    hunks_json: String,
+    analysis: AnalysisResult,
+    constraints: AnalysisConstraints,
+    pr_body: Option<String>,
+    guidelines: Option<String>,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<AnalysisResponse, PrvwError> {
+    let op = Op::start(op_id, "adjust_analysis");
+    let result: Result<AnalysisResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                repo.as_deref(),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+
+            let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+                .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+            let valid_ids: HashSet<String> = hunks.iter().map(|h| h.id.clone()).collect();
+            if valid_ids.is_empty() {
+                return Err(t(Msg::NoHunksToAnalyze, &[]).into());
+            }
+
+            let (adjusted, pins, mut warnings) =
+                constraints::apply_constraints(&analysis, &constraints, &valid_ids);
+            if pins.is_empty() {
+                let reason = if warnings.is_empty() {
+                    "No constraints given.".to_string()
+                } else {
+                    format!("None of the constraints apply: {}", warnings.join("; "))
+                };
+                return Err(reason.into());
+            }
+
+            let analysis_json = serde_json::to_string(&analysis)
+                .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+            let constraints_json = serde_json::to_string(&constraints)
+                .map_err(|e| format!("Failed to serialize constraints: {}", e))?;
+
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            let cache_key = cache::hash_key(&format!(
+                "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+                hunks_json,
+                analysis_json,
+                constraints_json,
+                pr_body.as_deref().unwrap_or(""),
+                guidelines.as_deref().unwrap_or(""),
+                model_str,
+                lang_str
+            ));
+
+            if force != Some(true) {
+                if let Some(dir) = app_data_dir {
+                    if let Some(mut cached) =
+                        cache::read_cache_async::<AnalysisResponse>(dir, "cache/adjust", &cache_key)
+                            .await
+                    {
+                        cached.from_cache = true;
+                        checklists::apply_templates(
+                            Some(dir),
+                            repo.as_deref(),
+                            &mut cached.result.groups,
+                        );
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (model_hunks_json, base_prompt) = analysis_input(
+                &hunks_json,
+                &hunks,
+                valid_ids.len(),
+                &pr_body,
+                false,
+                &guidelines,
+                &lang,
+            )?;
+            let (temp_dir, schema_path, output_path) = codex_runner::prepare_temp_dir(
+                &model_hunks_json,
+                ANALYSIS_SCHEMA,
+                "analysis.json",
+            )?;
+            let adjusted_json = serde_json::to_string(&adjusted)
+                .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+            std::fs::write(temp_dir.path().join("previous.json"), &adjusted_json)
+                .map_err(|e| format!("Failed to write previous.json: {}", e))?;
+
+            let prompt = format!(
+                "{}{}",
+                base_prompt,
+                constraints::constraints_prompt(&adjusted, &pins)
+            );
+
+            op.progress(t(Msg::RunningAnalysis, &[&provider.name()]));
+            let estimated_tokens = usage::estimate_tokens(&model_hunks_json)
+                + usage::estimate_tokens(&adjusted_json)
+                + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path: temp_dir.path(),
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir,
+                "analysis-adjust",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let (_, result) = read_analysis_output(&output_path)?;
+            let validation = validate_analysis(&result, &valid_ids);
+            warnings.extend(validation.warnings);
+            let mut cleaned = validation.cleaned;
+            warnings.extend(constraints::enforce_pins(&mut cleaned, &pins, &adjusted));
+
+            let leftovers = leftovers::scan_hunks(&hunks);
+            leftovers::annotate_checklists(&mut cleaned, &leftovers);
+            let risky_patterns = risky::scan_hunks(&hunks);
+
+            let mut log = codex_runner::build_log("analysis-adjust", &codex_output);
+            log.push_str(&format!(
+                "[analysis-adjust] pins={} groups={}\n",
+                pins.len(),
+                cleaned.groups.len()
+            ));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let mut response = AnalysisResponse {
+                result: cleaned,
+                leftovers,
+                risky_patterns,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(dir) = app_data_dir {
+                cache::write_cache(dir, "cache/adjust", &cache_key, &response);
+            }
+            checklists::apply_templates(app_data_dir, repo.as_deref(), &mut response.result.groups);
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+/// Dry run of `analyze_intents_with_codex`: the prompt, schema, and input files an analysis
+/// would send, without running a provider. For debugging grouping quality and prompt edits.
+/// With full-repo context, `execute` additionally prefixes a preamble naming the checkout.
+pub async fn preview_analysis_prompt(
+    hunks_json: String,
+    pr_body: Option<String>,
+    hunk_summaries: Option<Vec<HunkSummary>>,
+    guidelines: Option<String>,
+    lang: Option<String>,
+    repo: Option<String>,
+) -> Result<AnalysisPromptPreview, PrvwError> {
+    let defaults = settings::for_repo(repo.as_deref());
+    let guidelines = config::with_prompt(guidelines, &defaults, "analysis");
+    let lang = lang.or(defaults.lang);
+    let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+    let hunk_count = hunks
+        .iter()
+        .map(|h| h.id.as_str())
+        .collect::<HashSet<_>>()
+        .len();
+    if hunk_count == 0 {
+        return Err(PrvwError::InvalidInput(t(Msg::NoHunksToAnalyze, &[])));
+    }
+    let summaries_json = match hunk_summaries {
+        Some(ref s) if !s.is_empty() => Some(
+            serde_json::to_string(s)
+                .map_err(|e| format!("Failed to serialize hunk summaries: {}", e))?,
+        ),
+        _ => None,
+    };
+    let (model_hunks_json, prompt) = analysis_input(
+        &hunks_json,
+        &hunks,
+        hunk_count,
+        &pr_body,
+        summaries_json.is_some(),
+        &guidelines,
+        &lang,
+    )?;
+    let estimated_tokens =
+        usage::estimate_tokens(&model_hunks_json) + usage::estimate_tokens(&prompt);
+
+    Ok(AnalysisPromptPreview {
+        prompt,
+        schema: ANALYSIS_SCHEMA.to_string(),
+        hunks_json: model_hunks_json,
+        summaries_json,
+        estimated_tokens,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn refine_group(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    states: &ReviewStates,
+    session_id: Option<String>,
+    hunks_json: Option<String>,
+    group_id: String,
+    group_title: String,
+    hunk_ids: Vec<String>,
+    guidelines: Option<String>,
+    repo_context: Option<RepoContext>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    cache_owner: Option<RepoContext>,
+    op_id: Option<String>,
+) -> Result<RefineResponse, PrvwError> {
+    let op = Op::start(op_id, "refine_group");
+    let result: Result<RefineResponse, PrvwError> = op
+        .scope(async {
+            let defaults = settings::for_repo(
+                cache_owner
+                    .as_ref()
+                    .or(repo_context.as_ref())
+                    .map(|c| c.repo.as_str()),
+            );
+            let guidelines = config::with_prompt(guidelines, &defaults, "refine");
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::from_settings(
+                &defaults,
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+
+            let hunks_json = review_state::resolve_hunks(
+                states,
+                session_id.as_deref(),
+                Some(&hunk_ids),
+                hunks_json,
+            )?;
+            let all_hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+                .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+
+            let hunk_id_set: HashSet<String> = hunk_ids.into_iter().collect();
+            let group_hunks: Vec<&Hunk> = all_hunks
+                .iter()
+                .filter(|h| hunk_id_set.contains(&h.id))
+                .collect();
+
+            if group_hunks.is_empty() {
+                return Err(t(Msg::NoHunksInGroup, &[]).into());
+            }
+
+            let group_hunks_json = serde_json::to_string(&group_hunks)
+                .map_err(|e| format!("Failed to serialize group hunks: {}", e))?;
+
+            let template_repo = cache_owner
+                .as_ref()
+                .or(repo_context.as_ref())
+                .map(|c| c.repo.clone());
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            let cache_key = cache::hash_key(&format!(
+                "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+                group_hunks_json,
+                group_id,
+                group_title,
+                guidelines.as_deref().unwrap_or(""),
+                repo_context_key(&repo_context),
+                model_str,
+                lang_str
+            ));
+
+            // Check cache (unless force)
+            if force != Some(true) {
+                if let Some(dir) = app_data_dir {
+                    if let Some(mut cached) =
+                        cache::read_cache_async::<RefineResponse>(dir, "cache/refine", &cache_key)
+                            .await
+                    {
+                        cached.from_cache = true;
+                        checklists::apply_templates(
+                            Some(dir),
+                            template_repo.as_deref(),
+                            &mut cached.sub_groups,
+                        );
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            if repo_context.is_some() {
+                op.progress(t(Msg::PreparingCheckout, &[]));
+            }
+            let checkout = prepare_repo_context(app_data_dir, &repo_context)?;
+            let (temp_dir, schema_path, output_path) =
+                codex_runner::prepare_temp_dir(&group_hunks_json, REFINE_SCHEMA, "refine.json")?;
+            op.progress(t(Msg::Refining, &[&group_title, &provider.name()]));
+
+            let prompt = build_refine_prompt(&group_title, &group_id, &guidelines, &lang);
+
+            let estimated_tokens =
+                usage::estimate_tokens(&group_hunks_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path: temp_dir.path(),
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: checkout.as_ref().map(|(path, _)| path.as_path()),
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir,
+                "refine",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read refine.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let refine_result: RefineResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse refine.json: {}", e))?;
+
+            // Validate: strip invalid hunk IDs
+            let mut warnings: Vec<String> = Vec::new();
+            let mut cleaned_groups = refine_result.groups;
+            for g in &mut cleaned_groups {
+                let before = g.hunk_ids.len();
+                g.hunk_ids.retain(|id| {
+                    if hunk_id_set.contains(id) {
+                        true
+                    } else {
+                        warnings.push(format!(
+                            "Removed non-existent hunk id '{}' from sub-group '{}'",
+                            id, g.title
+                        ));
+                        false
+                    }
+                });
+                if g.hunk_ids.len() != before {
+                    warnings.push(format!(
+                        "Sub-group '{}': {} -> {} hunks",
+                        g.title,
+                        before,
+                        g.hunk_ids.len()
+                    ));
+                }
+                validate_risk(g, &mut warnings);
+            }
+            cleaned_groups.retain(|g| !g.hunk_ids.is_empty());
+
+            let mut log = codex_runner::build_log("refine", &codex_output);
+            if let Some((_, sha)) = &checkout {
+                log.push_str(&format!("[refine] repo context at {}\n", sha));
+            }
+            log.push_str(&format!(
+                "[refine] group=\"{}\" sub-groups={}\n",
+                group_title,
+                cleaned_groups.len()
+            ));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let mut response = RefineResponse {
+                sub_groups: cleaned_groups,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            // Write cache
+            if let Some(dir) = app_data_dir {
+                cache::write_cache_owned(
+                    dir,
+                    "cache/refine",
+                    &cache_key,
+                    &response,
+                    cache_owner.as_ref(),
+                );
+            }
+
+            checklists::apply_templates(
+                app_data_dir,
+                template_repo.as_deref(),
+                &mut response.sub_groups,
+            );
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+/// Like `refine_group`, but regroups by the reviewer's free-form `guidance` ("split out the
+/// migration", "these two belong together"). The sub-groups must cover exactly the group's hunks;
+/// they replace the group in `analysis`, and the merged analysis is returned.
+#[allow(clippy::too_many_arguments)]
+pub async fn refine_group_with_guidance(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    hunks_json: String,
+    analysis: AnalysisResult,
+    group_id: String,
+    guidance: String,
+    guidelines: Option<String>,
+    repo_context: Option<RepoContext>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<GuidedRefineResponse, PrvwError> {
+    let op = Op::start(op_id, "refine_group_with_guidance");
+    let result: Result<GuidedRefineResponse, PrvwError> = op.scope(async {
+        let defaults = settings::for_repo(repo_context.as_ref().map(|c| c.repo.as_str()));
+        let guidelines = config::with_prompt(guidelines, &defaults, "refine");
+        let RunDefaults {
+            model,
+            lang,
+            provider,
+            options,
+            timeout_minutes,
+        } = RunDefaults::from_settings(&defaults, model, lang, provider, options, timeout_minutes);
+        let provider = providers.resolve(&provider)?;
+
+        if guidance.trim().is_empty() {
+            return Err("No guidance given for the refinement.".to_string().into());
+        }
+        let group = analysis
+            .groups
+            .iter()
+            .find(|g| g.id == group_id)
+            .ok_or_else(|| {
+                PrvwError::NotFound(format!("Group {} not found in the analysis.", group_id))
+            })?;
+
+        let all_hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+            .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+        let group_hunks: Vec<&Hunk> = all_hunks
+            .iter()
+            .filter(|h| group.hunk_ids.contains(&h.id))
+            .collect();
+        if group_hunks.is_empty() {
+            return Err(t(Msg::NoHunksInGroup, &[]).into());
+        }
+        let hunk_ids: Vec<String> = group_hunks.iter().map(|h| h.id.clone()).collect();
+
+        let group_hunks_json = serde_json::to_string(&group_hunks)
+            .map_err(|e| format!("Failed to serialize group hunks: {}", e))?;
+        let analysis_json = serde_json::to_string(&analysis)
+            .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+
+        let model_str = format!(
+            "{}:{}:{}",
+            provider.name(),
+            model.as_deref().unwrap_or(""),
+            options.cache_key()
+        );
+        let lang_str = lang.as_deref().unwrap_or("");
+        let cache_key = cache::hash_key(&format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            group_hunks_json,
+            analysis_json,
+            group_id,
+            guidance.trim(),
+            guidelines.as_deref().unwrap_or(""),
+            repo_context_key(&repo_context),
+            model_str,
+            lang_str
+        ));
+
+        if force != Some(true) {
+            if let Some(dir) = app_data_dir {
+                if let Some(mut cached) = cache::read_cache_async::<GuidedRefineResponse>(
+                    dir,
+                    "cache/refine_guided",
+                    &cache_key,
+                )
+                .await
+                {
+                    cached.from_cache = true;
+                    apply_guided_templates(Some(dir), &repo_context, &mut cached);
+                    return Ok(cached);
+                }
+            }
+        }
+
+        if repo_context.is_some() {
+            op.progress(t(Msg::PreparingCheckout, &[]));
+        }
+        let checkout = prepare_repo_context(app_data_dir, &repo_context)?;
+        let (temp_dir, schema_path, output_path) =
+            codex_runner::prepare_temp_dir(&group_hunks_json, REFINE_SCHEMA, "refine.json")?;
+
+        let prompt = build_guided_refine_prompt(&group.title, &group_id, &guidance, &guidelines, &lang);
+
+        op.progress(t(Msg::Refining, &[&group.title, &provider.name()]));
+        let estimated_tokens =
+            usage::estimate_tokens(&group_hunks_json) + usage::estimate_tokens(&prompt);
+        let codex_output = provider::execute(
+            provider.as_ref(),
+            Job {
+                temp_path: temp_dir.path(),
+                schema_path: &schema_path,
+                output_path: &output_path,
+                repo_dir: checkout.as_ref().map(|(path, _)| path.as_path()),
+                model: &model,
+                options: &options,
+                prompt,
+                timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+            },
+        )?;
+        usage::record(
+            app_data_dir,
+            "refine-guided",
+            provider.name(),
+            estimated_tokens,
+            &codex_output,
+        );
+
+        let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+            format!(
+                "Failed to read refine.json: {}. Codex may not have produced output.",
+                e
+            )
+        })?;
+
+        let refine_result: RefineResult = serde_json::from_str(&result_str)
+            .map_err(|e| format!("Failed to parse refine.json: {}", e))?;
+
+        let (sub_groups, warnings, missing) =
+            validate_guided_refine(refine_result.groups, &group_id, &hunk_ids);
+        history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+        // Merging a partial regrouping would silently drop hunks from the review.
+        if !missing.is_empty() {
+            return Err(format!(
+                "The refinement left {} hunk(s) of group {} unassigned ({}); the analysis was not changed. \
+                 Try rephrasing the guidance.",
+                missing.len(),
+                group_id,
+                missing.join(", ")
+            )
+            .into());
+        }
+
+        let mut result = analysis;
+        merge_sub_groups(&mut result, &group_id, &sub_groups);
+
+        let mut log = codex_runner::build_log("refine-guided", &codex_output);
+        if let Some((_, sha)) = &checkout {
+            log.push_str(&format!("[refine-guided] repo context at {}\n", sha));
+        }
+        log.push_str(&format!(
+            "[refine-guided] group={} sub-groups={}\n",
+            group_id,
+            sub_groups.len()
+        ));
+        if !warnings.is_empty() {
+            log.push_str("--- validation warnings ---\n");
+            for w in &warnings {
+                log.push_str(w);
+                log.push('\n');
+            }
+        }
+
+        let mut response = GuidedRefineResponse {
+            result,
+            sub_groups,
+            codex_log: log,
+            from_cache: false,
+        };
+
+        if let Some(dir) = app_data_dir {
+            cache::write_cache(dir, "cache/refine_guided", &cache_key, &response);
+        }
+        apply_guided_templates(app_data_dir, &repo_context, &mut response);
+
+        Ok(response)
+    })
+    .await;
+    op.finish(result)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn draft_comments_for_group(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    hunks_json: String,
+    group_id: String,
+    group_title: String,
+    hunk_ids: Vec<String>,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<DraftCommentsResponse, PrvwError> {
+    let op = Op::start(op_id, "draft_comments_for_group");
+    let result: Result<DraftCommentsResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                repo.as_deref(),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+
+            let all_hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+                .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+
+            let hunk_id_set: HashSet<String> = hunk_ids.into_iter().collect();
+            let group_hunks: Vec<&Hunk> = all_hunks
+                .iter()
+                .filter(|h| hunk_id_set.contains(&h.id))
+                .collect();
+
+            if group_hunks.is_empty() {
+                return Err(t(Msg::NoHunksInGroup, &[]).into());
+            }
+
+            let group_hunks_json = serde_json::to_string(&group_hunks)
+                .map_err(|e| format!("Failed to serialize group hunks: {}", e))?;
+
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            let cache_key = cache::hash_key(&format!(
+                "{}\n{}\n{}\n{}\n{}",
+                group_hunks_json, group_id, group_title, model_str, lang_str
+            ));
+
+            if force != Some(true) {
+                if let Some(dir) = app_data_dir {
+                    if let Some(mut cached) = cache::read_cache_async::<DraftCommentsResponse>(
+                        dir,
+                        "cache/comments",
+                        &cache_key,
+                    )
+                    .await
+                    {
+                        cached.from_cache = true;
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (temp_dir, schema_path, output_path) = codex_runner::prepare_temp_dir(
+                &group_hunks_json,
+                COMMENTS_SCHEMA,
+                "comments.json",
+            )?;
+
+            let prompt = build_draft_comments_prompt(&group_title, &lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens =
+                usage::estimate_tokens(&group_hunks_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path: temp_dir.path(),
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir,
+                "comments",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read comments.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: DraftCommentsResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse comments.json: {}", e))?;
+
+            let (comments, warnings) = validate_draft_comments(result.comments, &group_hunks);
+
+            let mut log = codex_runner::build_log("comments", &codex_output);
+            log.push_str(&format!(
+                "[comments] group=\"{}\" comments={}\n",
+                group_title,
+                comments.len()
+            ));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let response = DraftCommentsResponse {
+                comments,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(dir) = app_data_dir {
+                cache::write_cache(dir, "cache/comments", &cache_key, &response);
+            }
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+/// Ask the model how an oversized PR could be split into smaller, independently mergeable PRs.
+/// The plan is validated against the real hunk ids before it is returned.
+#[allow(clippy::too_many_arguments)]
+pub async fn suggest_split_plan(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    hunks_json: String,
+    pr_body: Option<String>,
+    analysis: Option<AnalysisResult>,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<SplitPlanResponse, PrvwError> {
+    let op = Op::start(op_id, "suggest_split_plan");
+    let result: Result<SplitPlanResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                repo.as_deref(),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+
+            let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+                .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+            let hunk_ids: Vec<String> = hunks.iter().map(|h| h.id.clone()).collect();
+            if hunk_ids.is_empty() {
+                return Err("No hunks to split.".to_string().into());
+            }
+
+            let analysis_json = analysis
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()
+                .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            let cache_key = cache::hash_key(&format!(
+                "{}\n{}\n{}\n{}\n{}",
+                hunks_json,
+                pr_body.as_deref().unwrap_or(""),
+                analysis_json.as_deref().unwrap_or(""),
+                model_str,
+                lang_str
+            ));
+
+            if force != Some(true) {
+                if let Some(dir) = app_data_dir {
+                    if let Some(mut cached) =
+                        cache::read_cache_async::<SplitPlanResponse>(dir, "cache/split", &cache_key)
+                            .await
+                    {
+                        cached.from_cache = true;
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (temp_dir, schema_path, output_path) =
+                codex_runner::prepare_temp_dir(&hunks_json, SPLIT_PLAN_SCHEMA, "split.json")?;
+            if let Some(ref json) = analysis_json {
+                std::fs::write(temp_dir.path().join("analysis.json"), json)
+                    .map_err(|e| format!("Failed to write analysis.json: {}", e))?;
+            }
+
+            let prompt =
+                build_split_plan_prompt(hunk_ids.len(), &pr_body, analysis_json.is_some(), &lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens =
+                usage::estimate_tokens(&hunks_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path: temp_dir.path(),
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir,
+                "split",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read split.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: SplitPlanResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse split.json: {}", e))?;
+
+            let (plan, warnings) = validate_split_plan(result, &hunk_ids);
+
+            let mut log = codex_runner::build_log("split", &codex_output);
+            log.push_str(&format!(
+                "[split] hunks={} proposed_prs={}\n",
+                hunk_ids.len(),
+                plan.prs.len()
+            ));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let response = SplitPlanResponse {
+                plan,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(dir) = app_data_dir {
+                cache::write_cache(dir, "cache/split", &cache_key, &response);
+            }
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+/// Analysis pass over public surfaces: flag potentially breaking changes per intent group,
+/// with the hunks involved, validated against the analysis.
+#[allow(clippy::too_many_arguments)]
+pub async fn detect_breaking_changes(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    hunks_json: String,
+    analysis: AnalysisResult,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<BreakingChangesResponse, PrvwError> {
+    let op = Op::start(op_id, "detect_breaking_changes");
+    let result: Result<BreakingChangesResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                repo.as_deref(),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+
+            if analysis.groups.is_empty() {
+                return Err("Analysis has no groups to check.".to_string().into());
+            }
+            let analysis_json = serde_json::to_string(&analysis)
+                .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            let cache_key = cache::hash_key(&format!(
+                "{}\n{}\n{}\n{}",
+                hunks_json, analysis_json, model_str, lang_str
+            ));
+
+            if force != Some(true) {
+                if let Some(dir) = app_data_dir {
+                    if let Some(mut cached) = cache::read_cache_async::<BreakingChangesResponse>(
+                        dir,
+                        "cache/breaking",
+                        &cache_key,
+                    )
+                    .await
+                    {
+                        cached.from_cache = true;
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (temp_dir, schema_path, output_path) =
+                codex_runner::prepare_temp_dir(&hunks_json, BREAKING_SCHEMA, "breaking.json")?;
+            std::fs::write(temp_dir.path().join("analysis.json"), &analysis_json)
+                .map_err(|e| format!("Failed to write analysis.json: {}", e))?;
+
+            let prompt = build_breaking_changes_prompt(&lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens = usage::estimate_tokens(&hunks_json)
+                + usage::estimate_tokens(&analysis_json)
+                + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path: temp_dir.path(),
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir,
+                "breaking",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read breaking.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: BreakingChangesResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse breaking.json: {}", e))?;
+
+            let (findings, warnings) = validate_breaking_changes(result.findings, &analysis.groups);
+
+            let mut log = codex_runner::build_log("breaking", &codex_output);
+            log.push_str(&format!("[breaking] findings={}\n", findings.len()));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let response = BreakingChangesResponse {
+                findings,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(dir) = app_data_dir {
+                cache::write_cache(dir, "cache/breaking", &cache_key, &response);
+            }
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+/// Compare the PR title/description against the analysis and report undisclosed changes,
+/// unfulfilled claims, and contradictions, validated against the analysis groups.
+#[allow(clippy::too_many_arguments)]
+pub async fn check_pr_consistency(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    pr_title: String,
+    pr_body: Option<String>,
+    analysis: AnalysisResult,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<ConsistencyResponse, PrvwError> {
+    let op = Op::start(op_id, "check_pr_consistency");
+    let result: Result<ConsistencyResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                repo.as_deref(),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+
+            if pr_title.trim().is_empty() {
+                return Err(PrvwError::InvalidInput("PR title is empty.".to_string()));
+            }
+            if analysis.groups.is_empty() {
+                return Err("Analysis has no groups to check.".to_string().into());
+            }
+            let analysis_json = serde_json::to_string(&analysis)
+                .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            let cache_key = cache::hash_key(&format!(
+                "{}\n{}\n{}\n{}\n{}",
+                pr_title,
+                pr_body.as_deref().unwrap_or(""),
+                analysis_json,
+                model_str,
+                lang_str
+            ));
+
+            if force != Some(true) {
+                if let Some(dir) = app_data_dir {
+                    if let Some(mut cached) = cache::read_cache_async::<ConsistencyResponse>(
+                        dir,
+                        "cache/consistency",
+                        &cache_key,
+                    )
+                    .await
+                    {
+                        cached.from_cache = true;
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (temp_dir, schema_path, output_path) = codex_runner::prepare_temp_dir(
+                &analysis_json,
+                CONSISTENCY_SCHEMA,
+                "consistency.json",
+            )?;
+            // The input is the analysis, not hunks
+            std::fs::rename(
+                temp_dir.path().join("hunks.json"),
+                temp_dir.path().join("analysis.json"),
+            )
+            .map_err(|e| format!("Failed to rename temp file: {}", e))?;
+
+            let prompt = build_consistency_prompt(&pr_title, &pr_body, &lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens =
+                usage::estimate_tokens(&analysis_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path: temp_dir.path(),
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir,
+                "consistency",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read consistency.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: ConsistencyResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse consistency.json: {}", e))?;
+
+            let (mismatches, warnings) = validate_consistency(result.mismatches, &analysis.groups);
+
+            let mut log = codex_runner::build_log("consistency", &codex_output);
+            log.push_str(&format!("[consistency] mismatches={}\n", mismatches.len()));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let response = ConsistencyResponse {
+                consistent: mismatches.is_empty(),
+                summary: result.summary,
+                mismatches,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(dir) = app_data_dir {
+                cache::write_cache(dir, "cache/consistency", &cache_key, &response);
+            }
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn summarize_hunks(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    hunks_json: String,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<SummarizeResponse, PrvwError> {
+    let op = Op::start(op_id, "summarize_hunks");
+    let result: Result<SummarizeResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                repo.as_deref(),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+
+            let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+                .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+            let valid_ids: HashSet<String> = hunks.iter().map(|h| h.id.clone()).collect();
+
+            if valid_ids.is_empty() {
+                return Err("No hunks to summarize.".to_string().into());
+            }
+
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            let cache_key =
+                cache::hash_key(&format!("{}\n{}\n{}", hunks_json, model_str, lang_str));
+
+            if force != Some(true) {
+                if let Some(dir) = app_data_dir {
+                    if let Some(mut cached) = cache::read_cache_async::<SummarizeResponse>(
+                        dir,
+                        "cache/summary",
+                        &cache_key,
+                    )
+                    .await
+                    {
+                        cached.from_cache = true;
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (temp_dir, schema_path, output_path) =
+                codex_runner::prepare_temp_dir(&hunks_json, SUMMARIZE_SCHEMA, "summaries.json")?;
+
+            let prompt = build_summarize_prompt(valid_ids.len(), &lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens =
+                usage::estimate_tokens(&hunks_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path: temp_dir.path(),
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir,
+                "summarize",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read summaries.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: SummarizeResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse summaries.json: {}", e))?;
+
+            let (summaries, warnings) = validate_summaries(result.summaries, &valid_ids);
+
+            let mut log = codex_runner::build_log("summarize", &codex_output);
+            log.push_str(&format!(
+                "[summarize] hunks={} summaries={}\n",
+                valid_ids.len(),
+                summaries.len()
+            ));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let response = SummarizeResponse {
+                summaries,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(dir) = app_data_dir {
+                cache::write_cache(dir, "cache/summary", &cache_key, &response);
+            }
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn explain_hunk(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    hunk_json: String,
+    file_path: String,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<ExplainResponse, PrvwError> {
+    let op = Op::start(op_id, "explain_hunk");
+    let result: Result<ExplainResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                repo.as_deref(),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            let cache_key = cache::hash_key(&format!("{}\n{}\n{}", hunk_json, model_str, lang_str));
+
+            if force != Some(true) {
+                if let Some(dir) = app_data_dir {
+                    if let Some(mut cached) =
+                        cache::read_cache_async::<ExplainResponse>(dir, "cache/explain", &cache_key)
+                            .await
+                    {
+                        cached.from_cache = true;
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (temp_dir, schema_path, output_path) =
+                codex_runner::prepare_temp_dir(&hunk_json, EXPLAIN_SCHEMA, "explain.json")?;
+
+            // Rename hunks.json → hunk.json for clarity in the prompt
+            let temp_path = temp_dir.path();
+            std::fs::rename(temp_path.join("hunks.json"), temp_path.join("hunk.json"))
+                .map_err(|e| format!("Failed to rename temp file: {}", e))?;
+
+            let prompt = build_explain_prompt(&file_path, &lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens =
+                usage::estimate_tokens(&hunk_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path,
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir,
+                "explain",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read explain.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: ExplainResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse explain.json: {}", e))?;
+
+            let log = codex_runner::build_log("explain", &codex_output);
+
+            let response = ExplainResponse {
+                explanation: result.explanation,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(dir) = app_data_dir {
+                cache::write_cache(dir, "cache/explain", &cache_key, &response);
+            }
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn ask_about_hunk(
+    app_data_dir: Option<&Path>,
+    providers: &ProviderRegistry,
+    hunk_json: String,
+    file_path: String,
+    question: String,
+    context: String,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<ExplainResponse, PrvwError> {
+    let op = Op::start(op_id, "ask_about_hunk");
+    let result: Result<ExplainResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                repo.as_deref(),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+            let (temp_dir, schema_path, output_path) =
+                codex_runner::prepare_temp_dir(&hunk_json, EXPLAIN_SCHEMA, "ask.json")?;
+
+            let temp_path = temp_dir.path();
+            std::fs::rename(temp_path.join("hunks.json"), temp_path.join("hunk.json"))
+                .map_err(|e| format!("Failed to rename temp file: {}", e))?;
+
+            let prompt = build_ask_prompt(&file_path, &question, &context, &lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens =
+                usage::estimate_tokens(&hunk_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path,
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir,
+                "ask",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read ask.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: ExplainResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse ask.json: {}", e))?;
+
+            let log = codex_runner::build_log("ask", &codex_output);
+
+            Ok(ExplainResponse {
+                explanation: result.explanation,
+                codex_log: log,
+                from_cache: false,
+            })
+        })
+        .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analysis_prompt_includes_hunk_count() {
+        let prompt = build_analysis_prompt(5, &None, false, &None, "", "", &None);
+        assert!(prompt.contains("5 hunks"));
+    }
+
+    #[test]
+    fn analysis_prompt_includes_risky_patterns() {
+        let summary = "- H2 [eval] src/a.py:3: `eval(expr)`";
+        let prompt = build_analysis_prompt(1, &None, false, &None, "", summary, &None);
+        assert!(prompt.contains(summary));
+        assert!(prompt.contains("riskEvidence"));
+        assert!(
+            !build_analysis_prompt(1, &None, false, &None, "", "", &None)
+                .contains("deterministic scan")
+        );
+    }
+
+    #[test]
+    fn analysis_prompt_no_pr_body() {
+        let prompt = build_analysis_prompt(1, &None, false, &None, "", "", &None);
+        assert!(!prompt.contains("PR description"));
+    }
+
+    #[test]
+    fn analysis_prompt_with_pr_body() {
+        let body = Some("Fix login bug".to_string());
+        let prompt = build_analysis_prompt(1, &body, false, &None, "", "", &None);
+        assert!(prompt.contains("Fix login bug"));
+        assert!(prompt.contains("PR description"));
+    }
+
+    #[test]
+    fn analysis_prompt_truncates_long_body() {
+        let body = Some("x".repeat(3000));
+        let prompt = build_analysis_prompt(1, &body, false, &None, "", "", &None);
+        // The body in the prompt should be truncated to ~2000 chars
+        let base_len = build_analysis_prompt(1, &None, false, &None, "", "", &None).len();
+        assert!(prompt.len() < base_len + 2100);
+        assert!(prompt.contains("PR description"));
+    }
+
+    #[test]
+    fn analysis_prompt_with_lang() {
+        let prompt = build_analysis_prompt(
+            1,
+            &None,
+            false,
+            &None,
+            "",
+            "",
+            &Some("Japanese".to_string()),
+        );
+        assert!(prompt.contains("Respond in Japanese."));
+    }
+
+    #[test]
+    fn analysis_prompt_mentions_summaries_when_present() {
+        assert!(
+            build_analysis_prompt(1, &None, true, &None, "", "", &None).contains("summaries.json")
+        );
+        assert!(
+            !build_analysis_prompt(1, &None, false, &None, "", "", &None)
+                .contains("summaries.json")
+        );
+    }
+
+    #[test]
+    fn repair_prompt_lists_problems_and_caps_them() {
+        let warnings: Vec<String> = (0..40).map(|i| format!("problem {}", i)).collect();
+        let prompt = build_repair_prompt("Base prompt.", &warnings);
+        assert!(prompt.starts_with("Base prompt."));
+        assert!(prompt.contains("previous.json"));
+        assert!(prompt.contains("- problem 0\n"));
+        assert!(prompt.contains("- problem 29\n"));
+        assert!(!prompt.contains("problem 30"));
+    }
+
+    #[test]
+    fn summarize_prompt_includes_hunk_count_and_lang() {
+        let prompt = build_summarize_prompt(7, &Some("Japanese".to_string()));
+        assert!(prompt.contains("7 hunks"));
+        assert!(prompt.contains("Respond in Japanese."));
+    }
+
+    #[test]
+    fn refine_prompt_contains_group_info() {
+        let prompt = build_refine_prompt("Auth changes", "G1", &None, &None);
+        assert!(prompt.contains("Auth changes"));
+        assert!(prompt.contains("G1.1"));
+        assert!(prompt.contains("G1.2"));
+    }
+
+    #[test]
+    fn prompts_include_guidelines() {
+        let guidelines = Some("## Style\n- Every public fn needs a doc comment".to_string());
+        let prompt = build_analysis_prompt(1, &None, false, &guidelines, "", "", &None);
+        assert!(prompt.contains("Every public fn needs a doc comment"));
+        assert!(prompt.contains("reviewerChecklist"));
+        let refine = build_refine_prompt("Title", "G1", &guidelines, &None);
+        assert!(refine.contains("review guidelines"));
+        assert!(
+            !build_analysis_prompt(1, &None, false, &None, "", "", &None)
+                .contains("review guidelines")
+        );
+    }
+
+    #[test]
+    fn analysis_prompt_includes_dependency_summary() {
+        let summary = "Cargo.lock: bumped serde 1.0.1 -> 1.0.2";
+        assert!(
+            build_analysis_prompt(1, &None, false, &None, summary, "", &None).contains(summary)
+        );
+        assert!(
+            !build_analysis_prompt(1, &None, false, &None, "", "", &None)
+                .contains("Dependency changes")
+        );
+    }
+
+    #[test]
+    fn strip_lockfile_lines_keeps_ids() {
+        use crate::types::DiffLine;
+        let hunk = |id: &str, path: &str| Hunk {
+            id: id.to_string(),
+            file_path: path.to_string(),
+            subproject: None,
+            header: String::new(),
+            old_start: 1,
+            old_lines: 1,
+            new_start: 1,
+            new_lines: 1,
+            lines: vec![DiffLine {
+                kind: "add".to_string(),
+                old_line: None,
+                new_line: Some(1),
+                text: "x".to_string(),
+            }],
+        };
+        let hunks = vec![hunk("H1", "src/main.rs"), hunk("H2", "Cargo.lock")];
+        let json = serde_json::to_string(&hunks).expect("serialize");
+        let stripped: Vec<Hunk> =
+            serde_json::from_str(&strip_lockfile_lines(&json, &hunks).expect("strip"))
+                .expect("parse");
+        assert_eq!(stripped.len(), 2);
+        assert_eq!(stripped[0].lines.len(), 1);
+        assert!(stripped[1].lines.is_empty());
+        assert_eq!(
+            strip_lockfile_lines(&json, &hunks[..1]).expect("strip"),
+            json
+        );
+    }
+
+    #[test]
+    fn prompts_request_risk_evidence() {
+        assert!(
+            build_analysis_prompt(1, &None, false, &None, "", "", &None).contains("riskEvidence")
+        );
+        assert!(build_refine_prompt("Title", "G1", &None, &None).contains("riskEvidence"));
+    }
+
+    #[test]
+    fn draft_comments_prompt_contains_group_title() {
+        let prompt = build_draft_comments_prompt("Auth changes", &None);
+        assert!(prompt.contains("Auth changes"));
+        assert!(prompt.contains("RIGHT"));
+    }
+
+    #[test]
+    fn split_plan_prompt_mentions_analysis_only_when_given() {
+        let body = Some("Big refactor".to_string());
+        let prompt = build_split_plan_prompt(12, &body, true, &None);
+        assert!(prompt.contains("12 hunks"));
+        assert!(prompt.contains("Big refactor"));
+        assert!(prompt.contains("analysis.json"));
+        assert!(!build_split_plan_prompt(12, &None, false, &None).contains("analysis.json"));
+    }
+
+    #[test]
+    fn breaking_changes_prompt_covers_public_surfaces() {
+        let prompt = build_breaking_changes_prompt(&Some("German".to_string()));
+        assert!(prompt.contains("analysis.json"));
+        assert!(prompt.contains("possibly-breaking"));
+        assert!(prompt.contains("Respond in German."));
+    }
+
+    #[test]
+    fn consistency_prompt_quotes_title_and_body() {
+        let body = Some("Fixes a typo in the README.".to_string());
+        let prompt = build_consistency_prompt("Fix typo", &body, &None);
+        assert!(prompt.contains("\"Fix typo\""));
+        assert!(prompt.contains("Fixes a typo in the README."));
+        assert!(prompt.contains("undisclosed"));
+        assert!(build_consistency_prompt("Fix typo", &None, &None).contains("no description"));
+    }
+
+    #[test]
+    fn refine_prompt_with_lang() {
+        let prompt = build_refine_prompt("Title", "G1", &None, &Some("Spanish".to_string()));
+        assert!(prompt.contains("Respond in Spanish."));
+    }
+
+    #[test]
+    fn guided_refine_prompt_quotes_guidance() {
+        let prompt = build_guided_refine_prompt(
+            "Auth changes",
+            "G2",
+            "  split out the migration ",
+            &None,
+            &None,
+        );
+        assert!(prompt.contains("\"split out the migration\""));
+        assert!(prompt.contains("G2.1"));
+        assert!(prompt.contains("exactly one sub-group"));
+    }
+
+    #[test]
+    fn merge_sub_groups_keeps_group_position() {
+        let group = |id: &str| IntentGroup {
+            id: id.to_string(),
+            title: id.to_string(),
+            category: "logic".to_string(),
+            rationale: String::new(),
+            risk: "low".to_string(),
+            risk_score: 0,
+            risk_evidence: vec![],
+            hunk_ids: vec![],
+            reviewer_checklist: vec![],
+            suggested_tests: vec![],
+        };
+        let mut analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![group("G1"), group("G2"), group("G3")],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        };
+        merge_sub_groups(&mut analysis, "G2", &[group("G2.1"), group("G2.2")]);
+        let ids: Vec<&str> = analysis.groups.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids, vec!["G1", "G2.1", "G2.2", "G3"]);
+    }
+}
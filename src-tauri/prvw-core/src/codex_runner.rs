@@ -0,0 +1,544 @@
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+pub use crate::provider::{is_rate_limited, CodexOutput, RunError};
+
+use crate::i18n::{t, Msg};
+use crate::operations;
+use crate::provider::{AnalysisProvider, Capabilities, Job};
+use crate::types::RunOptions;
+use crate::usage;
+
+/// Default upper bound for a single Codex run when the caller does not specify one.
+pub const DEFAULT_TIMEOUT_MINUTES: u64 = 10;
+/// Longest timeout a setting can ask for; larger values are clamped to it.
+pub const MAX_TIMEOUT_MINUTES: u64 = 24 * 60;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Values accepted by Codex for `model_reasoning_effort` and `approval_policy`.
+const REASONING_EFFORTS: &[&str] = &["minimal", "low", "medium", "high"];
+const APPROVAL_MODES: &[&str] = &["untrusted", "on-failure", "on-request", "never"];
+
+fn codex_env() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("GH_PAGER", "cat"),
+        ("PAGER", "cat"),
+        ("NO_COLOR", "1"),
+        ("GH_FORCE_TTY", "0"),
+    ]
+}
+
+pub fn lang_suffix(lang: &Option<String>) -> String {
+    match lang.as_deref() {
+        Some(l) if !l.trim().is_empty() => format!(" Respond in {}.", l.trim()),
+        _ => String::new(),
+    }
+}
+
+/// Resolve the user-facing timeout setting (in minutes) into a `Duration`.
+/// `None` or `0` falls back to `DEFAULT_TIMEOUT_MINUTES`; values above `MAX_TIMEOUT_MINUTES`
+/// are clamped.
+pub fn timeout_from_minutes(minutes: Option<u64>) -> Duration {
+    let minutes = match minutes {
+        Some(m) if m > 0 => m,
+        _ => DEFAULT_TIMEOUT_MINUTES,
+    };
+    Duration::from_secs(minutes.min(MAX_TIMEOUT_MINUTES) * 60)
+}
+
+/// Build CLI arguments for Codex exec, write input files, and return args vector.
+pub fn build_args(
+    temp_path: &std::path::Path,
+    schema_path: &str,
+    output_path: &str,
+    model: &Option<String>,
+    options: &RunOptions,
+    prompt: String,
+) -> Result<Vec<String>, String> {
+    let approval_mode = checked_option(&options.approval_mode, APPROVAL_MODES, "approval mode")?;
+    let mut args = vec![
+        "exec".to_string(),
+        "-C".to_string(),
+        temp_path
+            .to_str()
+            .ok_or_else(|| "Non-UTF-8 temp path".to_string())?
+            .to_string(),
+        "--skip-git-repo-check".to_string(),
+        "--sandbox".to_string(),
+        "read-only".to_string(),
+        "--color".to_string(),
+        "never".to_string(),
+        "--output-schema".to_string(),
+        schema_path.to_string(),
+        "-o".to_string(),
+        output_path.to_string(),
+    ];
+
+    match approval_mode {
+        Some(mode) => {
+            args.push("-c".to_string());
+            args.push(format!("approval_policy=\"{}\"", mode));
+        }
+        None => args.push("--full-auto".to_string()),
+    }
+
+    if let Some(m) = model {
+        if !m.trim().is_empty() {
+            args.push("-m".to_string());
+            args.push(m.trim().to_string());
+        }
+    }
+
+    if let Some(profile) = options.profile.as_deref().map(str::trim) {
+        if !profile.is_empty() {
+            if !profile
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+            {
+                return Err(format!("Invalid Codex profile name: {}", profile));
+            }
+            args.push("--profile".to_string());
+            args.push(profile.to_string());
+        }
+    }
+
+    if let Some(effort) = checked_option(
+        &options.reasoning_effort,
+        REASONING_EFFORTS,
+        "reasoning effort",
+    )? {
+        args.push("-c".to_string());
+        args.push(format!("model_reasoning_effort=\"{}\"", effort));
+    }
+
+    args.push(prompt);
+    Ok(args)
+}
+
+/// Trim an optional setting and check it against the values Codex accepts; empty means unset.
+fn checked_option<'a>(
+    value: &'a Option<String>,
+    allowed: &[&str],
+    what: &str,
+) -> Result<Option<&'a str>, String> {
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(v) if allowed.contains(&v) => Ok(Some(v)),
+        Some(v) => Err(format!(
+            "Invalid {} '{}'. Expected one of: {}.",
+            what,
+            v,
+            allowed.join(", ")
+        )),
+    }
+}
+
+/// Drain a child pipe on a background thread so the child never blocks on a full pipe.
+fn spawn_reader<R: Read + Send + 'static>(source: Option<R>) -> JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut r) = source {
+            let _ = r.read_to_end(&mut buf);
+        }
+        String::from_utf8_lossy(&buf).to_string()
+    })
+}
+
+/// Raw result of a finished CLI subprocess.
+pub struct ProcessOutput {
+    pub status: std::process::ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+    pub elapsed_secs: f64,
+}
+
+/// Spawn `cmd` with piped output and wait for it, killing it if it runs longer than `timeout`.
+/// `program` names the CLI in errors; `install_hint` is returned when the binary is missing.
+pub fn run_process(
+    cmd: &mut Command,
+    program: &'static str,
+    install_hint: &str,
+    timeout: Duration,
+) -> Result<ProcessOutput, RunError> {
+    let start = Instant::now();
+    let mut child = operations::spawn(
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )
+    .map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            RunError::NotInstalled {
+                program,
+                hint: install_hint.to_string(),
+            }
+        } else if e.kind() == std::io::ErrorKind::Interrupted {
+            RunError::Cancelled { program }
+        } else {
+            RunError::unknown(format!("Failed to execute {}: {}", program, e))
+        }
+    })?;
+
+    let stdout_reader = spawn_reader(child.stdout.take());
+    let stderr_reader = spawn_reader(child.stderr.take());
+
+    // Returning early drops `child`, which kills it with everything it started.
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if start.elapsed() >= timeout => {
+                return Err(RunError::Timeout {
+                    program,
+                    minutes: timeout.as_secs() / 60,
+                });
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                return Err(RunError::unknown(format!(
+                    "Failed to wait for {}: {}",
+                    program, e
+                )));
+            }
+        }
+    };
+    drop(child);
+    if operations::is_cancelled() {
+        return Err(RunError::Cancelled { program });
+    }
+
+    Ok(ProcessOutput {
+        status,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+        elapsed_secs: start.elapsed().as_secs_f64(),
+    })
+}
+
+/// Run Codex CLI with the given args and return captured output.
+/// The child is killed if it runs longer than `timeout`.
+pub fn run(args: &[String], timeout: Duration) -> Result<CodexOutput, RunError> {
+    let model_used = args
+        .windows(2)
+        .find(|w| w[0] == "-m")
+        .map(|w| w[1].clone())
+        .unwrap_or_else(|| "(config default)".to_string());
+
+    let mut cmd = Command::new("codex");
+    cmd.args(args).envs(codex_env());
+    let output = run_process(&mut cmd, "codex", &t(Msg::CodexNotInstalled, &[]), timeout)?;
+
+    if !output.status.success() {
+        let stderr = &output.stderr;
+        if is_rate_limited(stderr) {
+            return Err(RunError::RateLimited {
+                program: "codex",
+                detail: stderr.trim().to_string(),
+            });
+        }
+        if stderr.contains("login") || stderr.contains("auth") || stderr.contains("API key") {
+            return Err(RunError::NotAuthenticated {
+                program: "codex",
+                hint: t(Msg::CodexNotAuthenticated, &[]),
+            });
+        }
+        return Err(RunError::Unknown {
+            stderr: format!("Codex exec failed: {}", stderr),
+        });
+    }
+    let usage = usage::parse_codex_usage(&output.stderr)
+        .or_else(|| usage::parse_codex_usage(&output.stdout));
+    Ok(CodexOutput {
+        stdout: output.stdout,
+        stderr: output.stderr,
+        elapsed_secs: output.elapsed_secs,
+        model_used,
+        usage,
+        args: args.to_vec(),
+        run_id: None,
+    })
+}
+
+/// Codex CLI backend: reads the temp dir itself and enforces the schema via `--output-schema`.
+pub struct CodexProvider;
+
+impl AnalysisProvider for CodexProvider {
+    fn name(&self) -> &'static str {
+        "codex"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            reads_files: true,
+            native_schema: true,
+            cli: Some("codex"),
+        }
+    }
+
+    fn run(&self, job: &Job) -> Result<(CodexOutput, String), RunError> {
+        let path_str = |path: &Path, what: &str| {
+            path.to_str()
+                .map(str::to_string)
+                .ok_or_else(|| RunError::unknown(format!("Non-UTF-8 {} path", what)))
+        };
+        let args = build_args(
+            job.repo_dir.unwrap_or(job.temp_path),
+            &path_str(job.schema_path, "schema")?,
+            &path_str(job.output_path, "output")?,
+            job.model,
+            job.options,
+            job.prompt.clone(),
+        )
+        .map_err(RunError::unknown)?;
+        let output = run(&args, job.timeout)?;
+        let raw = std::fs::read_to_string(job.output_path).map_err(|e| RunError::EmptyOutput {
+            program: "codex",
+            detail: e.to_string(),
+        })?;
+        Ok((output, raw))
+    }
+}
+
+/// Build a structured log string from Codex output.
+pub fn build_log(label: &str, output: &CodexOutput) -> String {
+    let mut log = format!(
+        "[{}] model={} elapsed={:.1}s\n",
+        label, output.model_used, output.elapsed_secs
+    );
+    if !output.stderr.is_empty() {
+        log.push_str(&output.stderr);
+        log.push('\n');
+    }
+    if !output.stdout.is_empty() {
+        log.push_str(&output.stdout);
+        log.push('\n');
+    }
+    log
+}
+
+/// Prepare a temp directory with hunks.json and schema.json, returning
+/// (temp_dir, schema_path, output_path) for the caller to use.
+pub fn prepare_temp_dir(
+    hunks_json: &str,
+    schema_content: &str,
+    output_filename: &str,
+) -> Result<(tempfile::TempDir, std::path::PathBuf, std::path::PathBuf), String> {
+    let temp_dir =
+        tempfile::tempdir().map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let temp_path = temp_dir.path();
+
+    std::fs::write(temp_path.join("hunks.json"), hunks_json)
+        .map_err(|e| format!("Failed to write hunks.json: {}", e))?;
+
+    let schema_path = temp_path.join("schema.json");
+    std::fs::write(&schema_path, schema_content)
+        .map_err(|e| format!("Failed to write schema.json: {}", e))?;
+
+    let output_path = temp_path.join(output_filename);
+
+    Ok((temp_dir, schema_path, output_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lang_suffix_none() {
+        assert_eq!(lang_suffix(&None), "");
+    }
+
+    #[test]
+    fn lang_suffix_empty_string() {
+        assert_eq!(lang_suffix(&Some(String::new())), "");
+    }
+
+    #[test]
+    fn lang_suffix_whitespace_only() {
+        assert_eq!(lang_suffix(&Some("   ".to_string())), "");
+    }
+
+    #[test]
+    fn lang_suffix_japanese() {
+        assert_eq!(
+            lang_suffix(&Some("Japanese".to_string())),
+            " Respond in Japanese."
+        );
+    }
+
+    #[test]
+    fn lang_suffix_trims_whitespace() {
+        assert_eq!(
+            lang_suffix(&Some("  English  ".to_string())),
+            " Respond in English."
+        );
+    }
+
+    #[test]
+    fn timeout_from_minutes_default() {
+        let default = Duration::from_secs(DEFAULT_TIMEOUT_MINUTES * 60);
+        assert_eq!(timeout_from_minutes(None), default);
+        assert_eq!(timeout_from_minutes(Some(0)), default);
+    }
+
+    #[test]
+    fn timeout_from_minutes_custom() {
+        assert_eq!(timeout_from_minutes(Some(3)), Duration::from_secs(180));
+    }
+
+    #[test]
+    fn timeout_from_minutes_clamps() {
+        assert_eq!(
+            timeout_from_minutes(Some(u64::MAX)),
+            Duration::from_secs(MAX_TIMEOUT_MINUTES * 60)
+        );
+    }
+
+    #[test]
+    fn build_args_without_model() {
+        let tmp = tempfile::tempdir().unwrap();
+        let args = build_args(
+            tmp.path(),
+            "/schema.json",
+            "/output.json",
+            &None,
+            &RunOptions::default(),
+            "prompt text".to_string(),
+        )
+        .unwrap();
+        assert!(args.contains(&"exec".to_string()));
+        assert!(args.contains(&"--full-auto".to_string()));
+        assert!(args.contains(&"--sandbox".to_string()));
+        assert!(args.contains(&"read-only".to_string()));
+        assert!(args.contains(&"--output-schema".to_string()));
+        assert!(!args.contains(&"-m".to_string()));
+        // Last arg is the prompt
+        assert_eq!(args.last().unwrap(), "prompt text");
+    }
+
+    #[test]
+    fn build_args_with_model() {
+        let tmp = tempfile::tempdir().unwrap();
+        let args = build_args(
+            tmp.path(),
+            "/schema.json",
+            "/output.json",
+            &Some("gpt-4".to_string()),
+            &RunOptions::default(),
+            "prompt".to_string(),
+        )
+        .unwrap();
+        let m_pos = args.iter().position(|a| a == "-m").unwrap();
+        assert_eq!(args[m_pos + 1], "gpt-4");
+    }
+
+    #[test]
+    fn build_args_empty_model_ignored() {
+        let tmp = tempfile::tempdir().unwrap();
+        let args = build_args(
+            tmp.path(),
+            "/schema.json",
+            "/output.json",
+            &Some("  ".to_string()),
+            &RunOptions::default(),
+            "prompt".to_string(),
+        )
+        .unwrap();
+        assert!(!args.contains(&"-m".to_string()));
+    }
+
+    #[test]
+    fn build_args_pass_through_options() {
+        let tmp = tempfile::tempdir().unwrap();
+        let options = RunOptions {
+            profile: Some("deep".to_string()),
+            reasoning_effort: Some("high".to_string()),
+            approval_mode: Some("never".to_string()),
+        };
+        let args = build_args(
+            tmp.path(),
+            "/schema.json",
+            "/output.json",
+            &None,
+            &options,
+            "prompt".to_string(),
+        )
+        .unwrap();
+        let p = args.iter().position(|a| a == "--profile").unwrap();
+        assert_eq!(args[p + 1], "deep");
+        assert!(args.contains(&"model_reasoning_effort=\"high\"".to_string()));
+        assert!(args.contains(&"approval_policy=\"never\"".to_string()));
+        assert!(!args.contains(&"--full-auto".to_string()));
+        assert_eq!(args.last().unwrap(), "prompt");
+    }
+
+    #[test]
+    fn build_args_rejects_invalid_options() {
+        let tmp = tempfile::tempdir().unwrap();
+        let bad_effort = RunOptions {
+            reasoning_effort: Some("extreme".to_string()),
+            ..RunOptions::default()
+        };
+        let err =
+            build_args(tmp.path(), "/s", "/o", &None, &bad_effort, "p".to_string()).unwrap_err();
+        assert!(err.contains("reasoning effort 'extreme'"));
+        let bad_profile = RunOptions {
+            profile: Some("a b".to_string()),
+            ..RunOptions::default()
+        };
+        assert!(build_args(tmp.path(), "/s", "/o", &None, &bad_profile, "p".to_string()).is_err());
+    }
+
+    #[test]
+    fn build_log_with_stderr_and_stdout() {
+        let output = CodexOutput {
+            stdout: "stdout text".to_string(),
+            stderr: "stderr text".to_string(),
+            elapsed_secs: 1.5,
+            model_used: "gpt-4".to_string(),
+            usage: None,
+            args: vec![],
+            run_id: None,
+        };
+        let log = build_log("test", &output);
+        assert!(log.contains("[test]"));
+        assert!(log.contains("model=gpt-4"));
+        assert!(log.contains("1.5s"));
+        assert!(log.contains("stderr text"));
+        assert!(log.contains("stdout text"));
+    }
+
+    #[test]
+    fn build_log_empty_stderr_omitted() {
+        let output = CodexOutput {
+            stdout: "out".to_string(),
+            stderr: String::new(),
+            elapsed_secs: 0.0,
+            model_used: "m".to_string(),
+            usage: None,
+            args: vec![],
+            run_id: None,
+        };
+        let log = build_log("x", &output);
+        // Should have header + stdout, no extra empty stderr section
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines[0], "[x] model=m elapsed=0.0s");
+        assert_eq!(lines[1], "out");
+    }
+
+    #[test]
+    fn prepare_temp_dir_creates_files() {
+        let (temp_dir, schema_path, output_path) =
+            prepare_temp_dir("{}", "{\"type\":\"object\"}", "out.json").unwrap();
+        let temp_path = temp_dir.path();
+        assert!(temp_path.join("hunks.json").exists());
+        assert!(schema_path.exists());
+        // output_path should not exist yet (codex writes it)
+        assert!(!output_path.exists());
+        let hunks = std::fs::read_to_string(temp_path.join("hunks.json")).unwrap();
+        assert_eq!(hunks, "{}");
+    }
+}
@@ -0,0 +1,356 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+use serde::Deserialize;
+
+use crate::settings;
+use crate::time::now_secs;
+use crate::types::{AppSettings, ConfigStatus, RunOptions, SettingsUpdate};
+
+/// `prvw.toml` in the app config dir, for settings kept in dotfiles rather than in Settings.
+const CONFIG_FILE: &str = "prvw.toml";
+/// Per-repo overrides at the root of the repo's clone (its `clonePath` repo setting).
+const REPO_CONFIG_FILE: &str = ".prvw.toml";
+/// How often the config file is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Keys of a config file; unset keys leave the Settings values alone. Per-repo files only
+/// apply `model`, `lang`, `ignore_globs` and `prompts`.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    model: Option<String>,
+    lang: Option<String>,
+    ui_lang: Option<String>,
+    provider: Option<String>,
+    timeout_minutes: Option<u64>,
+    ignore_globs: Option<Vec<String>>,
+    gh_host: Option<String>,
+    gitea_url: Option<String>,
+    azure_devops_url: Option<String>,
+    editor_command: Option<String>,
+    codex: Option<CodexConfig>,
+    /// Extra instructions appended to the prompt of a kind of run ("analysis", "refine").
+    prompts: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct CodexConfig {
+    profile: Option<String>,
+    reasoning_effort: Option<String>,
+    approval_mode: Option<String>,
+}
+
+impl FileConfig {
+    fn update(&self) -> SettingsUpdate {
+        SettingsUpdate {
+            model: self.model.clone(),
+            lang: self.lang.clone(),
+            ui_lang: self.ui_lang.clone(),
+            provider: self.provider.clone(),
+            timeout_minutes: self.timeout_minutes,
+            ignore_globs: self.ignore_globs.clone(),
+            gh_host: self.gh_host.clone(),
+            gitea_url: self.gitea_url.clone(),
+            azure_devops_url: self.azure_devops_url.clone(),
+            editor_command: self.editor_command.clone(),
+            ..SettingsUpdate::default()
+        }
+    }
+
+    /// Set the codex options the file sets, keeping the others.
+    fn apply_codex(&self, options: &mut RunOptions) {
+        let Some(codex) = &self.codex else {
+            return;
+        };
+        for (value, option) in [
+            (&codex.profile, &mut options.profile),
+            (&codex.reasoning_effort, &mut options.reasoning_effort),
+            (&codex.approval_mode, &mut options.approval_mode),
+        ] {
+            if let Some(value) = value {
+                *option = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+            }
+        }
+    }
+
+    /// Settings keys the file sets, as in `ConfigStatus::overrides`.
+    fn overrides(&self) -> Vec<String> {
+        let mut keys: Vec<String> = [
+            ("model", self.model.is_some()),
+            ("lang", self.lang.is_some()),
+            ("uiLang", self.ui_lang.is_some()),
+            ("provider", self.provider.is_some()),
+            ("timeoutMinutes", self.timeout_minutes.is_some()),
+            ("ignoreGlobs", self.ignore_globs.is_some()),
+            ("ghHost", self.gh_host.is_some()),
+            ("giteaUrl", self.gitea_url.is_some()),
+            ("azureDevopsUrl", self.azure_devops_url.is_some()),
+            ("editorCommand", self.editor_command.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(key, _)| key.to_string())
+        .collect();
+        if let Some(codex) = &self.codex {
+            for (key, value) in [
+                ("runOptions.profile", &codex.profile),
+                ("runOptions.reasoningEffort", &codex.reasoning_effort),
+                ("runOptions.approvalMode", &codex.approval_mode),
+            ] {
+                if value.is_some() {
+                    keys.push(key.to_string());
+                }
+            }
+        }
+        keys
+    }
+
+    /// The subset a per-repo file may set.
+    fn repo_update(&self) -> SettingsUpdate {
+        SettingsUpdate {
+            model: self.model.clone(),
+            lang: self.lang.clone(),
+            ignore_globs: self.ignore_globs.clone(),
+            ..SettingsUpdate::default()
+        }
+    }
+}
+
+/// The config file as last read; a file that fails to load keeps the previous config.
+#[derive(Default)]
+struct Loaded {
+    config: FileConfig,
+    status: ConfigStatus,
+    modified: Option<SystemTime>,
+}
+
+static CONFIG: RwLock<Option<Loaded>> = RwLock::new(None);
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Parse and check a config file: the values must pass the same validation as Settings.
+fn parse(text: &str) -> Result<FileConfig, String> {
+    let config: FileConfig = toml::from_str(text).map_err(|e| e.to_string())?;
+    settings::apply_update(&mut AppSettings::default(), config.update())?;
+    Ok(config)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Read the config file into `CONFIG` if it changed since the last read. Returns the new status
+/// when it did.
+fn reload(path: &Path) -> Option<ConfigStatus> {
+    let mtime = modified(path);
+    let mut guard = CONFIG.write().ok()?;
+    let loaded = guard.get_or_insert_with(Loaded::default);
+    if loaded.status.loaded_at > 0 && loaded.modified == mtime {
+        return None;
+    }
+    loaded.modified = mtime;
+    loaded.status = ConfigStatus {
+        path: path.to_string_lossy().into_owned(),
+        exists: mtime.is_some(),
+        error: None,
+        loaded_at: now_secs().max(1),
+        overrides: Vec::new(),
+    };
+    match fs::read_to_string(path) {
+        Ok(text) => match parse(&text) {
+            Ok(config) => loaded.config = config,
+            Err(e) => {
+                eprintln!("[config] {}: {}", path.display(), e);
+                loaded.status.error = Some(e);
+            }
+        },
+        // No file is the same as an empty one.
+        Err(_) => loaded.config = FileConfig::default(),
+    }
+    loaded.status.overrides = loaded.config.overrides();
+    Some(loaded.status.clone())
+}
+
+/// Load `prvw.toml` from `config_dir` once, without watching it (for the CLI).
+pub fn load(config_dir: &Path) -> PathBuf {
+    let path = config_dir.join(CONFIG_FILE);
+    let _ = CONFIG_PATH.set(path.clone());
+    reload(&path);
+    path
+}
+
+/// Check `path` (from `load`) for changes in the background, passing the new status to
+/// `on_reload` whenever it reloads.
+pub fn watch(path: PathBuf, on_reload: impl Fn(&ConfigStatus) + Send + 'static) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(WATCH_INTERVAL);
+        if let Some(status) = reload(&path) {
+            on_reload(&status);
+        }
+    });
+}
+
+/// Where `prvw.toml` is read from, and whether it loaded.
+pub fn status() -> ConfigStatus {
+    let status = CONFIG
+        .read()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|l| l.status.clone()));
+    status.unwrap_or_else(|| ConfigStatus {
+        path: CONFIG_PATH
+            .get()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        ..ConfigStatus::default()
+    })
+}
+
+/// Apply `prvw.toml` on top of the saved settings. Its values were validated when it loaded.
+pub fn apply(settings: &mut AppSettings) {
+    let Ok(guard) = CONFIG.read() else {
+        return;
+    };
+    let Some(loaded) = guard.as_ref() else {
+        return;
+    };
+    let _ = settings::apply_update(settings, loaded.config.update());
+    loaded.config.apply_codex(&mut settings.run_options);
+    settings.prompts.extend(loaded.config.prompts.clone());
+}
+
+/// Settings keys the loaded `prvw.toml` sets, as in `ConfigStatus::overrides`.
+pub fn overrides() -> Vec<String> {
+    CONFIG
+        .read()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|l| l.status.overrides.clone()))
+        .unwrap_or_default()
+}
+
+/// Apply the `.prvw.toml` at the root of `repo`'s clone, if it has one. It is read on each use,
+/// so edits apply to the next run.
+pub fn apply_repo(settings: &mut AppSettings, repo: &str) {
+    let Some(clone_path) =
+        settings::repo_settings(settings, repo).and_then(|r| r.clone_path.clone())
+    else {
+        return;
+    };
+    let path = Path::new(&clone_path).join(REPO_CONFIG_FILE);
+    let Ok(text) = fs::read_to_string(&path) else {
+        return;
+    };
+    match toml::from_str::<FileConfig>(&text) {
+        Ok(config) => {
+            if let Err(e) = settings::apply_update(settings, config.repo_update()) {
+                eprintln!("[config] {}: {}", path.display(), e);
+            }
+            settings.prompts.extend(config.prompts);
+        }
+        Err(e) => eprintln!("[config] {}: {}", path.display(), e),
+    }
+}
+
+/// `guidelines` with the configured extra instructions for `kind` runs appended, so they reach
+/// the prompt and the cache key alike.
+pub fn with_prompt(
+    guidelines: Option<String>,
+    settings: &AppSettings,
+    kind: &str,
+) -> Option<String> {
+    let Some(extra) = settings
+        .prompts
+        .get(kind)
+        .map(|p| p.as_str().trim())
+        .filter(|p| !p.is_empty())
+    else {
+        return guidelines;
+    };
+    Some(match guidelines.as_deref().map(str::trim) {
+        Some(g) if !g.is_empty() => format!("{}\n\nAdditional instructions:\n{}", g, extra),
+        _ => format!("Additional instructions:\n{}", extra),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_applies_a_config() {
+        let config = parse(
+            r#"
+model = "gpt-5"
+ignore_globs = ["*.lock", " "]
+gh_host = "github.example.com"
+
+[codex]
+reasoning_effort = "high"
+
+[prompts]
+analysis = "Group migrations separately."
+"#,
+        )
+        .unwrap();
+        let mut settings = AppSettings {
+            lang: Some("ja".to_string()),
+            run_options: RunOptions {
+                profile: Some("work".to_string()),
+                ..RunOptions::default()
+            },
+            ..AppSettings::default()
+        };
+        settings::apply_update(&mut settings, config.update()).unwrap();
+        config.apply_codex(&mut settings.run_options);
+        assert_eq!(settings.model.as_deref(), Some("gpt-5"));
+        assert_eq!(settings.lang.as_deref(), Some("ja"));
+        assert_eq!(settings.ignore_globs, vec!["*.lock"]);
+        assert_eq!(
+            settings.run_options.reasoning_effort.as_deref(),
+            Some("high")
+        );
+        assert_eq!(settings.run_options.profile.as_deref(), Some("work"));
+        assert_eq!(config.prompts["analysis"], "Group migrations separately.");
+        assert_eq!(
+            config.overrides(),
+            vec![
+                "model",
+                "ignoreGlobs",
+                "ghHost",
+                "runOptions.reasoningEffort"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_invalid_values() {
+        assert!(parse("modle = \"x\"").is_err());
+        assert!(parse("gitea_url = \"codeberg.org\"").is_err());
+        assert!(parse("model = ").is_err());
+        assert_eq!(parse("").unwrap(), FileConfig::default());
+    }
+
+    #[test]
+    fn appends_prompt_extras_to_guidelines() {
+        let mut settings = AppSettings::default();
+        assert_eq!(
+            with_prompt(Some("g".to_string()), &settings, "analysis"),
+            Some("g".to_string())
+        );
+        settings
+            .prompts
+            .insert("analysis".to_string(), " Be terse. ".to_string());
+        assert_eq!(
+            with_prompt(Some("g".to_string()), &settings, "analysis").as_deref(),
+            Some("g\n\nAdditional instructions:\nBe terse.")
+        );
+        assert_eq!(
+            with_prompt(None, &settings, "analysis").as_deref(),
+            Some("Additional instructions:\nBe terse.")
+        );
+        assert_eq!(with_prompt(None, &settings, "refine"), None);
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::types::{AnalysisConstraints, AnalysisResult, IntentGroup};
+
+/// A hunk that must end up in a group: (hunk id, group id).
+pub type Pin = (String, String);
+
+fn risk_rank(risk: &str) -> u8 {
+    match risk {
+        "high" => 2,
+        "medium" => 1,
+        _ => 0,
+    }
+}
+
+/// Move `hunk_id` into group `group_id`, taking it (and its risk evidence) out of every other
+/// group and out of the unassigned list.
+pub fn move_hunk(analysis: &mut AnalysisResult, hunk_id: &str, group_id: &str) {
+    for group in &mut analysis.groups {
+        if group.id != group_id {
+            group.hunk_ids.retain(|h| h != hunk_id);
+            group.risk_evidence.retain(|e| e.hunk_id != hunk_id);
+        }
+    }
+    analysis.unassigned_hunk_ids.retain(|h| h != hunk_id);
+    if let Some(group) = analysis.groups.iter_mut().find(|g| g.id == group_id) {
+        if !group.hunk_ids.iter().any(|h| h == hunk_id) {
+            group.hunk_ids.push(hunk_id.to_string());
+        }
+    }
+}
+
+/// Fold `other` into `target`: hunks, evidence, checklist, and tests are concatenated and the
+/// higher risk wins.
+pub fn merge_into(target: &mut IntentGroup, other: IntentGroup) {
+    if risk_rank(&other.risk) > risk_rank(&target.risk) {
+        target.risk = other.risk;
+    }
+    target.risk_score = target.risk_score.max(other.risk_score);
+    target.hunk_ids.extend(other.hunk_ids);
+    target.risk_evidence.extend(other.risk_evidence);
+    target.reviewer_checklist.extend(other.reviewer_checklist);
+    target.suggested_tests.extend(other.suggested_tests);
+}
+
+/// Apply the reviewer's corrections to `analysis`: merge groups, then move pinned hunks.
+/// Pins to a group merged away follow it to the merged group. Returns the adjusted analysis,
+/// the pins to enforce on the re-run (every hunk of a merged group is pinned to it), and
+/// warnings for constraints that refer to unknown groups or hunks.
+pub fn apply_constraints(
+    analysis: &AnalysisResult,
+    constraints: &AnalysisConstraints,
+    valid_ids: &HashSet<String>,
+) -> (AnalysisResult, Vec<Pin>, Vec<String>) {
+    let mut adjusted = analysis.clone();
+    let mut warnings: Vec<String> = Vec::new();
+    let mut pins: Vec<Pin> = Vec::new();
+    // merged-away group id -> group it was merged into
+    let mut redirects: HashMap<String, String> = HashMap::new();
+
+    for merge in &constraints.merges {
+        let mut known: Vec<&String> = Vec::new();
+        for id in merge {
+            if !adjusted.groups.iter().any(|g| &g.id == id) {
+                warnings.push(format!("Ignored merge of unknown group '{}'", id));
+            } else if !known.contains(&id) {
+                known.push(id);
+            }
+        }
+        let Some((target_id, others)) = known.split_first() else {
+            continue;
+        };
+        if others.is_empty() {
+            continue;
+        }
+        for other_id in others {
+            let Some(pos) = adjusted.groups.iter().position(|g| &g.id == *other_id) else {
+                continue;
+            };
+            let other = adjusted.groups.remove(pos);
+            if let Some(target) = adjusted.groups.iter_mut().find(|g| &g.id == *target_id) {
+                merge_into(target, other);
+            }
+            redirects.insert((*other_id).clone(), (*target_id).clone());
+        }
+        if let Some(target) = adjusted.groups.iter().find(|g| &g.id == *target_id) {
+            pins.extend(
+                target
+                    .hunk_ids
+                    .iter()
+                    .map(|h| (h.clone(), target.id.clone())),
+            );
+        }
+    }
+
+    for pin in &constraints.pins {
+        let group_id = redirects.get(&pin.group_id).unwrap_or(&pin.group_id);
+        if !valid_ids.contains(&pin.hunk_id) {
+            warnings.push(format!("Ignored pin of unknown hunk '{}'", pin.hunk_id));
+            continue;
+        }
+        if !adjusted.groups.iter().any(|g| &g.id == group_id) {
+            warnings.push(format!(
+                "Ignored pin of hunk '{}' to unknown group '{}'",
+                pin.hunk_id, pin.group_id
+            ));
+            continue;
+        }
+        move_hunk(&mut adjusted, &pin.hunk_id, group_id);
+        pins.retain(|(h, _)| *h != pin.hunk_id);
+        pins.push((pin.hunk_id.clone(), group_id.clone()));
+    }
+
+    adjusted.groups.retain(|g| !g.hunk_ids.is_empty());
+    (adjusted, pins, warnings)
+}
+
+/// Make a re-run honor `pins`: pinned hunks the model put elsewhere are moved back, and pinned
+/// groups it dropped are restored from `adjusted`. Returns a warning per correction.
+pub fn enforce_pins(
+    result: &mut AnalysisResult,
+    pins: &[Pin],
+    adjusted: &AnalysisResult,
+) -> Vec<String> {
+    let mut warnings: Vec<String> = Vec::new();
+    for (hunk_id, group_id) in pins {
+        if !result.groups.iter().any(|g| &g.id == group_id) {
+            let Some(original) = adjusted.groups.iter().find(|g| &g.id == group_id) else {
+                continue;
+            };
+            warnings.push(format!("Restored pinned group '{}'", group_id));
+            result.groups.push(IntentGroup {
+                hunk_ids: Vec::new(),
+                risk_evidence: Vec::new(),
+                ..original.clone()
+            });
+        }
+        let in_place = result
+            .groups
+            .iter()
+            .any(|g| &g.id == group_id && g.hunk_ids.contains(hunk_id));
+        if !in_place {
+            warnings.push(format!(
+                "Moved pinned hunk '{}' back to group '{}'",
+                hunk_id, group_id
+            ));
+            move_hunk(result, hunk_id, group_id);
+        }
+    }
+    result.groups.retain(|g| !g.hunk_ids.is_empty());
+    warnings
+}
+
+/// Prompt fragment listing the constraints the re-run must honor.
+pub fn constraints_prompt(adjusted: &AnalysisResult, pins: &[Pin]) -> String {
+    let mut by_group: Vec<(&IntentGroup, Vec<&str>)> = Vec::new();
+    for (hunk_id, group_id) in pins {
+        let Some(group) = adjusted.groups.iter().find(|g| &g.id == group_id) else {
+            continue;
+        };
+        match by_group.iter_mut().find(|(g, _)| g.id == group.id) {
+            Some((_, hunks)) => hunks.push(hunk_id.as_str()),
+            None => by_group.push((group, vec![hunk_id.as_str()])),
+        }
+    }
+    let lines: Vec<String> = by_group
+        .iter()
+        .map(|(g, hunks)| {
+            format!(
+                "- group \"{}\" (\"{}\") must contain hunks {}",
+                g.id,
+                g.title,
+                hunks.join(", ")
+            )
+        })
+        .collect();
+    format!(
+        "\n\nprevious.json contains an earlier grouping of these hunks, corrected by the reviewer. \
+         The reviewer's corrections are fixed constraints you must honor:\n{}\n\
+         Keep these group ids and titles unless the title no longer fits its hunks. \
+         Group the remaining hunks as you see fit; they may join the constrained groups.",
+        lines.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{HunkPin, RiskEvidence};
+
+    fn group(id: &str, risk: &str, hunk_ids: &[&str]) -> IntentGroup {
+        IntentGroup {
+            id: id.to_string(),
+            title: format!("Title {}", id),
+            category: "logic".to_string(),
+            rationale: String::new(),
+            risk: risk.to_string(),
+            risk_score: 0,
+            risk_evidence: hunk_ids
+                .iter()
+                .map(|h| RiskEvidence {
+                    hunk_id: h.to_string(),
+                    reason: "r".to_string(),
+                })
+                .collect(),
+            hunk_ids: hunk_ids.iter().map(ToString::to_string).collect(),
+            reviewer_checklist: vec![],
+            suggested_tests: vec![],
+        }
+    }
+
+    fn analysis(groups: Vec<IntentGroup>) -> AnalysisResult {
+        AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups,
+            unassigned_hunk_ids: vec!["H5".to_string()],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        }
+    }
+
+    fn hunks_of<'a>(analysis: &'a AnalysisResult, id: &str) -> Vec<&'a str> {
+        analysis
+            .groups
+            .iter()
+            .find(|g| g.id == id)
+            .map(|g| g.hunk_ids.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    fn valid() -> HashSet<String> {
+        ["H1", "H2", "H3", "H4", "H5"]
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn merges_groups_and_follows_pins_to_merged_group() {
+        let original = analysis(vec![
+            group("G1", "low", &["H1"]),
+            group("G2", "high", &["H2"]),
+            group("G3", "low", &["H3", "H4"]),
+        ]);
+        let constraints = AnalysisConstraints {
+            pins: vec![
+                HunkPin {
+                    hunk_id: "H3".to_string(),
+                    group_id: "G2".to_string(),
+                },
+                HunkPin {
+                    hunk_id: "H5".to_string(),
+                    group_id: "G9".to_string(),
+                },
+            ],
+            merges: vec![vec!["G1".to_string(), "G2".to_string()]],
+        };
+        let (adjusted, pins, warnings) = apply_constraints(&original, &constraints, &valid());
+        assert_eq!(hunks_of(&adjusted, "G1"), vec!["H1", "H2", "H3"]);
+        assert_eq!(hunks_of(&adjusted, "G3"), vec!["H4"]);
+        assert!(adjusted.groups.iter().all(|g| g.id != "G2"));
+        assert_eq!(adjusted.groups[0].risk, "high");
+        assert_eq!(adjusted.groups[1].risk_evidence.len(), 1);
+        assert_eq!(pins.len(), 3);
+        assert!(pins.iter().all(|(_, g)| g == "G1"));
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn enforce_moves_pinned_hunks_and_restores_groups() {
+        let adjusted = analysis(vec![
+            group("G1", "low", &["H1", "H2"]),
+            group("G3", "low", &["H3"]),
+        ]);
+        let pins = vec![
+            ("H1".to_string(), "G1".to_string()),
+            ("H2".to_string(), "G1".to_string()),
+        ];
+        let mut result = analysis(vec![
+            group("N1", "low", &["H1", "H3"]),
+            group("N2", "low", &["H2"]),
+        ]);
+        let warnings = enforce_pins(&mut result, &pins, &adjusted);
+        assert_eq!(hunks_of(&result, "G1"), vec!["H1", "H2"]);
+        assert_eq!(hunks_of(&result, "N1"), vec!["H3"]);
+        assert!(result.groups.iter().all(|g| g.id != "N2"));
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[test]
+    fn constraints_prompt_lists_pins_per_group() {
+        let adjusted = analysis(vec![group("G1", "low", &["H1", "H2"])]);
+        let prompt = constraints_prompt(
+            &adjusted,
+            &[
+                ("H1".to_string(), "G1".to_string()),
+                ("H2".to_string(), "G1".to_string()),
+            ],
+        );
+        assert!(prompt.contains("group \"G1\" (\"Title G1\") must contain hunks H1, H2"));
+        assert!(prompt.contains("previous.json"));
+    }
+}
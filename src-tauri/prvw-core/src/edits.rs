@@ -0,0 +1,640 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::constraints::{merge_into, move_hunk};
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::json_store;
+use crate::session;
+use crate::time::now_secs;
+use crate::types::{AnalysisEdit, AnalysisEditState, AnalysisResult, EditedAnalysis, IntentGroup};
+use crate::validation::validate_analysis;
+
+/// Edits kept per PR; older ones can no longer be undone.
+const MAX_EDITS: usize = 100;
+
+/// Edited analyses are user corrections: they live outside `cache/` so `clear_cache` keeps them.
+const EDITS_DIR: &str = "edits";
+
+struct Entry {
+    label: String,
+    before: AnalysisResult,
+    after: AnalysisResult,
+}
+
+/// Undo and redo stacks of one PR's manual edits.
+#[derive(Default)]
+struct EditLog {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+}
+
+impl EditLog {
+    fn record(&mut self, label: String, before: AnalysisResult, after: AnalysisResult) {
+        self.redo.clear();
+        self.undo.push(Entry {
+            label,
+            before,
+            after,
+        });
+        if self.undo.len() > MAX_EDITS {
+            self.undo.remove(0);
+        }
+    }
+
+    fn undo(&mut self) -> Option<AnalysisResult> {
+        let entry = self.undo.pop()?;
+        let before = entry.before.clone();
+        self.redo.push(entry);
+        Some(before)
+    }
+
+    fn redo(&mut self) -> Option<AnalysisResult> {
+        let entry = self.redo.pop()?;
+        let after = entry.after.clone();
+        self.undo.push(entry);
+        Some(after)
+    }
+
+    /// Whether `analysis` is what the last edit produced. When it is not, the analysis was
+    /// replaced (re-run, refined, imported) and the log no longer applies to it.
+    fn is_at(&self, analysis: &AnalysisResult) -> bool {
+        self.undo.last().is_some_and(|e| same(&e.after, analysis))
+    }
+
+    /// Ids of every group an undoable or redoable edit shows, so undo never brings back a
+    /// group whose id was handed out again.
+    fn group_ids(&self) -> impl Iterator<Item = &str> {
+        self.undo
+            .iter()
+            .chain(self.redo.iter())
+            .flat_map(|e| e.before.groups.iter().chain(e.after.groups.iter()))
+            .map(|g| g.id.as_str())
+    }
+
+    fn state(&self, analysis: AnalysisResult) -> AnalysisEditState {
+        AnalysisEditState {
+            analysis,
+            undo_label: self.undo.last().map(|e| e.label.clone()),
+            redo_label: self.redo.last().map(|e| e.label.clone()),
+        }
+    }
+}
+
+fn same(a: &AnalysisResult, b: &AnalysisResult) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Manual edit logs of the PRs opened in this app session, keyed `owner/repo#123`.
+#[derive(Default)]
+pub struct EditHistory {
+    logs: Mutex<HashMap<String, EditLog>>,
+}
+
+fn label(edit: &AnalysisEdit) -> String {
+    match edit {
+        AnalysisEdit::MoveHunks {
+            hunk_ids,
+            group_id: Some(group_id),
+        } => format!("Move {} to {}", hunk_ids.join(", "), group_id),
+        AnalysisEdit::MoveHunks { hunk_ids, .. } => {
+            format!("Unassign {}", hunk_ids.join(", "))
+        }
+        AnalysisEdit::MergeGroups { group_ids } => format!("Merge {}", group_ids.join(", ")),
+        AnalysisEdit::RenameGroup { group_id, .. } => format!("Rename {}", group_id),
+        AnalysisEdit::CreateGroup { title, .. } => format!("Create group \"{}\"", title.trim()),
+    }
+}
+
+/// Next `G<n>` group id past both the analysis groups and `issued`: ids of groups that were
+/// dropped, which session state (marks, skips, assignments, order) may still refer to.
+fn next_group_id(analysis: &AnalysisResult, issued: &HashSet<&str>) -> String {
+    let n = analysis
+        .groups
+        .iter()
+        .map(|g| g.id.as_str())
+        .chain(issued.iter().copied())
+        .filter_map(|id| id.strip_prefix('G')?.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("G{}", n + 1)
+}
+
+/// Every hunk the analysis covered before an edit must still be covered exactly once after it.
+fn check_coverage(before: &AnalysisResult, after: &AnalysisResult) -> Result<(), String> {
+    let valid_ids: HashSet<String> = before
+        .groups
+        .iter()
+        .flat_map(|g| g.hunk_ids.iter())
+        .chain(before.unassigned_hunk_ids.iter())
+        .chain(before.non_substantive_hunk_ids.iter())
+        .cloned()
+        .collect();
+    let was = validate_analysis(before, &valid_ids).id_errors;
+    let result = validate_analysis(after, &valid_ids);
+    if result.id_errors > was {
+        return Err(format!(
+            "The edit would break hunk coverage: {}",
+            result.warnings.join("; ")
+        ));
+    }
+    Ok(())
+}
+
+/// `analysis` with `edit` applied; groups left without hunks are dropped. New groups get ids
+/// past those in `issued`.
+fn apply_edit(
+    analysis: &AnalysisResult,
+    edit: &AnalysisEdit,
+    issued: &HashSet<&str>,
+) -> Result<AnalysisResult, PrvwError> {
+    let mut edited = analysis.clone();
+    let has_group = |id: &str| analysis.groups.iter().any(|g| g.id == id);
+    match edit {
+        AnalysisEdit::MoveHunks { hunk_ids, group_id } => {
+            let known: HashSet<&str> = analysis
+                .groups
+                .iter()
+                .flat_map(|g| g.hunk_ids.iter())
+                .chain(analysis.unassigned_hunk_ids.iter())
+                .map(String::as_str)
+                .collect();
+            if hunk_ids.is_empty() {
+                return Err(PrvwError::InvalidInput("No hunks to move.".to_string()));
+            }
+            if let Some(unknown) = hunk_ids.iter().find(|h| !known.contains(h.as_str())) {
+                return Err(PrvwError::InvalidInput(format!(
+                    "Unknown hunk '{}'",
+                    unknown
+                )));
+            }
+            match group_id {
+                Some(group_id) if !has_group(group_id) => {
+                    return Err(PrvwError::InvalidInput(format!(
+                        "Unknown group '{}'",
+                        group_id
+                    )));
+                }
+                Some(group_id) => {
+                    for hunk_id in hunk_ids {
+                        move_hunk(&mut edited, hunk_id, group_id);
+                    }
+                }
+                None => {
+                    for group in &mut edited.groups {
+                        group.hunk_ids.retain(|h| !hunk_ids.contains(h));
+                        group
+                            .risk_evidence
+                            .retain(|e| !hunk_ids.contains(&e.hunk_id));
+                    }
+                    for hunk_id in hunk_ids {
+                        if !edited.unassigned_hunk_ids.contains(hunk_id) {
+                            edited.unassigned_hunk_ids.push(hunk_id.clone());
+                        }
+                    }
+                }
+            }
+        }
+        AnalysisEdit::MergeGroups { group_ids } => {
+            if let Some(unknown) = group_ids.iter().find(|g| !has_group(g)) {
+                return Err(PrvwError::InvalidInput(format!(
+                    "Unknown group '{}'",
+                    unknown
+                )));
+            }
+            let mut distinct: Vec<&String> = Vec::new();
+            for id in group_ids {
+                if !distinct.contains(&id) {
+                    distinct.push(id);
+                }
+            }
+            let Some((target_id, others)) = distinct.split_first() else {
+                return Err(PrvwError::InvalidInput(
+                    "Select at least two groups to merge.".to_string(),
+                ));
+            };
+            if others.is_empty() {
+                return Err(PrvwError::InvalidInput(
+                    "Select at least two groups to merge.".to_string(),
+                ));
+            }
+            for other_id in others {
+                let Some(pos) = edited.groups.iter().position(|g| &g.id == *other_id) else {
+                    continue;
+                };
+                let other = edited.groups.remove(pos);
+                if let Some(target) = edited.groups.iter_mut().find(|g| &g.id == *target_id) {
+                    merge_into(target, other);
+                }
+            }
+        }
+        AnalysisEdit::CreateGroup {
+            title,
+            hunk_ids,
+            category,
+        } => {
+            let title = title.trim();
+            if title.is_empty() {
+                return Err(PrvwError::InvalidInput("Group title is empty.".to_string()));
+            }
+            if hunk_ids.is_empty() {
+                return Err(PrvwError::InvalidInput(
+                    "A new group needs at least one hunk.".to_string(),
+                ));
+            }
+            let id = next_group_id(analysis, issued);
+            edited.groups.push(IntentGroup {
+                id: id.clone(),
+                title: title.to_string(),
+                category: category.clone().unwrap_or_else(|| "other".to_string()),
+                rationale: String::new(),
+                risk: "low".to_string(),
+                risk_score: 0,
+                risk_evidence: Vec::new(),
+                hunk_ids: Vec::new(),
+                reviewer_checklist: Vec::new(),
+                suggested_tests: Vec::new(),
+            });
+            return apply_edit(
+                &edited,
+                &AnalysisEdit::MoveHunks {
+                    hunk_ids: hunk_ids.clone(),
+                    group_id: Some(id),
+                },
+                issued,
+            );
+        }
+        AnalysisEdit::RenameGroup { group_id, title } => {
+            let title = title.trim();
+            if title.is_empty() {
+                return Err(PrvwError::InvalidInput("Group title is empty.".to_string()));
+            }
+            let group = edited
+                .groups
+                .iter_mut()
+                .find(|g| &g.id == group_id)
+                .ok_or_else(|| PrvwError::InvalidInput(format!("Unknown group '{}'", group_id)))?;
+            group.title = title.to_string();
+        }
+    }
+    edited.groups.retain(|g| !g.hunk_ids.is_empty());
+    check_coverage(analysis, &edited)?;
+    Ok(edited)
+}
+
+fn saved_path(app_data_dir: &Path, repo: &str, pr_number: u32) -> PathBuf {
+    app_data_dir
+        .join(EDITS_DIR)
+        .join(format!("{}__{}.json", repo.replace('/', "__"), pr_number))
+}
+
+fn load_saved(app_data_dir: &Path, repo: &str, pr_number: u32) -> Option<EditedAnalysis> {
+    json_store::load(&saved_path(app_data_dir, repo, pr_number))
+}
+
+/// Save the PR's edited analysis via a temp file and rename; None removes it.
+fn store(
+    app_data_dir: &Path,
+    repo: &str,
+    pr_number: u32,
+    edited: Option<&EditedAnalysis>,
+) -> Result<(), String> {
+    let path = saved_path(app_data_dir, repo, pr_number);
+    match edited {
+        Some(edited) => json_store::save(&path, edited, "edited analysis"),
+        None => json_store::remove(&path, "edited analysis"),
+    }
+}
+
+fn log_key(repo: &str, pr_number: u32) -> Result<String, PrvwError> {
+    validate_repo(repo)?;
+    Ok(format!("{}#{}", repo, pr_number))
+}
+
+/// The PR's saved edited analysis, unless it was saved at a head other than `head_sha`.
+pub fn saved_analysis(
+    app_data_dir: &Path,
+    repo: &str,
+    pr_number: u32,
+    head_sha: Option<&str>,
+) -> Option<AnalysisResult> {
+    load_saved(app_data_dir, repo, pr_number)
+        .filter(|s| match (&s.head_sha, head_sha) {
+            (Some(saved_head), Some(head)) => saved_head == head,
+            _ => true,
+        })
+        .map(|s| s.analysis)
+}
+
+/// Persist the analysis a log now shows, or drop the saved copy once every edit is undone.
+fn persist(
+    app_data_dir: &Path,
+    repo: &str,
+    pr_number: u32,
+    head_sha: Option<String>,
+    log: &EditLog,
+    analysis: &AnalysisResult,
+) -> Result<(), String> {
+    if log.undo.is_empty() {
+        return store(app_data_dir, repo, pr_number, None);
+    }
+    let edited = EditedAnalysis {
+        repo: repo.to_string(),
+        pr_number,
+        head_sha,
+        analysis: analysis.clone(),
+        saved_at: now_secs(),
+    };
+    store(app_data_dir, repo, pr_number, Some(&edited))
+}
+
+impl EditHistory {
+    /// Run `f` on the PR's log, starting an empty one if there is none.
+    fn with_log<T>(
+        &self,
+        repo: &str,
+        pr_number: u32,
+        f: impl FnOnce(&mut EditLog) -> Result<T, PrvwError>,
+    ) -> Result<T, PrvwError> {
+        let key = log_key(repo, pr_number)?;
+        let mut logs = self
+            .logs
+            .lock()
+            .map_err(|_| "Edit history lock poisoned".to_string())?;
+        f(logs.entry(key).or_default())
+    }
+
+    /// Apply a manual edit to the PR's current analysis, record it for undo, and save the
+    /// result. Edits made to an analysis the log did not produce start a new log.
+    pub fn edit(
+        &self,
+        app_data_dir: &Path,
+        repo: &str,
+        pr_number: u32,
+        analysis: AnalysisResult,
+        edit: &AnalysisEdit,
+        head_sha: Option<String>,
+    ) -> Result<AnalysisEditState, PrvwError> {
+        let repo = repo.trim();
+        self.with_log(repo, pr_number, |log| {
+            if !log.is_at(&analysis) {
+                *log = EditLog::default();
+            }
+            let session = session::load(app_data_dir, repo, pr_number);
+            let mut issued = session::group_ids_in_use(&session);
+            issued.extend(log.group_ids());
+            let edited = apply_edit(&analysis, edit, &issued)?;
+            log.record(label(edit), analysis, edited.clone());
+            persist(app_data_dir, repo, pr_number, head_sha, log, &edited)?;
+            Ok(log.state(edited))
+        })
+    }
+
+    pub fn undo(
+        &self,
+        app_data_dir: &Path,
+        repo: &str,
+        pr_number: u32,
+        head_sha: Option<String>,
+    ) -> Result<AnalysisEditState, PrvwError> {
+        let repo = repo.trim();
+        self.with_log(repo, pr_number, |log| {
+            let analysis = log.undo().ok_or_else(|| "Nothing to undo.".to_string())?;
+            persist(app_data_dir, repo, pr_number, head_sha, log, &analysis)?;
+            Ok(log.state(analysis))
+        })
+    }
+
+    pub fn redo(
+        &self,
+        app_data_dir: &Path,
+        repo: &str,
+        pr_number: u32,
+        head_sha: Option<String>,
+    ) -> Result<AnalysisEditState, PrvwError> {
+        let repo = repo.trim();
+        self.with_log(repo, pr_number, |log| {
+            let analysis = log.redo().ok_or_else(|| "Nothing to redo.".to_string())?;
+            persist(app_data_dir, repo, pr_number, head_sha, log, &analysis)?;
+            Ok(log.state(analysis))
+        })
+    }
+
+    /// Forget the PR's edit log and saved edits.
+    pub fn clear(&self, app_data_dir: &Path, repo: &str, pr_number: u32) -> Result<(), PrvwError> {
+        let repo = repo.trim();
+        let key = log_key(repo, pr_number)?;
+        self.logs
+            .lock()
+            .map_err(|_| "Edit history lock poisoned".to_string())?
+            .remove(&key);
+        Ok(store(app_data_dir, repo, pr_number, None)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::IntentGroup;
+
+    fn group(id: &str, hunk_ids: &[&str]) -> IntentGroup {
+        IntentGroup {
+            id: id.to_string(),
+            title: id.to_string(),
+            category: "logic".to_string(),
+            rationale: String::new(),
+            risk: "low".to_string(),
+            risk_score: 0,
+            risk_evidence: vec![],
+            hunk_ids: hunk_ids.iter().map(ToString::to_string).collect(),
+            reviewer_checklist: vec![],
+            suggested_tests: vec![],
+        }
+    }
+
+    fn analysis() -> AnalysisResult {
+        AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![group("G1", &["H1", "H2"]), group("G2", &["H3"])],
+            unassigned_hunk_ids: vec!["H4".to_string()],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        }
+    }
+
+    fn hunks_of(analysis: &AnalysisResult, group_id: &str) -> Vec<String> {
+        analysis
+            .groups
+            .iter()
+            .find(|g| g.id == group_id)
+            .map(|g| g.hunk_ids.clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn applies_moves_merges_and_renames() {
+        let moved = apply_edit(
+            &analysis(),
+            &AnalysisEdit::MoveHunks {
+                hunk_ids: vec!["H4".to_string(), "H3".to_string()],
+                group_id: Some("G1".to_string()),
+            },
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert_eq!(hunks_of(&moved, "G1"), vec!["H1", "H2", "H4", "H3"]);
+        // G2 lost its only hunk
+        assert_eq!(moved.groups.len(), 1);
+        assert!(moved.unassigned_hunk_ids.is_empty());
+
+        let unassigned = apply_edit(
+            &analysis(),
+            &AnalysisEdit::MoveHunks {
+                hunk_ids: vec!["H1".to_string()],
+                group_id: None,
+            },
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert_eq!(unassigned.unassigned_hunk_ids, vec!["H4", "H1"]);
+
+        let merged = apply_edit(
+            &analysis(),
+            &AnalysisEdit::MergeGroups {
+                group_ids: vec!["G2".to_string(), "G1".to_string()],
+            },
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert_eq!(hunks_of(&merged, "G2"), vec!["H3", "H1", "H2"]);
+
+        let renamed = apply_edit(
+            &analysis(),
+            &AnalysisEdit::RenameGroup {
+                group_id: "G1".to_string(),
+                title: " Parsing ".to_string(),
+            },
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert_eq!(renamed.groups[0].title, "Parsing");
+
+        let bad = [
+            AnalysisEdit::MoveHunks {
+                hunk_ids: vec!["H9".to_string()],
+                group_id: None,
+            },
+            AnalysisEdit::MergeGroups {
+                group_ids: vec!["G1".to_string(), "G1".to_string()],
+            },
+            AnalysisEdit::RenameGroup {
+                group_id: "G1".to_string(),
+                title: " ".to_string(),
+            },
+        ];
+        for edit in &bad {
+            assert!(
+                apply_edit(&analysis(), edit, &HashSet::new()).is_err(),
+                "{:?}",
+                edit
+            );
+        }
+    }
+
+    #[test]
+    fn creates_groups_and_keeps_coverage() {
+        let created = apply_edit(
+            &analysis(),
+            &AnalysisEdit::CreateGroup {
+                title: "Docs".to_string(),
+                hunk_ids: vec!["H2".to_string(), "H4".to_string()],
+                category: Some("docs".to_string()),
+            },
+            &HashSet::new(),
+        )
+        .unwrap();
+        assert_eq!(created.groups[2].id, "G3");
+        assert_eq!(hunks_of(&created, "G3"), vec!["H2", "H4"]);
+        assert_eq!(hunks_of(&created, "G1"), vec!["H1"]);
+        assert!(created.unassigned_hunk_ids.is_empty());
+        assert!(apply_edit(
+            &analysis(),
+            &AnalysisEdit::CreateGroup {
+                title: "Empty".to_string(),
+                hunk_ids: vec![],
+                category: None,
+            },
+            &HashSet::new(),
+        )
+        .is_err());
+
+        // ids still referenced by review state are never handed out again
+        let reused = apply_edit(
+            &analysis(),
+            &AnalysisEdit::CreateGroup {
+                title: "Docs".to_string(),
+                hunk_ids: vec!["H4".to_string()],
+                category: None,
+            },
+            &HashSet::from(["G5", "other"]),
+        )
+        .unwrap();
+        assert_eq!(reused.groups[2].id, "G6");
+
+        // an edit that drops a hunk is refused
+        let mut lost = analysis();
+        lost.unassigned_hunk_ids.clear();
+        assert!(check_coverage(&analysis(), &lost).is_err());
+        assert!(check_coverage(&analysis(), &created).is_ok());
+    }
+
+    #[test]
+    fn saved_edits_roundtrip_and_clear() {
+        let tmp = tempfile::tempdir().unwrap();
+        let edited = EditedAnalysis {
+            repo: "o/r".to_string(),
+            pr_number: 7,
+            head_sha: Some("abc".to_string()),
+            analysis: analysis(),
+            saved_at: 1,
+        };
+        store(tmp.path(), "o/r", 7, Some(&edited)).unwrap();
+        let loaded = load_saved(tmp.path(), "o/r", 7).unwrap();
+        assert_eq!(loaded.head_sha.as_deref(), Some("abc"));
+        assert!(same(&loaded.analysis, &analysis()));
+        store(tmp.path(), "o/r", 7, None).unwrap();
+        assert!(load_saved(tmp.path(), "o/r", 7).is_none());
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_log() {
+        let original = analysis();
+        let merge = AnalysisEdit::MergeGroups {
+            group_ids: vec!["G1".to_string(), "G2".to_string()],
+        };
+        let merged = apply_edit(&original, &merge, &HashSet::new()).unwrap();
+        let mut log = EditLog::default();
+        log.record(label(&merge), original.clone(), merged.clone());
+        assert!(log.is_at(&merged));
+        assert!(!log.is_at(&original));
+
+        let undone = log.undo().unwrap();
+        assert!(same(&undone, &original));
+        assert_eq!(
+            log.state(undone).redo_label.as_deref(),
+            Some("Merge G1, G2")
+        );
+        assert!(log.undo().is_none());
+
+        let redone = log.redo().unwrap();
+        assert!(same(&redone, &merged));
+        assert!(log.redo().is_none());
+
+        // a new edit after an undo drops the redo stack
+        log.undo();
+        log.record("Rename G1".to_string(), original.clone(), original);
+        assert!(log.redo().is_none());
+    }
+}
@@ -0,0 +1,218 @@
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::provider::RunError;
+
+/// Error of every command. Serialized as `{kind, message, remediation, retryable}` so the
+/// frontend can branch on `kind`; provider failures also carry their `RunError` fields.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PrvwError {
+    /// A provider or CLI run failed.
+    Run(RunError),
+    /// A token, URL or other setting the command needs is not set.
+    NotConfigured(String),
+    /// The arguments are malformed or not allowed.
+    InvalidInput(String),
+    /// What the command refers to (a report, an operation, a PR) does not exist.
+    NotFound(String),
+    /// A request to a forge or API could not be made or got no answer.
+    Network(String),
+    /// Anything else; the message says what happened.
+    Other(String),
+}
+
+type RemediationHook = Box<dyn Fn(&PrvwError) -> Option<String> + Send + Sync>;
+/// Wording of remediations in the interface language; unset in tests and the CLI.
+static REMEDIATION_HOOK: OnceLock<RemediationHook> = OnceLock::new();
+
+/// Reword remediations for the user; returning None keeps the English text. Only the first hook
+/// is kept.
+pub fn on_remediation(hook: impl Fn(&PrvwError) -> Option<String> + Send + Sync + 'static) {
+    let _ = REMEDIATION_HOOK.set(Box::new(hook));
+}
+
+impl PrvwError {
+    /// The serialized `kind` tag; provider failures use their `RunError` kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PrvwError::Run(e) => e.kind(),
+            PrvwError::NotConfigured(_) => "notConfigured",
+            PrvwError::InvalidInput(_) => "invalidInput",
+            PrvwError::NotFound(_) => "notFound",
+            PrvwError::Network(_) => "network",
+            PrvwError::Other(_) => "other",
+        }
+    }
+
+    /// The error as shown to the user.
+    pub fn message(&self) -> String {
+        match self {
+            PrvwError::Run(e) => e.message(),
+            PrvwError::NotConfigured(msg)
+            | PrvwError::InvalidInput(msg)
+            | PrvwError::NotFound(msg)
+            | PrvwError::Network(msg)
+            | PrvwError::Other(msg) => msg.clone(),
+        }
+    }
+
+    /// What the user can do about it, when the message does not already say so.
+    pub fn remediation(&self) -> Option<String> {
+        if let Some(text) = REMEDIATION_HOOK.get().and_then(|hook| hook(self)) {
+            return Some(text);
+        }
+        let text = match self {
+            PrvwError::Run(RunError::SchemaViolation { .. }) => {
+                "Retry, or pick another model or provider in Settings."
+            }
+            PrvwError::Run(RunError::EmptyOutput { .. }) => {
+                "Retry; the run history has the provider's full output."
+            }
+            PrvwError::Run(RunError::RateLimited { .. }) => {
+                "Wait a minute before retrying, or switch provider in Settings."
+            }
+            PrvwError::Network(_) => "Check the network connection and retry.",
+            _ => return None,
+        };
+        Some(text.to_string())
+    }
+
+    /// Whether running the same command again may succeed without changing anything.
+    pub fn retryable(&self) -> bool {
+        matches!(
+            self,
+            PrvwError::Run(
+                RunError::Timeout { .. }
+                    | RunError::SchemaViolation { .. }
+                    | RunError::EmptyOutput { .. }
+                    | RunError::RateLimited { .. }
+            ) | PrvwError::Network(_)
+        )
+    }
+}
+
+impl std::fmt::Display for PrvwError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl From<RunError> for PrvwError {
+    fn from(e: RunError) -> Self {
+        PrvwError::Run(e)
+    }
+}
+
+impl From<String> for PrvwError {
+    fn from(msg: String) -> Self {
+        PrvwError::Other(msg)
+    }
+}
+
+impl From<&str> for PrvwError {
+    fn from(msg: &str) -> Self {
+        PrvwError::Other(msg.to_string())
+    }
+}
+
+impl From<PrvwError> for String {
+    fn from(e: PrvwError) -> Self {
+        e.message()
+    }
+}
+
+impl Serialize for PrvwError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Typed<'a> {
+            #[serde(flatten)]
+            error: &'a RunError,
+            message: String,
+            remediation: Option<String>,
+            retryable: bool,
+        }
+        #[derive(Serialize)]
+        struct Plain {
+            kind: &'static str,
+            message: String,
+            remediation: Option<String>,
+            retryable: bool,
+        }
+        match self {
+            PrvwError::Run(error) => Typed {
+                error,
+                message: self.message(),
+                remediation: self.remediation(),
+                retryable: self.retryable(),
+            }
+            .serialize(serializer),
+            _ => Plain {
+                kind: self.kind(),
+                message: self.message(),
+                remediation: self.remediation(),
+                retryable: self.retryable(),
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_run_errors_with_their_fields() {
+        let json = serde_json::to_value(PrvwError::from(RunError::Timeout {
+            program: "codex",
+            minutes: 3,
+        }))
+        .unwrap();
+        assert_eq!(json["kind"], "timeout");
+        assert_eq!(json["minutes"], 3);
+        assert_eq!(json["retryable"], true);
+        assert!(json["remediation"].is_null());
+        assert!(json["message"]
+            .as_str()
+            .unwrap()
+            .contains("timed out after 3 minute(s)"));
+    }
+
+    #[test]
+    fn serializes_other_errors_with_a_kind() {
+        let json = serde_json::to_value(PrvwError::from("bad input")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "kind": "other",
+                "message": "bad input",
+                "remediation": null,
+                "retryable": false,
+            })
+        );
+        let json = serde_json::to_value(PrvwError::Network("timed out".to_string())).unwrap();
+        assert_eq!(json["kind"], "network");
+        assert_eq!(json["retryable"], true);
+        assert_eq!(
+            json["remediation"],
+            "Check the network connection and retry."
+        );
+    }
+
+    #[test]
+    fn every_run_error_kind_matches_its_tag() {
+        let errors = [
+            RunError::Cancelled { program: "codex" },
+            RunError::RateLimited {
+                program: "codex",
+                detail: "429".to_string(),
+            },
+            RunError::unknown("boom"),
+        ];
+        for error in errors {
+            let json = serde_json::to_value(&error).unwrap();
+            assert_eq!(json["kind"], error.kind());
+        }
+    }
+}
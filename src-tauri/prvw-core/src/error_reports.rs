@@ -0,0 +1,289 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::OnceLock;
+
+use crate::error::PrvwError;
+use crate::settings;
+use crate::time::now_secs;
+use crate::types::ErrorReport;
+
+/// Reports are user data like the usage ledger: kept outside `cache/`.
+const REPORTS_DIR: &str = "reports";
+/// Reports kept on disk; the oldest are dropped first.
+const MAX_REPORTS: usize = 50;
+const MAX_MESSAGE_LINES: usize = 8;
+const MAX_MESSAGE_CHARS: usize = 1000;
+const MAX_BACKTRACE_CHARS: usize = 6000;
+/// Where "submit" opens a prefilled issue; the user reviews it there before anything is sent.
+const ISSUES_URL: &str = "https://github.com/gami/prvw/issues/new";
+
+/// Set once at startup. Recording is a no-op until then (and in tests).
+static DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Tells apart reports captured within the same second.
+static SEQ: AtomicU32 = AtomicU32::new(0);
+
+pub fn init(app_data_dir: &Path) {
+    let _ = DIR.set(app_data_dir.join(REPORTS_DIR));
+}
+
+/// Record panics as reports (when enabled), then run the default hook as before.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        record("panic", &location, &message, Some(&backtrace));
+        previous(info);
+    }));
+}
+
+/// Record a failed command (`source` is its operation kind), when error reports are enabled.
+/// Cancellations are not failures and are not passed here.
+pub fn record_failure(source: &str, message: &str) {
+    record("command", source, message, None);
+}
+
+fn record(kind: &str, source: &str, message: &str, backtrace: Option<&str>) {
+    let Some(dir) = DIR.get() else {
+        return;
+    };
+    if !settings::current().error_reports {
+        return;
+    }
+    let at = now_secs();
+    let report = ErrorReport {
+        id: format!("{}-{}", at, SEQ.fetch_add(1, Ordering::Relaxed)),
+        kind: kind.to_string(),
+        source: redact_home(source),
+        message: sanitize(message),
+        backtrace: backtrace.map(|b| truncate(&redact_home(b), MAX_BACKTRACE_CHARS)),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        at,
+    };
+    if let Err(e) = save(dir, &report) {
+        eprintln!("[error_reports] failed to save report: {}", e);
+    }
+}
+
+/// Lines that could be diff content: file and hunk headers, added and removed lines.
+fn is_diff_line(line: &str) -> bool {
+    line.starts_with("diff --git")
+        || line.starts_with("index ")
+        || line.starts_with("@@")
+        || line.starts_with('+')
+        || line.starts_with('-')
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+fn redact_home(text: &str) -> String {
+    match std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")) {
+        Ok(home) if home.len() > 1 => text.replace(&home, "~"),
+        _ => text.to_string(),
+    }
+}
+
+/// Keep the first lines of an error message, without anything that looks like diff content
+/// (errors can quote provider output or `gh` stderr) or the user's home directory.
+fn sanitize(message: &str) -> String {
+    let mut dropped = 0;
+    let mut kept: Vec<&str> = Vec::new();
+    for line in message.lines() {
+        if is_diff_line(line.trim_start()) {
+            dropped += 1;
+        } else if kept.len() < MAX_MESSAGE_LINES {
+            kept.push(line);
+        }
+    }
+    let mut text = truncate(&redact_home(&kept.join("\n")), MAX_MESSAGE_CHARS);
+    if dropped > 0 {
+        text.push_str(&format!("\n[{} diff-like line(s) removed]", dropped));
+    }
+    text
+}
+
+fn save(dir: &Path, report: &ErrorReport) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create reports dir: {}", e))?;
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize report: {}", e))?;
+    fs::write(dir.join(format!("{}.json", report.id)), json)
+        .map_err(|e| format!("Failed to write report: {}", e))?;
+    for old in load_all(dir).iter().skip(MAX_REPORTS) {
+        let _ = fs::remove_file(dir.join(format!("{}.json", old.id)));
+    }
+    Ok(())
+}
+
+/// All saved reports, newest first; unreadable files are skipped.
+fn load_all(dir: &Path) -> Vec<ErrorReport> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<ErrorReport> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|data| serde_json::from_str(&data).ok())
+        .collect();
+    reports.sort_by(|a, b| b.at.cmp(&a.at).then_with(|| b.id.cmp(&a.id)));
+    reports
+}
+
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// New-issue URL with the report as title and body.
+fn issue_url(report: &ErrorReport) -> String {
+    let title = format!(
+        "[{}] {}",
+        report.kind,
+        report.message.lines().next().unwrap_or_default()
+    );
+    let mut body = format!(
+        "prvw {} on {}\nSource: {}\n\n```\n{}\n```\n",
+        report.app_version, report.os, report.source, report.message
+    );
+    if let Some(backtrace) = &report.backtrace {
+        // Long URLs get rejected; the export has the full backtrace.
+        body.push_str(&format!(
+            "\n<details><summary>Backtrace</summary>\n\n```\n{}\n```\n</details>\n",
+            truncate(backtrace, 2000)
+        ));
+    }
+    format!(
+        "{}?title={}&body={}",
+        ISSUES_URL,
+        encode_query(&truncate(&title, 120)),
+        encode_query(&body)
+    )
+}
+
+/// Saved reports, newest first.
+pub fn list(app_data_dir: &Path) -> Vec<ErrorReport> {
+    load_all(&app_data_dir.join(REPORTS_DIR))
+}
+
+/// Write the saved reports (or those in `ids`) as a JSON array to `path`. Returns how many.
+pub fn export(app_data_dir: &Path, path: &str, ids: Option<&[String]>) -> Result<usize, PrvwError> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(PrvwError::InvalidInput(
+            "Choose a file to export the reports to.".to_string(),
+        ));
+    }
+    let reports: Vec<ErrorReport> = list(app_data_dir)
+        .into_iter()
+        .filter(|r| ids.is_none_or(|ids| ids.contains(&r.id)))
+        .collect();
+    let json = serde_json::to_string_pretty(&reports)
+        .map_err(|e| format!("Failed to serialize reports: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(reports.len())
+}
+
+/// URL of a new GitHub issue prefilled with the report.
+pub fn submit_url(app_data_dir: &Path, id: &str) -> Result<String, PrvwError> {
+    list(app_data_dir)
+        .iter()
+        .find(|r| r.id == id)
+        .map(issue_url)
+        .ok_or_else(|| PrvwError::NotFound(format!("Error report '{}' not found.", id)))
+}
+
+/// Delete the reports in `ids`, or all of them. Returns how many were deleted.
+pub fn delete(app_data_dir: &Path, ids: Option<&[String]>) -> usize {
+    let dir = app_data_dir.join(REPORTS_DIR);
+    let mut deleted = 0;
+    for report in load_all(&dir) {
+        if ids.is_none_or(|ids| ids.contains(&report.id))
+            && fs::remove_file(dir.join(format!("{}.json", report.id))).is_ok()
+        {
+            deleted += 1;
+        }
+    }
+    deleted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(id: &str, at: u64) -> ErrorReport {
+        ErrorReport {
+            id: id.to_string(),
+            kind: "command".to_string(),
+            source: "get_pr_diff".to_string(),
+            message: "gh pr diff failed: HTTP 502".to_string(),
+            backtrace: None,
+            app_version: "0.3.0".to_string(),
+            os: "linux x86_64".to_string(),
+            at,
+        }
+    }
+
+    #[test]
+    fn sanitize_drops_diff_lines() {
+        let message = "Codex failed: invalid output\n\
+                       diff --git a/a.rs b/a.rs\n\
+                       @@ -1,2 +1,2 @@\n\
+                       -let secret = 1;\n\
+                       +let secret = 2;\n\
+                       exit status 1";
+        assert_eq!(
+            sanitize(message),
+            "Codex failed: invalid output\nexit status 1\n[4 diff-like line(s) removed]"
+        );
+        assert_eq!(sanitize("plain"), "plain");
+        assert_eq!(
+            sanitize(&"x".repeat(MAX_MESSAGE_CHARS + 5)).chars().count(),
+            MAX_MESSAGE_CHARS + 1
+        );
+    }
+
+    #[test]
+    fn keeps_the_newest_reports() {
+        let tmp = tempfile::tempdir().unwrap();
+        for i in 0..MAX_REPORTS + 2 {
+            save(tmp.path(), &report(&format!("r{:03}", i), i as u64)).unwrap();
+        }
+        let reports = load_all(tmp.path());
+        assert_eq!(reports.len(), MAX_REPORTS);
+        assert_eq!(reports[0].id, format!("r{:03}", MAX_REPORTS + 1));
+        assert!(reports.iter().all(|r| r.id != "r000" && r.id != "r001"));
+    }
+
+    #[test]
+    fn issue_url_is_encoded() {
+        let url = issue_url(&report("r1", 1));
+        assert!(url.starts_with(
+            "https://github.com/gami/prvw/issues/new?title=%5Bcommand%5D%20gh%20pr%20diff%20failed%3A"
+        ));
+        assert!(url.contains("&body=prvw%200.3.0%20on%20linux%20x86_64%0ASource%3A%20get_pr_diff"));
+        assert!(!url.contains(' '));
+    }
+}
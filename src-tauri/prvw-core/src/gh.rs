@@ -0,0 +1,663 @@
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::cache;
+use crate::codex_runner::RunError;
+use crate::error::PrvwError;
+use crate::i18n::{t, Msg};
+use crate::operations;
+use crate::secrets::{self, Secret};
+use crate::settings;
+use crate::types::{PrListItem, RecentPr, RepoContext};
+
+pub fn validate_repo(repo: &str) -> Result<(), PrvwError> {
+    let parts: Vec<&str> = repo.split('/').collect();
+    if parts.len() != 2
+        || parts[0].is_empty()
+        || parts[1].is_empty()
+        || parts
+            .iter()
+            .any(|p| p.contains(|c: char| c.is_whitespace()))
+    {
+        return Err(PrvwError::InvalidInput(format!(
+            "Invalid repo format: '{}'. Expected 'owner/repo'.",
+            repo
+        )));
+    }
+    Ok(())
+}
+
+/// `owner/repo#123` or `https://github.com/owner/repo/pull/123[/files]`.
+pub fn parse_pr_ref(target: &str) -> Result<(String, u32), String> {
+    let invalid = || {
+        format!(
+            "Not a PR reference: {}. Use owner/repo#123 or a PR URL.",
+            target
+        )
+    };
+    let target = target.trim();
+    let (repo, number) = match target.split_once('#') {
+        Some((repo, number)) => (repo.to_string(), number),
+        None => {
+            let path = target
+                .split_once("://")
+                .map_or(target, |(_, rest)| rest)
+                .split(['?', '#'])
+                .next()
+                .unwrap_or_default();
+            let parts: Vec<&str> = path.split('/').collect();
+            match parts.as_slice() {
+                [_host, owner, name, "pull", number, ..] => {
+                    (format!("{}/{}", owner, name), *number)
+                }
+                _ => return Err(invalid()),
+            }
+        }
+    };
+    validate_repo(&repo).map_err(|_| invalid())?;
+    let number = number.parse::<u32>().map_err(|_| invalid())?;
+    Ok((repo, number))
+}
+
+fn gh_env() -> Vec<(&'static str, String)> {
+    let mut env: Vec<(&'static str, String)> = [
+        ("GH_PAGER", "cat"),
+        ("PAGER", "cat"),
+        ("NO_COLOR", "1"),
+        ("GH_FORCE_TTY", "0"),
+    ]
+    .iter()
+    .map(|(k, v)| (*k, v.to_string()))
+    .collect();
+    let host = settings::current().gh_host;
+    // A token stored in Settings takes precedence over `gh auth login`.
+    if let Ok(Some(token)) = secrets::get(Secret::GitHub) {
+        let var = if host.is_some() {
+            "GH_ENTERPRISE_TOKEN"
+        } else {
+            "GH_TOKEN"
+        };
+        env.push((var, token));
+    }
+    if let Some(host) = host {
+        env.push(("GH_HOST", host));
+    }
+    env
+}
+
+/// Error for a gh process that could not be started.
+fn spawn_error(e: &std::io::Error) -> PrvwError {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        PrvwError::Run(RunError::NotInstalled {
+            program: "gh",
+            hint: t(Msg::GhNotInstalled, &[]),
+        })
+    } else if e.kind() == std::io::ErrorKind::Interrupted {
+        PrvwError::Run(RunError::Cancelled { program: "gh" })
+    } else {
+        PrvwError::Other(format!("Failed to execute gh: {}", e))
+    }
+}
+
+/// Error for a gh run that exited unsuccessfully; `label` names the subcommand.
+fn failure_error(stderr: &str, label: &str) -> PrvwError {
+    if stderr.contains("auth login") || stderr.contains("not logged") {
+        PrvwError::Run(RunError::NotAuthenticated {
+            program: "gh",
+            hint: t(Msg::GhNotAuthenticated, &[]),
+        })
+    } else {
+        PrvwError::Other(format!("{} failed: {}", label, stderr))
+    }
+}
+
+/// Run gh with the standard environment, returning its output on success.
+/// `label` names the subcommand in error messages (e.g. "gh pr comment").
+pub fn run_gh(args: &[&str], label: &str) -> Result<std::process::Output, PrvwError> {
+    let output = operations::output(Command::new("gh").args(args).envs(gh_env()))
+        .map_err(|e| spawn_error(&e))?;
+    if !output.status.success() {
+        return Err(failure_error(
+            &String::from_utf8_lossy(&output.stderr),
+            label,
+        ));
+    }
+    Ok(output)
+}
+
+const PR_LIST_FIELDS: &str =
+    "number,title,author,updatedAt,createdAt,url,headRefName,headRefOid,baseRefName,headRepositoryOwner,isCrossRepository,reviewDecision,isDraft,body";
+
+pub fn fetch_pr_list(
+    repo: &str,
+    limit: u32,
+    state: &str,
+    search: Option<String>,
+) -> Result<Vec<PrListItem>, PrvwError> {
+    let stdout = run_pr_list(repo, limit, state, search, PR_LIST_FIELDS)?;
+    let items: Vec<PrListItem> =
+        serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse gh output: {}", e))?;
+    Ok(items)
+}
+
+#[derive(Deserialize)]
+struct PrFile {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct PrFiles {
+    #[serde(default)]
+    files: Vec<PrFile>,
+}
+
+/// The PRs among the first `limit` that touch a file under `root`. gh reports at most 100 files
+/// per PR, so a PR changing more than that outside its first 100 files is missed.
+fn fetch_subproject_prs(
+    repo: &str,
+    limit: u32,
+    state: &str,
+    search: Option<String>,
+    root: &str,
+) -> Result<Vec<PrListItem>, PrvwError> {
+    let fields = format!("{},files", PR_LIST_FIELDS);
+    let stdout = run_pr_list(repo, limit, state, search, &fields)?;
+    let items: Vec<PrListItem> =
+        serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse gh output: {}", e))?;
+    let files: Vec<PrFiles> =
+        serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse gh output: {}", e))?;
+    Ok(filter_by_subproject(items, files, root))
+}
+
+/// PRs as `list_prs` lists them: with `subproject` (a path prefix such as a monorepo
+/// subproject root), only those touching a file under it.
+pub fn list_prs(
+    repo: &str,
+    limit: u32,
+    state: &str,
+    search: Option<String>,
+    subproject: Option<&str>,
+) -> Result<Vec<PrListItem>, PrvwError> {
+    let subproject = subproject
+        .map(|root| root.trim().trim_start_matches("./").trim_matches('/'))
+        .filter(|root| !root.is_empty());
+    match subproject {
+        Some(root) => fetch_subproject_prs(repo, limit, state, search, root),
+        None => fetch_pr_list(repo, limit, state, search),
+    }
+}
+
+fn filter_by_subproject(
+    items: Vec<PrListItem>,
+    files: Vec<PrFiles>,
+    root: &str,
+) -> Vec<PrListItem> {
+    let roots = [root.to_string()];
+    items
+        .into_iter()
+        .zip(files)
+        .filter(|(_, pr)| {
+            pr.files
+                .iter()
+                .any(|f| settings::subproject_of(&f.path, &roots).is_some())
+        })
+        .map(|(item, _)| item)
+        .collect()
+}
+
+/// Raw JSON of `gh pr list` with the given `--json` fields.
+pub fn run_pr_list(
+    repo: &str,
+    limit: u32,
+    state: &str,
+    search: Option<String>,
+    fields: &str,
+) -> Result<String, PrvwError> {
+    validate_repo(repo)?;
+
+    let mut args = vec![
+        "pr".to_string(),
+        "list".to_string(),
+        "-R".to_string(),
+        repo.to_string(),
+        "--state".to_string(),
+        state.to_string(),
+        "--limit".to_string(),
+        limit.to_string(),
+        "--json".to_string(),
+        fields.to_string(),
+    ];
+
+    if let Some(s) = search {
+        if !s.trim().is_empty() {
+            args.push("--search".to_string());
+            args.push(s);
+        }
+    }
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = run_gh(&args, "gh pr list")?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn diff_cache_key(repo: &str, pr_number: u32, updated_at: Option<&str>) -> String {
+    let ts = updated_at.unwrap_or("").replace(':', "-");
+    format!("{}__{}_{}", repo.replace('/', "__"), pr_number, ts)
+}
+
+/// Whether `fetch_pr_diff` would be served from the disk cache.
+pub fn is_diff_cached(app_data_dir: &Path, repo: &str, pr_number: u32, updated_at: &str) -> bool {
+    cache::read_cache::<String>(
+        app_data_dir,
+        "cache/diff",
+        &diff_cache_key(repo, pr_number, Some(updated_at)),
+    )
+    .is_some()
+}
+
+/// Fetch a PR diff through the disk cache, keyed by `updated_at` so new pushes miss it.
+pub fn fetch_pr_diff(
+    app_data_dir: Option<&Path>,
+    repo: &str,
+    pr_number: u32,
+    updated_at: Option<&str>,
+    force: bool,
+) -> Result<String, PrvwError> {
+    validate_repo(repo)?;
+
+    let cache_key = diff_cache_key(repo, pr_number, updated_at);
+
+    // Check cache (unless force)
+    if !force {
+        if let Some(dir) = app_data_dir {
+            if let Some(cached) = cache::read_cache::<String>(dir, "cache/diff", &cache_key) {
+                return Ok(cached);
+            }
+        }
+    }
+
+    let output = operations::output(
+        Command::new("gh")
+            .args([
+                "pr",
+                "diff",
+                "-R",
+                repo,
+                &pr_number.to_string(),
+                "--color",
+                "never",
+            ])
+            .envs(gh_env()),
+    )
+    .map_err(|e| spawn_error(&e))?;
+
+    let diff = if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("too_large") || stderr.contains("HTTP 406") {
+            // Diff too large for GitHub API — fall back to git diff via local clone
+            get_pr_diff_via_git(repo, pr_number)?
+        } else {
+            return Err(failure_error(&stderr, "gh pr diff"));
+        }
+    } else {
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    if diff.trim().is_empty() {
+        return Err(t(Msg::DiffEmpty, &[]).into());
+    }
+
+    // Write cache
+    if let Some(dir) = app_data_dir {
+        let owner = RepoContext {
+            repo: repo.to_string(),
+            pr_number,
+        };
+        cache::write_cache_owned(dir, "cache/diff", &cache_key, &diff, Some(&owner));
+    }
+
+    Ok(diff)
+}
+
+/// Post a Markdown body to a PR, either as a plain comment or as a "comment" review.
+/// Returns gh's stdout (typically the URL of the created comment).
+pub fn post_body(
+    repo: &str,
+    pr_number: u32,
+    body: &str,
+    as_review: bool,
+) -> Result<String, PrvwError> {
+    validate_repo(repo)?;
+    if body.trim().is_empty() {
+        return Err(PrvwError::InvalidInput("Review body is empty.".to_string()));
+    }
+
+    // Pass the body through a file to avoid argument length limits on large reports.
+    let body_file =
+        tempfile::NamedTempFile::new().map_err(|e| format!("Failed to create temp file: {}", e))?;
+    std::fs::write(body_file.path(), body)
+        .map_err(|e| format!("Failed to write review body: {}", e))?;
+    let body_path = body_file
+        .path()
+        .to_str()
+        .ok_or_else(|| "Non-UTF-8 temp path".to_string())?;
+
+    let pr = pr_number.to_string();
+    let output = if as_review {
+        run_gh(
+            &[
+                "pr",
+                "review",
+                &pr,
+                "-R",
+                repo,
+                "--comment",
+                "--body-file",
+                body_path,
+            ],
+            "gh pr review",
+        )?
+    } else {
+        run_gh(
+            &["pr", "comment", &pr, "-R", repo, "--body-file", body_path],
+            "gh pr comment",
+        )?
+    };
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// One PR as `list_prs` lists it.
+pub fn fetch_pr_item(repo: &str, pr_number: u32) -> Result<PrListItem, PrvwError> {
+    validate_repo(repo)?;
+    let output = run_gh(
+        &[
+            "pr",
+            "view",
+            &pr_number.to_string(),
+            "-R",
+            repo,
+            "--json",
+            PR_LIST_FIELDS,
+        ],
+        "gh pr view",
+    )?;
+    Ok(serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse gh output: {}", e))?)
+}
+
+/// Current head commit SHA of a PR.
+pub fn fetch_head_sha(repo: &str, pr_number: u32) -> Result<String, PrvwError> {
+    validate_repo(repo)?;
+    let output = run_gh(
+        &[
+            "pr",
+            "view",
+            &pr_number.to_string(),
+            "-R",
+            repo,
+            "--json",
+            "headRefOid",
+            "-q",
+            ".headRefOid",
+        ],
+        "gh pr view",
+    )?;
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if sha.is_empty() {
+        return Err("gh pr view returned no head commit.".into());
+    }
+    Ok(sha)
+}
+
+/// The `limit` most recently updated PRs in any state, with the files each one touches.
+pub fn fetch_recent_prs_with_files(repo: &str, limit: u32) -> Result<Vec<RecentPr>, PrvwError> {
+    validate_repo(repo)?;
+    let output = run_gh(
+        &[
+            "pr",
+            "list",
+            "-R",
+            repo,
+            "--state",
+            "all",
+            "--limit",
+            &limit.to_string(),
+            "--json",
+            "number,title,url,state,updatedAt,author,body,files",
+        ],
+        "gh pr list",
+    )?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse gh output: {}", e))?)
+}
+
+/// Names of the labels defined in a repository.
+pub fn fetch_repo_labels(repo: &str) -> Result<Vec<String>, PrvwError> {
+    validate_repo(repo)?;
+    let output = run_gh(
+        &[
+            "label", "list", "-R", repo, "--limit", "500", "--json", "name", "-q", ".[].name",
+        ],
+        "gh label list",
+    )?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Add existing repository labels to a PR.
+pub fn add_pr_labels(repo: &str, pr_number: u32, labels: &[String]) -> Result<(), PrvwError> {
+    validate_repo(repo)?;
+    let pr = pr_number.to_string();
+    let mut args = vec!["pr", "edit", pr.as_str(), "-R", repo];
+    // One flag per label: gh splits a single value on commas.
+    for label in labels {
+        args.extend(["--add-label", label.as_str()]);
+    }
+    run_gh(&args, "gh pr edit")?;
+    Ok(())
+}
+
+/// Open an issue and return its URL. Labels must already exist in the repository.
+pub fn create_issue(
+    repo: &str,
+    title: &str,
+    body: &str,
+    labels: &[String],
+) -> Result<String, PrvwError> {
+    validate_repo(repo)?;
+    let body_file =
+        tempfile::NamedTempFile::new().map_err(|e| format!("Failed to create temp file: {}", e))?;
+    std::fs::write(body_file.path(), body)
+        .map_err(|e| format!("Failed to write issue body: {}", e))?;
+    let body_path = body_file
+        .path()
+        .to_str()
+        .ok_or_else(|| "Non-UTF-8 temp path".to_string())?;
+    let mut args = vec![
+        "issue",
+        "create",
+        "-R",
+        repo,
+        "--title",
+        title,
+        "--body-file",
+        body_path,
+    ];
+    for label in labels {
+        args.extend(["--label", label.as_str()]);
+    }
+    let output = run_gh(&args, "gh issue create")?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Fallback: fetch PR branch refs via gh, then use git diff against a local clone.
+fn get_pr_diff_via_git(repo: &str, pr_number: u32) -> Result<String, String> {
+    // Get head and base branch names from the PR metadata
+    let meta_output = operations::output(
+        Command::new("gh")
+            .args([
+                "pr",
+                "view",
+                "-R",
+                repo,
+                &pr_number.to_string(),
+                "--json",
+                "headRefName,baseRefName",
+            ])
+            .envs(gh_env()),
+    )
+    .map_err(|e| format!("Failed to execute gh pr view: {}", e))?;
+
+    if !meta_output.status.success() {
+        let stderr = String::from_utf8_lossy(&meta_output.stderr);
+        return Err(format!("gh pr view failed: {}", stderr));
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PrMeta {
+        head_ref_name: String,
+        base_ref_name: String,
+    }
+
+    let meta: PrMeta = serde_json::from_slice(&meta_output.stdout)
+        .map_err(|e| format!("Failed to parse PR metadata: {}", e))?;
+
+    // Clone (shallow, bare) into a temp dir and diff
+    let temp_dir = tempfile::tempdir().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let clone_path = temp_dir.path().join("repo");
+
+    let clone_output = operations::output(
+        Command::new("gh")
+            .args([
+                "repo",
+                "clone",
+                repo,
+                clone_path
+                    .to_str()
+                    .ok_or_else(|| "Non-UTF-8 temp path".to_string())?,
+                "--",
+                "--bare",
+                "--filter=blob:none",
+            ])
+            .envs(gh_env()),
+    )
+    .map_err(|e| format!("Failed to clone repo: {}", e))?;
+
+    if !clone_output.status.success() {
+        return Err("Failed to clone repository for large diff fallback.".to_string());
+    }
+
+    let clone_str = clone_path
+        .to_str()
+        .ok_or_else(|| "Non-UTF-8 clone path".to_string())?;
+
+    // Fetch both branches explicitly (bare clone may not have all refs)
+    let _ = operations::output(Command::new("git").args([
+        "-C",
+        clone_str,
+        "fetch",
+        "origin",
+        &format!(
+            "+refs/heads/{}:refs/heads/{} +refs/heads/{}:refs/heads/{}",
+            meta.base_ref_name, meta.base_ref_name, meta.head_ref_name, meta.head_ref_name
+        ),
+    ]));
+
+    let diff_output = operations::output(Command::new("git").args([
+        "-C",
+        clone_str,
+        "diff",
+        &format!("{}...{}", meta.base_ref_name, meta.head_ref_name),
+    ]))
+    .map_err(|e| format!("git diff failed: {}", e))?;
+
+    if !diff_output.status.success() {
+        let stderr = String::from_utf8_lossy(&diff_output.stderr);
+        return Err(format!("git diff failed for large PR: {}", stderr));
+    }
+
+    Ok(String::from_utf8_lossy(&diff_output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_prs_by_subproject() {
+        let json = r#"[
+          {"number": 1, "title": "API fix", "url": "u1", "files": [{"path": "services/api/main.go"}]},
+          {"number": 2, "title": "Web tweak", "url": "u2", "files": [{"path": "web/app.ts"}]},
+          {"number": 3, "title": "Look-alike", "url": "u3", "files": [{"path": "services/api-old/x"}]}
+        ]"#;
+        let items: Vec<PrListItem> = serde_json::from_str(json).unwrap();
+        let files: Vec<PrFiles> = serde_json::from_str(json).unwrap();
+        let kept: Vec<u64> = filter_by_subproject(items, files, "services/api")
+            .iter()
+            .map(|pr| pr.number)
+            .collect();
+        assert_eq!(kept, vec![1]);
+    }
+
+    #[test]
+    fn gh_failures_are_typed() {
+        assert_eq!(
+            failure_error("To get started, please run:  gh auth login", "gh pr list").kind(),
+            "notAuthenticated"
+        );
+        assert_eq!(failure_error("HTTP 502", "gh pr list").kind(), "other");
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert_eq!(spawn_error(&missing).kind(), "notInstalled");
+        assert_eq!(validate_repo("noslash").unwrap_err().kind(), "invalidInput");
+    }
+
+    #[test]
+    fn parses_pr_refs() {
+        assert_eq!(parse_pr_ref("o/r#12").unwrap(), ("o/r".to_string(), 12));
+        assert_eq!(
+            parse_pr_ref("https://github.com/o/r/pull/7/files?w=1").unwrap(),
+            ("o/r".to_string(), 7)
+        );
+        assert!(parse_pr_ref("o/r").is_err());
+        assert!(parse_pr_ref("o/r#x").is_err());
+        assert!(parse_pr_ref("https://github.com/o/r/issues/7").is_err());
+    }
+
+    #[test]
+    fn validate_repo_valid() {
+        assert!(validate_repo("owner/repo").is_ok());
+    }
+
+    #[test]
+    fn validate_repo_no_slash() {
+        assert!(validate_repo("noslash").is_err());
+    }
+
+    #[test]
+    fn validate_repo_empty_owner() {
+        assert!(validate_repo("/repo").is_err());
+    }
+
+    #[test]
+    fn validate_repo_empty_name() {
+        assert!(validate_repo("owner/").is_err());
+    }
+
+    #[test]
+    fn validate_repo_whitespace_in_parts() {
+        assert!(validate_repo("ow ner/repo").is_err());
+        assert!(validate_repo("owner/re po").is_err());
+    }
+
+    #[test]
+    fn validate_repo_too_many_slashes() {
+        assert!(validate_repo("a/b/c").is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::gh::{run_gh, validate_repo};
+
+/// Files checked in order; the first one found on the default branch is used.
+const GUIDELINE_PATHS: &[&str] = &[
+    "REVIEW.md",
+    ".github/REVIEW.md",
+    "CONTRIBUTING.md",
+    ".github/CONTRIBUTING.md",
+    "docs/CONTRIBUTING.md",
+];
+
+/// Upper bound for the condensed guidelines injected into prompts.
+pub const MAX_GUIDELINES_CHARS: usize = 3000;
+
+fn is_list_item(line: &str) -> bool {
+    if line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ") {
+        return true;
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && line[digits..].starts_with(". ")
+}
+
+fn first_sentence(line: &str) -> &str {
+    match line.find(". ") {
+        Some(i) => &line[..=i],
+        None => line,
+    }
+}
+
+/// Condense a guidelines document into headings, list items, and the first sentence of each
+/// paragraph, dropping code blocks, HTML, images, and tables, capped at `MAX_GUIDELINES_CHARS`.
+pub fn condense_guidelines(text: &str) -> String {
+    let mut out = String::new();
+    let mut in_code = false;
+    let mut in_paragraph = false;
+
+    for raw in text.lines() {
+        let line = raw.trim();
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code
+            || line.is_empty()
+            || line.starts_with('<')
+            || line.starts_with("![")
+            || line.starts_with('|')
+        {
+            in_paragraph = false;
+            continue;
+        }
+
+        let kept = if line.starts_with('#') || is_list_item(line) {
+            in_paragraph = false;
+            line
+        } else if in_paragraph {
+            continue;
+        } else {
+            in_paragraph = true;
+            first_sentence(line)
+        };
+        out.push_str(kept);
+        out.push('\n');
+    }
+
+    if out.len() > MAX_GUIDELINES_CHARS {
+        let end = out.floor_char_boundary(MAX_GUIDELINES_CHARS);
+        out.truncate(end);
+    }
+    out.trim_end().to_string()
+}
+
+pub fn fetch_review_guidelines(repo: &str) -> Result<Option<String>, String> {
+    validate_repo(repo)?;
+
+    for path in GUIDELINE_PATHS {
+        let endpoint = format!("repos/{}/contents/{}", repo, path);
+        match run_gh(
+            &["api", &endpoint, "-H", "Accept: application/vnd.github.raw"],
+            "gh api",
+        ) {
+            Ok(output) => {
+                let text = String::from_utf8_lossy(&output.stdout);
+                let condensed = condense_guidelines(&text);
+                if !condensed.is_empty() {
+                    return Ok(Some(format!("(from {})\n{}", path, condensed)));
+                }
+            }
+            Err(e) if e.message().contains("Not Found") || e.message().contains("404") => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn condense_keeps_headings_lists_and_first_sentences() {
+        let text = "# Contributing\n\nThanks for helping. We review every PR.\nSecond line.\n\n\
+                    ## Style\n- Run `cargo fmt`\n1. Add tests\n\n```sh\nmake lint\n```\n\
+                    <img src=\"x\">\n| a | b |\n";
+        let condensed = condense_guidelines(text);
+        assert_eq!(
+            condensed,
+            "# Contributing\nThanks for helping.\n## Style\n- Run `cargo fmt`\n1. Add tests"
+        );
+    }
+
+    #[test]
+    fn condense_caps_length() {
+        let text = "- rule\n".repeat(1000);
+        assert!(condense_guidelines(&text).len() <= MAX_GUIDELINES_CHARS);
+    }
+
+    #[test]
+    fn list_item_detection() {
+        assert!(is_list_item("- a"));
+        assert!(is_list_item("12. a"));
+        assert!(!is_list_item("2023 was"));
+        assert!(!is_list_item("-dash"));
+    }
+}
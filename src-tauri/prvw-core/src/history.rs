@@ -0,0 +1,271 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::codex_runner::{CodexOutput, RunError};
+use crate::error::PrvwError;
+use crate::provider::Job;
+use crate::types::{CodexRun, CodexRunSummary};
+
+/// Run records live outside `cache/` so clearing the cache keeps them, like the usage ledger.
+const HISTORY_DIR: &str = "history/runs";
+/// Runs kept on disk; the oldest are deleted beyond this.
+const MAX_RUNS: usize = 200;
+/// stdout/stderr are cut to their last this-many characters.
+const MAX_OUTPUT_CHARS: usize = 20_000;
+
+/// Set once at startup. Recording is a no-op until then (and in tests).
+static RUNS_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Tells apart runs of one kind started in the same millisecond, e.g. batched summaries.
+static RUN_SEQ: AtomicU64 = AtomicU64::new(0);
+
+pub fn init(app_data_dir: &Path) {
+    let _ = RUNS_DIR.set(app_data_dir.join(HISTORY_DIR));
+}
+
+/// Keep the tail of `text`, where errors and summaries end up. Returns whether it was cut.
+fn tail(text: &str) -> (String, bool) {
+    let count = text.chars().count();
+    if count <= MAX_OUTPUT_CHARS {
+        return (text.to_string(), false);
+    }
+    (text.chars().skip(count - MAX_OUTPUT_CHARS).collect(), true)
+}
+
+/// Run ids sort chronologically: zero-padded unix millis, then a process-wide sequence number,
+/// plus the run's kind.
+fn run_id(timestamp_ms: u128, seq: u64, kind: &str) -> String {
+    format!("{:015}-{:06}-{}", timestamp_ms, seq % 1_000_000, kind)
+}
+
+/// Ids may come from the frontend; only accept what `run_id` produces.
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// The kind of a run, taken from its output file name (`analysis.json` -> `analysis`).
+fn job_kind(job: &Job) -> String {
+    job.output_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| is_valid_id(s))
+        .unwrap_or("run")
+        .to_string()
+}
+
+fn write_run(dir: &Path, run: &CodexRun) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(run).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(format!("{}.json", run.id)), json).map_err(|e| e.to_string())
+}
+
+fn read_run(dir: &Path, id: &str) -> Option<CodexRun> {
+    let text = std::fs::read_to_string(dir.join(format!("{}.json", id))).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Run ids on disk, newest first.
+fn run_ids(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut ids: Vec<String> = entries
+        .flatten()
+        .filter_map(|e| {
+            e.file_name()
+                .to_str()
+                .and_then(|n| n.strip_suffix(".json"))
+                .map(String::from)
+        })
+        .collect();
+    ids.sort_unstable_by(|a, b| b.cmp(a));
+    ids
+}
+
+fn prune(dir: &Path, keep: usize) {
+    for id in run_ids(dir).into_iter().skip(keep) {
+        let _ = std::fs::remove_file(dir.join(format!("{}.json", id)));
+    }
+}
+
+/// Persist one provider invocation, successful or not. Best-effort like the usage ledger:
+/// failures are only logged. Returns the run id for attaching validation warnings later.
+pub fn record(
+    provider: &str,
+    job: &Job,
+    result: &Result<CodexOutput, RunError>,
+    elapsed: Duration,
+) -> Option<String> {
+    let dir = RUNS_DIR.get()?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let kind = job_kind(job);
+    let (args, stdout, stderr, model, error) = match result {
+        Ok(output) => (
+            output.args.clone(),
+            output.stdout.as_str(),
+            output.stderr.as_str(),
+            output.model_used.clone(),
+            None,
+        ),
+        Err(e) => (
+            Vec::new(),
+            "",
+            "",
+            job.model.clone().unwrap_or_default(),
+            Some(e.to_string()),
+        ),
+    };
+    let (stdout, stdout_cut) = tail(stdout);
+    let (stderr, stderr_cut) = tail(stderr);
+    let run = CodexRun {
+        id: run_id(
+            now.as_millis(),
+            RUN_SEQ.fetch_add(1, Ordering::Relaxed),
+            &kind,
+        ),
+        timestamp: now.as_secs(),
+        kind,
+        provider: provider.to_string(),
+        model,
+        args,
+        prompt: job.prompt.clone(),
+        elapsed_secs: elapsed.as_secs_f64(),
+        warnings: Vec::new(),
+        stdout,
+        stderr,
+        output_truncated: stdout_cut || stderr_cut,
+        error,
+    };
+    if let Err(e) = write_run(dir, &run) {
+        eprintln!("[history] failed to record run: {}", e);
+        return None;
+    }
+    prune(dir, MAX_RUNS);
+    Some(run.id)
+}
+
+/// Attach the validation warnings a command produced for the output of run `run_id`.
+pub fn attach_warnings(run_id: Option<&str>, warnings: &[String]) {
+    let (Some(dir), Some(id)) = (RUNS_DIR.get(), run_id) else {
+        return;
+    };
+    if warnings.is_empty() {
+        return;
+    }
+    if let Some(mut run) = read_run(dir, id) {
+        run.warnings.extend_from_slice(warnings);
+        if let Err(e) = write_run(dir, &run) {
+            eprintln!("[history] failed to attach warnings: {}", e);
+        }
+    }
+}
+
+fn summarize(run: CodexRun) -> CodexRunSummary {
+    CodexRunSummary {
+        id: run.id,
+        timestamp: run.timestamp,
+        kind: run.kind,
+        provider: run.provider,
+        model: run.model,
+        elapsed_secs: run.elapsed_secs,
+        warning_count: run.warnings.len(),
+        error: run.error,
+    }
+}
+
+/// Recorded runs, newest first (at most `limit`), without prompts and output.
+pub fn list_runs(app_data_dir: &Path, limit: usize) -> Vec<CodexRunSummary> {
+    let dir = app_data_dir.join(HISTORY_DIR);
+    run_ids(&dir)
+        .iter()
+        .filter_map(|id| read_run(&dir, id))
+        .take(limit)
+        .map(summarize)
+        .collect()
+}
+
+/// One recorded run with its arguments, prompt, warnings, and (truncated) output.
+pub fn get_run(app_data_dir: &Path, id: &str) -> Result<CodexRun, PrvwError> {
+    if !is_valid_id(id) {
+        return Err(PrvwError::InvalidInput(format!("Invalid run id: {}", id)));
+    }
+    read_run(&app_data_dir.join(HISTORY_DIR), id)
+        .ok_or_else(|| PrvwError::NotFound(format!("Run {} not found", id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: &str) -> CodexRun {
+        CodexRun {
+            id: id.to_string(),
+            timestamp: 1,
+            kind: "analysis".to_string(),
+            provider: "codex".to_string(),
+            model: "m".to_string(),
+            args: vec!["exec".to_string()],
+            prompt: "p".to_string(),
+            elapsed_secs: 1.0,
+            warnings: vec![],
+            stdout: String::new(),
+            stderr: String::new(),
+            output_truncated: false,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn tail_keeps_end_of_long_output() {
+        assert_eq!(tail("short"), ("short".to_string(), false));
+        let long = format!("{}end", "x".repeat(MAX_OUTPUT_CHARS));
+        let (kept, cut) = tail(&long);
+        assert!(cut);
+        assert_eq!(kept.chars().count(), MAX_OUTPUT_CHARS);
+        assert!(kept.ends_with("end"));
+    }
+
+    #[test]
+    fn run_ids_sort_newest_first_and_prune() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        for ms in [5_u128, 100, 20] {
+            write_run(tmp.path(), &run(&run_id(ms, 0, "analysis"))).expect("write");
+        }
+        assert_eq!(
+            run_ids(tmp.path()),
+            vec![
+                run_id(100, 0, "analysis"),
+                run_id(20, 0, "analysis"),
+                run_id(5, 0, "analysis")
+            ]
+        );
+        prune(tmp.path(), 2);
+        assert_eq!(run_ids(tmp.path()).len(), 2);
+        assert!(read_run(tmp.path(), &run_id(5, 0, "analysis")).is_none());
+    }
+
+    #[test]
+    fn same_millisecond_runs_get_distinct_ids() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        for seq in [7, 8] {
+            write_run(tmp.path(), &run(&run_id(42, seq, "summarize"))).expect("write");
+        }
+        assert_eq!(
+            run_ids(tmp.path()),
+            vec![run_id(42, 8, "summarize"), run_id(42, 7, "summarize")]
+        );
+    }
+
+    #[test]
+    fn rejects_path_like_ids() {
+        assert!(is_valid_id(&run_id(1, 0, "analysis")));
+        assert!(!is_valid_id("../secrets"));
+        assert!(!is_valid_id(""));
+    }
+}
@@ -0,0 +1,347 @@
+use std::fmt::Display;
+
+use crate::error::{self, PrvwError};
+use crate::provider::{self, RunError};
+
+use crate::settings;
+
+/// Languages backend messages are translated to; anything else gets English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Ja,
+}
+
+impl Locale {
+    /// A language as users write it in Settings: `ja`, `ja-JP`, `Japanese`, `日本語`, ...
+    pub fn parse(lang: &str) -> Option<Locale> {
+        let lang = lang.trim().to_lowercase();
+        let code = lang.split(['-', '_']).next().unwrap_or_default();
+        match code {
+            "en" | "english" => Some(Locale::En),
+            "ja" | "jp" | "japanese" | "日本語" => Some(Locale::Ja),
+            _ => None,
+        }
+    }
+}
+
+/// `uiLang` when set, else the analysis language when it is one we have messages for.
+fn resolve(ui_lang: Option<&str>, lang: Option<&str>) -> Locale {
+    ui_lang
+        .or(lang)
+        .and_then(Locale::parse)
+        .unwrap_or(Locale::En)
+}
+
+pub fn locale() -> Locale {
+    let settings = settings::current();
+    resolve(settings.ui_lang.as_deref(), settings.lang.as_deref())
+}
+
+/// User-facing error and status messages. Each `{}` is filled from the arguments in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    GhNotInstalled,
+    GhNotAuthenticated,
+    GitNotInstalled,
+    JjNotInstalled,
+    CodexNotInstalled,
+    CodexNotAuthenticated,
+    ClaudeNotInstalled,
+    ClaudeNotAuthenticated,
+    OpenAiKeyNotSet,
+    OpenAiKeyInvalid,
+    /// `{}`: what is missing, e.g. "Gitea/Forgejo token".
+    NotSet,
+    /// `{}`: the secret's label, in all `Secret*` messages; a second `{}` is the HTTP status
+    /// or the error.
+    SecretWorks,
+    SecretRejected,
+    SecretHttpStatus,
+    SecretCheckFailed,
+    DiffEmpty,
+    NoHunksToAnalyze,
+    NoHunksInGroup,
+    RunTimeout,
+    RunSchemaViolation,
+    RunEmptyOutput,
+    RunRateLimited,
+    RunCancelled,
+    RemedySchemaViolation,
+    RemedyEmptyOutput,
+    RemedyRateLimited,
+    RemedyNetwork,
+    FetchingDiff,
+    ParsingDiff,
+    RunningAnalysis,
+    Refining,
+    PreparingCheckout,
+    CarryingReview,
+    SubmittingReview,
+    /// `{}`: provider name.
+    WaitingFor,
+    Posting,
+}
+
+impl Msg {
+    fn text(self, locale: Locale) -> &'static str {
+        use Locale::{En, Ja};
+        match (self, locale) {
+            (Msg::GhNotInstalled, En) => {
+                "GitHub CLI (gh) is not installed. Please install it: https://cli.github.com/"
+            }
+            (Msg::GhNotInstalled, Ja) => {
+                "GitHub CLI (gh) がインストールされていません。インストールしてください: https://cli.github.com/"
+            }
+            (Msg::GhNotAuthenticated, En) => {
+                "GitHub CLI is not authenticated. Please run: gh auth login"
+            }
+            (Msg::GhNotAuthenticated, Ja) => {
+                "GitHub CLI が認証されていません。次を実行してください: gh auth login"
+            }
+            (Msg::GitNotInstalled, En) => {
+                "git is not installed. Please install it to use full-repo context."
+            }
+            (Msg::GitNotInstalled, Ja) => {
+                "git がインストールされていません。リポジトリ全体のコンテキストを使うにはインストールしてください。"
+            }
+            (Msg::JjNotInstalled, En) => {
+                "Jujutsu (jj) is not installed. Please install it: https://jj-vcs.github.io/jj/"
+            }
+            (Msg::JjNotInstalled, Ja) => {
+                "Jujutsu (jj) がインストールされていません。インストールしてください: https://jj-vcs.github.io/jj/"
+            }
+            (Msg::CodexNotInstalled, En) => {
+                "Codex CLI is not installed. Please install it: https://github.com/openai/codex"
+            }
+            (Msg::CodexNotInstalled, Ja) => {
+                "Codex CLI がインストールされていません。インストールしてください: https://github.com/openai/codex"
+            }
+            (Msg::CodexNotAuthenticated, En) => {
+                "Codex CLI is not authenticated. Please run: codex login"
+            }
+            (Msg::CodexNotAuthenticated, Ja) => {
+                "Codex CLI が認証されていません。次を実行してください: codex login"
+            }
+            (Msg::ClaudeNotInstalled, En) => {
+                "Claude Code CLI is not installed. Please install it: https://docs.anthropic.com/en/docs/claude-code"
+            }
+            (Msg::ClaudeNotInstalled, Ja) => {
+                "Claude Code CLI がインストールされていません。インストールしてください: https://docs.anthropic.com/en/docs/claude-code"
+            }
+            (Msg::ClaudeNotAuthenticated, En) => {
+                "Claude Code CLI is not authenticated. Please run: claude, then /login"
+            }
+            (Msg::ClaudeNotAuthenticated, Ja) => {
+                "Claude Code CLI が認証されていません。claude を起動して /login を実行してください"
+            }
+            (Msg::OpenAiKeyNotSet, En) => "OpenAI API key is not set. Add it in Settings.",
+            (Msg::OpenAiKeyNotSet, Ja) => {
+                "OpenAI API キーが設定されていません。設定画面で追加してください。"
+            }
+            (Msg::OpenAiKeyInvalid, En) => "OpenAI API key is invalid. Update it in Settings.",
+            (Msg::OpenAiKeyInvalid, Ja) => {
+                "OpenAI API キーが無効です。設定画面で更新してください。"
+            }
+            (Msg::NotSet, En) => "{} is not set. Add it in Settings.",
+            (Msg::NotSet, Ja) => "{} が設定されていません。設定画面で追加してください。",
+            (Msg::SecretWorks, En) => "{} works.",
+            (Msg::SecretWorks, Ja) => "{} は有効です。",
+            (Msg::SecretRejected, En) => "{} was rejected.",
+            (Msg::SecretRejected, Ja) => "{} は拒否されました。",
+            (Msg::SecretHttpStatus, En) => "Checking the {} returned HTTP {}.",
+            (Msg::SecretHttpStatus, Ja) => "{} の確認で HTTP {} が返されました。",
+            (Msg::SecretCheckFailed, En) => "Checking the {} failed: {}",
+            (Msg::SecretCheckFailed, Ja) => "{} の確認に失敗しました: {}",
+            (Msg::DiffEmpty, En) => "Diff is empty. The PR may have no changes.",
+            (Msg::DiffEmpty, Ja) => "差分が空です。この PR には変更がない可能性があります。",
+            (Msg::NoHunksToAnalyze, En) => "No hunks to analyze.",
+            (Msg::NoHunksToAnalyze, Ja) => "分析するハンクがありません。",
+            (Msg::NoHunksInGroup, En) => "No hunks found for this group.",
+            (Msg::NoHunksInGroup, Ja) => "このグループのハンクが見つかりません。",
+            (Msg::RunTimeout, En) => {
+                "{} timed out after {} minute(s) and was stopped. \
+                 Try again or increase the timeout in Settings."
+            }
+            (Msg::RunTimeout, Ja) => {
+                "{} が {} 分でタイムアウトしたため停止しました。\
+                 再実行するか、設定画面でタイムアウトを延ばしてください。"
+            }
+            (Msg::RunSchemaViolation, En) => {
+                "{} returned output that does not match the schema: {}"
+            }
+            (Msg::RunSchemaViolation, Ja) => "{} の出力がスキーマに一致しません: {}",
+            (Msg::RunEmptyOutput, En) => "{} did not produce output: {}",
+            (Msg::RunEmptyOutput, Ja) => "{} が出力を返しませんでした: {}",
+            (Msg::RunRateLimited, En) => "{} rate limit exceeded: {}",
+            (Msg::RunRateLimited, Ja) => "{} のレート制限に達しました: {}",
+            (Msg::RunCancelled, En) => "{} run cancelled.",
+            (Msg::RunCancelled, Ja) => "{} の実行をキャンセルしました。",
+            (Msg::RemedySchemaViolation, En) => {
+                "Retry, or pick another model or provider in Settings."
+            }
+            (Msg::RemedySchemaViolation, Ja) => {
+                "再実行するか、設定画面で別のモデルかプロバイダーを選んでください。"
+            }
+            (Msg::RemedyEmptyOutput, En) => {
+                "Retry; the run history has the provider's full output."
+            }
+            (Msg::RemedyEmptyOutput, Ja) => {
+                "再実行してください。プロバイダーの出力全体は実行履歴にあります。"
+            }
+            (Msg::RemedyRateLimited, En) => {
+                "Wait a minute before retrying, or switch provider in Settings."
+            }
+            (Msg::RemedyRateLimited, Ja) => {
+                "少し待ってから再実行するか、設定画面でプロバイダーを切り替えてください。"
+            }
+            (Msg::RemedyNetwork, En) => "Check the network connection and retry.",
+            (Msg::RemedyNetwork, Ja) => "ネットワーク接続を確認して再実行してください。",
+            (Msg::FetchingDiff, En) => "Fetching diff of {}#{}",
+            (Msg::FetchingDiff, Ja) => "{}#{} の差分を取得しています",
+            (Msg::ParsingDiff, En) => "Parsing diff",
+            (Msg::ParsingDiff, Ja) => "差分を解析しています",
+            (Msg::RunningAnalysis, En) => "Running intent analysis with {}",
+            (Msg::RunningAnalysis, Ja) => "{} で意図分析を実行しています",
+            (Msg::Refining, En) => "Refining \"{}\" with {}",
+            (Msg::Refining, Ja) => "「{}」を {} で再分析しています",
+            (Msg::PreparingCheckout, En) => "Preparing the PR checkout",
+            (Msg::PreparingCheckout, Ja) => "PR のチェックアウトを準備しています",
+            (Msg::CarryingReview, En) => "Carrying the review over",
+            (Msg::CarryingReview, Ja) => "レビューを引き継いでいます",
+            (Msg::SubmittingReview, En) => "Submitting review to {}#{}",
+            (Msg::SubmittingReview, Ja) => "{}#{} にレビューを送信しています",
+            (Msg::WaitingFor, En) => "Waiting for {}",
+            (Msg::WaitingFor, Ja) => "{} の応答を待っています",
+            (Msg::Posting, En) => "Posting to {}#{}",
+            (Msg::Posting, Ja) => "{}#{} に投稿しています",
+        }
+    }
+}
+
+/// `template` with each `{}` replaced by the next argument.
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    let mut out = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+/// `msg` in the configured interface language.
+pub fn t(msg: Msg, args: &[&dyn Display]) -> String {
+    fill(msg.text(locale()), args)
+}
+
+fn run_error_message(error: &RunError, locale: Locale) -> Option<String> {
+    let text = |msg: Msg, args: &[&dyn Display]| fill(msg.text(locale), args);
+    Some(match error {
+        RunError::Timeout { program, minutes } => text(Msg::RunTimeout, &[program, minutes]),
+        RunError::SchemaViolation { program, detail } => {
+            text(Msg::RunSchemaViolation, &[program, detail])
+        }
+        RunError::EmptyOutput { program, detail } => text(Msg::RunEmptyOutput, &[program, detail]),
+        RunError::RateLimited { program, detail } => text(Msg::RunRateLimited, &[program, detail]),
+        RunError::Cancelled { program } => text(Msg::RunCancelled, &[program]),
+        // Hints are worded where they are raised; provider output is passed on as is.
+        RunError::NotInstalled { .. }
+        | RunError::NotAuthenticated { .. }
+        | RunError::Unknown { .. } => return None,
+    })
+}
+
+fn remediation(error: &PrvwError, locale: Locale) -> Option<String> {
+    let msg = match error {
+        PrvwError::Run(RunError::SchemaViolation { .. }) => Msg::RemedySchemaViolation,
+        PrvwError::Run(RunError::EmptyOutput { .. }) => Msg::RemedyEmptyOutput,
+        PrvwError::Run(RunError::RateLimited { .. }) => Msg::RemedyRateLimited,
+        PrvwError::Network(_) => Msg::RemedyNetwork,
+        _ => return None,
+    };
+    Some(msg.text(locale).to_string())
+}
+
+/// Word provider run errors and command error remediations in the interface language too.
+pub fn init() {
+    provider::on_message(|error| match locale() {
+        Locale::En => None,
+        locale => run_error_message(error, locale),
+    });
+    error::on_remediation(|error| match locale() {
+        Locale::En => None,
+        locale => remediation(error, locale),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_the_locale() {
+        assert_eq!(resolve(None, None), Locale::En);
+        assert_eq!(resolve(None, Some("Japanese")), Locale::Ja);
+        assert_eq!(resolve(None, Some("ja-JP")), Locale::Ja);
+        assert_eq!(resolve(None, Some("French")), Locale::En);
+        assert_eq!(resolve(Some("en"), Some("ja")), Locale::En);
+        assert_eq!(resolve(Some("日本語"), None), Locale::Ja);
+    }
+
+    #[test]
+    fn fills_placeholders_in_order() {
+        assert_eq!(
+            fill(Msg::FetchingDiff.text(Locale::Ja), &[&"o/r", &7]),
+            "o/r#7 の差分を取得しています"
+        );
+        assert_eq!(
+            fill(Msg::ParsingDiff.text(Locale::En), &[&"x"]),
+            "Parsing diff"
+        );
+        assert_eq!(fill("{} and {}", &[&1]), "1 and ");
+    }
+
+    #[test]
+    fn english_run_errors_match_their_display() {
+        let errors = [
+            RunError::Timeout {
+                program: "codex",
+                minutes: 10,
+            },
+            RunError::RateLimited {
+                program: "codex",
+                detail: "429".to_string(),
+            },
+            RunError::Cancelled { program: "claude" },
+        ];
+        for error in errors {
+            assert_eq!(
+                run_error_message(&error, Locale::En),
+                Some(error.to_string())
+            );
+        }
+        assert_eq!(
+            run_error_message(&RunError::unknown("boom"), Locale::Ja),
+            None
+        );
+    }
+
+    #[test]
+    fn english_remediations_match_the_defaults() {
+        let errors = [
+            PrvwError::Run(RunError::EmptyOutput {
+                program: "codex",
+                detail: "no file".to_string(),
+            }),
+            PrvwError::Network("reset".to_string()),
+            PrvwError::NotFound("gone".to_string()),
+        ];
+        for error in errors {
+            assert_eq!(remediation(&error, Locale::En), error.remediation());
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::PrvwError;
+use crate::types::{ChecklistTemplate, CommentTemplate};
+
+/// Read a JSON file; a missing or unreadable one reads as `None`.
+pub fn load<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+}
+
+/// Write via a temp file and rename so a crash never leaves a truncated file. `what` names the
+/// data in errors, e.g. "session".
+pub fn save<T: Serialize + ?Sized>(path: &Path, value: &T, what: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", what, e))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)
+        .and_then(|()| fs::rename(&tmp, path))
+        .map_err(|e| format!("Failed to save {}: {}", what, e))
+}
+
+/// Remove a file that may not exist.
+pub fn remove(path: &Path, what: &str) -> Result<(), String> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to remove {}: {}", what, e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether a template scoped to `scope` (`None`: every repo) applies to `repo`.
+pub fn applies_to(scope: Option<&str>, repo: Option<&str>) -> bool {
+    match (scope, repo) {
+        (None, _) => true,
+        (Some(t), Some(r)) => t.eq_ignore_ascii_case(r),
+        (Some(_), None) => false,
+    }
+}
+
+/// An entry of a `TemplateStore`.
+pub trait Template: Serialize + DeserializeOwned + Clone {
+    fn id(&self) -> &str;
+    fn set_id(&mut self, id: String);
+    /// The repo the template is scoped to; `None` applies everywhere.
+    fn repo(&self) -> Option<&str>;
+}
+
+impl Template for ChecklistTemplate {
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+    fn repo(&self) -> Option<&str> {
+        self.repo.as_deref()
+    }
+}
+
+impl Template for CommentTemplate {
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+    fn repo(&self) -> Option<&str> {
+        self.repo.as_deref()
+    }
+}
+
+/// Templates saved as one JSON list in the app data dir. They are user data, kept outside
+/// `cache/` so clearing the cache keeps them.
+pub struct TemplateStore {
+    file: &'static str,
+    /// Ids are `<id_prefix><n>`.
+    id_prefix: char,
+    /// Names a template in errors, e.g. "checklist template".
+    label: &'static str,
+    /// Serializes read-modify-write cycles of the file.
+    lock: Mutex<()>,
+}
+
+impl TemplateStore {
+    pub const fn new(file: &'static str, id_prefix: char, label: &'static str) -> Self {
+        TemplateStore {
+            file,
+            id_prefix,
+            label,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn load<T: Template>(&self, app_data_dir: &Path) -> Vec<T> {
+        load(&app_data_dir.join(self.file)).unwrap_or_default()
+    }
+
+    /// All templates, or those applying to `repo` (global ones included).
+    pub fn list<T: Template>(&self, app_data_dir: &Path, repo: Option<&str>) -> Vec<T> {
+        let templates = self.load::<T>(app_data_dir);
+        match repo.map(str::trim) {
+            Some(repo) if !repo.is_empty() => templates
+                .into_iter()
+                .filter(|t| applies_to(t.repo(), Some(repo)))
+                .collect(),
+            _ => templates,
+        }
+    }
+
+    /// The template with `id`.
+    pub fn get<T: Template>(&self, app_data_dir: &Path, id: &str) -> Result<T, PrvwError> {
+        self.load::<T>(app_data_dir)
+            .into_iter()
+            .find(|t| t.id() == id)
+            .ok_or_else(|| self.unknown(id))
+    }
+
+    /// Create (empty id) or replace a template. Returns it as saved.
+    pub fn save<T: Template>(&self, app_data_dir: &Path, mut template: T) -> Result<T, PrvwError> {
+        let _guard = self.guard()?;
+        let mut templates = self.load::<T>(app_data_dir);
+        if template.id().is_empty() {
+            template.set_id(self.next_id(&templates));
+            templates.push(template.clone());
+        } else {
+            let existing = templates
+                .iter_mut()
+                .find(|t| t.id() == template.id())
+                .ok_or_else(|| self.unknown(template.id()))?;
+            *existing = template.clone();
+        }
+        save(&app_data_dir.join(self.file), &templates, self.label)?;
+        Ok(template)
+    }
+
+    pub fn delete<T: Template>(&self, app_data_dir: &Path, id: &str) -> Result<(), PrvwError> {
+        let _guard = self.guard()?;
+        let mut templates = self.load::<T>(app_data_dir);
+        let before = templates.len();
+        templates.retain(|t| t.id() != id);
+        if templates.len() == before {
+            return Err(self.unknown(id));
+        }
+        Ok(save(&app_data_dir.join(self.file), &templates, self.label)?)
+    }
+
+    /// Next free id of the form `<id_prefix><n>`.
+    pub fn next_id<T: Template>(&self, templates: &[T]) -> String {
+        let max = templates
+            .iter()
+            .filter_map(|t| t.id().strip_prefix(self.id_prefix)?.parse::<u32>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("{}{}", self.id_prefix, max + 1)
+    }
+
+    fn guard(&self) -> Result<std::sync::MutexGuard<'_, ()>, String> {
+        self.lock
+            .lock()
+            .map_err(|_| format!("Lock of {}s poisoned", self.label))
+    }
+
+    fn unknown(&self, id: &str) -> PrvwError {
+        PrvwError::NotFound(format!("Unknown {} '{}'", self.label, id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static STORE: TemplateStore = TemplateStore::new("templates.json", 'T', "checklist template");
+
+    fn template(id: &str, repo: Option<&str>) -> ChecklistTemplate {
+        ChecklistTemplate {
+            id: id.to_string(),
+            name: id.to_string(),
+            repo: repo.map(str::to_string),
+            items: vec!["x".to_string()],
+        }
+    }
+
+    #[test]
+    fn numbers_scopes_and_deletes_templates() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(
+            STORE.next_id(&[template("T2", None), template("x", None)]),
+            "T3"
+        );
+        let first = STORE.save(tmp.path(), template("", None)).unwrap();
+        let second = STORE
+            .save(tmp.path(), template("", Some("Org/App")))
+            .unwrap();
+        assert_eq!((first.id.as_str(), second.id.as_str()), ("T1", "T2"));
+        assert!(STORE.save(tmp.path(), template("T9", None)).is_err());
+
+        let ids = |repo| -> Vec<String> {
+            STORE
+                .list::<ChecklistTemplate>(tmp.path(), repo)
+                .into_iter()
+                .map(|t| t.id)
+                .collect()
+        };
+        assert_eq!(ids(Some("org/app")), vec!["T1", "T2"]);
+        assert_eq!(ids(Some("org/other")), vec!["T1"]);
+
+        STORE.delete::<ChecklistTemplate>(tmp.path(), "T1").unwrap();
+        assert!(STORE.delete::<ChecklistTemplate>(tmp.path(), "T1").is_err());
+        assert_eq!(ids(None), vec!["T2"]);
+    }
+}
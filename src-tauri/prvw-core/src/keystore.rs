@@ -0,0 +1,77 @@
+//! The OS keyring, which holds the secrets and the cache encryption key. Built without the
+//! `keyring` feature nothing is stored: reads find no entry and writes fail.
+
+#[cfg(feature = "keyring")]
+const SERVICE: &str = "prvw";
+
+#[cfg(feature = "keyring")]
+fn entry(user: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, user).map_err(|e| format!("Failed to access keyring: {}", e))
+}
+
+/// Read a value stored with `set_password`; None when there is no entry.
+#[cfg(feature = "keyring")]
+pub fn get_password(user: &str) -> Result<Option<String>, String> {
+    match entry(user)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(feature = "keyring")]
+pub fn set_password(user: &str, value: &str) -> Result<(), String> {
+    entry(user)?.set_password(value).map_err(|e| e.to_string())
+}
+
+/// Read a value stored with `set_secret`; None when there is no entry.
+#[cfg(feature = "keyring")]
+pub fn get_secret(user: &str) -> Result<Option<Vec<u8>>, String> {
+    match entry(user)?.get_secret() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(feature = "keyring")]
+pub fn set_secret(user: &str, value: &[u8]) -> Result<(), String> {
+    entry(user)?.set_secret(value).map_err(|e| e.to_string())
+}
+
+/// Delete an entry; deleting one that does not exist succeeds.
+#[cfg(feature = "keyring")]
+pub fn delete(user: &str) -> Result<(), String> {
+    match entry(user)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+const UNSUPPORTED: &str = "this build of prvw has no keyring support";
+
+#[cfg(not(feature = "keyring"))]
+pub fn get_password(_user: &str) -> Result<Option<String>, String> {
+    Ok(None)
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn set_password(_user: &str, _value: &str) -> Result<(), String> {
+    Err(UNSUPPORTED.to_string())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn get_secret(_user: &str) -> Result<Option<Vec<u8>>, String> {
+    Ok(None)
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn set_secret(_user: &str, _value: &[u8]) -> Result<(), String> {
+    Err(UNSUPPORTED.to_string())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn delete(_user: &str) -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use std::sync::LazyLock;
+
+use crate::types::{AnalysisResult, Hunk, LeftoverFinding};
+
+/// Checklist items added per group; the rest are summarized in one line.
+const MAX_CHECKLIST_ITEMS: usize = 5;
+/// Longest line excerpt kept in a finding.
+const MAX_EXCERPT_CHARS: usize = 120;
+
+/// Debugging statements that rarely belong in a merged change.
+static DEBUG_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r#"\bconsole\.(log|debug|trace|dir)\(|\bdebugger\s*;|\bdbg!\(|\be?println!\(\s*"[^"]*\{:#?\?\}|\bbreakpoint\(\)|\bpdb\.set_trace\(\)"#,
+    )
+    .expect("invalid debug regex")
+});
+
+static TODO_RE: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"\b(TODO|FIXME|XXX|HACK)\b").expect("invalid todo regex"));
+
+/// Comment bodies that read like code rather than prose.
+static CODE_LIKE_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r"^(let|const|var|fn|def|return|if|for|while|import|use|await|self\.|this\.)\b|[;{}]$|^\w[\w.]*\(.*\)$|^\w+\s*=[^=]",
+    )
+    .expect("invalid code-like regex")
+});
+
+/// Line comment prefix for a file, by extension. Only `#` languages where `#` is not syntax.
+pub fn comment_prefix(path: &str) -> Option<&'static str> {
+    let ext = path.rsplit_once('.').map(|(_, e)| e)?;
+    match ext {
+        "rs" | "ts" | "tsx" | "js" | "jsx" | "go" | "java" | "kt" | "swift" | "c" | "h" | "cpp"
+        | "cs" | "scala" | "dart" => Some("//"),
+        "py" | "rb" | "sh" | "bash" | "yml" | "yaml" | "toml" => Some("#"),
+        "sql" | "lua" => Some("--"),
+        _ => None,
+    }
+}
+
+/// Body of a plain line comment, or `None` for non-comments and doc comments.
+fn comment_body<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = text.trim_start().strip_prefix(prefix)?;
+    if prefix == "//" && (rest.starts_with('/') || rest.starts_with('!')) {
+        return None;
+    }
+    if prefix == "#" && rest.starts_with('!') {
+        return None;
+    }
+    Some(rest.trim())
+}
+
+/// Trimmed line text, shortened to `MAX_EXCERPT_CHARS` for display and prompts.
+pub fn excerpt(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Classify one added line. TODO markers win over commented-out code, so a
+/// `// TODO: fix()` comment is reported once.
+fn classify(text: &str, prefix: Option<&str>) -> Option<&'static str> {
+    if TODO_RE.is_match(text) {
+        return Some("todo");
+    }
+    if let Some(body) = prefix.and_then(|p| comment_body(text, p)) {
+        return CODE_LIKE_RE.is_match(body).then_some("commented-code");
+    }
+    DEBUG_RE.is_match(text).then_some("debug")
+}
+
+/// Scan the added lines of every hunk for debug statements, TODO/FIXME markers, and
+/// commented-out code. Purely textual, so it runs before (and independently of) analysis.
+pub fn scan_hunks(hunks: &[Hunk]) -> Vec<LeftoverFinding> {
+    let mut findings = Vec::new();
+    for hunk in hunks {
+        let prefix = comment_prefix(&hunk.file_path);
+        for line in hunk.lines.iter().filter(|l| l.kind == "add") {
+            if let Some(kind) = classify(&line.text, prefix) {
+                findings.push(LeftoverFinding {
+                    hunk_id: hunk.id.clone(),
+                    file_path: hunk.file_path.clone(),
+                    line: line.new_line,
+                    kind: kind.to_string(),
+                    text: excerpt(&line.text),
+                });
+            }
+        }
+    }
+    findings
+}
+
+fn checklist_item(finding: &LeftoverFinding) -> String {
+    let what = match finding.kind.as_str() {
+        "debug" => "Remove debug statement",
+        "todo" => "Resolve or track TODO",
+        _ => "Remove commented-out code",
+    };
+    let location = match finding.line {
+        Some(line) => format!("{}:{}", finding.file_path, line),
+        None => finding.file_path.clone(),
+    };
+    format!("{} at {}: `{}`", what, location, finding.text)
+}
+
+/// Append a reviewer checklist item to each group for the findings in its hunks.
+pub fn annotate_checklists(result: &mut AnalysisResult, findings: &[LeftoverFinding]) {
+    for group in &mut result.groups {
+        let in_group: Vec<&LeftoverFinding> = findings
+            .iter()
+            .filter(|f| group.hunk_ids.contains(&f.hunk_id))
+            .collect();
+        group.reviewer_checklist.extend(
+            in_group
+                .iter()
+                .take(MAX_CHECKLIST_ITEMS)
+                .copied()
+                .map(checklist_item),
+        );
+        if in_group.len() > MAX_CHECKLIST_ITEMS {
+            group.reviewer_checklist.push(format!(
+                "Check {} more debug leftover(s) / TODOs in this group",
+                in_group.len() - MAX_CHECKLIST_ITEMS
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DiffLine, IntentGroup};
+
+    fn hunk(id: &str, file_path: &str, lines: &[(&str, &str)]) -> Hunk {
+        Hunk {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            subproject: None,
+            header: String::new(),
+            old_start: 1,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 0,
+            lines: lines
+                .iter()
+                .enumerate()
+                .map(|(i, (kind, text))| DiffLine {
+                    kind: kind.to_string(),
+                    old_line: None,
+                    new_line: Some(i as u32 + 1),
+                    text: text.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn kinds(findings: &[LeftoverFinding]) -> Vec<(&str, Option<u32>)> {
+        findings.iter().map(|f| (f.kind.as_str(), f.line)).collect()
+    }
+
+    #[test]
+    fn detects_debug_statements_on_added_lines_only() {
+        let h = hunk(
+            "H1",
+            "src/app.ts",
+            &[
+                ("add", "  console.log(state);"),
+                ("remove", "  console.log(old);"),
+                ("context", "  console.debug(x);"),
+                ("add", "  logger.info(\"started\");"),
+                ("add", "  debugger;"),
+            ],
+        );
+        assert_eq!(
+            kinds(&scan_hunks(&[h])),
+            vec![("debug", Some(1)), ("debug", Some(5))]
+        );
+    }
+
+    #[test]
+    fn println_is_flagged_only_with_debug_formatting() {
+        let h = hunk(
+            "H1",
+            "src/main.rs",
+            &[
+                ("add", "    dbg!(&config);"),
+                ("add", "    println!(\"{:?}\", hunks);"),
+                ("add", "    println!(\"Listening on {}\", addr);"),
+            ],
+        );
+        assert_eq!(
+            kinds(&scan_hunks(&[h])),
+            vec![("debug", Some(1)), ("debug", Some(2))]
+        );
+    }
+
+    #[test]
+    fn detects_todos_and_commented_out_code() {
+        let h = hunk(
+            "H1",
+            "src/lib.rs",
+            &[
+                ("add", "    // TODO: handle the empty case"),
+                ("add", "    // let cached = read_cache(dir);"),
+                ("add", "    // Falls back to the default model."),
+                ("add", "    /// Returns the parsed diff."),
+                ("add", "    #[allow(dead_code)]"),
+            ],
+        );
+        assert_eq!(
+            kinds(&scan_hunks(&[h])),
+            vec![("todo", Some(1)), ("commented-code", Some(2))]
+        );
+    }
+
+    #[test]
+    fn hash_comments_only_in_hash_languages() {
+        let py = hunk("H1", "tool.py", &[("add", "# result = compute(x)")]);
+        let md = hunk("H2", "README.md", &[("add", "# result = compute(x)")]);
+        assert_eq!(
+            kinds(&scan_hunks(&[py, md])),
+            vec![("commented-code", Some(1))]
+        );
+    }
+
+    #[test]
+    fn annotates_owning_group_checklist() {
+        let findings = scan_hunks(&[hunk("H2", "src/a.ts", &[("add", "console.log(1);")])]);
+        let group = |id: &str, hunk_ids: &[&str]| IntentGroup {
+            id: id.to_string(),
+            title: id.to_string(),
+            category: String::new(),
+            rationale: String::new(),
+            risk: "low".to_string(),
+            risk_score: 0,
+            risk_evidence: vec![],
+            hunk_ids: hunk_ids.iter().map(ToString::to_string).collect(),
+            reviewer_checklist: vec![],
+            suggested_tests: vec![],
+        };
+        let mut result = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![group("G1", &["H1"]), group("G2", &["H2"])],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        };
+        annotate_checklists(&mut result, &findings);
+        assert!(result.groups[0].reviewer_checklist.is_empty());
+        assert_eq!(
+            result.groups[1].reviewer_checklist,
+            vec!["Remove debug statement at src/a.ts:1: `console.log(1);`"]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+pub mod cache;
+mod cache_crypto;
+pub mod checklists;
+pub mod claude_runner;
+pub mod codex;
+pub mod codex_runner;
+pub mod config;
+pub mod constraints;
+pub mod diff_parser;
+pub mod edits;
+pub mod error;
+pub mod error_reports;
+pub mod gh;
+pub mod guidelines;
+pub mod history;
+pub mod i18n;
+pub mod json_store;
+mod keystore;
+pub mod leftovers;
+pub mod local_range;
+pub mod manifest;
+pub mod openai_runner;
+pub mod operations;
+pub mod provider;
+pub mod push_hook;
+pub mod report;
+pub mod review_state;
+pub mod risky;
+pub mod secrets;
+pub mod session;
+pub mod settings;
+pub mod time;
+pub mod types;
+pub mod usage;
+pub mod validation;
+pub mod workspace;
@@ -0,0 +1,591 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use git2::{Commit, Delta, Diff, DiffDelta, DiffFindOptions, DiffFormat, Repository, Sort};
+
+use crate::cache;
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::types::{BranchComparison, ChangedFile, CloneRemotes, RangeCommit, RangeDiff};
+
+/// Commits listed in the range description; the diff itself is never truncated.
+const MAX_COMMITS: usize = 200;
+
+/// A diff of a local repo, computed in-process with libgit2 (no `git` on PATH needed).
+pub struct LocalDiff {
+    /// Unified diff text, for `parse_diff`.
+    pub patch: String,
+    pub files: Vec<ChangedFile>,
+    /// Changes whenever the diffed content does (hash of the blob ids and paths).
+    pub stamp: String,
+}
+
+fn git_err(e: &git2::Error) -> String {
+    format!("git: {}", e.message())
+}
+
+/// Open the repository containing `path`, and the top level of its working tree.
+pub fn open(path: &str) -> Result<(Repository, PathBuf), String> {
+    let dir = Path::new(path.trim());
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", dir.display()));
+    }
+    let repo =
+        Repository::discover(dir).map_err(|e| format!("Not a git repository: {}", e.message()))?;
+    // `workdir` ends with a separator; collecting the components drops it.
+    let root = repo
+        .workdir()
+        .ok_or_else(|| "Bare repositories have no working tree.".to_string())?
+        .components()
+        .collect();
+    Ok((repo, root))
+}
+
+/// `owner/name` of a GitHub remote URL (`git@github.com:o/r.git`, `https://github.com/o/r`).
+pub fn github_repo(url: &str) -> Option<String> {
+    let url = url.trim();
+    let path = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| url.strip_prefix("ssh://git@github.com/"))
+        .or_else(|| url.strip_prefix("https://github.com/"))?;
+    let repo = path.trim_end_matches('/').trim_end_matches(".git");
+    validate_repo(repo).ok()?;
+    Some(repo.to_string())
+}
+
+fn remote_repo(repo: &Repository, name: &str) -> Option<String> {
+    github_repo(repo.find_remote(name).ok()?.url()?)
+}
+
+/// The GitHub repos behind a clone's remotes. In a triangular (fork) workflow `origin` is the
+/// user's fork and `upstream` the repo PRs are opened against, so `upstream` wins when it is on
+/// GitHub. No GitHub remote just means no per-repo settings.
+pub fn clone_remotes(repo: &Repository) -> CloneRemotes {
+    prefer_upstream(remote_repo(repo, "origin"), remote_repo(repo, "upstream"))
+}
+
+/// `clone_remotes` from the GitHub repos of the `origin` and `upstream` remotes.
+pub fn prefer_upstream(origin: Option<String>, upstream: Option<String>) -> CloneRemotes {
+    match upstream {
+        Some(upstream) => CloneRemotes {
+            fork: origin.filter(|o| !o.eq_ignore_ascii_case(&upstream)),
+            repo: Some(upstream),
+            remote: Some("upstream".to_string()),
+        },
+        None => CloneRemotes {
+            remote: origin.is_some().then(|| "origin".to_string()),
+            repo: origin,
+            fork: None,
+        },
+    }
+}
+
+/// Name of the remote of `repo` pointing at the GitHub repo `slug`, if any.
+pub fn remote_for(repo: &Repository, slug: &str) -> Option<String> {
+    let names = repo.remotes().ok()?;
+    names
+        .iter()
+        .flatten()
+        .find(|name| remote_repo(repo, name).is_some_and(|r| r.eq_ignore_ascii_case(slug)))
+        .map(str::to_string)
+}
+
+fn changed_file(delta: &DiffDelta<'_>) -> ChangedFile {
+    let old_path = delta
+        .old_file()
+        .path()
+        .map(|p| p.to_string_lossy().into_owned());
+    let new_path = delta
+        .new_file()
+        .path()
+        .map(|p| p.to_string_lossy().into_owned());
+    let status = match delta.status() {
+        Delta::Added => "added",
+        Delta::Deleted => "deleted",
+        Delta::Renamed => "renamed",
+        Delta::Copied => "copied",
+        Delta::Typechange => "typechange",
+        _ => "modified",
+    };
+    ChangedFile {
+        path: new_path.or_else(|| old_path.clone()).unwrap_or_default(),
+        old_path: old_path.filter(|_| matches!(status, "renamed" | "copied")),
+        status: status.to_string(),
+        binary: delta.flags().is_binary(),
+    }
+}
+
+/// Detect renames (like `git diff -M`) and render the diff as patch text.
+fn render(diff: &mut Diff<'_>) -> Result<LocalDiff, String> {
+    let mut find = DiffFindOptions::new();
+    find.renames(true);
+    diff.find_similar(Some(&mut find))
+        .map_err(|e| git_err(&e))?;
+
+    let mut stamp = String::new();
+    for delta in diff.deltas() {
+        let _ = writeln!(
+            stamp,
+            "{} {} {:?}",
+            delta.old_file().id(),
+            delta.new_file().id(),
+            delta.new_file().path()
+        );
+    }
+
+    let mut patch: Vec<u8> = Vec::new();
+    // Whether a file is binary is only known once its content is loaded, i.e. while printing.
+    let mut binary: HashSet<String> = HashSet::new();
+    diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+        if line.origin() == 'B' || delta.flags().is_binary() {
+            binary.insert(changed_file(&delta).path);
+        }
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            patch.push(line.origin() as u8);
+        }
+        patch.extend_from_slice(line.content());
+        true
+    })
+    .map_err(|e| git_err(&e))?;
+
+    let files = diff
+        .deltas()
+        .map(|delta| {
+            let mut file = changed_file(&delta);
+            file.binary |= binary.contains(&file.path);
+            file
+        })
+        .collect();
+    Ok(LocalDiff {
+        patch: String::from_utf8_lossy(&patch).into_owned(),
+        files,
+        stamp: cache::hash_key(&stamp),
+    })
+}
+
+/// The staged changes: HEAD against the index (like `git diff --cached -M`). The index is
+/// re-read from disk, so a long-lived `repo` sees changes made by other processes.
+pub fn staged(repo: &Repository) -> Result<LocalDiff, String> {
+    // On an unborn branch there is no HEAD tree: everything staged is new.
+    let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let mut index = repo.index().map_err(|e| git_err(&e))?;
+    index.read(false).map_err(|e| git_err(&e))?;
+    let mut diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), Some(&index), None)
+        .map_err(|e| git_err(&e))?;
+    render(&mut diff)
+}
+
+#[derive(Debug, PartialEq)]
+enum RevRange<'a> {
+    /// One commit, diffed against its first parent.
+    Single(&'a str),
+    /// `from..to`, or `from...to` (from their merge base) when `symmetric`. An empty side is HEAD.
+    Range {
+        from: &'a str,
+        to: &'a str,
+        symmetric: bool,
+    },
+}
+
+/// Check a revision range (`main..feature`, `main...feature`, `HEAD~5..HEAD`) or single commit.
+fn parse_range(range: &str) -> Result<RevRange<'_>, PrvwError> {
+    let range = range.trim();
+    if range.is_empty() {
+        return Err(PrvwError::InvalidInput(
+            "Revision range is empty.".to_string(),
+        ));
+    }
+    if range.starts_with('-') || range.chars().any(char::is_whitespace) {
+        return Err(PrvwError::InvalidInput(format!(
+            "Invalid revision range '{}'",
+            range
+        )));
+    }
+    let (sides, symmetric): (Vec<&str>, bool) = if range.contains("...") {
+        (range.split("...").collect(), true)
+    } else {
+        (range.split("..").collect(), false)
+    };
+    match sides.as_slice() {
+        [single] => Ok(RevRange::Single(single)),
+        [from, to] if !(from.is_empty() && to.is_empty()) => Ok(RevRange::Range {
+            from,
+            to,
+            symmetric,
+        }),
+        _ => Err(PrvwError::InvalidInput(format!(
+            "Invalid revision range '{}'",
+            range
+        ))),
+    }
+}
+
+fn resolve<'r>(repo: &'r Repository, rev: &str) -> Result<Commit<'r>, String> {
+    let rev = if rev.is_empty() { "HEAD" } else { rev };
+    repo.revparse_single(rev)
+        .and_then(|o| o.peel_to_commit())
+        .map_err(|e| format!("Unknown revision '{}': {}", rev, e.message()))
+}
+
+/// The diff of a revision range and its commits, newest first.
+pub fn range_diff(
+    repo: &Repository,
+    range: &str,
+) -> Result<(LocalDiff, Vec<RangeCommit>), PrvwError> {
+    let (base, to, commits) = match parse_range(range)? {
+        RevRange::Single(rev) => {
+            let commit = resolve(repo, rev)?;
+            (commit.parent(0).ok(), commit.clone(), vec![commit])
+        }
+        RevRange::Range {
+            from,
+            to,
+            symmetric,
+        } => {
+            let from = resolve(repo, from)?;
+            let to = resolve(repo, to)?;
+            let mut walk = repo.revwalk().map_err(|e| git_err(&e))?;
+            walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+                .map_err(|e| git_err(&e))?;
+            walk.push(to.id()).map_err(|e| git_err(&e))?;
+            let base = if symmetric {
+                let merge_base = repo
+                    .merge_base(from.id(), to.id())
+                    .map_err(|e| git_err(&e))?;
+                walk.push(from.id()).map_err(|e| git_err(&e))?;
+                walk.hide(merge_base).map_err(|e| git_err(&e))?;
+                repo.find_commit(merge_base).map_err(|e| git_err(&e))?
+            } else {
+                walk.hide(from.id()).map_err(|e| git_err(&e))?;
+                from
+            };
+            let commits = walk
+                .map(|oid| oid.and_then(|oid| repo.find_commit(oid)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| git_err(&e))?;
+            (Some(base), to, commits)
+        }
+    };
+
+    let old_tree = match &base {
+        Some(commit) => Some(commit.tree().map_err(|e| git_err(&e))?),
+        None => None,
+    };
+    let new_tree = to.tree().map_err(|e| git_err(&e))?;
+    let mut diff = repo
+        .diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)
+        .map_err(|e| git_err(&e))?;
+    let commits = commits
+        .iter()
+        .map(|c| RangeCommit {
+            sha: c.id().to_string(),
+            subject: c.summary().unwrap_or_default().to_string(),
+        })
+        .collect();
+    Ok((render(&mut diff)?, commits))
+}
+
+/// Commit subjects as a Markdown list, standing in for the PR description.
+pub fn describe(range: &str, commits: &[RangeCommit]) -> String {
+    let mut md = format!("Changes in `{}`:\n\n", range);
+    for commit in commits.iter().take(MAX_COMMITS) {
+        md.push_str(&format!(
+            "- {} ({})\n",
+            commit.subject,
+            commit.sha.get(..7).unwrap_or(&commit.sha)
+        ));
+    }
+    if commits.len() > MAX_COMMITS {
+        md.push_str(&format!(
+            "- … and {} more commits\n",
+            commits.len() - MAX_COMMITS
+        ));
+    }
+    md
+}
+
+/// Check a branch, tag or commit name given on its own (not a range).
+fn check_ref(name: &str) -> Result<&str, PrvwError> {
+    let name = name.trim();
+    if name.is_empty() || name.starts_with('-') || name.contains("..") {
+        return Err(PrvwError::InvalidInput(format!("Invalid ref '{}'", name)));
+    }
+    Ok(name)
+}
+
+/// Compare `head` against `base` like `git diff base...head`, with ahead/behind counts.
+pub fn compare_refs(
+    repo: &Repository,
+    base: &str,
+    head: &str,
+) -> Result<BranchComparison, PrvwError> {
+    let base = check_ref(base)?;
+    let head = check_ref(head)?;
+    let base_commit = resolve(repo, base)?;
+    let head_commit = resolve(repo, head)?;
+    let merge_base = repo
+        .merge_base(base_commit.id(), head_commit.id())
+        .map_err(|_| format!("'{}' and '{}' have no common history", base, head))?;
+    let (ahead, behind) = repo
+        .graph_ahead_behind(head_commit.id(), base_commit.id())
+        .map_err(|e| git_err(&e))?;
+    let (diff, commits) = range_diff(repo, &format!("{}..{}", merge_base, head_commit.id()))?;
+    if diff.files.is_empty() {
+        return Err(PrvwError::InvalidInput(format!(
+            "'{}' has no changes since it diverged from '{}'",
+            head, base
+        )));
+    }
+
+    let remotes = clone_remotes(repo);
+    let range = format!("{}...{}", base, head);
+    let mut description = describe(&range, &commits);
+    description.push_str(&format!(
+        "\n`{}` is {} commit{} ahead of and {} commit{} behind `{}` (merge base {}).\n",
+        head,
+        ahead,
+        if ahead == 1 { "" } else { "s" },
+        behind,
+        if behind == 1 { "" } else { "s" },
+        base,
+        merge_base.to_string().get(..7).unwrap_or_default()
+    ));
+    Ok(BranchComparison {
+        diff: RangeDiff {
+            repo_path: String::new(),
+            range,
+            repo: remotes.repo,
+            fork: remotes.fork,
+            description,
+            commits,
+            files: diff.files,
+            diff: diff.patch,
+        },
+        base: base.to_string(),
+        head: head.to_string(),
+        merge_base: merge_base.to_string(),
+        ahead,
+        behind,
+    })
+}
+
+/// The diff of `range` in the clone at `repo_path`, with its commits and the clone's remotes.
+pub fn get_range_diff(repo_path: &str, range: &str) -> Result<RangeDiff, PrvwError> {
+    let (repo, root) = open(repo_path)?;
+    let (diff, commits) = range_diff(&repo, range)?;
+    if diff.files.is_empty() {
+        return Err(PrvwError::NotFound(format!(
+            "No changes in '{}'",
+            range.trim()
+        )));
+    }
+    let remotes = clone_remotes(&repo);
+    Ok(RangeDiff {
+        repo_path: root.display().to_string(),
+        range: range.trim().to_string(),
+        repo: remotes.repo,
+        fork: remotes.fork,
+        description: describe(range.trim(), &commits),
+        commits,
+        files: diff.files,
+        diff: diff.patch,
+    })
+}
+
+/// Three-dot diff of `head` against `base` in the clone at `repo_path`; see `compare_refs`.
+pub fn compare_branches(
+    repo_path: &str,
+    base: &str,
+    head: &str,
+) -> Result<BranchComparison, PrvwError> {
+    let (repo, root) = open(repo_path)?;
+    let mut comparison = compare_refs(&repo, base, head)?;
+    comparison.diff.repo_path = root.display().to_string();
+    Ok(comparison)
+}
+
+/// `clone_remotes` of the clone at `repo_path`.
+pub fn get_clone_remotes(repo_path: &str) -> Result<CloneRemotes, PrvwError> {
+    let (repo, _) = open(repo_path)?;
+    Ok(clone_remotes(&repo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{IndexAddOption, Signature};
+
+    fn stage_all(repo: &Repository) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"].iter(), IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.update_all(["*"].iter(), None).unwrap();
+        index.write().unwrap();
+    }
+
+    fn commit_all(repo: &Repository, message: &str) {
+        stage_all(repo);
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap();
+    }
+
+    #[test]
+    fn validates_ranges() {
+        assert_eq!(
+            parse_range(" main..feature "),
+            Ok(RevRange::Range {
+                from: "main",
+                to: "feature",
+                symmetric: false
+            })
+        );
+        assert_eq!(
+            parse_range("main...feature"),
+            Ok(RevRange::Range {
+                from: "main",
+                to: "feature",
+                symmetric: true
+            })
+        );
+        assert_eq!(parse_range("abc123"), Ok(RevRange::Single("abc123")));
+        assert!(parse_range("").is_err());
+        assert!(parse_range("--output=x").is_err());
+        assert!(parse_range("a b").is_err());
+        assert!(parse_range("a..b..c").is_err());
+        assert!(parse_range("..").is_err());
+    }
+
+    #[test]
+    fn diffs_ranges_and_staged_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        let lines: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(tmp.path().join("a.txt"), &lines).unwrap();
+        commit_all(&repo, "Add a");
+        std::fs::write(
+            tmp.path().join("a.txt"),
+            lines.replace("line 3\n", "three\n"),
+        )
+        .unwrap();
+        commit_all(&repo, "Edit a");
+
+        let (diff, commits) = range_diff(&repo, "HEAD~1..HEAD").unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].subject, "Edit a");
+        assert!(diff.patch.contains("diff --git a/a.txt b/a.txt"));
+        assert!(diff.patch.contains("-line 3\n+three\n"));
+        let (first, _) = range_diff(&repo, "HEAD~1").unwrap();
+        assert_eq!(first.files[0].status, "added");
+        assert!(staged(&repo).unwrap().files.is_empty());
+
+        std::fs::rename(tmp.path().join("a.txt"), tmp.path().join("b.txt")).unwrap();
+        std::fs::write(tmp.path().join("logo.png"), [0u8, 159, 146, 150, 0, 1]).unwrap();
+        stage_all(&repo);
+        let staged = staged(&repo).unwrap();
+        let files: Vec<(&str, Option<&str>, &str, bool)> = staged
+            .files
+            .iter()
+            .map(|f| {
+                (
+                    f.path.as_str(),
+                    f.old_path.as_deref(),
+                    f.status.as_str(),
+                    f.binary,
+                )
+            })
+            .collect();
+        assert!(files.contains(&("b.txt", Some("a.txt"), "renamed", false)));
+        assert!(files.contains(&("logo.png", None, "added", true)));
+    }
+
+    #[test]
+    fn compares_diverged_branches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        std::fs::write(tmp.path().join("a.txt"), "one\n").unwrap();
+        commit_all(&repo, "Add a");
+        let root = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.branch("feature", &root, false).unwrap();
+        std::fs::write(tmp.path().join("a.txt"), "one\ntwo\n").unwrap();
+        commit_all(&repo, "Extend a on main");
+        let main = repo.head().unwrap().name().unwrap().to_string();
+
+        repo.set_head("refs/heads/feature").unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+        std::fs::write(tmp.path().join("b.txt"), "bee\n").unwrap();
+        commit_all(&repo, "Add b");
+        std::fs::write(tmp.path().join("b.txt"), "bee\nsting\n").unwrap();
+        commit_all(&repo, "Extend b");
+
+        let comparison = compare_refs(&repo, &main, "feature").unwrap();
+        assert_eq!((comparison.ahead, comparison.behind), (2, 1));
+        assert_eq!(comparison.merge_base, root.id().to_string());
+        let paths: Vec<&str> = comparison
+            .diff
+            .files
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["b.txt"]);
+        let subjects: Vec<&str> = comparison
+            .diff
+            .commits
+            .iter()
+            .map(|c| c.subject.as_str())
+            .collect();
+        assert_eq!(subjects, vec!["Extend b", "Add b"]);
+        assert!(comparison
+            .diff
+            .description
+            .contains("is 2 commits ahead of and 1 commit behind"));
+
+        assert!(compare_refs(&repo, "feature", "feature").is_err());
+        assert!(compare_refs(&repo, "main..x", "feature").is_err());
+    }
+
+    #[test]
+    fn reads_github_remotes_and_commits() {
+        assert_eq!(
+            github_repo("git@github.com:owner/repo.git\n").as_deref(),
+            Some("owner/repo")
+        );
+        assert_eq!(
+            github_repo("https://github.com/owner/repo").as_deref(),
+            Some("owner/repo")
+        );
+        assert_eq!(github_repo("https://gitlab.com/owner/repo"), None);
+
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        repo.remote("origin", "git@github.com:me/repo.git").unwrap();
+        let remotes = clone_remotes(&repo);
+        assert_eq!(remotes.repo.as_deref(), Some("me/repo"));
+        assert_eq!(remotes.fork, None);
+        repo.remote("upstream", "https://github.com/owner/repo.git")
+            .unwrap();
+        let remotes = clone_remotes(&repo);
+        assert_eq!(remotes.repo.as_deref(), Some("owner/repo"));
+        assert_eq!(remotes.remote.as_deref(), Some("upstream"));
+        assert_eq!(remotes.fork.as_deref(), Some("me/repo"));
+        assert_eq!(remote_for(&repo, "Owner/Repo").as_deref(), Some("upstream"));
+        assert_eq!(remote_for(&repo, "other/repo"), None);
+
+        let commits = vec![RangeCommit {
+            sha: "0123456789abcdef".to_string(),
+            subject: "Fix parser".to_string(),
+        }];
+        assert_eq!(
+            describe("a..b", &commits),
+            "Changes in `a..b`:\n\n- Fix parser (0123456)\n"
+        );
+    }
+}
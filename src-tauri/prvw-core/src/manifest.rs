@@ -0,0 +1,445 @@
+use std::collections::BTreeMap;
+
+use crate::types::{DependencyChange, Hunk};
+
+/// Cargo.toml keys that are package metadata rather than dependencies.
+const CARGO_NON_DEP_KEYS: &[&str] = &[
+    "name",
+    "version",
+    "edition",
+    "authors",
+    "description",
+    "license",
+    "rust-version",
+    "readme",
+    "repository",
+    "homepage",
+    "documentation",
+    "keywords",
+    "categories",
+    "publish",
+    "build",
+    "resolver",
+    "members",
+    "exclude",
+    "include",
+    "default-run",
+];
+
+const PACKAGE_JSON_DEP_SECTIONS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/// Upper bound for the dependency summary injected into the analysis prompt.
+pub const MAX_SUMMARY_CHARS: usize = 2000;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Manifest {
+    CargoToml,
+    CargoLock,
+    PackageJson,
+}
+
+fn manifest_kind(path: &str) -> Option<Manifest> {
+    match path.rsplit('/').next()? {
+        "Cargo.toml" => Some(Manifest::CargoToml),
+        "Cargo.lock" => Some(Manifest::CargoLock),
+        "package.json" => Some(Manifest::PackageJson),
+        _ => None,
+    }
+}
+
+/// Lockfile hunks are pure noise once summarized.
+pub fn is_lockfile(path: &str) -> bool {
+    manifest_kind(path) == Some(Manifest::CargoLock)
+}
+
+/// Old and new versions seen for one dependency in one manifest.
+#[derive(Default)]
+struct Versions {
+    old: Option<String>,
+    new: Option<String>,
+}
+
+/// Split `key = value` (TOML) or `"key": value` (JSON), returning the trimmed key and value.
+fn split_entry(line: &str, manifest: Manifest) -> Option<(&str, &str)> {
+    let line = line.trim().trim_end_matches(',');
+    let (key, value) = match manifest {
+        Manifest::PackageJson => line.split_once(':')?,
+        _ => line.split_once('=')?,
+    };
+    let key = key.trim().trim_matches('"');
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((key, value.trim()))
+}
+
+fn unquote(value: &str) -> Option<&str> {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .filter(|v| !v.is_empty())
+}
+
+/// Version of a Cargo.toml dependency line: `"1.0"` or `{ version = "1.0", ... }`.
+/// Path/git-only dependencies are reported with their source instead.
+fn cargo_dep_version(value: &str) -> Option<String> {
+    if let Some(v) = unquote(value) {
+        return Some(v.to_string());
+    }
+    let inner = value.strip_prefix('{')?.strip_suffix('}')?;
+    let mut fallback = None;
+    for part in inner.split(',') {
+        let Some((k, v)) = part.split_once('=') else {
+            continue;
+        };
+        let Some(v) = unquote(v.trim()) else {
+            continue;
+        };
+        match k.trim() {
+            "version" => return Some(v.to_string()),
+            "path" | "git" => fallback = Some(format!("{} {}", k.trim(), v)),
+            _ => {}
+        }
+    }
+    fallback.or_else(|| Some("*".to_string()))
+}
+
+fn looks_like_version_spec(spec: &str) -> bool {
+    spec.starts_with(|c: char| c.is_ascii_digit() || "^~*<>=".contains(c))
+        || ["npm:", "workspace:", "file:", "link:", "git", "http"]
+            .iter()
+            .any(|p| spec.starts_with(p))
+        || spec == "latest"
+}
+
+/// Record the version on the side(s) the diff line belongs to.
+fn record(versions: &mut BTreeMap<String, Versions>, name: &str, version: String, kind: &str) {
+    let entry = versions.entry(name.to_string()).or_default();
+    match kind {
+        "remove" => entry.old = entry.old.take().or(Some(version)),
+        "add" => entry.new = entry.new.take().or(Some(version)),
+        _ => {}
+    }
+}
+
+fn scan_cargo_toml(hunk: &Hunk, versions: &mut BTreeMap<String, Versions>) {
+    // `None` means the enclosing section is not visible in the hunk.
+    let mut in_deps: Option<bool> = None;
+    for line in &hunk.lines {
+        let text = line.text.trim();
+        if text.starts_with('[') {
+            let section = text.trim_matches(|c| c == '[' || c == ']');
+            in_deps = Some(section.ends_with("dependencies"));
+            continue;
+        }
+        if in_deps == Some(false) || text.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = split_entry(text, Manifest::CargoToml) else {
+            continue;
+        };
+        if in_deps.is_none() && CARGO_NON_DEP_KEYS.contains(&key) {
+            continue;
+        }
+        if let Some(version) = cargo_dep_version(value) {
+            record(versions, key, version, &line.kind);
+        }
+    }
+}
+
+fn scan_cargo_lock(hunk: &Hunk, versions: &mut BTreeMap<String, Versions>) {
+    // The package a version line belongs to, tracked separately for the old and new file.
+    let mut old_name: Option<String> = None;
+    let mut new_name: Option<String> = None;
+    for line in &hunk.lines {
+        let Some((key, value)) = split_entry(&line.text, Manifest::CargoLock) else {
+            if line.text.trim() == "[[package]]" {
+                match line.kind.as_str() {
+                    "remove" => old_name = None,
+                    "add" => new_name = None,
+                    _ => (old_name, new_name) = (None, None),
+                }
+            }
+            continue;
+        };
+        let Some(value) = unquote(value) else {
+            continue;
+        };
+        match (key, line.kind.as_str()) {
+            ("name", "remove") => old_name = Some(value.to_string()),
+            ("name", "add") => new_name = Some(value.to_string()),
+            ("name", _) => {
+                (old_name, new_name) = (Some(value.to_string()), Some(value.to_string()))
+            }
+            ("version", "remove") => {
+                if let Some(name) = &old_name {
+                    record(versions, name, value.to_string(), "remove");
+                }
+            }
+            ("version", "add") => {
+                if let Some(name) = &new_name {
+                    record(versions, name, value.to_string(), "add");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn scan_package_json(hunk: &Hunk, versions: &mut BTreeMap<String, Versions>) {
+    let mut in_deps: Option<bool> = None;
+    for line in &hunk.lines {
+        let text = line.text.trim();
+        if text.ends_with('{') {
+            let key = text
+                .trim_end_matches('{')
+                .trim()
+                .trim_end_matches(':')
+                .trim();
+            in_deps = Some(PACKAGE_JSON_DEP_SECTIONS.contains(&key.trim_matches('"')));
+            continue;
+        }
+        if text.starts_with('}') {
+            in_deps = Some(false);
+            continue;
+        }
+        if in_deps == Some(false) {
+            continue;
+        }
+        let Some((key, value)) = split_entry(text, Manifest::PackageJson) else {
+            continue;
+        };
+        let Some(spec) = unquote(value) else {
+            continue;
+        };
+        if in_deps.is_none() && (key == "version" || !looks_like_version_spec(spec)) {
+            continue;
+        }
+        record(versions, key, spec.to_string(), &line.kind);
+    }
+}
+
+/// Extract added, removed, and bumped dependencies from hunks touching Cargo.toml,
+/// Cargo.lock, or package.json. Purely textual: only lines visible in the hunks are used.
+pub fn dependency_changes(hunks: &[Hunk]) -> Vec<DependencyChange> {
+    // (manifest path, dependency) -> versions, ordered for stable output.
+    let mut by_file: BTreeMap<String, BTreeMap<String, Versions>> = BTreeMap::new();
+    for hunk in hunks {
+        let Some(kind) = manifest_kind(&hunk.file_path) else {
+            continue;
+        };
+        let versions = by_file.entry(hunk.file_path.clone()).or_default();
+        match kind {
+            Manifest::CargoToml => scan_cargo_toml(hunk, versions),
+            Manifest::CargoLock => scan_cargo_lock(hunk, versions),
+            Manifest::PackageJson => scan_package_json(hunk, versions),
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (manifest, versions) in by_file {
+        for (name, v) in versions {
+            let change = match (&v.old, &v.new) {
+                (None, Some(_)) => "added",
+                (Some(_), None) => "removed",
+                (Some(old), Some(new)) if old != new => "bumped",
+                _ => continue,
+            };
+            changes.push(DependencyChange {
+                manifest: manifest.clone(),
+                name,
+                change: change.to_string(),
+                from: v.old,
+                to: v.new,
+            });
+        }
+    }
+    changes
+}
+
+/// One line per manifest, e.g. `Cargo.lock: bumped serde 1.0.1 -> 1.0.2; added ureq 2.9`,
+/// capped at `MAX_SUMMARY_CHARS`.
+pub fn render_summary(changes: &[DependencyChange]) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current: Option<&str> = None;
+    for c in changes {
+        let item = match (c.change.as_str(), &c.from, &c.to) {
+            ("bumped", Some(from), Some(to)) => format!("bumped {} {} -> {}", c.name, from, to),
+            ("added", _, Some(to)) => format!("added {} {}", c.name, to),
+            ("removed", Some(from), _) => format!("removed {} {}", c.name, from),
+            _ => continue,
+        };
+        if current == Some(c.manifest.as_str()) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str("; ");
+                last.push_str(&item);
+            }
+        } else {
+            current = Some(c.manifest.as_str());
+            lines.push(format!("{}: {}", c.manifest, item));
+        }
+    }
+    let mut summary = lines.join("\n");
+    if summary.len() > MAX_SUMMARY_CHARS {
+        let end = summary.floor_char_boundary(MAX_SUMMARY_CHARS);
+        summary.truncate(end);
+        summary.push_str(" ...");
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DiffLine;
+
+    fn hunk(file_path: &str, lines: &[(&str, &str)]) -> Hunk {
+        Hunk {
+            id: "H1".to_string(),
+            file_path: file_path.to_string(),
+            subproject: None,
+            header: String::new(),
+            old_start: 1,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 0,
+            lines: lines
+                .iter()
+                .map(|(kind, text)| DiffLine {
+                    kind: kind.to_string(),
+                    old_line: None,
+                    new_line: None,
+                    text: text.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn describe(changes: &[DependencyChange]) -> Vec<String> {
+        changes
+            .iter()
+            .map(|c| {
+                format!(
+                    "{} {} {}->{}",
+                    c.change,
+                    c.name,
+                    c.from.as_deref().unwrap_or("-"),
+                    c.to.as_deref().unwrap_or("-")
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cargo_toml_dependencies() {
+        let h = hunk(
+            "src-tauri/Cargo.toml",
+            &[
+                ("context", "regex = \"1\""),
+                ("remove", "serde_json = \"1.0.100\""),
+                ("add", "serde_json = \"1.0.120\""),
+                ("add", "ureq = { version = \"2\", features = [\"json\"] }"),
+                ("remove", "old = { path = \"../old\" }"),
+                ("add", "edition = \"2024\""),
+            ],
+        );
+        assert_eq!(
+            describe(&dependency_changes(&[h])),
+            vec![
+                "removed old path ../old->-",
+                "bumped serde_json 1.0.100->1.0.120",
+                "added ureq -->2",
+            ]
+        );
+    }
+
+    #[test]
+    fn cargo_toml_skips_package_section() {
+        let h = hunk(
+            "Cargo.toml",
+            &[
+                ("context", "[package]"),
+                ("remove", "version = \"0.1.0\""),
+                ("add", "version = \"0.2.0\""),
+                ("context", "[dependencies]"),
+                ("add", "tempfile = \"3\""),
+            ],
+        );
+        assert_eq!(
+            describe(&dependency_changes(&[h])),
+            vec!["added tempfile -->3"]
+        );
+    }
+
+    #[test]
+    fn cargo_lock_bumps_and_new_packages() {
+        let h = hunk(
+            "Cargo.lock",
+            &[
+                ("context", "[[package]]"),
+                ("context", "name = \"serde\""),
+                ("remove", "version = \"1.0.1\""),
+                ("add", "version = \"1.0.2\""),
+                ("context", "source = \"registry\""),
+                ("add", "[[package]]"),
+                ("add", "name = \"ureq\""),
+                ("add", "version = \"2.9.0\""),
+            ],
+        );
+        assert_eq!(
+            describe(&dependency_changes(&[h])),
+            vec!["bumped serde 1.0.1->1.0.2", "added ureq -->2.9.0"]
+        );
+    }
+
+    #[test]
+    fn package_json_dependencies() {
+        let h = hunk(
+            "package.json",
+            &[
+                ("context", "\"scripts\": {"),
+                ("add", "\"lint\": \"biome check\","),
+                ("context", "},"),
+                ("context", "\"dependencies\": {"),
+                ("remove", "\"react\": \"^18.2.0\","),
+                ("add", "\"react\": \"^19.0.0\","),
+                ("remove", "\"left-pad\": \"1.0.0\""),
+            ],
+        );
+        assert_eq!(
+            describe(&dependency_changes(&[h])),
+            vec!["removed left-pad 1.0.0->-", "bumped react ^18.2.0->^19.0.0"]
+        );
+    }
+
+    #[test]
+    fn summary_groups_by_manifest() {
+        let change =
+            |manifest: &str, name: &str, from: Option<&str>, to: Option<&str>| DependencyChange {
+                manifest: manifest.to_string(),
+                name: name.to_string(),
+                change: match (from, to) {
+                    (Some(_), Some(_)) => "bumped",
+                    (None, _) => "added",
+                    _ => "removed",
+                }
+                .to_string(),
+                from: from.map(String::from),
+                to: to.map(String::from),
+            };
+        let summary = render_summary(&[
+            change("Cargo.lock", "serde", Some("1.0.1"), Some("1.0.2")),
+            change("Cargo.lock", "ureq", None, Some("2.9")),
+            change("package.json", "react", Some("^18"), None),
+        ]);
+        assert_eq!(
+            summary,
+            "Cargo.lock: bumped serde 1.0.1 -> 1.0.2; added ureq 2.9\npackage.json: removed react ^18"
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::codex_runner::{CodexOutput, RunError};
+use crate::i18n::{t, Msg};
+use crate::provider::{AnalysisProvider, Capabilities, Job};
+use crate::secrets::{self, Secret};
+use crate::types::TokenUsage;
+
+const RESPONSES_URL: &str = "https://api.openai.com/v1/responses";
+const DEFAULT_MODEL: &str = "gpt-5";
+
+fn api_key() -> Result<String, RunError> {
+    match secrets::get(Secret::OpenAi) {
+        Ok(Some(key)) => Ok(key),
+        Ok(None) => Err(RunError::NotAuthenticated {
+            program: "OpenAI API",
+            hint: t(Msg::OpenAiKeyNotSet, &[]),
+        }),
+        Err(e) => Err(RunError::unknown(e)),
+    }
+}
+
+/// The API cannot read the temp dir, so every input file the prompt refers to
+/// (hunks.json, summaries.json, history.json, ...) is inlined ahead of the prompt.
+fn build_input(temp_path: &Path, output_path: &Path, prompt: &str) -> Result<String, String> {
+    let mut names: Vec<String> = std::fs::read_dir(temp_path)
+        .map_err(|e| format!("Failed to read temp directory: {}", e))?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path() != output_path)
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".json") && name != "schema.json")
+        .collect();
+    names.sort();
+
+    let mut input = String::new();
+    for name in &names {
+        let content = std::fs::read_to_string(temp_path.join(name))
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        input.push_str(&format!(
+            "Contents of {}:\n```json\n{}\n```\n\n",
+            name, content
+        ));
+    }
+    input.push_str(prompt);
+    Ok(input)
+}
+
+fn build_request(model: &str, input: &str, schema: &Value) -> Value {
+    json!({
+        "model": model,
+        "input": [{
+            "role": "user",
+            "content": [{ "type": "input_text", "text": input }],
+        }],
+        "text": {
+            "format": {
+                "type": "json_schema",
+                "name": "result",
+                "schema": schema,
+                "strict": true,
+            },
+        },
+    })
+}
+
+/// Concatenate the `output_text` parts of a Responses API result, or surface a refusal.
+fn extract_output_text(response: &Value) -> Result<String, String> {
+    let mut text = String::new();
+    let items = response["output"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for item in items.iter().filter(|i| i["type"] == "message") {
+        let parts = item["content"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for part in parts {
+            match part["type"].as_str() {
+                Some("output_text") => text.push_str(part["text"].as_str().unwrap_or_default()),
+                Some("refusal") => {
+                    return Err(format!(
+                        "OpenAI refused the request: {}",
+                        part["refusal"].as_str().unwrap_or_default()
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
+    if text.trim().is_empty() {
+        let status = response["status"].as_str().unwrap_or("unknown");
+        return Err(format!("OpenAI returned no output (status: {}).", status));
+    }
+    Ok(text)
+}
+
+fn status_error(code: u16, body: &str) -> RunError {
+    let detail = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|v| v["error"]["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| body.to_string());
+    match code {
+        401 => RunError::NotAuthenticated {
+            program: "OpenAI API",
+            hint: t(Msg::OpenAiKeyInvalid, &[]),
+        },
+        429 => RunError::RateLimited {
+            program: "OpenAI API",
+            detail,
+        },
+        _ => RunError::unknown(format!("OpenAI API request failed ({}): {}", code, detail)),
+    }
+}
+
+/// Call the Responses API with `schema_path` as a strict structured output and return the
+/// log output plus the JSON text of the answer.
+pub fn run(
+    schema_path: &Path,
+    model: &Option<String>,
+    input: &str,
+    timeout: Duration,
+) -> Result<(CodexOutput, String), RunError> {
+    let key = api_key()?;
+    let model_used = model
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .unwrap_or(DEFAULT_MODEL)
+        .to_string();
+
+    let schema: Value = std::fs::read_to_string(schema_path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .ok_or_else(|| RunError::unknown("Failed to read schema.json"))?;
+    let request = build_request(&model_used, input, &schema);
+
+    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+    let start = Instant::now();
+    let response = agent
+        .post(RESPONSES_URL)
+        .set("Authorization", &format!("Bearer {}", key))
+        .send_json(request);
+    let elapsed_secs = start.elapsed().as_secs_f64();
+
+    let body = match response {
+        Ok(resp) => resp
+            .into_string()
+            .map_err(|e| RunError::unknown(format!("Failed to read OpenAI response: {}", e)))?,
+        Err(ureq::Error::Status(code, resp)) => {
+            return Err(status_error(code, &resp.into_string().unwrap_or_default()))
+        }
+        Err(ureq::Error::Transport(_)) if start.elapsed() >= timeout => {
+            return Err(RunError::Timeout {
+                program: "OpenAI API",
+                minutes: timeout.as_secs() / 60,
+            })
+        }
+        Err(e) => {
+            return Err(RunError::unknown(format!(
+                "OpenAI API request failed: {}",
+                e
+            )))
+        }
+    };
+
+    let parsed: Value = serde_json::from_str(&body)
+        .map_err(|e| RunError::unknown(format!("Failed to parse OpenAI response: {}", e)))?;
+    let text = extract_output_text(&parsed).map_err(|detail| RunError::EmptyOutput {
+        program: "OpenAI API",
+        detail,
+    })?;
+
+    let usage = &parsed["usage"];
+    let log = CodexOutput {
+        stdout: text.clone(),
+        stderr: format!(
+            "tokens: input={} output={}",
+            usage["input_tokens"], usage["output_tokens"]
+        ),
+        elapsed_secs,
+        model_used,
+        usage: usage["total_tokens"].as_u64().map(|total| TokenUsage {
+            input_tokens: usage["input_tokens"].as_u64(),
+            output_tokens: usage["output_tokens"].as_u64(),
+            total_tokens: total,
+        }),
+        args: vec![format!("POST {}", RESPONSES_URL)],
+        run_id: None,
+    };
+    Ok((log, text))
+}
+
+/// OpenAI Responses API backend: no CLI needed; input files are inlined into the prompt and
+/// the schema is enforced as a strict structured output.
+pub struct OpenAiProvider;
+
+impl AnalysisProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            reads_files: false,
+            native_schema: true,
+            cli: None,
+        }
+    }
+
+    fn prepare_input(&self, job: &mut Job) -> Result<(), RunError> {
+        job.prompt =
+            build_input(job.temp_path, job.output_path, &job.prompt).map_err(RunError::unknown)?;
+        Ok(())
+    }
+
+    fn run(&self, job: &Job) -> Result<(CodexOutput, String), RunError> {
+        run(job.schema_path, job.model, &job.prompt, job.timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_input_inlines_json_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("hunks.json"), "[1]").unwrap();
+        std::fs::write(dir.path().join("summaries.json"), "[2]").unwrap();
+        std::fs::write(dir.path().join("schema.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("result.json"), "{}").unwrap();
+
+        let input =
+            build_input(dir.path(), &dir.path().join("result.json"), "Group them.").unwrap();
+        assert!(input.contains("Contents of hunks.json:\n```json\n[1]\n```"));
+        assert!(input.contains("Contents of summaries.json"));
+        assert!(!input.contains("schema.json"));
+        assert!(!input.contains("result.json"));
+        assert!(input.ends_with("Group them."));
+    }
+
+    #[test]
+    fn request_uses_strict_schema() {
+        let req = build_request("gpt-5", "in", &json!({"type": "object"}));
+        assert_eq!(req["model"], "gpt-5");
+        assert_eq!(req["text"]["format"]["type"], "json_schema");
+        assert_eq!(req["text"]["format"]["strict"], true);
+        assert_eq!(req["input"][0]["content"][0]["text"], "in");
+    }
+
+    #[test]
+    fn extract_output_text_joins_message_parts() {
+        let resp = json!({
+            "status": "completed",
+            "output": [
+                { "type": "reasoning", "summary": [] },
+                { "type": "message", "content": [
+                    { "type": "output_text", "text": "{\"a\":" },
+                    { "type": "output_text", "text": "1}" },
+                ]},
+            ],
+        });
+        assert_eq!(extract_output_text(&resp).unwrap(), "{\"a\":1}");
+    }
+
+    #[test]
+    fn extract_output_text_reports_refusal_and_empty() {
+        let refusal = json!({ "output": [{ "type": "message", "content": [
+            { "type": "refusal", "refusal": "no" },
+        ]}]});
+        assert!(extract_output_text(&refusal)
+            .unwrap_err()
+            .contains("refused"));
+        let empty = json!({ "status": "incomplete", "output": [] });
+        assert!(extract_output_text(&empty)
+            .unwrap_err()
+            .contains("incomplete"));
+    }
+
+    #[test]
+    fn status_error_messages() {
+        assert!(status_error(401, "").to_string().contains("invalid"));
+        let body = r#"{"error":{"message":"slow down"}}"#;
+        assert!(status_error(429, body).to_string().contains("slow down"));
+        assert!(matches!(
+            status_error(429, body),
+            RunError::RateLimited { .. }
+        ));
+        assert!(matches!(
+            status_error(401, ""),
+            RunError::NotAuthenticated { .. }
+        ));
+        assert!(status_error(500, "boom")
+            .to_string()
+            .contains("(500): boom"));
+    }
+}
@@ -0,0 +1,444 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Display;
+use std::future::Future;
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::error_reports;
+use crate::time::now_secs;
+use crate::types::OperationRecord;
+
+/// Emitted with the operation's `OperationRecord` at each step of a long command.
+pub const PROGRESS_EVENT: &str = "op-progress";
+/// Emitted once when the command succeeds.
+pub const DONE_EVENT: &str = "op-done";
+/// Emitted once when the command fails; `error` holds the message.
+pub const ERROR_EVENT: &str = "op-error";
+
+/// Operations kept for `list_operations`; the oldest finished ones are dropped first.
+const MAX_HISTORY: usize = 50;
+
+/// Error of a subprocess whose operation was cancelled.
+pub const CANCELLED: &str = "Cancelled.";
+
+/// The operations of this process.
+static OPERATIONS: LazyLock<Operations> = LazyLock::new(Operations::default);
+
+type EventHook = Box<dyn Fn(&str, &OperationRecord) + Send + Sync>;
+/// Where progress events go, e.g. to the frontend; unset in tests and the CLI.
+static EVENT_HOOK: OnceLock<EventHook> = OnceLock::new();
+
+tokio::task_local! {
+    /// Operation of the command whose work is running, set by `Op::scope` for the task rather
+    /// than a thread, as a command may resume on another worker after each `.await`.
+    static CURRENT: String;
+}
+
+/// Running and recently finished operations of this process (newest last), and the
+/// processes each running one has spawned, so `cancel` can stop them.
+#[derive(Default)]
+pub struct Operations {
+    records: Mutex<VecDeque<OperationRecord>>,
+    children: Mutex<HashMap<String, Vec<u32>>>,
+    cancelled: Mutex<HashSet<String>>,
+}
+
+impl Operations {
+    fn register(&self, op_id: &str, pid: u32) {
+        if let Ok(mut children) = self.children.lock() {
+            children.entry(op_id.to_string()).or_default().push(pid);
+        }
+    }
+
+    fn release(&self, op_id: &str, pid: u32) {
+        if let Ok(mut children) = self.children.lock() {
+            if let Some(pids) = children.get_mut(op_id) {
+                pids.retain(|p| *p != pid);
+            }
+        }
+    }
+
+    fn is_cancelled(&self, op_id: &str) -> bool {
+        self.cancelled
+            .lock()
+            .is_ok_and(|cancelled| cancelled.contains(op_id))
+    }
+
+    /// Mark the operation cancelled and kill the processes it is running. Returns whether it
+    /// was running.
+    fn cancel(&self, op_id: &str) -> Result<bool, String> {
+        let running = self
+            .records
+            .lock()
+            .map_err(|_| "Operations lock poisoned".to_string())?
+            .iter()
+            .any(|r| r.op_id == op_id && r.status == "running");
+        if !running {
+            return Ok(false);
+        }
+        self.cancelled
+            .lock()
+            .map_err(|_| "Operations lock poisoned".to_string())?
+            .insert(op_id.to_string());
+        let pids = self
+            .children
+            .lock()
+            .map_err(|_| "Operations lock poisoned".to_string())?
+            .get(op_id)
+            .cloned()
+            .unwrap_or_default();
+        for pid in pids {
+            kill_tree(pid);
+        }
+        Ok(true)
+    }
+
+    /// Forget the operation's processes and cancellation once its command has returned.
+    fn end(&self, op_id: &str) {
+        if let Ok(mut children) = self.children.lock() {
+            children.remove(op_id);
+        }
+        if let Ok(mut cancelled) = self.cancelled.lock() {
+            cancelled.remove(op_id);
+        }
+    }
+
+    fn upsert(&self, record: &OperationRecord) {
+        let Ok(mut records) = self.records.lock() else {
+            return;
+        };
+        match records.iter_mut().find(|r| r.op_id == record.op_id) {
+            Some(existing) => *existing = record.clone(),
+            None => records.push_back(record.clone()),
+        }
+        while records.len() > MAX_HISTORY {
+            match records.iter().position(|r| r.status != "running") {
+                Some(pos) => records.remove(pos),
+                None => records.pop_front(),
+            };
+        }
+    }
+}
+
+/// Progress reporting of one long-running command. Commands take an optional `op_id` from the
+/// UI; without one nothing is emitted or recorded, so callers that don't track progress are
+/// unaffected.
+pub struct Op {
+    tracked: bool,
+    record: Mutex<OperationRecord>,
+    started: Instant,
+}
+
+impl Op {
+    pub fn start(op_id: Option<String>, kind: &str) -> Op {
+        let op_id = op_id.map(|id| id.trim().to_string()).unwrap_or_default();
+        let op = Op {
+            tracked: !op_id.is_empty(),
+            record: Mutex::new(OperationRecord {
+                op_id,
+                kind: kind.to_string(),
+                status: "running".to_string(),
+                message: None,
+                error: None,
+                started_at: now_secs(),
+                elapsed_ms: 0,
+            }),
+            started: Instant::now(),
+        };
+        op.update(PROGRESS_EVENT, |_| {});
+        op
+    }
+
+    fn op_id(&self) -> String {
+        self.record
+            .lock()
+            .map(|record| record.op_id.clone())
+            .unwrap_or_default()
+    }
+
+    /// Change the record and emit it as `event`.
+    fn update(&self, event: &str, f: impl FnOnce(&mut OperationRecord)) {
+        if !self.tracked {
+            return;
+        }
+        let Ok(mut record) = self.record.lock() else {
+            return;
+        };
+        f(&mut record);
+        record.elapsed_ms = self.started.elapsed().as_millis() as u64;
+        OPERATIONS.upsert(&record);
+        if let Some(hook) = EVENT_HOOK.get() {
+            hook(event, &record);
+        }
+    }
+
+    /// Run the command's work for this operation: the processes it spawns, on whichever thread
+    /// it resumes, are registered under it so `cancel` can stop them.
+    pub async fn scope<F: Future>(&self, work: F) -> F::Output {
+        if !self.tracked {
+            return work.await;
+        }
+        CURRENT.scope(self.op_id(), work).await
+    }
+
+    /// Report the step the command is on, e.g. "Fetching diff".
+    pub fn progress(&self, message: impl Into<String>) {
+        let message = message.into();
+        self.update(PROGRESS_EVENT, |record| record.message = Some(message));
+    }
+
+    /// Emit `op-done` or `op-error` for the command's result and pass it through.
+    pub fn finish<T, E: Display>(self, result: Result<T, E>) -> Result<T, E> {
+        match &result {
+            Ok(_) => self.update(DONE_EVENT, |record| record.status = "done".to_string()),
+            Err(e) => {
+                let cancelled = self.tracked && OPERATIONS.is_cancelled(&self.op_id());
+                self.update(ERROR_EVENT, |record| {
+                    record.status = if cancelled { "cancelled" } else { "error" }.to_string();
+                    record.error = Some(e.to_string());
+                    if !cancelled {
+                        error_reports::record_failure(&record.kind, &e.to_string());
+                    }
+                });
+            }
+        }
+        result
+    }
+}
+
+impl Drop for Op {
+    fn drop(&mut self) {
+        if self.tracked {
+            OPERATIONS.end(&self.op_id());
+        }
+    }
+}
+
+/// Send progress events to `hook` as well, e.g. to the frontend. Only the first hook is kept.
+pub fn on_event(hook: impl Fn(&str, &OperationRecord) + Send + Sync + 'static) {
+    let _ = EVENT_HOOK.set(Box::new(hook));
+}
+
+/// Running and recently finished operations, oldest first.
+pub fn list() -> Result<Vec<OperationRecord>, String> {
+    Ok(OPERATIONS
+        .records
+        .lock()
+        .map_err(|_| "Operations lock poisoned".to_string())?
+        .iter()
+        .cloned()
+        .collect())
+}
+
+/// Stop a running operation: its subprocesses are killed with their children and its command
+/// fails. Returns whether it was running.
+pub fn cancel(op_id: &str) -> Result<bool, String> {
+    OPERATIONS.cancel(op_id)
+}
+
+/// The id of the operation the running task works for, if any.
+fn current() -> Option<String> {
+    CURRENT.try_with(Clone::clone).ok()
+}
+
+/// Whether the operation the running task works for was cancelled.
+pub fn is_cancelled() -> bool {
+    current().is_some_and(|op_id| OPERATIONS.is_cancelled(&op_id))
+}
+
+fn cancelled_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, CANCELLED)
+}
+
+/// Spawn `cmd` in its own process group, registered under the current operation so
+/// `cancel` can kill it with everything it started. Once the operation is cancelled
+/// nothing more is spawned. Returns the operation it was registered under.
+fn spawn_child(cmd: &mut Command) -> io::Result<(Child, Option<String>)> {
+    if is_cancelled() {
+        return Err(cancelled_error());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    let child = cmd.spawn()?;
+    let op_id = current();
+    if let Some(op_id) = &op_id {
+        OPERATIONS.register(op_id, child.id());
+    }
+    Ok((child, op_id))
+}
+
+/// A process from `spawn`. It is unregistered when dropped; if it was not seen to exit by
+/// then, it is first killed with its process group, so nothing it started lives on holding
+/// its pipes.
+pub struct Spawned {
+    child: Child,
+    op_id: Option<String>,
+    exited: bool,
+}
+
+impl Spawned {
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        let status = self.child.try_wait()?;
+        self.exited |= status.is_some();
+        Ok(status)
+    }
+
+    pub fn wait(&mut self) -> io::Result<ExitStatus> {
+        let status = self.child.wait()?;
+        self.exited = true;
+        Ok(status)
+    }
+}
+
+impl Deref for Spawned {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        &self.child
+    }
+}
+
+impl DerefMut for Spawned {
+    fn deref_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+}
+
+impl Drop for Spawned {
+    fn drop(&mut self) {
+        let pid = self.child.id();
+        if !self.exited {
+            kill_tree(pid);
+            let _ = self.child.wait();
+        }
+        release(self.op_id.as_deref(), pid);
+    }
+}
+
+/// `spawn_child` for a process the caller waits for itself; see `Spawned`.
+pub fn spawn(cmd: &mut Command) -> io::Result<Spawned> {
+    let (child, op_id) = spawn_child(cmd)?;
+    Ok(Spawned {
+        child,
+        op_id,
+        exited: false,
+    })
+}
+
+/// Unregister a process from `spawn_child` that has exited.
+fn release(op_id: Option<&str>, pid: u32) {
+    if let Some(op_id) = op_id {
+        OPERATIONS.release(op_id, pid);
+    }
+}
+
+/// `Command::output` for a cancellable process: a process killed by `cancel` fails
+/// with `ErrorKind::Interrupted`.
+pub fn output(cmd: &mut Command) -> io::Result<Output> {
+    let (child, op_id) = spawn_child(
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )?;
+    let pid = child.id();
+    let output = child.wait_with_output();
+    release(op_id.as_deref(), pid);
+    if is_cancelled() {
+        return Err(cancelled_error());
+    }
+    output
+}
+
+/// Kill a process spawned by `spawn` together with its descendants.
+fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    let _ = Command::new("kill")
+        .args(["-s", "KILL", "--", &format!("-{}", pid)])
+        .output();
+    #[cfg(windows)]
+    let _ = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .output();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(op_id: &str, status: &str) -> OperationRecord {
+        OperationRecord {
+            op_id: op_id.to_string(),
+            kind: "get_pr_diff".to_string(),
+            status: status.to_string(),
+            message: None,
+            error: None,
+            started_at: 0,
+            elapsed_ms: 0,
+        }
+    }
+
+    #[test]
+    fn keeps_running_operations_over_finished_ones() {
+        let operations = Operations::default();
+        operations.upsert(&record("running", "running"));
+        for i in 0..MAX_HISTORY {
+            operations.upsert(&record(&format!("op{}", i), "done"));
+        }
+        operations.upsert(&record("running", "running"));
+        let records = operations.records.lock().unwrap();
+        assert_eq!(records.len(), MAX_HISTORY);
+        assert_eq!(records[0].op_id, "running");
+        assert_eq!(records[1].op_id, "op1");
+    }
+
+    #[test]
+    fn cancels_only_running_operations() {
+        let operations = Operations::default();
+        operations.upsert(&record("done", "done"));
+        operations.upsert(&record("running", "running"));
+        assert!(!operations.cancel("done").unwrap());
+        assert!(!operations.is_cancelled("done"));
+        assert!(operations.cancel("running").unwrap());
+        assert!(operations.is_cancelled("running"));
+        operations.end("running");
+        assert!(!operations.is_cancelled("running"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_process_groups() {
+        let operations = Operations::default();
+        operations.upsert(&record("op", "running"));
+        let mut child = spawn(Command::new("sleep").arg("30")).unwrap();
+        operations.register("op", child.id());
+        assert!(operations.cancel("op").unwrap());
+        assert!(!child.wait().unwrap().success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_unfinished_processes_on_drop() {
+        use std::io::{BufRead, Read};
+
+        let mut child = spawn(
+            Command::new("sh")
+                .args(["-c", "sleep 30 & echo started; wait"])
+                .stdout(Stdio::piped()),
+        )
+        .unwrap();
+        let mut stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let start = Instant::now();
+        drop(child);
+        // The pipe closes only once the backgrounded sleep is gone too.
+        stdout.read_to_end(&mut Vec::new()).unwrap();
+        assert!(start.elapsed().as_secs() < 10);
+    }
+}
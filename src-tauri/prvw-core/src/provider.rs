@@ -0,0 +1,481 @@
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::claude_runner::ClaudeCodeProvider;
+use crate::codex_runner::CodexProvider;
+use crate::history;
+use crate::openai_runner::OpenAiProvider;
+use crate::types::{RunOptions, TokenUsage};
+
+/// What a backend can do on its own; the frontend uses this to explain setup requirements.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    /// Reads input files from the temp dir itself (otherwise they must be inlined in the prompt).
+    pub reads_files: bool,
+    /// Enforces schema.json natively (otherwise the schema is requested through the prompt).
+    pub native_schema: bool,
+    /// CLI binary that must be installed, if any.
+    pub cli: Option<&'static str>,
+}
+
+/// One prompt run against a prepared temp dir holding the input files and schema.json.
+pub struct Job<'a> {
+    pub temp_path: &'a Path,
+    pub schema_path: &'a Path,
+    pub output_path: &'a Path,
+    /// Checkout of the PR head to run in for full-repo context; input files stay in `temp_path`.
+    pub repo_dir: Option<&'a Path>,
+    pub model: &'a Option<String>,
+    pub options: &'a RunOptions,
+    pub prompt: String,
+    pub timeout: Duration,
+}
+
+/// An AI backend that can answer a prompt with schema-conforming JSON.
+/// Implementations live next to their runner in the app (`codex_runner`, `claude_runner`,
+/// `openai_runner`) and are registered in its `ProviderRegistry`; commands only use this trait.
+pub trait AnalysisProvider: Send + Sync {
+    /// Stable identifier used in settings and cache keys.
+    fn name(&self) -> &'static str;
+
+    /// Other names accepted for this provider in the `provider` command argument.
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn capabilities(&self) -> Capabilities;
+
+    /// Adapt the job before running, e.g. inline input files or add schema instructions.
+    fn prepare_input(&self, _job: &mut Job) -> Result<(), RunError> {
+        Ok(())
+    }
+
+    /// Run the job and return the log output plus the raw model response.
+    fn run(&self, job: &Job) -> Result<(CodexOutput, String), RunError>;
+
+    /// Turn the raw model response into the JSON document to store in `output_path`.
+    fn parse_output(&self, raw: &str) -> Result<String, RunError> {
+        Ok(raw.to_string())
+    }
+}
+
+/// Why a provider run failed. Serialized tagged by `kind` (see `error::PrvwError`) so
+/// the frontend can offer a recovery action matching the failure.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RunError {
+    /// The CLI binary is missing; `hint` says how to install it.
+    NotInstalled { program: &'static str, hint: String },
+    /// The provider rejected or lacks credentials; `hint` says how to log in.
+    NotAuthenticated { program: &'static str, hint: String },
+    /// The CLI did not finish within the configured timeout and was killed.
+    Timeout { program: &'static str, minutes: u64 },
+    /// The provider answered, but not with JSON matching the schema.
+    SchemaViolation {
+        program: &'static str,
+        detail: String,
+    },
+    /// The provider finished without writing an answer.
+    EmptyOutput {
+        program: &'static str,
+        detail: String,
+    },
+    /// The provider throttled the request.
+    RateLimited {
+        program: &'static str,
+        detail: String,
+    },
+    /// The operation was cancelled and the CLI was killed.
+    Cancelled { program: &'static str },
+    /// Anything else: the provider's error output, or a local failure message.
+    Unknown { stderr: String },
+}
+
+type MessageHook = Box<dyn Fn(&RunError) -> Option<String> + Send + Sync>;
+/// Wording of run errors shown to the user, e.g. in their language; unset in tests and the CLI.
+static MESSAGE_HOOK: OnceLock<MessageHook> = OnceLock::new();
+
+/// Reword run errors for the user; returning None keeps the English `Display` text. Only the
+/// first hook is kept.
+pub fn on_message(hook: impl Fn(&RunError) -> Option<String> + Send + Sync + 'static) {
+    let _ = MESSAGE_HOOK.set(Box::new(hook));
+}
+
+impl RunError {
+    pub fn unknown(message: impl Into<String>) -> Self {
+        RunError::Unknown {
+            stderr: message.into(),
+        }
+    }
+
+    /// The serialized `kind` tag.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RunError::NotInstalled { .. } => "notInstalled",
+            RunError::NotAuthenticated { .. } => "notAuthenticated",
+            RunError::Timeout { .. } => "timeout",
+            RunError::SchemaViolation { .. } => "schemaViolation",
+            RunError::EmptyOutput { .. } => "emptyOutput",
+            RunError::RateLimited { .. } => "rateLimited",
+            RunError::Cancelled { .. } => "cancelled",
+            RunError::Unknown { .. } => "unknown",
+        }
+    }
+
+    /// The error as shown to the user.
+    pub fn message(&self) -> String {
+        MESSAGE_HOOK
+            .get()
+            .and_then(|hook| hook(self))
+            .unwrap_or_else(|| self.to_string())
+    }
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::NotInstalled { hint, .. } | RunError::NotAuthenticated { hint, .. } => {
+                f.write_str(hint)
+            }
+            RunError::Timeout { program, minutes } => write!(
+                f,
+                "{} timed out after {} minute(s) and was stopped. \
+                 Try again or increase the timeout in Settings.",
+                program, minutes
+            ),
+            RunError::SchemaViolation { program, detail } => {
+                write!(
+                    f,
+                    "{} returned output that does not match the schema: {}",
+                    program, detail
+                )
+            }
+            RunError::EmptyOutput { program, detail } => {
+                write!(f, "{} did not produce output: {}", program, detail)
+            }
+            RunError::RateLimited { program, detail } => {
+                write!(f, "{} rate limit exceeded: {}", program, detail)
+            }
+            RunError::Cancelled { program } => write!(f, "{} run cancelled.", program),
+            RunError::Unknown { stderr } => f.write_str(stderr),
+        }
+    }
+}
+
+impl From<RunError> for String {
+    fn from(e: RunError) -> Self {
+        e.message()
+    }
+}
+
+/// Whether a provider's error output says it was throttled.
+pub fn is_rate_limited(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    lower.contains("rate limit") || lower.contains("too many requests") || lower.contains(" 429")
+}
+
+#[derive(Debug)]
+pub struct CodexOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub elapsed_secs: f64,
+    pub model_used: String,
+    /// Tokens reported by the provider, when it reports them.
+    pub usage: Option<TokenUsage>,
+    /// CLI arguments (or request target, for API providers) of the run.
+    pub args: Vec<String>,
+    /// Id of the run in the persistent history, set by `provider::execute`.
+    pub run_id: Option<String>,
+}
+
+/// Instructions prepended to a job's prompt when it runs in a checkout of the PR head.
+pub fn repo_context_preamble(temp_path: &Path) -> String {
+    format!(
+        "The current working directory is a read-only checkout of the pull request's head commit; \
+         consult the surrounding code there when it helps to judge intent and risk. \
+         The input files named below (hunks.json and any others) are in {}. \
+         Hunk ids must still come only from those input files. ",
+        temp_path.display()
+    )
+}
+
+/// Providers available to commands, registered as managed state.
+pub struct ProviderRegistry {
+    providers: Vec<Arc<dyn AnalysisProvider>>,
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self {
+            providers: vec![
+                Arc::new(CodexProvider),
+                Arc::new(ClaudeCodeProvider),
+                Arc::new(OpenAiProvider),
+            ],
+        }
+    }
+}
+
+impl ProviderRegistry {
+    pub fn list(&self) -> Vec<ProviderInfo> {
+        self.providers
+            .iter()
+            .map(|p| ProviderInfo {
+                name: p.name(),
+                capabilities: p.capabilities(),
+            })
+            .collect()
+    }
+
+    /// Resolve the per-run provider option; `None` or empty selects the first (Codex).
+    pub fn resolve(&self, name: &Option<String>) -> Result<Arc<dyn AnalysisProvider>, String> {
+        let wanted = name.as_deref().map(str::trim).unwrap_or_default();
+        let found = if wanted.is_empty() {
+            self.providers.first()
+        } else {
+            self.providers
+                .iter()
+                .find(|p| p.name() == wanted || p.aliases().contains(&wanted))
+        };
+        found.cloned().ok_or_else(|| {
+            let names: Vec<&str> = self.providers.iter().map(|p| p.name()).collect();
+            format!(
+                "Unknown provider '{}'. Expected one of: {}.",
+                wanted,
+                names.join(", ")
+            )
+        })
+    }
+}
+
+/// Run `job` with `provider`. On success the schema-conforming JSON has been written to
+/// `job.output_path`, so callers read it the same way regardless of backend.
+pub fn execute(provider: &dyn AnalysisProvider, mut job: Job) -> Result<CodexOutput, RunError> {
+    if job.repo_dir.is_some() {
+        if !provider.capabilities().reads_files {
+            return Err(RunError::unknown(format!(
+                "{} cannot read a repository checkout. Disable full-repo context or choose another provider.",
+                provider.name()
+            )));
+        }
+        job.prompt = format!("{}{}", repo_context_preamble(job.temp_path), job.prompt);
+    }
+    // Recorded with the prompt as built by the command, before providers inline input files.
+    let prompt = job.prompt.clone();
+    let start = Instant::now();
+    let result = run_job(provider, &mut job);
+    job.prompt = prompt;
+    let run_id = history::record(provider.name(), &job, &result, start.elapsed());
+    result.map(|output| CodexOutput { run_id, ..output })
+}
+
+fn run_job(provider: &dyn AnalysisProvider, job: &mut Job) -> Result<CodexOutput, RunError> {
+    provider.prepare_input(job)?;
+    let (output, raw) = provider.run(job)?;
+    let json = provider.parse_output(&raw)?;
+    serde_json::from_str::<serde_json::Value>(&json).map_err(|e| RunError::SchemaViolation {
+        program: provider.name(),
+        detail: format!("invalid JSON: {}", e),
+    })?;
+    std::fs::write(job.output_path, json).map_err(|e| {
+        RunError::unknown(format!("Failed to write {} output: {}", provider.name(), e))
+    })?;
+    Ok(output)
+}
+
+/// A provider as `list_providers` lists it.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderInfo {
+    pub name: &'static str,
+    pub capabilities: Capabilities,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RunOptions;
+
+    #[test]
+    fn run_error_serializes_with_kind() {
+        let e = RunError::RateLimited {
+            program: "codex",
+            detail: "429".to_string(),
+        };
+        let json = serde_json::to_value(&e).unwrap();
+        assert_eq!(json["kind"], "rateLimited");
+        assert_eq!(json["program"], "codex");
+        let unknown = serde_json::to_value(RunError::unknown("boom")).unwrap();
+        assert_eq!(unknown["kind"], "unknown");
+        assert_eq!(unknown["stderr"], "boom");
+    }
+
+    #[test]
+    fn rate_limit_detection() {
+        assert!(is_rate_limited("Error: Rate limit reached for gpt-5"));
+        assert!(is_rate_limited("HTTP 429 Too Many Requests"));
+        assert!(!is_rate_limited("stream error: connection reset"));
+    }
+
+    #[test]
+    fn timeout_error_converts_to_message() {
+        let msg: String = RunError::Timeout {
+            program: "codex",
+            minutes: 5,
+        }
+        .into();
+        assert!(msg.contains("codex timed out after 5 minute(s)"));
+    }
+
+    #[test]
+    fn repo_context_preamble_points_at_inputs() {
+        let preamble = repo_context_preamble(Path::new("/tmp/x"));
+        assert!(preamble.contains("read-only checkout"));
+        assert!(preamble.contains("/tmp/x"));
+    }
+
+    struct EchoProvider;
+
+    impl AnalysisProvider for EchoProvider {
+        fn name(&self) -> &'static str {
+            "echo"
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities {
+                reads_files: false,
+                native_schema: false,
+                cli: None,
+            }
+        }
+
+        fn prepare_input(&self, job: &mut Job) -> Result<(), RunError> {
+            job.prompt = format!("{{\"prompt\": \"{}\"}}", job.prompt);
+            Ok(())
+        }
+
+        fn run(&self, job: &Job) -> Result<(CodexOutput, String), RunError> {
+            let output = CodexOutput {
+                stdout: String::new(),
+                stderr: String::new(),
+                elapsed_secs: 0.0,
+                model_used: "echo".to_string(),
+                usage: None,
+                args: vec![],
+                run_id: None,
+            };
+            Ok((output, format!("text {} text", job.prompt)))
+        }
+
+        fn parse_output(&self, raw: &str) -> Result<String, RunError> {
+            Ok(raw
+                .trim_start_matches("text ")
+                .trim_end_matches(" text")
+                .to_string())
+        }
+    }
+
+    fn resolve_name(name: Option<&str>) -> Result<&'static str, String> {
+        ProviderRegistry::default()
+            .resolve(&name.map(str::to_string))
+            .map(|p| p.name())
+    }
+
+    #[test]
+    fn resolve_defaults_to_codex() {
+        assert_eq!(resolve_name(None).unwrap(), "codex");
+        assert_eq!(resolve_name(Some(" ")).unwrap(), "codex");
+    }
+
+    #[test]
+    fn resolve_names_and_aliases() {
+        assert_eq!(resolve_name(Some("claude")).unwrap(), "claude");
+        assert_eq!(resolve_name(Some("claude-code")).unwrap(), "claude");
+        assert_eq!(resolve_name(Some("openai")).unwrap(), "openai");
+    }
+
+    #[test]
+    fn resolve_unknown_lists_providers() {
+        let err = resolve_name(Some("gemini")).unwrap_err();
+        assert!(err.contains("'gemini'"));
+        assert!(err.contains("codex, claude, openai"));
+    }
+
+    #[test]
+    fn execute_runs_pipeline_and_writes_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_path = dir.path().join("result.json");
+        let job = Job {
+            temp_path: dir.path(),
+            schema_path: &dir.path().join("schema.json"),
+            output_path: &output_path,
+            repo_dir: None,
+            model: &None,
+            options: &RunOptions::default(),
+            prompt: "hi".to_string(),
+            timeout: Duration::from_secs(1),
+        };
+        let output = execute(&EchoProvider, job).unwrap();
+        assert_eq!(output.model_used, "echo");
+        let written = std::fs::read_to_string(&output_path).unwrap();
+        assert_eq!(written, "{\"prompt\": \"hi\"}");
+    }
+
+    #[test]
+    fn execute_repo_context_requires_file_access() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_path = dir.path().join("result.json");
+        let job = Job {
+            temp_path: dir.path(),
+            schema_path: &dir.path().join("schema.json"),
+            output_path: &output_path,
+            repo_dir: Some(dir.path()),
+            model: &None,
+            options: &RunOptions::default(),
+            prompt: "hi".to_string(),
+            timeout: Duration::from_secs(1),
+        };
+        let err = execute(&EchoProvider, job).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("echo cannot read a repository checkout"));
+    }
+
+    #[test]
+    fn execute_rejects_invalid_json() {
+        struct BadProvider;
+        impl AnalysisProvider for BadProvider {
+            fn name(&self) -> &'static str {
+                "bad"
+            }
+            fn capabilities(&self) -> Capabilities {
+                EchoProvider.capabilities()
+            }
+            fn run(&self, job: &Job) -> Result<(CodexOutput, String), RunError> {
+                let (output, _) = EchoProvider.run(job)?;
+                Ok((output, "not json".to_string()))
+            }
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let job = Job {
+            temp_path: dir.path(),
+            schema_path: &dir.path().join("schema.json"),
+            output_path: &dir.path().join("result.json"),
+            repo_dir: None,
+            model: &None,
+            options: &RunOptions::default(),
+            prompt: String::new(),
+            timeout: Duration::from_secs(1),
+        };
+        let err = execute(&BadProvider, job).unwrap_err();
+        assert!(matches!(
+            err,
+            RunError::SchemaViolation { program: "bad", .. }
+        ));
+        assert!(err.to_string().contains("invalid JSON"));
+    }
+}
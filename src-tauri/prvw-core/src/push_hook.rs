@@ -0,0 +1,204 @@
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+use crate::local_range;
+use crate::types::PushHookStatus;
+
+/// First comment line of the installed script; a `pre-push` without it belongs to someone else.
+const HOOK_MARKER: &str = "# Installed by prvw.";
+const HOOK_NAME: &str = "pre-push";
+/// Risk levels a push can be blocked on, lowest first.
+pub const FAIL_ON_LEVELS: [&str; 3] = ["low", "medium", "high"];
+const DEFAULT_FAIL_ON: &str = "high";
+
+/// Analyzes each pushed range with the headless CLI (`prvw-cli check`) and blocks the push when
+/// it reports findings at or above `PRVW_FAIL_ON`. New branches are diffed against the remote's
+/// default branch. Without the CLI on PATH, or when an analysis fails, the hook only warns.
+const HOOK_SCRIPT: &str = r#"#!/bin/sh
+# Installed by prvw.
+# Analyzes outgoing commits and blocks the push on findings at or above PRVW_FAIL_ON.
+# Skip once with `PRVW_SKIP=1 git push` (or `git push --no-verify`).
+PRVW_FAIL_ON={fail_on}
+
+[ -n "$PRVW_SKIP" ] && exit 0
+if ! command -v prvw-cli >/dev/null 2>&1; then
+  echo "prvw: prvw-cli not found on PATH; skipping pre-push analysis." >&2
+  exit 0
+fi
+
+status=0
+while read -r local_ref local_sha remote_ref remote_sha; do
+  # An all-zero local sha is a branch deletion: nothing to analyze.
+  case "$local_sha" in *[!0]*) ;; *) continue ;; esac
+  case "$remote_sha" in
+    *[!0]*) base="$remote_sha" ;;
+    *) base=$(git merge-base "$local_sha" "refs/remotes/$1/HEAD" 2>/dev/null) || continue ;;
+  esac
+  prvw-cli check --repo . --range "$base..$local_sha" --fail-on "$PRVW_FAIL_ON" </dev/null
+  # 1 means findings; anything higher is a failed analysis, which only warns.
+  case $? in
+    0) ;;
+    1) status=1 ;;
+    *) echo "prvw: analysis of $base..$local_sha failed; not blocking the push." >&2 ;;
+  esac
+done
+
+if [ "$status" -ne 0 ]; then
+  echo "prvw: push blocked by $PRVW_FAIL_ON-risk findings. Override with PRVW_SKIP=1 git push." >&2
+fi
+exit $status
+"#;
+
+/// Hooks directory git runs hooks from: `core.hooksPath` (relative to the working tree) when
+/// set, otherwise `hooks/` of the shared git dir, so linked worktrees get the same hook.
+fn hooks_dir(repo: &Repository, root: &Path) -> PathBuf {
+    match repo
+        .config()
+        .ok()
+        .and_then(|config| config.get_path("core.hooksPath").ok())
+    {
+        Some(path) if path.is_absolute() => path,
+        Some(path) => root.join(path),
+        None => common_dir(repo).join("hooks"),
+    }
+}
+
+/// Git dir shared by all worktrees: a linked worktree's git dir names it in its `commondir` file.
+fn common_dir(repo: &Repository) -> PathBuf {
+    let git_dir = repo.path();
+    match std::fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common) => git_dir.join(common.trim_end()),
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
+fn status(hook: &Path) -> PushHookStatus {
+    let script = std::fs::read_to_string(hook).ok();
+    let installed = script
+        .as_deref()
+        .is_some_and(|s| s.lines().any(|l| l == HOOK_MARKER));
+    let fail_on = script
+        .as_deref()
+        .filter(|_| installed)
+        .and_then(|s| s.lines().find_map(|l| l.strip_prefix("PRVW_FAIL_ON=")))
+        .map(str::to_string);
+    PushHookStatus {
+        hook_path: hook.display().to_string(),
+        installed,
+        foreign: script.is_some() && !installed,
+        fail_on,
+    }
+}
+
+/// Write (or rewrite) the hook; an existing `pre-push` that prvw didn't install is left alone.
+fn install(hooks_dir: &Path, fail_on: &str) -> Result<PushHookStatus, String> {
+    if !FAIL_ON_LEVELS.contains(&fail_on) {
+        return Err(format!(
+            "Unknown risk level '{}'. Use one of: {}.",
+            fail_on,
+            FAIL_ON_LEVELS.join(", ")
+        ));
+    }
+    let hook = hooks_dir.join(HOOK_NAME);
+    if status(&hook).foreign {
+        return Err(format!(
+            "{} already exists. Remove it (or call `prvw-cli check` from it) to use prvw's hook.",
+            hook.display()
+        ));
+    }
+    std::fs::create_dir_all(hooks_dir)
+        .map_err(|e| format!("Failed to create {}: {}", hooks_dir.display(), e))?;
+    std::fs::write(&hook, HOOK_SCRIPT.replace("{fail_on}", fail_on))
+        .map_err(|e| format!("Failed to write {}: {}", hook.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make {} executable: {}", hook.display(), e))?;
+    }
+    Ok(status(&hook))
+}
+
+/// Remove the hook if prvw installed it. Returns whether it did.
+fn uninstall(hooks_dir: &Path) -> Result<bool, String> {
+    let hook = hooks_dir.join(HOOK_NAME);
+    if !status(&hook).installed {
+        return Ok(false);
+    }
+    std::fs::remove_file(&hook)
+        .map_err(|e| format!("Failed to remove {}: {}", hook.display(), e))?;
+    Ok(true)
+}
+
+fn repo_hooks_dir(repo_path: &str) -> Result<PathBuf, String> {
+    let (repo, root) = local_range::open(repo_path)?;
+    Ok(hooks_dir(&repo, &root))
+}
+
+/// Whether the repo containing `repo_path` has prvw's pre-push hook, or another one.
+pub fn get_status(repo_path: &str) -> Result<PushHookStatus, String> {
+    Ok(status(&repo_hooks_dir(repo_path)?.join(HOOK_NAME)))
+}
+
+/// Install the hook in the repo containing `repo_path`, blocking pushes with findings of
+/// `fail_on` risk or higher ("high" when unset).
+pub fn install_in(repo_path: &str, fail_on: Option<&str>) -> Result<PushHookStatus, String> {
+    let fail_on = fail_on
+        .map(str::trim)
+        .filter(|level| !level.is_empty())
+        .unwrap_or(DEFAULT_FAIL_ON);
+    install(&repo_hooks_dir(repo_path)?, fail_on)
+}
+
+/// Remove prvw's hook from the repo containing `repo_path`.
+pub fn uninstall_from(repo_path: &str) -> Result<bool, String> {
+    uninstall(&repo_hooks_dir(repo_path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installs_and_removes_the_hook() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        let dir = hooks_dir(&repo, tmp.path());
+        assert!(dir.ends_with(".git/hooks"));
+
+        let installed = install(&dir, "high").unwrap();
+        assert!(installed.installed);
+        assert_eq!(installed.fail_on.as_deref(), Some("high"));
+        let updated = install(&dir, "medium").unwrap();
+        assert_eq!(updated.fail_on.as_deref(), Some("medium"));
+        assert!(install(&dir, "severe").is_err());
+
+        assert!(uninstall(&dir).unwrap());
+        assert!(!uninstall(&dir).unwrap());
+        assert!(!dir.join(HOOK_NAME).exists());
+    }
+
+    #[test]
+    fn leaves_foreign_hooks_alone() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join(HOOK_NAME), "#!/bin/sh\nmake lint\n").unwrap();
+        let existing = status(&tmp.path().join(HOOK_NAME));
+        assert!(existing.foreign && !existing.installed);
+        assert_eq!(existing.fail_on, None);
+        assert!(install(tmp.path(), "high").is_err());
+        assert!(!uninstall(tmp.path()).unwrap());
+        assert!(tmp.path().join(HOOK_NAME).exists());
+    }
+
+    #[test]
+    fn honors_hooks_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        repo.config()
+            .unwrap()
+            .set_str("core.hooksPath", ".githooks")
+            .unwrap();
+        assert_eq!(hooks_dir(&repo, tmp.path()), tmp.path().join(".githooks"));
+    }
+}
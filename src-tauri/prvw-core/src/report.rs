@@ -0,0 +1,280 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::session;
+use crate::types::{AnalysisResult, GroupSkip, ReleaseNoteEntry, ReviewSession};
+
+/// Changelog sections in output order, with their headings.
+pub const RELEASE_NOTE_CATEGORIES: &[(&str, &str)] = &[
+    ("added", "Added"),
+    ("changed", "Changed"),
+    ("fixed", "Fixed"),
+];
+
+fn skip_note(skip: &GroupSkip) -> String {
+    let what = if skip.snoozed {
+        "Snoozed until the next push"
+    } else {
+        "Skipped"
+    };
+    if skip.reason.is_empty() {
+        format!("{}, not reviewed", what)
+    } else {
+        format!("{}, not reviewed: {}", what, skip.reason)
+    }
+}
+
+/// Render an analysis into a Markdown review body suitable for a PR comment or review.
+/// Groups follow the session's custom order, checklist items checked in `session` are ticked,
+/// and its skipped groups are called out.
+pub fn render_review_markdown(result: &AnalysisResult, session: &ReviewSession) -> String {
+    let mut ordered = result.clone();
+    session::apply_group_order(&mut ordered, session);
+    let result = &ordered;
+    let mut md = String::from("## PR Review Summary\n\n");
+    let checked: HashSet<String> = session::checklist_items(session, result)
+        .into_iter()
+        .filter(|c| c.checked)
+        .map(|c| c.id)
+        .collect();
+    let skips = session::active_skips(session);
+
+    if !result.overall_summary.trim().is_empty() {
+        md.push_str(result.overall_summary.trim());
+        md.push_str("\n\n");
+    }
+
+    if !skips.is_empty() {
+        md.push_str("### Not Reviewed\n\n");
+        for g in result
+            .groups
+            .iter()
+            .filter(|g| skips.contains_key(g.id.as_str()))
+        {
+            md.push_str(&format!(
+                "- **{}** — {}\n",
+                g.title,
+                skip_note(skips[g.id.as_str()])
+            ));
+        }
+        md.push('\n');
+    }
+
+    if !result.groups.is_empty() {
+        md.push_str("### Change Groups\n\n");
+        for (i, g) in result.groups.iter().enumerate() {
+            md.push_str(&format!(
+                "#### {}. {} — `{}` · risk: {} ({}/100)\n\n",
+                i + 1,
+                g.title,
+                g.category,
+                g.risk,
+                g.risk_score
+            ));
+            if let Some(skip) = skips.get(g.id.as_str()) {
+                md.push_str(&format!("> **{}**\n\n", skip_note(skip)));
+            }
+            if !g.rationale.trim().is_empty() {
+                md.push_str(g.rationale.trim());
+                md.push_str("\n\n");
+            }
+            if !g.risk_evidence.is_empty() {
+                md.push_str("**Risk evidence**\n");
+                for ev in &g.risk_evidence {
+                    md.push_str(&format!("- `{}` {}\n", ev.hunk_id, ev.reason));
+                }
+                md.push('\n');
+            }
+            if !g.reviewer_checklist.is_empty() {
+                md.push_str("**Checklist**\n");
+                for item in &g.reviewer_checklist {
+                    let mark = if checked.contains(&session::checklist_item_id(&g.id, item)) {
+                        "x"
+                    } else {
+                        " "
+                    };
+                    md.push_str(&format!("- [{}] {}\n", mark, item));
+                }
+                md.push('\n');
+            }
+            if !g.suggested_tests.is_empty() {
+                md.push_str("**Suggested tests**\n");
+                for t in &g.suggested_tests {
+                    md.push_str(&format!("- {}\n", t));
+                }
+                md.push('\n');
+            }
+        }
+    }
+
+    if !result.questions.is_empty() {
+        md.push_str("### Questions\n\n");
+        for q in &result.questions {
+            md.push_str(&format!("- {}\n", q));
+        }
+        md.push('\n');
+    }
+
+    md.push_str("---\n_Generated with prvw_\n");
+    md
+}
+
+/// Render release-note entries as a Keep a Changelog style Markdown section.
+pub fn render_release_notes_markdown(entries: &[ReleaseNoteEntry]) -> String {
+    let mut md = String::new();
+    for (category, heading) in RELEASE_NOTE_CATEGORIES {
+        let items: Vec<&ReleaseNoteEntry> =
+            entries.iter().filter(|e| e.category == *category).collect();
+        if items.is_empty() {
+            continue;
+        }
+        md.push_str(&format!("### {}\n\n", heading));
+        for e in items {
+            md.push_str(&format!("- {}\n", e.text.trim()));
+        }
+        md.push('\n');
+    }
+    md
+}
+
+/// `render_review_markdown` of `analysis`; with `repo` and `pr_number`, the PR's session orders
+/// the groups, ticks checked checklist items and calls out skipped groups.
+pub fn render_summary(
+    app_data_dir: &Path,
+    analysis: &AnalysisResult,
+    repo: Option<&str>,
+    pr_number: Option<u32>,
+) -> Result<String, PrvwError> {
+    let session = match (repo.map(str::trim), pr_number) {
+        (Some(repo), Some(pr_number)) => {
+            validate_repo(repo)?;
+            session::load(app_data_dir, repo, pr_number)
+        }
+        _ => ReviewSession::default(),
+    };
+    Ok(render_review_markdown(analysis, &session))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{GroupOrder, IntentGroup, ReviewMark, RiskEvidence};
+
+    fn make_result() -> AnalysisResult {
+        AnalysisResult {
+            version: 1,
+            overall_summary: "Adds login throttling.\n\n- Rate limiter".to_string(),
+            groups: vec![IntentGroup {
+                id: "G1".to_string(),
+                title: "Rate limiting".to_string(),
+                category: "logic".to_string(),
+                rationale: "Prevents brute force.".to_string(),
+                risk: "high".to_string(),
+                risk_score: 75,
+                risk_evidence: vec![RiskEvidence {
+                    hunk_id: "H2".to_string(),
+                    reason: "Changes auth flow".to_string(),
+                }],
+                hunk_ids: vec!["H1".to_string(), "H2".to_string()],
+                reviewer_checklist: vec!["Limits are configurable".to_string()],
+                suggested_tests: vec!["Lockout after 5 attempts".to_string()],
+            }],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec!["Why 5 attempts?".to_string()],
+        }
+    }
+
+    #[test]
+    fn renders_all_sections() {
+        let md = render_review_markdown(&make_result(), &ReviewSession::default());
+        assert!(md.starts_with("## PR Review Summary"));
+        assert!(md.contains("Adds login throttling."));
+        assert!(md.contains("#### 1. Rate limiting — `logic` · risk: high (75/100)"));
+        assert!(md.contains("- `H2` Changes auth flow"));
+        assert!(md.contains("- [ ] Limits are configurable"));
+        assert!(md.contains("- Lockout after 5 attempts"));
+        assert!(md.contains("### Questions"));
+        assert!(md.contains("- Why 5 attempts?"));
+    }
+
+    #[test]
+    fn omits_empty_sections() {
+        let mut result = make_result();
+        result.groups.clear();
+        result.questions.clear();
+        let md = render_review_markdown(&result, &ReviewSession::default());
+        assert!(!md.contains("### Change Groups"));
+        assert!(!md.contains("### Questions"));
+    }
+
+    #[test]
+    fn renders_session_checks_and_skips() {
+        let mut result = make_result();
+        result.groups[0]
+            .reviewer_checklist
+            .push("Errors are logged".to_string());
+        let mut session = ReviewSession::default();
+        session.checked_items.insert(
+            session::checklist_item_id("G1", "Errors are logged"),
+            ReviewMark {
+                at: 1,
+                head_sha: None,
+            },
+        );
+        session.skipped_groups.insert(
+            "G1".to_string(),
+            GroupSkip {
+                reason: "Vendored".to_string(),
+                snoozed: false,
+                at: 1,
+                head_sha: None,
+            },
+        );
+        let md = render_review_markdown(&result, &session);
+        assert!(md.contains("- [ ] Limits are configurable"));
+        assert!(md.contains("- [x] Errors are logged"));
+        assert!(md
+            .contains("### Not Reviewed\n\n- **Rate limiting** — Skipped, not reviewed: Vendored"));
+        assert!(md.contains("> **Skipped, not reviewed: Vendored**"));
+    }
+
+    #[test]
+    fn renders_groups_in_custom_order() {
+        let mut result = make_result();
+        let mut second = result.groups[0].clone();
+        second.id = "G2".to_string();
+        second.title = "Config".to_string();
+        result.groups.push(second);
+        let session = ReviewSession {
+            group_order: Some(GroupOrder {
+                group_ids: vec!["G2".to_string()],
+                at: 1,
+                head_sha: None,
+            }),
+            ..ReviewSession::default()
+        };
+        let md = render_review_markdown(&result, &session);
+        assert!(md.contains("#### 1. Config"));
+        assert!(md.contains("#### 2. Rate limiting"));
+    }
+
+    #[test]
+    fn release_notes_grouped_by_category_in_order() {
+        let entry = |category: &str, text: &str| ReleaseNoteEntry {
+            category: category.to_string(),
+            text: text.to_string(),
+            group_ids: vec![],
+        };
+        let md = render_release_notes_markdown(&[
+            entry("fixed", "Login no longer hangs"),
+            entry("added", "Rate limiting for logins "),
+        ]);
+        assert_eq!(
+            md,
+            "### Added\n\n- Rate limiting for logins\n\n### Fixed\n\n- Login no longer hangs\n\n"
+        );
+    }
+}
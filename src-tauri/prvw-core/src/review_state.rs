@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::cache;
+use crate::diff_parser;
+use crate::error::PrvwError;
+use crate::types::{AnalysisResult, Hunk, ReviewState};
+
+/// Reviews held at once; opening another one drops the least recently used.
+const MAX_REVIEWS: usize = 16;
+
+struct Review {
+    hunks: Vec<Hunk>,
+    analysis: Option<AnalysisResult>,
+    /// `ReviewStates::tick` at the last use.
+    used_at: u64,
+}
+
+/// Parsed diffs of the reviews open in this app session and their latest analyses, keyed by
+/// session id, so the AI commands take an id and hunk ids instead of the whole diff as JSON.
+#[derive(Default)]
+pub struct ReviewStates {
+    reviews: Mutex<HashMap<String, Review>>,
+    clock: AtomicU64,
+}
+
+fn not_open(session_id: &str) -> PrvwError {
+    PrvwError::NotFound(format!(
+        "Review session '{}' is not open. Reload the diff.",
+        session_id
+    ))
+}
+
+impl ReviewStates {
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, Review>>, String> {
+        self.reviews
+            .lock()
+            .map_err(|_| "Review state lock poisoned".to_string())
+    }
+
+    fn insert(&self, session_id: &str, hunks: Vec<Hunk>) -> Result<(), String> {
+        let mut reviews = self.lock()?;
+        if !reviews.contains_key(session_id) && reviews.len() >= MAX_REVIEWS {
+            let oldest = reviews
+                .iter()
+                .min_by_key(|(_, review)| review.used_at)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                reviews.remove(&oldest);
+            }
+        }
+        reviews.insert(
+            session_id.to_string(),
+            Review {
+                hunks,
+                analysis: None,
+                used_at: self.tick(),
+            },
+        );
+        Ok(())
+    }
+
+    /// The review's hunks as JSON: all of them, or the ones in `hunk_ids`.
+    pub fn hunks_json(
+        &self,
+        session_id: &str,
+        hunk_ids: Option<&[String]>,
+    ) -> Result<String, PrvwError> {
+        let mut reviews = self.lock()?;
+        let review = reviews
+            .get_mut(session_id)
+            .ok_or_else(|| not_open(session_id))?;
+        review.used_at = self.tick();
+        let selected: Vec<&Hunk> = match hunk_ids {
+            Some(ids) => {
+                let ids: HashSet<&str> = ids.iter().map(String::as_str).collect();
+                review
+                    .hunks
+                    .iter()
+                    .filter(|h| ids.contains(h.id.as_str()))
+                    .collect()
+            }
+            None => review.hunks.iter().collect(),
+        };
+        Ok(serde_json::to_string(&selected)
+            .map_err(|e| format!("Failed to serialize hunks: {}", e))?)
+    }
+
+    /// The review's hunks and latest analysis, e.g. to compare them with a newer diff.
+    pub fn review(
+        &self,
+        session_id: &str,
+    ) -> Result<(Vec<Hunk>, Option<AnalysisResult>), PrvwError> {
+        let mut reviews = self.lock()?;
+        let review = reviews
+            .get_mut(session_id)
+            .ok_or_else(|| not_open(session_id))?;
+        review.used_at = self.tick();
+        Ok((review.hunks.clone(), review.analysis.clone()))
+    }
+
+    /// Parse `diff_text` and hold its hunks under an id derived from the repo and the diff.
+    pub fn open(&self, diff_text: String, repo: Option<String>) -> Result<ReviewState, String> {
+        let session_id =
+            cache::hash_key(&format!("{}\n{}", repo.as_deref().unwrap_or(""), diff_text));
+        let diff = diff_parser::parse(diff_text, repo)?;
+        self.insert(&session_id, diff.hunks.clone())?;
+        Ok(ReviewState { session_id, diff })
+    }
+
+    /// Keep `analysis` as the review's current one; a closed session is ignored.
+    pub fn set_analysis(&self, session_id: &str, analysis: &AnalysisResult) -> Result<(), String> {
+        if let Some(review) = self.lock()?.get_mut(session_id) {
+            review.analysis = Some(analysis.clone());
+            review.used_at = self.tick();
+        }
+        Ok(())
+    }
+
+    /// The latest analysis run on the review, if any.
+    pub fn analysis(&self, session_id: &str) -> Result<Option<AnalysisResult>, String> {
+        Ok(self
+            .lock()?
+            .get(session_id)
+            .and_then(|review| review.analysis.clone()))
+    }
+
+    /// Drop the review's hunks and analysis. Returns whether it was open.
+    pub fn close(&self, session_id: &str) -> Result<bool, String> {
+        Ok(self.lock()?.remove(session_id).is_some())
+    }
+}
+
+/// Hunks JSON for an AI command: taken from the review state when `session_id` is given,
+/// otherwise the `hunks_json` the caller sent.
+pub fn resolve_hunks(
+    states: &ReviewStates,
+    session_id: Option<&str>,
+    hunk_ids: Option<&[String]>,
+    hunks_json: Option<String>,
+) -> Result<String, PrvwError> {
+    match (session_id, hunks_json) {
+        (Some(session_id), _) => states.hunks_json(session_id, hunk_ids),
+        (None, Some(hunks_json)) => Ok(hunks_json),
+        (None, None) => Err(PrvwError::InvalidInput(
+            "Either a review session or hunks are required.".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(id: &str) -> Hunk {
+        Hunk {
+            id: id.to_string(),
+            file_path: format!("{}.rs", id),
+            subproject: None,
+            header: String::new(),
+            old_start: 1,
+            old_lines: 1,
+            new_start: 1,
+            new_lines: 1,
+            lines: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn selects_hunks_by_id() {
+        let states = ReviewStates::default();
+        states.insert("s1", vec![hunk("H1"), hunk("H2")]).unwrap();
+        let selected: Vec<Hunk> =
+            serde_json::from_str(&states.hunks_json("s1", Some(&["H2".to_string()])).unwrap())
+                .unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].id, "H2");
+        let all: Vec<Hunk> =
+            serde_json::from_str(&resolve_hunks(&states, Some("s1"), None, None).unwrap()).unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(states.hunks_json("s2", None).is_err());
+        assert_eq!(
+            resolve_hunks(&states, None, None, Some("[]".to_string())).unwrap(),
+            "[]"
+        );
+        assert!(resolve_hunks(&states, None, None, None).is_err());
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let states = ReviewStates::default();
+        for i in 0..MAX_REVIEWS {
+            states.insert(&format!("s{}", i), vec![hunk("H1")]).unwrap();
+        }
+        states.hunks_json("s0", None).unwrap();
+        states.insert("new", vec![hunk("H1")]).unwrap();
+        let reviews = states.lock().unwrap();
+        assert_eq!(reviews.len(), MAX_REVIEWS);
+        assert!(reviews.contains_key("s0") && reviews.contains_key("new"));
+        assert!(!reviews.contains_key("s1"));
+    }
+}
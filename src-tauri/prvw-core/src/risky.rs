@@ -0,0 +1,215 @@
+use std::sync::LazyLock;
+
+use crate::leftovers;
+use crate::types::{Hunk, RiskyPatternHit};
+
+/// Hits listed in the analysis prompt; the rest are summarized in one line.
+const MAX_PROMPT_HITS: usize = 30;
+
+/// SQL built by string concatenation or interpolation rather than bound parameters.
+static RAW_SQL_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r#"(?i)(\b(execute|exec|query|raw|raw_sql|sql_query|executeQuery|createNativeQuery)\s*\(\s*(f["']|format!|`|["'][^"']*\b(select|insert|update|delete)\b[^"']*["']\s*(\+|%|\.format))|["'`]\s*(select|insert\s+into|update|delete\s+from)\b[^"'`]*["'`]\s*\+|`(select|insert\s+into|update|delete\s+from)\b[^`]*\$\{)"#,
+    )
+    .expect("invalid raw sql regex")
+});
+
+static UNSAFE_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"\bunsafe\s*(\{|fn\b|impl\b|trait\b)|\bdangerouslySetInnerHTML\b")
+        .expect("invalid unsafe regex")
+});
+
+static EVAL_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r"(^|[^.\w])(eval|exec)\s*\(|\bnew\s+Function\s*\(|\bsetTimeout\s*\(\s*['\x22]",
+    )
+    .expect("invalid eval regex")
+});
+
+/// Certificate or hostname verification turned off.
+static TLS_DISABLED_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r"(?i)\bverify\s*=\s*False\b|\brejectUnauthorized\s*:\s*false\b|NODE_TLS_REJECT_UNAUTHORIZED|\bInsecureSkipVerify\s*:\s*true\b|danger_accept_invalid_(certs|hostnames)\s*\(\s*true|CERT_NONE\b|\bcheck_hostname\s*=\s*False\b",
+    )
+    .expect("invalid tls regex")
+});
+
+/// Handlers that catch everything and drop it.
+static SWALLOWED_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r"^\s*except\s*(Exception|BaseException)?\s*(as\s+\w+\s*)?:\s*(pass)?\s*$|\bcatch\s*(\(\s*\w*\s*\)\s*)?\{\s*\}|\bcatch\s*\(\s*(Throwable|Exception)\s+\w+\s*\)\s*\{\s*\}|\brescue\s*(=>\s*\w+)?\s*$|\.catch\(\s*\(\s*\w*\s*\)\s*=>\s*\{\s*\}\s*\)|\.ok\(\)\s*;\s*$",
+    )
+    .expect("invalid swallowed exception regex")
+});
+
+/// Classify one added line; the first matching kind wins. Comment lines are never flagged.
+fn classify(text: &str, prefix: Option<&str>) -> Option<&'static str> {
+    if let Some(prefix) = prefix {
+        if text.trim_start().starts_with(prefix) {
+            return None;
+        }
+    }
+    let patterns: [(&'static str, &regex::Regex); 5] = [
+        ("tls-disabled", &TLS_DISABLED_RE),
+        ("raw-sql", &RAW_SQL_RE),
+        ("eval", &EVAL_RE),
+        ("unsafe", &UNSAFE_RE),
+        ("swallowed-exception", &SWALLOWED_RE),
+    ];
+    patterns
+        .iter()
+        .find(|(_, re)| re.is_match(text))
+        .map(|(kind, _)| *kind)
+}
+
+/// Scan the added lines of every hunk for raw SQL, `unsafe`, `eval`, disabled TLS
+/// verification, and swallowed exceptions. Purely textual, so hits do not depend on the
+/// model noticing them.
+pub fn scan_hunks(hunks: &[Hunk]) -> Vec<RiskyPatternHit> {
+    let mut hits = Vec::new();
+    for hunk in hunks {
+        let prefix = leftovers::comment_prefix(&hunk.file_path);
+        for line in hunk.lines.iter().filter(|l| l.kind == "add") {
+            if let Some(kind) = classify(&line.text, prefix) {
+                hits.push(RiskyPatternHit {
+                    hunk_id: hunk.id.clone(),
+                    file_path: hunk.file_path.clone(),
+                    line: line.new_line,
+                    kind: kind.to_string(),
+                    text: leftovers::excerpt(&line.text),
+                });
+            }
+        }
+    }
+    hits
+}
+
+/// One line per hit for the analysis prompt, or an empty string when nothing was found.
+pub fn render_summary(hits: &[RiskyPatternHit]) -> String {
+    let mut lines: Vec<String> = hits
+        .iter()
+        .take(MAX_PROMPT_HITS)
+        .map(|h| {
+            let location = match h.line {
+                Some(line) => format!("{}:{}", h.file_path, line),
+                None => h.file_path.clone(),
+            };
+            format!("- {} [{}] {}: `{}`", h.hunk_id, h.kind, location, h.text)
+        })
+        .collect();
+    if hits.len() > MAX_PROMPT_HITS {
+        lines.push(format!("- ... and {} more", hits.len() - MAX_PROMPT_HITS));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DiffLine;
+
+    fn hunk(id: &str, file_path: &str, lines: &[(&str, &str)]) -> Hunk {
+        Hunk {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            subproject: None,
+            header: String::new(),
+            old_start: 1,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 0,
+            lines: lines
+                .iter()
+                .enumerate()
+                .map(|(i, (kind, text))| DiffLine {
+                    kind: kind.to_string(),
+                    old_line: None,
+                    new_line: Some(i as u32 + 1),
+                    text: text.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn kinds(hits: &[RiskyPatternHit]) -> Vec<(&str, Option<u32>)> {
+        hits.iter().map(|h| (h.kind.as_str(), h.line)).collect()
+    }
+
+    #[test]
+    fn detects_patterns_on_added_lines_only() {
+        let h = hunk(
+            "H1",
+            "src/db.py",
+            &[
+                (
+                    "add",
+                    "    cursor.execute(f\"SELECT * FROM users WHERE id = {uid}\")",
+                ),
+                ("remove", "    eval(expr)"),
+                ("add", "    requests.get(url, verify=False)"),
+                ("add", "    result = eval(expr)"),
+                ("add", "    except Exception:"),
+                ("add", "        pass"),
+                ("add", "    # eval(expr) used to live here"),
+            ],
+        );
+        assert_eq!(
+            kinds(&scan_hunks(&[h])),
+            vec![
+                ("raw-sql", Some(1)),
+                ("tls-disabled", Some(3)),
+                ("eval", Some(4)),
+                ("swallowed-exception", Some(5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_rust_and_js_patterns() {
+        let rs = hunk(
+            "H1",
+            "src/lib.rs",
+            &[
+                ("add", "    let p = unsafe { &*ptr };"),
+                ("add", "    // unsafe { not code }"),
+                (
+                    "add",
+                    "    Client::builder().danger_accept_invalid_certs(true);",
+                ),
+                ("add", "    let unsafe_count = 3;"),
+            ],
+        );
+        let js = hunk(
+            "H2",
+            "src/api.ts",
+            &[
+                ("add", "  db.query(`SELECT * FROM t WHERE id = ${id}`);"),
+                ("add", "  try { run(); } catch (e) {}"),
+                (
+                    "add",
+                    "  const agent = new Agent({ rejectUnauthorized: false });",
+                ),
+            ],
+        );
+        assert_eq!(
+            kinds(&scan_hunks(&[rs, js])),
+            vec![
+                ("unsafe", Some(1)),
+                ("tls-disabled", Some(3)),
+                ("raw-sql", Some(1)),
+                ("swallowed-exception", Some(2)),
+                ("tls-disabled", Some(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_caps_listed_hits() {
+        let lines: Vec<(&str, &str)> = vec![("add", "eval(x)"); MAX_PROMPT_HITS + 2];
+        let hits = scan_hunks(&[hunk("H1", "a.py", &lines)]);
+        let summary = render_summary(&hits);
+        assert!(summary.starts_with("- H1 [eval] a.py:1: `eval(x)`"));
+        assert!(summary.ends_with("- ... and 2 more"));
+        assert_eq!(render_summary(&[]), "");
+    }
+}
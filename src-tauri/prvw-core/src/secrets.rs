@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use base64::Engine as _;
+
+use crate::error::PrvwError;
+use crate::i18n::{t, Msg};
+use crate::keystore;
+use crate::settings;
+use crate::types::{AppSettings, SecretTest};
+
+const TEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// A token of a non-CLI provider, kept in the OS keyring and never in `settings.json` or
+/// `prvw.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Secret {
+    OpenAi,
+    GitHub,
+    GitLab,
+    /// Needs the "repository" scope, with write access to comment.
+    Gitea,
+    /// Personal access token with the "Code: Read" scope.
+    AzureDevOps,
+    /// `username:app_password`; the app password needs "Pull requests: Read", and "Write" to
+    /// comment.
+    Bitbucket,
+    /// Bearer token of the local editor API, generated by `api_server`. Not settable by name.
+    ApiServer,
+}
+
+impl Secret {
+    /// The secrets the commands accept by name.
+    const ALL: [Secret; 6] = [
+        Secret::OpenAi,
+        Secret::GitHub,
+        Secret::GitLab,
+        Secret::Gitea,
+        Secret::AzureDevOps,
+        Secret::Bitbucket,
+    ];
+
+    /// Name used by the commands.
+    pub fn name(self) -> &'static str {
+        match self {
+            Secret::OpenAi => "openai",
+            Secret::GitHub => "github",
+            Secret::GitLab => "gitlab",
+            Secret::Gitea => "gitea",
+            Secret::AzureDevOps => "azure-devops",
+            Secret::Bitbucket => "bitbucket",
+            Secret::ApiServer => "api-server",
+        }
+    }
+
+    /// Keyring entry; the ones from before this module keep their names.
+    fn keyring_user(self) -> &'static str {
+        match self {
+            Secret::OpenAi => "openai-api-key",
+            Secret::GitHub => "github-token",
+            Secret::GitLab => "gitlab-token",
+            Secret::Gitea => "gitea-token",
+            Secret::AzureDevOps => "azure-devops-pat",
+            Secret::Bitbucket => "bitbucket-app-password",
+            Secret::ApiServer => "api-server-token",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Secret::OpenAi => "OpenAI API key",
+            Secret::GitHub => "GitHub token",
+            Secret::GitLab => "GitLab token",
+            Secret::Gitea => "Gitea/Forgejo token",
+            Secret::AzureDevOps => "Azure DevOps token",
+            Secret::Bitbucket => "Bitbucket app password",
+            Secret::ApiServer => "API server token",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Secret, PrvwError> {
+        let name = name.trim();
+        Secret::ALL
+            .into_iter()
+            .find(|s| s.name() == name)
+            .ok_or_else(|| PrvwError::InvalidInput(format!("Unknown secret '{}'", name)))
+    }
+}
+
+/// Values read from the keyring once per process; keyring lookups can be slow or prompt.
+static CACHE: Mutex<Option<HashMap<Secret, Option<String>>>> = Mutex::new(None);
+
+fn remember(secret: Secret, value: Option<String>) {
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.get_or_insert_with(HashMap::new).insert(secret, value);
+    }
+}
+
+/// The stored value; None when there is none (or it is blank).
+pub fn get(secret: Secret) -> Result<Option<String>, String> {
+    if let Some(value) = CACHE
+        .lock()
+        .ok()
+        .and_then(|cache| cache.as_ref()?.get(&secret).cloned())
+    {
+        return Ok(value);
+    }
+    let value = keystore::get_password(secret.keyring_user())
+        .map_err(|e| format!("Failed to read {} from keyring: {}", secret.label(), e))?
+        .filter(|v| !v.trim().is_empty());
+    remember(secret, value.clone());
+    Ok(value)
+}
+
+/// The stored value, or an error asking for it to be set in Settings.
+pub fn require(secret: Secret) -> Result<String, PrvwError> {
+    get(secret)?.ok_or_else(|| PrvwError::NotConfigured(t(Msg::NotSet, &[&secret.label()])))
+}
+
+/// Reject values the secret's service can never accept, before they reach the keyring.
+fn validate(secret: Secret, value: &str) -> Result<(), PrvwError> {
+    if value.is_empty() {
+        return Err(PrvwError::InvalidInput(format!(
+            "{} is empty.",
+            secret.label()
+        )));
+    }
+    if value.chars().any(char::is_whitespace) {
+        return Err(PrvwError::InvalidInput(format!(
+            "{} must not contain spaces.",
+            secret.label()
+        )));
+    }
+    if secret == Secret::Bitbucket {
+        match value.split_once(':') {
+            Some((username, app_password)) if !username.is_empty() && !app_password.is_empty() => {}
+            _ => {
+                return Err(PrvwError::InvalidInput(
+                    "Bitbucket credentials must be given as username:app_password.".to_string(),
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn set(secret: Secret, value: &str) -> Result<(), PrvwError> {
+    let value = value.trim();
+    validate(secret, value)?;
+    keystore::set_password(secret.keyring_user(), value)
+        .map_err(|e| format!("Failed to store {}: {}", secret.label(), e))?;
+    remember(secret, Some(value.to_string()));
+    Ok(())
+}
+
+pub fn delete(secret: Secret) -> Result<(), String> {
+    keystore::delete(secret.keyring_user())
+        .map_err(|e| format!("Failed to delete {}: {}", secret.label(), e))?;
+    remember(secret, None);
+    Ok(())
+}
+
+fn basic_auth(credentials: &str) -> String {
+    format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(credentials)
+    )
+}
+
+/// URL and auth header of a cheap authenticated request checking `value`.
+fn test_request(
+    secret: Secret,
+    value: &str,
+    settings: &AppSettings,
+) -> Result<(String, &'static str, String), String> {
+    Ok(match secret {
+        Secret::OpenAi => (
+            "https://api.openai.com/v1/models".to_string(),
+            "Authorization",
+            format!("Bearer {}", value),
+        ),
+        Secret::GitHub => (
+            match &settings.gh_host {
+                Some(host) => format!("https://{}/api/v3/user", host),
+                None => "https://api.github.com/user".to_string(),
+            },
+            "Authorization",
+            format!("Bearer {}", value),
+        ),
+        Secret::GitLab => (
+            "https://gitlab.com/api/v4/user".to_string(),
+            "PRIVATE-TOKEN",
+            value.to_string(),
+        ),
+        Secret::Gitea => (
+            format!(
+                "{}/api/v1/user",
+                settings
+                    .gitea_url
+                    .as_deref()
+                    .ok_or("Gitea/Forgejo URL is not set. Add it in Settings.")?
+            ),
+            "Authorization",
+            format!("token {}", value),
+        ),
+        Secret::AzureDevOps => (
+            format!(
+                "{}/_apis/projects?api-version=7.0&$top=1",
+                settings
+                    .azure_devops_url
+                    .as_deref()
+                    .ok_or("Azure DevOps organization URL is not set. Add it in Settings.")?
+            ),
+            "Authorization",
+            basic_auth(&format!(":{}", value)),
+        ),
+        Secret::Bitbucket => (
+            "https://api.bitbucket.org/2.0/user".to_string(),
+            "Authorization",
+            basic_auth(value),
+        ),
+        Secret::ApiServer => return Err("The API server token has no service to check.".into()),
+    })
+}
+
+/// Check the stored secret with an authenticated request to its service.
+pub fn test(secret: Secret) -> Result<SecretTest, PrvwError> {
+    let Some(value) = get(secret)? else {
+        return Ok(SecretTest {
+            name: secret.name().to_string(),
+            stored: false,
+            ok: false,
+            message: t(Msg::NotSet, &[&secret.label()]),
+        });
+    };
+    let (url, header, auth) = test_request(secret, &value, &settings::current())?;
+    let agent = ureq::AgentBuilder::new().timeout(TEST_TIMEOUT).build();
+    let (ok, message) = match agent.get(&url).set(header, &auth).call() {
+        Ok(_) => (true, t(Msg::SecretWorks, &[&secret.label()])),
+        Err(ureq::Error::Status(401 | 403, _)) => {
+            (false, t(Msg::SecretRejected, &[&secret.label()]))
+        }
+        Err(ureq::Error::Status(code, _)) => {
+            (false, t(Msg::SecretHttpStatus, &[&secret.label(), &code]))
+        }
+        Err(e) => (false, t(Msg::SecretCheckFailed, &[&secret.label(), &e])),
+    };
+    Ok(SecretTest {
+        name: secret.name().to_string(),
+        stored: true,
+        ok,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names() {
+        for secret in Secret::ALL {
+            assert_eq!(Secret::parse(secret.name()), Ok(secret));
+        }
+        assert_eq!(Secret::parse(" azure-devops "), Ok(Secret::AzureDevOps));
+        assert!(Secret::parse("github-token").is_err());
+    }
+
+    #[test]
+    fn validates_values_per_secret() {
+        assert!(validate(Secret::GitHub, "ghp_abc").is_ok());
+        assert!(validate(Secret::GitHub, "").is_err());
+        assert!(validate(Secret::Gitea, "two words").is_err());
+        assert!(validate(Secret::Bitbucket, "ada:app-password").is_ok());
+        for invalid in ["app-password", ":app-password", "ada:"] {
+            assert!(validate(Secret::Bitbucket, invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn builds_test_requests() {
+        let mut settings = AppSettings::default();
+        assert_eq!(
+            test_request(Secret::GitHub, "t", &settings).unwrap(),
+            (
+                "https://api.github.com/user".to_string(),
+                "Authorization",
+                "Bearer t".to_string()
+            )
+        );
+        assert!(test_request(Secret::Gitea, "t", &settings).is_err());
+
+        settings.gh_host = Some("github.example.com".to_string());
+        settings.azure_devops_url = Some("https://dev.azure.com/contoso".to_string());
+        assert_eq!(
+            test_request(Secret::GitHub, "t", &settings).unwrap().0,
+            "https://github.example.com/api/v3/user"
+        );
+        assert_eq!(
+            test_request(Secret::AzureDevOps, "pat", &settings).unwrap(),
+            (
+                "https://dev.azure.com/contoso/_apis/projects?api-version=7.0&$top=1".to_string(),
+                "Authorization",
+                "Basic OnBhdA==".to_string()
+            )
+        );
+        assert_eq!(
+            test_request(Secret::GitLab, "t", &settings).unwrap().1,
+            "PRIVATE-TOKEN"
+        );
+    }
+}
@@ -0,0 +1,1002 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::json_store;
+use crate::time::now_secs;
+use crate::types::{
+    AnalysisResult, BlockingItem, ChecklistItem, GroupOrder, GroupProgress, GroupSkip, IntentGroup,
+    NextUnreviewed, ReviewMark, ReviewProgress, ReviewSession, SessionUpdate,
+};
+
+/// Review progress is user data, not cache: it lives outside `cache/` so `clear_cache` keeps it.
+const SESSIONS_DIR: &str = "sessions";
+
+/// Gaps between consecutive marks longer than this count as breaks, not review time.
+const IDLE_GAP_SECS: u64 = 15 * 60;
+
+/// Serializes read-modify-write cycles of session files across concurrent commands.
+pub static SESSION_LOCK: Mutex<()> = Mutex::new(());
+
+fn session_path(app_data_dir: &Path, repo: &str, pr_number: u32) -> PathBuf {
+    app_data_dir
+        .join(SESSIONS_DIR)
+        .join(format!("{}__{}.json", repo.replace('/', "__"), pr_number))
+}
+
+pub fn load(app_data_dir: &Path, repo: &str, pr_number: u32) -> ReviewSession {
+    json_store::load(&session_path(app_data_dir, repo, pr_number)).unwrap_or_else(|| {
+        ReviewSession {
+            repo: repo.to_string(),
+            pr_number,
+            ..ReviewSession::default()
+        }
+    })
+}
+
+/// Every saved session; unreadable files are skipped.
+pub fn load_all(app_data_dir: &Path) -> Vec<ReviewSession> {
+    let Ok(entries) = fs::read_dir(app_data_dir.join(SESSIONS_DIR)) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|data| serde_json::from_str(&data).ok())
+        .collect()
+}
+
+/// Write via a temp file and rename so a crash never leaves a truncated session.
+pub fn save(app_data_dir: &Path, session: &ReviewSession) -> Result<(), String> {
+    json_store::save(
+        &session_path(app_data_dir, &session.repo, session.pr_number),
+        session,
+        "session",
+    )
+}
+
+fn apply_marks(
+    marks: &mut BTreeMap<String, ReviewMark>,
+    mark: &[String],
+    unmark: &[String],
+    stamp: &ReviewMark,
+) {
+    for id in mark {
+        marks.insert(id.clone(), stamp.clone());
+    }
+    for id in unmark {
+        marks.remove(id);
+    }
+}
+
+fn apply_blocking(items: &mut Vec<BlockingItem>, update: &SessionUpdate, now: u64) {
+    for item in &update.block_items {
+        if item.id.trim().is_empty() || item.text.trim().is_empty() {
+            continue;
+        }
+        let item = BlockingItem {
+            resolved: false,
+            at: now,
+            ..item.clone()
+        };
+        match items.iter_mut().find(|i| i.id == item.id) {
+            Some(existing) => *existing = item,
+            None => items.push(item),
+        }
+    }
+    for item in items.iter_mut() {
+        if update.resolve_items.contains(&item.id) {
+            item.resolved = true;
+        }
+    }
+    items.retain(|i| !update.unblock_items.contains(&i.id));
+}
+
+fn apply_skips(
+    skips: &mut BTreeMap<String, GroupSkip>,
+    update: &SessionUpdate,
+    stamp: &ReviewMark,
+) {
+    let reason = update.skip_reason.as_deref().unwrap_or_default().trim();
+    for group_id in &update.skip_groups {
+        skips.insert(
+            group_id.clone(),
+            GroupSkip {
+                reason: reason.to_string(),
+                snoozed: update.snooze,
+                at: stamp.at,
+                head_sha: stamp.head_sha.clone(),
+            },
+        );
+    }
+    for group_id in &update.unskip_groups {
+        skips.remove(group_id);
+    }
+}
+
+/// Whether a skip still applies: snoozes lapse once the session moves to another head.
+pub fn skip_active(skip: &GroupSkip, session: &ReviewSession) -> bool {
+    !skip.snoozed
+        || match (&skip.head_sha, &session.head_sha) {
+            (Some(skip_head), Some(head)) => skip_head == head,
+            _ => true,
+        }
+}
+
+/// Skips and snoozes in effect, by group id.
+pub fn active_skips(session: &ReviewSession) -> BTreeMap<&str, &GroupSkip> {
+    session
+        .skipped_groups
+        .iter()
+        .filter(|(_, skip)| skip_active(skip, session))
+        .map(|(id, skip)| (id.as_str(), skip))
+        .collect()
+}
+
+/// The custom group order in effect: one set at another head no longer applies, since the
+/// groups may have been regrouped since.
+pub fn active_group_order(session: &ReviewSession) -> &[String] {
+    match &session.group_order {
+        Some(order)
+            if match (&order.head_sha, &session.head_sha) {
+                (Some(order_head), Some(head)) => order_head == head,
+                _ => true,
+            } =>
+        {
+            &order.group_ids
+        }
+        _ => &[],
+    }
+}
+
+/// Put `analysis.groups` in the session's custom order; groups the order does not list keep
+/// their analysis order after the listed ones.
+pub fn apply_group_order(analysis: &mut AnalysisResult, session: &ReviewSession) {
+    let order = active_group_order(session);
+    if order.is_empty() {
+        return;
+    }
+    analysis.groups.sort_by_key(|g| {
+        order
+            .iter()
+            .position(|id| id == &g.id)
+            .unwrap_or(usize::MAX)
+    });
+}
+
+/// Blocking items not yet resolved, in the order they were marked.
+pub fn open_blocking_items(session: &ReviewSession) -> Vec<BlockingItem> {
+    session
+        .blocking_items
+        .iter()
+        .filter(|i| !i.resolved)
+        .cloned()
+        .collect()
+}
+
+/// Group ids the session keeps state for: review marks, checked items, skips, assignments,
+/// the custom order, and blocking items.
+pub fn group_ids_in_use(session: &ReviewSession) -> HashSet<&str> {
+    session
+        .reviewed_groups
+        .keys()
+        .chain(session.skipped_groups.keys())
+        .chain(session.assignments.keys())
+        .map(String::as_str)
+        .chain(
+            session
+                .checked_items
+                .keys()
+                .filter_map(|id| id.split(':').next()),
+        )
+        .chain(
+            session
+                .blocking_items
+                .iter()
+                .filter_map(|i| i.group_id.as_deref()),
+        )
+        .chain(
+            session
+                .group_order
+                .iter()
+                .flat_map(|o| o.group_ids.iter().map(String::as_str)),
+        )
+        .collect()
+}
+
+/// Id of a reviewer checklist item of a group: `G<n>:checklist:<hash of its text>`, so a check
+/// stays on its item when a refine or edit reorders, adds or removes items. The hash is 32-bit
+/// FNV-1a over the trimmed text, which the frontend computes too.
+pub fn checklist_item_id(group_id: &str, text: &str) -> String {
+    let hash = text.trim().bytes().fold(0x811c_9dc5_u32, |h, b| {
+        (h ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    });
+    format!("{}:checklist:{:08x}", group_id, hash)
+}
+
+/// Every reviewer checklist item of `analysis`, checked when it was checked at the current head.
+pub fn checklist_items(session: &ReviewSession, analysis: &AnalysisResult) -> Vec<ChecklistItem> {
+    analysis
+        .groups
+        .iter()
+        .flat_map(|group| {
+            group.reviewer_checklist.iter().map(|text| {
+                let id = checklist_item_id(&group.id, text);
+                let checked = session
+                    .checked_items
+                    .get(&id)
+                    .is_some_and(|m| is_current(m, session));
+                ChecklistItem {
+                    id,
+                    group_id: group.id.clone(),
+                    text: text.clone(),
+                    checked,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Apply `update` to `session`. New marks are stamped with `now` and the update's head SHA
+/// (or the session's last known head); re-marking an item refreshes its stamp.
+pub fn apply_update(session: &mut ReviewSession, update: &SessionUpdate, now: u64) {
+    if update.head_sha.is_some() {
+        session.head_sha.clone_from(&update.head_sha);
+    }
+    let stamp = ReviewMark {
+        at: now,
+        head_sha: session.head_sha.clone(),
+    };
+    apply_marks(
+        &mut session.reviewed_groups,
+        &update.mark_groups,
+        &update.unmark_groups,
+        &stamp,
+    );
+    apply_marks(
+        &mut session.reviewed_hunks,
+        &update.mark_hunks,
+        &update.unmark_hunks,
+        &stamp,
+    );
+    apply_marks(
+        &mut session.checked_items,
+        &update.check_items,
+        &update.uncheck_items,
+        &stamp,
+    );
+    apply_skips(&mut session.skipped_groups, update, &stamp);
+    if let Some(group_ids) = &update.group_order {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let group_ids: Vec<String> = group_ids
+            .iter()
+            .map(|id| id.trim())
+            .filter(|id| !id.is_empty() && seen.insert(*id))
+            .map(str::to_string)
+            .collect();
+        session.group_order = (!group_ids.is_empty()).then(|| GroupOrder {
+            group_ids,
+            at: now,
+            head_sha: session.head_sha.clone(),
+        });
+    }
+    apply_blocking(&mut session.blocking_items, update, now);
+    session.updated_at = now;
+}
+
+/// Marks made at another head SHA are stale: the code they covered may have changed.
+pub fn is_current(mark: &ReviewMark, session: &ReviewSession) -> bool {
+    match (&mark.head_sha, &session.head_sha) {
+        (Some(mark_head), Some(head)) => mark_head == head,
+        _ => true,
+    }
+}
+
+/// Sum of the gaps between consecutive current marks, skipping idle breaks.
+pub fn time_spent(session: &ReviewSession) -> u64 {
+    let mut stamps: Vec<u64> = session
+        .reviewed_groups
+        .values()
+        .chain(session.reviewed_hunks.values())
+        .filter(|m| is_current(m, session))
+        .map(|m| m.at)
+        .collect();
+    stamps.sort_unstable();
+    stamps
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|gap| *gap <= IDLE_GAP_SECS)
+        .sum()
+}
+
+/// Every hunk id in `analysis`: grouped, unassigned and non-substantive.
+fn all_hunk_ids(analysis: &AnalysisResult) -> HashSet<&str> {
+    analysis
+        .groups
+        .iter()
+        .flat_map(|g| g.hunk_ids.iter())
+        .chain(analysis.unassigned_hunk_ids.iter())
+        .chain(analysis.non_substantive_hunk_ids.iter())
+        .map(String::as_str)
+        .collect()
+}
+
+/// Hunks of `analysis` marked at the current head, directly or through a group containing them.
+fn reviewed_hunk_ids<'a>(
+    session: &ReviewSession,
+    analysis: &'a AnalysisResult,
+) -> HashSet<&'a str> {
+    let marked = |marks: &BTreeMap<String, ReviewMark>, id: &str| {
+        marks.get(id).is_some_and(|m| is_current(m, session))
+    };
+    let mut reviewed: HashSet<&str> = HashSet::new();
+    for group in &analysis.groups {
+        if marked(&session.reviewed_groups, &group.id) {
+            reviewed.extend(group.hunk_ids.iter().map(String::as_str));
+        }
+    }
+    reviewed.extend(
+        all_hunk_ids(analysis)
+            .into_iter()
+            .filter(|id| marked(&session.reviewed_hunks, id)),
+    );
+    reviewed
+}
+
+/// Progress of `session` over the hunks of `analysis`. A substantive hunk is reviewed when it
+/// or a group containing it is marked at the current head; non-substantive hunks are skipped,
+/// and so are unreviewed hunks that belong only to skipped or snoozed groups.
+pub fn progress(session: &ReviewSession, analysis: &AnalysisResult) -> ReviewProgress {
+    let skipped: HashSet<&str> = analysis
+        .non_substantive_hunk_ids
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let all = all_hunk_ids(analysis);
+    let mut reviewed = reviewed_hunk_ids(session, analysis);
+    reviewed.retain(|id| !skipped.contains(id));
+    let checklist = checklist_items(session, analysis);
+    let checked_in = |group_id: &str| {
+        let items = checklist.iter().filter(|c| c.group_id == group_id);
+        (items.clone().filter(|c| c.checked).count(), items.count())
+    };
+    let skips = active_skips(session);
+    let still_reviewed: HashSet<&str> = analysis
+        .groups
+        .iter()
+        .filter(|g| !skips.contains_key(g.id.as_str()))
+        .flat_map(|g| g.hunk_ids.iter())
+        .chain(analysis.unassigned_hunk_ids.iter())
+        .map(String::as_str)
+        .collect();
+    let left_out: HashSet<&str> = analysis
+        .groups
+        .iter()
+        .filter(|g| skips.contains_key(g.id.as_str()))
+        .flat_map(|g| g.hunk_ids.iter())
+        .map(String::as_str)
+        .filter(|id| {
+            !still_reviewed.contains(id) && !skipped.contains(id) && !reviewed.contains(id)
+        })
+        .collect();
+
+    let groups: Vec<GroupProgress> = analysis
+        .groups
+        .iter()
+        .map(|group| {
+            let substantive: HashSet<&str> = group
+                .hunk_ids
+                .iter()
+                .map(String::as_str)
+                .filter(|id| !skipped.contains(id))
+                .collect();
+            let done = substantive
+                .iter()
+                .filter(|id| reviewed.contains(*id))
+                .count();
+            let (checked_items, total_checklist_items) = checked_in(&group.id);
+            GroupProgress {
+                group_id: group.id.clone(),
+                title: group.title.clone(),
+                reviewed_hunks: done,
+                total_hunks: substantive.len(),
+                complete: done == substantive.len(),
+                checked_items,
+                total_checklist_items,
+                skip: skips.get(group.id.as_str()).map(|s| (*s).clone()),
+            }
+        })
+        .collect();
+
+    let auto_skipped = all.iter().filter(|id| skipped.contains(*id)).count();
+    ReviewProgress {
+        total_hunks: all.len(),
+        reviewed_hunks: reviewed.len(),
+        auto_skipped_hunks: auto_skipped,
+        skipped_hunks: left_out.len(),
+        groups,
+        time_spent_secs: time_spent(session),
+        ready_to_submit: reviewed.len() + auto_skipped + left_out.len() == all.len(),
+        open_blocking_items: session
+            .blocking_items
+            .iter()
+            .filter(|i| !i.resolved)
+            .count(),
+        checked_items: checklist.iter().filter(|c| c.checked).count(),
+        total_checklist_items: checklist.len(),
+    }
+}
+
+/// The first unreviewed hunk after `current_hunk_id` (wrapping around) in review order: groups
+/// in `group_order` (or the session's custom order when it is empty), then the remaining groups in analysis order, then unassigned hunks.
+/// Non-substantive hunks and hunks of groups in `skip_group_ids` or skipped in the session are
+/// passed over; a hunk that appears in several groups is visited with the first of them.
+pub fn next_unreviewed_hunk(
+    session: &ReviewSession,
+    analysis: &AnalysisResult,
+    group_order: &[String],
+    skip_group_ids: &[String],
+    current_hunk_id: Option<&str>,
+) -> Option<NextUnreviewed> {
+    let group_order = if group_order.is_empty() {
+        active_group_order(session)
+    } else {
+        group_order
+    };
+    let mut groups: Vec<&IntentGroup> = group_order
+        .iter()
+        .filter_map(|id| analysis.groups.iter().find(|g| &g.id == id))
+        .collect();
+    for group in &analysis.groups {
+        if !groups.iter().any(|g| g.id == group.id) {
+            groups.push(group);
+        }
+    }
+
+    let mut seen: HashSet<&str> = HashSet::new();
+    let order: Vec<(Option<&str>, &str)> = groups
+        .iter()
+        .flat_map(|g| g.hunk_ids.iter().map(|h| (Some(g.id.as_str()), h.as_str())))
+        .chain(
+            analysis
+                .unassigned_hunk_ids
+                .iter()
+                .map(|h| (None, h.as_str())),
+        )
+        .filter(|(_, h)| seen.insert(*h))
+        .collect();
+
+    let reviewed = reviewed_hunk_ids(session, analysis);
+    let skipped: HashSet<&str> = analysis
+        .non_substantive_hunk_ids
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let skips = active_skips(session);
+    let pending: Vec<(Option<&str>, &str)> = order
+        .iter()
+        .copied()
+        .filter(|(group_id, hunk_id)| {
+            !reviewed.contains(hunk_id)
+                && !skipped.contains(hunk_id)
+                && !group_id
+                    .is_some_and(|g| skip_group_ids.iter().any(|s| s == g) || skips.contains_key(g))
+        })
+        .collect();
+
+    let start = current_hunk_id
+        .and_then(|current| order.iter().position(|(_, h)| *h == current))
+        .map_or(0, |i| i + 1);
+    let after_current = |hunk_id: &str| {
+        order
+            .iter()
+            .position(|(_, h)| *h == hunk_id)
+            .is_some_and(|i| i >= start)
+    };
+    let (group_id, hunk_id) = pending
+        .iter()
+        .find(|(_, h)| after_current(h))
+        .or_else(|| pending.first())
+        .copied()?;
+    Some(NextUnreviewed {
+        group_id: group_id.map(str::to_string),
+        hunk_id: hunk_id.to_string(),
+        remaining: pending.len(),
+    })
+}
+
+/// Apply `update` to the PR's saved session and save it. Returns the saved session.
+pub fn save_update(
+    app_data_dir: &Path,
+    repo: &str,
+    pr_number: u32,
+    update: &SessionUpdate,
+) -> Result<ReviewSession, String> {
+    let _guard = SESSION_LOCK
+        .lock()
+        .map_err(|_| "Session lock poisoned".to_string())?;
+    let mut session = load(app_data_dir, repo, pr_number);
+    apply_update(&mut session, update, now_secs());
+    save(app_data_dir, &session)?;
+    Ok(session)
+}
+
+/// `load` for a repo given by a caller, which is checked first.
+pub fn get(app_data_dir: &Path, repo: &str, pr_number: u32) -> Result<ReviewSession, PrvwError> {
+    let repo = repo.trim();
+    validate_repo(repo)?;
+    Ok(load(app_data_dir, repo, pr_number))
+}
+
+/// `save_update` for a repo given by a caller, which is checked first.
+pub fn update(
+    app_data_dir: &Path,
+    repo: &str,
+    pr_number: u32,
+    update: &SessionUpdate,
+) -> Result<ReviewSession, PrvwError> {
+    let repo = repo.trim();
+    validate_repo(repo)?;
+    Ok(save_update(app_data_dir, repo, pr_number, update)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn marks_are_stamped_with_time_and_head() {
+        let mut session = ReviewSession::default();
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                head_sha: Some("abc".to_string()),
+                mark_groups: ids(&["G1", "G2"]),
+                mark_hunks: ids(&["H1"]),
+                ..SessionUpdate::default()
+            },
+            100,
+        );
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                unmark_groups: ids(&["G2"]),
+                mark_hunks: ids(&["H2"]),
+                ..SessionUpdate::default()
+            },
+            200,
+        );
+        assert_eq!(
+            session.reviewed_groups.keys().collect::<Vec<_>>(),
+            vec!["G1"]
+        );
+        assert_eq!(
+            session.reviewed_hunks.get("H2"),
+            Some(&ReviewMark {
+                at: 200,
+                head_sha: Some("abc".to_string()),
+            })
+        );
+        assert_eq!(session.updated_at, 200);
+    }
+
+    fn blocking(id: &str, text: &str) -> BlockingItem {
+        BlockingItem {
+            id: id.to_string(),
+            kind: "checklist".to_string(),
+            group_id: Some("G1".to_string()),
+            hunk_id: None,
+            text: text.to_string(),
+            resolved: false,
+            at: 0,
+        }
+    }
+
+    #[test]
+    fn blocking_items_open_resolve_and_reopen() {
+        let mut session = ReviewSession::default();
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                block_items: vec![
+                    blocking("G1:checklist:0", "Migration is reversible"),
+                    blocking("G1:checklist:1", "Feature flag defaults off"),
+                    blocking("G1:checklist:2", " "),
+                ],
+                ..SessionUpdate::default()
+            },
+            100,
+        );
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                resolve_items: ids(&["G1:checklist:0"]),
+                unblock_items: ids(&["G1:checklist:1"]),
+                ..SessionUpdate::default()
+            },
+            200,
+        );
+        assert_eq!(session.blocking_items.len(), 1);
+        assert!(open_blocking_items(&session).is_empty());
+
+        // marking it blocking again reopens it
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                block_items: vec![blocking("G1:checklist:0", "Migration is reversible")],
+                ..SessionUpdate::default()
+            },
+            300,
+        );
+        let open = open_blocking_items(&session);
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].at, 300);
+    }
+
+    fn group(id: &str, hunk_ids: &[&str]) -> IntentGroup {
+        IntentGroup {
+            id: id.to_string(),
+            title: id.to_string(),
+            category: String::new(),
+            rationale: String::new(),
+            risk: "low".to_string(),
+            risk_score: 0,
+            risk_evidence: vec![],
+            hunk_ids: ids(hunk_ids),
+            reviewer_checklist: vec![],
+            suggested_tests: vec![],
+        }
+    }
+
+    #[test]
+    fn progress_counts_groups_hunks_and_skips() {
+        let analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![group("G1", &["H1", "H2"]), group("G2", &["H3", "H4", "H5"])],
+            unassigned_hunk_ids: ids(&["H6"]),
+            non_substantive_hunk_ids: ids(&["H5"]),
+            questions: vec![],
+        };
+        let mut session = ReviewSession::default();
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                head_sha: Some("old".to_string()),
+                mark_hunks: ids(&["H6"]),
+                ..SessionUpdate::default()
+            },
+            50,
+        );
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                head_sha: Some("new".to_string()),
+                mark_groups: ids(&["G1"]),
+                ..SessionUpdate::default()
+            },
+            100,
+        );
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                mark_hunks: ids(&["H3"]),
+                ..SessionUpdate::default()
+            },
+            160,
+        );
+
+        let p = progress(&session, &analysis);
+        assert_eq!(p.total_hunks, 6);
+        // H6 was marked at an older head and no longer counts
+        assert_eq!(p.reviewed_hunks, 3);
+        assert_eq!(p.auto_skipped_hunks, 1);
+        assert_eq!(p.time_spent_secs, 60);
+        assert!(!p.ready_to_submit);
+        assert!(p.groups[0].complete);
+        assert_eq!(
+            (p.groups[1].reviewed_hunks, p.groups[1].total_hunks),
+            (1, 2)
+        );
+
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                mark_hunks: ids(&["H4", "H6"]),
+                ..SessionUpdate::default()
+            },
+            5000,
+        );
+        let p = progress(&session, &analysis);
+        assert!(p.ready_to_submit);
+        // the long pause before the last marks is a break
+        assert_eq!(p.time_spent_secs, 60);
+    }
+
+    #[test]
+    fn skipped_groups_leave_progress_and_snoozes_lapse_on_push() {
+        let analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![
+                group("G1", &["H1", "H2"]),
+                group("G2", &["H2", "H3"]),
+                group("G3", &["H4"]),
+            ],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        };
+        let mut session = ReviewSession::default();
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                head_sha: Some("a".to_string()),
+                skip_groups: ids(&["G1"]),
+                skip_reason: Some(" Generated code ".to_string()),
+                mark_groups: ids(&["G2"]),
+                ..SessionUpdate::default()
+            },
+            100,
+        );
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                skip_groups: ids(&["G3"]),
+                snooze: true,
+                ..SessionUpdate::default()
+            },
+            110,
+        );
+        assert_eq!(session.skipped_groups["G1"].reason, "Generated code");
+
+        let p = progress(&session, &analysis);
+        // H2 is also in G2 (reviewed), H4 only in the snoozed G3
+        assert_eq!(p.skipped_hunks, 2);
+        assert!(p.ready_to_submit);
+        assert!(p.groups[0].skip.is_some());
+        assert!(p.groups[2].skip.as_ref().is_some_and(|s| s.snoozed));
+        assert!(next_unreviewed_hunk(&session, &analysis, &[], &[], None).is_none());
+
+        // a push ends the snooze, the skip stays
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                head_sha: Some("b".to_string()),
+                ..SessionUpdate::default()
+            },
+            200,
+        );
+        assert_eq!(
+            active_skips(&session).keys().copied().collect::<Vec<_>>(),
+            vec!["G1"]
+        );
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                unskip_groups: ids(&["G1"]),
+                ..SessionUpdate::default()
+            },
+            210,
+        );
+        assert!(session.skipped_groups.contains_key("G3"));
+        assert!(active_skips(&session).is_empty());
+    }
+
+    #[test]
+    fn checklist_items_are_checked_per_head_and_counted() {
+        let mut g1 = group("G1", &["H1"]);
+        g1.reviewer_checklist = ids(&["Limits are configurable", "Errors are logged"]);
+        let mut g2 = group("G2", &["H2"]);
+        g2.reviewer_checklist = ids(&["Docs updated"]);
+        let analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![g1, g2],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        };
+        let limits = checklist_item_id("G1", "Limits are configurable");
+        let errors = checklist_item_id("G1", " Errors are logged ");
+        // the frontend's checklistItemId yields the same id
+        assert_eq!(errors, "G1:checklist:524c0c82");
+        let docs = checklist_item_id("G2", "Docs updated");
+        let mut session = ReviewSession::default();
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                head_sha: Some("old".to_string()),
+                check_items: vec![docs.clone()],
+                ..SessionUpdate::default()
+            },
+            50,
+        );
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                head_sha: Some("new".to_string()),
+                check_items: vec![limits.clone(), errors.clone()],
+                ..SessionUpdate::default()
+            },
+            100,
+        );
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                uncheck_items: vec![errors.clone()],
+                ..SessionUpdate::default()
+            },
+            110,
+        );
+
+        let items = checklist_items(&session, &analysis);
+        assert_eq!(
+            items
+                .iter()
+                .map(|c| (c.id.as_str(), c.checked))
+                .collect::<Vec<_>>(),
+            vec![
+                (limits.as_str(), true),
+                (errors.as_str(), false),
+                // checked at an older head
+                (docs.as_str(), false),
+            ]
+        );
+        let p = progress(&session, &analysis);
+        assert_eq!((p.checked_items, p.total_checklist_items), (1, 3));
+        assert_eq!(
+            (p.groups[0].checked_items, p.groups[0].total_checklist_items),
+            (1, 2)
+        );
+
+        // a refine that puts a new item first keeps the check on its item
+        let mut refined = analysis.clone();
+        refined.groups[0]
+            .reviewer_checklist
+            .insert(0, "Rollout is flagged".to_string());
+        let items = checklist_items(&session, &refined);
+        assert_eq!(
+            items
+                .iter()
+                .filter(|c| c.checked)
+                .map(|c| c.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Limits are configurable"]
+        );
+    }
+
+    #[test]
+    fn next_unreviewed_follows_order_and_wraps() {
+        let analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![
+                group("G1", &["H1", "H2"]),
+                group("G2", &["H3", "H4"]),
+                group("G3", &["H5"]),
+            ],
+            unassigned_hunk_ids: ids(&["H6"]),
+            non_substantive_hunk_ids: ids(&["H4"]),
+            questions: vec![],
+        };
+        let mut session = ReviewSession::default();
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                mark_hunks: ids(&["H3"]),
+                ..SessionUpdate::default()
+            },
+            100,
+        );
+        let order = ids(&["G2", "G1"]);
+        let skip = ids(&["G3"]);
+        let next = |current: Option<&str>| {
+            next_unreviewed_hunk(&session, &analysis, &order, &skip, current)
+                .map(|n| (n.group_id, n.hunk_id, n.remaining))
+        };
+
+        // G2 first: H3 is reviewed and H4 non-substantive; G3 is skipped
+        assert_eq!(
+            next(None),
+            Some((Some("G1".to_string()), "H1".to_string(), 3))
+        );
+        assert_eq!(next(Some("H2")), Some((None, "H6".to_string(), 3)));
+        assert_eq!(
+            next(Some("H6")),
+            Some((Some("G1".to_string()), "H1".to_string(), 3))
+        );
+
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                mark_groups: ids(&["G1"]),
+                mark_hunks: ids(&["H6"]),
+                ..SessionUpdate::default()
+            },
+            200,
+        );
+        assert_eq!(
+            next_unreviewed_hunk(&session, &analysis, &order, &skip, None).map(|n| n.hunk_id),
+            None
+        );
+    }
+
+    #[test]
+    fn custom_group_order_applies_at_its_head() {
+        let mut analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![
+                group("G1", &["H1"]),
+                group("G2", &["H2"]),
+                group("G3", &["H3"]),
+            ],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        };
+        let mut session = ReviewSession::default();
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                head_sha: Some("a".to_string()),
+                group_order: Some(ids(&["G3", " G3", "", "G9", "G2"])),
+                ..SessionUpdate::default()
+            },
+            100,
+        );
+        assert_eq!(active_group_order(&session), ids(&["G3", "G9", "G2"]));
+        assert_eq!(
+            next_unreviewed_hunk(&session, &analysis, &[], &[], None).map(|n| n.hunk_id),
+            Some("H3".to_string())
+        );
+        let mut ordered = analysis.clone();
+        apply_group_order(&mut ordered, &session);
+        let order: Vec<&str> = ordered.groups.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(order, vec!["G3", "G2", "G1"]);
+
+        // a new head falls back to the analysis order
+        session.head_sha = Some("b".to_string());
+        apply_group_order(&mut analysis, &session);
+        assert_eq!(analysis.groups[0].id, "G1");
+
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                group_order: Some(vec![]),
+                ..SessionUpdate::default()
+            },
+            200,
+        );
+        assert!(session.group_order.is_none());
+    }
+
+    #[test]
+    fn sessions_roundtrip_per_pr() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut session = load(tmp.path(), "o/r", 7);
+        assert_eq!(session.updated_at, 0);
+        apply_update(
+            &mut session,
+            &SessionUpdate {
+                mark_groups: ids(&["G1"]),
+                ..SessionUpdate::default()
+            },
+            100,
+        );
+        save(tmp.path(), &session).unwrap();
+
+        let loaded = load(tmp.path(), "o/r", 7);
+        assert!(loaded.reviewed_groups.contains_key("G1"));
+        assert!(load(tmp.path(), "o/r", 8).reviewed_groups.is_empty());
+    }
+}
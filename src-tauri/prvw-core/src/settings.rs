@@ -0,0 +1,632 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use regex::Regex;
+
+use crate::cache;
+use crate::config;
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::i18n::Locale;
+use crate::json_store;
+use crate::types::{AppSettings, RepoSettings, RunOptions, SettingsUpdate};
+
+/// User settings live outside `cache/` so clearing the cache keeps them.
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Set once at startup. Until then (and in tests) the defaults apply.
+static APP_DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Serializes read-modify-write cycles of the settings file.
+static SETTINGS_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn init(app_data_dir: &Path) {
+    let _ = APP_DATA_DIR.set(app_data_dir.to_path_buf());
+}
+
+fn load(app_data_dir: &Path) -> AppSettings {
+    let mut settings: AppSettings =
+        json_store::load(&app_data_dir.join(SETTINGS_FILE)).unwrap_or_default();
+    settings.cache_limit_mb = cache::max_mb(app_data_dir);
+    settings
+}
+
+fn save(app_data_dir: &Path, settings: &AppSettings) -> Result<(), String> {
+    json_store::save(&app_data_dir.join(SETTINGS_FILE), settings, "settings")
+}
+
+/// The saved settings with `prvw.toml` applied, for commands that fill unset run options from
+/// them.
+pub fn current() -> AppSettings {
+    effective(
+        APP_DATA_DIR
+            .get()
+            .map(|dir| load(dir.as_path()))
+            .unwrap_or_default(),
+    )
+}
+
+/// `saved` with `prvw.toml` applied.
+fn effective(mut saved: AppSettings) -> AppSettings {
+    config::apply(&mut saved);
+    saved
+}
+
+fn repo_key(repo: &str) -> String {
+    repo.trim().to_lowercase()
+}
+
+/// The overrides saved for `repo`, if any.
+pub fn repo_settings<'a>(settings: &'a AppSettings, repo: &str) -> Option<&'a RepoSettings> {
+    settings.repos.get(&repo_key(repo))
+}
+
+/// `settings` with the overrides of `repo` applied.
+fn resolve(mut settings: AppSettings, repo: Option<&str>) -> AppSettings {
+    let Some(overrides) = repo.and_then(|r| repo_settings(&settings, r)).cloned() else {
+        return settings;
+    };
+    if overrides.model.is_some() {
+        settings.model = overrides.model;
+    }
+    if overrides.lang.is_some() {
+        settings.lang = overrides.lang;
+    }
+    if let Some(globs) = overrides.ignore_globs {
+        settings.ignore_globs = globs;
+    }
+    settings
+}
+
+/// The saved settings as they apply while `repo` is open, its `.prvw.toml` included.
+pub fn for_repo(repo: Option<&str>) -> AppSettings {
+    let mut settings = resolve(current(), repo);
+    if let Some(repo) = repo {
+        config::apply_repo(&mut settings, repo);
+    }
+    settings
+}
+
+/// Model, language, provider, options and timeout of an AI run: what the caller passed, with
+/// the rest taken from the settings.
+pub struct RunDefaults {
+    pub model: Option<String>,
+    pub lang: Option<String>,
+    pub provider: Option<String>,
+    pub options: RunOptions,
+    pub timeout_minutes: Option<u64>,
+}
+
+impl RunDefaults {
+    /// Fill what the caller left unset from the settings as they apply to `repo`.
+    pub fn resolve(
+        repo: Option<&str>,
+        model: Option<String>,
+        lang: Option<String>,
+        provider: Option<String>,
+        options: Option<RunOptions>,
+        timeout_minutes: Option<u64>,
+    ) -> Self {
+        Self::from_settings(
+            &for_repo(repo),
+            model,
+            lang,
+            provider,
+            options,
+            timeout_minutes,
+        )
+    }
+
+    /// `resolve` against settings the command already loaded, e.g. for its prompts.
+    pub fn from_settings(
+        settings: &AppSettings,
+        model: Option<String>,
+        lang: Option<String>,
+        provider: Option<String>,
+        options: Option<RunOptions>,
+        timeout_minutes: Option<u64>,
+    ) -> Self {
+        RunDefaults {
+            model: model.or_else(|| settings.model.clone()),
+            lang: lang.or_else(|| settings.lang.clone()),
+            provider: provider.or_else(|| settings.provider.clone()),
+            options: options.unwrap_or_else(|| settings.run_options.clone()),
+            timeout_minutes: timeout_minutes.or(settings.timeout_minutes),
+        }
+    }
+}
+
+/// Trimmed, with an empty string meaning "unset".
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// An http(s) base URL without the trailing slash; empty means "unset".
+fn base_url(value: &str, service: &str) -> Result<Option<String>, PrvwError> {
+    let Some(url) = non_empty(value) else {
+        return Ok(None);
+    };
+    let url = url.trim_end_matches('/');
+    if !(url.starts_with("https://") || url.starts_with("http://"))
+        || url.contains(char::is_whitespace)
+    {
+        return Err(PrvwError::InvalidInput(format!(
+            "Invalid {} URL '{}'",
+            service, url
+        )));
+    }
+    Ok(Some(url.to_string()))
+}
+
+pub fn apply_update(settings: &mut AppSettings, update: SettingsUpdate) -> Result<(), PrvwError> {
+    if let Some(model) = update.model {
+        settings.model = non_empty(&model);
+    }
+    if let Some(lang) = update.lang {
+        settings.lang = non_empty(&lang);
+    }
+    if let Some(lang) = update.ui_lang {
+        let lang = non_empty(&lang);
+        if let Some(lang) = lang.as_deref().filter(|l| Locale::parse(l).is_none()) {
+            return Err(PrvwError::InvalidInput(format!(
+                "Unsupported interface language '{}'. Use en or ja.",
+                lang
+            )));
+        }
+        settings.ui_lang = lang;
+    }
+    if let Some(provider) = update.provider {
+        settings.provider = non_empty(&provider);
+    }
+    if let Some(options) = update.run_options {
+        settings.run_options = options;
+    }
+    if let Some(minutes) = update.timeout_minutes {
+        settings.timeout_minutes = (minutes > 0).then_some(minutes);
+    }
+    if let Some(mb) = update.cache_limit_mb {
+        settings.cache_limit_mb = mb;
+    }
+    if let Some(globs) = update.ignore_globs {
+        settings.ignore_globs = globs
+            .iter()
+            .map(String::as_str)
+            .filter_map(non_empty)
+            .collect();
+    }
+    if let Some(host) = update.gh_host {
+        let host = non_empty(&host);
+        if let Some(host) = &host {
+            if !host
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':'))
+            {
+                return Err(PrvwError::InvalidInput(format!(
+                    "Invalid GitHub host '{}'",
+                    host
+                )));
+            }
+        }
+        settings.gh_host = host;
+    }
+    if let Some(url) = update.gitea_url {
+        settings.gitea_url = base_url(&url, "Gitea/Forgejo")?;
+    }
+    if let Some(url) = update.azure_devops_url {
+        settings.azure_devops_url = base_url(&url, "Azure DevOps")?;
+    }
+    if let Some(enabled) = update.use_guidelines {
+        settings.use_guidelines = enabled;
+    }
+    if let Some(enabled) = update.use_repo_context {
+        settings.use_repo_context = enabled;
+    }
+    if let Some(budget) = update.token_budget {
+        settings.token_budget = budget;
+    }
+    if let Some(cost) = update.cost_per_m_tokens {
+        if !cost.is_finite() || cost < 0.0 {
+            return Err(PrvwError::InvalidInput(
+                "Cost per million tokens must be a non-negative number.".to_string(),
+            ));
+        }
+        settings.cost_per_m_tokens = cost;
+    }
+    if let Some(command) = update.editor_command {
+        let command = non_empty(&command);
+        if command.as_deref().is_some_and(|c| !c.contains("{file}")) {
+            return Err(PrvwError::InvalidInput(
+                "The editor command needs a {file} placeholder.".to_string(),
+            ));
+        }
+        settings.editor_command = command;
+    }
+    if let Some(notifications) = update.notifications {
+        settings.notifications = notifications;
+    }
+    if let Some(enabled) = update.error_reports {
+        settings.error_reports = enabled;
+    }
+    Ok(())
+}
+
+/// JSON pointer of a settings key as named in `ConfigStatus::overrides` (`runOptions.profile`).
+fn key_pointer(key: &str) -> String {
+    format!("/{}", key.replace('.', "/"))
+}
+
+/// `saved` with `update` applied, except for the `overridden` keys (`prvw.toml` sets them), which
+/// keep their saved values. An update may send such a key's effective value back, as a form
+/// saving all its fields does, but changing it is an error: the change would never apply.
+fn apply_user_update(
+    saved: &AppSettings,
+    effective: &AppSettings,
+    update: SettingsUpdate,
+    overridden: &[String],
+) -> Result<AppSettings, PrvwError> {
+    let to_json = |settings: &AppSettings| {
+        serde_json::to_value(settings).map_err(|e| format!("Failed to serialize settings: {}", e))
+    };
+    let mut probe = effective.clone();
+    apply_update(&mut probe, update.clone())?;
+    let (before, after) = (to_json(effective)?, to_json(&probe)?);
+    let changed: Vec<&str> = overridden
+        .iter()
+        .map(String::as_str)
+        .filter(|key| before.pointer(&key_pointer(key)) != after.pointer(&key_pointer(key)))
+        .collect();
+    if !changed.is_empty() {
+        return Err(PrvwError::InvalidInput(format!(
+            "Set in prvw.toml, so not changeable in Settings: {}",
+            changed.join(", ")
+        )));
+    }
+
+    let mut updated = saved.clone();
+    apply_update(&mut updated, update)?;
+    let saved_json = to_json(saved)?;
+    let mut updated_json = to_json(&updated)?;
+    for key in overridden {
+        let pointer = key_pointer(key);
+        if let (Some(value), Some(slot)) = (
+            saved_json.pointer(&pointer),
+            updated_json.pointer_mut(&pointer),
+        ) {
+            *slot = value.clone();
+        }
+    }
+    Ok(serde_json::from_value(updated_json)
+        .map_err(|e| format!("Failed to read settings: {}", e))?)
+}
+
+/// Trim the overrides; None when nothing is left to override.
+fn normalize_repo_settings(mut overrides: RepoSettings) -> Option<RepoSettings> {
+    overrides.model = overrides.model.as_deref().and_then(non_empty);
+    overrides.lang = overrides.lang.as_deref().and_then(non_empty);
+    overrides.ignore_globs = overrides.ignore_globs.map(|globs| {
+        globs
+            .iter()
+            .map(String::as_str)
+            .filter_map(non_empty)
+            .collect()
+    });
+    overrides.label_mapping = overrides.label_mapping.map(|mapping| {
+        mapping
+            .into_iter()
+            .filter_map(|(category, label)| Some((non_empty(&category)?, label.trim().to_string())))
+            .collect()
+    });
+    overrides.checklist_templates = overrides
+        .checklist_templates
+        .iter()
+        .map(String::as_str)
+        .filter_map(non_empty)
+        .collect();
+    overrides.clone_path = overrides.clone_path.as_deref().and_then(non_empty);
+    let mut subprojects: Vec<String> = overrides
+        .subprojects
+        .iter()
+        .filter_map(|root| non_empty(root.trim().trim_start_matches("./").trim_matches('/')))
+        .collect();
+    subprojects.sort();
+    subprojects.dedup();
+    overrides.subprojects = subprojects;
+    let empty = overrides.model.is_none()
+        && overrides.lang.is_none()
+        && overrides.ignore_globs.is_none()
+        && overrides.label_mapping.is_none()
+        && overrides.checklist_templates.is_empty()
+        && overrides.clone_path.is_none()
+        && overrides.subprojects.is_empty();
+    (!empty).then_some(overrides)
+}
+
+/// The subproject root `path` is under; the deepest one when roots nest.
+pub fn subproject_of<'a>(path: &str, roots: &'a [String]) -> Option<&'a str> {
+    roots
+        .iter()
+        .filter(|root| {
+            path.strip_prefix(root.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+        .max_by_key(|root| root.len())
+        .map(String::as_str)
+}
+
+/// Regex for a glob: `**` spans directories, `*` and `?` do not. A glob without `/` matches
+/// the file name in any directory, like in `.gitignore`.
+fn glob_regex(glob: &str) -> Option<Regex> {
+    let glob = glob.trim().trim_start_matches("./");
+    let mut pattern = String::from(if glob.contains('/') { "^" } else { "(^|/)" });
+    let mut chars = glob.trim_start_matches('/').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern).ok()
+}
+
+/// Compiled ignore globs; invalid ones are skipped.
+pub fn ignore_matchers(globs: &[String]) -> Vec<Regex> {
+    globs
+        .iter()
+        .map(String::as_str)
+        .filter_map(glob_regex)
+        .collect()
+}
+
+pub fn is_ignored(path: &str, matchers: &[Regex]) -> bool {
+    matchers.iter().any(|m| m.is_match(path))
+}
+
+/// The settings in effect: the saved ones with `prvw.toml` applied.
+pub fn get(app_data_dir: &Path) -> AppSettings {
+    effective(load(app_data_dir))
+}
+
+/// Apply `update` to the saved settings and return the settings in effect. Keys `prvw.toml`
+/// sets can't be changed. A new cache limit takes effect (and evicts) right away.
+pub fn update(app_data_dir: &Path, update: SettingsUpdate) -> Result<AppSettings, PrvwError> {
+    let _guard = SETTINGS_LOCK
+        .lock()
+        .map_err(|_| "Settings lock poisoned".to_string())?;
+    let saved = load(app_data_dir);
+    let new_limit = update.cache_limit_mb;
+    let settings = apply_user_update(
+        &saved,
+        &effective(saved.clone()),
+        update,
+        &config::overrides(),
+    )?;
+    if let Some(mb) = new_limit {
+        cache::set_limit(app_data_dir, mb)?;
+    }
+    save(app_data_dir, &settings)?;
+    Ok(effective(settings))
+}
+
+/// The overrides saved for `repo`, if any.
+pub fn get_repo(app_data_dir: &Path, repo: &str) -> Option<RepoSettings> {
+    repo_settings(&load(app_data_dir), repo).cloned()
+}
+
+/// Save the overrides for `repo`; None (or overrides with nothing set) removes them.
+pub fn set_repo(
+    app_data_dir: &Path,
+    repo: &str,
+    overrides: Option<RepoSettings>,
+) -> Result<Option<RepoSettings>, PrvwError> {
+    validate_repo(repo.trim())?;
+    let _guard = SETTINGS_LOCK
+        .lock()
+        .map_err(|_| "Settings lock poisoned".to_string())?;
+    let mut settings = load(app_data_dir);
+    let overrides = overrides.and_then(normalize_repo_settings);
+    match &overrides {
+        Some(o) => settings.repos.insert(repo_key(repo), o.clone()),
+        None => settings.repos.remove(&repo_key(repo)),
+    };
+    save(app_data_dir, &settings)?;
+    Ok(overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NotificationSettings;
+
+    #[test]
+    fn run_defaults_fill_only_unset_choices() {
+        let settings = AppSettings {
+            model: Some("gpt-5".to_string()),
+            lang: Some("Japanese".to_string()),
+            timeout_minutes: Some(5),
+            ..AppSettings::default()
+        };
+        let run =
+            RunDefaults::from_settings(&settings, Some("o3".to_string()), None, None, None, None);
+        assert_eq!(run.model.as_deref(), Some("o3"));
+        assert_eq!(run.lang.as_deref(), Some("Japanese"));
+        assert_eq!(run.provider, None);
+        assert_eq!(run.timeout_minutes, Some(5));
+    }
+
+    #[test]
+    fn updates_keep_unset_fields_and_clear_empty_ones() {
+        let mut settings = AppSettings {
+            model: Some("gpt-5".to_string()),
+            lang: Some("ja".to_string()),
+            ..AppSettings::default()
+        };
+        apply_update(
+            &mut settings,
+            SettingsUpdate {
+                lang: Some(" ".to_string()),
+                timeout_minutes: Some(0),
+                ignore_globs: Some(vec!["*.lock".to_string(), "".to_string()]),
+                gh_host: Some(" github.example.com ".to_string()),
+                gitea_url: Some(" https://codeberg.org/ ".to_string()),
+                azure_devops_url: Some("https://dev.azure.com/contoso".to_string()),
+                ..SettingsUpdate::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(settings.model.as_deref(), Some("gpt-5"));
+        assert_eq!(settings.lang, None);
+        assert_eq!(settings.timeout_minutes, None);
+        assert_eq!(settings.ignore_globs, vec!["*.lock"]);
+        assert_eq!(settings.gh_host.as_deref(), Some("github.example.com"));
+        assert_eq!(settings.gitea_url.as_deref(), Some("https://codeberg.org"));
+        assert_eq!(
+            settings.azure_devops_url.as_deref(),
+            Some("https://dev.azure.com/contoso")
+        );
+
+        let bad_host = SettingsUpdate {
+            gh_host: Some("https://github.example.com/".to_string()),
+            ..SettingsUpdate::default()
+        };
+        assert!(apply_update(&mut settings, bad_host).is_err());
+        let bad_url = SettingsUpdate {
+            gitea_url: Some("codeberg.org".to_string()),
+            ..SettingsUpdate::default()
+        };
+        assert!(apply_update(&mut settings, bad_url).is_err());
+        let bad_editor = SettingsUpdate {
+            editor_command: Some("vim".to_string()),
+            ..SettingsUpdate::default()
+        };
+        assert!(apply_update(&mut settings, bad_editor).is_err());
+    }
+
+    #[test]
+    fn keeps_keys_set_in_the_config_file() {
+        let saved = AppSettings {
+            model: Some("gpt-5".to_string()),
+            ..AppSettings::default()
+        };
+        let mut effective = saved.clone();
+        effective.model = Some("o3".to_string());
+        effective.run_options.reasoning_effort = Some("high".to_string());
+        let overridden = vec![
+            "model".to_string(),
+            "runOptions.reasoningEffort".to_string(),
+        ];
+
+        // A form sending back every value saves the others, not the file's.
+        let update = SettingsUpdate {
+            model: Some("o3".to_string()),
+            lang: Some("ja".to_string()),
+            run_options: Some(RunOptions {
+                profile: Some("work".to_string()),
+                reasoning_effort: Some("high".to_string()),
+                ..RunOptions::default()
+            }),
+            ..SettingsUpdate::default()
+        };
+        let updated = apply_user_update(&saved, &effective, update, &overridden).unwrap();
+        assert_eq!(updated.model.as_deref(), Some("gpt-5"));
+        assert_eq!(updated.lang.as_deref(), Some("ja"));
+        assert_eq!(updated.run_options.profile.as_deref(), Some("work"));
+        assert_eq!(updated.run_options.reasoning_effort, None);
+
+        let change = SettingsUpdate {
+            model: Some("gpt-4.1".to_string()),
+            ..SettingsUpdate::default()
+        };
+        let err = apply_user_update(&saved, &effective, change, &overridden).unwrap_err();
+        assert!(err.message().contains("model"));
+    }
+
+    #[test]
+    fn notifications_default_to_on() {
+        let settings: AppSettings = serde_json::from_str(r#"{"model":"gpt-5"}"#).unwrap();
+        assert_eq!(settings.notifications, NotificationSettings::default());
+        assert!(settings.notifications.new_pushes);
+        let partial: AppSettings =
+            serde_json::from_str(r#"{"notifications":{"ciFailures":false}}"#).unwrap();
+        assert!(!partial.notifications.ci_failures);
+        assert!(partial.notifications.analyses_done);
+    }
+
+    #[test]
+    fn repo_overrides_apply_only_to_their_repo() {
+        let mut settings = AppSettings {
+            model: Some("gpt-5".to_string()),
+            lang: Some("ja".to_string()),
+            ignore_globs: vec!["*.lock".to_string()],
+            ..AppSettings::default()
+        };
+        let overrides = normalize_repo_settings(RepoSettings {
+            lang: Some(" en ".to_string()),
+            ignore_globs: Some(vec!["vendor/**".to_string()]),
+            ..RepoSettings::default()
+        })
+        .unwrap();
+        settings.repos.insert(repo_key("Org/App"), overrides);
+
+        let resolved = resolve(settings.clone(), Some("org/app"));
+        assert_eq!(resolved.model.as_deref(), Some("gpt-5"));
+        assert_eq!(resolved.lang.as_deref(), Some("en"));
+        assert_eq!(resolved.ignore_globs, vec!["vendor/**"]);
+        let other = resolve(settings, Some("org/other"));
+        assert_eq!(other.lang.as_deref(), Some("ja"));
+
+        let roots = normalize_repo_settings(RepoSettings {
+            subprojects: vec![
+                "./services/api/".to_string(),
+                "services".to_string(),
+                " ".to_string(),
+            ],
+            ..RepoSettings::default()
+        })
+        .unwrap()
+        .subprojects;
+        assert_eq!(roots, vec!["services", "services/api"]);
+        assert_eq!(
+            subproject_of("services/api/src/main.rs", &roots),
+            Some("services/api")
+        );
+        assert_eq!(
+            subproject_of("services/web/app.ts", &roots),
+            Some("services")
+        );
+        assert_eq!(subproject_of("services-old/x", &roots), None);
+
+        assert!(normalize_repo_settings(RepoSettings {
+            model: Some(" ".to_string()),
+            ..RepoSettings::default()
+        })
+        .is_none());
+    }
+
+    #[test]
+    fn matches_ignore_globs() {
+        let matchers = ignore_matchers(&[
+            "*.lock".to_string(),
+            "generated/**".to_string(),
+            "docs/*.md".to_string(),
+        ]);
+        assert!(is_ignored("Cargo.lock", &matchers));
+        assert!(is_ignored("web/yarn.lock", &matchers));
+        assert!(is_ignored("generated/api/client.ts", &matchers));
+        assert!(is_ignored("docs/intro.md", &matchers));
+        assert!(!is_ignored("docs/guide/intro.md", &matchers));
+        assert!(!is_ignored("src/generated.rs", &matchers));
+        assert!(!is_ignored("src/lock.rs", &matchers));
+    }
+}
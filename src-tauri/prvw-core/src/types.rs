@@ -0,0 +1,1944 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PrAuthor {
+    pub login: String,
+}
+
+/// Account owning the repository a PR's head branch lives in.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoOwner {
+    pub login: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PrListItem {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub updated_at: String,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub author: Option<PrAuthor>,
+    #[serde(default)]
+    pub head_ref_name: Option<String>,
+    #[serde(default)]
+    pub head_ref_oid: Option<String>,
+    #[serde(default)]
+    pub base_ref_name: Option<String>,
+    #[serde(default)]
+    pub head_repository_owner: Option<RepoOwner>,
+    /// Whether the head branch is in a fork rather than the PR's own repository.
+    #[serde(default)]
+    pub is_cross_repository: Option<bool>,
+    #[serde(default)]
+    pub review_decision: Option<String>,
+    #[serde(default)]
+    pub is_draft: Option<bool>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+/// One PR in a stack of dependent PRs.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StackLayer {
+    pub number: u64,
+    pub title: String,
+    pub head_ref_name: String,
+    #[serde(default)]
+    pub head_ref_oid: Option<String>,
+    pub base_ref_name: String,
+    /// The PR whose head branch this one is based on; None for the bottom layer.
+    #[serde(default)]
+    pub parent: Option<u64>,
+}
+
+/// PRs chained by base branch, parents before children.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrStack {
+    pub layers: Vec<StackLayer>,
+}
+
+/// A file touched by a PR, as listed by `gh pr list --json files`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PrFile {
+    pub path: String,
+}
+
+/// A recent PR with the files it touches, used to find related PRs.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentPr {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    /// OPEN, CLOSED, or MERGED.
+    pub state: String,
+    #[serde(default)]
+    pub updated_at: String,
+    #[serde(default)]
+    pub author: Option<PrAuthor>,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub files: Vec<PrFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub kind: String, // "add", "remove", "context"
+    pub old_line: Option<u32>,
+    pub new_line: Option<u32>,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Hunk {
+    pub id: String,
+    pub file_path: String,
+    /// Root of the subproject (from the repo settings) the file belongs to.
+    #[serde(default)]
+    pub subproject: Option<String>,
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedDiff {
+    pub hunks: Vec<Hunk>,
+    pub raw: String,
+}
+
+/// A diff parsed into backend-held review state, addressed by `session_id` afterwards.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewState {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub diff: ParsedDiff,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeCommit {
+    pub sha: String,
+    pub subject: String,
+}
+
+/// A file in a local diff.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedFile {
+    pub path: String,
+    /// Previous path of a renamed or copied file.
+    pub old_path: Option<String>,
+    /// "added", "deleted", "modified", "renamed", "copied" or "typechange".
+    pub status: String,
+    /// Binary files have no hunks in the diff.
+    pub binary: bool,
+}
+
+/// The diff of a revision range in a local clone, analyzed like a PR diff.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeDiff {
+    /// Top level of the working tree.
+    pub repo_path: String,
+    pub range: String,
+    /// "owner/name" PRs are listed from: the `upstream` remote, else `origin`, when on GitHub.
+    pub repo: Option<String>,
+    /// The user's fork (`origin`) when `repo` comes from `upstream`.
+    pub fork: Option<String>,
+    /// Commit subjects, standing in for the PR description.
+    pub description: String,
+    pub commits: Vec<RangeCommit>,
+    pub files: Vec<ChangedFile>,
+    pub diff: String,
+}
+
+/// Three-dot comparison of two local refs: the diff of `head` since its merge base with `base`,
+/// with how far the branches have diverged.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchComparison {
+    #[serde(flatten)]
+    pub diff: RangeDiff,
+    pub base: String,
+    pub head: String,
+    pub merge_base: String,
+    /// Commits on `head` since the merge base (those in the diff).
+    pub ahead: usize,
+    /// Commits on `base` since the merge base, not part of the diff.
+    pub behind: usize,
+}
+
+/// A GitHub branch comparison (`compare/base...head`) without a PR, analyzed like a PR diff.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareDiff {
+    pub repo: String,
+    pub base: String,
+    pub head: String,
+    pub merge_base: String,
+    /// Unset when the comparison has more commits than the API lists.
+    pub head_sha: Option<String>,
+    /// Commits on `head` since the merge base.
+    pub ahead: usize,
+    /// Commits on `base` since the merge base, not part of the diff.
+    pub behind: usize,
+    /// The comparison on github.com.
+    pub url: String,
+    /// Commit subjects, standing in for the PR description.
+    pub description: String,
+    pub commits: Vec<RangeCommit>,
+    pub files: Vec<ChangedFile>,
+    pub diff: String,
+}
+
+/// GitHub repos behind a local clone's remotes.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneRemotes {
+    /// Where PRs live: the `upstream` remote when it is on GitHub, otherwise `origin`.
+    pub repo: Option<String>,
+    /// Name of the remote `repo` came from.
+    pub remote: Option<String>,
+    /// The user's fork (`origin`) in a triangular workflow, where branches are pushed.
+    pub fork: Option<String>,
+}
+
+/// A PR head checked out in its managed worktree.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PrWorkspace {
+    pub repo: String,
+    pub pr_number: u32,
+    pub path: String,
+    pub head_sha: String,
+}
+
+/// A PR checkout on disk, for listing and cleaning up workspaces.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceInfo {
+    pub repo: String,
+    pub pr_number: u32,
+    pub path: String,
+    /// Unset when the checkout is broken (e.g. its base clone was deleted).
+    pub head_sha: Option<String>,
+    /// The user's clone the checkout is a worktree of; unset for the managed clone.
+    pub clone_path: Option<String>,
+    pub size_bytes: u64,
+    /// Last modification of the checkout directory, in seconds since the epoch.
+    pub modified_at: Option<u64>,
+}
+
+/// State of the pre-push hook of a local repo.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PushHookStatus {
+    pub hook_path: String,
+    /// prvw's hook is installed.
+    pub installed: bool,
+    /// Another `pre-push` hook exists; prvw won't overwrite it.
+    pub foreign: bool,
+    /// Lowest risk level that blocks a push, when installed.
+    pub fail_on: Option<String>,
+}
+
+/// State of the localhost API for editor extensions.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerStatus {
+    /// Started now and again at each launch.
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    /// Bearer token clients must send in `Authorization`.
+    pub token: String,
+}
+
+/// The tray icon's view of a repo's review queue.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TrayStatus {
+    /// Repo whose queue is shown; the last one whose PRs were listed.
+    pub repo: Option<String>,
+    /// Open review requests of `repo`, as of `last_checked_at`.
+    pub pending: Vec<PrListItem>,
+    pub last_checked_at: Option<u64>,
+    pub error: Option<String>,
+    /// Closing the window hides it to the tray instead of quitting.
+    pub keep_running: bool,
+}
+
+/// Payload of `open-pr`: a PR picked from the tray menu or opened through a `prvw://` link.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenPr {
+    pub repo: String,
+    pub pr: PrListItem,
+}
+
+/// A diff reviewed without a forge (a patch file or pasted text), with the session tracking its
+/// review. `repo` (`local/<name>`) and `pr_number` address the session like a PR's.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AdhocReview {
+    pub repo: String,
+    pub pr_number: u32,
+    pub title: String,
+    /// Commit subjects of a format-patch series, standing in for the PR description.
+    pub description: String,
+    pub commits: Vec<RangeCommit>,
+    pub diff: ParsedDiff,
+    pub session: ReviewSession,
+}
+
+/// One line of one side of a full-file view.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SideLine {
+    /// 1-based line number in that version of the file.
+    pub number: u32,
+    pub text: String,
+}
+
+/// A row of a side-by-side view: base line on the left, head line on the right.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SideBySideRow {
+    /// "context", "remove", "add", or "change" (a removed line paired with its replacement).
+    pub kind: String,
+    pub left: Option<SideLine>,
+    pub right: Option<SideLine>,
+    /// Hunk the row belongs to; None for unchanged lines outside the hunks.
+    pub hunk_id: Option<String>,
+}
+
+/// Base and head versions of a file aligned row by row on the PR's hunks.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FullFileView {
+    pub file_path: String,
+    pub base_sha: String,
+    pub head_sha: String,
+    /// False when the file is added (no base) or deleted (no head) by the PR.
+    pub base_exists: bool,
+    pub head_exists: bool,
+    pub rows: Vec<SideBySideRow>,
+    /// Places where the hunks did not match the fetched file contents.
+    pub warnings: Vec<String>,
+}
+
+/// A concrete reason backing a group's risk label, tied to a specific hunk.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskEvidence {
+    pub hunk_id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IntentGroup {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub category: String,
+    pub rationale: String,
+    pub risk: String,
+    /// 0–100, consistent with `risk` (low ≤ 33 < medium ≤ 66 < high).
+    #[serde(default)]
+    pub risk_score: u32,
+    #[serde(default)]
+    pub risk_evidence: Vec<RiskEvidence>,
+    pub hunk_ids: Vec<String>,
+    pub reviewer_checklist: Vec<String>,
+    pub suggested_tests: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisResult {
+    pub version: u32,
+    pub overall_summary: String,
+    pub groups: Vec<IntentGroup>,
+    pub unassigned_hunk_ids: Vec<String>,
+    pub non_substantive_hunk_ids: Vec<String>,
+    pub questions: Vec<String>,
+}
+
+/// A manual correction: hunk `hunk_id` must end up in group `group_id`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkPin {
+    pub hunk_id: String,
+    pub group_id: String,
+}
+
+/// Manual corrections honored by `adjust_analysis`. Each entry of `merges` lists group ids
+/// that must become one group (kept under the first id).
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisConstraints {
+    #[serde(default)]
+    pub pins: Vec<HunkPin>,
+    #[serde(default)]
+    pub merges: Vec<Vec<String>>,
+}
+
+/// A manual change to an analysis' grouping, recorded for undo/redo.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum AnalysisEdit {
+    /// Move hunks into `group_id`, or to the unassigned list when it is None.
+    #[serde(rename_all = "camelCase")]
+    MoveHunks {
+        hunk_ids: Vec<String>,
+        #[serde(default)]
+        group_id: Option<String>,
+    },
+    /// Merge groups into the first one listed.
+    #[serde(rename_all = "camelCase")]
+    MergeGroups { group_ids: Vec<String> },
+    #[serde(rename_all = "camelCase")]
+    RenameGroup { group_id: String, title: String },
+    /// New group with the next free `G<n>` id, taking `hunk_ids` from wherever they are.
+    #[serde(rename_all = "camelCase")]
+    CreateGroup {
+        title: String,
+        hunk_ids: Vec<String>,
+        #[serde(default)]
+        category: Option<String>,
+    },
+}
+
+/// A manually edited analysis, saved so the corrections survive restarts.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EditedAnalysis {
+    pub repo: String,
+    pub pr_number: u32,
+    /// PR head the edits were made at; None when unknown.
+    #[serde(default)]
+    pub head_sha: Option<String>,
+    pub analysis: AnalysisResult,
+    /// Unix seconds.
+    pub saved_at: u64,
+}
+
+/// The analysis after an edit, undo, or redo, and what can be undone or redone next.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisEditState {
+    pub analysis: AnalysisResult,
+    /// Description of the edit `undo` would revert, e.g. "Merge G1, G2".
+    pub undo_label: Option<String>,
+    pub redo_label: Option<String>,
+}
+
+/// Codex pass-through options, set in Settings and overridable per run.
+/// Other providers ignore them.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RunOptions {
+    /// `--profile` from `~/.codex/config.toml`.
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// `model_reasoning_effort`: minimal, low, medium, or high.
+    #[serde(default)]
+    pub reasoning_effort: Option<String>,
+    /// `approval_policy`: untrusted, on-failure, on-request, or never. Unset keeps `--full-auto`.
+    #[serde(default)]
+    pub approval_mode: Option<String>,
+}
+
+impl RunOptions {
+    /// Fragment appended to cache keys so runs with different options are cached separately.
+    pub fn cache_key(&self) -> String {
+        format!(
+            "{}|{}|{}",
+            self.profile.as_deref().unwrap_or(""),
+            self.reasoning_effort.as_deref().unwrap_or(""),
+            self.approval_mode.as_deref().unwrap_or("")
+        )
+    }
+}
+
+/// App-wide defaults saved in `settings.json`. AI commands fall back to them for the run
+/// options a call leaves unset.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Output language of AI results; None for English.
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// Language of the app's own error and status messages ("en" or "ja"); None follows `lang`
+    /// when messages exist in it.
+    #[serde(default)]
+    pub ui_lang: Option<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub run_options: RunOptions,
+    #[serde(default)]
+    pub timeout_minutes: Option<u64>,
+    /// Cache size limit in MB (0 = unlimited); stored with the cache, mirrored here.
+    #[serde(default)]
+    pub cache_limit_mb: u64,
+    /// Files whose hunks `parse_diff` drops, e.g. "*.lock" or "generated/**".
+    #[serde(default)]
+    pub ignore_globs: Vec<String>,
+    /// GitHub Enterprise host passed to gh as `GH_HOST`; None for github.com.
+    #[serde(default)]
+    pub gh_host: Option<String>,
+    /// Base URL of a Gitea or Forgejo instance, e.g. `https://codeberg.org`.
+    #[serde(default)]
+    pub gitea_url: Option<String>,
+    /// Organization (or Azure DevOps Server collection) URL, e.g. `https://dev.azure.com/contoso`.
+    #[serde(default)]
+    pub azure_devops_url: Option<String>,
+    #[serde(default)]
+    pub use_guidelines: bool,
+    #[serde(default)]
+    pub use_repo_context: bool,
+    /// Estimated input tokens above which analysis asks for confirmation; 0 disables it.
+    #[serde(default)]
+    pub token_budget: u64,
+    /// USD per million tokens, for cost estimates only.
+    #[serde(default)]
+    pub cost_per_m_tokens: f64,
+    /// Command opening a file at a line, with `{file}` and `{line}` placeholders; None for
+    /// VS Code (`code -g {file}:{line}`).
+    #[serde(default)]
+    pub editor_command: Option<String>,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    /// Save panics and failed commands as local error reports; off unless the user opts in.
+    #[serde(default)]
+    pub error_reports: bool,
+    /// Extra prompt instructions per kind of run ("analysis", "refine"), from `prvw.toml`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prompts: BTreeMap<String, String>,
+    /// Overrides per repo, keyed by lowercase `owner/repo`.
+    #[serde(default)]
+    pub repos: BTreeMap<String, RepoSettings>,
+}
+
+/// Which events fire a desktop notification. Everything is on by default, but only while the
+/// window is in the background.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    /// Background pre-analysis of the review queue finished.
+    pub analyses_done: bool,
+    /// New commits were pushed to the PR open in the window.
+    pub new_pushes: bool,
+    /// CI started failing on a PR with review progress.
+    pub ci_failures: bool,
+    /// Skip notifications while the window has focus.
+    pub only_when_unfocused: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            analyses_done: true,
+            new_pushes: true,
+            ci_failures: true,
+            only_when_unfocused: true,
+        }
+    }
+}
+
+/// Settings overriding the app-wide ones while a repo is open. Unset fields fall back to them.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoSettings {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// Replaces the app-wide ignore globs for this repo.
+    #[serde(default)]
+    pub ignore_globs: Option<Vec<String>>,
+    /// Category to label mapping for `apply_category_labels` when a call passes none.
+    #[serde(default)]
+    pub label_mapping: Option<BTreeMap<String, String>>,
+    /// Checklist templates applied to this repo besides those that already apply to it.
+    #[serde(default)]
+    pub checklist_templates: Vec<String>,
+    /// The user's clone of the repo; PR checkouts become worktrees of it instead of a managed clone.
+    #[serde(default)]
+    pub clone_path: Option<String>,
+    /// Subproject roots of a monorepo (`services/api`); hunks are tagged with the one they are in.
+    #[serde(default)]
+    pub subprojects: Vec<String>,
+}
+
+/// Changes for `update_settings`; unset fields are kept and an empty string clears a value.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsUpdate {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub lang: Option<String>,
+    #[serde(default)]
+    pub ui_lang: Option<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub run_options: Option<RunOptions>,
+    /// 0 restores the default timeout.
+    #[serde(default)]
+    pub timeout_minutes: Option<u64>,
+    #[serde(default)]
+    pub cache_limit_mb: Option<u64>,
+    #[serde(default)]
+    pub ignore_globs: Option<Vec<String>>,
+    #[serde(default)]
+    pub gh_host: Option<String>,
+    #[serde(default)]
+    pub gitea_url: Option<String>,
+    #[serde(default)]
+    pub azure_devops_url: Option<String>,
+    #[serde(default)]
+    pub use_guidelines: Option<bool>,
+    #[serde(default)]
+    pub use_repo_context: Option<bool>,
+    #[serde(default)]
+    pub token_budget: Option<u64>,
+    #[serde(default)]
+    pub cost_per_m_tokens: Option<f64>,
+    #[serde(default)]
+    pub editor_command: Option<String>,
+    #[serde(default)]
+    pub notifications: Option<NotificationSettings>,
+    #[serde(default)]
+    pub error_reports: Option<bool>,
+}
+
+/// Result of `test_secret`: whether a stored token is accepted by its service.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretTest {
+    pub name: String,
+    pub stored: bool,
+    pub ok: bool,
+    pub message: String,
+}
+
+/// The `prvw.toml` config file, as last (re)loaded. Sent with the `config-reloaded` event.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigStatus {
+    pub path: String,
+    pub exists: bool,
+    /// Why the file was rejected; the previous config stays in effect.
+    pub error: Option<String>,
+    /// 0 before the first load.
+    pub loaded_at: u64,
+    /// Settings keys the file sets, as named in `AppSettings` (`runOptions.<option>` for codex
+    /// options). Settings shows these and `update_settings` refuses to change them.
+    #[serde(default)]
+    pub overrides: Vec<String>,
+}
+
+/// PR to check out when analysis should see the full repository, not just the hunks.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoContext {
+    pub repo: String,
+    pub pr_number: u32,
+}
+
+/// Token counts reported by a provider for one run. Codex only reports a total.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub input_tokens: Option<u64>,
+    #[serde(default)]
+    pub output_tokens: Option<u64>,
+    pub total_tokens: u64,
+}
+
+/// One line of the usage ledger: a single provider run.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageEntry {
+    /// Unix seconds.
+    pub timestamp: u64,
+    /// Log label of the run (analysis, refine, summarize, ...).
+    pub kind: String,
+    pub provider: String,
+    pub model: String,
+    pub estimated_input_tokens: u64,
+    /// `None` when the provider did not report usage.
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+    pub elapsed_secs: f64,
+}
+
+/// A persisted provider invocation, for debugging an analysis after the fact.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexRun {
+    /// Sortable id: unix millis plus `kind`.
+    pub id: String,
+    /// Unix seconds.
+    pub timestamp: u64,
+    /// Output name of the run (analysis, refine, explain, ...).
+    pub kind: String,
+    pub provider: String,
+    pub model: String,
+    /// Empty when the run failed before the provider reported them.
+    pub args: Vec<String>,
+    pub prompt: String,
+    pub elapsed_secs: f64,
+    /// Validation warnings the command reported for this run's output.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    pub stdout: String,
+    pub stderr: String,
+    /// stdout or stderr was cut to its tail.
+    pub output_truncated: bool,
+    pub error: Option<String>,
+}
+
+/// A `CodexRun` without prompt and output, for listing.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexRunSummary {
+    pub id: String,
+    pub timestamp: u64,
+    pub kind: String,
+    pub provider: String,
+    pub model: String,
+    pub elapsed_secs: f64,
+    pub warning_count: usize,
+    pub error: Option<String>,
+}
+
+/// Local usage metrics aggregated by `get_usage_stats`; nothing is sent anywhere.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStats {
+    /// Intent analyses that ran a provider (cache hits not included).
+    pub analyses_run: u64,
+    /// Provider runs by log label (analysis, refine, summarize, ...).
+    pub runs_by_kind: BTreeMap<String, u64>,
+    pub models: Vec<ModelLatency>,
+    pub cache: Vec<CacheStats>,
+    /// Hits over lookups across all namespaces; None before the first lookup.
+    pub cache_hit_rate: Option<f64>,
+    pub reviews: ReviewDurations,
+}
+
+/// Provider latency of one model, from the usage ledger.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelLatency {
+    pub provider: String,
+    pub model: String,
+    pub runs: u64,
+    pub average_secs: f64,
+}
+
+/// Cache lookups of one namespace (e.g. `cache/analysis`).
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub namespace: String,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Active review time per PR with review marks, as `get_review_progress` estimates it.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewDurations {
+    pub reviewed_prs: u64,
+    pub total_secs: u64,
+    pub average_secs: u64,
+    pub median_secs: u64,
+}
+
+/// Pre-run estimate for a hunks payload, checked against the user's budget.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenEstimate {
+    pub estimated_tokens: u64,
+    pub budget: Option<u64>,
+    pub over_budget: bool,
+}
+
+/// A line in the PR head that calls a function changed by the PR.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CallSite {
+    pub file_path: String,
+    pub line: u32,
+    pub text: String,
+    /// Whether the call is on a line the PR itself adds.
+    pub in_diff: bool,
+}
+
+/// Call sites of one function changed by an intent group.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolImpact {
+    pub group_id: String,
+    pub symbol: String,
+    /// Hunks of the group that change the function.
+    pub hunk_ids: Vec<String>,
+    pub call_sites: Vec<CallSite>,
+    /// More call sites exist than are listed.
+    pub truncated: bool,
+}
+
+/// Response for analyze_impact command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpactResponse {
+    pub head_sha: String,
+    pub impacts: Vec<SymbolImpact>,
+}
+
+/// A dependency added, removed, or bumped in a Cargo.toml, Cargo.lock, or package.json.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyChange {
+    /// Path of the manifest the change was found in.
+    pub manifest: String,
+    pub name: String,
+    pub change: String, // "added", "removed", "bumped"
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// A debug statement, TODO marker, or commented-out code found on an added line.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LeftoverFinding {
+    pub hunk_id: String,
+    pub file_path: String,
+    /// New-file line number.
+    pub line: Option<u32>,
+    pub kind: String, // "debug", "todo", "commented-code"
+    /// The offending line, trimmed and shortened.
+    pub text: String,
+}
+
+/// A risky construct (raw SQL, `unsafe`, `eval`, disabled TLS verification, swallowed
+/// exception) found on an added line.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskyPatternHit {
+    pub hunk_id: String,
+    pub file_path: String,
+    /// New-file line number.
+    pub line: Option<u32>,
+    pub kind: String, // "raw-sql", "unsafe", "eval", "tls-disabled", "swallowed-exception"
+    /// The matching line, trimmed and shortened.
+    pub text: String,
+}
+
+/// Settings for a background pre-analysis run over the user's review queue. They mirror the
+/// foreground analysis arguments so the results land under the same cache keys.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefetchRequest {
+    pub repo: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub lang: Option<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub options: Option<RunOptions>,
+    #[serde(default)]
+    pub use_guidelines: bool,
+    #[serde(default)]
+    pub use_repo_context: bool,
+    #[serde(default)]
+    pub timeout_minutes: Option<u64>,
+    /// Seconds to wait before starting (e.g. until the night); 0 or unset starts now.
+    #[serde(default)]
+    pub delay_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PrefetchPhase {
+    #[default]
+    Idle,
+    Scheduled,
+    Running,
+    Done,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefetchStatus {
+    pub phase: PrefetchPhase,
+    pub repo: Option<String>,
+    /// Unix seconds at which a scheduled run starts.
+    pub starts_at: Option<u64>,
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub current_pr: Option<u32>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderRequest {
+    pub repo: String,
+    /// Review requests open at least this many days are reminded about.
+    pub stale_days: u32,
+    /// Minutes between checks; defaults to 60.
+    #[serde(default)]
+    pub interval_minutes: Option<u64>,
+}
+
+/// A PR awaiting the user's review with no review progress at its current head.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleReview {
+    pub pr_number: u32,
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub head_sha: Option<String>,
+    pub age_days: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderStatus {
+    pub active: bool,
+    pub repo: Option<String>,
+    pub stale_days: u32,
+    /// Unix seconds of the last completed check.
+    pub last_checked_at: Option<u64>,
+    /// Stale reviews found by the last check.
+    pub pending: Vec<StaleReview>,
+    pub errors: Vec<String>,
+}
+
+/// State of the watched index of a local repo.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct StagedStatus {
+    pub watching: bool,
+    pub repo_path: Option<String>,
+    /// Changes whenever the staged content does; None when nothing is staged.
+    pub stamp: Option<String>,
+    pub files: Vec<ChangedFile>,
+    /// Unix seconds of the last change seen.
+    pub changed_at: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Codex output shape for self_review_staged.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfReviewResult {
+    pub commit_message: String,
+    pub checklist: Vec<String>,
+}
+
+/// Response for self_review_staged command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfReviewResponse {
+    pub commit_message: String,
+    /// Things to verify before pushing.
+    pub checklist: Vec<String>,
+    /// Staged-content stamp the review was made for (see `StagedStatus`).
+    pub stamp: String,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// Wrapper for Codex command results that includes CLI log output.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisResponse {
+    pub result: AnalysisResult,
+    /// Debug leftovers and TODOs found in the hunks, also listed in the group checklists.
+    #[serde(default)]
+    pub leftovers: Vec<LeftoverFinding>,
+    /// Risky patterns found in the hunks, also given to the model as risk evidence.
+    #[serde(default)]
+    pub risky_patterns: Vec<RiskyPatternHit>,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// What an analysis run would send to the provider, returned by `preview_analysis_prompt`
+/// without running it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisPromptPreview {
+    pub prompt: String,
+    pub schema: String,
+    /// hunks.json as written for the model (lockfile hunk lines stripped).
+    pub hunks_json: String,
+    /// summaries.json, when hunk summaries were passed.
+    pub summaries_json: Option<String>,
+    pub estimated_tokens: u64,
+}
+
+/// Response for refine_group command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RefineResponse {
+    pub sub_groups: Vec<IntentGroup>,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// Response for refine_group_with_guidance: the analysis with the group replaced by its
+/// sub-groups, plus the sub-groups themselves.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GuidedRefineResponse {
+    pub result: AnalysisResult,
+    pub sub_groups: Vec<IntentGroup>,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// Codex output shape for refine (same structure as analysis but only groups).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefineResult {
+    pub groups: Vec<IntentGroup>,
+}
+
+/// Codex output shape for explain_hunk.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainResult {
+    pub explanation: String,
+}
+
+/// Response for explain_hunk command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainResponse {
+    pub explanation: String,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// One-sentence explanation of a single hunk.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkSummary {
+    pub hunk_id: String,
+    pub summary: String,
+}
+
+/// Codex output shape for summarize_hunks.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummarizeResult {
+    pub summaries: Vec<HunkSummary>,
+}
+
+/// Response for summarize_hunks command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SummarizeResponse {
+    pub summaries: Vec<HunkSummary>,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// A proposed inline review comment anchored to a line of a hunk.
+/// `side` is "RIGHT" (new file line) or "LEFT" (old file line), matching the GitHub review API.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DraftComment {
+    pub hunk_id: String,
+    pub file_path: String,
+    pub line: u32,
+    pub side: String,
+    pub body: String,
+    pub severity: String,
+}
+
+/// A draft comment waiting in a PR's comment queue until the review is submitted.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedComment {
+    pub id: String,
+    #[serde(flatten)]
+    pub comment: DraftComment,
+    /// "ai" (from draft_comments_for_group) or "manual".
+    pub source: String,
+    /// Unix seconds.
+    pub created_at: u64,
+}
+
+/// Changes to one queued comment; unset fields are kept.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedCommentEdit {
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub severity: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    #[serde(default)]
+    pub side: Option<String>,
+}
+
+/// An inline comment placed on the diff, in the shape of the GitHub reviews API.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewCommentPayload {
+    pub path: String,
+    pub line: u32,
+    pub side: String, // "LEFT", "RIGHT"
+    pub body: String,
+}
+
+/// A review assembled from draft comments by `submit_assembled_review`: what was (or, in a
+/// dry run, would be) submitted.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AssembledReview {
+    pub event: String, // "APPROVE", "REQUEST_CHANGES", "COMMENT"
+    /// Overall body, including a section for comments that could not be placed.
+    pub body: String,
+    pub comments: Vec<ReviewCommentPayload>,
+    /// Drafts whose line is not part of the current diff; listed in the body instead.
+    pub unplaced: Vec<DraftComment>,
+    pub dry_run: bool,
+    /// URL of the submitted review.
+    pub url: Option<String>,
+    /// Open blocking items of the PR; while any are open, an approval is sent as
+    /// "request changes" listing them.
+    #[serde(default)]
+    pub blocking_items: Vec<BlockingItem>,
+    /// True when the requested approval was turned into "request changes".
+    #[serde(default)]
+    pub approval_blocked: bool,
+}
+
+/// Codex output shape for draft_comments_for_group.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DraftCommentsResult {
+    pub comments: Vec<DraftComment>,
+}
+
+/// Response for draft_comments_for_group command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DraftCommentsResponse {
+    pub comments: Vec<DraftComment>,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// A change to a public surface (exported symbol, endpoint, schema, ...) that may break callers.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakingChange {
+    pub group_id: String,
+    pub hunk_ids: Vec<String>,
+    /// symbol, endpoint, schema, config, cli, or other.
+    pub kind: String,
+    /// The affected name, e.g. `fn parse_diff` or `GET /api/users`.
+    pub subject: String,
+    pub description: String,
+    /// "breaking" or "possibly-breaking".
+    pub severity: String,
+}
+
+/// Codex output shape for detect_breaking_changes.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakingChangesResult {
+    pub findings: Vec<BreakingChange>,
+}
+
+/// Response for detect_breaking_changes command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakingChangesResponse {
+    pub findings: Vec<BreakingChange>,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// A gap between what the PR title/description claims and what the analysis shows.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsistencyMismatch {
+    /// "undisclosed" (change not mentioned), "unfulfilled" (claim with no matching change),
+    /// or "contradicted" (change at odds with a claim).
+    pub kind: String,
+    /// The title/description statement involved; empty for undisclosed changes.
+    pub claim: String,
+    /// Intent groups involved; empty for unfulfilled claims.
+    pub group_ids: Vec<String>,
+    pub description: String,
+    pub severity: String, // "low", "medium", "high"
+}
+
+/// Codex output shape for check_pr_consistency.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsistencyResult {
+    pub summary: String,
+    pub mismatches: Vec<ConsistencyMismatch>,
+}
+
+/// Response for check_pr_consistency command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsistencyResponse {
+    /// True when no mismatches remain after validation.
+    pub consistent: bool,
+    pub summary: String,
+    pub mismatches: Vec<ConsistencyMismatch>,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// One proposed PR of a split plan.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitPr {
+    pub title: String,
+    pub description: String,
+    pub hunk_ids: Vec<String>,
+    /// 1-based positions of earlier proposed PRs this one must land after.
+    #[serde(default)]
+    pub depends_on: Vec<u32>,
+}
+
+/// Codex output shape for suggest_split_plan.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitPlanResult {
+    pub rationale: String,
+    pub prs: Vec<SplitPr>,
+}
+
+/// Validated split plan: proposed PRs in merge order, plus hunks no proposed PR covers.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitPlan {
+    pub rationale: String,
+    pub prs: Vec<SplitPr>,
+    pub unassigned_hunk_ids: Vec<String>,
+}
+
+/// Response for suggest_split_plan command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitPlanResponse {
+    pub plan: SplitPlan,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// One user-facing changelog entry derived from the analysis.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseNoteEntry {
+    /// "added", "changed", or "fixed".
+    pub category: String,
+    pub text: String,
+    /// Intent groups the entry is based on.
+    #[serde(default)]
+    pub group_ids: Vec<String>,
+}
+
+/// Codex output shape for generate_release_notes.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseNotesResult {
+    pub entries: Vec<ReleaseNoteEntry>,
+}
+
+/// Response for generate_release_notes command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseNotesResponse {
+    pub entries: Vec<ReleaseNoteEntry>,
+    /// The entries rendered as a Markdown changelog section.
+    pub markdown: String,
+    pub head_sha: String,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// Translated text of one intent group; `riskReasons` follow the order of `riskEvidence`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslatedGroup {
+    pub id: String,
+    pub title: String,
+    pub rationale: String,
+    pub risk_reasons: Vec<String>,
+    pub reviewer_checklist: Vec<String>,
+    pub suggested_tests: Vec<String>,
+}
+
+/// Codex output shape for translate_analysis.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationResult {
+    pub overall_summary: String,
+    pub groups: Vec<TranslatedGroup>,
+    pub questions: Vec<String>,
+}
+
+/// Response for translate_analysis command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslateResponse {
+    pub result: AnalysisResult,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// A recent PR touching some of the same files as the reviewed one.
+/// `relation` is "conflict", "duplicate", or "none".
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedPr {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub state: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    pub overlapping_files: Vec<String>,
+    pub relation: String,
+    pub reason: String,
+}
+
+/// The model's judgement of one candidate PR.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedPrAssessment {
+    pub number: u64,
+    pub relation: String,
+    pub reason: String,
+}
+
+/// Codex output shape for find_related_prs.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedPrsResult {
+    pub assessments: Vec<RelatedPrAssessment>,
+}
+
+/// Response for find_related_prs command. Most likely conflicts first.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedPrsResponse {
+    pub related: Vec<RelatedPr>,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// Result of apply_category_labels.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelResult {
+    /// Labels added to the PR (or that would be, on a dry run).
+    pub applied: Vec<String>,
+    /// Mapped labels that do not exist in the repository and were skipped.
+    pub missing: Vec<String>,
+    pub dry_run: bool,
+}
+
+/// A question or finding to track as a follow-up GitHub issue.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowUpItem {
+    /// "question" or "finding".
+    pub kind: String,
+    pub text: String,
+    /// Issue title; defaults to the first line of `text`.
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// File (and line in the PR head) the item is about, linked from the issue.
+    #[serde(default)]
+    pub file_path: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+}
+
+/// A follow-up issue, created or (on a dry run) only drafted.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowUpIssue {
+    pub title: String,
+    pub body: String,
+    /// URL of the created issue; None on a dry run or when creating it failed.
+    pub url: Option<String>,
+    pub error: Option<String>,
+}
+
+/// One comment of a review thread.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadComment {
+    pub author: String,
+    pub body: String,
+    pub created_at: String,
+}
+
+/// An inline review thread on a PR, as fetched by get_review_threads.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewThread {
+    pub id: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    pub is_resolved: bool,
+    pub is_outdated: bool,
+    pub comments: Vec<ThreadComment>,
+}
+
+/// Status summary of one review thread. `status` is "resolved" or "open".
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadSummary {
+    pub thread_id: String,
+    pub status: String,
+    /// What the reviewer asked for.
+    pub asked: String,
+    /// What has been addressed so far (empty if nothing).
+    pub resolved: String,
+    /// What is still open (empty if nothing).
+    pub open: String,
+}
+
+/// Codex output shape for summarize_review_threads.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadSummariesResult {
+    pub summaries: Vec<ThreadSummary>,
+}
+
+/// Response for summarize_review_threads command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadSummariesResponse {
+    pub summaries: Vec<ThreadSummary>,
+    pub codex_log: String,
+    #[serde(default)]
+    pub from_cache: bool,
+}
+
+/// When a group or hunk was marked reviewed, and the PR head it was reviewed at.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewMark {
+    /// Unix seconds.
+    pub at: u64,
+    #[serde(default)]
+    pub head_sha: Option<String>,
+}
+
+/// Where a hunk sits in the diff, so a shared analysis can be re-anchored on another machine
+/// whose hunk ids differ.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkAnchor {
+    pub id: String,
+    pub file_path: String,
+    pub header: String,
+}
+
+/// An analysis exported for a co-reviewer, with the hunks it refers to.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedAnalysis {
+    /// Always "prvw-analysis"; tells exported files apart from bare analyses.
+    pub format: String,
+    #[serde(default)]
+    pub repo: Option<String>,
+    #[serde(default)]
+    pub pr_number: Option<u32>,
+    #[serde(default)]
+    pub head_sha: Option<String>,
+    pub analysis: AnalysisResult,
+    #[serde(default)]
+    pub anchors: Vec<HunkAnchor>,
+}
+
+/// Result of `import_analysis`: the analysis re-anchored and validated against the local diff.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedAnalysis {
+    pub result: AnalysisResult,
+    /// Hunk ids that were renamed to match the local diff.
+    pub remapped: usize,
+    pub warnings: Vec<String>,
+    /// PR the analysis was exported from, when the file says so.
+    #[serde(default)]
+    pub repo: Option<String>,
+    #[serde(default)]
+    pub pr_number: Option<u32>,
+}
+
+/// Review state of a PR as it was at one head commit, with the anchors needed to carry it over
+/// to a later head.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadState {
+    pub repo: String,
+    pub pr_number: u32,
+    pub head_sha: String,
+    pub anchors: Vec<HunkAnchor>,
+    /// Hunk id to a hash of its lines, to tell moved hunks from changed ones.
+    #[serde(default)]
+    pub fingerprints: BTreeMap<String, String>,
+    #[serde(default)]
+    pub analysis: Option<AnalysisResult>,
+    pub session: ReviewSession,
+    #[serde(default)]
+    pub comments: Vec<QueuedComment>,
+    /// Unix seconds.
+    pub saved_at: u64,
+}
+
+/// Review state carried from one head commit to another by `migrate_head_state`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadMigration {
+    pub from_head_sha: String,
+    pub head_sha: String,
+    /// The analysis re-anchored on the new diff; None when none was saved for the old head.
+    pub analysis: Option<AnalysisResult>,
+    pub session: ReviewSession,
+    pub comments: Vec<QueuedComment>,
+    /// Hunk ids that were renamed to match the new diff.
+    pub remapped: usize,
+    /// Review marks still valid at the new head.
+    pub carried_marks: usize,
+    /// State that could not be carried over, one line each.
+    pub not_carried: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// A panic or failed command saved by the opt-in error reporting. Messages are cut down and
+/// stripped of diff-like lines, so a report can be shared without the code under review.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorReport {
+    pub id: String,
+    /// "panic" or "command".
+    pub kind: String,
+    /// Operation kind of the failed command, or where the panic happened.
+    pub source: String,
+    pub message: String,
+    #[serde(default)]
+    pub backtrace: Option<String>,
+    pub app_version: String,
+    pub os: String,
+    pub at: u64,
+}
+
+/// New commits detected on the PR open in the window (the `pr-head-changed` event).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadChange {
+    pub repo: String,
+    pub pr_number: u32,
+    pub from_head_sha: String,
+    pub head_sha: String,
+}
+
+/// A PR re-fetched at its new head by `refresh_pr_head`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadRefresh {
+    pub head_sha: String,
+    /// The new diff, held as review state like `open_review_state` holds it.
+    pub review: ReviewState,
+    /// The previous analysis re-anchored on the new diff; None when there was none.
+    pub analysis: Option<AnalysisResult>,
+    /// Review state carried over; None when the session had no progress at an older head.
+    pub migration: Option<HeadMigration>,
+    /// What the push changed per group; hunks in `new_hunk_ids` are the ones left to analyze.
+    pub delta: Option<AnalysisDelta>,
+}
+
+/// Reusable reviewer checklist items merged into every group's `reviewer_checklist`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChecklistTemplate {
+    /// Assigned on first save; empty for a new template.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// "owner/name" the template applies to; None applies it to every repo.
+    #[serde(default)]
+    pub repo: Option<String>,
+    pub items: Vec<String>,
+}
+
+/// A reusable review comment. `{file}`, `{line}` and `{group}` in the body are filled in when it
+/// is inserted into the comment queue.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentTemplate {
+    /// Assigned on first save; empty for a new template.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub body: String,
+    /// Severity of the inserted comment: "nit", "suggestion" (the default), "issue" or "blocker".
+    #[serde(default)]
+    pub severity: String,
+    /// "owner/name" the template applies to; None offers it in every repo.
+    #[serde(default)]
+    pub repo: Option<String>,
+}
+
+/// Per-PR review progress, persisted across app restarts.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewSession {
+    pub repo: String,
+    pub pr_number: u32,
+    /// Latest PR head seen by an update.
+    #[serde(default)]
+    pub head_sha: Option<String>,
+    #[serde(default)]
+    pub reviewed_groups: BTreeMap<String, ReviewMark>,
+    #[serde(default)]
+    pub reviewed_hunks: BTreeMap<String, ReviewMark>,
+    /// Findings and checklist items the reviewer marked as blocking, resolved or not.
+    #[serde(default)]
+    pub blocking_items: Vec<BlockingItem>,
+    /// Checked reviewer checklist items, by `G<n>:checklist:<index>` id.
+    #[serde(default)]
+    pub checked_items: BTreeMap<String, ReviewMark>,
+    /// Groups set aside without review, by group id.
+    #[serde(default)]
+    pub skipped_groups: BTreeMap<String, GroupSkip>,
+    /// Reviewer (GitHub login) each intent group is assigned to, by group id.
+    #[serde(default)]
+    pub assignments: BTreeMap<String, String>,
+    /// The reviewer's own group order, used instead of the analysis order where set.
+    #[serde(default)]
+    pub group_order: Option<GroupOrder>,
+    /// Unix seconds of the last update; 0 for a session never saved.
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+/// A custom group order, set at `head_sha`. Groups it does not list follow in analysis order.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupOrder {
+    pub group_ids: Vec<String>,
+    /// Unix seconds.
+    pub at: u64,
+    #[serde(default)]
+    pub head_sha: Option<String>,
+}
+
+/// Why and how a group was set aside. A skip lasts until undone; a snooze only until the next
+/// push (it applies while the session is still at `head_sha`).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupSkip {
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default)]
+    pub snoozed: bool,
+    /// Unix seconds.
+    pub at: u64,
+    #[serde(default)]
+    pub head_sha: Option<String>,
+}
+
+/// A finding or checklist item that must be addressed before the PR can be approved.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockingItem {
+    /// Chosen by the caller and stable for the item, e.g. "G1:checklist:2" or "G1:test:0".
+    pub id: String,
+    /// "checklist", "test", "question", "comment", or "finding".
+    pub kind: String,
+    #[serde(default)]
+    pub group_id: Option<String>,
+    #[serde(default)]
+    pub hunk_id: Option<String>,
+    pub text: String,
+    #[serde(default)]
+    pub resolved: bool,
+    /// Unix seconds when it was marked blocking; set by `update_session`.
+    #[serde(default)]
+    pub at: u64,
+}
+
+/// Changes for `update_session`; marks are stamped with the time and `head_sha`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUpdate {
+    #[serde(default)]
+    pub head_sha: Option<String>,
+    #[serde(default)]
+    pub mark_groups: Vec<String>,
+    #[serde(default)]
+    pub unmark_groups: Vec<String>,
+    #[serde(default)]
+    pub mark_hunks: Vec<String>,
+    #[serde(default)]
+    pub unmark_hunks: Vec<String>,
+    /// Items to mark as blocking; an item with an existing id replaces it and reopens it.
+    #[serde(default)]
+    pub block_items: Vec<BlockingItem>,
+    #[serde(default)]
+    pub resolve_items: Vec<String>,
+    /// Ids of items that are no longer blocking at all.
+    #[serde(default)]
+    pub unblock_items: Vec<String>,
+    /// Checklist item ids to check; stamped like review marks.
+    #[serde(default)]
+    pub check_items: Vec<String>,
+    #[serde(default)]
+    pub uncheck_items: Vec<String>,
+    /// Groups to skip (or snooze, with `snooze`), with `skip_reason`.
+    #[serde(default)]
+    pub skip_groups: Vec<String>,
+    #[serde(default)]
+    pub skip_reason: Option<String>,
+    #[serde(default)]
+    pub snooze: bool,
+    #[serde(default)]
+    pub unskip_groups: Vec<String>,
+    /// New custom group order; an empty list restores the analysis order.
+    #[serde(default)]
+    pub group_order: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupProgress {
+    pub group_id: String,
+    pub title: String,
+    /// Substantive hunks of the group that are reviewed.
+    pub reviewed_hunks: usize,
+    pub total_hunks: usize,
+    pub complete: bool,
+    /// Checklist items of the group checked at the current head.
+    #[serde(default)]
+    pub checked_items: usize,
+    #[serde(default)]
+    pub total_checklist_items: usize,
+    /// Set while the group is skipped or snoozed; it then does not count towards progress.
+    #[serde(default)]
+    pub skip: Option<GroupSkip>,
+}
+
+/// Review progress of a PR, derived from its session and analysis.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewProgress {
+    /// Distinct hunks in the analysis, non-substantive ones included.
+    pub total_hunks: usize,
+    /// Substantive hunks reviewed, directly or through their group.
+    pub reviewed_hunks: usize,
+    /// Non-substantive hunks, which count as done without review.
+    pub auto_skipped_hunks: usize,
+    /// Unreviewed hunks only in skipped or snoozed groups, left out of the review.
+    #[serde(default)]
+    pub skipped_hunks: usize,
+    pub groups: Vec<GroupProgress>,
+    /// Active review time estimated from the gaps between marks, seconds.
+    pub time_spent_secs: u64,
+    pub ready_to_submit: bool,
+    /// Unresolved blocking items; while any are open the PR cannot be approved.
+    #[serde(default)]
+    pub open_blocking_items: usize,
+    #[serde(default)]
+    pub checked_items: usize,
+    #[serde(default)]
+    pub total_checklist_items: usize,
+}
+
+/// One intent group and the reviewer it is assigned to.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupAssignment {
+    pub group_id: String,
+    pub title: String,
+    pub risk: String,
+    pub risk_score: u32,
+    pub hunk_count: usize,
+    /// None while the group is unassigned.
+    pub reviewer: Option<String>,
+}
+
+/// The assignment table of a PR, as rows and as a Markdown comment body.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignmentTable {
+    pub rows: Vec<GroupAssignment>,
+    pub markdown: String,
+}
+
+/// A reviewer checklist item of a group, addressable by id.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChecklistItem {
+    /// `<group id>:checklist:<index>`, the same id a blocking checklist item uses.
+    pub id: String,
+    pub group_id: String,
+    pub text: String,
+    /// Checked at the current head.
+    pub checked: bool,
+}
+
+/// The next hunk to review and the group it is reviewed with.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NextUnreviewed {
+    /// None for unassigned hunks.
+    pub group_id: Option<String>,
+    pub hunk_id: String,
+    /// Unreviewed hunks left, this one included.
+    pub remaining: usize,
+}
+
+/// Outcome of `sync_viewed_state`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewedSync {
+    /// Files fully reviewed locally that were marked viewed on GitHub.
+    pub marked_on_github: Vec<String>,
+    /// Files viewed on GitHub whose hunks were marked reviewed locally.
+    pub marked_locally: Vec<String>,
+    /// The session after the sync.
+    pub session: ReviewSession,
+}
+
+/// One turn of the per-PR question/answer conversation.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub role: String, // "user", "assistant"
+    pub content: String,
+    /// Hunk IDs the answer is based on (always empty for user messages).
+    #[serde(default)]
+    pub hunk_ids: Vec<String>,
+}
+
+/// Codex output shape for ask_about_pr.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AskPrResult {
+    pub answer: String,
+    pub hunk_ids: Vec<String>,
+}
+
+/// Response for ask_about_pr command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AskPrResponse {
+    pub answer: ChatMessage,
+    pub codex_log: String,
+}
+
+/// How one intent group changed between two analyses of the same PR.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupDelta {
+    /// Group ID in the new analysis (or in the old one for removed groups).
+    pub group_id: String,
+    pub title: String,
+    pub status: String, // "new", "changed", "unchanged", "removed"
+    /// ID of the best-matching group in the old analysis.
+    pub matched_group_id: Option<String>,
+    /// New-analysis hunk IDs whose content was not in the matched old group.
+    pub gained_hunk_ids: Vec<String>,
+    /// Old-analysis hunk IDs whose content is no longer in the group.
+    pub lost_hunk_ids: Vec<String>,
+}
+
+/// Response for diff_analyses command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisDelta {
+    pub groups: Vec<GroupDelta>,
+    pub removed_groups: Vec<GroupDelta>,
+    /// New-analysis hunk IDs whose content did not exist before the push.
+    pub new_hunk_ids: Vec<String>,
+}
+
+/// A long-running command as reported by `op-progress`/`op-done`/`op-error` events.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationRecord {
+    /// Id chosen by the UI when it started the command.
+    pub op_id: String,
+    /// Command name, e.g. `get_pr_diff`.
+    pub kind: String,
+    /// "running", "done", "error" or "cancelled".
+    pub status: String,
+    /// Current step while running.
+    pub message: Option<String>,
+    pub error: Option<String>,
+    pub started_at: u64,
+    pub elapsed_ms: u64,
+}
@@ -0,0 +1,161 @@
+use std::io::Write;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::codex_runner::CodexOutput;
+use crate::types::{TokenEstimate, TokenUsage, UsageEntry};
+
+/// The ledger is user data, not cache: it lives outside `cache/` so clearing the cache keeps it.
+const LEDGER_PATH: &str = "usage/ledger.jsonl";
+
+/// Matches Codex's end-of-run summary, both `tokens used: 1234` and `tokens used\n1,234`.
+static CODEX_TOKENS_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"(?i)tokens used:?\s*([\d,]+)").expect("invalid tokens regex")
+});
+
+/// Rough token count (~4 characters per token); good enough for budget warnings.
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+/// Parse the last "tokens used" figure Codex prints, if any.
+pub fn parse_codex_usage(text: &str) -> Option<TokenUsage> {
+    let caps = CODEX_TOKENS_RE.captures_iter(text).last()?;
+    let total = caps[1].replace(',', "").parse().ok()?;
+    Some(TokenUsage {
+        input_tokens: None,
+        output_tokens: None,
+        total_tokens: total,
+    })
+}
+
+/// Append one run to the ledger. Best-effort, like cache writes: failures are only logged.
+pub fn record(
+    app_data_dir: Option<&Path>,
+    kind: &str,
+    provider: &str,
+    estimated_input_tokens: u64,
+    output: &CodexOutput,
+) {
+    let Some(dir) = app_data_dir else {
+        return;
+    };
+    let entry = UsageEntry {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        kind: kind.to_string(),
+        provider: provider.to_string(),
+        model: output.model_used.clone(),
+        estimated_input_tokens,
+        usage: output.usage.clone(),
+        elapsed_secs: output.elapsed_secs,
+    };
+    if let Err(e) = append_entry(&dir.join(LEDGER_PATH), &entry) {
+        eprintln!("[usage] failed to record run: {}", e);
+    }
+}
+
+fn append_entry(path: &Path, entry: &UsageEntry) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())
+}
+
+/// Read the ledger newest-first, skipping lines that fail to parse.
+fn read_ledger(path: &Path, limit: usize) -> Vec<UsageEntry> {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    text.lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect()
+}
+
+/// Every recorded run, newest first.
+pub fn ledger(app_data_dir: &Path) -> Vec<UsageEntry> {
+    read_ledger(&app_data_dir.join(LEDGER_PATH), usize::MAX)
+}
+
+/// The estimated input tokens of an analysis run of `hunks_json`, compared with `budget`.
+pub fn estimate(hunks_json: &str, budget: Option<u64>) -> TokenEstimate {
+    let estimated_tokens = estimate_tokens(hunks_json);
+    let budget = budget.filter(|b| *b > 0);
+    TokenEstimate {
+        estimated_tokens,
+        budget,
+        over_budget: budget.is_some_and(|b| estimated_tokens > b),
+    }
+}
+
+/// The newest `limit` recorded runs, newest first.
+pub fn recent(app_data_dir: &Path, limit: usize) -> Vec<UsageEntry> {
+    read_ledger(&app_data_dir.join(LEDGER_PATH), limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(usage: Option<TokenUsage>) -> CodexOutput {
+        CodexOutput {
+            stdout: String::new(),
+            stderr: String::new(),
+            elapsed_secs: 2.0,
+            model_used: "m".to_string(),
+            usage,
+            args: vec![],
+            run_id: None,
+        }
+    }
+
+    #[test]
+    fn estimate_rounds_up() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn parse_codex_usage_formats() {
+        let inline = parse_codex_usage("...\ntokens used: 1234\n").map(|u| u.total_tokens);
+        assert_eq!(inline, Some(1234));
+        let split = parse_codex_usage("[ts] tokens used\n12,345\n").map(|u| u.total_tokens);
+        assert_eq!(split, Some(12345));
+        assert!(parse_codex_usage("no usage here").is_none());
+    }
+
+    #[test]
+    fn ledger_round_trip_newest_first() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let usage = TokenUsage {
+            total_tokens: 10,
+            ..TokenUsage::default()
+        };
+        record(
+            Some(tmp.path()),
+            "analysis",
+            "codex",
+            5,
+            &output(Some(usage)),
+        );
+        record(Some(tmp.path()), "refine", "claude", 3, &output(None));
+
+        let entries = read_ledger(&tmp.path().join(LEDGER_PATH), 10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, "refine");
+        assert!(entries[0].usage.is_none());
+        assert_eq!(entries[1].usage.as_ref().map(|u| u.total_tokens), Some(10));
+        assert_eq!(read_ledger(&tmp.path().join(LEDGER_PATH), 1).len(), 1);
+    }
+}
@@ -0,0 +1,1021 @@
+use std::collections::HashSet;
+
+use crate::types::{
+    AnalysisResult, BreakingChange, ConsistencyMismatch, DraftComment, Hunk, HunkSummary,
+    IntentGroup, SplitPlan, SplitPlanResult,
+};
+
+pub struct ValidationResult {
+    pub cleaned: AnalysisResult,
+    pub warnings: Vec<String>,
+    /// Number of invalid, duplicate, or missing hunk IDs found in the raw result.
+    pub id_errors: usize,
+}
+
+/// Minimum number of ID problems before a repair retry is worth its cost.
+const REPAIR_MIN_ID_ERRORS: usize = 3;
+
+impl ValidationResult {
+    /// Whether the raw result was broken badly enough to ask the model for a repaired one:
+    /// more than `REPAIR_MIN_ID_ERRORS` ID problems, affecting at least 10% of the hunks.
+    pub fn needs_repair(&self, hunk_count: usize) -> bool {
+        self.id_errors > REPAIR_MIN_ID_ERRORS && self.id_errors * 10 >= hunk_count
+    }
+}
+
+/// Expected `risk_score` range for each risk label.
+fn risk_band(risk: &str) -> Option<(u32, u32)> {
+    match risk {
+        "low" => Some((0, 33)),
+        "medium" => Some((34, 66)),
+        "high" => Some((67, 100)),
+        _ => None,
+    }
+}
+
+/// Validate a group's risk score and evidence against its (already cleaned) hunk IDs.
+/// Evidence pointing outside the group is dropped; inconsistencies are reported as warnings.
+pub fn validate_risk(group: &mut IntentGroup, warnings: &mut Vec<String>) {
+    if group.risk_score > 100 {
+        warnings.push(format!(
+            "Group '{}': risk score {} clamped to 100",
+            group.title, group.risk_score
+        ));
+        group.risk_score = 100;
+    }
+
+    let hunk_ids: HashSet<&String> = group.hunk_ids.iter().collect();
+    let title = &group.title;
+    group.risk_evidence.retain(|ev| {
+        if !hunk_ids.contains(&ev.hunk_id) {
+            warnings.push(format!(
+                "Removed risk evidence for hunk id '{}' not in group '{}'",
+                ev.hunk_id, title
+            ));
+            return false;
+        }
+        if ev.reason.trim().is_empty() {
+            warnings.push(format!(
+                "Removed risk evidence without reason for hunk id '{}' in group '{}'",
+                ev.hunk_id, title
+            ));
+            return false;
+        }
+        true
+    });
+
+    if group.risk != "low" && group.risk_evidence.is_empty() {
+        warnings.push(format!(
+            "Group '{}' is rated {} risk but has no evidence",
+            group.title, group.risk
+        ));
+    }
+    if let Some((lo, hi)) = risk_band(&group.risk) {
+        if group.risk_score < lo || group.risk_score > hi {
+            warnings.push(format!(
+                "Group '{}': risk score {} does not match '{}' ({}-{})",
+                group.title, group.risk_score, group.risk, lo, hi
+            ));
+        }
+    }
+}
+
+/// Validate and clean up analysis results.
+/// Instead of failing on invalid IDs, remove them and collect warnings.
+pub fn validate_analysis(result: &AnalysisResult, valid_ids: &HashSet<String>) -> ValidationResult {
+    let mut warnings: Vec<String> = Vec::new();
+    let mut id_errors = 0;
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut cleaned = result.clone();
+
+    // Clean groups: remove invalid/duplicate hunk IDs
+    for group in &mut cleaned.groups {
+        let original_len = group.hunk_ids.len();
+        group.hunk_ids.retain(|hid| {
+            if !valid_ids.contains(hid) {
+                id_errors += 1;
+                warnings.push(format!(
+                    "Removed non-existent hunk id '{}' from group '{}'",
+                    hid, group.title
+                ));
+                return false;
+            }
+            if seen.contains(hid) {
+                id_errors += 1;
+                warnings.push(format!(
+                    "Removed duplicate hunk id '{}' in group '{}'",
+                    hid, group.title
+                ));
+                return false;
+            }
+            seen.insert(hid.clone());
+            true
+        });
+        if group.hunk_ids.len() != original_len {
+            warnings.push(format!(
+                "Group '{}': {} -> {} hunks after cleanup",
+                group.title,
+                original_len,
+                group.hunk_ids.len()
+            ));
+        }
+        validate_risk(group, &mut warnings);
+    }
+
+    // Remove empty groups
+    let before = cleaned.groups.len();
+    cleaned.groups.retain(|g| !g.hunk_ids.is_empty());
+    if cleaned.groups.len() != before {
+        warnings.push(format!(
+            "Removed {} empty group(s) after cleanup",
+            before - cleaned.groups.len()
+        ));
+    }
+
+    // Clean unassigned: remove invalid/duplicate
+    cleaned.unassigned_hunk_ids.retain(|hid| {
+        if !valid_ids.contains(hid) {
+            id_errors += 1;
+            warnings.push(format!("Removed non-existent unassigned hunk id '{}'", hid));
+            return false;
+        }
+        if seen.contains(hid) {
+            id_errors += 1;
+            warnings.push(format!("Removed duplicate unassigned hunk id '{}'", hid));
+            return false;
+        }
+        seen.insert(hid.clone());
+        true
+    });
+
+    // Add missing hunks to unassigned
+    let missing: Vec<String> = valid_ids
+        .iter()
+        .filter(|id| !seen.contains(*id))
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        id_errors += missing.len();
+        warnings.push(format!(
+            "Added {} missing hunk(s) to unassigned: {:?}",
+            missing.len(),
+            missing
+        ));
+        cleaned.unassigned_hunk_ids.extend(missing);
+    }
+
+    // Clean nonSubstantiveHunkIds: remove invalid IDs
+    let original_ns_len = cleaned.non_substantive_hunk_ids.len();
+    cleaned.non_substantive_hunk_ids.retain(|hid| {
+        if valid_ids.contains(hid) {
+            true
+        } else {
+            id_errors += 1;
+            warnings.push(format!(
+                "Removed non-existent non-substantive hunk id '{}'",
+                hid
+            ));
+            false
+        }
+    });
+    if cleaned.non_substantive_hunk_ids.len() != original_ns_len {
+        warnings.push(format!(
+            "nonSubstantiveHunkIds: {} -> {} after cleanup",
+            original_ns_len,
+            cleaned.non_substantive_hunk_ids.len()
+        ));
+    }
+
+    ValidationResult {
+        cleaned,
+        warnings,
+        id_errors,
+    }
+}
+
+/// Validate per-hunk summaries: drop unknown/duplicate hunk IDs and empty summaries,
+/// and warn about hunks that did not get a summary.
+pub fn validate_summaries(
+    summaries: Vec<HunkSummary>,
+    valid_ids: &HashSet<String>,
+) -> (Vec<HunkSummary>, Vec<String>) {
+    let mut warnings: Vec<String> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut cleaned: Vec<HunkSummary> = Vec::new();
+
+    for s in summaries {
+        if !valid_ids.contains(&s.hunk_id) {
+            warnings.push(format!(
+                "Removed summary for non-existent hunk id '{}'",
+                s.hunk_id
+            ));
+            continue;
+        }
+        if seen.contains(&s.hunk_id) {
+            warnings.push(format!(
+                "Removed duplicate summary for hunk id '{}'",
+                s.hunk_id
+            ));
+            continue;
+        }
+        if s.summary.trim().is_empty() {
+            warnings.push(format!("Removed empty summary for hunk id '{}'", s.hunk_id));
+            continue;
+        }
+        seen.insert(s.hunk_id.clone());
+        cleaned.push(s);
+    }
+
+    let missing = valid_ids.iter().filter(|id| !seen.contains(*id)).count();
+    if missing > 0 {
+        warnings.push(format!("{} hunk(s) have no summary", missing));
+    }
+
+    (cleaned, warnings)
+}
+
+/// Whether `line` on `side` is a line the hunk actually shows (and can thus carry a comment).
+pub fn hunk_has_line(hunk: &Hunk, line: u32, side: &str) -> bool {
+    hunk.lines.iter().any(|l| match side {
+        "LEFT" => l.old_line == Some(line) && l.kind != "add",
+        _ => l.new_line == Some(line) && l.kind != "remove",
+    })
+}
+
+/// Validate AI-drafted inline comments against the real hunks: each comment must reference
+/// one of `hunks`, its file, and a line visible in that hunk on the given side.
+pub fn validate_draft_comments(
+    comments: Vec<DraftComment>,
+    hunks: &[&Hunk],
+) -> (Vec<DraftComment>, Vec<String>) {
+    let mut warnings: Vec<String> = Vec::new();
+    let mut cleaned: Vec<DraftComment> = Vec::new();
+
+    for mut c in comments {
+        let Some(hunk) = hunks.iter().find(|h| h.id == c.hunk_id) else {
+            warnings.push(format!(
+                "Removed comment for non-existent hunk id '{}'",
+                c.hunk_id
+            ));
+            continue;
+        };
+        if c.file_path != hunk.file_path {
+            warnings.push(format!(
+                "Corrected file path '{}' -> '{}' for comment on {}",
+                c.file_path, hunk.file_path, c.hunk_id
+            ));
+            c.file_path = hunk.file_path.clone();
+        }
+        if c.side != "LEFT" && c.side != "RIGHT" {
+            c.side = "RIGHT".to_string();
+        }
+        if !hunk_has_line(hunk, c.line, &c.side) {
+            warnings.push(format!(
+                "Removed comment on {} line {} ({}) which is not part of hunk {}",
+                c.file_path, c.line, c.side, c.hunk_id
+            ));
+            continue;
+        }
+        if c.body.trim().is_empty() {
+            warnings.push(format!(
+                "Removed empty comment on {} line {}",
+                c.file_path, c.line
+            ));
+            continue;
+        }
+        cleaned.push(c);
+    }
+
+    (cleaned, warnings)
+}
+
+/// Validate breaking-change findings against the analysis: each finding must name an existing
+/// group and keep only hunk IDs from that group. Findings left without hunks are dropped.
+pub fn validate_breaking_changes(
+    findings: Vec<BreakingChange>,
+    groups: &[IntentGroup],
+) -> (Vec<BreakingChange>, Vec<String>) {
+    let mut warnings: Vec<String> = Vec::new();
+    let mut cleaned: Vec<BreakingChange> = Vec::new();
+
+    for mut f in findings {
+        let Some(group) = groups.iter().find(|g| g.id == f.group_id) else {
+            warnings.push(format!(
+                "Removed finding '{}' for non-existent group id '{}'",
+                f.subject, f.group_id
+            ));
+            continue;
+        };
+        let subject = f.subject.clone();
+        let mut seen: HashSet<String> = HashSet::new();
+        f.hunk_ids.retain(|hid| {
+            if !group.hunk_ids.contains(hid) {
+                warnings.push(format!(
+                    "Removed hunk id '{}' not in group '{}' from finding '{}'",
+                    hid, group.id, subject
+                ));
+                return false;
+            }
+            seen.insert(hid.clone())
+        });
+        if f.hunk_ids.is_empty() {
+            warnings.push(format!(
+                "Removed finding '{}' with no valid hunk ids",
+                f.subject
+            ));
+            continue;
+        }
+        if f.severity != "breaking" && f.severity != "possibly-breaking" {
+            f.severity = "possibly-breaking".to_string();
+        }
+        cleaned.push(f);
+    }
+
+    (cleaned, warnings)
+}
+
+/// Validate consistency mismatches against the analysis groups: drop unknown group ids,
+/// mismatches left without the groups (or claim) their kind needs, and normalize severity.
+pub fn validate_consistency(
+    mismatches: Vec<ConsistencyMismatch>,
+    groups: &[IntentGroup],
+) -> (Vec<ConsistencyMismatch>, Vec<String>) {
+    let group_ids: HashSet<&String> = groups.iter().map(|g| &g.id).collect();
+    let mut warnings: Vec<String> = Vec::new();
+    let mut cleaned: Vec<ConsistencyMismatch> = Vec::new();
+
+    for mut m in mismatches {
+        let mut seen: HashSet<String> = HashSet::new();
+        m.group_ids.retain(|gid| {
+            if !group_ids.contains(gid) {
+                warnings.push(format!(
+                    "Removed non-existent group id '{}' from mismatch",
+                    gid
+                ));
+                return false;
+            }
+            seen.insert(gid.clone())
+        });
+        let complete = match m.kind.as_str() {
+            "undisclosed" => !m.group_ids.is_empty(),
+            "unfulfilled" => !m.claim.trim().is_empty(),
+            "contradicted" => !m.group_ids.is_empty() && !m.claim.trim().is_empty(),
+            _ => false,
+        };
+        if !complete {
+            warnings.push(format!(
+                "Removed incomplete '{}' mismatch: {}",
+                m.kind, m.description
+            ));
+            continue;
+        }
+        if !matches!(m.severity.as_str(), "low" | "medium" | "high") {
+            m.severity = "medium".to_string();
+        }
+        cleaned.push(m);
+    }
+
+    (cleaned, warnings)
+}
+
+/// Validate a split plan against the real hunks (`hunk_ids`, in diff order): drop unknown and
+/// duplicate ids and emptied PRs, keep only dependencies on earlier PRs (renumbered after
+/// removals), and collect hunks no proposed PR covers into `unassigned_hunk_ids`.
+pub fn validate_split_plan(
+    result: SplitPlanResult,
+    hunk_ids: &[String],
+) -> (SplitPlan, Vec<String>) {
+    let valid_ids: HashSet<&String> = hunk_ids.iter().collect();
+    let mut warnings: Vec<String> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    // new_positions[i] is the 1-based position of proposed PR i after cleanup, if kept.
+    let mut new_positions: Vec<Option<u32>> = Vec::new();
+    let mut prs = Vec::new();
+
+    for mut pr in result.prs {
+        let title = pr.title.clone();
+        pr.hunk_ids.retain(|hid| {
+            if !valid_ids.contains(hid) {
+                warnings.push(format!(
+                    "Removed non-existent hunk id '{}' from proposed PR '{}'",
+                    hid, title
+                ));
+                return false;
+            }
+            if !seen.insert(hid.clone()) {
+                warnings.push(format!(
+                    "Removed duplicate hunk id '{}' from proposed PR '{}'",
+                    hid, title
+                ));
+                return false;
+            }
+            true
+        });
+        if pr.hunk_ids.is_empty() {
+            warnings.push(format!("Removed empty proposed PR '{}'", title));
+            new_positions.push(None);
+            continue;
+        }
+
+        let own = new_positions.len() as u32 + 1;
+        let mut depends_on = Vec::new();
+        for dep in pr.depends_on {
+            let target = if dep >= 1 && dep < own {
+                new_positions[(dep - 1) as usize]
+            } else {
+                None
+            };
+            match target {
+                Some(pos) if !depends_on.contains(&pos) => depends_on.push(pos),
+                Some(_) => {}
+                None => warnings.push(format!(
+                    "Removed dependency on PR {} from proposed PR '{}' (must be an earlier, kept PR)",
+                    dep, title
+                )),
+            }
+        }
+        pr.depends_on = depends_on;
+        prs.push(pr);
+        new_positions.push(Some(prs.len() as u32));
+    }
+
+    let unassigned_hunk_ids: Vec<String> = hunk_ids
+        .iter()
+        .filter(|id| !seen.contains(*id))
+        .cloned()
+        .collect();
+    if !unassigned_hunk_ids.is_empty() {
+        warnings.push(format!(
+            "{} hunk(s) not covered by any proposed PR: {:?}",
+            unassigned_hunk_ids.len(),
+            unassigned_hunk_ids
+        ));
+    }
+
+    let plan = SplitPlan {
+        rationale: result.rationale,
+        prs,
+        unassigned_hunk_ids,
+    };
+    (plan, warnings)
+}
+
+/// Validate sub-groups from a guided refinement of group `group_id`: drop unknown and duplicate
+/// hunk ids and emptied sub-groups, and number the sub-groups `<group_id>.1`, `<group_id>.2`, ...
+/// Returns the cleaned sub-groups, warnings, and the group's hunk ids no sub-group covers.
+pub fn validate_guided_refine(
+    groups: Vec<IntentGroup>,
+    group_id: &str,
+    hunk_ids: &[String],
+) -> (Vec<IntentGroup>, Vec<String>, Vec<String>) {
+    let valid_ids: HashSet<&String> = hunk_ids.iter().collect();
+    let mut warnings: Vec<String> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut cleaned: Vec<IntentGroup> = Vec::new();
+
+    for mut g in groups {
+        let title = g.title.clone();
+        g.hunk_ids.retain(|hid| {
+            if !valid_ids.contains(hid) {
+                warnings.push(format!(
+                    "Removed hunk id '{}' outside the group from sub-group '{}'",
+                    hid, title
+                ));
+                return false;
+            }
+            if !seen.insert(hid.clone()) {
+                warnings.push(format!(
+                    "Removed duplicate hunk id '{}' from sub-group '{}'",
+                    hid, title
+                ));
+                return false;
+            }
+            true
+        });
+        if g.hunk_ids.is_empty() {
+            warnings.push(format!("Removed empty sub-group '{}'", title));
+            continue;
+        }
+        let id = format!("{}.{}", group_id, cleaned.len() + 1);
+        if g.id != id {
+            warnings.push(format!("Renumbered sub-group '{}' to {}", g.id, id));
+            g.id = id;
+        }
+        validate_risk(&mut g, &mut warnings);
+        cleaned.push(g);
+    }
+
+    let missing: Vec<String> = hunk_ids
+        .iter()
+        .filter(|id| !seen.contains(*id))
+        .cloned()
+        .collect();
+    (cleaned, warnings, missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        AnalysisResult, DiffLine, DraftComment, Hunk, HunkSummary, IntentGroup, RiskEvidence,
+        SplitPr,
+    };
+
+    fn make_group(id: &str, title: &str, hunk_ids: Vec<&str>) -> IntentGroup {
+        IntentGroup {
+            id: id.to_string(),
+            title: title.to_string(),
+            category: "logic".to_string(),
+            rationale: String::new(),
+            risk: "low".to_string(),
+            risk_score: 10,
+            risk_evidence: vec![],
+            hunk_ids: hunk_ids.into_iter().map(String::from).collect(),
+            reviewer_checklist: vec![],
+            suggested_tests: vec![],
+        }
+    }
+
+    fn make_result(
+        groups: Vec<IntentGroup>,
+        unassigned: Vec<&str>,
+        non_sub: Vec<&str>,
+    ) -> AnalysisResult {
+        AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups,
+            unassigned_hunk_ids: unassigned.into_iter().map(String::from).collect(),
+            non_substantive_hunk_ids: non_sub.into_iter().map(String::from).collect(),
+            questions: vec![],
+        }
+    }
+
+    fn ids(slice: &[&str]) -> HashSet<String> {
+        slice.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn full_coverage_no_warnings() {
+        let result = make_result(
+            vec![make_group("G1", "Group 1", vec!["H1", "H2"])],
+            vec![],
+            vec![],
+        );
+        let valid = ids(&["H1", "H2"]);
+        let vr = validate_analysis(&result, &valid);
+        assert!(vr.warnings.is_empty());
+        assert_eq!(vr.cleaned.groups.len(), 1);
+        assert_eq!(vr.cleaned.groups[0].hunk_ids, vec!["H1", "H2"]);
+    }
+
+    #[test]
+    fn counts_id_errors_for_repair() {
+        let result = make_result(
+            vec![make_group("G1", "Group 1", vec!["H1", "H1", "H98", "H99"])],
+            vec!["H97"],
+            vec![],
+        );
+        let valid = ids(&["H1", "H2", "H3"]);
+        let vr = validate_analysis(&result, &valid);
+        // 1 duplicate + 3 non-existent + 2 missing (H2, H3)
+        assert_eq!(vr.id_errors, 6);
+        assert!(vr.needs_repair(valid.len()));
+        assert!(!vr.needs_repair(100));
+    }
+
+    #[test]
+    fn clean_result_needs_no_repair() {
+        let result = make_result(
+            vec![make_group("G1", "Group 1", vec!["H1", "H2"])],
+            vec![],
+            vec![],
+        );
+        let vr = validate_analysis(&result, &ids(&["H1", "H2"]));
+        assert_eq!(vr.id_errors, 0);
+        assert!(!vr.needs_repair(2));
+    }
+
+    #[test]
+    fn removes_nonexistent_ids_from_groups() {
+        let result = make_result(
+            vec![make_group("G1", "Group 1", vec!["H1", "H99"])],
+            vec![],
+            vec![],
+        );
+        let valid = ids(&["H1"]);
+        let vr = validate_analysis(&result, &valid);
+        assert_eq!(vr.cleaned.groups[0].hunk_ids, vec!["H1"]);
+        assert!(vr.warnings.iter().any(|w| w.contains("H99")));
+    }
+
+    #[test]
+    fn removes_duplicate_ids_across_groups() {
+        let result = make_result(
+            vec![
+                make_group("G1", "First", vec!["H1", "H2"]),
+                make_group("G2", "Second", vec!["H2", "H3"]),
+            ],
+            vec![],
+            vec![],
+        );
+        let valid = ids(&["H1", "H2", "H3"]);
+        let vr = validate_analysis(&result, &valid);
+        assert_eq!(vr.cleaned.groups[0].hunk_ids, vec!["H1", "H2"]);
+        assert_eq!(vr.cleaned.groups[1].hunk_ids, vec!["H3"]);
+        assert!(vr.warnings.iter().any(|w| w.contains("duplicate")));
+    }
+
+    #[test]
+    fn removes_duplicate_ids_within_group() {
+        let result = make_result(
+            vec![make_group("G1", "Group", vec!["H1", "H1", "H2"])],
+            vec![],
+            vec![],
+        );
+        let valid = ids(&["H1", "H2"]);
+        let vr = validate_analysis(&result, &valid);
+        assert_eq!(vr.cleaned.groups[0].hunk_ids, vec!["H1", "H2"]);
+    }
+
+    #[test]
+    fn removes_empty_groups_after_cleanup() {
+        let result = make_result(
+            vec![
+                make_group("G1", "Valid", vec!["H1"]),
+                make_group("G2", "Invalid", vec!["H99"]),
+            ],
+            vec![],
+            vec![],
+        );
+        let valid = ids(&["H1"]);
+        let vr = validate_analysis(&result, &valid);
+        assert_eq!(vr.cleaned.groups.len(), 1);
+        assert_eq!(vr.cleaned.groups[0].title, "Valid");
+        assert!(vr.warnings.iter().any(|w| w.contains("empty group")));
+    }
+
+    #[test]
+    fn adds_missing_hunks_to_unassigned() {
+        let result = make_result(vec![make_group("G1", "Group", vec!["H1"])], vec![], vec![]);
+        let valid = ids(&["H1", "H2", "H3"]);
+        let vr = validate_analysis(&result, &valid);
+        let unassigned = &vr.cleaned.unassigned_hunk_ids;
+        assert!(unassigned.contains(&"H2".to_string()));
+        assert!(unassigned.contains(&"H3".to_string()));
+        assert!(vr.warnings.iter().any(|w| w.contains("missing")));
+    }
+
+    #[test]
+    fn removes_invalid_unassigned_ids() {
+        let result = make_result(
+            vec![make_group("G1", "Group", vec!["H1"])],
+            vec!["H99"],
+            vec![],
+        );
+        let valid = ids(&["H1"]);
+        let vr = validate_analysis(&result, &valid);
+        assert!(vr.cleaned.unassigned_hunk_ids.is_empty());
+        assert!(vr
+            .warnings
+            .iter()
+            .any(|w| w.contains("non-existent unassigned")));
+    }
+
+    #[test]
+    fn removes_duplicate_unassigned_already_in_group() {
+        let result = make_result(
+            vec![make_group("G1", "Group", vec!["H1"])],
+            vec!["H1"],
+            vec![],
+        );
+        let valid = ids(&["H1"]);
+        let vr = validate_analysis(&result, &valid);
+        assert!(vr.cleaned.unassigned_hunk_ids.is_empty());
+        assert!(vr
+            .warnings
+            .iter()
+            .any(|w| w.contains("duplicate unassigned")));
+    }
+
+    #[test]
+    fn removes_invalid_non_substantive_ids() {
+        let result = make_result(
+            vec![make_group("G1", "Group", vec!["H1"])],
+            vec![],
+            vec!["H1", "H99"],
+        );
+        let valid = ids(&["H1"]);
+        let vr = validate_analysis(&result, &valid);
+        assert_eq!(vr.cleaned.non_substantive_hunk_ids, vec!["H1"]);
+        assert!(vr.warnings.iter().any(|w| w.contains("non-substantive")));
+    }
+
+    #[test]
+    fn all_unassigned_with_no_groups() {
+        let result = make_result(vec![], vec![], vec![]);
+        let valid = ids(&["H1", "H2"]);
+        let vr = validate_analysis(&result, &valid);
+        assert!(vr.cleaned.groups.is_empty());
+        let unassigned = &vr.cleaned.unassigned_hunk_ids;
+        assert!(unassigned.contains(&"H1".to_string()));
+        assert!(unassigned.contains(&"H2".to_string()));
+    }
+
+    fn make_summary(hunk_id: &str, summary: &str) -> HunkSummary {
+        HunkSummary {
+            hunk_id: hunk_id.to_string(),
+            summary: summary.to_string(),
+        }
+    }
+
+    #[test]
+    fn summaries_full_coverage_no_warnings() {
+        let summaries = vec![
+            make_summary("H1", "Adds a check"),
+            make_summary("H2", "Renames x"),
+        ];
+        let (cleaned, warnings) = validate_summaries(summaries, &ids(&["H1", "H2"]));
+        assert_eq!(cleaned.len(), 2);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn summaries_drop_invalid_duplicate_and_empty() {
+        let summaries = vec![
+            make_summary("H1", "First"),
+            make_summary("H1", "Again"),
+            make_summary("H99", "Ghost"),
+            make_summary("H2", "  "),
+        ];
+        let (cleaned, warnings) = validate_summaries(summaries, &ids(&["H1", "H2"]));
+        assert_eq!(cleaned.len(), 1);
+        assert_eq!(cleaned[0].summary, "First");
+        assert!(warnings.iter().any(|w| w.contains("duplicate")));
+        assert!(warnings.iter().any(|w| w.contains("H99")));
+        assert!(warnings.iter().any(|w| w.contains("empty")));
+        assert!(warnings
+            .iter()
+            .any(|w| w.contains("1 hunk(s) have no summary")));
+    }
+
+    fn evidence(hunk_id: &str, reason: &str) -> RiskEvidence {
+        RiskEvidence {
+            hunk_id: hunk_id.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    #[test]
+    fn risk_evidence_outside_group_removed() {
+        let mut group = make_group("G1", "Auth", vec!["H1"]);
+        group.risk = "high".to_string();
+        group.risk_score = 80;
+        group.risk_evidence = vec![
+            evidence("H1", "Touches token check"),
+            evidence("H2", "Other"),
+        ];
+        let mut warnings = Vec::new();
+        validate_risk(&mut group, &mut warnings);
+        assert_eq!(group.risk_evidence.len(), 1);
+        assert_eq!(group.risk_evidence[0].hunk_id, "H1");
+        assert!(warnings.iter().any(|w| w.contains("'H2' not in group")));
+    }
+
+    #[test]
+    fn risk_without_evidence_warns() {
+        let mut group = make_group("G1", "Auth", vec!["H1"]);
+        group.risk = "medium".to_string();
+        group.risk_score = 50;
+        let mut warnings = Vec::new();
+        validate_risk(&mut group, &mut warnings);
+        assert!(warnings.iter().any(|w| w.contains("no evidence")));
+    }
+
+    #[test]
+    fn risk_score_mismatch_and_clamp() {
+        let mut group = make_group("G1", "Docs", vec!["H1"]);
+        group.risk_score = 150;
+        let mut warnings = Vec::new();
+        validate_risk(&mut group, &mut warnings);
+        assert_eq!(group.risk_score, 100);
+        assert!(warnings.iter().any(|w| w.contains("clamped")));
+        assert!(warnings.iter().any(|w| w.contains("does not match 'low'")));
+    }
+
+    #[test]
+    fn low_risk_with_matching_score_has_no_warnings() {
+        let mut group = make_group("G1", "Docs", vec!["H1"]);
+        let mut warnings = Vec::new();
+        validate_risk(&mut group, &mut warnings);
+        assert!(warnings.is_empty());
+    }
+
+    fn make_hunk(id: &str, file_path: &str) -> Hunk {
+        let line = |kind: &str, old_line: Option<u32>, new_line: Option<u32>| DiffLine {
+            kind: kind.to_string(),
+            old_line,
+            new_line,
+            text: String::new(),
+        };
+        Hunk {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            subproject: None,
+            header: "@@ -10,2 +10,2 @@".to_string(),
+            old_start: 10,
+            old_lines: 2,
+            new_start: 10,
+            new_lines: 2,
+            lines: vec![
+                line("context", Some(10), Some(10)),
+                line("remove", Some(11), None),
+                line("add", None, Some(11)),
+            ],
+        }
+    }
+
+    fn make_comment(hunk_id: &str, line: u32, side: &str) -> DraftComment {
+        DraftComment {
+            hunk_id: hunk_id.to_string(),
+            file_path: "src/a.rs".to_string(),
+            line,
+            side: side.to_string(),
+            body: "Consider a test".to_string(),
+            severity: "suggestion".to_string(),
+        }
+    }
+
+    #[test]
+    fn draft_comments_on_real_lines_kept() {
+        let hunk = make_hunk("H1", "src/a.rs");
+        let comments = vec![
+            make_comment("H1", 11, "RIGHT"),
+            make_comment("H1", 11, "LEFT"),
+        ];
+        let (cleaned, warnings) = validate_draft_comments(comments, &[&hunk]);
+        assert_eq!(cleaned.len(), 2);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn draft_comments_outside_hunk_removed() {
+        let hunk = make_hunk("H1", "src/a.rs");
+        let comments = vec![
+            make_comment("H1", 42, "RIGHT"),
+            make_comment("H9", 11, "RIGHT"),
+        ];
+        let (cleaned, warnings) = validate_draft_comments(comments, &[&hunk]);
+        assert!(cleaned.is_empty());
+        assert!(warnings.iter().any(|w| w.contains("line 42")));
+        assert!(warnings.iter().any(|w| w.contains("'H9'")));
+    }
+
+    #[test]
+    fn draft_comment_file_path_corrected() {
+        let hunk = make_hunk("H1", "src/real.rs");
+        let (cleaned, warnings) =
+            validate_draft_comments(vec![make_comment("H1", 10, "RIGHT")], &[&hunk]);
+        assert_eq!(cleaned[0].file_path, "src/real.rs");
+        assert!(warnings.iter().any(|w| w.contains("Corrected file path")));
+    }
+
+    fn make_split_pr(title: &str, hunk_ids: Vec<&str>, depends_on: Vec<u32>) -> SplitPr {
+        SplitPr {
+            title: title.to_string(),
+            description: String::new(),
+            hunk_ids: hunk_ids.into_iter().map(String::from).collect(),
+            depends_on,
+        }
+    }
+
+    #[test]
+    fn split_plan_cleans_ids_and_collects_unassigned() {
+        let result = SplitPlanResult {
+            rationale: "r".to_string(),
+            prs: vec![
+                make_split_pr("a", vec!["H1", "H9"], vec![]),
+                make_split_pr("b", vec!["H1", "H2"], vec![1]),
+            ],
+        };
+        let hunk_ids: Vec<String> = ["H1", "H2", "H3"].iter().map(ToString::to_string).collect();
+        let (plan, warnings) = validate_split_plan(result, &hunk_ids);
+        assert_eq!(plan.prs[0].hunk_ids, vec!["H1"]);
+        assert_eq!(plan.prs[1].hunk_ids, vec!["H2"]);
+        assert_eq!(plan.unassigned_hunk_ids, vec!["H3"]);
+        assert!(warnings.iter().any(|w| w.contains("'H9'")));
+        assert!(warnings
+            .iter()
+            .any(|w| w.contains("duplicate hunk id 'H1'")));
+    }
+
+    #[test]
+    fn split_plan_renumbers_dependencies() {
+        let result = SplitPlanResult {
+            rationale: String::new(),
+            prs: vec![
+                make_split_pr("empty", vec!["X"], vec![]),
+                make_split_pr("base", vec!["H1"], vec![]),
+                make_split_pr("top", vec!["H2"], vec![1, 2, 2, 3, 7]),
+            ],
+        };
+        let hunk_ids: Vec<String> = vec!["H1".to_string(), "H2".to_string()];
+        let (plan, warnings) = validate_split_plan(result, &hunk_ids);
+        assert_eq!(plan.prs.len(), 2);
+        assert_eq!(plan.prs[1].depends_on, vec![1]);
+        // Dependencies on the removed PR, itself, and a nonexistent PR are dropped.
+        assert_eq!(
+            warnings
+                .iter()
+                .filter(|w| w.contains("Removed dependency"))
+                .count(),
+            3
+        );
+    }
+
+    fn make_finding(group_id: &str, hunk_ids: Vec<&str>) -> BreakingChange {
+        BreakingChange {
+            group_id: group_id.to_string(),
+            hunk_ids: hunk_ids.into_iter().map(String::from).collect(),
+            kind: "symbol".to_string(),
+            subject: "fn parse".to_string(),
+            description: "Signature changed".to_string(),
+            severity: "breaking".to_string(),
+        }
+    }
+
+    #[test]
+    fn breaking_changes_keep_only_hunks_of_their_group() {
+        let groups = vec![
+            make_group("G1", "API", vec!["H1", "H2"]),
+            make_group("G2", "Docs", vec!["H3"]),
+        ];
+        let (cleaned, warnings) = validate_breaking_changes(
+            vec![
+                make_finding("G1", vec!["H1", "H3"]),
+                make_finding("G2", vec!["H1"]),
+                make_finding("G9", vec!["H1"]),
+            ],
+            &groups,
+        );
+        assert_eq!(cleaned.len(), 1);
+        assert_eq!(cleaned[0].hunk_ids, vec!["H1"]);
+        assert_eq!(warnings.len(), 4);
+    }
+
+    fn make_mismatch(kind: &str, claim: &str, group_ids: Vec<&str>) -> ConsistencyMismatch {
+        ConsistencyMismatch {
+            kind: kind.to_string(),
+            claim: claim.to_string(),
+            group_ids: group_ids.into_iter().map(String::from).collect(),
+            description: "d".to_string(),
+            severity: "critical".to_string(),
+        }
+    }
+
+    #[test]
+    fn consistency_drops_incomplete_mismatches() {
+        let groups = vec![make_group("G1", "Auth", vec!["H1"])];
+        let (cleaned, warnings) = validate_consistency(
+            vec![
+                make_mismatch("undisclosed", "", vec!["G1", "G1", "G9"]),
+                make_mismatch("undisclosed", "", vec!["G9"]),
+                make_mismatch("unfulfilled", "Adds retries", vec![]),
+                make_mismatch("contradicted", "", vec!["G1"]),
+            ],
+            &groups,
+        );
+        assert_eq!(cleaned.len(), 2);
+        assert_eq!(cleaned[0].group_ids, vec!["G1"]);
+        assert_eq!(cleaned[0].severity, "medium");
+        assert_eq!(cleaned[1].kind, "unfulfilled");
+        assert_eq!(warnings.len(), 4);
+    }
+
+    #[test]
+    fn guided_refine_renumbers_and_reports_missing_hunks() {
+        let group_hunks: Vec<String> = ["H1", "H2", "H3"].iter().map(ToString::to_string).collect();
+        let (groups, warnings, missing) = validate_guided_refine(
+            vec![
+                make_group("G2.1", "Migration", vec!["H1", "H9"]),
+                make_group("X", "Empty", vec!["H1"]),
+                make_group("G2.3", "Rest", vec!["H2"]),
+            ],
+            "G2",
+            &group_hunks,
+        );
+        let got: Vec<(&str, Vec<String>)> = groups
+            .iter()
+            .map(|g| (g.id.as_str(), g.hunk_ids.clone()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("G2.1", vec!["H1".to_string()]),
+                ("G2.2", vec!["H2".to_string()])
+            ]
+        );
+        assert_eq!(missing, vec!["H3"]);
+        assert_eq!(warnings.len(), 4);
+    }
+}
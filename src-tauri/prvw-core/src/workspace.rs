@@ -0,0 +1,437 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use crate::cache;
+use crate::error::PrvwError;
+use crate::gh::{run_gh, validate_repo};
+use crate::i18n::{t, Msg};
+use crate::local_range;
+use crate::operations;
+use crate::settings;
+use crate::types::{PrWorkspace, WorkspaceInfo};
+
+/// Checkouts are kept across runs (unlike `cache/`) so later fetches are incremental.
+const WORKSPACE_DIR: &str = "workspaces";
+
+/// Serializes git operations on the shared base clone; worktrees themselves are per PR.
+static WORKSPACE_LOCK: Mutex<()> = Mutex::new(());
+
+fn repo_dir(app_data_dir: &Path, repo: &str) -> PathBuf {
+    app_data_dir
+        .join(WORKSPACE_DIR)
+        .join(repo.replace('/', "__"))
+}
+
+/// Repo of a workspace directory name (`owner__repo`).
+fn dir_repo(name: &str) -> Option<String> {
+    let repo = name.replacen("__", "/", 1);
+    validate_repo(&repo).ok()?;
+    Some(repo)
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = operations::output(
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .env("GIT_TERMINAL_PROMPT", "0"),
+    )
+    .map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            t(Msg::GitNotInstalled, &[])
+        } else {
+            format!("Failed to execute git: {}", e)
+        }
+    })?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn path_str(path: &Path) -> Result<&str, String> {
+    path.to_str()
+        .ok_or_else(|| "Non-UTF-8 workspace path".to_string())
+}
+
+/// Shared git directory of a repo or worktree, canonicalized so checkouts of the same base
+/// compare equal.
+fn common_dir(dir: &Path) -> Option<PathBuf> {
+    if !dir.join(".git").exists() && !dir.join("HEAD").exists() {
+        // Not a repo itself; don't let git find one further up.
+        return None;
+    }
+    let dir = run_git(
+        dir,
+        &["rev-parse", "--path-format=absolute", "--git-common-dir"],
+    )
+    .ok()?;
+    std::fs::canonicalize(dir).ok()
+}
+
+/// The user's clone of `repo` set in its repo settings and the remote to fetch PRs from: the
+/// one pointing at `repo` (`upstream` in a fork clone), or `origin` when no remote is on GitHub
+/// (enterprise remotes can't be told apart). None when the clone is gone or points elsewhere.
+fn user_clone(repo: &str) -> Option<(PathBuf, String)> {
+    let settings = settings::current();
+    let path = settings::repo_settings(&settings, repo)?
+        .clone_path
+        .as_deref()?;
+    let (git_repo, root) = local_range::open(path).ok()?;
+    let remote = match local_range::remote_for(&git_repo, repo) {
+        Some(remote) => remote,
+        None if local_range::clone_remotes(&git_repo).repo.is_none() => {
+            git_repo.find_remote("origin").ok()?;
+            "origin".to_string()
+        }
+        None => return None,
+    };
+    Some((root, remote))
+}
+
+/// Check out the head of `repo#pr_number` into a worktree under
+/// `app_data_dir/workspaces/owner__repo/pr-N`, returning (checkout path, head sha).
+/// The worktree belongs to the user's clone when the repo settings name one; otherwise to a
+/// managed base clone, created once (blobless) and only fetched afterwards.
+pub fn prepare_pr_checkout(
+    app_data_dir: &Path,
+    repo: &str,
+    pr_number: u32,
+) -> Result<(PathBuf, String), String> {
+    validate_repo(repo)?;
+    let _guard = WORKSPACE_LOCK
+        .lock()
+        .map_err(|_| "Workspace lock poisoned".to_string())?;
+
+    let root = repo_dir(app_data_dir, repo);
+    std::fs::create_dir_all(&root)
+        .map_err(|e| format!("Failed to create workspace directory: {}", e))?;
+    let (base, remote) =
+        user_clone(repo).unwrap_or_else(|| (root.join("base"), "origin".to_string()));
+    if base.starts_with(&root) && !base.join("HEAD").exists() {
+        if base.exists() {
+            std::fs::remove_dir_all(&base)
+                .map_err(|e| format!("Failed to reset workspace: {}", e))?;
+        }
+        run_gh(
+            &[
+                "repo",
+                "clone",
+                repo,
+                path_str(&base)?,
+                "--",
+                "--bare",
+                "--filter=blob:none",
+            ],
+            "gh repo clone",
+        )?;
+    }
+
+    let pr_ref = format!("refs/prvw/pr-{}", pr_number);
+    run_git(
+        &base,
+        &[
+            "fetch",
+            "--force",
+            &remote,
+            &format!("+refs/pull/{}/head:{}", pr_number, pr_ref),
+        ],
+    )?;
+
+    let checkout = root.join(format!("pr-{}", pr_number));
+    // A checkout of the other base (the clone setting changed since) is recreated.
+    if checkout.exists() && common_dir(&checkout) != common_dir(&base) {
+        remove_checkout(&root, pr_number)?;
+    }
+    if checkout.join(".git").exists() {
+        run_git(&checkout, &["checkout", "--force", "--detach", &pr_ref])?;
+        run_git(&checkout, &["clean", "-fd"])?;
+    } else {
+        run_git(&base, &["worktree", "prune"])?;
+        run_git(
+            &base,
+            &[
+                "worktree",
+                "add",
+                "--force",
+                "--detach",
+                path_str(&checkout)?,
+                &pr_ref,
+            ],
+        )?;
+    }
+
+    let sha = run_git(&checkout, &["rev-parse", "HEAD"])?;
+    Ok((checkout, sha))
+}
+
+/// Working tree of `repo` to open files from: the PR's checkout when one exists, else the
+/// user's clone from the repo settings.
+pub fn local_root(app_data_dir: &Path, repo: &str, pr_number: Option<u32>) -> Option<PathBuf> {
+    let checkout = pr_number
+        .map(|n| repo_dir(app_data_dir, repo).join(format!("pr-{}", n)))
+        .filter(|dir| dir.join(".git").exists());
+    checkout.or_else(|| {
+        let settings = settings::current();
+        let path = settings::repo_settings(&settings, repo)?
+            .clone_path
+            .as_deref()?;
+        local_range::open(path).ok().map(|(_, root)| root)
+    })
+}
+
+/// Remove one PR's worktree and its fetched ref from the clone it belongs to (the managed one
+/// or the user's). Returns whether there was one.
+fn remove_checkout(root: &Path, pr_number: u32) -> Result<bool, String> {
+    let checkout = root.join(format!("pr-{}", pr_number));
+    if !checkout.exists() {
+        return Ok(false);
+    }
+    let managed = root.join("base");
+    let base = common_dir(&checkout).or_else(|| managed.join("HEAD").exists().then_some(managed));
+    if let Some(base) = &base {
+        // Best effort: deleting the directory below and pruning has the same end result.
+        let _ = run_git(
+            base,
+            &["worktree", "remove", "--force", path_str(&checkout)?],
+        );
+        let _ = run_git(
+            base,
+            &["update-ref", "-d", &format!("refs/prvw/pr-{}", pr_number)],
+        );
+    }
+    if checkout.exists() {
+        std::fs::remove_dir_all(&checkout)
+            .map_err(|e| format!("Failed to remove {}: {}", checkout.display(), e))?;
+    }
+    if let Some(base) = &base {
+        let _ = run_git(base, &["worktree", "prune"]);
+    }
+    Ok(true)
+}
+
+/// PR numbers of the checkouts under a repo's workspace directory, ascending.
+fn checkout_numbers(root: &Path) -> Vec<u32> {
+    let mut numbers: Vec<u32> = std::fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter_map(|e| e.file_name().to_str()?.strip_prefix("pr-")?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    numbers.sort_unstable();
+    numbers
+}
+
+/// Remove the checkout of one PR, every checkout of `repo` (with its base clone), or all
+/// workspaces. Returns the number of PR checkouts removed.
+fn cleanup(app_data_dir: &Path, repo: Option<&str>, pr_number: Option<u32>) -> Result<u32, String> {
+    let _guard = WORKSPACE_LOCK
+        .lock()
+        .map_err(|_| "Workspace lock poisoned".to_string())?;
+    let roots: Vec<PathBuf> = match repo {
+        Some(repo) => {
+            validate_repo(repo)?;
+            vec![repo_dir(app_data_dir, repo)]
+        }
+        None => std::fs::read_dir(app_data_dir.join(WORKSPACE_DIR))
+            .map(|entries| entries.filter_map(Result::ok).map(|e| e.path()).collect())
+            .unwrap_or_default(),
+    };
+    let mut removed = 0;
+    for root in roots.iter().filter(|r| r.is_dir()) {
+        if let Some(pr_number) = pr_number {
+            removed += u32::from(remove_checkout(root, pr_number)?);
+        } else {
+            // Worktrees of the user's clone are unregistered from it, not just deleted.
+            for pr_number in checkout_numbers(root) {
+                removed += u32::from(remove_checkout(root, pr_number)?);
+            }
+            std::fs::remove_dir_all(root)
+                .map_err(|e| format!("Failed to remove {}: {}", root.display(), e))?;
+        }
+    }
+    Ok(removed)
+}
+
+/// Every PR checkout on disk, by repo and PR number.
+pub fn list(app_data_dir: &Path) -> Vec<WorkspaceInfo> {
+    let mut workspaces = Vec::new();
+    let Ok(entries) = std::fs::read_dir(app_data_dir.join(WORKSPACE_DIR)) else {
+        return workspaces;
+    };
+    let mut roots: Vec<(String, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|e| Some((dir_repo(e.file_name().to_str()?)?, e.path())))
+        .collect();
+    roots.sort();
+    for (repo, root) in roots {
+        let managed = std::fs::canonicalize(root.join("base")).ok();
+        for pr_number in checkout_numbers(&root) {
+            let path = root.join(format!("pr-{}", pr_number));
+            let head_sha = path
+                .join(".git")
+                .exists()
+                .then(|| run_git(&path, &["rev-parse", "HEAD"]).ok())
+                .flatten();
+            let clone_path = common_dir(&path)
+                .filter(|dir| Some(dir) != managed.as_ref())
+                .map(|dir| match dir.parent() {
+                    Some(work_tree) if dir.ends_with(".git") => work_tree.display().to_string(),
+                    _ => dir.display().to_string(),
+                });
+            let modified_at = std::fs::metadata(&path)
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|age| age.as_secs());
+            workspaces.push(WorkspaceInfo {
+                repo: repo.clone(),
+                pr_number,
+                path: path.display().to_string(),
+                head_sha,
+                clone_path,
+                size_bytes: cache::dir_size(&path),
+                modified_at,
+            });
+        }
+    }
+    workspaces
+}
+
+/// One `git grep` hit: (path, line number, line text).
+pub type GrepHit = (String, u32, String);
+
+/// Run `git grep` for an extended regex in a checkout, returning at most `max` hits.
+/// No match is an empty result, not an error.
+pub fn grep_checkout(checkout: &Path, pattern: &str, max: usize) -> Result<Vec<GrepHit>, String> {
+    let output = operations::output(Command::new("git").arg("-C").arg(checkout).args([
+        "grep",
+        "-n",
+        "-I",
+        "--no-color",
+        "-E",
+        "-e",
+        pattern,
+    ]))
+    .map_err(|e| format!("Failed to execute git grep: {}", e))?;
+    match output.status.code() {
+        Some(0) => {}
+        Some(1) => return Ok(Vec::new()),
+        _ => {
+            return Err(format!(
+                "git grep failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (path, rest) = line.split_once(':')?;
+            let (num, text) = rest.split_once(':')?;
+            Some((path.to_string(), num.parse().ok()?, text.to_string()))
+        })
+        .take(max)
+        .collect())
+}
+
+/// `prepare_pr_checkout`, described for the frontend.
+pub fn checkout(app_data_dir: &Path, repo: &str, pr_number: u32) -> Result<PrWorkspace, PrvwError> {
+    let repo = repo.trim();
+    let (path, head_sha) = prepare_pr_checkout(app_data_dir, repo, pr_number)?;
+    Ok(PrWorkspace {
+        repo: repo.to_string(),
+        pr_number,
+        path: path.display().to_string(),
+        head_sha,
+    })
+}
+
+/// `cleanup` with `repo` trimmed; a PR number needs its repo.
+pub fn remove(
+    app_data_dir: &Path,
+    repo: Option<&str>,
+    pr_number: Option<u32>,
+) -> Result<u32, PrvwError> {
+    let repo = repo.map(str::trim).filter(|r| !r.is_empty());
+    if pr_number.is_some() && repo.is_none() {
+        return Err(PrvwError::InvalidInput(
+            "A PR number needs its repo.".to_string(),
+        ));
+    }
+    Ok(cleanup(app_data_dir, repo, pr_number)?)
+}
+
+/// Disk usage of all workspaces (checkouts and managed base clones), human-readable.
+pub fn size(app_data_dir: &Path) -> String {
+    cache::format_bytes(cache::dir_size(&app_data_dir.join(WORKSPACE_DIR)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_dir_flattens_owner() {
+        let dir = repo_dir(Path::new("/data"), "owner/repo");
+        assert_eq!(dir, Path::new("/data/workspaces/owner__repo"));
+    }
+
+    #[test]
+    fn dir_repo_restores_owner() {
+        assert_eq!(dir_repo("owner__repo").as_deref(), Some("owner/repo"));
+        assert_eq!(
+            dir_repo("owner__my__repo").as_deref(),
+            Some("owner/my__repo")
+        );
+        assert_eq!(dir_repo("stray"), None);
+    }
+
+    #[test]
+    fn lists_checkouts() {
+        let tmp = tempfile::tempdir().unwrap();
+        for dir in [
+            "o__r/base",
+            "o__r/pr-10",
+            "o__r/pr-2",
+            "o__r/notes",
+            "stray",
+        ] {
+            std::fs::create_dir_all(tmp.path().join(WORKSPACE_DIR).join(dir)).unwrap();
+        }
+        std::fs::write(repo_dir(tmp.path(), "o/r").join("pr-2/a.txt"), "hello").unwrap();
+
+        let workspaces = list(tmp.path());
+        let numbers: Vec<u32> = workspaces.iter().map(|w| w.pr_number).collect();
+        assert_eq!(numbers, vec![2, 10]);
+        assert_eq!(workspaces[0].repo, "o/r");
+        assert_eq!(workspaces[0].size_bytes, 5);
+        assert_eq!(workspaces[0].head_sha, None);
+        assert_eq!(workspaces[0].clone_path, None);
+        assert!(workspaces[0].modified_at.is_some());
+    }
+
+    #[test]
+    fn cleans_up_checkouts() {
+        let tmp = tempfile::tempdir().unwrap();
+        for dir in ["o__r/base", "o__r/pr-1", "o__r/pr-2", "o__other/pr-3"] {
+            std::fs::create_dir_all(tmp.path().join(WORKSPACE_DIR).join(dir)).unwrap();
+        }
+        assert_eq!(cleanup(tmp.path(), Some("o/r"), Some(1)).unwrap(), 1);
+        assert_eq!(cleanup(tmp.path(), Some("o/r"), Some(1)).unwrap(), 0);
+        assert!(repo_dir(tmp.path(), "o/r").join("pr-2").exists());
+        assert_eq!(cleanup(tmp.path(), Some("o/r"), None).unwrap(), 1);
+        assert!(!repo_dir(tmp.path(), "o/r").exists());
+        assert_eq!(cleanup(tmp.path(), None, None).unwrap(), 1);
+        assert!(!repo_dir(tmp.path(), "o/other").exists());
+    }
+}
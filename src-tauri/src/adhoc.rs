@@ -0,0 +1,470 @@
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::cache;
+use crate::diff_parser;
+use crate::error::PrvwError;
+use crate::session::{self, SESSION_LOCK};
+use crate::time::now_secs;
+use crate::types::{AdhocReview, RangeCommit, SessionUpdate};
+
+/// Owner part of the repo key of reviews without a forge; sessions live under `local/<name>`.
+pub const LOCAL_OWNER: &str = "local";
+/// Patch files larger than this are refused rather than parsed.
+const MAX_PATCH_BYTES: u64 = 50 * 1024 * 1024;
+/// Pasted diffs larger than this are refused; anything bigger belongs in a file.
+const MAX_PASTE_BYTES: usize = 10 * 1024 * 1024;
+/// Downloads are cut off (and refused) past the patch file limit.
+const MAX_DOWNLOAD_BYTES: u64 = MAX_PATCH_BYTES;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+/// Name of a pasted diff given none, and the file path of a snippet without file headers.
+const PASTED_NAME: &str = "pasted";
+
+/// Repo key for an ad-hoc review named `name`: `local/<name>` with unsafe characters replaced.
+fn local_repo(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug.trim_matches('-');
+    format!(
+        "{}/{}",
+        LOCAL_OWNER,
+        if slug.is_empty() { "diff" } else { slug }
+    )
+}
+
+/// Stand-in PR number derived from the diff content, so reopening the same diff resumes its
+/// session and a different diff under the same name gets a new one.
+fn local_number(stamp: &str) -> u32 {
+    stamp
+        .get(..7)
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .unwrap_or_default()
+}
+
+/// Commits of a `git format-patch` series: the `From <sha>` line and `Subject:` header of each
+/// mail, with the `[PATCH n/m]` prefix dropped. Empty for a plain diff.
+fn parse_series(text: &str) -> Vec<RangeCommit> {
+    let mut commits: Vec<RangeCommit> = Vec::new();
+    let mut in_subject = false;
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("From ") {
+            let sha = rest.split_whitespace().next().unwrap_or_default();
+            if sha.len() == 40 && sha.chars().all(|c| c.is_ascii_hexdigit()) {
+                commits.push(RangeCommit {
+                    sha: sha.to_string(),
+                    subject: String::new(),
+                });
+            }
+            in_subject = false;
+            continue;
+        }
+        let Some(commit) = commits.last_mut() else {
+            continue;
+        };
+        if let Some(subject) = line.strip_prefix("Subject: ") {
+            if commit.subject.is_empty() {
+                let subject = subject.trim();
+                let subject = match subject.strip_prefix("[PATCH") {
+                    Some(rest) => rest.split_once(']').map_or(subject, |(_, s)| s),
+                    None => subject,
+                };
+                commit.subject = subject.trim().to_string();
+                in_subject = true;
+            }
+        } else if in_subject && line.starts_with([' ', '\t']) {
+            // Folded header line.
+            commit.subject.push(' ');
+            commit.subject.push_str(line.trim());
+        } else {
+            in_subject = false;
+        }
+    }
+    commits
+}
+
+/// Commit subjects as a Markdown list, standing in for the PR description.
+fn describe(name: &str, commits: &[RangeCommit]) -> String {
+    let mut md = format!("Changes in `{}`", name);
+    if commits.is_empty() {
+        md.push('\n');
+        return md;
+    }
+    md.push_str(":\n\n");
+    for commit in commits {
+        md.push_str(&format!(
+            "- {} ({})\n",
+            commit.subject,
+            commit.sha.get(..7).unwrap_or(&commit.sha)
+        ));
+    }
+    md
+}
+
+/// Clean up diff text pasted from chat or piped in: CRLF line endings, prose around a Markdown
+/// code fence, and a snippet of bare hunks without file headers. Errors when there is no hunk.
+fn clean_pasted(text: &str) -> Result<String, String> {
+    let text = text.replace("\r\n", "\n");
+    let mut lines: Vec<&str> = text.lines().collect();
+    let is_fence = |l: &&str| l.trim_start().starts_with("```");
+    // The first fenced block holding a hunk, if the diff came in one.
+    let mut start = 0;
+    while let Some(open) = lines[start..].iter().position(is_fence).map(|i| start + i) {
+        let close = lines[open + 1..]
+            .iter()
+            .position(is_fence)
+            .map_or(lines.len(), |i| open + 1 + i);
+        if lines[open + 1..close].iter().any(|l| l.starts_with("@@ -")) {
+            lines = lines[open + 1..close].to_vec();
+            break;
+        }
+        start = (close + 1).min(lines.len());
+    }
+    let Some(first_hunk) = lines.iter().position(|l| l.starts_with("@@ -")) else {
+        return Err("The text does not look like a unified diff (no @@ hunk headers).".to_string());
+    };
+    let mut diff = String::new();
+    if !lines[..first_hunk].iter().any(|l| l.starts_with("+++ ")) {
+        diff.push_str(&format!("--- a/{name}\n+++ b/{name}\n", name = PASTED_NAME));
+    }
+    for line in lines {
+        diff.push_str(line);
+        diff.push('\n');
+    }
+    Ok(diff)
+}
+
+/// Whether a response of this Content-Type can hold a diff. Servers label patches loosely
+/// (`text/x-diff`, `text/plain`, `application/octet-stream` for CI artifacts), so only types
+/// that certainly aren't one, like web pages, JSON or images, are refused.
+fn is_diff_content_type(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match mime.as_str() {
+        "" | "application/octet-stream" => true,
+        "text/html" => false,
+        _ => {
+            mime.starts_with("text/")
+                || mime.starts_with("application/")
+                    && (mime.contains("diff") || mime.contains("patch"))
+        }
+    }
+}
+
+/// Session name of a downloaded diff: the last few path segments of its URL
+/// (`https://github.com/o/r/pull/12.diff` becomes `o-r-pull-12.diff`).
+fn url_name(url: &str) -> String {
+    let path = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').skip(1).filter(|s| !s.is_empty()).collect();
+    let name = segments[segments.len().saturating_sub(4)..].join("-");
+    if name.is_empty() {
+        path.to_string()
+    } else {
+        name
+    }
+}
+
+/// Download a diff, refusing anything that isn't text or is over the size limit.
+fn download_diff(url: &str) -> Result<String, PrvwError> {
+    let agent = ureq::AgentBuilder::new().timeout(DOWNLOAD_TIMEOUT).build();
+    let response = match agent
+        .get(url)
+        .set(
+            "Accept",
+            "text/x-diff, text/x-patch, text/plain;q=0.9, */*;q=0.1",
+        )
+        .call()
+    {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => {
+            return Err(PrvwError::NotFound(format!("{} was not found.", url)))
+        }
+        Err(ureq::Error::Status(code, _)) => {
+            return Err(format!("Downloading {} failed with HTTP {}", url, code).into())
+        }
+        Err(e) => {
+            return Err(PrvwError::Network(format!(
+                "Failed to download {}: {}",
+                url, e
+            )))
+        }
+    };
+    let content_type = response.header("Content-Type").unwrap_or_default();
+    if !is_diff_content_type(content_type) {
+        return Err(PrvwError::InvalidInput(format!(
+            "{} is not a diff (Content-Type: {}).",
+            url, content_type
+        )));
+    }
+    let too_large = || {
+        PrvwError::InvalidInput(format!(
+            "The diff at {} is too large to review (over {} MB).",
+            url,
+            MAX_DOWNLOAD_BYTES / (1024 * 1024)
+        ))
+    };
+    if response
+        .header("Content-Length")
+        .and_then(|len| len.parse::<u64>().ok())
+        .is_some_and(|len| len > MAX_DOWNLOAD_BYTES)
+    {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_DOWNLOAD_BYTES + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| PrvwError::Network(format!("Failed to download {}: {}", url, e)))?;
+    if body.len() as u64 > MAX_DOWNLOAD_BYTES {
+        return Err(too_large());
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Parse a diff that never touched a forge and open (or resume) its review session.
+pub fn open_adhoc(app_data_dir: &Path, name: &str, text: String) -> Result<AdhocReview, String> {
+    let stamp = cache::hash_key(&text);
+    let repo = local_repo(name);
+    let pr_number = local_number(&stamp);
+    let commits = parse_series(&text);
+    let title = match commits.as_slice() {
+        [only] if !only.subject.is_empty() => only.subject.clone(),
+        _ => name.trim().to_string(),
+    };
+    let description = describe(name.trim(), &commits);
+
+    let diff = diff_parser::parse(text, Some(repo.clone()))?;
+    if diff.hunks.is_empty() {
+        return Err(format!("No changes found in '{}'.", name.trim()));
+    }
+
+    let _guard = SESSION_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut session = session::load(app_data_dir, &repo, pr_number);
+    if session.updated_at == 0 {
+        session::apply_update(
+            &mut session,
+            &SessionUpdate {
+                head_sha: Some(stamp),
+                ..SessionUpdate::default()
+            },
+            now_secs(),
+        );
+        session::save(app_data_dir, &session)?;
+    }
+
+    Ok(AdhocReview {
+        repo,
+        pr_number,
+        title,
+        description,
+        commits,
+        diff,
+        session,
+    })
+}
+
+/// Review a local `.patch`/`.diff` file, including a multi-commit `git format-patch` series.
+/// The returned `repo`/`prNumber` address its session in the regular session commands.
+#[tauri::command]
+pub async fn open_patch_file(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<AdhocReview, PrvwError> {
+    let path = Path::new(path.trim());
+    let meta =
+        std::fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if !meta.is_file() {
+        return Err(PrvwError::InvalidInput(format!(
+            "Not a file: {}",
+            path.display()
+        )));
+    }
+    if meta.len() > MAX_PATCH_BYTES {
+        return Err(PrvwError::InvalidInput(format!(
+            "{} is too large to review ({} MB).",
+            path.display(),
+            meta.len() / (1024 * 1024)
+        )));
+    }
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(open_adhoc(
+        &app_data_dir,
+        &name,
+        String::from_utf8_lossy(&bytes).into_owned(),
+    )?)
+}
+
+/// Review raw diff text pasted from the clipboard (or piped in) in its own session. `name`
+/// keys the session; "pasted" when unset.
+#[tauri::command]
+pub async fn open_diff_text(
+    app: tauri::AppHandle,
+    text: String,
+    name: Option<String>,
+) -> Result<AdhocReview, PrvwError> {
+    if text.len() > MAX_PASTE_BYTES {
+        return Err(PrvwError::InvalidInput(
+            "The pasted diff is too large. Save it to a file and open that.".to_string(),
+        ));
+    }
+    let diff = clean_pasted(&text)?;
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| PASTED_NAME.to_string());
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(open_adhoc(&app_data_dir, &name, diff)?)
+}
+
+/// Review a `.diff`/`.patch` downloaded from a URL (a CI artifact, `github.com/o/r/pull/N.diff`)
+/// in its own session, named after the URL.
+#[tauri::command]
+pub async fn open_diff_url(app: tauri::AppHandle, url: String) -> Result<AdhocReview, PrvwError> {
+    let url = url.trim();
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(PrvwError::InvalidInput(format!(
+            "Not an http(s) URL: {}",
+            url
+        )));
+    }
+    let text = download_diff(url)?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(open_adhoc(&app_data_dir, &url_name(url), text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERIES: &str = "From 0123456789abcdef0123456789abcdef01234567 Mon Sep 17 00:00:00 2001
+From: Ada <ada@example.com>
+Date: Tue, 5 Mar 2024 12:34:56 +0000
+Subject: [PATCH 1/2] Add a parser for the config
+ file format
+
+---
+ a.rs | 1 +
+ 1 file changed, 1 insertion(+)
+
+diff --git a/a.rs b/a.rs
+--- a/a.rs
++++ b/a.rs
+@@ -1 +1,2 @@
+ one
++two
+--
+2.44.0
+
+From fedcba9876543210fedcba9876543210fedcba98 Mon Sep 17 00:00:00 2001
+From: Ada <ada@example.com>
+Subject: [PATCH 2/2] Use it
+
+diff --git a/b.rs b/b.rs
+--- a/b.rs
++++ b/b.rs
+@@ -1 +1 @@
+-old
++new
+";
+
+    #[test]
+    fn parses_format_patch_series() {
+        let commits = parse_series(SERIES);
+        let subjects: Vec<&str> = commits.iter().map(|c| c.subject.as_str()).collect();
+        assert_eq!(
+            subjects,
+            vec!["Add a parser for the config file format", "Use it"]
+        );
+        assert_eq!(commits[1].sha, "fedcba9876543210fedcba9876543210fedcba98");
+        assert!(parse_series("diff --git a/a b/a\n").is_empty());
+    }
+
+    #[test]
+    fn opens_and_resumes_sessions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let review = open_adhoc(tmp.path(), "fix parser.patch", SERIES.to_string()).unwrap();
+        assert_eq!(review.repo, "local/fix-parser.patch");
+        assert_eq!(review.title, "fix parser.patch");
+        let files: Vec<&str> = review
+            .diff
+            .hunks
+            .iter()
+            .map(|h| h.file_path.as_str())
+            .collect();
+        assert_eq!(files, vec!["a.rs", "b.rs"]);
+        assert!(review.session.updated_at > 0);
+
+        let again = open_adhoc(tmp.path(), "fix parser.patch", SERIES.to_string()).unwrap();
+        assert_eq!(again.pr_number, review.pr_number);
+        assert_eq!(again.session.updated_at, review.session.updated_at);
+        assert!(open_adhoc(tmp.path(), "empty.diff", "hello\n".to_string()).is_err());
+    }
+
+    #[test]
+    fn checks_downloads() {
+        assert!(is_diff_content_type("text/plain; charset=utf-8"));
+        assert!(is_diff_content_type("text/x-diff"));
+        assert!(is_diff_content_type("application/octet-stream"));
+        assert!(is_diff_content_type("application/x-patch"));
+        assert!(!is_diff_content_type("text/html; charset=utf-8"));
+        assert!(!is_diff_content_type("application/json"));
+        assert!(!is_diff_content_type("image/png"));
+
+        assert_eq!(
+            url_name("https://github.com/o/r/pull/12.diff?full_index=1"),
+            "o-r-pull-12.diff"
+        );
+        assert_eq!(url_name("https://ci.example.com/a.patch"), "a.patch");
+    }
+
+    #[test]
+    fn cleans_pasted_diffs() {
+        let fenced = "Here you go:\r\n```diff\r\n--- a/f.rs\r\n+++ b/f.rs\r\n@@ -1 +1 @@\r\n-a\r\n+b\r\n```\r\nthanks";
+        assert_eq!(
+            clean_pasted(fenced).unwrap(),
+            "--- a/f.rs\n+++ b/f.rs\n@@ -1 +1 @@\n-a\n+b\n"
+        );
+        let plain = "--- a/f.rs\n+++ b/f.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        assert_eq!(clean_pasted(plain).unwrap(), plain);
+        let snippet =
+            clean_pasted("@@ -3,2 +3,2 @@ fn main() {\n-    old();\n+    new();\n }\n").unwrap();
+        assert!(snippet.starts_with("--- a/pasted\n+++ b/pasted\n@@ -3,2"));
+        assert!(clean_pasted("just some text\n").is_err());
+    }
+}
@@ -0,0 +1,603 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use subtle::ConstantTimeEq;
+use tauri::{Emitter, Manager};
+
+use crate::cache;
+use crate::edits;
+use crate::error::PrvwError;
+use crate::gh::{self, validate_repo};
+use crate::json_store;
+use crate::secrets::{self, Secret};
+use crate::session;
+use crate::types::{
+    AnalysisResponse, AnalysisResult, ApiServerStatus, ReviewSession, SessionUpdate,
+};
+
+/// Enabled flag and port, kept in the app data dir so the server comes back on launch. The
+/// token lives in the keyring.
+const CONFIG_FILE: &str = "api_server.json";
+pub const DEFAULT_PORT: u16 = 7733;
+
+/// Emitted with the saved `ReviewSession` when a client marks groups or hunks reviewed.
+pub const SESSION_UPDATED_EVENT: &str = "session-updated";
+
+const MAX_BODY: usize = 1024 * 1024;
+const MAX_HEADERS: usize = 100;
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often the accept loop checks whether it was stopped.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Config {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    port: Option<u16>,
+}
+
+fn config_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(CONFIG_FILE)
+}
+
+fn load_config(app_data_dir: &Path) -> Config {
+    json_store::load(&config_path(app_data_dir)).unwrap_or_default()
+}
+
+fn save_config(app_data_dir: &Path, config: &Config) -> Result<(), String> {
+    json_store::save(&config_path(app_data_dir), config, "API server config")
+}
+
+/// 128 random bits from the OS as hex.
+fn new_token() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The token, generating and storing one on first use.
+fn token() -> Result<String, PrvwError> {
+    if let Some(token) = secrets::get(Secret::ApiServer)? {
+        return Ok(token);
+    }
+    let token = new_token();
+    secrets::set(Secret::ApiServer, &token)?;
+    Ok(token)
+}
+
+struct Running {
+    port: u16,
+    stop: Arc<AtomicBool>,
+    accept_thread: std::thread::JoinHandle<()>,
+}
+
+/// The running server, if any, registered as managed state.
+#[derive(Default)]
+pub struct ApiServer {
+    running: Mutex<Option<Running>>,
+}
+
+impl ApiServer {
+    fn stop(&self) -> Result<(), String> {
+        let running = self
+            .running
+            .lock()
+            .map_err(|_| "API server lock poisoned".to_string())?
+            .take();
+        if let Some(running) = running {
+            running.stop.store(true, Ordering::Relaxed);
+            // Wait for the listener to close so the port can be bound again right away.
+            let _ = running.accept_thread.join();
+        }
+        Ok(())
+    }
+
+    /// (Re)start on `port`, serving requests that carry `token`.
+    fn start(&self, app: &tauri::AppHandle, port: u16, token: String) -> Result<(), String> {
+        self.stop()?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .map_err(|e| format!("Failed to listen on 127.0.0.1:{}: {}", port, e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure the API listener: {}", e))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let context = Arc::new(Context {
+            app: app.clone(),
+            app_data_dir: app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to get app data dir: {}", e))?,
+            token,
+        });
+        let stopped = stop.clone();
+        let accept_thread = std::thread::spawn(move || accept_loop(&listener, &stopped, &context));
+        *self
+            .running
+            .lock()
+            .map_err(|_| "API server lock poisoned".to_string())? = Some(Running {
+            port,
+            stop,
+            accept_thread,
+        });
+        Ok(())
+    }
+
+    fn status(&self, config: &Config, token: String) -> Result<ApiServerStatus, String> {
+        let running = self
+            .running
+            .lock()
+            .map_err(|_| "API server lock poisoned".to_string())?;
+        Ok(ApiServerStatus {
+            enabled: config.enabled,
+            running: running.is_some(),
+            port: running
+                .as_ref()
+                .map(|r| r.port)
+                .or(config.port)
+                .unwrap_or(DEFAULT_PORT),
+            token,
+        })
+    }
+}
+
+struct Context {
+    app: tauri::AppHandle,
+    app_data_dir: PathBuf,
+    token: String,
+}
+
+fn accept_loop(listener: &TcpListener, stop: &AtomicBool, context: &Arc<Context>) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let context = context.clone();
+                std::thread::spawn(move || handle_connection(&stream, &context));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            Err(e) => {
+                eprintln!("[api] accept failed: {}", e);
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    /// Header names lowercased.
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+/// Decode `%XX` escapes and `+` in a query component.
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => match input
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    out.push(byte);
+                    i += 2;
+                }
+                None => out.push(b'%'),
+            },
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<Request, String> {
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|e| format!("Failed to read request: {}", e))?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err("Malformed request line".to_string());
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: parse_query(query),
+        ..Request::default()
+    };
+    loop {
+        line.clear();
+        reader
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read headers: {}", e))?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if request.headers.len() >= MAX_HEADERS {
+            return Err("Too many headers".to_string());
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            request
+                .headers
+                .insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    let length = match request.headers.get("content-length") {
+        Some(len) => len
+            .parse::<usize>()
+            .map_err(|_| "Invalid Content-Length".to_string())?,
+        None => 0,
+    };
+    if length > MAX_BODY {
+        return Err("Request body too large".to_string());
+    }
+    request.body = vec![0; length];
+    reader
+        .read_exact(&mut request.body)
+        .map_err(|e| format!("Failed to read body: {}", e))?;
+    Ok(request)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+fn write_response(stream: &mut impl Write, status: u16, body: &Value) -> std::io::Result<()> {
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn error(status: u16, message: impl Into<String>) -> (u16, Value) {
+    (status, json!({ "error": message.into() }))
+}
+
+/// Browsers always send `Origin` on cross-site requests; editor extensions don't, so refusing
+/// it keeps web pages from driving the API even if they learn the port.
+fn authorize(request: &Request, token: &str) -> Result<(), (u16, Value)> {
+    if request.headers.contains_key("origin") {
+        return Err(error(403, "Browser requests are not allowed."));
+    }
+    let sent = request
+        .headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim);
+    // Constant time, so response timing doesn't reveal how much of a guess was right.
+    let matches = sent.is_some_and(|sent| bool::from(sent.as_bytes().ct_eq(token.as_bytes())));
+    if !matches {
+        return Err(error(401, "Missing or wrong bearer token."));
+    }
+    Ok(())
+}
+
+/// Analysis of a PR for `GET /v1/analysis`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PrAnalysis {
+    analysis: AnalysisResult,
+    /// The reviewer's manually edited grouping rather than the model's.
+    edited: bool,
+    session: ReviewSession,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkReviewed {
+    repo: String,
+    pr_number: u32,
+    #[serde(flatten)]
+    update: SessionUpdate,
+}
+
+fn query_repo(request: &Request) -> Result<String, (u16, Value)> {
+    let repo = request
+        .query
+        .get("repo")
+        .map(|r| r.trim().to_string())
+        .unwrap_or_default();
+    validate_repo(&repo).map_err(|e| error(400, e))?;
+    Ok(repo)
+}
+
+fn query_pr(request: &Request) -> Result<u32, (u16, Value)> {
+    request
+        .query
+        .get("pr")
+        .and_then(|pr| pr.parse().ok())
+        .ok_or_else(|| error(400, "pr must be a PR number."))
+}
+
+fn to_value<T: Serialize>(value: &T) -> (u16, Value) {
+    match serde_json::to_value(value) {
+        Ok(value) => (200, value),
+        Err(e) => error(500, format!("Failed to serialize response: {}", e)),
+    }
+}
+
+/// Answer an authorized request. `on_session` is told about sessions changed by the client.
+fn route(
+    app_data_dir: &Path,
+    request: &Request,
+    on_session: &dyn Fn(&ReviewSession),
+) -> Result<(u16, Value), (u16, Value)> {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/v1/prs") => {
+            let repo = query_repo(request)?;
+            let limit = request
+                .query
+                .get("limit")
+                .and_then(|l| l.parse().ok())
+                .unwrap_or(30);
+            let state = request.query.get("state").map_or("open", String::as_str);
+            let search = request.query.get("search").cloned();
+            let prs = gh::fetch_pr_list(&repo, limit, state, search).map_err(|e| error(500, e))?;
+            Ok(to_value(&prs))
+        }
+        ("GET", "/v1/analysis") => {
+            let repo = query_repo(request)?;
+            let pr_number = query_pr(request)?;
+            let head_sha = request.query.get("headSha").map(String::as_str);
+            let edited = edits::saved_analysis(app_data_dir, &repo, pr_number, head_sha);
+            let (analysis, edited) = match edited {
+                Some(analysis) => (analysis, true),
+                None => {
+                    let response: AnalysisResponse =
+                        cache::latest_owned(app_data_dir, "cache/analysis", &repo, pr_number)
+                            .ok_or_else(|| {
+                                error(404, "No analysis of this PR yet. Analyze it in prvw.")
+                            })?;
+                    (response.result, false)
+                }
+            };
+            Ok(to_value(&PrAnalysis {
+                analysis,
+                edited,
+                session: session::load(app_data_dir, &repo, pr_number),
+            }))
+        }
+        ("POST", "/v1/reviewed") => {
+            let mark: MarkReviewed = serde_json::from_slice(&request.body)
+                .map_err(|e| error(400, format!("Invalid body: {}", e)))?;
+            let repo = mark.repo.trim();
+            validate_repo(repo).map_err(|e| error(400, e))?;
+            let session = session::save_update(app_data_dir, repo, mark.pr_number, &mark.update)
+                .map_err(|e| error(500, e))?;
+            on_session(&session);
+            Ok(to_value(&session))
+        }
+        (_, "/v1/prs" | "/v1/analysis" | "/v1/reviewed") => Err(error(405, "Method not allowed.")),
+        _ => Err(error(404, "Not found.")),
+    }
+}
+
+fn handle_connection(stream: &TcpStream, context: &Context) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+    let mut reader = BufReader::new(stream);
+    let (status, body) = match read_request(&mut reader) {
+        Ok(request) => authorize(&request, &context.token)
+            .and_then(|()| {
+                route(&context.app_data_dir, &request, &|session| {
+                    let _ = context.app.emit(SESSION_UPDATED_EVENT, session);
+                })
+            })
+            .unwrap_or_else(|e| e),
+        Err(e) => error(400, e),
+    };
+    if let Err(e) = write_response(&mut &*stream, status, &body) {
+        eprintln!("[api] failed to write response: {}", e);
+    }
+}
+
+/// Start the server at launch when it was left enabled.
+pub fn restore(app: &tauri::AppHandle) {
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let config = load_config(&app_data_dir);
+    if !config.enabled {
+        return;
+    }
+    let token = match secrets::get(Secret::ApiServer) {
+        Ok(Some(token)) => token,
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("[api] {}", e);
+            return;
+        }
+    };
+    let port = config.port.unwrap_or(DEFAULT_PORT);
+    if let Err(e) = app.state::<ApiServer>().start(app, port, token) {
+        eprintln!("[api] {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_api_server_status(
+    app: tauri::AppHandle,
+    server: tauri::State<'_, ApiServer>,
+) -> Result<ApiServerStatus, PrvwError> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let token = secrets::get(Secret::ApiServer)?.unwrap_or_default();
+    Ok(server.status(&load_config(&app_data_dir), token)?)
+}
+
+/// Serve the editor API on 127.0.0.1 (`port`, or the last one used) and keep it enabled across
+/// launches. Requests must carry the status's token as `Authorization: Bearer <token>`.
+#[tauri::command]
+pub async fn start_api_server(
+    app: tauri::AppHandle,
+    server: tauri::State<'_, ApiServer>,
+    port: Option<u16>,
+) -> Result<ApiServerStatus, PrvwError> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let mut config = load_config(&app_data_dir);
+    let token = token()?;
+    let port = port
+        .filter(|p| *p > 0)
+        .or(config.port)
+        .unwrap_or(DEFAULT_PORT);
+    server.start(&app, port, token.clone())?;
+    config.enabled = true;
+    config.port = Some(port);
+    save_config(&app_data_dir, &config)?;
+    Ok(server.status(&config, token)?)
+}
+
+/// Stop the editor API and keep it off at the next launch.
+#[tauri::command]
+pub async fn stop_api_server(
+    app: tauri::AppHandle,
+    server: tauri::State<'_, ApiServer>,
+) -> Result<ApiServerStatus, PrvwError> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    server.stop()?;
+    let mut config = load_config(&app_data_dir);
+    if config.enabled {
+        config.enabled = false;
+        save_config(&app_data_dir, &config)?;
+    }
+    let token = secrets::get(Secret::ApiServer)?.unwrap_or_default();
+    Ok(server.status(&config, token)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(raw: &str) -> Request {
+        read_request(&mut raw.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn parses_requests() {
+        let req = request(
+            "POST /v1/reviewed?repo=o%2Fr&x=a+b HTTP/1.1\r\nAuthorization: Bearer t\r\n\
+             Content-Length: 2\r\n\r\n{}",
+        );
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.path, "/v1/reviewed");
+        assert_eq!(req.query["repo"], "o/r");
+        assert_eq!(req.query["x"], "a b");
+        assert_eq!(req.headers["authorization"], "Bearer t");
+        assert_eq!(req.body, b"{}");
+        assert!(read_request(&mut "GET\r\n\r\n".as_bytes()).is_err());
+        let huge = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        assert!(read_request(&mut huge.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn requires_token_and_no_origin() {
+        let ok = request("GET /v1/prs HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n");
+        assert!(authorize(&ok, "secret").is_ok());
+        assert_eq!(authorize(&ok, "other").unwrap_err().0, 401);
+        assert_eq!(authorize(&ok, "secret2").unwrap_err().0, 401);
+        let anonymous = request("GET /v1/prs HTTP/1.1\r\n\r\n");
+        assert_eq!(authorize(&anonymous, "secret").unwrap_err().0, 401);
+        let browser = request(
+            "GET /v1/prs HTTP/1.1\r\nAuthorization: Bearer secret\r\n\
+             Origin: https://x.test\r\n\r\n",
+        );
+        assert_eq!(authorize(&browser, "secret").unwrap_err().0, 403);
+    }
+
+    #[test]
+    fn marks_reviewed_and_reads_analysis() {
+        let tmp = tempfile::tempdir().unwrap();
+        let updated = std::cell::RefCell::new(None);
+        let on_session = |s: &ReviewSession| *updated.borrow_mut() = Some(s.clone());
+
+        let missing = request("GET /v1/analysis?repo=o/r&pr=1 HTTP/1.1\r\n\r\n");
+        assert_eq!(route(tmp.path(), &missing, &on_session).unwrap_err().0, 404);
+
+        let body = r#"{"repo":"o/r","prNumber":1,"markGroups":["G1"]}"#;
+        let mark = request(&format!(
+            "POST /v1/reviewed HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        ));
+        let (status, session) = route(tmp.path(), &mark, &on_session).unwrap();
+        assert_eq!(status, 200);
+        assert!(session["reviewedGroups"]["G1"].is_object());
+        assert_eq!(updated.borrow().as_ref().unwrap().pr_number, 1);
+        assert!(session::load(tmp.path(), "o/r", 1)
+            .reviewed_groups
+            .contains_key("G1"));
+
+        let wrong = request("GET /v1/reviewed HTTP/1.1\r\n\r\n");
+        assert_eq!(route(tmp.path(), &wrong, &on_session).unwrap_err().0, 405);
+        let bad = request("GET /v1/prs?repo=nope HTTP/1.1\r\n\r\n");
+        assert_eq!(route(tmp.path(), &bad, &on_session).unwrap_err().0, 400);
+    }
+
+    #[test]
+    fn tokens_are_random_hex() {
+        let (a, b) = (new_token(), new_token());
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+
+use crate::error::PrvwError;
+use crate::gh::{self, validate_repo};
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::session::{self, SESSION_LOCK};
+use crate::time::now_secs;
+use crate::types::{AnalysisResult, AssignmentTable, GroupAssignment, ReviewSession};
+
+/// A GitHub login, with an optional leading `@` stripped.
+fn normalize_reviewer(reviewer: &str) -> Result<String, PrvwError> {
+    let login = reviewer.trim().trim_start_matches('@');
+    let valid = !login.is_empty()
+        && login.len() <= 39
+        && !login.starts_with('-')
+        && login.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !valid {
+        return Err(PrvwError::InvalidInput(format!(
+            "Invalid reviewer '{}'",
+            reviewer.trim()
+        )));
+    }
+    Ok(login.to_string())
+}
+
+/// Assign groups to reviewers (replacing earlier assignments) and drop the ones in `unassign`.
+fn apply_assignments(
+    session: &mut ReviewSession,
+    assign: &BTreeMap<String, String>,
+    unassign: &[String],
+) -> Result<(), PrvwError> {
+    let assign = assign
+        .iter()
+        .map(|(group_id, reviewer)| Ok((group_id.clone(), normalize_reviewer(reviewer)?)))
+        .collect::<Result<Vec<_>, PrvwError>>()?;
+    session.assignments.extend(assign);
+    session
+        .assignments
+        .retain(|group_id, _| !unassign.contains(group_id));
+    Ok(())
+}
+
+/// One row per group of `analysis`, in the session's group order.
+fn assignment_rows(session: &ReviewSession, analysis: &AnalysisResult) -> Vec<GroupAssignment> {
+    let mut analysis = analysis.clone();
+    session::apply_group_order(&mut analysis, session);
+    analysis
+        .groups
+        .iter()
+        .map(|g| GroupAssignment {
+            group_id: g.id.clone(),
+            title: g.title.clone(),
+            risk: g.risk.clone(),
+            risk_score: g.risk_score,
+            hunk_count: g.hunk_ids.len(),
+            reviewer: session.assignments.get(&g.id).cloned(),
+        })
+        .collect()
+}
+
+/// Markdown table of the assignments, followed by the groups each reviewer owns.
+fn render_assignments_markdown(rows: &[GroupAssignment]) -> String {
+    let mut md = String::from("## Review assignments\n\n");
+    md.push_str("| Group | Risk | Hunks | Reviewer |\n|---|---|---|---|\n");
+    for row in rows {
+        md.push_str(&format!(
+            "| {}. {} | {} ({}/100) | {} | {} |\n",
+            row.group_id,
+            row.title.replace('|', "\\|"),
+            row.risk,
+            row.risk_score,
+            row.hunk_count,
+            row.reviewer
+                .as_ref()
+                .map_or_else(|| "_unassigned_".to_string(), |r| format!("@{}", r))
+        ));
+    }
+
+    let mut by_reviewer: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for row in rows {
+        if let Some(reviewer) = &row.reviewer {
+            by_reviewer
+                .entry(reviewer.as_str())
+                .or_default()
+                .push(row.group_id.as_str());
+        }
+    }
+    if !by_reviewer.is_empty() {
+        md.push_str("\n**By reviewer**\n");
+        for (reviewer, groups) in by_reviewer {
+            md.push_str(&format!("- @{}: {}\n", reviewer, groups.join(", ")));
+        }
+    }
+    md.push_str("\n---\n_Generated with prvw_\n");
+    md
+}
+
+fn table(
+    app: &tauri::AppHandle,
+    repo: &str,
+    pr_number: u32,
+    analysis: &AnalysisResult,
+) -> Result<AssignmentTable, String> {
+    use tauri::Manager;
+    validate_repo(repo)?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let rows = assignment_rows(&session::load(&app_data_dir, repo, pr_number), analysis);
+    let markdown = render_assignments_markdown(&rows);
+    Ok(AssignmentTable { rows, markdown })
+}
+
+/// Assign intent groups to reviewers (group id -> GitHub login) and/or unassign groups, for
+/// splitting the review of a large PR. Returns the saved session.
+#[tauri::command]
+pub async fn assign_groups(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    assignments: BTreeMap<String, String>,
+    unassign: Option<Vec<String>>,
+) -> Result<ReviewSession, PrvwError> {
+    use tauri::Manager;
+    let repo = repo.trim();
+    validate_repo(repo)?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let _guard = SESSION_LOCK
+        .lock()
+        .map_err(|_| "Session lock poisoned".to_string())?;
+    let mut session = session::load(&app_data_dir, repo, pr_number);
+    apply_assignments(
+        &mut session,
+        &assignments,
+        unassign.as_deref().unwrap_or_default(),
+    )?;
+    session.updated_at = now_secs();
+    session::save(&app_data_dir, &session)?;
+    Ok(session)
+}
+
+/// The assignment table of every group in the analysis, for export or handoff.
+#[tauri::command]
+pub async fn get_assignments(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    analysis: AnalysisResult,
+) -> Result<AssignmentTable, PrvwError> {
+    Ok(table(&app, repo.trim(), pr_number, &analysis)?)
+}
+
+/// Post the assignment table as a PR comment. Returns the comment URL.
+#[tauri::command]
+pub async fn post_assignments(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    analysis: AnalysisResult,
+    op_id: Option<String>,
+) -> Result<String, PrvwError> {
+    let op = Op::start(op_id, "post_assignments");
+    let result: Result<String, PrvwError> = op
+        .scope(async {
+            let repo = repo.trim();
+            let table = table(&app, repo, pr_number, &analysis)?;
+            if table.rows.iter().all(|r| r.reviewer.is_none()) {
+                return Err(PrvwError::InvalidInput(
+                    "No groups are assigned to a reviewer.".to_string(),
+                ));
+            }
+            op.progress(t(Msg::Posting, &[&repo, &pr_number]));
+            gh::post_body(repo, pr_number, &table.markdown, false)
+        })
+        .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::IntentGroup;
+
+    fn group(id: &str, title: &str, hunks: usize) -> IntentGroup {
+        IntentGroup {
+            id: id.to_string(),
+            title: title.to_string(),
+            category: String::new(),
+            rationale: String::new(),
+            risk: "low".to_string(),
+            risk_score: 10,
+            risk_evidence: vec![],
+            hunk_ids: (0..hunks).map(|i| format!("H{}", i)).collect(),
+            reviewer_checklist: vec![],
+            suggested_tests: vec![],
+        }
+    }
+
+    #[test]
+    fn normalizes_and_validates_reviewers() {
+        assert_eq!(normalize_reviewer(" @alice-b ").unwrap(), "alice-b");
+        assert!(normalize_reviewer("@").is_err());
+        assert!(normalize_reviewer("-alice").is_err());
+        assert!(normalize_reviewer("alice bob").is_err());
+    }
+
+    #[test]
+    fn assigns_and_renders_table() {
+        let analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![
+                group("G1", "Auth | tokens", 2),
+                group("G2", "Docs", 1),
+                group("G3", "Cleanup", 3),
+            ],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        };
+        let mut session = ReviewSession::default();
+        let assign = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+            pairs
+                .iter()
+                .map(|(g, r)| (g.to_string(), r.to_string()))
+                .collect()
+        };
+        apply_assignments(
+            &mut session,
+            &assign(&[("G1", "@alice"), ("G2", "bob"), ("G3", "alice")]),
+            &[],
+        )
+        .unwrap();
+        apply_assignments(&mut session, &BTreeMap::new(), &["G2".to_string()]).unwrap();
+        assert!(apply_assignments(&mut session, &assign(&[("G2", "not valid")]), &[]).is_err());
+
+        let rows = assignment_rows(&session, &analysis);
+        assert_eq!(rows[0].reviewer.as_deref(), Some("alice"));
+        assert_eq!(rows[1].reviewer, None);
+
+        let md = render_assignments_markdown(&rows);
+        assert!(md.contains("| G1. Auth \\| tokens | low (10/100) | 2 | @alice |"));
+        assert!(md.contains("| G2. Docs | low (10/100) | 1 | _unassigned_ |"));
+        assert!(md.contains("- @alice: G1, G3"));
+    }
+}
@@ -0,0 +1,476 @@
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use base64::Engine as _;
+use serde::Deserialize;
+
+use crate::cache;
+use crate::error::PrvwError;
+use crate::gh::{diff_cache_key, validate_repo};
+use crate::i18n::{t, Msg};
+use crate::secrets::{self, Secret};
+use crate::settings;
+use crate::types::{PrAuthor, PrListItem, RepoContext};
+
+const API_VERSION: &str = "7.0";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Page size for change lists; the API's own maximum.
+const CHANGES_PAGE_LEN: u32 = 2000;
+/// Files fetched for one diff; each costs two blob requests.
+const MAX_FILES: usize = 500;
+
+/// The `Authorization` header for the stored personal access token (an empty user name).
+fn auth_header() -> Result<String, PrvwError> {
+    let pat = secrets::require(Secret::AzureDevOps)?;
+    Ok(format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(format!(":{}", pat))
+    ))
+}
+
+fn org_url() -> Result<String, String> {
+    settings::current()
+        .azure_devops_url
+        .ok_or_else(|| "Azure DevOps organization URL is not set. Add it in Settings.".to_string())
+}
+
+/// Git API URL of `path` in `repo` (`project/repository`).
+fn repo_api_url(repo: &str, path: &str) -> Result<String, String> {
+    let (project, name) = repo.split_once('/').unwrap_or((repo, repo));
+    Ok(format!(
+        "{}/{}/_apis/git/repositories/{}{}",
+        org_url()?,
+        project,
+        name,
+        path
+    ))
+}
+
+/// GET an API URL, returning the response body as bytes.
+fn get(url: &str, query: &[(&str, &str)]) -> Result<Vec<u8>, String> {
+    let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+    let mut request = agent
+        .get(url)
+        .set("Authorization", &auth_header()?)
+        .query("api-version", API_VERSION);
+    for (key, value) in query {
+        request = request.query(key, value);
+    }
+    match request.call() {
+        Ok(resp) => {
+            let mut body = Vec::new();
+            resp.into_reader()
+                .read_to_end(&mut body)
+                .map_err(|e| format!("Failed to read Azure DevOps response: {}", e))?;
+            Ok(body)
+        }
+        // A rejected token gets a sign-in page (203) or 401.
+        Err(ureq::Error::Status(401 | 403, _)) => Err(
+            "Azure DevOps rejected the token. Check the access token and its scopes in Settings."
+                .to_string(),
+        ),
+        Err(ureq::Error::Status(404, _)) => {
+            Err("Not found on Azure DevOps. Check the project/repository name.".to_string())
+        }
+        Err(ureq::Error::Status(code, resp)) => Err(format!(
+            "Azure DevOps API returned HTTP {}: {}",
+            code,
+            resp.into_string().unwrap_or_default()
+        )),
+        Err(e) => Err(format!("Azure DevOps API request failed: {}", e)),
+    }
+}
+
+fn get_json<T: serde::de::DeserializeOwned>(
+    url: &str,
+    query: &[(&str, &str)],
+) -> Result<T, String> {
+    let body = get(url, query)?;
+    serde_json::from_slice(&body).map_err(|_| {
+        "Failed to parse Azure DevOps response. Check the access token in Settings.".to_string()
+    })
+}
+
+#[derive(Deserialize)]
+struct List<T> {
+    value: Vec<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Identity {
+    unique_name: Option<String>,
+    display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitRef {
+    commit_id: String,
+}
+
+#[derive(Deserialize)]
+struct Reviewer {
+    #[serde(default)]
+    vote: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequest {
+    pull_request_id: u64,
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    creation_date: String,
+    #[serde(default)]
+    closed_date: Option<String>,
+    #[serde(default)]
+    is_draft: Option<bool>,
+    created_by: Option<Identity>,
+    #[serde(default)]
+    source_ref_name: String,
+    #[serde(default)]
+    target_ref_name: String,
+    last_merge_source_commit: Option<CommitRef>,
+    #[serde(default)]
+    reviewers: Vec<Reviewer>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Iteration {
+    id: u32,
+    source_ref_commit: Option<CommitRef>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChangeItem {
+    #[serde(default)]
+    path: String,
+    object_id: Option<String>,
+    original_object_id: Option<String>,
+    #[serde(default)]
+    is_folder: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Change {
+    /// "add", "edit", "delete", "rename", or a combination like "edit, rename".
+    change_type: String,
+    item: ChangeItem,
+    original_path: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Changes {
+    change_entries: Vec<Change>,
+    #[serde(default)]
+    next_skip: u32,
+    #[serde(default)]
+    next_top: u32,
+}
+
+/// Azure DevOps status for a `list_prs` state ("open", "closed", "merged" or "all").
+fn api_status(state: &str) -> Result<&'static str, String> {
+    match state {
+        "open" => Ok("active"),
+        "closed" => Ok("abandoned"),
+        "merged" => Ok("completed"),
+        "all" => Ok("all"),
+        _ => Err(format!("Unknown PR state '{}'", state)),
+    }
+}
+
+/// Map an Azure DevOps pull request into the GitHub-shaped list item the rest of the app uses.
+/// Reviewer votes stand in for GitHub's review decision (10/5 approve, -5/-10 wait or reject).
+fn to_list_item(org_url: &str, repo: &str, pr: PullRequest) -> PrListItem {
+    let (project, name) = repo.split_once('/').unwrap_or((repo, repo));
+    let review_decision = if pr.reviewers.iter().any(|r| r.vote < 0) {
+        "CHANGES_REQUESTED"
+    } else if pr.reviewers.iter().any(|r| r.vote > 0) {
+        "APPROVED"
+    } else {
+        "REVIEW_REQUIRED"
+    };
+    let branch = |name: &str| name.strip_prefix("refs/heads/").unwrap_or(name).to_string();
+    PrListItem {
+        number: pr.pull_request_id,
+        url: format!(
+            "{}/{}/_git/{}/pullrequest/{}",
+            org_url, project, name, pr.pull_request_id
+        ),
+        title: pr.title,
+        // The list has no update time; closing is the last change a closed PR gets.
+        updated_at: pr.closed_date.unwrap_or_else(|| pr.creation_date.clone()),
+        created_at: pr.creation_date,
+        author: pr
+            .created_by
+            .and_then(|c| c.unique_name.or(c.display_name))
+            .map(|login| PrAuthor { login }),
+        head_ref_name: Some(branch(&pr.source_ref_name)),
+        head_ref_oid: pr.last_merge_source_commit.map(|c| c.commit_id),
+        base_ref_name: Some(branch(&pr.target_ref_name)),
+        head_repository_owner: None,
+        is_cross_repository: None,
+        review_decision: Some(review_decision.to_string()),
+        is_draft: pr.is_draft,
+        body: pr.description.filter(|d| !d.trim().is_empty()),
+    }
+}
+
+/// List pull requests of `repo` (`project/repository`; a project name with spaces is written
+/// URL-encoded, `My%20Project/repo`).
+pub fn fetch_pr_list(repo: &str, limit: u32, state: &str) -> Result<Vec<PrListItem>, String> {
+    validate_repo(repo)?;
+    let org_url = org_url()?;
+    let list: List<PullRequest> = get_json(
+        &repo_api_url(repo, "/pullrequests")?,
+        &[
+            ("searchCriteria.status", api_status(state)?),
+            ("$top", &limit.to_string()),
+        ],
+    )?;
+    Ok(list
+        .value
+        .into_iter()
+        .map(|pr| to_list_item(&org_url, repo, pr))
+        .collect())
+}
+
+/// Unified diff of one file from its two versions, with `/dev/null` for an added or deleted
+/// side as `parse_diff` expects.
+fn file_patch(
+    old_path: &str,
+    old: &[u8],
+    new_path: &str,
+    new: &[u8],
+    change_type: &str,
+) -> Result<String, String> {
+    let mut patch = git2::Patch::from_buffers(
+        old,
+        Some(Path::new(old_path)),
+        new,
+        Some(Path::new(new_path)),
+        None,
+    )
+    .map_err(|e| format!("Failed to diff {}: {}", new_path, e.message()))?;
+    let buf = patch
+        .to_buf()
+        .map_err(|e| format!("Failed to diff {}: {}", new_path, e.message()))?;
+    let text = String::from_utf8_lossy(&buf).into_owned();
+    Ok(if change_type.contains("add") {
+        text.replacen(&format!("--- a/{}\n", old_path), "--- /dev/null\n", 1)
+    } else if change_type.contains("delete") {
+        text.replacen(&format!("+++ b/{}\n", new_path), "+++ /dev/null\n", 1)
+    } else {
+        text
+    })
+}
+
+fn blob(repo: &str, object_id: Option<&str>) -> Result<Vec<u8>, String> {
+    match object_id {
+        Some(id) => get(
+            &repo_api_url(repo, &format!("/blobs/{}", id))?,
+            &[("$format", "octetstream")],
+        ),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Diff of the latest iteration against the merge base, rebuilt file by file from the blobs
+/// of both sides, since the API has no unified diff.
+fn build_diff(repo: &str, pr_number: u32, iteration: u32) -> Result<String, String> {
+    let url = repo_api_url(
+        repo,
+        &format!(
+            "/pullRequests/{}/iterations/{}/changes",
+            pr_number, iteration
+        ),
+    )?;
+    let mut changes: Vec<Change> = Vec::new();
+    let mut skip = 0;
+    loop {
+        let page: Changes = get_json(
+            &url,
+            &[
+                ("$compareTo", "0"),
+                ("$top", &CHANGES_PAGE_LEN.to_string()),
+                ("$skip", &skip.to_string()),
+            ],
+        )?;
+        changes.extend(page.change_entries);
+        if page.next_top == 0 || changes.len() >= MAX_FILES {
+            break;
+        }
+        skip = page.next_skip;
+    }
+    changes.retain(|c| !c.item.is_folder);
+    changes.truncate(MAX_FILES);
+
+    let mut diff = String::new();
+    for change in &changes {
+        let kind = change.change_type.as_str();
+        let new_path = change.item.path.trim_start_matches('/');
+        let old_path = change
+            .original_path
+            .as_deref()
+            .unwrap_or(&change.item.path)
+            .trim_start_matches('/');
+        let (old_id, new_id) = if kind.contains("add") {
+            (None, change.item.object_id.as_deref())
+        } else if kind.contains("delete") {
+            let old = change.item.original_object_id.as_deref();
+            (old.or(change.item.object_id.as_deref()), None)
+        } else {
+            (
+                change.item.original_object_id.as_deref(),
+                change.item.object_id.as_deref(),
+            )
+        };
+        let old = blob(repo, old_id)?;
+        let new = blob(repo, new_id)?;
+        diff.push_str(&file_patch(old_path, &old, new_path, &new, kind)?);
+    }
+    Ok(diff)
+}
+
+/// Fetch the diff of a PR's latest iteration through the disk cache, keyed by iteration so a
+/// new push misses it.
+pub fn fetch_pr_diff(
+    app_data_dir: Option<&Path>,
+    repo: &str,
+    pr_number: u32,
+    force: bool,
+) -> Result<String, String> {
+    validate_repo(repo)?;
+    let iterations: List<Iteration> = get_json(
+        &repo_api_url(repo, &format!("/pullRequests/{}/iterations", pr_number))?,
+        &[],
+    )?;
+    let latest = iterations
+        .value
+        .into_iter()
+        .max_by_key(|i| i.id)
+        .ok_or_else(|| "The PR has no iterations.".to_string())?;
+    let version = format!(
+        "{}-{}",
+        latest.id,
+        latest
+            .source_ref_commit
+            .map(|c| c.commit_id)
+            .unwrap_or_default()
+    );
+    let cache_key = diff_cache_key(&format!("azure/{}", repo), pr_number, Some(&version));
+    if !force {
+        if let Some(dir) = app_data_dir {
+            if let Some(cached) = cache::read_cache::<String>(dir, "cache/diff", &cache_key) {
+                return Ok(cached);
+            }
+        }
+    }
+
+    let diff = build_diff(repo, pr_number, latest.id)?;
+    if diff.trim().is_empty() {
+        return Err(t(Msg::DiffEmpty, &[]));
+    }
+    if let Some(dir) = app_data_dir {
+        let owner = RepoContext {
+            repo: repo.to_string(),
+            pr_number,
+        };
+        cache::write_cache_owned(dir, "cache/diff", &cache_key, &diff, Some(&owner));
+    }
+    Ok(diff)
+}
+
+/// List pull requests of an Azure DevOps repository (`project/repository`) in the organization
+/// set in Settings, in the same shape as `list_prs`.
+#[tauri::command]
+pub async fn list_azure_devops_prs(
+    repo: String,
+    limit: u32,
+    state: String,
+) -> Result<Vec<PrListItem>, PrvwError> {
+    Ok(fetch_pr_list(repo.trim(), limit, &state)?)
+}
+
+/// The unified diff of an Azure DevOps pull request's latest iteration, for `parse_diff`.
+#[tauri::command]
+pub async fn get_azure_devops_pr_diff(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    force: Option<bool>,
+) -> Result<String, PrvwError> {
+    use tauri::Manager;
+    let app_data_dir = app.path().app_data_dir().ok();
+    Ok(fetch_pr_diff(
+        app_data_dir.as_deref(),
+        repo.trim(),
+        pr_number,
+        force == Some(true),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_parser;
+
+    #[test]
+    fn maps_pull_requests_to_list_items() {
+        let list: List<PullRequest> = serde_json::from_str(
+            r#"{"value": [{
+              "pullRequestId": 42,
+              "title": "Add parser",
+              "description": "Adds the parser.",
+              "status": "active",
+              "creationDate": "2024-03-05T12:34:56.1234567Z",
+              "isDraft": false,
+              "createdBy": {"displayName": "Ada L", "uniqueName": "ada@contoso.com"},
+              "sourceRefName": "refs/heads/feature",
+              "targetRefName": "refs/heads/main",
+              "lastMergeSourceCommit": {"commitId": "0123456789abcdef"},
+              "reviewers": [{"vote": 10}, {"vote": -5}]
+            }]}"#,
+        )
+        .unwrap();
+        let pr = list.value.into_iter().next().unwrap();
+        let item = to_list_item("https://dev.azure.com/contoso", "Proj/repo", pr);
+        assert_eq!(item.number, 42);
+        assert_eq!(
+            item.url,
+            "https://dev.azure.com/contoso/Proj/_git/repo/pullrequest/42"
+        );
+        assert_eq!(item.updated_at, "2024-03-05T12:34:56.1234567Z");
+        assert_eq!(
+            item.author.map(|a| a.login).as_deref(),
+            Some("ada@contoso.com")
+        );
+        assert_eq!(item.head_ref_name.as_deref(), Some("feature"));
+        assert_eq!(item.base_ref_name.as_deref(), Some("main"));
+        assert_eq!(item.review_decision.as_deref(), Some("CHANGES_REQUESTED"));
+    }
+
+    #[test]
+    fn rebuilds_file_patches_for_parse_diff() {
+        let edited =
+            file_patch("src/a.rs", b"one\ntwo\n", "src/a.rs", b"one\n2\n", "edit").unwrap();
+        assert!(edited.contains("--- a/src/a.rs\n+++ b/src/a.rs\n"));
+        assert!(edited.contains("-two\n+2\n"));
+        let added = file_patch("new.rs", b"", "new.rs", b"fn main() {}\n", "add").unwrap();
+        assert!(added.contains("--- /dev/null\n+++ b/new.rs\n"));
+        let deleted = file_patch("old.rs", b"gone\n", "old.rs", b"", "delete").unwrap();
+        assert!(deleted.contains("--- a/old.rs\n+++ /dev/null\n"));
+
+        let parsed = diff_parser::parse(format!("{}{}{}", edited, added, deleted), None).unwrap();
+        let files: Vec<&str> = parsed.hunks.iter().map(|h| h.file_path.as_str()).collect();
+        assert_eq!(files, vec!["src/a.rs", "new.rs", "old.rs"]);
+    }
+}
@@ -0,0 +1,326 @@
+use std::path::Path;
+use std::time::Duration;
+
+use base64::Engine as _;
+use serde::Deserialize;
+
+use crate::cache;
+use crate::error::PrvwError;
+use crate::gh::{diff_cache_key, validate_repo};
+use crate::i18n::{t, Msg};
+use crate::secrets::{self, Secret};
+use crate::types::{PrAuthor, PrListItem, RepoContext};
+
+const API_URL: &str = "https://api.bitbucket.org/2.0";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Largest page size the pull request endpoint accepts.
+const MAX_PAGE_LEN: u32 = 50;
+
+/// The `Authorization` header for the stored `username:app_password`.
+fn auth_header() -> Result<String, String> {
+    match secrets::get(Secret::Bitbucket)? {
+        Some(credentials) if credentials.contains(':') => Ok(format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        )),
+        _ => Err(t(Msg::NotSet, &[&Secret::Bitbucket.label()])),
+    }
+}
+
+/// GET a Bitbucket API URL, returning the response body.
+fn get(url: &str, query: &[(&str, &str)]) -> Result<String, String> {
+    let auth = auth_header()?;
+    // The diff endpoint redirects within api.bitbucket.org; credentials must survive that.
+    let agent = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .redirect_auth_headers(ureq::RedirectAuthHeaders::SameHost)
+        .build();
+    let mut request = agent.get(url).set("Authorization", &auth);
+    for (key, value) in query {
+        request = request.query(key, value);
+    }
+    match request.call() {
+        Ok(resp) => resp
+            .into_string()
+            .map_err(|e| format!("Failed to read Bitbucket response: {}", e)),
+        Err(ureq::Error::Status(401 | 403, _)) => Err(
+            "Bitbucket rejected the credentials. Check the username and app password in Settings."
+                .to_string(),
+        ),
+        Err(ureq::Error::Status(404, _)) => {
+            Err("Not found on Bitbucket. Check the workspace/repository name.".to_string())
+        }
+        Err(ureq::Error::Status(code, resp)) => Err(format!(
+            "Bitbucket API returned HTTP {}: {}",
+            code,
+            resp.into_string().unwrap_or_default()
+        )),
+        Err(e) => Err(format!("Bitbucket API request failed: {}", e)),
+    }
+}
+
+#[derive(Deserialize)]
+struct Page<T> {
+    values: Vec<T>,
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct User {
+    nickname: Option<String>,
+    display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Name {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Hash {
+    hash: String,
+}
+
+#[derive(Deserialize)]
+struct Endpoint {
+    branch: Option<Name>,
+    commit: Option<Hash>,
+}
+
+#[derive(Deserialize)]
+struct Href {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct Links {
+    html: Option<Href>,
+}
+
+#[derive(Deserialize)]
+struct Participant {
+    state: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PullRequest {
+    id: u64,
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    draft: Option<bool>,
+    #[serde(default)]
+    created_on: String,
+    #[serde(default)]
+    updated_on: String,
+    author: Option<User>,
+    source: Endpoint,
+    destination: Endpoint,
+    links: Links,
+    #[serde(default)]
+    participants: Vec<Participant>,
+}
+
+/// Bitbucket states for a `list_prs` state ("open", "closed", "merged" or "all").
+fn api_states(state: &str) -> Result<&'static [&'static str], String> {
+    match state {
+        "open" => Ok(&["OPEN"]),
+        "closed" => Ok(&["DECLINED", "SUPERSEDED"]),
+        "merged" => Ok(&["MERGED"]),
+        "all" => Ok(&["OPEN", "MERGED", "DECLINED", "SUPERSEDED"]),
+        _ => Err(format!("Unknown PR state '{}'", state)),
+    }
+}
+
+/// Timestamps as GitHub formats them (`2024-03-05T12:34:56Z`), so age checks work alike.
+fn github_timestamp(ts: &str) -> String {
+    match ts.split_once('.') {
+        Some((secs, _)) if ts.ends_with("+00:00") || ts.ends_with('Z') => format!("{}Z", secs),
+        _ => ts.replace("+00:00", "Z"),
+    }
+}
+
+/// Map a Bitbucket pull request into the GitHub-shaped list item the rest of the app uses.
+/// Participant votes stand in for GitHub's review decision.
+fn to_list_item(pr: PullRequest) -> PrListItem {
+    let votes = |state: &str| {
+        pr.participants
+            .iter()
+            .any(|p| p.state.as_deref() == Some(state))
+    };
+    let review_decision = if votes("changes_requested") {
+        Some("CHANGES_REQUESTED".to_string())
+    } else if votes("approved") {
+        Some("APPROVED".to_string())
+    } else {
+        Some("REVIEW_REQUIRED".to_string())
+    };
+    PrListItem {
+        number: pr.id,
+        title: pr.title,
+        url: pr.links.html.map(|l| l.href).unwrap_or_default(),
+        updated_at: github_timestamp(&pr.updated_on),
+        created_at: github_timestamp(&pr.created_on),
+        author: pr
+            .author
+            .and_then(|a| a.nickname.or(a.display_name))
+            .map(|login| PrAuthor { login }),
+        head_ref_name: pr.source.branch.map(|b| b.name),
+        // Bitbucket abbreviates commit hashes to 12 characters here.
+        head_ref_oid: pr.source.commit.map(|c| c.hash),
+        base_ref_name: pr.destination.branch.map(|b| b.name),
+        head_repository_owner: None,
+        is_cross_repository: None,
+        review_decision,
+        is_draft: pr.draft,
+        body: pr.description.filter(|d| !d.trim().is_empty()),
+    }
+}
+
+pub fn fetch_pr_list(repo: &str, limit: u32, state: &str) -> Result<Vec<PrListItem>, String> {
+    validate_repo(repo)?;
+    let page_len = limit.clamp(1, MAX_PAGE_LEN).to_string();
+    let mut query: Vec<(&str, &str)> = vec![
+        ("pagelen", page_len.as_str()),
+        ("sort", "-updated_on"),
+        ("fields", "+values.participants,+values.description"),
+    ];
+    query.extend(api_states(state)?.iter().map(|s| ("state", *s)));
+
+    let mut items: Vec<PrListItem> = Vec::new();
+    let mut next = Some(format!("{}/repositories/{}/pullrequests", API_URL, repo));
+    while let Some(url) = next.take() {
+        // `next` links already carry the query.
+        let body = if items.is_empty() {
+            get(&url, &query)?
+        } else {
+            get(&url, &[])?
+        };
+        let page: Page<PullRequest> = serde_json::from_str(&body)
+            .map_err(|e| format!("Failed to parse Bitbucket response: {}", e))?;
+        items.extend(page.values.into_iter().map(to_list_item));
+        if items.len() < limit as usize {
+            next = page.next;
+        }
+    }
+    items.truncate(limit as usize);
+    Ok(items)
+}
+
+/// Fetch a PR diff through the disk cache, keyed by `updated_at` like GitHub diffs.
+pub fn fetch_pr_diff(
+    app_data_dir: Option<&Path>,
+    repo: &str,
+    pr_number: u32,
+    updated_at: Option<&str>,
+    force: bool,
+) -> Result<String, String> {
+    validate_repo(repo)?;
+    let cache_key = diff_cache_key(&format!("bitbucket/{}", repo), pr_number, updated_at);
+    if !force {
+        if let Some(dir) = app_data_dir {
+            if let Some(cached) = cache::read_cache::<String>(dir, "cache/diff", &cache_key) {
+                return Ok(cached);
+            }
+        }
+    }
+
+    let diff = get(
+        &format!(
+            "{}/repositories/{}/pullrequests/{}/diff",
+            API_URL, repo, pr_number
+        ),
+        &[],
+    )?;
+    if diff.trim().is_empty() {
+        return Err(t(Msg::DiffEmpty, &[]));
+    }
+
+    if let Some(dir) = app_data_dir {
+        let owner = RepoContext {
+            repo: repo.to_string(),
+            pr_number,
+        };
+        cache::write_cache_owned(dir, "cache/diff", &cache_key, &diff, Some(&owner));
+    }
+    Ok(diff)
+}
+
+/// List pull requests of a Bitbucket Cloud repository (`workspace/repo_slug`), newest first,
+/// in the same shape as `list_prs`.
+#[tauri::command]
+pub async fn list_bitbucket_prs(
+    repo: String,
+    limit: u32,
+    state: String,
+) -> Result<Vec<PrListItem>, PrvwError> {
+    Ok(fetch_pr_list(repo.trim(), limit, &state)?)
+}
+
+/// The unified diff of a Bitbucket Cloud pull request, for `parse_diff`.
+#[tauri::command]
+pub async fn get_bitbucket_pr_diff(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    updated_at: Option<String>,
+    force: Option<bool>,
+) -> Result<String, PrvwError> {
+    use tauri::Manager;
+    let app_data_dir = app.path().app_data_dir().ok();
+    Ok(fetch_pr_diff(
+        app_data_dir.as_deref(),
+        repo.trim(),
+        pr_number,
+        updated_at.as_deref(),
+        force == Some(true),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_pull_requests_to_list_items() {
+        let page: Page<PullRequest> = serde_json::from_str(
+            r#"{
+              "values": [{
+                "id": 7,
+                "title": "Add parser",
+                "description": "",
+                "state": "OPEN",
+                "created_on": "2024-03-05T12:34:56.123456+00:00",
+                "updated_on": "2024-03-06T08:00:00.5+00:00",
+                "author": {"display_name": "Ada L", "nickname": "ada"},
+                "source": {"branch": {"name": "feature"}, "commit": {"hash": "0123456789ab"}},
+                "destination": {"branch": {"name": "main"}, "commit": {"hash": "ba9876543210"}},
+                "links": {"html": {"href": "https://bitbucket.org/ws/repo/pull-requests/7"}},
+                "participants": [{"state": "approved"}, {"state": null}]
+              }],
+              "next": "https://api.bitbucket.org/2.0/repositories/ws/repo/pullrequests?page=2"
+            }"#,
+        )
+        .unwrap();
+        assert!(page.next.is_some());
+        let item = to_list_item(page.values.into_iter().next().unwrap());
+        assert_eq!(item.number, 7);
+        assert_eq!(item.url, "https://bitbucket.org/ws/repo/pull-requests/7");
+        assert_eq!(item.created_at, "2024-03-05T12:34:56Z");
+        assert_eq!(item.updated_at, "2024-03-06T08:00:00Z");
+        assert_eq!(item.author.map(|a| a.login).as_deref(), Some("ada"));
+        assert_eq!(item.head_ref_name.as_deref(), Some("feature"));
+        assert_eq!(item.head_ref_oid.as_deref(), Some("0123456789ab"));
+        assert_eq!(item.base_ref_name.as_deref(), Some("main"));
+        assert_eq!(item.review_decision.as_deref(), Some("APPROVED"));
+        assert_eq!(item.body, None);
+    }
+
+    #[test]
+    fn maps_list_states() {
+        assert_eq!(api_states("open").unwrap(), ["OPEN"]);
+        assert_eq!(api_states("all").unwrap().len(), 4);
+        assert!(api_states("draft").is_err());
+    }
+}
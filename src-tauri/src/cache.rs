@@ -1,154 +1,66 @@
-use std::collections::hash_map::DefaultHasher;
-use std::fs;
-use std::hash::{Hash, Hasher};
-use std::path::Path;
+use tauri::Emitter;
 
-use serde::de::DeserializeOwned;
-use serde::Serialize;
+use crate::error::PrvwError;
 
-pub fn hash_key(input: &str) -> String {
-    let mut hasher = DefaultHasher::new();
-    input.hash(&mut hasher);
-    format!("{:016x}", hasher.finish())
-}
+/// Storage is in `prvw_core::cache`; this module adds its commands and the write-failure event.
+pub use prvw_core::cache::*;
 
-pub fn read_cache<T: DeserializeOwned>(app_data_dir: &Path, subdir: &str, key: &str) -> Option<T> {
-    let path = app_data_dir.join(subdir).join(format!("{}.json", key));
-    let data = fs::read_to_string(path).ok()?;
-    serde_json::from_str(&data).ok()
-}
+/// Event emitted when a background cache write fails; the payload is the error message.
+pub const WRITE_FAILED_EVENT: &str = "cache-write-failed";
 
-pub fn write_cache<T: Serialize>(app_data_dir: &Path, subdir: &str, key: &str, value: &T) {
-    let dir = app_data_dir.join(subdir);
-    if let Err(e) = fs::create_dir_all(&dir) {
-        eprintln!("[cache] failed to create dir {:?}: {}", dir, e);
-        return;
-    }
-    let path = dir.join(format!("{}.json", key));
-    match serde_json::to_string(value) {
-        Ok(json) => {
-            if let Err(e) = fs::write(&path, json) {
-                eprintln!("[cache] failed to write {:?}: {}", path, e);
-            }
-        }
-        Err(e) => {
-            eprintln!("[cache] failed to serialize for key {}: {}", key, e);
-        }
-    }
+/// Let background writes report failures to the frontend.
+pub fn init(app: &tauri::AppHandle) {
+    let app = app.clone();
+    on_write_error(move |message| {
+        let _ = app.emit(WRITE_FAILED_EVENT, message);
+    });
 }
 
-fn dir_size(path: &Path) -> u64 {
-    let mut total = 0;
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries.flatten() {
-            let p = entry.path();
-            if p.is_dir() {
-                total += dir_size(&p);
-            } else if let Ok(meta) = p.metadata() {
-                total += meta.len();
-            }
-        }
-    }
-    total
+#[tauri::command]
+pub async fn get_cache_size(app: tauri::AppHandle) -> Result<String, PrvwError> {
+    Ok(size(&crate::app_data_dir(&app)?))
 }
 
-fn format_bytes(bytes: u64) -> String {
-    if bytes < 1024 {
-        format!("{} B", bytes)
-    } else if bytes < 1024 * 1024 {
-        format!("{:.1} KB", bytes as f64 / 1024.0)
-    } else {
-        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
-    }
+#[tauri::command]
+pub async fn clear_cache(app: tauri::AppHandle) -> Result<String, PrvwError> {
+    clear(&crate::app_data_dir(&app)?)?;
+    Ok("Cache cleared.".to_string())
 }
 
+/// Drop the cached diffs, analyses, and refinements of one PR, or of every PR in `repo` when
+/// `pr_number` is omitted. Returns how many entries were removed.
 #[tauri::command]
-pub async fn get_cache_size(app: tauri::AppHandle) -> Result<String, String> {
-    use tauri::Manager;
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    let cache_dir = app_data_dir.join("cache");
-    if !cache_dir.exists() {
-        return Ok("0 B".to_string());
-    }
-    Ok(format_bytes(dir_size(&cache_dir)))
+pub async fn invalidate_cache(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: Option<u32>,
+) -> Result<usize, PrvwError> {
+    Ok(invalidate(
+        &crate::app_data_dir(&app)?,
+        repo.trim(),
+        pr_number,
+    )?)
 }
 
 #[tauri::command]
-pub async fn clear_cache(app: tauri::AppHandle) -> Result<String, String> {
-    use tauri::Manager;
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    let cache_dir = app_data_dir.join("cache");
-    if cache_dir.exists() {
-        fs::remove_dir_all(&cache_dir).map_err(|e| format!("Failed to clear cache: {}", e))?;
-    }
-    Ok("Cache cleared.".to_string())
+pub async fn get_cache_encryption(app: tauri::AppHandle) -> Result<bool, PrvwError> {
+    Ok(encryption_enabled(&crate::app_data_dir(&app)?))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn hash_key_is_deterministic() {
-        let a = hash_key("hello world");
-        let b = hash_key("hello world");
-        assert_eq!(a, b);
-    }
-
-    #[test]
-    fn hash_key_different_inputs_differ() {
-        let a = hash_key("input_a");
-        let b = hash_key("input_b");
-        assert_ne!(a, b);
-    }
-
-    #[test]
-    fn hash_key_is_16_hex_chars() {
-        let h = hash_key("test");
-        assert_eq!(h.len(), 16);
-        assert!(h.chars().all(|c| c.is_ascii_hexdigit()));
-    }
-
-    #[test]
-    fn format_bytes_zero() {
-        assert_eq!(format_bytes(0), "0 B");
-    }
-
-    #[test]
-    fn format_bytes_small() {
-        assert_eq!(format_bytes(512), "512 B");
-    }
-
-    #[test]
-    fn format_bytes_kilobytes() {
-        assert_eq!(format_bytes(1024), "1.0 KB");
-    }
-
-    #[test]
-    fn format_bytes_megabytes() {
-        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
-    }
+/// Turn encryption of new cache entries on or off.
+#[tauri::command]
+pub async fn set_cache_encryption(app: tauri::AppHandle, enabled: bool) -> Result<(), PrvwError> {
+    Ok(set_encryption(&crate::app_data_dir(&app)?, enabled)?)
+}
 
-    #[test]
-    fn read_write_cache_roundtrip() {
-        let tmp = tempfile::tempdir().unwrap();
-        let key = "test_key";
-        let value = serde_json::json!({"foo": "bar", "num": 42});
-        write_cache(tmp.path(), "sub", key, &value);
-        let read: Option<serde_json::Value> = read_cache(tmp.path(), "sub", key);
-        assert_eq!(read.unwrap(), value);
-    }
+/// Cache size limit in MB (0 = unlimited).
+#[tauri::command]
+pub async fn get_cache_limit(app: tauri::AppHandle) -> Result<u64, PrvwError> {
+    Ok(max_mb(&crate::app_data_dir(&app)?))
+}
 
-    #[test]
-    fn read_cache_miss_returns_none() {
-        let tmp = tempfile::tempdir().unwrap();
-        let read: Option<serde_json::Value> = read_cache(tmp.path(), "sub", "nonexistent");
-        assert!(read.is_none());
-    }
+/// Set the cache size limit in MB (0 = unlimited) and evict down to it right away.
+#[tauri::command]
+pub async fn set_cache_limit(app: tauri::AppHandle, max_mb: u64) -> Result<(), PrvwError> {
+    Ok(set_limit(&crate::app_data_dir(&app)?, max_mb)?)
 }
@@ -0,0 +1,340 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+
+use crate::cache;
+use crate::codex_runner::{self, lang_suffix};
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::history;
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::provider::{self, Job, ProviderRegistry};
+use crate::settings::RunDefaults;
+use crate::types::{AskPrResponse, AskPrResult, ChatMessage, Hunk, RunOptions};
+use crate::usage;
+
+const ASK_PR_SCHEMA: &str = include_str!("../schemas/ask_pr.json");
+
+/// Conversations are user data, not cache: they live outside `cache/` so `clear_cache` keeps them.
+/// They are written synchronously, never through the cache's write queue.
+const CHAT_DIR: &str = "chat";
+
+/// Serializes read-append-write cycles and clears of chat histories across concurrent commands.
+static CHAT_LOCK: Mutex<()> = Mutex::new(());
+
+static HUNK_REF_RE: LazyLock<regex::Regex> =
+    LazyLock::new(|| regex::Regex::new(r"\bH\d+\b").expect("invalid hunk ref regex"));
+
+fn chat_key(repo: &str, pr_number: u32) -> String {
+    format!("{}__{}", repo.replace('/', "__"), pr_number)
+}
+
+/// Append messages to the stored history, re-read under the lock so exchanges finished
+/// meanwhile by other questions are kept.
+fn append_chat(app_data_dir: &Path, key: &str, messages: Vec<ChatMessage>) -> Result<(), String> {
+    let _guard = CHAT_LOCK
+        .lock()
+        .map_err(|_| "Chat lock poisoned".to_string())?;
+    let mut history: Vec<ChatMessage> =
+        cache::read_cache(app_data_dir, CHAT_DIR, key).unwrap_or_default();
+    history.extend(messages);
+    cache::write_cache_now(app_data_dir, CHAT_DIR, key, &history)
+        .map_err(|e| format!("Failed to save chat: {}", e))
+}
+
+/// Collect the hunk IDs an answer refers to (listed explicitly or cited inline as `H12`),
+/// keeping only IDs that exist in the PR. Returns (valid ids in first-seen order, warnings).
+fn collect_hunk_refs(
+    listed: Vec<String>,
+    answer: &str,
+    valid_ids: &HashSet<String>,
+) -> (Vec<String>, Vec<String>) {
+    let mut warnings: Vec<String> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut refs: Vec<String> = Vec::new();
+
+    let inline = HUNK_REF_RE
+        .find_iter(answer)
+        .map(|m| m.as_str().to_string());
+    for id in listed.into_iter().chain(inline) {
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+        if valid_ids.contains(&id) {
+            refs.push(id);
+        } else {
+            warnings.push(format!("Answer referenced non-existent hunk id '{}'", id));
+        }
+    }
+    if refs.is_empty() {
+        warnings.push("Answer does not reference any hunk".to_string());
+    }
+    (refs, warnings)
+}
+
+fn build_ask_pr_prompt(question: &str, lang: &Option<String>) -> String {
+    format!(
+        "Read hunks.json which contains every hunk of a pull request, and history.json which contains \
+         the conversation so far between a reviewer and you. \
+         The reviewer now asks: \"{}\"\n\n\
+         Answer concisely in markdown, based only on the hunks. \
+         Cite the hunks your answer relies on inline by id (e.g. H3) and list all of them in hunkIds. \
+         Use only existing hunk ids. Do not invent ids. \
+         Use inline code (backticks) for identifiers.{}",
+        question,
+        lang_suffix(lang)
+    )
+}
+
+#[tauri::command]
+pub async fn get_pr_chat(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+) -> Result<Vec<ChatMessage>, PrvwError> {
+    use tauri::Manager;
+    validate_repo(&repo)?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let key = chat_key(&repo, pr_number);
+    Ok(cache::read_cache_async(&app_data_dir, CHAT_DIR, &key)
+        .await
+        .unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn clear_pr_chat(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+) -> Result<(), PrvwError> {
+    use tauri::Manager;
+    validate_repo(&repo)?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let path = app_data_dir
+        .join(CHAT_DIR)
+        .join(format!("{}.json", chat_key(&repo, pr_number)));
+    let _guard = CHAT_LOCK
+        .lock()
+        .map_err(|_| "Chat lock poisoned".to_string())?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to clear chat: {}", e))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn ask_about_pr(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    repo: String,
+    pr_number: u32,
+    hunks_json: String,
+    question: String,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<AskPrResponse, PrvwError> {
+    use tauri::Manager;
+    let op = Op::start(op_id, "ask_about_pr");
+    let result: Result<AskPrResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                Some(repo.as_str()),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+            validate_repo(&repo)?;
+
+            let question = question.trim().to_string();
+            if question.is_empty() {
+                return Err(PrvwError::InvalidInput("Question is empty.".to_string()));
+            }
+
+            let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+                .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+            let valid_ids: HashSet<String> = hunks.iter().map(|h| h.id.clone()).collect();
+            if valid_ids.is_empty() {
+                return Err(PrvwError::InvalidInput(
+                    "No hunks to ask about.".to_string(),
+                ));
+            }
+
+            let app_data_dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+            let key = chat_key(&repo, pr_number);
+            let history: Vec<ChatMessage> = cache::read_cache_async(&app_data_dir, CHAT_DIR, &key)
+                .await
+                .unwrap_or_default();
+
+            let (temp_dir, schema_path, output_path) =
+                codex_runner::prepare_temp_dir(&hunks_json, ASK_PR_SCHEMA, "answer.json")?;
+
+            let history_json = serde_json::to_string(&history)
+                .map_err(|e| format!("Failed to serialize chat history: {}", e))?;
+            std::fs::write(temp_dir.path().join("history.json"), history_json)
+                .map_err(|e| format!("Failed to write history.json: {}", e))?;
+
+            let prompt = build_ask_pr_prompt(&question, &lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens =
+                usage::estimate_tokens(&hunks_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path: temp_dir.path(),
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                Some(&app_data_dir),
+                "ask-pr",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read answer.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: AskPrResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse answer.json: {}", e))?;
+
+            let (hunk_ids, warnings) =
+                collect_hunk_refs(result.hunk_ids, &result.answer, &valid_ids);
+
+            let mut log = codex_runner::build_log("ask-pr", &codex_output);
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let answer = ChatMessage {
+                role: "assistant".to_string(),
+                content: result.answer,
+                hunk_ids,
+            };
+            append_chat(
+                &app_data_dir,
+                &key,
+                vec![
+                    ChatMessage {
+                        role: "user".to_string(),
+                        content: question,
+                        hunk_ids: Vec::new(),
+                    },
+                    answer.clone(),
+                ],
+            )?;
+
+            Ok(AskPrResponse {
+                answer,
+                codex_log: log,
+            })
+        })
+        .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(slice: &[&str]) -> HashSet<String> {
+        slice.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn chat_key_flattens_repo() {
+        assert_eq!(chat_key("owner/repo", 12), "owner__repo__12");
+    }
+
+    fn message(content: &str) -> ChatMessage {
+        ChatMessage {
+            role: "user".to_string(),
+            content: content.to_string(),
+            hunk_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn append_keeps_exchanges_saved_meanwhile() {
+        let tmp = tempfile::tempdir().unwrap();
+        append_chat(tmp.path(), "o__r__1", vec![message("first")]).unwrap();
+        append_chat(tmp.path(), "o__r__1", vec![message("second")]).unwrap();
+        let history: Vec<ChatMessage> = cache::read_cache(tmp.path(), CHAT_DIR, "o__r__1").unwrap();
+        let contents: Vec<&str> = history.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn hunk_refs_merge_listed_and_inline() {
+        let (refs, warnings) = collect_hunk_refs(
+            vec!["H1".to_string()],
+            "See H2 and H1 for the change.",
+            &ids(&["H1", "H2", "H3"]),
+        );
+        assert_eq!(refs, vec!["H1", "H2"]);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn hunk_refs_drop_invalid_ids() {
+        let (refs, warnings) =
+            collect_hunk_refs(vec!["H9".to_string()], "Mentioned in H1.", &ids(&["H1"]));
+        assert_eq!(refs, vec!["H1"]);
+        assert!(warnings.iter().any(|w| w.contains("'H9'")));
+    }
+
+    #[test]
+    fn hunk_refs_warn_when_none() {
+        let (refs, warnings) = collect_hunk_refs(vec![], "No idea.", &ids(&["H1"]));
+        assert!(refs.is_empty());
+        assert!(warnings.iter().any(|w| w.contains("does not reference")));
+    }
+
+    #[test]
+    fn ask_pr_prompt_includes_question() {
+        let prompt = build_ask_pr_prompt("Is this safe?", &Some("Japanese".to_string()));
+        assert!(prompt.contains("Is this safe?"));
+        assert!(prompt.contains("history.json"));
+        assert!(prompt.contains("Respond in Japanese."));
+    }
+}
@@ -0,0 +1,28 @@
+use crate::error::PrvwError;
+use crate::types::ChecklistTemplate;
+
+/// Templates are kept by `prvw_core::checklists`; this module adds their commands.
+pub use prvw_core::checklists::*;
+
+/// Saved checklist templates: all of them, or those applying to `repo` (global ones included).
+#[tauri::command]
+pub async fn list_checklist_templates(
+    app: tauri::AppHandle,
+    repo: Option<String>,
+) -> Result<Vec<ChecklistTemplate>, PrvwError> {
+    Ok(list(&crate::app_data_dir(&app)?, repo.as_deref()))
+}
+
+/// Create (empty `id`) or replace a checklist template. Returns it as saved.
+#[tauri::command]
+pub async fn save_checklist_template(
+    app: tauri::AppHandle,
+    template: ChecklistTemplate,
+) -> Result<ChecklistTemplate, PrvwError> {
+    save(&crate::app_data_dir(&app)?, template)
+}
+
+#[tauri::command]
+pub async fn delete_checklist_template(app: tauri::AppHandle, id: String) -> Result<(), PrvwError> {
+    delete(&crate::app_data_dir(&app)?, &id)
+}
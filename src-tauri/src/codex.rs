@@ -1,515 +1,440 @@
-use std::collections::HashSet;
+//! Commands of the analysis pipeline in `prvw_core::codex`; each passes its arguments through.
 
-use crate::cache;
-use crate::codex_runner::{self, lang_suffix};
+use crate::error::PrvwError;
+use crate::provider::ProviderRegistry;
+use crate::review_state::ReviewStates;
 use crate::types::{
-    AnalysisResponse, AnalysisResult, ExplainResponse, ExplainResult, Hunk, RefineResponse,
-    RefineResult,
+    AnalysisConstraints, AnalysisPromptPreview, AnalysisResponse, AnalysisResult,
+    BreakingChangesResponse, ConsistencyResponse, DraftCommentsResponse, ExplainResponse,
+    GuidedRefineResponse, HunkSummary, RefineResponse, RepoContext, RunOptions, SplitPlanResponse,
+    SummarizeResponse,
 };
-use crate::validation::validate_analysis;
 
-const ANALYSIS_SCHEMA: &str = include_str!("../schemas/analysis.json");
-const REFINE_SCHEMA: &str = include_str!("../schemas/refine.json");
-const EXPLAIN_SCHEMA: &str = include_str!("../schemas/explain.json");
+pub use prvw_core::codex::*;
 
-fn build_analysis_prompt(
-    hunk_count: usize,
-    pr_body: &Option<String>,
-    lang: &Option<String>,
-) -> String {
-    let pr_context = match pr_body.as_deref() {
-        Some(body) if !body.trim().is_empty() => {
-            let truncated = if body.len() > 2000 {
-                let end = body.floor_char_boundary(2000);
-                &body[..end]
-            } else {
-                body
-            };
-            format!(" The PR description is: \"{}\".", truncated)
-        }
-        _ => String::new(),
-    };
-
-    format!(
-        "Read hunks.json which contains {} hunks and group ALL of them by change intent for PR review.{} \
-         Every single hunk must be assigned to exactly one group — do not leave any hunk unassigned. \
-         Use only existing hunk ids. Output must match the schema. Do not invent ids. \
-         Order the groups array by logical processing flow \
-         (e.g. data model / schema first, then business logic, then API / controller, then UI, then tests, then config). \
-         Give each group a clear, descriptive title that serves as a section heading for reviewers. \
-         Assign each group a category from: schema, logic, api, ui, test, config, docs, refactor, other. \
-         For overallSummary, write a structured reviewer-facing summary in this format: \
-         First line: a single sentence stating WHAT the PR changes and WHY (keep it short). \
-         Then a blank line (\"\\n\\n\"), followed by bullet points (each starting with \"- \") listing each key change, one per line. \
-         Rules for bullets: one change per bullet, keep each bullet under 80 characters, \
-         use noun phrases (no trailing verbs like \"〜している\"), \
-         include only 1-2 key identifiers per bullet (the most important function or type name) — do NOT exhaustively list every touched symbol. \
-         Do NOT mention hunks, hunks.json, grouping process, or analysis internals — write as if summarizing the PR itself. \
-         Also classify each hunk as substantive or non-substantive. \
-         Non-substantive changes are: formatting/whitespace-only changes, code moved to another file without modification, \
-         indentation changes, lock file updates, auto-generated code changes, snapshot updates. \
-         Note: variable/function renames and comment changes ARE substantive. \
-         List non-substantive hunk IDs in nonSubstantiveHunkIds.{}",
-        hunk_count,
-        pr_context,
-        lang_suffix(lang)
-    )
-}
-
-fn build_refine_prompt(group_title: &str, group_id: &str, lang: &Option<String>) -> String {
-    format!(
-        "Read hunks.json. These hunks all belong to a single intent group titled \"{}\". \
-         Split them into smaller, more focused sub-groups by specific change purpose. \
-         Use only existing hunk ids from the input. Do not invent ids. \
-         Sub-group ids must be \"{}.1\", \"{}.2\", etc. \
-         Order sub-groups by logical processing flow. \
-         Give each sub-group a clear, descriptive title. \
-         Assign each sub-group a category from: schema, logic, api, ui, test, config, docs, refactor, other.{}",
-        group_title, group_id, group_id, lang_suffix(lang)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn analyze_intents_with_codex(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    states: tauri::State<'_, ReviewStates>,
+    session_id: Option<String>,
+    hunk_ids: Option<Vec<String>>,
+    hunks_json: Option<String>,
+    pr_body: Option<String>,
+    hunk_summaries: Option<Vec<HunkSummary>>,
+    guidelines: Option<String>,
+    repo_context: Option<RepoContext>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    cache_owner: Option<RepoContext>,
+    op_id: Option<String>,
+) -> Result<AnalysisResponse, PrvwError> {
+    prvw_core::codex::analyze_intents_with_codex(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        &states,
+        session_id,
+        hunk_ids,
+        hunks_json,
+        pr_body,
+        hunk_summaries,
+        guidelines,
+        repo_context,
+        model,
+        lang,
+        provider,
+        options,
+        force,
+        timeout_minutes,
+        cache_owner,
+        op_id,
     )
+    .await
 }
 
 #[tauri::command]
-pub async fn analyze_intents_with_codex(
+#[allow(clippy::too_many_arguments)]
+pub async fn adjust_analysis(
     app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
     hunks_json: String,
+    analysis: AnalysisResult,
+    constraints: AnalysisConstraints,
     pr_body: Option<String>,
+    guidelines: Option<String>,
+    repo: Option<String>,
     model: Option<String>,
     lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
     force: Option<bool>,
-) -> Result<AnalysisResponse, String> {
-    use tauri::Manager;
-
-    let hunks: Vec<Hunk> =
-        serde_json::from_str(&hunks_json).map_err(|e| format!("Invalid hunks JSON: {}", e))?;
-    let valid_ids: HashSet<String> = hunks.iter().map(|h| h.id.clone()).collect();
-
-    if valid_ids.is_empty() {
-        return Err("No hunks to analyze.".to_string());
-    }
-
-    let app_data_dir = app.path().app_data_dir().ok();
-    let model_str = model.as_deref().unwrap_or("");
-    let lang_str = lang.as_deref().unwrap_or("");
-    let pr_body_str = pr_body.as_deref().unwrap_or("");
-    let cache_key = cache::hash_key(&format!(
-        "{}\n{}\n{}\n{}",
-        hunks_json, pr_body_str, model_str, lang_str
-    ));
-
-    // Check cache (unless force)
-    if force != Some(true) {
-        if let Some(ref dir) = app_data_dir {
-            if let Some(mut cached) =
-                cache::read_cache::<AnalysisResponse>(dir, "cache/analysis", &cache_key)
-            {
-                cached.from_cache = true;
-                return Ok(cached);
-            }
-        }
-    }
-
-    let (temp_dir, schema_path, output_path) =
-        codex_runner::prepare_temp_dir(&hunks_json, ANALYSIS_SCHEMA, "analysis.json")?;
-
-    let prompt = build_analysis_prompt(valid_ids.len(), &pr_body, &lang);
-
-    let args = codex_runner::build_args(
-        temp_dir.path(),
-        schema_path
-            .to_str()
-            .ok_or_else(|| "Non-UTF-8 schema path".to_string())?,
-        output_path
-            .to_str()
-            .ok_or_else(|| "Non-UTF-8 output path".to_string())?,
-        &model,
-        prompt,
-    )?;
-
-    let codex_output = codex_runner::run(&args)?;
-
-    let analysis_str = std::fs::read_to_string(&output_path).map_err(|e| {
-        format!(
-            "Failed to read analysis.json: {}. Codex may not have produced output.",
-            e
-        )
-    })?;
-
-    let result: AnalysisResult = serde_json::from_str(&analysis_str)
-        .map_err(|e| format!("Failed to parse analysis.json: {}", e))?;
-
-    let validation = validate_analysis(&result, &valid_ids);
-
-    let mut log = codex_runner::build_log("analysis", &codex_output);
-    log.push_str(&format!(
-        "[analysis] hunks={} groups={}\n",
-        valid_ids.len(),
-        validation.cleaned.groups.len()
-    ));
-    if !validation.warnings.is_empty() {
-        log.push_str("--- validation warnings ---\n");
-        for w in &validation.warnings {
-            log.push_str(w);
-            log.push('\n');
-        }
-    }
-
-    let response = AnalysisResponse {
-        result: validation.cleaned,
-        codex_log: log,
-        from_cache: false,
-    };
-
-    // Write cache
-    if let Some(ref dir) = app_data_dir {
-        cache::write_cache(dir, "cache/analysis", &cache_key, &response);
-    }
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<AnalysisResponse, PrvwError> {
+    prvw_core::codex::adjust_analysis(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        hunks_json,
+        analysis,
+        constraints,
+        pr_body,
+        guidelines,
+        repo,
+        model,
+        lang,
+        provider,
+        options,
+        force,
+        timeout_minutes,
+        op_id,
+    )
+    .await
+}
 
-    Ok(response)
+#[tauri::command]
+pub async fn preview_analysis_prompt(
+    hunks_json: String,
+    pr_body: Option<String>,
+    hunk_summaries: Option<Vec<HunkSummary>>,
+    guidelines: Option<String>,
+    lang: Option<String>,
+    repo: Option<String>,
+) -> Result<AnalysisPromptPreview, PrvwError> {
+    prvw_core::codex::preview_analysis_prompt(
+        hunks_json,
+        pr_body,
+        hunk_summaries,
+        guidelines,
+        lang,
+        repo,
+    )
+    .await
 }
 
 #[tauri::command]
 #[allow(clippy::too_many_arguments)]
 pub async fn refine_group(
     app: tauri::AppHandle,
-    hunks_json: String,
+    providers: tauri::State<'_, ProviderRegistry>,
+    states: tauri::State<'_, ReviewStates>,
+    session_id: Option<String>,
+    hunks_json: Option<String>,
     group_id: String,
     group_title: String,
     hunk_ids: Vec<String>,
+    guidelines: Option<String>,
+    repo_context: Option<RepoContext>,
     model: Option<String>,
     lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
     force: Option<bool>,
-) -> Result<RefineResponse, String> {
-    use tauri::Manager;
-
-    let all_hunks: Vec<Hunk> =
-        serde_json::from_str(&hunks_json).map_err(|e| format!("Invalid hunks JSON: {}", e))?;
-
-    let hunk_id_set: HashSet<String> = hunk_ids.into_iter().collect();
-    let group_hunks: Vec<&Hunk> = all_hunks
-        .iter()
-        .filter(|h| hunk_id_set.contains(&h.id))
-        .collect();
-
-    if group_hunks.is_empty() {
-        return Err("No hunks found for this group.".to_string());
-    }
-
-    let group_hunks_json = serde_json::to_string(&group_hunks)
-        .map_err(|e| format!("Failed to serialize group hunks: {}", e))?;
-
-    let app_data_dir = app.path().app_data_dir().ok();
-    let model_str = model.as_deref().unwrap_or("");
-    let lang_str = lang.as_deref().unwrap_or("");
-    let cache_key = cache::hash_key(&format!(
-        "{}\n{}\n{}\n{}\n{}",
-        group_hunks_json, group_id, group_title, model_str, lang_str
-    ));
-
-    // Check cache (unless force)
-    if force != Some(true) {
-        if let Some(ref dir) = app_data_dir {
-            if let Some(mut cached) =
-                cache::read_cache::<RefineResponse>(dir, "cache/refine", &cache_key)
-            {
-                cached.from_cache = true;
-                return Ok(cached);
-            }
-        }
-    }
-
-    let (temp_dir, schema_path, output_path) =
-        codex_runner::prepare_temp_dir(&group_hunks_json, REFINE_SCHEMA, "refine.json")?;
-
-    let prompt = build_refine_prompt(&group_title, &group_id, &lang);
-
-    let args = codex_runner::build_args(
-        temp_dir.path(),
-        schema_path
-            .to_str()
-            .ok_or_else(|| "Non-UTF-8 schema path".to_string())?,
-        output_path
-            .to_str()
-            .ok_or_else(|| "Non-UTF-8 output path".to_string())?,
-        &model,
-        prompt,
-    )?;
-
-    let codex_output = codex_runner::run(&args)?;
-
-    let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
-        format!(
-            "Failed to read refine.json: {}. Codex may not have produced output.",
-            e
-        )
-    })?;
-
-    let refine_result: RefineResult = serde_json::from_str(&result_str)
-        .map_err(|e| format!("Failed to parse refine.json: {}", e))?;
+    timeout_minutes: Option<u64>,
+    cache_owner: Option<RepoContext>,
+    op_id: Option<String>,
+) -> Result<RefineResponse, PrvwError> {
+    prvw_core::codex::refine_group(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        &states,
+        session_id,
+        hunks_json,
+        group_id,
+        group_title,
+        hunk_ids,
+        guidelines,
+        repo_context,
+        model,
+        lang,
+        provider,
+        options,
+        force,
+        timeout_minutes,
+        cache_owner,
+        op_id,
+    )
+    .await
+}
 
-    // Validate: strip invalid hunk IDs
-    let mut warnings: Vec<String> = Vec::new();
-    let mut cleaned_groups = refine_result.groups;
-    for g in &mut cleaned_groups {
-        let before = g.hunk_ids.len();
-        g.hunk_ids.retain(|id| {
-            if hunk_id_set.contains(id) {
-                true
-            } else {
-                warnings.push(format!(
-                    "Removed non-existent hunk id '{}' from sub-group '{}'",
-                    id, g.title
-                ));
-                false
-            }
-        });
-        if g.hunk_ids.len() != before {
-            warnings.push(format!(
-                "Sub-group '{}': {} -> {} hunks",
-                g.title,
-                before,
-                g.hunk_ids.len()
-            ));
-        }
-    }
-    cleaned_groups.retain(|g| !g.hunk_ids.is_empty());
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn refine_group_with_guidance(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    hunks_json: String,
+    analysis: AnalysisResult,
+    group_id: String,
+    guidance: String,
+    guidelines: Option<String>,
+    repo_context: Option<RepoContext>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<GuidedRefineResponse, PrvwError> {
+    prvw_core::codex::refine_group_with_guidance(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        hunks_json,
+        analysis,
+        group_id,
+        guidance,
+        guidelines,
+        repo_context,
+        model,
+        lang,
+        provider,
+        options,
+        force,
+        timeout_minutes,
+        op_id,
+    )
+    .await
+}
 
-    let mut log = codex_runner::build_log("refine", &codex_output);
-    log.push_str(&format!(
-        "[refine] group=\"{}\" sub-groups={}\n",
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn draft_comments_for_group(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    hunks_json: String,
+    group_id: String,
+    group_title: String,
+    hunk_ids: Vec<String>,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<DraftCommentsResponse, PrvwError> {
+    prvw_core::codex::draft_comments_for_group(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        hunks_json,
+        group_id,
         group_title,
-        cleaned_groups.len()
-    ));
-    if !warnings.is_empty() {
-        log.push_str("--- validation warnings ---\n");
-        for w in &warnings {
-            log.push_str(w);
-            log.push('\n');
-        }
-    }
+        hunk_ids,
+        repo,
+        model,
+        lang,
+        provider,
+        options,
+        force,
+        timeout_minutes,
+        op_id,
+    )
+    .await
+}
 
-    let response = RefineResponse {
-        sub_groups: cleaned_groups,
-        codex_log: log,
-        from_cache: false,
-    };
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn suggest_split_plan(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    hunks_json: String,
+    pr_body: Option<String>,
+    analysis: Option<AnalysisResult>,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<SplitPlanResponse, PrvwError> {
+    prvw_core::codex::suggest_split_plan(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        hunks_json,
+        pr_body,
+        analysis,
+        repo,
+        model,
+        lang,
+        provider,
+        options,
+        force,
+        timeout_minutes,
+        op_id,
+    )
+    .await
+}
 
-    // Write cache
-    if let Some(ref dir) = app_data_dir {
-        cache::write_cache(dir, "cache/refine", &cache_key, &response);
-    }
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn detect_breaking_changes(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    hunks_json: String,
+    analysis: AnalysisResult,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<BreakingChangesResponse, PrvwError> {
+    prvw_core::codex::detect_breaking_changes(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        hunks_json,
+        analysis,
+        repo,
+        model,
+        lang,
+        provider,
+        options,
+        force,
+        timeout_minutes,
+        op_id,
+    )
+    .await
+}
 
-    Ok(response)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn check_pr_consistency(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    pr_title: String,
+    pr_body: Option<String>,
+    analysis: AnalysisResult,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<ConsistencyResponse, PrvwError> {
+    prvw_core::codex::check_pr_consistency(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        pr_title,
+        pr_body,
+        analysis,
+        repo,
+        model,
+        lang,
+        provider,
+        options,
+        force,
+        timeout_minutes,
+        op_id,
+    )
+    .await
 }
 
-fn build_explain_prompt(file_path: &str, lang: &Option<String>) -> String {
-    format!(
-        "Read hunk.json which contains a single code hunk from the file \"{}\". \
-         Explain this change for a code reviewer. Your explanation (in markdown) must cover: \
-         1. **What changed** — describe the concrete code change in a few sentences. \
-         2. **Intent** — why this change was likely made. \
-         3. **Impact** — what behavior or logic is affected, and any potential risks. \
-         Keep it concise and reviewer-focused. Use inline code (backticks) for identifiers.{}",
-        file_path,
-        lang_suffix(lang)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn summarize_hunks(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    hunks_json: String,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<SummarizeResponse, PrvwError> {
+    prvw_core::codex::summarize_hunks(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        hunks_json,
+        repo,
+        model,
+        lang,
+        provider,
+        options,
+        force,
+        timeout_minutes,
+        op_id,
     )
+    .await
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn explain_hunk(
     app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
     hunk_json: String,
     file_path: String,
+    repo: Option<String>,
     model: Option<String>,
     lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
     force: Option<bool>,
-) -> Result<ExplainResponse, String> {
-    use tauri::Manager;
-
-    let app_data_dir = app.path().app_data_dir().ok();
-    let model_str = model.as_deref().unwrap_or("");
-    let lang_str = lang.as_deref().unwrap_or("");
-    let cache_key = cache::hash_key(&format!("{}\n{}\n{}", hunk_json, model_str, lang_str));
-
-    if force != Some(true) {
-        if let Some(ref dir) = app_data_dir {
-            if let Some(mut cached) =
-                cache::read_cache::<ExplainResponse>(dir, "cache/explain", &cache_key)
-            {
-                cached.from_cache = true;
-                return Ok(cached);
-            }
-        }
-    }
-
-    let (temp_dir, schema_path, output_path) =
-        codex_runner::prepare_temp_dir(&hunk_json, EXPLAIN_SCHEMA, "explain.json")?;
-
-    // Rename hunks.json → hunk.json for clarity in the prompt
-    let temp_path = temp_dir.path();
-    std::fs::rename(temp_path.join("hunks.json"), temp_path.join("hunk.json"))
-        .map_err(|e| format!("Failed to rename temp file: {}", e))?;
-
-    let prompt = build_explain_prompt(&file_path, &lang);
-
-    let args = codex_runner::build_args(
-        temp_path,
-        schema_path
-            .to_str()
-            .ok_or_else(|| "Non-UTF-8 schema path".to_string())?,
-        output_path
-            .to_str()
-            .ok_or_else(|| "Non-UTF-8 output path".to_string())?,
-        &model,
-        prompt,
-    )?;
-
-    let codex_output = codex_runner::run(&args)?;
-
-    let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
-        format!(
-            "Failed to read explain.json: {}. Codex may not have produced output.",
-            e
-        )
-    })?;
-
-    let result: ExplainResult = serde_json::from_str(&result_str)
-        .map_err(|e| format!("Failed to parse explain.json: {}", e))?;
-
-    let log = codex_runner::build_log("explain", &codex_output);
-
-    let response = ExplainResponse {
-        explanation: result.explanation,
-        codex_log: log,
-        from_cache: false,
-    };
-
-    if let Some(ref dir) = app_data_dir {
-        cache::write_cache(dir, "cache/explain", &cache_key, &response);
-    }
-
-    Ok(response)
-}
-
-fn build_ask_prompt(
-    file_path: &str,
-    question: &str,
-    context: &str,
-    lang: &Option<String>,
-) -> String {
-    format!(
-        "Read hunk.json which contains a single code hunk from the file \"{}\". \
-         A reviewer has already received this analysis:\n\n{}\n\n\
-         The reviewer now asks: \"{}\"\n\n\
-         Answer the question concisely in markdown. Use inline code (backticks) for identifiers.{}",
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<ExplainResponse, PrvwError> {
+    prvw_core::codex::explain_hunk(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        hunk_json,
         file_path,
-        context,
-        question,
-        lang_suffix(lang)
+        repo,
+        model,
+        lang,
+        provider,
+        options,
+        force,
+        timeout_minutes,
+        op_id,
     )
+    .await
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn ask_about_hunk(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
     hunk_json: String,
     file_path: String,
     question: String,
     context: String,
+    repo: Option<String>,
     model: Option<String>,
     lang: Option<String>,
-) -> Result<ExplainResponse, String> {
-    let (temp_dir, schema_path, output_path) =
-        codex_runner::prepare_temp_dir(&hunk_json, EXPLAIN_SCHEMA, "ask.json")?;
-
-    let temp_path = temp_dir.path();
-    std::fs::rename(temp_path.join("hunks.json"), temp_path.join("hunk.json"))
-        .map_err(|e| format!("Failed to rename temp file: {}", e))?;
-
-    let prompt = build_ask_prompt(&file_path, &question, &context, &lang);
-
-    let args = codex_runner::build_args(
-        temp_path,
-        schema_path
-            .to_str()
-            .ok_or_else(|| "Non-UTF-8 schema path".to_string())?,
-        output_path
-            .to_str()
-            .ok_or_else(|| "Non-UTF-8 output path".to_string())?,
-        &model,
-        prompt,
-    )?;
-
-    let codex_output = codex_runner::run(&args)?;
-
-    let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
-        format!(
-            "Failed to read ask.json: {}. Codex may not have produced output.",
-            e
-        )
-    })?;
-
-    let result: ExplainResult = serde_json::from_str(&result_str)
-        .map_err(|e| format!("Failed to parse ask.json: {}", e))?;
-
-    let log = codex_runner::build_log("ask", &codex_output);
-
-    Ok(ExplainResponse {
-        explanation: result.explanation,
-        codex_log: log,
-        from_cache: false,
-    })
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn analysis_prompt_includes_hunk_count() {
-        let prompt = build_analysis_prompt(5, &None, &None);
-        assert!(prompt.contains("5 hunks"));
-    }
-
-    #[test]
-    fn analysis_prompt_no_pr_body() {
-        let prompt = build_analysis_prompt(1, &None, &None);
-        assert!(!prompt.contains("PR description"));
-    }
-
-    #[test]
-    fn analysis_prompt_with_pr_body() {
-        let body = Some("Fix login bug".to_string());
-        let prompt = build_analysis_prompt(1, &body, &None);
-        assert!(prompt.contains("Fix login bug"));
-        assert!(prompt.contains("PR description"));
-    }
-
-    #[test]
-    fn analysis_prompt_truncates_long_body() {
-        let body = Some("x".repeat(3000));
-        let prompt = build_analysis_prompt(1, &body, &None);
-        // The body in the prompt should be truncated to ~2000 chars
-        assert!(prompt.len() < 3000 + 800);
-        assert!(prompt.contains("PR description"));
-    }
-
-    #[test]
-    fn analysis_prompt_with_lang() {
-        let prompt = build_analysis_prompt(1, &None, &Some("Japanese".to_string()));
-        assert!(prompt.contains("Respond in Japanese."));
-    }
-
-    #[test]
-    fn refine_prompt_contains_group_info() {
-        let prompt = build_refine_prompt("Auth changes", "G1", &None);
-        assert!(prompt.contains("Auth changes"));
-        assert!(prompt.contains("G1.1"));
-        assert!(prompt.contains("G1.2"));
-    }
-
-    #[test]
-    fn refine_prompt_with_lang() {
-        let prompt = build_refine_prompt("Title", "G1", &Some("Spanish".to_string()));
-        assert!(prompt.contains("Respond in Spanish."));
-    }
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<ExplainResponse, PrvwError> {
+    prvw_core::codex::ask_about_hunk(
+        crate::app_data_dir(&app).ok().as_deref(),
+        &providers,
+        hunk_json,
+        file_path,
+        question,
+        context,
+        repo,
+        model,
+        lang,
+        provider,
+        options,
+        timeout_minutes,
+        op_id,
+    )
+    .await
 }
@@ -0,0 +1,220 @@
+use crate::drafts;
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::json_store::TemplateStore;
+use crate::time::now_secs;
+use crate::types::{CommentTemplate, DraftComment, QueuedComment};
+
+/// Saved templates, with ids of the form `S<n>`.
+static TEMPLATES: TemplateStore =
+    TemplateStore::new("comment_templates.json", 'S', "comment template");
+
+const SEVERITIES: &[&str] = &["nit", "suggestion", "issue", "blocker"];
+
+/// Trim the template and check its name, body, severity and repo.
+fn normalize(mut template: CommentTemplate) -> Result<CommentTemplate, PrvwError> {
+    template.name = template.name.trim().to_string();
+    if template.name.is_empty() {
+        return Err(PrvwError::InvalidInput(
+            "Comment template name is empty.".to_string(),
+        ));
+    }
+    template.body = template.body.trim().to_string();
+    if template.body.is_empty() {
+        return Err(PrvwError::InvalidInput(
+            "Comment template body is empty.".to_string(),
+        ));
+    }
+    template.severity = match template.severity.trim() {
+        "" => "suggestion".to_string(),
+        s if SEVERITIES.contains(&s) => s.to_string(),
+        other => {
+            return Err(PrvwError::InvalidInput(format!(
+                "Unknown comment severity '{}'",
+                other
+            )))
+        }
+    };
+    template.repo = template
+        .repo
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    if let Some(repo) = &template.repo {
+        validate_repo(repo)?;
+    }
+    Ok(template)
+}
+
+/// Fill in `{file}`, `{line}` and `{group}`. Unknown placeholders are kept as written, and
+/// substituted values are not scanned again.
+fn render(body: &str, file: &str, line: u32, group: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let value = match &after[..end] {
+                "file" => file.to_string(),
+                "line" => line.to_string(),
+                "group" => group.to_string(),
+                _ => return None,
+            };
+            Some((value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                out.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Saved comment templates: all of them, or those offered in `repo` (global ones included).
+#[tauri::command]
+pub async fn list_comment_templates(
+    app: tauri::AppHandle,
+    repo: Option<String>,
+) -> Result<Vec<CommentTemplate>, PrvwError> {
+    use tauri::Manager;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(TEMPLATES.list(&app_data_dir, repo.as_deref()))
+}
+
+/// Create (empty `id`) or replace a comment template. Returns it as saved.
+#[tauri::command]
+pub async fn save_comment_template(
+    app: tauri::AppHandle,
+    template: CommentTemplate,
+) -> Result<CommentTemplate, PrvwError> {
+    use tauri::Manager;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    TEMPLATES.save(&app_data_dir, normalize(template)?)
+}
+
+#[tauri::command]
+pub async fn delete_comment_template(app: tauri::AppHandle, id: String) -> Result<(), PrvwError> {
+    use tauri::Manager;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    TEMPLATES.delete::<CommentTemplate>(&app_data_dir, &id)
+}
+
+/// Fill in a template for a diff line and append it to the PR's comment queue as a manual
+/// comment. Returns the saved queue.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_comment_template(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    template_id: String,
+    hunk_id: String,
+    file_path: String,
+    line: u32,
+    side: Option<String>,
+    group_title: Option<String>,
+) -> Result<Vec<QueuedComment>, PrvwError> {
+    use tauri::Manager;
+    let side = side.unwrap_or_else(|| "RIGHT".to_string());
+    if side != "LEFT" && side != "RIGHT" {
+        return Err(PrvwError::InvalidInput(format!(
+            "Unknown comment side '{}'",
+            side
+        )));
+    }
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let template: CommentTemplate = TEMPLATES.get(&app_data_dir, &template_id)?;
+    let comment = DraftComment {
+        body: render(
+            &template.body,
+            &file_path,
+            line,
+            group_title.as_deref().unwrap_or_default().trim(),
+        ),
+        hunk_id,
+        file_path,
+        line,
+        side,
+        severity: template.severity,
+    };
+    drafts::modify(&app, &repo, pr_number, |queue| {
+        drafts::enqueue(queue, vec![comment], "manual", now_secs());
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(id: &str, body: &str) -> CommentTemplate {
+        CommentTemplate {
+            id: id.to_string(),
+            name: id.to_string(),
+            body: body.to_string(),
+            severity: String::new(),
+            repo: None,
+        }
+    }
+
+    #[test]
+    fn renders_known_placeholders_once() {
+        assert_eq!(
+            render(
+                "Please add a test for {group} ({file}:{line}). {unknown} {",
+                "src/{line}.rs",
+                12,
+                "Rate limiting",
+            ),
+            "Please add a test for Rate limiting (src/{line}.rs:12). {unknown} {"
+        );
+    }
+
+    #[test]
+    fn normalizes_and_numbers_templates() {
+        assert!(normalize(template(" ", "x")).is_err());
+        assert!(normalize(template("a", " ")).is_err());
+        assert!(normalize(CommentTemplate {
+            severity: "fatal".to_string(),
+            ..template("a", "x")
+        })
+        .is_err());
+        let t = normalize(CommentTemplate {
+            repo: Some(" ".to_string()),
+            ..template(" Test ", " Add a test ")
+        })
+        .unwrap();
+        assert_eq!(
+            (
+                t.name.as_str(),
+                t.body.as_str(),
+                t.severity.as_str(),
+                t.repo
+            ),
+            ("Test", "Add a test", "suggestion", None)
+        );
+        assert_eq!(
+            TEMPLATES.next_id(&[template("S2", "x"), template("T9", "x")]),
+            "S3"
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::cache;
+use crate::error::PrvwError;
+use crate::gh::{run_gh, validate_repo};
+use crate::local_range;
+use crate::types::{ChangedFile, CompareDiff, RangeCommit};
+
+/// A `github.com/<owner>/<repo>/compare/<base>...<head>` URL: (repo, base, head). `base` is
+/// unset for `compare/<head>`, which GitHub compares against the default branch.
+fn parse_compare_url(url: &str) -> Result<(String, Option<String>, String), String> {
+    let invalid = || {
+        format!(
+            "Not a GitHub compare URL: {}. Expected https://github.com/owner/repo/compare/base...head",
+            url.trim()
+        )
+    };
+    let path = url
+        .trim()
+        .split_once("://")
+        .map_or(url.trim(), |(_, rest)| rest)
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default();
+    // host/owner/repo/compare/<spec>; branch names may contain slashes, so the spec is the rest.
+    let mut parts = path.splitn(5, '/');
+    let (_host, owner, name, compare, spec) = (
+        parts.next(),
+        parts.next().ok_or_else(invalid)?,
+        parts.next().ok_or_else(invalid)?,
+        parts.next().ok_or_else(invalid)?,
+        parts.next().ok_or_else(invalid)?,
+    );
+    let repo = format!("{}/{}", owner, name);
+    if compare != "compare" || validate_repo(&repo).is_err() {
+        return Err(invalid());
+    }
+    let spec = spec.trim_end_matches('/');
+    let (base, head) = match spec.split_once("...") {
+        Some((base, head)) => (Some(base), head),
+        None if spec.contains("..") => {
+            return Err("Two-dot compare URLs aren't supported; use base...head.".to_string())
+        }
+        None => (None, spec),
+    };
+    let bad_ref =
+        |r: &str| r.is_empty() || r.starts_with('-') || r.chars().any(char::is_whitespace);
+    if bad_ref(head) || base.is_some_and(bad_ref) {
+        return Err(invalid());
+    }
+    Ok((repo, base.map(str::to_string), head.to_string()))
+}
+
+#[derive(Deserialize)]
+struct CommitDetail {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct ApiCommit {
+    sha: String,
+    commit: CommitDetail,
+}
+
+#[derive(Deserialize)]
+struct ApiFile {
+    filename: String,
+    #[serde(default)]
+    previous_filename: Option<String>,
+    status: String,
+    /// Missing for binary files and very large diffs.
+    #[serde(default)]
+    patch: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MergeBase {
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct Comparison {
+    html_url: String,
+    merge_base_commit: MergeBase,
+    ahead_by: usize,
+    behind_by: usize,
+    #[serde(default)]
+    commits: Vec<ApiCommit>,
+    #[serde(default)]
+    files: Vec<ApiFile>,
+}
+
+fn changed_file(file: ApiFile) -> ChangedFile {
+    let status = match file.status.as_str() {
+        "removed" => "deleted",
+        "changed" => "typechange",
+        status => status,
+    }
+    .to_string();
+    ChangedFile {
+        binary: file.patch.is_none() && status != "renamed",
+        path: file.filename,
+        old_path: file.previous_filename,
+        status,
+    }
+}
+
+fn default_branch(repo: &str) -> Result<String, String> {
+    let output = run_gh(
+        &["api", &format!("repos/{}", repo), "-q", ".default_branch"],
+        "gh api repos",
+    )?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Resolve a GitHub compare URL through the compare API and fetch its diff, for analyzing a
+/// branch comparison that has no PR. The diff is cached by merge base and head commit.
+pub fn fetch_compare(app_data_dir: Option<&Path>, url: &str) -> Result<CompareDiff, String> {
+    let (repo, base, head) = parse_compare_url(url)?;
+    let base = match base {
+        Some(base) => base,
+        None => default_branch(&repo)?,
+    };
+    let endpoint = format!("repos/{}/compare/{}...{}", repo, base, head);
+    let output = run_gh(&["api", &endpoint], "gh api compare")?;
+    let comparison: Comparison = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse compare response: {}", e))?;
+    if comparison.files.is_empty() {
+        return Err(format!("No changes between {} and {}", base, head));
+    }
+    let commits: Vec<RangeCommit> = comparison
+        .commits
+        .iter()
+        .rev()
+        .map(|c| RangeCommit {
+            sha: c.sha.clone(),
+            subject: c
+                .commit
+                .message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        })
+        .collect();
+    // The API lists at most 250 commits; past that the newest (head) commit is unknown and the
+    // diff isn't cached.
+    let head_sha = commits
+        .first()
+        .filter(|_| commits.len() == comparison.ahead_by)
+        .map(|c| c.sha.clone());
+    let cache_key = head_sha.as_ref().map(|sha| {
+        format!(
+            "compare__{}__{}_{}",
+            repo.replace('/', "__"),
+            comparison.merge_base_commit.sha,
+            sha
+        )
+    });
+    let cached = app_data_dir
+        .zip(cache_key.as_deref())
+        .and_then(|(dir, key)| cache::read_cache::<String>(dir, "cache/diff", key));
+    let diff = match cached {
+        Some(diff) => diff,
+        None => {
+            let output = run_gh(
+                &[
+                    "api",
+                    "-H",
+                    "Accept: application/vnd.github.diff",
+                    &endpoint,
+                ],
+                "gh api compare",
+            )?;
+            let diff = String::from_utf8_lossy(&output.stdout).into_owned();
+            if let Some((dir, key)) = app_data_dir.zip(cache_key.as_deref()) {
+                cache::write_cache(dir, "cache/diff", key, &diff);
+            }
+            diff
+        }
+    };
+
+    let range = format!("{}...{}", base, head);
+    let mut description = local_range::describe(&range, &commits);
+    description.push_str(&format!(
+        "\n`{}` is {} commit{} ahead of and {} commit{} behind `{}`.\n",
+        head,
+        comparison.ahead_by,
+        if comparison.ahead_by == 1 { "" } else { "s" },
+        comparison.behind_by,
+        if comparison.behind_by == 1 { "" } else { "s" },
+        base
+    ));
+    Ok(CompareDiff {
+        repo,
+        base,
+        head,
+        merge_base: comparison.merge_base_commit.sha,
+        head_sha,
+        ahead: comparison.ahead_by,
+        behind: comparison.behind_by,
+        url: comparison.html_url,
+        description,
+        commits,
+        files: comparison.files.into_iter().map(changed_file).collect(),
+        diff,
+    })
+}
+
+/// The diff behind a pasted `github.com/owner/repo/compare/base...head` URL. Pass `diff` to
+/// `parse_diff` with `repo` and `description` as the PR body, like a range diff.
+#[tauri::command]
+pub async fn get_compare_diff(
+    app: tauri::AppHandle,
+    url: String,
+) -> Result<CompareDiff, PrvwError> {
+    use tauri::Manager;
+    let app_data_dir = app.path().app_data_dir().ok();
+    Ok(fetch_compare(app_data_dir.as_deref(), &url)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_compare_urls() {
+        assert_eq!(
+            parse_compare_url("https://github.com/o/r/compare/main...feature/x?expand=1").unwrap(),
+            (
+                "o/r".to_string(),
+                Some("main".to_string()),
+                "feature/x".to_string()
+            )
+        );
+        assert_eq!(
+            parse_compare_url("github.com/o/r/compare/me:topic").unwrap(),
+            ("o/r".to_string(), None, "me:topic".to_string())
+        );
+        assert!(parse_compare_url("https://github.com/o/r/compare/a..b").is_err());
+        assert!(parse_compare_url("https://github.com/o/r/pull/1").is_err());
+        assert!(parse_compare_url("https://github.com/o/r/compare/").is_err());
+    }
+
+    #[test]
+    fn maps_compare_files() {
+        let comparison: Comparison = serde_json::from_str(
+            r#"{
+              "html_url": "https://github.com/o/r/compare/main...topic",
+              "merge_base_commit": {"sha": "aaa"},
+              "ahead_by": 1,
+              "behind_by": 0,
+              "commits": [{"sha": "bbb", "commit": {"message": "Add x\n\nBody"}}],
+              "files": [
+                {"filename": "x.rs", "status": "added", "patch": "@@ -0,0 +1 @@\n+x"},
+                {"filename": "logo.png", "status": "removed"},
+                {"filename": "b.rs", "previous_filename": "a.rs", "status": "renamed"}
+              ]
+            }"#,
+        )
+        .unwrap();
+        let files: Vec<ChangedFile> = comparison.files.into_iter().map(changed_file).collect();
+        assert_eq!(files[0].status, "added");
+        assert!(!files[0].binary);
+        assert_eq!(
+            (files[1].status.as_str(), files[1].binary),
+            ("deleted", true)
+        );
+        assert_eq!(files[2].old_path.as_deref(), Some("a.rs"));
+        assert!(!files[2].binary);
+    }
+}
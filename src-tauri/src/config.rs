@@ -0,0 +1,28 @@
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::PrvwError;
+use crate::types::ConfigStatus;
+
+/// Loading is in `prvw_core::config`; this module watches the file for the app and adds the
+/// command.
+pub use prvw_core::config::*;
+
+const CONFIG_RELOADED_EVENT: &str = "config-reloaded";
+
+/// Load `prvw.toml` and watch it, emitting `config-reloaded` with the new status on changes.
+pub fn init(app: &AppHandle) {
+    let Ok(dir) = app.path().app_config_dir() else {
+        return;
+    };
+    let path = load(&dir);
+    let app = app.clone();
+    watch(path, move |status| {
+        let _ = app.emit(CONFIG_RELOADED_EVENT, status);
+    });
+}
+
+/// Where `prvw.toml` is read from, and whether it loaded.
+#[tauri::command]
+pub async fn get_config_status() -> Result<ConfigStatus, PrvwError> {
+    Ok(status())
+}
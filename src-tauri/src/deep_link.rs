@@ -0,0 +1,125 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::error::PrvwError;
+use crate::gh;
+use crate::tray::{self, OPEN_PR_EVENT};
+use crate::types::OpenPr;
+
+const SCHEME: &str = "prvw://";
+/// Emitted with the message when a link can't be opened.
+pub const FAILED_EVENT: &str = "deep-link-failed";
+
+/// Links opened before the window listens for `open-pr` (the one that launched the app) wait
+/// here until it calls `deep_links_ready`.
+#[derive(Default)]
+pub struct DeepLinks {
+    queue: Mutex<LinkQueue>,
+}
+
+#[derive(Default)]
+struct LinkQueue {
+    ready: bool,
+    pending: Vec<OpenPr>,
+}
+
+/// Repo and PR number of `prvw://owner/repo/pull/123` (a PR URL with `prvw` as its scheme and
+/// no host; trailing segments like `/files` are ignored).
+fn parse_link(url: &str) -> Result<(String, u32), String> {
+    let invalid = || format!("Not a prvw link: {}. Use prvw://owner/repo/pull/123.", url);
+    let url = url.trim();
+    let rest = url
+        .get(..SCHEME.len())
+        .filter(|scheme| scheme.eq_ignore_ascii_case(SCHEME))
+        .map(|_| &url[SCHEME.len()..])
+        .ok_or_else(invalid)?;
+    let path = rest.split(['?', '#']).next().unwrap_or_default();
+    gh::parse_pr_ref(&format!("{}host/{}", SCHEME, path)).map_err(|_| invalid())
+}
+
+/// Look up the PR of a link and hand it to the window, or queue it until the window listens.
+fn open(app: &AppHandle, url: &str) {
+    let app = app.clone();
+    let url = url.to_string();
+    // gh blocks; link events arrive on the main thread.
+    std::thread::spawn(move || {
+        let result = parse_link(&url).and_then(|(repo, number)| {
+            Ok(OpenPr {
+                pr: gh::fetch_pr_item(&repo, number)?,
+                repo,
+            })
+        });
+        tray::show_window(&app);
+        match result {
+            Ok(open) => {
+                if let Ok(mut queue) = app.state::<DeepLinks>().queue.lock() {
+                    if !queue.ready {
+                        queue.pending.push(open);
+                        return;
+                    }
+                }
+                let _ = app.emit(OPEN_PR_EVENT, open);
+            }
+            Err(e) => {
+                eprintln!("[deep-link] {}", e);
+                let _ = app.emit(FAILED_EVENT, e);
+            }
+        }
+    });
+}
+
+/// Handle the link the app was launched with and any opened later. On Linux (and in Windows
+/// dev builds) the scheme is registered at runtime; bundles register it at install.
+pub fn init(app: &AppHandle) {
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = app.deep_link().register_all() {
+        eprintln!("[deep-link] failed to register prvw://: {}", e);
+    }
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            open(app, url.as_str());
+        }
+    }
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open(&handle, url.as_str());
+        }
+    });
+}
+
+/// Called by the window once it listens for `open-pr`; returns the links that arrived before.
+#[tauri::command]
+pub async fn deep_links_ready(
+    state: tauri::State<'_, DeepLinks>,
+) -> Result<Vec<OpenPr>, PrvwError> {
+    let mut queue = state
+        .queue
+        .lock()
+        .map_err(|_| "Deep link lock poisoned".to_string())?;
+    queue.ready = true;
+    Ok(std::mem::take(&mut queue.pending))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pr_links() {
+        assert_eq!(
+            parse_link("prvw://owner/repo/pull/123").unwrap(),
+            ("owner/repo".to_string(), 123)
+        );
+        assert_eq!(
+            parse_link(" PRVW://owner/repo/pull/7/files#diff-1 ").unwrap(),
+            ("owner/repo".to_string(), 7)
+        );
+        assert!(parse_link("prvw://owner/repo").is_err());
+        assert!(parse_link("prvw://owner/repo/pull/abc").is_err());
+        assert!(parse_link("https://github.com/owner/repo/pull/1").is_err());
+        assert!(parse_link("prvw:").is_err());
+    }
+}
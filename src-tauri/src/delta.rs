@@ -0,0 +1,268 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::cache;
+use crate::error::PrvwError;
+use crate::types::{AnalysisDelta, AnalysisResult, GroupDelta, Hunk};
+
+/// Content fingerprint of a hunk, independent of its ID and line numbers, so the same change
+/// can be recognized across re-parses of an updated PR.
+pub fn hunk_fingerprint(hunk: &Hunk) -> String {
+    let mut input = hunk.file_path.clone();
+    for line in &hunk.lines {
+        input.push('\n');
+        input.push(match line.kind.as_str() {
+            "add" => '+',
+            "remove" => '-',
+            _ => ' ',
+        });
+        input.push_str(&line.text);
+    }
+    cache::hash_key(&input)
+}
+
+fn fingerprints_by_id(hunks: &[Hunk]) -> HashMap<&str, String> {
+    hunks
+        .iter()
+        .map(|h| (h.id.as_str(), hunk_fingerprint(h)))
+        .collect()
+}
+
+fn group_fingerprints<'a>(
+    hunk_ids: &[String],
+    by_id: &'a HashMap<&str, String>,
+) -> HashSet<&'a str> {
+    hunk_ids
+        .iter()
+        .filter_map(|id| by_id.get(id.as_str()).map(String::as_str))
+        .collect()
+}
+
+/// Compare two analyses of the same PR (before and after a push).
+/// Groups are matched by the largest overlap of hunk content; a group whose hunks did not change
+/// is reported as "unchanged" so reviewers can skip it.
+pub fn compute_delta(
+    old_hunks: &[Hunk],
+    old: &AnalysisResult,
+    new_hunks: &[Hunk],
+    new: &AnalysisResult,
+) -> AnalysisDelta {
+    let old_fp = fingerprints_by_id(old_hunks);
+    let new_fp = fingerprints_by_id(new_hunks);
+    let old_all: HashSet<&str> = old_fp.values().map(String::as_str).collect();
+
+    let old_groups: Vec<HashSet<&str>> = old
+        .groups
+        .iter()
+        .map(|g| group_fingerprints(&g.hunk_ids, &old_fp))
+        .collect();
+    let mut matched_old: HashSet<usize> = HashSet::new();
+    let mut groups: Vec<GroupDelta> = Vec::new();
+
+    for g in &new.groups {
+        let fps = group_fingerprints(&g.hunk_ids, &new_fp);
+        let best = old_groups
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !matched_old.contains(i))
+            .map(|(i, old_set)| (i, old_set.intersection(&fps).count()))
+            .filter(|(_, overlap)| *overlap > 0)
+            .max_by_key(|(_, overlap)| *overlap);
+
+        let Some((old_idx, _)) = best else {
+            groups.push(GroupDelta {
+                group_id: g.id.clone(),
+                title: g.title.clone(),
+                status: "new".to_string(),
+                matched_group_id: None,
+                gained_hunk_ids: g.hunk_ids.clone(),
+                lost_hunk_ids: Vec::new(),
+            });
+            continue;
+        };
+        matched_old.insert(old_idx);
+        let old_group = &old.groups[old_idx];
+        let old_set = &old_groups[old_idx];
+
+        let gained_hunk_ids: Vec<String> = g
+            .hunk_ids
+            .iter()
+            .filter(|id| {
+                new_fp
+                    .get(id.as_str())
+                    .is_some_and(|fp| !old_set.contains(fp.as_str()))
+            })
+            .cloned()
+            .collect();
+        let lost_hunk_ids: Vec<String> = old_group
+            .hunk_ids
+            .iter()
+            .filter(|id| {
+                old_fp
+                    .get(id.as_str())
+                    .is_some_and(|fp| !fps.contains(fp.as_str()))
+            })
+            .cloned()
+            .collect();
+        let status = if gained_hunk_ids.is_empty() && lost_hunk_ids.is_empty() {
+            "unchanged"
+        } else {
+            "changed"
+        };
+        groups.push(GroupDelta {
+            group_id: g.id.clone(),
+            title: g.title.clone(),
+            status: status.to_string(),
+            matched_group_id: Some(old_group.id.clone()),
+            gained_hunk_ids,
+            lost_hunk_ids,
+        });
+    }
+
+    let removed_groups: Vec<GroupDelta> = old
+        .groups
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !matched_old.contains(i))
+        .map(|(_, g)| GroupDelta {
+            group_id: g.id.clone(),
+            title: g.title.clone(),
+            status: "removed".to_string(),
+            matched_group_id: None,
+            gained_hunk_ids: Vec::new(),
+            lost_hunk_ids: g.hunk_ids.clone(),
+        })
+        .collect();
+
+    let new_hunk_ids: Vec<String> = new_hunks
+        .iter()
+        .filter(|h| {
+            new_fp
+                .get(h.id.as_str())
+                .is_some_and(|fp| !old_all.contains(fp.as_str()))
+        })
+        .map(|h| h.id.clone())
+        .collect();
+
+    AnalysisDelta {
+        groups,
+        removed_groups,
+        new_hunk_ids,
+    }
+}
+
+#[tauri::command]
+pub async fn diff_analyses(
+    old_hunks_json: String,
+    old_analysis: AnalysisResult,
+    new_hunks_json: String,
+    new_analysis: AnalysisResult,
+) -> Result<AnalysisDelta, PrvwError> {
+    let old_hunks: Vec<Hunk> = serde_json::from_str(&old_hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid old hunks JSON: {}", e)))?;
+    let new_hunks: Vec<Hunk> = serde_json::from_str(&new_hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid new hunks JSON: {}", e)))?;
+    Ok(compute_delta(
+        &old_hunks,
+        &old_analysis,
+        &new_hunks,
+        &new_analysis,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DiffLine, IntentGroup};
+
+    fn make_hunk(id: &str, file_path: &str, added: &str) -> Hunk {
+        Hunk {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            subproject: None,
+            header: "@@ -1,1 +1,2 @@".to_string(),
+            old_start: 1,
+            old_lines: 1,
+            new_start: 1,
+            new_lines: 2,
+            lines: vec![DiffLine {
+                kind: "add".to_string(),
+                old_line: None,
+                new_line: Some(1),
+                text: added.to_string(),
+            }],
+        }
+    }
+
+    fn make_group(id: &str, hunk_ids: Vec<&str>) -> IntentGroup {
+        IntentGroup {
+            id: id.to_string(),
+            title: format!("Group {}", id),
+            category: "logic".to_string(),
+            rationale: String::new(),
+            risk: "low".to_string(),
+            risk_score: 0,
+            risk_evidence: vec![],
+            hunk_ids: hunk_ids.into_iter().map(String::from).collect(),
+            reviewer_checklist: vec![],
+            suggested_tests: vec![],
+        }
+    }
+
+    fn make_result(groups: Vec<IntentGroup>) -> AnalysisResult {
+        AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups,
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        }
+    }
+
+    #[test]
+    fn fingerprint_ignores_id() {
+        let a = make_hunk("H1", "a.rs", "x");
+        let b = make_hunk("H7", "a.rs", "x");
+        assert_eq!(hunk_fingerprint(&a), hunk_fingerprint(&b));
+        assert_ne!(
+            hunk_fingerprint(&a),
+            hunk_fingerprint(&make_hunk("H1", "b.rs", "x"))
+        );
+    }
+
+    #[test]
+    fn unchanged_group_despite_renumbered_ids() {
+        let old_hunks = vec![make_hunk("H1", "a.rs", "x"), make_hunk("H2", "b.rs", "y")];
+        let new_hunks = vec![make_hunk("H1", "z.rs", "new"), make_hunk("H2", "a.rs", "x")];
+        let old = make_result(vec![
+            make_group("G1", vec!["H1"]),
+            make_group("G2", vec!["H2"]),
+        ]);
+        let new = make_result(vec![
+            make_group("G1", vec!["H2"]),
+            make_group("G2", vec!["H1"]),
+        ]);
+
+        let delta = compute_delta(&old_hunks, &old, &new_hunks, &new);
+        assert_eq!(delta.groups[0].status, "unchanged");
+        assert_eq!(delta.groups[0].matched_group_id.as_deref(), Some("G1"));
+        assert_eq!(delta.groups[1].status, "new");
+        assert_eq!(delta.removed_groups.len(), 1);
+        assert_eq!(delta.removed_groups[0].group_id, "G2");
+        assert_eq!(delta.new_hunk_ids, vec!["H1"]);
+    }
+
+    #[test]
+    fn changed_group_reports_gained_and_lost() {
+        let old_hunks = vec![make_hunk("H1", "a.rs", "x"), make_hunk("H2", "a.rs", "y")];
+        let new_hunks = vec![make_hunk("H1", "a.rs", "x"), make_hunk("H2", "a.rs", "y2")];
+        let old = make_result(vec![make_group("G1", vec!["H1", "H2"])]);
+        let new = make_result(vec![make_group("G1", vec!["H1", "H2"])]);
+
+        let delta = compute_delta(&old_hunks, &old, &new_hunks, &new);
+        assert_eq!(delta.groups[0].status, "changed");
+        assert_eq!(delta.groups[0].gained_hunk_ids, vec!["H2"]);
+        assert_eq!(delta.groups[0].lost_hunk_ids, vec!["H2"]);
+        assert!(delta.removed_groups.is_empty());
+    }
+}
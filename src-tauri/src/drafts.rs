@@ -0,0 +1,294 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::json_store;
+use crate::time::now_secs;
+use crate::types::{DraftComment, QueuedComment, QueuedCommentEdit};
+
+/// Queued comments are unsent user work: they live outside `cache/` so `clear_cache` keeps them.
+const DRAFTS_DIR: &str = "drafts";
+
+/// Serializes read-modify-write cycles of queue files across concurrent commands.
+pub static DRAFTS_LOCK: Mutex<()> = Mutex::new(());
+
+fn queue_path(app_data_dir: &Path, repo: &str, pr_number: u32) -> PathBuf {
+    app_data_dir
+        .join(DRAFTS_DIR)
+        .join(format!("{}__{}.json", repo.replace('/', "__"), pr_number))
+}
+
+pub fn load(app_data_dir: &Path, repo: &str, pr_number: u32) -> Vec<QueuedComment> {
+    json_store::load(&queue_path(app_data_dir, repo, pr_number)).unwrap_or_default()
+}
+
+/// Write via a temp file and rename so a crash never loses the queue; an empty queue removes
+/// the file.
+pub fn save(
+    app_data_dir: &Path,
+    repo: &str,
+    pr_number: u32,
+    queue: &[QueuedComment],
+) -> Result<(), String> {
+    let path = queue_path(app_data_dir, repo, pr_number);
+    if queue.is_empty() {
+        return json_store::remove(&path, "comment queue");
+    }
+    json_store::save(&path, queue, "comment queue")
+}
+
+/// Next free id of the form `C<n>`.
+fn next_id(queue: &[QueuedComment]) -> u32 {
+    queue
+        .iter()
+        .filter_map(|c| c.id.strip_prefix('C')?.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
+pub fn enqueue(
+    queue: &mut Vec<QueuedComment>,
+    comments: Vec<DraftComment>,
+    source: &str,
+    now: u64,
+) {
+    let mut n = next_id(queue);
+    for comment in comments {
+        if comment.body.trim().is_empty() {
+            continue;
+        }
+        queue.push(QueuedComment {
+            id: format!("C{}", n),
+            comment,
+            source: source.to_string(),
+            created_at: now,
+        });
+        n += 1;
+    }
+}
+
+fn edit(queue: &mut [QueuedComment], id: &str, edit: QueuedCommentEdit) -> Result<(), PrvwError> {
+    let queued = queue
+        .iter_mut()
+        .find(|c| c.id == id)
+        .ok_or_else(|| PrvwError::NotFound(format!("Unknown queued comment '{}'", id)))?;
+    if let Some(body) = edit.body {
+        if body.trim().is_empty() {
+            return Err(PrvwError::InvalidInput(
+                "Comment body is empty.".to_string(),
+            ));
+        }
+        queued.comment.body = body;
+    }
+    if let Some(side) = edit.side {
+        if side != "LEFT" && side != "RIGHT" {
+            return Err(PrvwError::InvalidInput(format!(
+                "Unknown comment side '{}'",
+                side
+            )));
+        }
+        queued.comment.side = side;
+    }
+    if let Some(severity) = edit.severity {
+        queued.comment.severity = severity;
+    }
+    if let Some(line) = edit.line {
+        queued.comment.line = line;
+    }
+    Ok(())
+}
+
+/// Put the comments listed in `ids` first, in that order; the rest keep their relative order.
+fn reorder(queue: &mut Vec<QueuedComment>, ids: &[String]) {
+    let mut ordered: Vec<QueuedComment> = ids
+        .iter()
+        .filter_map(|id| {
+            let i = queue.iter().position(|c| &c.id == id)?;
+            Some(queue.remove(i))
+        })
+        .collect();
+    ordered.append(queue);
+    *queue = ordered;
+}
+
+/// App data dir for a PR queue of `repo`, after checking the repo name.
+fn queue_dir(app: &tauri::AppHandle, repo: &str) -> Result<PathBuf, PrvwError> {
+    use tauri::Manager;
+    validate_repo(repo)?;
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?)
+}
+
+/// Load, change, and save a PR's queue under the lock. Returns the saved queue.
+pub fn modify(
+    app: &tauri::AppHandle,
+    repo: &str,
+    pr_number: u32,
+    change: impl FnOnce(&mut Vec<QueuedComment>) -> Result<(), PrvwError>,
+) -> Result<Vec<QueuedComment>, PrvwError> {
+    let repo = repo.trim();
+    let app_data_dir = queue_dir(app, repo)?;
+    let _guard = DRAFTS_LOCK
+        .lock()
+        .map_err(|_| "Comment queue lock poisoned".to_string())?;
+    let mut queue = load(&app_data_dir, repo, pr_number);
+    change(&mut queue)?;
+    save(&app_data_dir, repo, pr_number, &queue)?;
+    Ok(queue)
+}
+
+/// The queued draft comments of a PR, in submission order. Only reads: a queue file that fails
+/// to parse is left on disk rather than saved back as empty.
+#[tauri::command]
+pub async fn get_comment_queue(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+) -> Result<Vec<QueuedComment>, PrvwError> {
+    let repo = repo.trim();
+    let app_data_dir = queue_dir(&app, repo)?;
+    Ok(load(&app_data_dir, repo, pr_number))
+}
+
+/// Append draft comments to a PR's queue. `source` is "ai" or "manual" (the default).
+#[tauri::command]
+pub async fn queue_comments(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    comments: Vec<DraftComment>,
+    source: Option<String>,
+) -> Result<Vec<QueuedComment>, PrvwError> {
+    let source = match source.as_deref() {
+        Some("ai") => "ai",
+        None | Some("manual") => "manual",
+        Some(other) => {
+            return Err(PrvwError::InvalidInput(format!(
+                "Unknown comment source '{}'",
+                other
+            )))
+        }
+    };
+    modify(&app, &repo, pr_number, |queue| {
+        enqueue(queue, comments, source, now_secs());
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn edit_queued_comment(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    id: String,
+    changes: QueuedCommentEdit,
+) -> Result<Vec<QueuedComment>, PrvwError> {
+    modify(&app, &repo, pr_number, |queue| edit(queue, &id, changes))
+}
+
+/// Remove queued comments by id; with no ids, empty the queue (e.g. after submitting).
+#[tauri::command]
+pub async fn delete_queued_comments(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    ids: Option<Vec<String>>,
+) -> Result<Vec<QueuedComment>, PrvwError> {
+    modify(&app, &repo, pr_number, |queue| {
+        match &ids {
+            Some(ids) => queue.retain(|c| !ids.contains(&c.id)),
+            None => queue.clear(),
+        }
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn reorder_comment_queue(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    ids: Vec<String>,
+) -> Result<Vec<QueuedComment>, PrvwError> {
+    modify(&app, &repo, pr_number, |queue| {
+        reorder(queue, &ids);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(body: &str) -> DraftComment {
+        DraftComment {
+            hunk_id: "H1".to_string(),
+            file_path: "a.rs".to_string(),
+            line: 1,
+            side: "RIGHT".to_string(),
+            body: body.to_string(),
+            severity: "nit".to_string(),
+        }
+    }
+
+    fn ids(queue: &[QueuedComment]) -> Vec<&str> {
+        queue.iter().map(|c| c.id.as_str()).collect()
+    }
+
+    #[test]
+    fn queue_edit_and_reorder() {
+        let mut queue: Vec<QueuedComment> = Vec::new();
+        enqueue(
+            &mut queue,
+            vec![draft("a"), draft(" "), draft("b")],
+            "ai",
+            10,
+        );
+        enqueue(&mut queue, vec![draft("c")], "manual", 20);
+        assert_eq!(ids(&queue), vec!["C1", "C2", "C3"]);
+
+        edit(
+            &mut queue,
+            "C2",
+            QueuedCommentEdit {
+                body: Some("b, reworded".to_string()),
+                side: Some("LEFT".to_string()),
+                ..QueuedCommentEdit::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(queue[1].comment.body, "b, reworded");
+        assert!(edit(&mut queue, "C9", QueuedCommentEdit::default()).is_err());
+        assert!(edit(
+            &mut queue,
+            "C1",
+            QueuedCommentEdit {
+                body: Some(" ".to_string()),
+                ..QueuedCommentEdit::default()
+            }
+        )
+        .is_err());
+
+        reorder(&mut queue, &["C3".to_string(), "C9".to_string()]);
+        assert_eq!(ids(&queue), vec!["C3", "C1", "C2"]);
+    }
+
+    #[test]
+    fn queue_roundtrips_and_empty_queue_removes_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut queue = Vec::new();
+        enqueue(&mut queue, vec![draft("a")], "manual", 10);
+        save(tmp.path(), "o/r", 7, &queue).unwrap();
+        let loaded = load(tmp.path(), "o/r", 7);
+        assert_eq!(loaded[0].comment.body, "a");
+        assert_eq!(loaded[0].source, "manual");
+
+        save(tmp.path(), "o/r", 7, &[]).unwrap();
+        assert!(!queue_path(tmp.path(), "o/r", 7).exists());
+        assert!(load(tmp.path(), "o/r", 7).is_empty());
+    }
+}
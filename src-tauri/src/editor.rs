@@ -0,0 +1,172 @@
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::operations;
+use crate::settings;
+use crate::workspace;
+
+/// Used when the settings name no editor.
+const DEFAULT_COMMAND: &str = "code -g {file}:{line}";
+
+/// Split an editor command into arguments on whitespace; double quotes group an argument with
+/// spaces (`"/Applications/Sublime Text.app/..." {file}:{line}`).
+fn split_command(command: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for c in command.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    args.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        args.push(current);
+    }
+    args
+}
+
+/// Program and arguments opening `file` at `line`, from an editor command template.
+fn editor_args(command: &str, file: &Path, line: u32) -> Result<(String, Vec<String>), String> {
+    let file = file
+        .to_str()
+        .ok_or_else(|| "Non-UTF-8 file path".to_string())?;
+    let mut args = split_command(command).into_iter().map(|arg| {
+        arg.replace("{file}", file)
+            .replace("{line}", &line.to_string())
+    });
+    let program = args
+        .next()
+        .ok_or_else(|| "The editor command is empty.".to_string())?;
+    Ok((program, args.collect()))
+}
+
+/// `path` (as in the diff) under `root`, refusing paths that would leave it.
+fn resolve_path(root: &Path, path: &str) -> Result<PathBuf, PrvwError> {
+    let relative = Path::new(path.trim());
+    if relative.as_os_str().is_empty()
+        || !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(PrvwError::InvalidInput(format!(
+            "Invalid file path '{}'",
+            path
+        )));
+    }
+    Ok(root.join(relative))
+}
+
+/// Open a file of the diff in the user's editor at `line` (1-based, default 1). The file is
+/// looked up in the PR's checkout (`checkout_pr_workspace`) when there is one, else in the
+/// repo's clone from its settings. Returns the path that was opened.
+#[tauri::command]
+pub async fn open_in_editor(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: Option<u32>,
+    path: String,
+    line: Option<u32>,
+) -> Result<String, PrvwError> {
+    use tauri::Manager;
+    let repo = repo.trim();
+    validate_repo(repo)?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let root = workspace::local_root(&app_data_dir, repo, pr_number).ok_or_else(|| {
+        format!(
+            "No local copy of {}: check out the PR or set the repo's clone path first.",
+            repo
+        )
+    })?;
+    let file = resolve_path(&root, &path)?;
+    if !file.is_file() {
+        return Err(PrvwError::InvalidInput(format!(
+            "{} is not in {}",
+            path,
+            root.display()
+        )));
+    }
+
+    let command = settings::current()
+        .editor_command
+        .unwrap_or_else(|| DEFAULT_COMMAND.to_string());
+    let (program, args) = editor_args(&command, &file, line.unwrap_or(1).max(1))?;
+    let mut child = operations::spawn(
+        Command::new(&program)
+            .args(&args)
+            .current_dir(&root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null()),
+    )
+    .map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            format!(
+                "{} is not installed or not on PATH. Set the editor command in Settings.",
+                program
+            )
+        } else {
+            format!("Failed to start {}: {}", program, e)
+        }
+    })?;
+    // Launchers like `code` return right away; terminal editors live on. Reap either.
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(file.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_quoted_arguments() {
+        assert_eq!(
+            split_command(r#" "/opt/My Editor/bin/ed"  --goto {file}:{line} "" "#),
+            vec!["/opt/My Editor/bin/ed", "--goto", "{file}:{line}", ""]
+        );
+    }
+
+    #[test]
+    fn fills_in_file_and_line() {
+        let (program, args) =
+            editor_args("nvim +{line} {file}", Path::new("/src/a.rs"), 12).unwrap();
+        assert_eq!(program, "nvim");
+        assert_eq!(args, vec!["+12", "/src/a.rs"]);
+        let (program, args) = editor_args(DEFAULT_COMMAND, Path::new("/src/a.rs"), 3).unwrap();
+        assert_eq!(program, "code");
+        assert_eq!(args, vec!["-g", "/src/a.rs:3"]);
+        assert!(editor_args("  ", Path::new("/src/a.rs"), 1).is_err());
+    }
+
+    #[test]
+    fn paths_stay_inside_the_root() {
+        let root = Path::new("/work/pr-1");
+        assert_eq!(
+            resolve_path(root, "src/./lib.rs").unwrap(),
+            root.join("src/lib.rs")
+        );
+        assert!(resolve_path(root, "../secrets").is_err());
+        assert!(resolve_path(root, "/etc/passwd").is_err());
+        assert!(resolve_path(root, " ").is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::types::{AnalysisEdit, AnalysisEditState, AnalysisResult};
+
+pub use prvw_core::edits::*;
+
+/// Apply a manual edit to the PR's current analysis, record it for undo, and save the result.
+/// Edits made to an analysis the log did not produce start a new log.
+#[tauri::command]
+pub async fn edit_analysis(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, EditHistory>,
+    repo: String,
+    pr_number: u32,
+    analysis: AnalysisResult,
+    edit: AnalysisEdit,
+    head_sha: Option<String>,
+) -> Result<AnalysisEditState, PrvwError> {
+    history.edit(
+        &crate::app_data_dir(&app)?,
+        &repo,
+        pr_number,
+        analysis,
+        &edit,
+        head_sha,
+    )
+}
+
+#[tauri::command]
+pub async fn undo_analysis_edit(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, EditHistory>,
+    repo: String,
+    pr_number: u32,
+    head_sha: Option<String>,
+) -> Result<AnalysisEditState, PrvwError> {
+    history.undo(&crate::app_data_dir(&app)?, &repo, pr_number, head_sha)
+}
+
+#[tauri::command]
+pub async fn redo_analysis_edit(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, EditHistory>,
+    repo: String,
+    pr_number: u32,
+    head_sha: Option<String>,
+) -> Result<AnalysisEditState, PrvwError> {
+    history.redo(&crate::app_data_dir(&app)?, &repo, pr_number, head_sha)
+}
+
+/// The PR's saved manually edited analysis, to show instead of a fresh one. With `head_sha`,
+/// edits saved at another head are not returned (their hunk ids may no longer match).
+#[tauri::command]
+pub async fn get_edited_analysis(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    head_sha: Option<String>,
+) -> Result<Option<AnalysisResult>, PrvwError> {
+    let repo = repo.trim();
+    validate_repo(repo)?;
+    Ok(saved_analysis(
+        &crate::app_data_dir(&app)?,
+        repo,
+        pr_number,
+        head_sha.as_deref(),
+    ))
+}
+
+/// Forget the PR's edit log and saved edits, e.g. when a new analysis replaces the edited one.
+#[tauri::command]
+pub async fn clear_analysis_edits(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, EditHistory>,
+    repo: String,
+    pr_number: u32,
+) -> Result<(), PrvwError> {
+    history.clear(&crate::app_data_dir(&app)?, &repo, pr_number)
+}
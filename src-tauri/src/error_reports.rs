@@ -0,0 +1,35 @@
+use crate::error::PrvwError;
+use crate::types::ErrorReport;
+
+pub use prvw_core::error_reports::*;
+
+/// Saved error reports, newest first. Only recorded while `errorReports` is on in the settings.
+#[tauri::command]
+pub async fn list_error_reports(app: tauri::AppHandle) -> Result<Vec<ErrorReport>, PrvwError> {
+    Ok(list(&crate::app_data_dir(&app)?))
+}
+
+/// Write the saved reports (or those in `ids`) as a JSON array to `path`. Returns how many.
+#[tauri::command]
+pub async fn export_error_reports(
+    app: tauri::AppHandle,
+    path: String,
+    ids: Option<Vec<String>>,
+) -> Result<usize, PrvwError> {
+    export(&crate::app_data_dir(&app)?, &path, ids.as_deref())
+}
+
+/// URL of a new GitHub issue prefilled with the report, for the user to review and submit.
+#[tauri::command]
+pub async fn submit_error_report(app: tauri::AppHandle, id: String) -> Result<String, PrvwError> {
+    submit_url(&crate::app_data_dir(&app)?, &id)
+}
+
+/// Delete the reports in `ids`, or all of them. Returns how many were deleted.
+#[tauri::command]
+pub async fn delete_error_reports(
+    app: tauri::AppHandle,
+    ids: Option<Vec<String>>,
+) -> Result<usize, PrvwError> {
+    Ok(delete(&crate::app_data_dir(&app)?, ids.as_deref()))
+}
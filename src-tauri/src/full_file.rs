@@ -0,0 +1,455 @@
+use std::path::Path;
+
+use crate::cache;
+use crate::error::PrvwError;
+use crate::gh::{self, validate_repo};
+use crate::types::{FullFileView, Hunk, RepoContext, SideBySideRow, SideLine};
+
+fn is_sha(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Percent-encode the characters of a repo path that would break a contents API URL.
+fn encode_path(path: &str) -> String {
+    path.chars()
+        .map(|c| match c {
+            '%' => "%25".to_string(),
+            ' ' => "%20".to_string(),
+            '#' => "%23".to_string(),
+            '?' => "%3F".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Merge base and head commit of a PR: the two ends its diff was computed between.
+fn pr_shas(repo: &str, pr_number: u32) -> Result<(String, String), String> {
+    let output = gh::run_gh(
+        &[
+            "pr",
+            "view",
+            &pr_number.to_string(),
+            "-R",
+            repo,
+            "--json",
+            "baseRefOid,headRefOid",
+            "-q",
+            ".baseRefOid + \" \" + .headRefOid",
+        ],
+        "gh pr view",
+    )?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (base, head) = stdout
+        .trim()
+        .split_once(' ')
+        .ok_or_else(|| "gh pr view returned no base/head commits.".to_string())?;
+    let endpoint = format!("repos/{}/compare/{}...{}", repo, base, head);
+    let output = gh::run_gh(
+        &["api", &endpoint, "-q", ".merge_base_commit.sha"],
+        "gh api compare",
+    )?;
+    let merge_base = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((
+        if is_sha(&merge_base) {
+            merge_base
+        } else {
+            base.to_string()
+        },
+        head.to_string(),
+    ))
+}
+
+/// Contents of `path` at commit `sha`; None when the file does not exist there. Contents at
+/// a commit never change, so found files are cached.
+fn fetch_file(
+    app_data_dir: Option<&Path>,
+    repo: &str,
+    pr_number: u32,
+    sha: &str,
+    path: &str,
+) -> Result<Option<String>, String> {
+    let cache_key = format!(
+        "{}__{}_{}",
+        repo.replace('/', "__"),
+        sha,
+        cache::hash_key(path)
+    );
+    if let Some(dir) = app_data_dir {
+        if let Some(cached) = cache::read_cache::<String>(dir, "cache/file", &cache_key) {
+            return Ok(Some(cached));
+        }
+    }
+    let endpoint = format!("repos/{}/contents/{}?ref={}", repo, encode_path(path), sha);
+    let output = match gh::run_gh(
+        &["api", "-H", "Accept: application/vnd.github.raw", &endpoint],
+        "gh api contents",
+    ) {
+        Ok(output) => output,
+        Err(e) if e.message().contains("404") || e.message().contains("Not Found") => {
+            return Ok(None)
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let text = String::from_utf8_lossy(&output.stdout).to_string();
+    if let Some(dir) = app_data_dir {
+        let owner = RepoContext {
+            repo: repo.to_string(),
+            pr_number,
+        };
+        cache::write_cache_owned(dir, "cache/file", &cache_key, &text, Some(&owner));
+    }
+    Ok(Some(text))
+}
+
+fn side(lines: &[&str], number: u32, fallback: &str) -> SideLine {
+    let text = number
+        .checked_sub(1)
+        .and_then(|i| lines.get(i as usize))
+        .copied()
+        .unwrap_or(fallback);
+    SideLine {
+        number,
+        text: text.to_string(),
+    }
+}
+
+fn row(
+    kind: &str,
+    left: Option<SideLine>,
+    right: Option<SideLine>,
+    hunk_id: Option<&str>,
+) -> SideBySideRow {
+    SideBySideRow {
+        kind: kind.to_string(),
+        left,
+        right,
+        hunk_id: hunk_id.map(str::to_string),
+    }
+}
+
+/// Pair buffered removed and added lines as changes; the longer run's rest stays one-sided.
+fn flush_changes(
+    rows: &mut Vec<SideBySideRow>,
+    removed: &mut Vec<SideLine>,
+    added: &mut Vec<SideLine>,
+    hunk_id: &str,
+) {
+    let mut removed = removed.drain(..);
+    let mut added = added.drain(..);
+    loop {
+        match (removed.next(), added.next()) {
+            (Some(l), Some(r)) => rows.push(row("change", Some(l), Some(r), Some(hunk_id))),
+            (Some(l), None) => rows.push(row("remove", Some(l), None, Some(hunk_id))),
+            (None, Some(r)) => rows.push(row("add", None, Some(r), Some(hunk_id))),
+            (None, None) => break,
+        }
+    }
+}
+
+/// Pair unchanged lines up to (not including) `old_end`/`new_end`, advancing `old`/`new`.
+/// Returns true when the two sides ran out at different points, i.e. they did not line up.
+fn pair_until(
+    rows: &mut Vec<SideBySideRow>,
+    base_lines: &[&str],
+    head_lines: &[&str],
+    (old, new): (&mut u32, &mut u32),
+    old_end: u32,
+    new_end: u32,
+) -> bool {
+    while *old < old_end && *new < new_end {
+        rows.push(row(
+            "context",
+            Some(side(base_lines, *old, "")),
+            Some(side(head_lines, *new, "")),
+            None,
+        ));
+        *old += 1;
+        *new += 1;
+    }
+    let drift = *old < old_end || *new < new_end;
+    while *old < old_end {
+        rows.push(row("remove", Some(side(base_lines, *old, "")), None, None));
+        *old += 1;
+    }
+    while *new < new_end {
+        rows.push(row("add", None, Some(side(head_lines, *new, "")), None));
+        *new += 1;
+    }
+    drift
+}
+
+/// Align the base and head text of a file on its hunks. Lines between hunks are paired one to
+/// one; inside a hunk, runs of removed lines are paired with the added lines that follow them.
+/// Returns the rows and a warning for each place the hunks disagree with the file contents.
+fn align(base: &str, head: &str, hunks: &[&Hunk]) -> (Vec<SideBySideRow>, Vec<String>) {
+    let base_lines: Vec<&str> = base.lines().collect();
+    let head_lines: Vec<&str> = head.lines().collect();
+    let mut hunks: Vec<&Hunk> = hunks.to_vec();
+    hunks.sort_by_key(|h| h.old_start);
+
+    let mut rows: Vec<SideBySideRow> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+    // Next unconsumed line on each side, 1-based.
+    let (mut old, mut new) = (1u32, 1u32);
+
+    for hunk in hunks {
+        // A hunk without lines on one side sits after its start line on that side.
+        let first_old = hunk.old_start + u32::from(hunk.old_lines == 0);
+        let first_new = hunk.new_start + u32::from(hunk.new_lines == 0);
+        if first_old < old {
+            warnings.push(format!("Hunk {} overlaps the previous hunk.", hunk.id));
+            continue;
+        }
+        if pair_until(
+            &mut rows,
+            &base_lines,
+            &head_lines,
+            (&mut old, &mut new),
+            first_old,
+            first_new,
+        ) {
+            warnings.push(format!(
+                "Unchanged lines before hunk {} do not line up.",
+                hunk.id
+            ));
+        }
+
+        let mut removed: Vec<SideLine> = Vec::new();
+        let mut added: Vec<SideLine> = Vec::new();
+        let mut mismatch = false;
+        for line in &hunk.lines {
+            match (line.kind.as_str(), line.old_line, line.new_line) {
+                ("remove", Some(o), _) => removed.push(side(&base_lines, o, &line.text)),
+                ("add", _, Some(n)) => added.push(side(&head_lines, n, &line.text)),
+                ("context", Some(o), Some(n)) => {
+                    flush_changes(&mut rows, &mut removed, &mut added, &hunk.id);
+                    let (left, right) = (
+                        side(&base_lines, o, &line.text),
+                        side(&head_lines, n, &line.text),
+                    );
+                    mismatch |= left.text != line.text || right.text != line.text;
+                    rows.push(row(
+                        "context",
+                        Some(left),
+                        Some(right),
+                        Some(hunk.id.as_str()),
+                    ));
+                }
+                _ => continue,
+            }
+            if let Some(o) = line.old_line {
+                old = old.max(o + 1);
+            }
+            if let Some(n) = line.new_line {
+                new = new.max(n + 1);
+            }
+        }
+        flush_changes(&mut rows, &mut removed, &mut added, &hunk.id);
+        if mismatch {
+            warnings.push(format!(
+                "Hunk {} does not match the fetched file contents.",
+                hunk.id
+            ));
+        }
+        old = old.max(first_old);
+        new = new.max(first_new);
+    }
+
+    let (old_end, new_end) = (base_lines.len() as u32 + 1, head_lines.len() as u32 + 1);
+    if pair_until(
+        &mut rows,
+        &base_lines,
+        &head_lines,
+        (&mut old, &mut new),
+        old_end,
+        new_end,
+    ) {
+        warnings.push("Unchanged lines after the last hunk do not line up.".to_string());
+    }
+    (rows, warnings)
+}
+
+/// Base and head versions of one file, aligned on the PR's hunks for a full-file split view.
+/// `old_path` is the file's path in the base for renames. Without `base_sha`/`head_sha` the
+/// PR's merge base and head are looked up.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn get_full_file(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    file_path: String,
+    hunks_json: String,
+    old_path: Option<String>,
+    base_sha: Option<String>,
+    head_sha: Option<String>,
+) -> Result<FullFileView, PrvwError> {
+    use tauri::Manager;
+    let repo = repo.trim();
+    validate_repo(repo)?;
+    let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+    let (base_sha, head_sha) = match (base_sha, head_sha) {
+        (Some(base), Some(head)) => (base.trim().to_string(), head.trim().to_string()),
+        _ => pr_shas(repo, pr_number)?,
+    };
+    if !is_sha(&base_sha) || !is_sha(&head_sha) {
+        return Err(PrvwError::InvalidInput(
+            "Full-file views need commit SHAs.".to_string(),
+        ));
+    }
+
+    let app_data_dir = app.path().app_data_dir().ok();
+    let old_path = old_path
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .unwrap_or(&file_path);
+    let base = fetch_file(
+        app_data_dir.as_deref(),
+        repo,
+        pr_number,
+        &base_sha,
+        old_path,
+    )?;
+    let head = fetch_file(
+        app_data_dir.as_deref(),
+        repo,
+        pr_number,
+        &head_sha,
+        &file_path,
+    )?;
+    if base.is_none() && head.is_none() {
+        return Err(PrvwError::NotFound(format!(
+            "'{}' exists in neither the base nor the head commit.",
+            file_path
+        )));
+    }
+
+    let file_hunks: Vec<&Hunk> = hunks.iter().filter(|h| h.file_path == file_path).collect();
+    let (rows, warnings) = align(
+        base.as_deref().unwrap_or_default(),
+        head.as_deref().unwrap_or_default(),
+        &file_hunks,
+    );
+    Ok(FullFileView {
+        file_path,
+        base_sha,
+        head_sha,
+        base_exists: base.is_some(),
+        head_exists: head.is_some(),
+        rows,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DiffLine;
+
+    fn line(kind: &str, old_line: Option<u32>, new_line: Option<u32>, text: &str) -> DiffLine {
+        DiffLine {
+            kind: kind.to_string(),
+            old_line,
+            new_line,
+            text: text.to_string(),
+        }
+    }
+
+    fn kinds(rows: &[SideBySideRow]) -> Vec<(&str, Option<u32>, Option<u32>)> {
+        rows.iter()
+            .map(|r| {
+                (
+                    r.kind.as_str(),
+                    r.left.as_ref().map(|l| l.number),
+                    r.right.as_ref().map(|r| r.number),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn aligns_changes_inserts_and_unchanged_lines() {
+        let base = "a\nb\nc\nd\ne\n";
+        let head = "a\nB\nc\nd\nx\ny\ne\n";
+        let hunks = [
+            Hunk {
+                id: "H1".to_string(),
+                file_path: "f".to_string(),
+                subproject: None,
+                header: "@@ -1,3 +1,3 @@".to_string(),
+                old_start: 1,
+                old_lines: 3,
+                new_start: 1,
+                new_lines: 3,
+                lines: vec![
+                    line("context", Some(1), Some(1), "a"),
+                    line("remove", Some(2), None, "b"),
+                    line("add", None, Some(2), "B"),
+                    line("context", Some(3), Some(3), "c"),
+                ],
+            },
+            Hunk {
+                id: "H2".to_string(),
+                file_path: "f".to_string(),
+                subproject: None,
+                header: "@@ -4,0 +5,2 @@".to_string(),
+                old_start: 4,
+                old_lines: 0,
+                new_start: 5,
+                new_lines: 2,
+                lines: vec![
+                    line("add", None, Some(5), "x"),
+                    line("add", None, Some(6), "y"),
+                ],
+            },
+        ];
+        let (rows, warnings) = align(base, head, &hunks.iter().collect::<Vec<_>>());
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(
+            kinds(&rows),
+            vec![
+                ("context", Some(1), Some(1)),
+                ("change", Some(2), Some(2)),
+                ("context", Some(3), Some(3)),
+                ("context", Some(4), Some(4)),
+                ("add", None, Some(5)),
+                ("add", None, Some(6)),
+                ("context", Some(5), Some(7)),
+            ]
+        );
+        assert_eq!(rows[1].hunk_id.as_deref(), Some("H1"));
+        assert_eq!(rows[3].hunk_id, None);
+        assert_eq!(rows[4].right.as_ref().unwrap().text, "x");
+    }
+
+    #[test]
+    fn added_file_is_all_additions() {
+        let hunk = Hunk {
+            id: "H1".to_string(),
+            file_path: "f".to_string(),
+            subproject: None,
+            header: "@@ -0,0 +1,2 @@".to_string(),
+            old_start: 0,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 2,
+            lines: vec![
+                line("add", None, Some(1), "x"),
+                line("add", None, Some(2), "y"),
+            ],
+        };
+        let (rows, warnings) = align("", "x\ny\n", &[&hunk]);
+        assert!(warnings.is_empty());
+        assert_eq!(
+            kinds(&rows),
+            vec![("add", None, Some(1)), ("add", None, Some(2))]
+        );
+    }
+
+    #[test]
+    fn encodes_special_path_characters() {
+        assert_eq!(encode_path("docs/a b#1.md"), "docs/a%20b%231.md");
+    }
+}
@@ -0,0 +1,308 @@
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::cache;
+use crate::error::PrvwError;
+use crate::gh::{diff_cache_key, validate_repo};
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::secrets::{self, Secret};
+use crate::settings;
+use crate::types::{PrAuthor, PrListItem, RepoContext};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Largest page size Gitea allows by default (`MAX_RESPONSE_ITEMS`).
+const MAX_PAGE_LEN: u32 = 50;
+
+fn token() -> Result<String, PrvwError> {
+    secrets::require(Secret::Gitea)
+}
+
+/// API URL of `path` on the instance set in Settings.
+fn api_url(path: &str) -> Result<String, String> {
+    let base = settings::current()
+        .gitea_url
+        .ok_or_else(|| "Gitea/Forgejo URL is not set. Add it in Settings.".to_string())?;
+    Ok(format!("{}/api/v1{}", base, path))
+}
+
+fn call(request: ureq::Request, body: Option<serde_json::Value>) -> Result<String, String> {
+    let request = request.set("Authorization", &format!("token {}", token()?));
+    let response = match body {
+        Some(body) => request.send_json(body),
+        None => request.call(),
+    };
+    match response {
+        Ok(resp) => resp
+            .into_string()
+            .map_err(|e| format!("Failed to read Gitea/Forgejo response: {}", e)),
+        Err(ureq::Error::Status(401 | 403, _)) => Err(
+            "Gitea/Forgejo rejected the token. Check the access token and its scopes in Settings."
+                .to_string(),
+        ),
+        Err(ureq::Error::Status(404, _)) => {
+            Err("Not found on Gitea/Forgejo. Check the owner/repo and PR number.".to_string())
+        }
+        Err(ureq::Error::Status(code, resp)) => Err(format!(
+            "Gitea/Forgejo API returned HTTP {}: {}",
+            code,
+            resp.into_string().unwrap_or_default()
+        )),
+        Err(e) => Err(format!("Gitea/Forgejo API request failed: {}", e)),
+    }
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build()
+}
+
+#[derive(Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct Branch {
+    #[serde(rename = "ref")]
+    name: String,
+    sha: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PullRequest {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    html_url: String,
+    #[serde(default)]
+    created_at: String,
+    #[serde(default)]
+    updated_at: String,
+    user: Option<User>,
+    head: Option<Branch>,
+    base: Option<Branch>,
+    #[serde(default)]
+    merged: bool,
+    /// Only reported by newer versions; older ones mark drafts with a "WIP:" title prefix.
+    #[serde(default)]
+    draft: Option<bool>,
+}
+
+/// Gitea PR state for a `list_prs` state ("open", "closed", "merged" or "all"). Merged PRs are
+/// closed ones, filtered afterwards.
+fn api_state(state: &str) -> Result<&'static str, String> {
+    match state {
+        "open" => Ok("open"),
+        "closed" | "merged" => Ok("closed"),
+        "all" => Ok("all"),
+        _ => Err(format!("Unknown PR state '{}'", state)),
+    }
+}
+
+/// Map a Gitea/Forgejo pull request into the GitHub-shaped list item the rest of the app uses.
+fn to_list_item(pr: PullRequest) -> PrListItem {
+    let wip = ["WIP:", "[WIP]"]
+        .iter()
+        .any(|prefix| pr.title.to_uppercase().starts_with(prefix));
+    PrListItem {
+        number: pr.number,
+        is_draft: Some(pr.draft.unwrap_or(wip)),
+        title: pr.title,
+        url: pr.html_url,
+        updated_at: pr.updated_at,
+        created_at: pr.created_at,
+        author: pr.user.map(|u| PrAuthor { login: u.login }),
+        head_ref_name: pr.head.as_ref().map(|b| b.name.clone()),
+        head_ref_oid: pr.head.and_then(|b| b.sha),
+        base_ref_name: pr.base.map(|b| b.name),
+        head_repository_owner: None,
+        is_cross_repository: None,
+        review_decision: None,
+        body: pr.body.filter(|b| !b.trim().is_empty()),
+    }
+}
+
+pub fn fetch_pr_list(repo: &str, limit: u32, state: &str) -> Result<Vec<PrListItem>, String> {
+    validate_repo(repo)?;
+    let api_state = api_state(state)?;
+    let url = api_url(&format!("/repos/{}/pulls", repo))?;
+    let page_len = limit.clamp(1, MAX_PAGE_LEN);
+    let agent = agent();
+
+    let mut items: Vec<PrListItem> = Vec::new();
+    for page in 1.. {
+        let body = call(
+            agent
+                .get(&url)
+                .query("state", api_state)
+                .query("sort", "recentupdate")
+                .query("limit", &page_len.to_string())
+                .query("page", &page.to_string()),
+            None,
+        )?;
+        let prs: Vec<PullRequest> = serde_json::from_str(&body)
+            .map_err(|e| format!("Failed to parse Gitea/Forgejo response: {}", e))?;
+        let last = prs.len() < page_len as usize;
+        items.extend(
+            prs.into_iter()
+                .filter(|pr| state != "merged" || pr.merged)
+                .map(to_list_item),
+        );
+        if last || items.len() >= limit as usize {
+            break;
+        }
+    }
+    items.truncate(limit as usize);
+    Ok(items)
+}
+
+/// Fetch a PR diff through the disk cache, keyed by `updated_at` like GitHub diffs.
+pub fn fetch_pr_diff(
+    app_data_dir: Option<&Path>,
+    repo: &str,
+    pr_number: u32,
+    updated_at: Option<&str>,
+    force: bool,
+) -> Result<String, String> {
+    validate_repo(repo)?;
+    let cache_key = diff_cache_key(&format!("gitea/{}", repo), pr_number, updated_at);
+    if !force {
+        if let Some(dir) = app_data_dir {
+            if let Some(cached) = cache::read_cache::<String>(dir, "cache/diff", &cache_key) {
+                return Ok(cached);
+            }
+        }
+    }
+
+    let url = api_url(&format!("/repos/{}/pulls/{}.diff", repo, pr_number))?;
+    let diff = call(agent().get(&url), None)?;
+    if diff.trim().is_empty() {
+        return Err(t(Msg::DiffEmpty, &[]));
+    }
+
+    if let Some(dir) = app_data_dir {
+        let owner = RepoContext {
+            repo: repo.to_string(),
+            pr_number,
+        };
+        cache::write_cache_owned(dir, "cache/diff", &cache_key, &diff, Some(&owner));
+    }
+    Ok(diff)
+}
+
+/// List pull requests of a repository on the Gitea/Forgejo instance set in Settings, newest
+/// first, in the same shape as `list_prs`.
+#[tauri::command]
+pub async fn list_gitea_prs(
+    repo: String,
+    limit: u32,
+    state: String,
+) -> Result<Vec<PrListItem>, PrvwError> {
+    Ok(fetch_pr_list(repo.trim(), limit, &state)?)
+}
+
+/// The unified diff of a Gitea/Forgejo pull request, for `parse_diff`.
+#[tauri::command]
+pub async fn get_gitea_pr_diff(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    updated_at: Option<String>,
+    force: Option<bool>,
+) -> Result<String, PrvwError> {
+    use tauri::Manager;
+    let app_data_dir = app.path().app_data_dir().ok();
+    Ok(fetch_pr_diff(
+        app_data_dir.as_deref(),
+        repo.trim(),
+        pr_number,
+        updated_at.as_deref(),
+        force == Some(true),
+    )?)
+}
+
+/// Post a Markdown comment to a Gitea/Forgejo pull request. Returns the URL of the comment.
+#[tauri::command]
+pub async fn post_gitea_comment(
+    repo: String,
+    pr_number: u32,
+    body: String,
+    op_id: Option<String>,
+) -> Result<String, PrvwError> {
+    let op = Op::start(op_id, "post_gitea_comment");
+    let result: Result<String, PrvwError> = op
+        .scope(async {
+            let repo = repo.trim();
+            validate_repo(repo)?;
+            if body.trim().is_empty() {
+                return Err(PrvwError::InvalidInput(
+                    "Comment body is empty.".to_string(),
+                ));
+            }
+            op.progress(t(Msg::Posting, &[&repo, &pr_number]));
+            // PR comments go through the issue API; PRs share the issue numbering.
+            let url = api_url(&format!("/repos/{}/issues/{}/comments", repo, pr_number))?;
+            let response = call(
+                agent().post(&url),
+                Some(serde_json::json!({ "body": body })),
+            )?;
+            let comment: serde_json::Value = serde_json::from_str(&response)
+                .map_err(|e| format!("Failed to parse Gitea/Forgejo response: {}", e))?;
+            Ok(comment["html_url"].as_str().unwrap_or_default().to_string())
+        })
+        .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_pull_requests_to_list_items() {
+        let prs: Vec<PullRequest> = serde_json::from_str(
+            r#"[{
+              "number": 12,
+              "title": "WIP: Add parser",
+              "body": "Adds the parser.",
+              "html_url": "https://codeberg.org/o/r/pulls/12",
+              "created_at": "2024-03-05T12:34:56Z",
+              "updated_at": "2024-03-06T08:00:00Z",
+              "user": {"login": "ada"},
+              "head": {"ref": "feature", "sha": "0123456789abcdef"},
+              "base": {"ref": "main", "sha": "fedcba9876543210"},
+              "merged": false
+            }, {
+              "number": 13,
+              "title": "[wip] tidy",
+              "html_url": "https://codeberg.org/o/r/pulls/13",
+              "draft": false,
+              "user": null,
+              "head": null,
+              "base": null
+            }]"#,
+        )
+        .unwrap();
+        let mut items = prs.into_iter().map(to_list_item);
+        let item = items.next().unwrap();
+        assert_eq!(item.number, 12);
+        assert_eq!(item.is_draft, Some(true));
+        assert_eq!(item.author.map(|a| a.login).as_deref(), Some("ada"));
+        assert_eq!(item.head_ref_name.as_deref(), Some("feature"));
+        assert_eq!(item.head_ref_oid.as_deref(), Some("0123456789abcdef"));
+        assert_eq!(item.base_ref_name.as_deref(), Some("main"));
+        assert_eq!(item.body.as_deref(), Some("Adds the parser."));
+        // An explicit draft flag wins over the title prefix.
+        assert_eq!(items.next().unwrap().is_draft, Some(false));
+    }
+
+    #[test]
+    fn maps_list_states() {
+        assert_eq!(api_state("merged").unwrap(), "closed");
+        assert_eq!(api_state("all").unwrap(), "all");
+        assert!(api_state("draft").is_err());
+    }
+}
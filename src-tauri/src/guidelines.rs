@@ -0,0 +1,10 @@
+use crate::error::PrvwError;
+
+pub use prvw_core::guidelines::*;
+
+/// Fetch the repo's review guidelines (REVIEW.md or CONTRIBUTING.md) and return a condensed
+/// version for prompt injection, or `None` if the repo has neither file.
+#[tauri::command]
+pub async fn get_review_guidelines(repo: String) -> Result<Option<String>, PrvwError> {
+    Ok(fetch_review_guidelines(&repo)?)
+}
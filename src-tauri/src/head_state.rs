@@ -0,0 +1,610 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::cache::hash_key;
+use crate::drafts;
+use crate::error::PrvwError;
+use crate::gh::validate_repo;
+use crate::json_store;
+use crate::session::{self, is_current};
+use crate::share::{anchor_map, hunk_anchors, reanchor};
+use crate::time::now_secs;
+use crate::types::{
+    AnalysisResult, GroupOrder, HeadMigration, HeadState, Hunk, QueuedComment, ReviewMark,
+    ReviewSession,
+};
+use crate::validation::validate_analysis;
+
+/// Per-head snapshots are user data like sessions: kept outside `cache/`.
+const STATES_DIR: &str = "states";
+
+pub fn is_sha(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn state_path(app_data_dir: &Path, repo: &str, pr_number: u32, head_sha: &str) -> PathBuf {
+    app_data_dir
+        .join(STATES_DIR)
+        .join(format!("{}__{}", repo.replace('/', "__"), pr_number))
+        .join(format!("{}.json", head_sha))
+}
+
+fn load(app_data_dir: &Path, repo: &str, pr_number: u32, head_sha: &str) -> Option<HeadState> {
+    json_store::load(&state_path(app_data_dir, repo, pr_number, head_sha))
+}
+
+/// Write via a temp file and rename so a crash never leaves a truncated snapshot.
+fn save(app_data_dir: &Path, state: &HeadState) -> Result<(), String> {
+    json_store::save(
+        &state_path(app_data_dir, &state.repo, state.pr_number, &state.head_sha),
+        state,
+        "review state",
+    )
+}
+
+/// Hash of each hunk's lines: equal fingerprints mean the hunk did not change, only moved.
+fn fingerprints(hunks: &[Hunk]) -> BTreeMap<String, String> {
+    hunks
+        .iter()
+        .map(|h| {
+            let text: String = h
+                .lines
+                .iter()
+                .map(|l| {
+                    let prefix = match l.kind.as_str() {
+                        "add" => '+',
+                        "remove" => '-',
+                        _ => ' ',
+                    };
+                    format!("{}{}\n", prefix, l.text)
+                })
+                .collect();
+            (
+                h.id.clone(),
+                hash_key(&format!("{}\n{}", h.file_path, text)),
+            )
+        })
+        .collect()
+}
+
+/// Old and new start lines from a `@@ -a,b +c,d @@` header.
+fn header_starts(header: &str) -> Option<(u32, u32)> {
+    let mut parts = header.split_whitespace().skip(1);
+    let start = |part: Option<&str>, sign: char| -> Option<u32> {
+        part?.strip_prefix(sign)?.split(',').next()?.parse().ok()
+    };
+    Some((start(parts.next(), '-')?, start(parts.next(), '+')?))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn snapshot(
+    repo: &str,
+    pr_number: u32,
+    head_sha: &str,
+    hunks: &[Hunk],
+    analysis: Option<AnalysisResult>,
+    session: ReviewSession,
+    comments: Vec<QueuedComment>,
+    now: u64,
+) -> HeadState {
+    HeadState {
+        repo: repo.to_string(),
+        pr_number,
+        head_sha: head_sha.to_string(),
+        anchors: hunk_anchors(hunks),
+        fingerprints: fingerprints(hunks),
+        analysis,
+        session,
+        comments,
+        saved_at: now,
+    }
+}
+
+/// Carry `session` and `comments` (the live state, made at `old.head_sha`) and the analysis
+/// saved in `old` over to `hunks` at `head_sha`. Hunks are matched with the same re-anchoring
+/// pass as imported analyses. Review marks survive only on hunks whose lines did not change,
+/// and group marks only when all of the group's hunks did; blocking items and queued comments
+/// are never dropped, only re-pointed (or left unanchored) and reported.
+fn migrate(
+    old: &HeadState,
+    mut session: ReviewSession,
+    mut comments: Vec<QueuedComment>,
+    head_sha: &str,
+    hunks: &[Hunk],
+) -> HeadMigration {
+    let map = anchor_map(&old.anchors, hunks);
+    let new_fingerprints = fingerprints(hunks);
+    let new_hunks: HashMap<&str, &Hunk> = hunks.iter().map(|h| (h.id.as_str(), h)).collect();
+    let old_anchors: HashMap<&str, _> = old.anchors.iter().map(|a| (a.id.as_str(), a)).collect();
+    let describe = |id: &str| match old_anchors.get(id) {
+        Some(anchor) => format!("{} ({})", id, anchor.file_path),
+        None => id.to_string(),
+    };
+    let target = |id: &str| -> Option<&String> { map.get(id)?.as_ref() };
+    let unchanged = |id: &str| -> Option<&String> {
+        let new_id = target(id)?;
+        (old.fingerprints.get(id)? == new_fingerprints.get(new_id)?).then_some(new_id)
+    };
+
+    let mut not_carried: Vec<String> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+
+    let mut remapped = 0;
+    let analysis = old.analysis.clone().map(|mut analysis| {
+        remapped = reanchor(&mut analysis, &map);
+        let valid_ids: HashSet<String> = hunks.iter().map(|h| h.id.clone()).collect();
+        let validation = validate_analysis(&analysis, &valid_ids);
+        warnings.extend(validation.warnings);
+        validation.cleaned
+    });
+
+    let stamp = |mark: &ReviewMark| ReviewMark {
+        at: mark.at,
+        head_sha: Some(head_sha.to_string()),
+    };
+    let mut reviewed_hunks: BTreeMap<String, ReviewMark> = BTreeMap::new();
+    for (id, mark) in &session.reviewed_hunks {
+        if !is_current(mark, &session) {
+            continue;
+        }
+        match (unchanged(id), target(id)) {
+            (Some(new_id), _) => {
+                reviewed_hunks.insert(new_id.clone(), stamp(mark));
+            }
+            (None, Some(_)) => {
+                not_carried.push(format!("Review of hunk {}: it changed", describe(id)))
+            }
+            (None, None) => not_carried.push(format!(
+                "Review of hunk {}: it is no longer in the diff",
+                describe(id)
+            )),
+        }
+    }
+
+    // Group-level state (group marks, checked checklist items) survives only when the group
+    // still exists and none of its hunks changed; otherwise the reason it was dropped.
+    let group_unchanged = |group_id: &str| -> Result<(), String> {
+        let old_group = old
+            .analysis
+            .as_ref()
+            .and_then(|a| a.groups.iter().find(|g| g.id == group_id));
+        let still_exists = analysis
+            .as_ref()
+            .is_some_and(|a| a.groups.iter().any(|g| g.id == group_id));
+        match old_group {
+            Some(group)
+                if still_exists && group.hunk_ids.iter().all(|h| unchanged(h).is_some()) =>
+            {
+                Ok(())
+            }
+            Some(group) => Err(format!(
+                "group {} ({}): some of its hunks changed",
+                group_id, group.title
+            )),
+            None => Err(format!(
+                "group {}: no analysis was saved for the old head",
+                group_id
+            )),
+        }
+    };
+
+    let mut reviewed_groups: BTreeMap<String, ReviewMark> = BTreeMap::new();
+    for (group_id, mark) in &session.reviewed_groups {
+        if !is_current(mark, &session) {
+            continue;
+        }
+        match group_unchanged(group_id) {
+            Ok(()) => {
+                reviewed_groups.insert(group_id.clone(), stamp(mark));
+            }
+            Err(reason) => not_carried.push(format!("Review of {}", reason)),
+        }
+    }
+
+    let mut checked_items: BTreeMap<String, ReviewMark> = BTreeMap::new();
+    for (id, mark) in &session.checked_items {
+        if !is_current(mark, &session) {
+            continue;
+        }
+        let group_id = id.split_once(":checklist:").map_or(id.as_str(), |(g, _)| g);
+        match group_unchanged(group_id) {
+            Ok(()) => {
+                checked_items.insert(id.clone(), stamp(mark));
+            }
+            Err(reason) => not_carried.push(format!("Checklist item {} of {}", id, reason)),
+        }
+    }
+    let carried_marks = reviewed_hunks.len() + reviewed_groups.len() + checked_items.len();
+    session.reviewed_hunks = reviewed_hunks;
+    session.reviewed_groups = reviewed_groups;
+    session.checked_items = checked_items;
+
+    // The custom group order is about groups, not code: keep it for the groups that remain.
+    let group_order: Vec<String> = session::active_group_order(&session)
+        .iter()
+        .filter(|id| {
+            analysis
+                .as_ref()
+                .is_none_or(|a| a.groups.iter().any(|g| &g.id == *id))
+        })
+        .cloned()
+        .collect();
+    session.group_order = (!group_order.is_empty()).then(|| GroupOrder {
+        group_ids: group_order,
+        at: now_secs(),
+        head_sha: Some(head_sha.to_string()),
+    });
+    session.head_sha = Some(head_sha.to_string());
+
+    for item in &mut session.blocking_items {
+        let Some(hunk_id) = item.hunk_id.clone() else {
+            continue;
+        };
+        match map.get(&hunk_id) {
+            Some(Some(new_id)) => item.hunk_id = Some(new_id.clone()),
+            Some(None) => {
+                item.hunk_id = None;
+                warnings.push(format!(
+                    "Blocking item '{}' lost its hunk {}; it stays open.",
+                    item.text.trim(),
+                    describe(&hunk_id)
+                ));
+            }
+            None => {}
+        }
+    }
+
+    for queued in &mut comments {
+        let comment = &mut queued.comment;
+        let Some(Some(new_id)) = map.get(&comment.hunk_id) else {
+            if map.contains_key(&comment.hunk_id) {
+                warnings.push(format!(
+                    "Comment {} on {}:{} no longer matches a hunk; it will be listed in the review \
+                     body if its line is gone.",
+                    queued.id, comment.file_path, comment.line
+                ));
+            }
+            continue;
+        };
+        // Follow the hunk when it moved: shift the line by how far its start moved.
+        let starts = old_anchors
+            .get(comment.hunk_id.as_str())
+            .and_then(|a| header_starts(&a.header));
+        if let (Some((old_left, old_right)), Some(hunk)) = (starts, new_hunks.get(new_id.as_str()))
+        {
+            let (from, to) = if comment.side == "LEFT" {
+                (old_left, hunk.old_start)
+            } else {
+                (old_right, hunk.new_start)
+            };
+            comment.line = (i64::from(comment.line) + i64::from(to) - i64::from(from))
+                .try_into()
+                .unwrap_or(comment.line);
+        }
+        comment.hunk_id.clone_from(new_id);
+    }
+
+    HeadMigration {
+        from_head_sha: old.head_sha.clone(),
+        head_sha: head_sha.to_string(),
+        analysis,
+        session,
+        comments,
+        remapped,
+        carried_marks,
+        not_carried,
+        warnings,
+    }
+}
+
+/// Whether a review state was saved at `head_sha`.
+pub fn exists(app_data_dir: &Path, repo: &str, pr_number: u32, head_sha: &str) -> bool {
+    state_path(app_data_dir, repo, pr_number, head_sha).exists()
+}
+
+/// Snapshot the PR's live session and comment queue with `hunks` and `analysis` as the state
+/// at `head_sha`.
+pub fn save_current(
+    app_data_dir: &Path,
+    repo: &str,
+    pr_number: u32,
+    head_sha: &str,
+    hunks: &[Hunk],
+    analysis: Option<AnalysisResult>,
+) -> Result<(), String> {
+    let _guard = session::SESSION_LOCK
+        .lock()
+        .map_err(|_| "Session lock poisoned".to_string())?;
+    let state = snapshot(
+        repo,
+        pr_number,
+        head_sha,
+        hunks,
+        analysis,
+        session::load(app_data_dir, repo, pr_number),
+        drafts::load(app_data_dir, repo, pr_number),
+        now_secs(),
+    );
+    save(app_data_dir, &state)
+}
+
+/// Save the PR's current session, comment queue, and analysis as the state at `head_sha`, with
+/// anchors of its hunks so it can be migrated when the PR is updated.
+#[tauri::command]
+pub async fn save_head_state(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    head_sha: String,
+    hunks_json: String,
+    analysis: Option<AnalysisResult>,
+) -> Result<(), PrvwError> {
+    use tauri::Manager;
+    let repo = repo.trim();
+    let head_sha = head_sha.trim();
+    validate_repo(repo)?;
+    if !is_sha(head_sha) {
+        return Err(PrvwError::InvalidInput(
+            "Review state is keyed by a commit SHA.".to_string(),
+        ));
+    }
+    let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(save_current(
+        &app_data_dir,
+        repo,
+        pr_number,
+        head_sha,
+        &hunks,
+        analysis,
+    )?)
+}
+
+/// The review state saved at `head_sha`, if any.
+#[tauri::command]
+pub async fn get_head_state(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    head_sha: String,
+) -> Result<Option<HeadState>, PrvwError> {
+    use tauri::Manager;
+    let repo = repo.trim();
+    validate_repo(repo)?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(load(&app_data_dir, repo, pr_number, head_sha.trim()))
+}
+
+/// Carry the PR's live review state from the session's head over to `hunks` at `head_sha`,
+/// and save the result as the live state and as the state at `head_sha`.
+pub fn migrate_to(
+    app_data_dir: &Path,
+    repo: &str,
+    pr_number: u32,
+    head_sha: &str,
+    hunks: &[Hunk],
+) -> Result<HeadMigration, String> {
+    let _session_guard = session::SESSION_LOCK
+        .lock()
+        .map_err(|_| "Session lock poisoned".to_string())?;
+    let _drafts_guard = drafts::DRAFTS_LOCK
+        .lock()
+        .map_err(|_| "Comment queue lock poisoned".to_string())?;
+
+    let live = session::load(app_data_dir, repo, pr_number);
+    let from = match live.head_sha.as_deref() {
+        Some(from) if from == head_sha => {
+            return Err("The review state is already at this head.".to_string())
+        }
+        Some(from) => from.to_string(),
+        None => return Err("The review session has no head to migrate from.".to_string()),
+    };
+    let old = load(app_data_dir, repo, pr_number, &from)
+        .ok_or_else(|| format!("No review state was saved at {}.", from))?;
+
+    let migration = migrate(
+        &old,
+        live,
+        drafts::load(app_data_dir, repo, pr_number),
+        head_sha,
+        hunks,
+    );
+    session::save(app_data_dir, &migration.session)?;
+    drafts::save(app_data_dir, repo, pr_number, &migration.comments)?;
+    save(
+        app_data_dir,
+        &snapshot(
+            repo,
+            pr_number,
+            head_sha,
+            hunks,
+            migration.analysis.clone(),
+            migration.session.clone(),
+            migration.comments.clone(),
+            now_secs(),
+        ),
+    )?;
+    Ok(migration)
+}
+
+/// Carry the PR's review state over to a new head: re-anchor the analysis, review marks,
+/// blocking items, and queued comments saved at the session's previous head onto `hunks_json`,
+/// save the result as the live state and as the state at `head_sha`, and report what could not
+/// be carried over.
+#[tauri::command]
+pub async fn migrate_head_state(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    head_sha: String,
+    hunks_json: String,
+) -> Result<HeadMigration, PrvwError> {
+    use tauri::Manager;
+    let repo = repo.trim();
+    let head_sha = head_sha.trim();
+    validate_repo(repo)?;
+    if !is_sha(head_sha) {
+        return Err(PrvwError::InvalidInput(
+            "Review state is keyed by a commit SHA.".to_string(),
+        ));
+    }
+    let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(migrate_to(
+        &app_data_dir,
+        repo,
+        pr_number,
+        head_sha,
+        &hunks,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BlockingItem, DiffLine, DraftComment, IntentGroup};
+
+    fn hunk(id: &str, header: &str, new_start: u32, text: &str) -> Hunk {
+        Hunk {
+            id: id.to_string(),
+            file_path: "a.rs".to_string(),
+            subproject: None,
+            header: header.to_string(),
+            old_start: new_start,
+            old_lines: 0,
+            new_start,
+            new_lines: 1,
+            lines: vec![DiffLine {
+                kind: "add".to_string(),
+                old_line: None,
+                new_line: Some(new_start),
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn mark(head: &str) -> ReviewMark {
+        ReviewMark {
+            at: 100,
+            head_sha: Some(head.to_string()),
+        }
+    }
+
+    #[test]
+    fn parses_header_starts() {
+        assert_eq!(header_starts("@@ -10,3 +12,4 @@ fn main()"), Some((10, 12)));
+        assert_eq!(header_starts("@@ -1 +1 @@"), Some((1, 1)));
+        assert_eq!(header_starts("not a header"), None);
+    }
+
+    #[test]
+    fn migrates_unchanged_hunks_and_reports_the_rest() {
+        let old_hunks = vec![
+            hunk("H1", "@@ -1,0 +1,1 @@ fn main()", 1, "a"),
+            hunk("H2", "@@ -20,0 +20,1 @@ fn helper()", 20, "b"),
+            hunk("H3", "@@ -40,0 +40,1 @@ fn gone()", 40, "c"),
+        ];
+        // H1 is now H2 (a hunk was inserted above it); helper() moved and changed; gone() is gone
+        let new_hunks = vec![
+            hunk("H1", "@@ -0,0 +1,3 @@", 1, "new"),
+            hunk("H2", "@@ -1,0 +4,1 @@ fn main()", 4, "a"),
+            hunk("H3", "@@ -20,0 +25,1 @@ fn helper()", 25, "b, changed"),
+        ];
+        let analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![IntentGroup {
+                id: "G1".to_string(),
+                title: "Main".to_string(),
+                category: String::new(),
+                rationale: String::new(),
+                risk: "low".to_string(),
+                risk_score: 0,
+                risk_evidence: vec![],
+                hunk_ids: vec!["H1".to_string(), "H2".to_string()],
+                reviewer_checklist: vec![],
+                suggested_tests: vec![],
+            }],
+            unassigned_hunk_ids: vec!["H3".to_string()],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        };
+        let mut session = ReviewSession {
+            head_sha: Some("aaa".to_string()),
+            ..ReviewSession::default()
+        };
+        for id in ["H1", "H2", "H3"] {
+            session.reviewed_hunks.insert(id.to_string(), mark("aaa"));
+        }
+        session
+            .reviewed_groups
+            .insert("G1".to_string(), mark("aaa"));
+        session
+            .checked_items
+            .insert("G1:checklist:0".to_string(), mark("aaa"));
+        session.blocking_items.push(BlockingItem {
+            id: "G1:finding:0".to_string(),
+            kind: "finding".to_string(),
+            group_id: Some("G1".to_string()),
+            hunk_id: Some("H3".to_string()),
+            text: "Leaks a file handle".to_string(),
+            resolved: false,
+            at: 100,
+        });
+        let comments = vec![QueuedComment {
+            id: "C1".to_string(),
+            comment: DraftComment {
+                hunk_id: "H1".to_string(),
+                file_path: "a.rs".to_string(),
+                line: 1,
+                side: "RIGHT".to_string(),
+                body: "Why?".to_string(),
+                severity: "nit".to_string(),
+            },
+            source: "manual".to_string(),
+            created_at: 100,
+        }];
+        let old = snapshot(
+            "o/r",
+            7,
+            "aaa",
+            &old_hunks,
+            Some(analysis),
+            session.clone(),
+            comments.clone(),
+            100,
+        );
+
+        let migration = migrate(&old, session, comments, "bbb", &new_hunks);
+        let reviewed: Vec<&String> = migration.session.reviewed_hunks.keys().collect();
+        assert_eq!(reviewed, vec!["H2"]);
+        assert_eq!(
+            migration.session.reviewed_hunks["H2"].head_sha.as_deref(),
+            Some("bbb")
+        );
+        assert_eq!(migration.carried_marks, 1);
+        assert_eq!(migration.not_carried.len(), 4);
+        assert!(migration.not_carried[0].contains("H2 (a.rs): it changed"));
+        assert!(migration.not_carried[1].contains("H3 (a.rs): it is no longer in the diff"));
+        assert!(migration.not_carried[2].contains("group G1 (Main)"));
+        assert!(migration.not_carried[3].starts_with("Checklist item G1:checklist:0 of group G1"));
+        assert!(migration.session.checked_items.is_empty());
+
+        let analysis = migration.analysis.unwrap();
+        assert_eq!(analysis.groups[0].hunk_ids, vec!["H2", "H3"]);
+        assert_eq!(migration.session.blocking_items[0].hunk_id, None);
+        assert_eq!(migration.comments[0].comment.hunk_id, "H2");
+        assert_eq!(migration.comments[0].comment.line, 4);
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::delta::compute_delta;
+use crate::error::PrvwError;
+use crate::gh;
+use crate::head_state;
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::review_state::ReviewStates;
+use crate::run_state::RunState;
+use crate::session;
+use crate::share::{anchor_map, hunk_anchors, reanchor};
+use crate::types::{AnalysisResult, HeadChange, HeadRefresh, Hunk};
+use crate::validation::validate_analysis;
+
+const HEAD_CHANGED_EVENT: &str = "pr-head-changed";
+/// Time between checks of the open PR's head commit.
+const HEAD_INTERVAL: Duration = Duration::from_secs(60);
+/// How often a running watcher wakes up to check whether it was stopped.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The PR open in the window and the head its diff was loaded at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedHead {
+    repo: String,
+    pr_number: u32,
+    head_sha: String,
+}
+
+/// Managed state for the head watcher, holding the watched PR; every watch replaces the
+/// running thread.
+pub type HeadWatch = RunState<Option<WatchedHead>>;
+
+impl HeadWatch {
+    /// Record `head_sha` as seen so each push is reported once.
+    fn set_head(&self, run_id: u64, head_sha: &str) {
+        self.update(run_id, |target| {
+            if let Some(current) = target.as_mut() {
+                current.head_sha = head_sha.to_string();
+            }
+        });
+    }
+}
+
+/// The change to report when the PR's head is now `head_sha`, if it moved.
+fn head_change(watched: &WatchedHead, head_sha: &str) -> Option<HeadChange> {
+    (!head_sha.is_empty() && head_sha != watched.head_sha).then(|| HeadChange {
+        repo: watched.repo.clone(),
+        pr_number: watched.pr_number,
+        from_head_sha: watched.head_sha.clone(),
+        head_sha: head_sha.to_string(),
+    })
+}
+
+fn run_watch(app: &AppHandle, run_id: u64) {
+    let state = app.state::<HeadWatch>();
+    while state.is_current(run_id) {
+        let next_at = Instant::now() + HEAD_INTERVAL;
+        while Instant::now() < next_at {
+            if !state.is_current(run_id) {
+                return;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+
+        let Some(watched) = state.snapshot() else {
+            return;
+        };
+        match gh::fetch_head_sha(&watched.repo, watched.pr_number) {
+            Ok(head_sha) => {
+                if let Some(change) = head_change(&watched, &head_sha) {
+                    if !state.is_current(run_id) {
+                        return;
+                    }
+                    state.set_head(run_id, &head_sha);
+                    let _ = app.emit(HEAD_CHANGED_EVENT, change);
+                }
+            }
+            Err(e) => eprintln!(
+                "[head_watch] checking {}#{} failed: {}",
+                watched.repo, watched.pr_number, e
+            ),
+        }
+    }
+}
+
+/// Poll the head commit of the PR open in the window (loaded at `head_sha`) about once a
+/// minute, emitting `pr-head-changed` when new commits are pushed. Replaces the previous watch.
+#[tauri::command]
+pub async fn watch_pr_head(
+    app: AppHandle,
+    state: tauri::State<'_, HeadWatch>,
+    repo: String,
+    pr_number: u32,
+    head_sha: String,
+) -> Result<(), PrvwError> {
+    let repo = repo.trim().to_string();
+    let head_sha = head_sha.trim().to_string();
+    gh::validate_repo(&repo)?;
+    if !head_state::is_sha(&head_sha) {
+        return Err(PrvwError::InvalidInput(
+            "A PR head is watched from a commit SHA.".to_string(),
+        ));
+    }
+    let run_id = state.begin();
+    state.update(run_id, |target| {
+        *target = Some(WatchedHead {
+            repo,
+            pr_number,
+            head_sha,
+        });
+    });
+    std::thread::spawn(move || run_watch(&app, run_id));
+    Ok(())
+}
+
+/// Stop polling, e.g. when the window goes back to the PR list.
+#[tauri::command]
+pub async fn unwatch_pr_head(state: tauri::State<'_, HeadWatch>) -> Result<(), PrvwError> {
+    let run_id = state.begin();
+    state.update(run_id, |target| *target = None);
+    Ok(())
+}
+
+/// `analysis` of `old_hunks` re-anchored on `hunks`; hunks it doesn't cover end up unassigned.
+fn carry_analysis(
+    old_hunks: &[Hunk],
+    mut analysis: AnalysisResult,
+    hunks: &[Hunk],
+) -> AnalysisResult {
+    reanchor(&mut analysis, &anchor_map(&hunk_anchors(old_hunks), hunks));
+    let valid_ids: HashSet<String> = hunks.iter().map(|h| h.id.clone()).collect();
+    validate_analysis(&analysis, &valid_ids).cleaned
+}
+
+/// Re-fetch the PR at `head_sha` (its current head when omitted) after a push, hold the new diff
+/// as review state, carry the review from the session's previous head over to it, and compare
+/// the re-anchored analysis with the one of `session_id` so only the new hunks need analyzing.
+/// The old review state stays open; the caller closes it.
+#[tauri::command]
+pub async fn refresh_pr_head(
+    app: AppHandle,
+    states: tauri::State<'_, ReviewStates>,
+    repo: String,
+    pr_number: u32,
+    session_id: String,
+    head_sha: Option<String>,
+    op_id: Option<String>,
+) -> Result<HeadRefresh, PrvwError> {
+    let op = Op::start(op_id, "refresh_pr_head");
+    let result: Result<HeadRefresh, PrvwError> = op
+        .scope(async {
+            let repo = repo.trim();
+            gh::validate_repo(repo)?;
+            let app_data_dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+            let (old_hunks, old_analysis) = states.review(&session_id)?;
+            let head_sha = match head_sha.map(|sha| sha.trim().to_string()) {
+                Some(sha) if !sha.is_empty() => sha,
+                _ => gh::fetch_head_sha(repo, pr_number)?,
+            };
+            if !head_state::is_sha(&head_sha) {
+                return Err(PrvwError::InvalidInput(
+                    "A PR is refreshed to a commit SHA.".to_string(),
+                ));
+            }
+
+            op.progress(t(Msg::FetchingDiff, &[&repo, &pr_number]));
+            // Keyed by the head, so the diff cached for the old one is not served.
+            let diff =
+                gh::fetch_pr_diff(Some(&app_data_dir), repo, pr_number, Some(&head_sha), false)?;
+            op.progress(t(Msg::ParsingDiff, &[]));
+            let review = states.open(diff, Some(repo.to_string()))?;
+            let hunks = &review.diff.hunks;
+
+            // With review progress at an older head, carry it (and the analysis) over; the state at
+            // that head is saved first when the window never did.
+            let migration = match session::load(&app_data_dir, repo, pr_number).head_sha {
+                Some(from) if from != head_sha => {
+                    op.progress(t(Msg::CarryingReview, &[]));
+                    if !head_state::exists(&app_data_dir, repo, pr_number, &from) {
+                        head_state::save_current(
+                            &app_data_dir,
+                            repo,
+                            pr_number,
+                            &from,
+                            &old_hunks,
+                            old_analysis.clone(),
+                        )?;
+                    }
+                    Some(head_state::migrate_to(
+                        &app_data_dir,
+                        repo,
+                        pr_number,
+                        &head_sha,
+                        hunks,
+                    )?)
+                }
+                _ => None,
+            };
+            let analysis = match &migration {
+                Some(migration) => migration.analysis.clone(),
+                None => old_analysis
+                    .clone()
+                    .map(|analysis| carry_analysis(&old_hunks, analysis, hunks)),
+            };
+            let delta = old_analysis
+                .as_ref()
+                .zip(analysis.as_ref())
+                .map(|(old, new)| compute_delta(&old_hunks, old, hunks, new));
+            if let Some(analysis) = &analysis {
+                states.set_analysis(&review.session_id, analysis)?;
+            }
+            Ok(HeadRefresh {
+                head_sha,
+                review,
+                analysis,
+                migration,
+                delta,
+            })
+        })
+        .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DiffLine, IntentGroup};
+
+    fn hunk(id: &str, header: &str, text: &str) -> Hunk {
+        Hunk {
+            id: id.to_string(),
+            file_path: "a.rs".to_string(),
+            subproject: None,
+            header: header.to_string(),
+            old_start: 1,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 1,
+            lines: vec![DiffLine {
+                kind: "add".to_string(),
+                old_line: None,
+                new_line: Some(1),
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn reports_only_moved_heads() {
+        let watched = WatchedHead {
+            repo: "o/r".to_string(),
+            pr_number: 7,
+            head_sha: "aaa".to_string(),
+        };
+        assert_eq!(head_change(&watched, "aaa"), None);
+        assert_eq!(head_change(&watched, ""), None);
+        assert_eq!(
+            head_change(&watched, "bbb"),
+            Some(HeadChange {
+                repo: "o/r".to_string(),
+                pr_number: 7,
+                from_head_sha: "aaa".to_string(),
+                head_sha: "bbb".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn carries_analysis_and_leaves_new_hunks_unassigned() {
+        let old_hunks = vec![hunk("H1", "@@ -1,0 +1,1 @@ fn main()", "a")];
+        // A hunk was inserted above main(), so its id moved to H2.
+        let hunks = vec![
+            hunk("H1", "@@ -0,0 +1,1 @@", "new"),
+            hunk("H2", "@@ -1,0 +2,1 @@ fn main()", "a"),
+        ];
+        let analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![IntentGroup {
+                id: "G1".to_string(),
+                title: "Main".to_string(),
+                category: String::new(),
+                rationale: String::new(),
+                risk: "low".to_string(),
+                risk_score: 0,
+                risk_evidence: vec![],
+                hunk_ids: vec!["H1".to_string()],
+                reviewer_checklist: vec![],
+                suggested_tests: vec![],
+            }],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        };
+        let carried = carry_analysis(&old_hunks, analysis.clone(), &hunks);
+        assert_eq!(carried.groups[0].hunk_ids, vec!["H2"]);
+        assert_eq!(carried.unassigned_hunk_ids, vec!["H1"]);
+
+        let delta = compute_delta(&old_hunks, &analysis, &hunks, &carried);
+        assert_eq!(delta.groups[0].status, "unchanged");
+        assert_eq!(delta.new_hunk_ids, vec!["H1"]);
+    }
+}
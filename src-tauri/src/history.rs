@@ -0,0 +1,19 @@
+use crate::error::PrvwError;
+use crate::types::{CodexRun, CodexRunSummary};
+
+pub use prvw_core::history::*;
+
+/// Recorded runs, newest first (at most `limit`, default 50), without prompts and output.
+#[tauri::command]
+pub async fn list_codex_runs(
+    app: tauri::AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<CodexRunSummary>, PrvwError> {
+    Ok(list_runs(&crate::app_data_dir(&app)?, limit.unwrap_or(50)))
+}
+
+/// One recorded run with its arguments, prompt, warnings, and (truncated) output.
+#[tauri::command]
+pub async fn get_codex_run(app: tauri::AppHandle, id: String) -> Result<CodexRun, PrvwError> {
+    get_run(&crate::app_data_dir(&app)?, &id)
+}
@@ -0,0 +1,308 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+use crate::error::PrvwError;
+use crate::types::{AnalysisResult, CallSite, Hunk, ImpactResponse, SymbolImpact};
+use crate::workspace::{self, GrepHit};
+
+/// Distinct changed functions searched per run; the rest are skipped.
+const MAX_SYMBOLS: usize = 40;
+/// Call sites listed per symbol.
+const MAX_CALL_SITES: usize = 20;
+/// Raw `git grep` hits read per symbol, before definitions are filtered out.
+const MAX_GREP_HITS: usize = 200;
+/// Names too short or too generic to grep for meaningfully.
+const MIN_SYMBOL_LEN: usize = 3;
+const COMMON_NAMES: &[&str] = &[
+    "new",
+    "main",
+    "default",
+    "init",
+    "from",
+    "into",
+    "drop",
+    "fmt",
+    "clone",
+    "run",
+    "get",
+    "set",
+    "render",
+    "setup",
+    "constructor",
+];
+
+/// Function definitions in Rust, JS/TS (declarations and arrow-function bindings), Python, and Go.
+static DEFINITION_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r"\bfn\s+([A-Za-z_]\w*)|\bfunction\s*\*?\s*([A-Za-z_$][\w$]*)|\bdef\s+([A-Za-z_]\w*)|\bfunc\s+(?:\([^)]*\)\s*)?([A-Za-z_]\w*)|\b(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*=\s*(?:async\s*)?(?:\([^)]*\)|[A-Za-z_$][\w$]*)\s*=>",
+    )
+    .expect("invalid definition regex")
+});
+
+fn definitions(text: &str) -> impl Iterator<Item = &str> {
+    DEFINITION_RE
+        .captures_iter(text)
+        .filter_map(|caps| caps.iter().skip(1).flatten().next().map(|m| m.as_str()))
+}
+
+fn is_searchable(symbol: &str) -> bool {
+    symbol.len() >= MIN_SYMBOL_LEN && !COMMON_NAMES.contains(&symbol)
+}
+
+/// Functions a hunk modifies: those defined on its added/removed lines, plus the enclosing
+/// function git names in the hunk header (`@@ -1,3 +1,4 @@ fn parse(`).
+pub fn changed_symbols(hunk: &Hunk) -> Vec<String> {
+    let header_context = hunk.header.rsplit("@@").next().unwrap_or_default();
+    let changed_lines = hunk
+        .lines
+        .iter()
+        .filter(|l| l.kind != "context")
+        .map(|l| l.text.as_str());
+
+    let mut seen: HashSet<&str> = HashSet::new();
+    std::iter::once(header_context)
+        .chain(changed_lines)
+        .flat_map(definitions)
+        .filter(|s| is_searchable(s) && seen.insert(*s))
+        .map(String::from)
+        .collect()
+}
+
+/// `git grep -E` pattern for calls of `symbol`: the name as a whole word followed by `(`.
+fn call_pattern(symbol: &str) -> String {
+    format!("(^|[^A-Za-z0-9_$]){}[[:space:]]*\\(", regex::escape(symbol))
+}
+
+/// Call sites of `symbol` (capped at `MAX_CALL_SITES`) and whether more were found.
+fn search_call_sites(
+    symbol: &str,
+    added_lines: &HashSet<(&str, u32)>,
+    grep: impl Fn(&str) -> Result<Vec<GrepHit>, String>,
+) -> Result<(Vec<CallSite>, bool), String> {
+    let mut sites: Vec<CallSite> = grep(&call_pattern(symbol))?
+        .into_iter()
+        .filter(|(_, _, text)| !definitions(text).any(|d| d == symbol))
+        .map(|(file_path, line, text)| CallSite {
+            in_diff: added_lines.contains(&(file_path.as_str(), line)),
+            file_path,
+            line,
+            text: text.trim().to_string(),
+        })
+        .collect();
+    let truncated = sites.len() > MAX_CALL_SITES;
+    sites.truncate(MAX_CALL_SITES);
+    Ok((sites, truncated))
+}
+
+/// Find call sites of the functions each group changes. `grep` runs a `git grep -E` pattern
+/// over the PR head; definitions of the symbol itself are not reported as call sites.
+pub fn find_impacts(
+    hunks: &[Hunk],
+    analysis: &AnalysisResult,
+    grep: impl Fn(&str) -> Result<Vec<GrepHit>, String>,
+) -> Result<Vec<SymbolImpact>, String> {
+    let hunks_by_id: HashMap<&str, &Hunk> = hunks.iter().map(|h| (h.id.as_str(), h)).collect();
+    let added_lines: HashSet<(&str, u32)> = hunks
+        .iter()
+        .flat_map(|h| {
+            h.lines
+                .iter()
+                .filter(|l| l.kind == "add")
+                .filter_map(move |l| Some((h.file_path.as_str(), l.new_line?)))
+        })
+        .collect();
+
+    let mut sites_by_symbol: HashMap<String, (Vec<CallSite>, bool)> = HashMap::new();
+    let mut impacts = Vec::new();
+
+    for group in &analysis.groups {
+        // symbol -> hunks of this group that change it, in first-seen order
+        let mut symbols: Vec<(String, Vec<String>)> = Vec::new();
+        for hid in &group.hunk_ids {
+            let Some(hunk) = hunks_by_id.get(hid.as_str()) else {
+                continue;
+            };
+            for symbol in changed_symbols(hunk) {
+                match symbols.iter_mut().find(|(s, _)| *s == symbol) {
+                    Some((_, ids)) => ids.push(hid.clone()),
+                    None => symbols.push((symbol, vec![hid.clone()])),
+                }
+            }
+        }
+
+        for (symbol, hunk_ids) in symbols {
+            let full = sites_by_symbol.len() >= MAX_SYMBOLS;
+            let (call_sites, truncated) = match sites_by_symbol.entry(symbol.clone()) {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(_) if full => continue,
+                Entry::Vacant(e) => e.insert(search_call_sites(&symbol, &added_lines, &grep)?),
+            };
+            if call_sites.is_empty() {
+                continue;
+            }
+            impacts.push(SymbolImpact {
+                group_id: group.id.clone(),
+                symbol,
+                hunk_ids,
+                call_sites: call_sites.clone(),
+                truncated: *truncated,
+            });
+        }
+    }
+
+    Ok(impacts)
+}
+
+/// Full-repo context pass: check out the PR head and list, per intent group, the call sites
+/// of the functions it changes, so reviewers can judge the blast radius.
+#[tauri::command]
+pub async fn analyze_impact(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    hunks_json: String,
+    analysis: AnalysisResult,
+) -> Result<ImpactResponse, PrvwError> {
+    use tauri::Manager;
+    let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    let (checkout, head_sha) =
+        workspace::prepare_pr_checkout(&app_data_dir, repo.trim(), pr_number)?;
+    let impacts = find_impacts(&hunks, &analysis, |pattern| {
+        workspace::grep_checkout(&checkout, pattern, MAX_GREP_HITS)
+    })?;
+
+    Ok(ImpactResponse { head_sha, impacts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DiffLine, IntentGroup};
+
+    fn hunk(id: &str, file_path: &str, header: &str, lines: &[(&str, u32, &str)]) -> Hunk {
+        Hunk {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            subproject: None,
+            header: header.to_string(),
+            old_start: 1,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 0,
+            lines: lines
+                .iter()
+                .map(|(kind, line, text)| DiffLine {
+                    kind: kind.to_string(),
+                    old_line: None,
+                    new_line: Some(*line),
+                    text: text.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn group(id: &str, hunk_ids: &[&str]) -> IntentGroup {
+        IntentGroup {
+            id: id.to_string(),
+            title: id.to_string(),
+            category: String::new(),
+            rationale: String::new(),
+            risk: "low".to_string(),
+            risk_score: 0,
+            risk_evidence: vec![],
+            hunk_ids: hunk_ids.iter().map(ToString::to_string).collect(),
+            reviewer_checklist: vec![],
+            suggested_tests: vec![],
+        }
+    }
+
+    #[test]
+    fn changed_symbols_from_header_and_lines() {
+        let h = hunk(
+            "H1",
+            "src/lib.rs",
+            "@@ -10,3 +10,4 @@ pub fn parse_diff(raw: String) -> ParsedDiff {",
+            &[
+                ("context", 10, "    let x = 1;"),
+                ("add", 11, "fn helper_one() {}"),
+                ("remove", 0, "fn new() -> Self {"),
+                ("add", 12, "export const loadPrs = async (repo) => {"),
+                ("context", 13, "fn untouched() {}"),
+            ],
+        );
+        assert_eq!(
+            changed_symbols(&h),
+            vec!["parse_diff", "helper_one", "loadPrs"]
+        );
+    }
+
+    #[test]
+    fn call_pattern_escapes_symbol() {
+        assert_eq!(
+            call_pattern("$get"),
+            "(^|[^A-Za-z0-9_$])\\$get[[:space:]]*\\("
+        );
+    }
+
+    #[test]
+    fn impacts_skip_definitions_and_mark_changed_lines() {
+        let hunks = vec![hunk(
+            "H1",
+            "src/parse.rs",
+            "@@ -1,2 +1,3 @@",
+            &[
+                ("add", 2, "pub fn parse_diff(raw: String) {"),
+                ("add", 9, "    parse_diff(s);"),
+            ],
+        )];
+        let analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![group("G1", &["H1"])],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        };
+        let grep = |pattern: &str| {
+            assert!(pattern.contains("parse_diff"));
+            Ok(vec![
+                (
+                    "src/parse.rs".to_string(),
+                    2,
+                    "pub fn parse_diff(raw: String) {".to_string(),
+                ),
+                (
+                    "src/parse.rs".to_string(),
+                    9,
+                    "    parse_diff(s);".to_string(),
+                ),
+                (
+                    "src/main.rs".to_string(),
+                    4,
+                    "parse_diff(input)".to_string(),
+                ),
+            ])
+        };
+        let impacts = find_impacts(&hunks, &analysis, grep).expect("impacts");
+        assert_eq!(impacts.len(), 1);
+        assert_eq!(impacts[0].group_id, "G1");
+        assert_eq!(impacts[0].hunk_ids, vec!["H1"]);
+        let sites: Vec<(&str, u32, bool)> = impacts[0]
+            .call_sites
+            .iter()
+            .map(|c| (c.file_path.as_str(), c.line, c.in_diff))
+            .collect();
+        assert_eq!(
+            sites,
+            vec![("src/parse.rs", 9, true), ("src/main.rs", 4, false)]
+        );
+        assert!(!impacts[0].truncated);
+    }
+}
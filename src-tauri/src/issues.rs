@@ -0,0 +1,195 @@
+use crate::error::PrvwError;
+use crate::gh::{self, validate_repo};
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::settings;
+use crate::types::{FollowUpIssue, FollowUpItem};
+
+/// GitHub caps titles at 256 characters; keep them readable in issue lists.
+const MAX_TITLE_CHARS: usize = 80;
+
+fn issue_title(item: &FollowUpItem) -> String {
+    let title = item
+        .title
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| item.text.trim().lines().next().unwrap_or_default().trim());
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return title.to_string();
+    }
+    let cut: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Link to a file (and line) at the PR head on the configured GitHub host.
+fn file_link(host: &str, repo: &str, head_sha: &str, path: &str, line: Option<u32>) -> String {
+    let anchor = line.map(|l| format!("#L{}", l)).unwrap_or_default();
+    let label = line.map_or_else(|| path.to_string(), |l| format!("{}:{}", path, l));
+    format!(
+        "[`{}`](https://{}/{}/blob/{}/{}{})",
+        label, host, repo, head_sha, path, anchor
+    )
+}
+
+fn issue_body(
+    item: &FollowUpItem,
+    repo: &str,
+    pr_number: u32,
+    host: &str,
+    head_sha: Option<&str>,
+) -> String {
+    let mut md = String::new();
+    md.push_str(item.text.trim());
+    md.push_str("\n\n---\n");
+    let kind = if item.kind == "question" {
+        "an open question"
+    } else {
+        "a finding"
+    };
+    md.push_str(&format!(
+        "Follow-up to {} raised while reviewing {}#{}.\n",
+        kind, repo, pr_number
+    ));
+    if let Some(path) = item.file_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        let file = match head_sha {
+            Some(sha) => file_link(host, repo, sha, path.trim(), item.line),
+            None => format!("`{}`", path.trim()),
+        };
+        md.push_str(&format!("\nFile: {}\n", file));
+    }
+    md
+}
+
+/// Turn questions/findings that are out of scope for the PR into GitHub issues linking back
+/// to it and to the files involved. With `dry_run`, only the drafted issues are returned.
+/// Items are created independently; a failed one carries its error instead of a URL.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_follow_up_issues(
+    repo: String,
+    pr_number: u32,
+    items: Vec<FollowUpItem>,
+    head_sha: Option<String>,
+    labels: Option<Vec<String>>,
+    dry_run: Option<bool>,
+    op_id: Option<String>,
+) -> Result<Vec<FollowUpIssue>, PrvwError> {
+    let op = Op::start(op_id, "create_follow_up_issues");
+    let result: Result<Vec<FollowUpIssue>, PrvwError> = op
+        .scope(async {
+            let repo = repo.trim();
+            validate_repo(repo)?;
+            let items: Vec<FollowUpItem> = items
+                .into_iter()
+                .filter(|i| !i.text.trim().is_empty())
+                .collect();
+            if items.is_empty() {
+                return Err(PrvwError::InvalidInput(
+                    "No items to create issues for.".to_string(),
+                ));
+            }
+            let dry_run = dry_run == Some(true);
+
+            let head_sha = match head_sha.filter(|s| !s.trim().is_empty()) {
+                Some(sha) => Some(sha),
+                None if items.iter().any(|i| i.file_path.is_some()) => {
+                    gh::fetch_head_sha(repo, pr_number).ok()
+                }
+                None => None,
+            };
+            let host = settings::current()
+                .gh_host
+                .unwrap_or_else(|| "github.com".to_string());
+            // Unknown labels make `gh issue create` fail; keep only the ones the repo has.
+            let labels: Vec<String> = match labels.filter(|l| !l.is_empty()) {
+                Some(wanted) => {
+                    let existing = gh::fetch_repo_labels(repo)?;
+                    wanted
+                        .iter()
+                        .filter_map(|l| existing.iter().find(|e| e.eq_ignore_ascii_case(l.trim())))
+                        .cloned()
+                        .collect()
+                }
+                None => Vec::new(),
+            };
+
+            if !dry_run {
+                op.progress(t(Msg::Posting, &[&repo, &pr_number]));
+            }
+            Ok(items
+                .iter()
+                .map(|item| {
+                    let title = issue_title(item);
+                    let body = issue_body(item, repo, pr_number, &host, head_sha.as_deref());
+                    let (url, error) = if dry_run {
+                        (None, None)
+                    } else {
+                        match gh::create_issue(repo, &title, &body, &labels) {
+                            Ok(url) => (Some(url), None),
+                            Err(e) => (None, Some(e.message())),
+                        }
+                    };
+                    FollowUpIssue {
+                        title,
+                        body,
+                        url,
+                        error,
+                    }
+                })
+                .collect())
+        })
+        .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str) -> FollowUpItem {
+        FollowUpItem {
+            kind: "finding".to_string(),
+            text: text.to_string(),
+            title: None,
+            group_id: None,
+            file_path: Some("src/auth.rs".to_string()),
+            line: Some(42),
+        }
+    }
+
+    #[test]
+    fn titles_default_to_the_first_line_and_are_truncated() {
+        assert_eq!(
+            issue_title(&item("Token is never rotated\nDetails")),
+            "Token is never rotated"
+        );
+        let long = issue_title(&item(&"word ".repeat(40)));
+        assert_eq!(long.chars().count(), MAX_TITLE_CHARS);
+        assert!(long.ends_with('…'));
+        let titled = FollowUpItem {
+            title: Some(" Rotate tokens ".to_string()),
+            ..item("Token is never rotated")
+        };
+        assert_eq!(issue_title(&titled), "Rotate tokens");
+    }
+
+    #[test]
+    fn body_links_back_to_pr_and_file() {
+        let body = issue_body(
+            &item("Token is never rotated"),
+            "o/r",
+            7,
+            "github.com",
+            Some("abc123"),
+        );
+        assert!(body.starts_with("Token is never rotated\n"));
+        assert!(body.contains("a finding raised while reviewing o/r#7"));
+        assert!(
+            body.contains("[`src/auth.rs:42`](https://github.com/o/r/blob/abc123/src/auth.rs#L42)")
+        );
+
+        let body = issue_body(&item("Why?"), "o/r", 7, "github.com", None);
+        assert!(body.contains("File: `src/auth.rs`"));
+    }
+}
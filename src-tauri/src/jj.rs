@@ -0,0 +1,219 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::PrvwError;
+use crate::i18n::{t, Msg};
+use crate::local_range;
+use crate::operations;
+use crate::types::{ChangedFile, RangeCommit, RangeDiff};
+
+/// Revision diffed when none is given: the working-copy change.
+const DEFAULT_REVSET: &str = "@";
+/// `jj log` template: one `commit_id<TAB>first line of the description` line per change.
+const LOG_TEMPLATE: &str = r#"commit_id ++ "\t" ++ description.first_line() ++ "\n""#;
+
+/// Run jj in the repo at `dir` without pager or color, returning stdout.
+fn run_jj(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = operations::output(
+        Command::new("jj")
+            .arg("-R")
+            .arg(dir)
+            .args(["--no-pager", "--color=never"])
+            .args(args),
+    )
+    .map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            t(Msg::JjNotInstalled, &[])
+        } else {
+            format!("Failed to execute jj: {}", e)
+        }
+    })?;
+    if !output.status.success() {
+        return Err(format!(
+            "jj {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn check_revset(revset: &str) -> Result<&str, PrvwError> {
+    let revset = revset.trim();
+    if revset.is_empty() {
+        return Ok(DEFAULT_REVSET);
+    }
+    if revset.starts_with('-') || revset.contains('\n') {
+        return Err(PrvwError::InvalidInput(format!(
+            "Invalid revset '{}'",
+            revset
+        )));
+    }
+    Ok(revset)
+}
+
+/// Changed files of a `--git` diff, from its per-file headers.
+fn changed_files(patch: &str) -> Vec<ChangedFile> {
+    let mut files: Vec<ChangedFile> = Vec::new();
+    for line in patch.lines() {
+        if let Some(paths) = line.strip_prefix("diff --git a/") {
+            let (old, new) = paths.split_once(" b/").unwrap_or((paths, paths));
+            files.push(ChangedFile {
+                path: new.to_string(),
+                old_path: (old != new).then(|| old.to_string()),
+                status: if old == new { "modified" } else { "renamed" }.to_string(),
+                binary: false,
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("new file mode") {
+            file.status = "added".to_string();
+        } else if line.starts_with("deleted file mode") {
+            file.status = "deleted".to_string();
+        } else if line.starts_with("copy from ") {
+            file.status = "copied".to_string();
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.binary = true;
+        }
+    }
+    files
+}
+
+fn parse_log(log: &str) -> Vec<RangeCommit> {
+    log.lines()
+        .filter_map(|line| {
+            let (sha, subject) = line.split_once('\t')?;
+            Some(RangeCommit {
+                sha: sha.trim().to_string(),
+                subject: if subject.trim().is_empty() {
+                    "(no description set)".to_string()
+                } else {
+                    subject.trim().to_string()
+                },
+            })
+        })
+        .collect()
+}
+
+/// GitHub repos of the `origin` and `upstream` remotes in `jj git remote list` output
+/// (`<name> <url>` per line).
+fn parse_remotes(list: &str) -> (Option<String>, Option<String>) {
+    let url = |name: &str| {
+        list.lines().find_map(|line| {
+            let (remote, url) = line.split_once(' ')?;
+            (remote == name)
+                .then(|| local_range::github_repo(url))
+                .flatten()
+        })
+    };
+    (url("origin"), url("upstream"))
+}
+
+/// The diff of a Jujutsu change (or revset of changes, `@` by default) in a jj-managed repo,
+/// shaped like `get_range_diff` so it goes through `parse_diff` and analysis the same way.
+#[tauri::command]
+pub async fn get_jj_diff(
+    repo_path: String,
+    revset: Option<String>,
+) -> Result<RangeDiff, PrvwError> {
+    let dir = Path::new(repo_path.trim());
+    if !dir.is_dir() {
+        return Err(PrvwError::InvalidInput(format!(
+            "Not a directory: {}",
+            dir.display()
+        )));
+    }
+    let revset = check_revset(revset.as_deref().unwrap_or_default())?;
+    let root = run_jj(dir, &["root"])?.trim().to_string();
+    let patch = run_jj(dir, &["diff", "--git", "-r", revset])?;
+    let files = changed_files(&patch);
+    if files.is_empty() {
+        return Err(PrvwError::NotFound(format!("No changes in '{}'", revset)));
+    }
+    let commits = parse_log(&run_jj(
+        dir,
+        &["log", "--no-graph", "-r", revset, "-T", LOG_TEMPLATE],
+    )?);
+    // Remotes are optional: a repo without any only loses per-repo settings.
+    let (origin, upstream) = run_jj(dir, &["git", "remote", "list"])
+        .map(|list| parse_remotes(&list))
+        .unwrap_or_default();
+    let remotes = local_range::prefer_upstream(origin, upstream);
+    Ok(RangeDiff {
+        repo_path: root,
+        range: revset.to_string(),
+        repo: remotes.repo,
+        fork: remotes.fork,
+        description: local_range::describe(revset, &commits),
+        commits,
+        files,
+        diff: patch,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_files_from_git_diffs() {
+        let patch = "diff --git a/src/new.rs b/src/new.rs
+new file mode 100644
+index 0000000000..1111111111
+--- /dev/null
++++ b/src/new.rs
+@@ -0,0 +1 @@
++fn main() {}
+diff --git a/old.txt b/renamed.txt
+rename from old.txt
+rename to renamed.txt
+diff --git a/logo.png b/logo.png
+deleted file mode 100644
+Binary files a/logo.png and /dev/null differ
+";
+        let changed = changed_files(patch);
+        let files: Vec<(&str, Option<&str>, &str, bool)> = changed
+            .iter()
+            .map(|f| {
+                (
+                    f.path.as_str(),
+                    f.old_path.as_deref(),
+                    f.status.as_str(),
+                    f.binary,
+                )
+            })
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("src/new.rs", None, "added", false),
+                ("renamed.txt", Some("old.txt"), "renamed", false),
+                ("logo.png", None, "deleted", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_log_and_remotes() {
+        let commits = parse_log("0123abcd\tFix the parser\nfedc9876\t\n");
+        assert_eq!(commits[0].subject, "Fix the parser");
+        assert_eq!(commits[1].subject, "(no description set)");
+
+        let (origin, upstream) = parse_remotes(
+            "origin git@github.com:me/repo.git\nupstream https://github.com/owner/repo\n",
+        );
+        assert_eq!(origin.as_deref(), Some("me/repo"));
+        assert_eq!(upstream.as_deref(), Some("owner/repo"));
+        assert_eq!(parse_remotes(""), (None, None));
+    }
+
+    #[test]
+    fn validates_revsets() {
+        assert_eq!(check_revset(" ").unwrap(), "@");
+        assert_eq!(check_revset("trunk()..@").unwrap(), "trunk()..@");
+        assert!(check_revset("--config=x").is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use crate::error::PrvwError;
+use crate::gh;
+use crate::settings;
+use crate::types::{AnalysisResult, LabelResult};
+
+/// Labels for the analysis' group categories, in group order without duplicates. With no
+/// mapping each category maps to a label of the same name; an empty label skips the category.
+fn mapped_labels(
+    analysis: &AnalysisResult,
+    mapping: &Option<HashMap<String, String>>,
+) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for group in &analysis.groups {
+        let label = match mapping {
+            Some(m) => m.get(&group.category).map(String::as_str),
+            None => Some(group.category.as_str()),
+        };
+        if let Some(label) = label.map(str::trim).filter(|l| !l.is_empty()) {
+            if !labels.iter().any(|l| l == label) {
+                labels.push(label.to_string());
+            }
+        }
+    }
+    labels
+}
+
+/// Split `wanted` into labels that exist in the repo (with the repo's spelling, matched
+/// case-insensitively like GitHub does) and labels that do not.
+fn split_existing(wanted: Vec<String>, existing: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut found: Vec<String> = Vec::new();
+    let mut missing: Vec<String> = Vec::new();
+    for label in wanted {
+        match existing.iter().find(|e| e.eq_ignore_ascii_case(&label)) {
+            Some(e) if !found.contains(e) => found.push(e.clone()),
+            Some(_) => {}
+            None => missing.push(label),
+        }
+    }
+    (found, missing)
+}
+
+/// Label the PR from its analysis: map group categories to repository labels and add them via
+/// `gh pr edit --add-label`. Without a `mapping`, the repo's saved label mapping is used, if any.
+/// Labels missing from the repo are reported, not created.
+#[tauri::command]
+pub async fn apply_category_labels(
+    repo: String,
+    pr_number: u32,
+    analysis: AnalysisResult,
+    mapping: Option<HashMap<String, String>>,
+    dry_run: Option<bool>,
+) -> Result<LabelResult, PrvwError> {
+    let repo = repo.trim();
+    let dry_run = dry_run == Some(true);
+    let mapping = mapping.or_else(|| {
+        let settings = settings::current();
+        let overrides = settings::repo_settings(&settings, repo)?;
+        Some(overrides.label_mapping.clone()?.into_iter().collect())
+    });
+    let wanted = mapped_labels(&analysis, &mapping);
+    if wanted.is_empty() {
+        return Ok(LabelResult {
+            applied: Vec::new(),
+            missing: Vec::new(),
+            dry_run,
+        });
+    }
+
+    let (applied, missing) = split_existing(wanted, &gh::fetch_repo_labels(repo)?);
+    if !dry_run && !applied.is_empty() {
+        gh::add_pr_labels(repo, pr_number, &applied)?;
+    }
+    Ok(LabelResult {
+        applied,
+        missing,
+        dry_run,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::IntentGroup;
+
+    fn analysis(categories: &[&str]) -> AnalysisResult {
+        AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: categories
+                .iter()
+                .enumerate()
+                .map(|(i, category)| IntentGroup {
+                    id: format!("G{}", i + 1),
+                    title: String::new(),
+                    category: category.to_string(),
+                    rationale: String::new(),
+                    risk: "low".to_string(),
+                    risk_score: 0,
+                    risk_evidence: vec![],
+                    hunk_ids: vec![],
+                    reviewer_checklist: vec![],
+                    suggested_tests: vec![],
+                })
+                .collect(),
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        }
+    }
+
+    #[test]
+    fn maps_categories_in_group_order() {
+        let a = analysis(&["test", "logic", "docs", "test"]);
+        assert_eq!(mapped_labels(&a, &None), vec!["test", "logic", "docs"]);
+
+        let mapping: HashMap<String, String> =
+            [("docs", "documentation"), ("test", "tests"), ("logic", " ")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+        assert_eq!(
+            mapped_labels(&a, &Some(mapping)),
+            vec!["tests", "documentation"]
+        );
+    }
+
+    #[test]
+    fn matches_repo_labels_case_insensitively() {
+        let existing = vec!["Documentation".to_string(), "tests".to_string()];
+        let (found, missing) = split_existing(
+            vec![
+                "documentation".to_string(),
+                "TESTS".to_string(),
+                "ui".to_string(),
+            ],
+            &existing,
+        );
+        assert_eq!(found, vec!["Documentation", "tests"]);
+        assert_eq!(missing, vec!["ui"]);
+    }
+}
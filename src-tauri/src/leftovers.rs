@@ -0,0 +1,11 @@
+use crate::error::PrvwError;
+use crate::types::{Hunk, LeftoverFinding};
+
+pub use prvw_core::leftovers::*;
+
+#[tauri::command]
+pub async fn scan_leftovers(hunks_json: String) -> Result<Vec<LeftoverFinding>, PrvwError> {
+    let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+    Ok(scan_hunks(&hunks))
+}
@@ -1,25 +1,267 @@
+mod adhoc;
+mod api_server;
+mod assignments;
+mod azure_devops;
+mod bitbucket;
 mod cache;
+mod chat;
+mod checklists;
 mod codex;
-mod codex_runner;
+mod comment_templates;
+mod compare;
+mod config;
+mod deep_link;
+mod delta;
 mod diff_parser;
+mod drafts;
+mod editor;
+mod edits;
+mod error_reports;
+mod full_file;
 mod gh;
-mod types;
-mod validation;
+mod gitea;
+mod guidelines;
+mod head_state;
+mod head_watch;
+mod history;
+mod impact;
+mod issues;
+mod jj;
+mod labels;
+mod leftovers;
+mod local_range;
+mod manifest;
+mod metrics;
+mod notifications;
+mod operations;
+mod prefetch;
+mod provider;
+mod push_hook;
+mod related;
+mod release_notes;
+mod reminders;
+mod report;
+mod review_state;
+mod review_submit;
+mod risky;
+mod run_state;
+mod secrets;
+mod session;
+mod settings;
+mod share;
+mod stack;
+mod staged;
+mod threads;
+mod translate;
+mod tray;
+mod usage;
+mod viewed;
+mod workspace;
+
+use prvw_core::{codex_runner, error, i18n, json_store, time, types, validation};
+
+/// The app's data directory, where settings, sessions and caches are kept.
+fn app_data_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    use tauri::Manager;
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // First, so a second launch (e.g. by a prvw:// link) hands its link to this instance.
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            tray::show_window(app);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .manage(provider::ProviderRegistry::default())
+        .manage(api_server::ApiServer::default())
+        .manage(prefetch::PrefetchState::default())
+        .manage(reminders::ReminderState::default())
+        .manage(notifications::PrWatch::default())
+        .manage(head_watch::HeadWatch::default())
+        .manage(tray::TrayState::default())
+        .manage(deep_link::DeepLinks::default())
+        .manage(staged::StagedWatchState::default())
+        .manage(edits::EditHistory::default())
+        .manage(review_state::ReviewStates::default())
+        .setup(|app| {
+            use tauri::Manager;
+            cache::init(app.handle());
+            operations::init(app.handle());
+            error_reports::install_panic_hook();
+            i18n::init();
+            api_server::restore(app.handle());
+            tray::init(app.handle())?;
+            deep_link::init(app.handle());
+            config::init(app.handle());
+            if let Ok(dir) = app.path().app_data_dir() {
+                history::init(&dir);
+                settings::init(&dir);
+                error_reports::init(&dir);
+                metrics::init(&dir);
+                cache::start_sweeper(dir);
+            }
+            Ok(())
+        })
+        .on_window_event(tray::on_window_event)
         .invoke_handler(tauri::generate_handler![
             gh::list_prs,
             gh::get_pr_diff,
+            full_file::get_full_file,
+            stack::get_pr_stacks,
+            stack::get_stack_layer_diff,
+            gh::post_review_summary,
+            issues::create_follow_up_issues,
+            review_submit::submit_assembled_review,
+            drafts::get_comment_queue,
+            drafts::queue_comments,
+            drafts::edit_queued_comment,
+            drafts::delete_queued_comments,
+            drafts::reorder_comment_queue,
+            guidelines::get_review_guidelines,
+            checklists::list_checklist_templates,
+            checklists::save_checklist_template,
+            checklists::delete_checklist_template,
+            comment_templates::list_comment_templates,
+            comment_templates::save_comment_template,
+            comment_templates::delete_comment_template,
+            comment_templates::insert_comment_template,
             diff_parser::parse_diff,
+            review_state::open_review_state,
+            review_state::get_review_state_analysis,
+            review_state::close_review_state,
+            operations::list_operations,
+            operations::cancel_operation,
+            local_range::get_range_diff,
+            local_range::compare_branches,
+            local_range::get_clone_remotes,
+            jj::get_jj_diff,
+            compare::get_compare_diff,
+            adhoc::open_patch_file,
+            adhoc::open_diff_text,
+            adhoc::open_diff_url,
+            workspace::checkout_pr_workspace,
+            workspace::cleanup_workspaces,
+            workspace::get_workspace_size,
+            workspace::list_workspaces,
+            workspace::remove_workspace,
+            editor::open_in_editor,
+            staged::watch_staged_changes,
+            staged::stop_watching_staged,
+            staged::get_staged_status,
+            staged::get_staged_diff,
+            staged::self_review_staged,
+            push_hook::get_push_hook_status,
+            push_hook::install_push_hook,
+            push_hook::uninstall_push_hook,
+            api_server::get_api_server_status,
+            api_server::start_api_server,
+            api_server::stop_api_server,
+            manifest::get_dependency_changes,
+            leftovers::scan_leftovers,
+            risky::scan_risky_patterns,
             codex::analyze_intents_with_codex,
+            codex::preview_analysis_prompt,
+            codex::adjust_analysis,
             codex::refine_group,
+            codex::refine_group_with_guidance,
             codex::explain_hunk,
             codex::ask_about_hunk,
+            codex::summarize_hunks,
+            codex::draft_comments_for_group,
+            codex::suggest_split_plan,
+            codex::detect_breaking_changes,
+            codex::check_pr_consistency,
+            impact::analyze_impact,
+            report::render_review_summary,
+            share::share_analysis_gist,
+            share::import_analysis,
+            labels::apply_category_labels,
+            related::find_related_prs,
+            release_notes::generate_release_notes,
+            threads::get_review_threads,
+            threads::summarize_review_threads,
+            translate::translate_analysis,
+            chat::ask_about_pr,
+            chat::get_pr_chat,
+            chat::clear_pr_chat,
+            delta::diff_analyses,
+            edits::edit_analysis,
+            edits::undo_analysis_edit,
+            edits::redo_analysis_edit,
+            edits::get_edited_analysis,
+            edits::clear_analysis_edits,
+            session::get_review_progress,
+            session::get_session,
+            session::next_unreviewed,
+            session::update_session,
+            session::get_checklist,
+            session::check_checklist_items,
+            session::skip_groups,
+            session::unskip_groups,
+            session::set_group_order,
+            session::order_analysis,
+            assignments::assign_groups,
+            assignments::get_assignments,
+            assignments::post_assignments,
+            head_state::save_head_state,
+            head_state::get_head_state,
+            head_state::migrate_head_state,
+            head_watch::watch_pr_head,
+            head_watch::unwatch_pr_head,
+            head_watch::refresh_pr_head,
+            viewed::sync_viewed_state,
             cache::get_cache_size,
             cache::clear_cache,
+            cache::invalidate_cache,
+            cache::get_cache_limit,
+            cache::set_cache_limit,
+            settings::get_settings,
+            settings::update_settings,
+            config::get_config_status,
+            settings::get_repo_settings,
+            settings::set_repo_settings,
+            cache::get_cache_encryption,
+            cache::set_cache_encryption,
+            secrets::set_secret,
+            secrets::has_secret,
+            secrets::delete_secret,
+            secrets::test_secret,
+            bitbucket::list_bitbucket_prs,
+            bitbucket::get_bitbucket_pr_diff,
+            gitea::list_gitea_prs,
+            gitea::get_gitea_pr_diff,
+            gitea::post_gitea_comment,
+            azure_devops::list_azure_devops_prs,
+            azure_devops::get_azure_devops_pr_diff,
+            provider::list_providers,
+            usage::estimate_tokens_for_hunks,
+            usage::get_usage_ledger,
+            metrics::get_usage_stats,
+            history::list_codex_runs,
+            history::get_codex_run,
+            prefetch::start_prefetch,
+            prefetch::cancel_prefetch,
+            prefetch::get_prefetch_status,
+            reminders::start_review_reminders,
+            reminders::stop_review_reminders,
+            reminders::get_review_reminders,
+            reminders::check_stale_reviews,
+            notifications::watch_pr,
+            notifications::unwatch_pr,
+            error_reports::list_error_reports,
+            error_reports::export_error_reports,
+            error_reports::submit_error_report,
+            error_reports::delete_error_reports,
+            tray::get_tray_status,
+            tray::set_tray_repo,
+            tray::set_keep_running,
+            deep_link::deep_links_ready,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,30 @@
+use crate::error::PrvwError;
+use crate::types::{BranchComparison, CloneRemotes, RangeDiff};
+
+pub use prvw_core::local_range::*;
+
+/// The diff of a revision range in a local clone, for analysis without a PR (code archaeology,
+/// reviewing a branch before opening one). Pass `diff` to `parse_diff` with `repo` and
+/// `description` as the PR body.
+#[tauri::command]
+pub async fn get_range_diff(repo_path: String, range: String) -> Result<RangeDiff, PrvwError> {
+    prvw_core::local_range::get_range_diff(&repo_path, &range)
+}
+
+/// Three-dot diff of two local branches (or any refs), for inspecting how a long-lived branch
+/// diverged without a PR. The diff holds only `head`'s side; `behind` counts what `base` gained.
+#[tauri::command]
+pub async fn compare_branches(
+    repo_path: String,
+    base: String,
+    head: String,
+) -> Result<BranchComparison, PrvwError> {
+    prvw_core::local_range::compare_branches(&repo_path, &base, &head)
+}
+
+/// The GitHub repo a local clone's PRs live in and the fork it pushes to, for filling in the
+/// repo slug from a folder.
+#[tauri::command]
+pub async fn get_clone_remotes(repo_path: String) -> Result<CloneRemotes, PrvwError> {
+    prvw_core::local_range::get_clone_remotes(&repo_path)
+}
@@ -0,0 +1,13 @@
+use crate::error::PrvwError;
+use crate::types::{DependencyChange, Hunk};
+
+pub use prvw_core::manifest::*;
+
+#[tauri::command]
+pub async fn get_dependency_changes(
+    hunks_json: String,
+) -> Result<Vec<DependencyChange>, PrvwError> {
+    let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+    Ok(dependency_changes(&hunks))
+}
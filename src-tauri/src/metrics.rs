@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache;
+use crate::error::PrvwError;
+use crate::session;
+use crate::types::{
+    CacheStats, ModelLatency, ReviewDurations, ReviewSession, UsageEntry, UsageStats,
+};
+use crate::usage;
+
+/// Lookup counts sit next to the usage ledger, outside `cache/`, so clearing the cache keeps them.
+const LOOKUPS_PATH: &str = "usage/cache_lookups.json";
+
+/// Cache lookups per namespace, loaded on the first lookup and written back after each one.
+static LOOKUPS: Mutex<Option<BTreeMap<String, Lookups>>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+struct Lookups {
+    hits: u64,
+    misses: u64,
+}
+
+fn load_lookups(path: &Path) -> BTreeMap<String, Lookups> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Start counting cache hits and misses. Best-effort, like the ledger: failures are only logged.
+pub fn init(app_data_dir: &Path) {
+    let path = app_data_dir.join(LOOKUPS_PATH);
+    cache::on_lookup(move |namespace, hit| record_lookup(&path, namespace, hit));
+}
+
+fn record_lookup(path: &Path, namespace: &str, hit: bool) {
+    let Ok(mut lookups) = LOOKUPS.lock() else {
+        return;
+    };
+    let counts = lookups.get_or_insert_with(|| load_lookups(path));
+    let entry = counts.entry(namespace.to_string()).or_default();
+    if hit {
+        entry.hits += 1;
+    } else {
+        entry.misses += 1;
+    }
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(path, serde_json::to_string(&*counts).unwrap_or_default()));
+    if let Err(e) = result {
+        eprintln!("[metrics] failed to record cache lookup: {}", e);
+    }
+}
+
+/// Average provider latency per model, busiest first.
+fn model_latencies(entries: &[UsageEntry]) -> Vec<ModelLatency> {
+    let mut by_model: BTreeMap<(&str, &str), (u64, f64)> = BTreeMap::new();
+    for entry in entries {
+        let (runs, secs) = by_model
+            .entry((entry.provider.as_str(), entry.model.as_str()))
+            .or_default();
+        *runs += 1;
+        *secs += entry.elapsed_secs;
+    }
+    let mut models: Vec<ModelLatency> = by_model
+        .into_iter()
+        .map(|((provider, model), (runs, secs))| ModelLatency {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            runs,
+            average_secs: secs / runs as f64,
+        })
+        .collect();
+    models.sort_by_key(|m| std::cmp::Reverse(m.runs));
+    models
+}
+
+fn review_durations(sessions: &[ReviewSession]) -> ReviewDurations {
+    let mut secs: Vec<u64> = sessions
+        .iter()
+        .map(session::time_spent)
+        .filter(|secs| *secs > 0)
+        .collect();
+    if secs.is_empty() {
+        return ReviewDurations::default();
+    }
+    secs.sort_unstable();
+    let total_secs: u64 = secs.iter().sum();
+    ReviewDurations {
+        reviewed_prs: secs.len() as u64,
+        total_secs,
+        average_secs: total_secs / secs.len() as u64,
+        median_secs: secs[secs.len() / 2],
+    }
+}
+
+fn aggregate(
+    entries: &[UsageEntry],
+    lookups: &BTreeMap<String, Lookups>,
+    sessions: &[ReviewSession],
+) -> UsageStats {
+    let mut runs_by_kind: BTreeMap<String, u64> = BTreeMap::new();
+    for entry in entries {
+        *runs_by_kind.entry(entry.kind.clone()).or_default() += 1;
+    }
+    let cache: Vec<CacheStats> = lookups
+        .iter()
+        .map(|(namespace, counts)| CacheStats {
+            namespace: namespace.clone(),
+            hits: counts.hits,
+            misses: counts.misses,
+        })
+        .collect();
+    let hits: u64 = cache.iter().map(|c| c.hits).sum();
+    let total: u64 = cache.iter().map(|c| c.hits + c.misses).sum();
+    UsageStats {
+        analyses_run: runs_by_kind.get("analysis").copied().unwrap_or_default(),
+        runs_by_kind,
+        models: model_latencies(entries),
+        cache,
+        cache_hit_rate: (total > 0).then(|| hits as f64 / total as f64),
+        reviews: review_durations(sessions),
+    }
+}
+
+/// Analyses run, cache hit rates, provider latency per model, and review durations, from what
+/// this machine recorded. Strictly local: the ledger, lookup counts and sessions are only read.
+#[tauri::command]
+pub async fn get_usage_stats(app: tauri::AppHandle) -> Result<UsageStats, PrvwError> {
+    use tauri::Manager;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let lookups = match LOOKUPS.lock().ok().and_then(|l| l.clone()) {
+        Some(lookups) => lookups,
+        None => load_lookups(&app_data_dir.join(LOOKUPS_PATH)),
+    };
+    Ok(aggregate(
+        &usage::ledger(&app_data_dir),
+        &lookups,
+        &session::load_all(&app_data_dir),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ReviewMark;
+
+    fn entry(kind: &str, model: &str, elapsed_secs: f64) -> UsageEntry {
+        UsageEntry {
+            timestamp: 0,
+            kind: kind.to_string(),
+            provider: "codex".to_string(),
+            model: model.to_string(),
+            estimated_input_tokens: 0,
+            usage: None,
+            elapsed_secs,
+        }
+    }
+
+    fn session(marks_at: &[u64]) -> ReviewSession {
+        let mut session = ReviewSession::default();
+        for (i, at) in marks_at.iter().enumerate() {
+            session.reviewed_hunks.insert(
+                format!("H{}", i),
+                ReviewMark {
+                    at: *at,
+                    head_sha: None,
+                },
+            );
+        }
+        session
+    }
+
+    #[test]
+    fn aggregates_runs_latency_cache_and_reviews() {
+        let entries = [
+            entry("analysis", "a", 10.0),
+            entry("analysis", "a", 20.0),
+            entry("refine", "b", 4.0),
+        ];
+        let lookups = BTreeMap::from([
+            ("cache/analysis".to_string(), Lookups { hits: 3, misses: 1 }),
+            ("cache/diff".to_string(), Lookups { hits: 0, misses: 4 }),
+        ]);
+        let sessions = [session(&[100, 160]), session(&[]), session(&[0, 30, 90])];
+
+        let stats = aggregate(&entries, &lookups, &sessions);
+        assert_eq!(stats.analyses_run, 2);
+        assert_eq!(stats.runs_by_kind["refine"], 1);
+        assert_eq!(stats.models[0].model, "a");
+        assert_eq!(stats.models[0].runs, 2);
+        assert!((stats.models[0].average_secs - 15.0).abs() < f64::EPSILON);
+        assert_eq!(stats.cache_hit_rate, Some(3.0 / 8.0));
+        assert_eq!(
+            stats.reviews,
+            ReviewDurations {
+                reviewed_prs: 2,
+                total_secs: 150,
+                average_secs: 75,
+                median_secs: 90,
+            }
+        );
+    }
+
+    #[test]
+    fn empty_metrics() {
+        let stats = aggregate(&[], &BTreeMap::new(), &[]);
+        assert_eq!(stats.cache_hit_rate, None);
+        assert_eq!(stats.reviews, ReviewDurations::default());
+        assert!(stats.models.is_empty());
+    }
+}
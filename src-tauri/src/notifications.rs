@@ -0,0 +1,330 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::error::PrvwError;
+use crate::gh;
+use crate::run_state::RunState;
+use crate::session;
+use crate::settings;
+use crate::types::{NotificationSettings, ReviewSession};
+
+/// Time between checks of the watched repo's open PRs.
+const WATCH_INTERVAL: Duration = Duration::from_secs(3 * 60);
+/// How often a running watcher wakes up to check whether it was stopped.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Open PRs checked for CI failures per check.
+const WATCH_LIMIT: u32 = 100;
+const WATCH_FIELDS: &str = "number,title,headRefOid,statusCheckRollup";
+
+/// Events that can fire a notification, each switched on or off in the settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    AnalysesDone,
+    NewPush,
+    CiFailed,
+}
+
+impl Event {
+    fn enabled(self, settings: &NotificationSettings) -> bool {
+        match self {
+            Event::AnalysesDone => settings.analyses_done,
+            Event::NewPush => settings.new_pushes,
+            Event::CiFailed => settings.ci_failures,
+        }
+    }
+}
+
+/// Show a desktop notification regardless of the notification settings, for ones the user
+/// asked for explicitly (review reminders).
+pub fn show(app: &AppHandle, title: &str, body: &str) {
+    let result = app.notification().builder().title(title).body(body).show();
+    if let Err(e) = result {
+        eprintln!("[notifications] notification failed: {}", e);
+    }
+}
+
+/// Notify about `event` unless the settings turn it off, or the window has focus and only
+/// background notifications are wanted.
+pub fn send(app: &AppHandle, event: Event, title: &str, body: &str) {
+    let settings = settings::current().notifications;
+    if !event.enabled(&settings) {
+        return;
+    }
+    if settings.only_when_unfocused
+        && app
+            .webview_windows()
+            .values()
+            .any(|window| window.is_focused().unwrap_or(false))
+    {
+        return;
+    }
+    show(app, title, body);
+}
+
+/// The PR open in the window and the head it shows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedPr {
+    repo: String,
+    pr_number: u32,
+    head_sha: Option<String>,
+}
+
+/// Managed state for the PR watcher, holding the watched PR; watching another repo replaces
+/// the running thread.
+pub type PrWatch = RunState<Option<WatchedPr>>;
+
+impl PrWatch {
+    /// Record the head a notification was sent for, unless the window moved to another PR.
+    fn set_head(&self, run_id: u64, watched: &WatchedPr) {
+        self.update(run_id, |target| {
+            if let Some(current) = target.as_mut() {
+                if current.repo == watched.repo && current.pr_number == watched.pr_number {
+                    current.head_sha.clone_from(&watched.head_sha);
+                }
+            }
+        });
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenPr {
+    number: u32,
+    title: String,
+    #[serde(default)]
+    head_ref_oid: Option<String>,
+    #[serde(default)]
+    status_check_rollup: Option<Vec<Value>>,
+}
+
+/// Whether a `statusCheckRollup` holds a failed check run (`conclusion`) or commit status
+/// (`state`). Checks still running don't count.
+fn ci_failed(rollup: &[Value]) -> bool {
+    rollup.iter().any(|check| {
+        let outcome = check
+            .get("conclusion")
+            .or_else(|| check.get("state"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        matches!(
+            outcome,
+            "FAILURE" | "ERROR" | "TIMED_OUT" | "STARTUP_FAILURE" | "ACTION_REQUIRED"
+        )
+    })
+}
+
+fn has_progress(session: &ReviewSession) -> bool {
+    !session.reviewed_groups.is_empty() || !session.reviewed_hunks.is_empty()
+}
+
+/// Notifications (event, title, body) for what changed since the last check: a new head on the
+/// watched PR, and CI turning red on PRs for which `reviewed` holds. `failing` carries the PRs
+/// failing CI between checks; it is None before the first one, which only records them.
+fn changes(
+    watched: &mut WatchedPr,
+    prs: &[OpenPr],
+    failing: &mut Option<HashSet<u32>>,
+    reviewed: impl Fn(u32) -> bool,
+) -> Vec<(Event, String, String)> {
+    let mut notes = Vec::new();
+    if let Some(pr) = prs.iter().find(|pr| pr.number == watched.pr_number) {
+        if pr.head_ref_oid.is_some() && pr.head_ref_oid != watched.head_sha {
+            if watched.head_sha.is_some() {
+                notes.push((
+                    Event::NewPush,
+                    format!("New commits on {}#{}", watched.repo, pr.number),
+                    pr.title.clone(),
+                ));
+            }
+            watched.head_sha.clone_from(&pr.head_ref_oid);
+        }
+    }
+
+    let now_failing: HashSet<u32> = prs
+        .iter()
+        .filter(|pr| ci_failed(pr.status_check_rollup.as_deref().unwrap_or_default()))
+        .map(|pr| pr.number)
+        .collect();
+    if let Some(before) = failing.as_ref() {
+        for pr in prs {
+            if now_failing.contains(&pr.number)
+                && !before.contains(&pr.number)
+                && reviewed(pr.number)
+            {
+                notes.push((
+                    Event::CiFailed,
+                    format!("CI failing on {}#{}", watched.repo, pr.number),
+                    pr.title.clone(),
+                ));
+            }
+        }
+    }
+    *failing = Some(now_failing);
+    notes
+}
+
+fn run_watch(app: &AppHandle, run_id: u64) {
+    let state = app.state::<PrWatch>();
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let mut failing = None;
+    while state.is_current(run_id) {
+        let Some(mut watched) = state.snapshot() else {
+            return;
+        };
+        let prs = gh::run_pr_list(&watched.repo, WATCH_LIMIT, "open", None, WATCH_FIELDS)
+            .map_err(String::from)
+            .and_then(|stdout| {
+                serde_json::from_str::<Vec<OpenPr>>(&stdout)
+                    .map_err(|e| format!("Failed to parse gh output: {}", e))
+            });
+        match prs {
+            Ok(prs) => {
+                let repo = watched.repo.clone();
+                let notes = changes(&mut watched, &prs, &mut failing, |pr_number| {
+                    has_progress(&session::load(&app_data_dir, &repo, pr_number))
+                });
+                state.set_head(run_id, &watched);
+                for (event, title, body) in notes {
+                    send(app, event, &title, &body);
+                }
+            }
+            Err(e) => eprintln!("[notifications] watching {} failed: {}", watched.repo, e),
+        }
+
+        let next_at = Instant::now() + WATCH_INTERVAL;
+        while Instant::now() < next_at {
+            if !state.is_current(run_id) {
+                return;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Watch the PR open in the window (showing `head_sha`) for new pushes, and its repo's open PRs
+/// with review progress for CI turning red, notifying per the notification settings. Replaces
+/// the previous watch; the repo is checked every few minutes while watched.
+#[tauri::command]
+pub async fn watch_pr(
+    app: AppHandle,
+    state: tauri::State<'_, PrWatch>,
+    repo: String,
+    pr_number: u32,
+    head_sha: Option<String>,
+) -> Result<(), PrvwError> {
+    let repo = repo.trim().to_string();
+    gh::validate_repo(&repo)?;
+    let watched = WatchedPr {
+        repo,
+        pr_number,
+        head_sha: head_sha.filter(|sha| !sha.is_empty()),
+    };
+    // The running watcher picks up another PR of its repo at the next check.
+    let same_repo = state.modify(|target| {
+        let same_repo = target.as_ref().is_some_and(|t| t.repo == watched.repo);
+        *target = Some(watched);
+        same_repo
+    })?;
+    if !same_repo {
+        let run_id = state.begin();
+        std::thread::spawn(move || run_watch(&app, run_id));
+    }
+    Ok(())
+}
+
+/// Stop watching, e.g. when the window goes back to the PR list.
+#[tauri::command]
+pub async fn unwatch_pr(state: tauri::State<'_, PrWatch>) -> Result<(), PrvwError> {
+    let run_id = state.begin();
+    state.update(run_id, |target| *target = None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn open_pr(number: u32, head: &str, conclusion: &str) -> OpenPr {
+        OpenPr {
+            number,
+            title: format!("PR {}", number),
+            head_ref_oid: Some(head.to_string()),
+            status_check_rollup: Some(vec![
+                json!({"status": "COMPLETED", "conclusion": conclusion}),
+            ]),
+        }
+    }
+
+    #[test]
+    fn reads_failures_from_check_runs_and_statuses() {
+        assert!(ci_failed(&[
+            json!({"conclusion": "SUCCESS"}),
+            json!({"state": "ERROR"})
+        ]));
+        assert!(ci_failed(&[
+            json!({"status": "COMPLETED", "conclusion": "FAILURE"})
+        ]));
+        assert!(!ci_failed(&[
+            json!({"status": "IN_PROGRESS", "conclusion": ""})
+        ]));
+        assert!(!ci_failed(&[json!({"state": "PENDING"})]));
+        assert!(!ci_failed(&[]));
+    }
+
+    #[test]
+    fn notifies_new_heads_and_ci_turning_red() {
+        let mut watched = WatchedPr {
+            repo: "o/r".to_string(),
+            pr_number: 1,
+            head_sha: Some("a".to_string()),
+        };
+        let mut failing = None;
+        // The first check only records what is already failing.
+        let prs = [open_pr(1, "a", "SUCCESS"), open_pr(2, "x", "FAILURE")];
+        assert!(changes(&mut watched, &prs, &mut failing, |_| true).is_empty());
+
+        let prs = [
+            open_pr(1, "b", "FAILURE"),
+            open_pr(2, "x", "FAILURE"),
+            open_pr(3, "y", "FAILURE"),
+        ];
+        let notes = changes(&mut watched, &prs, &mut failing, |n| n != 3);
+        let titles: Vec<(Event, &str)> = notes.iter().map(|(e, t, _)| (*e, t.as_str())).collect();
+        assert_eq!(
+            titles,
+            vec![
+                (Event::NewPush, "New commits on o/r#1"),
+                (Event::CiFailed, "CI failing on o/r#1"),
+            ]
+        );
+        assert_eq!(watched.head_sha.as_deref(), Some("b"));
+        // Nothing new: no repeats.
+        assert!(changes(&mut watched, &prs, &mut failing, |_| true).is_empty());
+    }
+
+    #[test]
+    fn unknown_head_is_recorded_without_notifying() {
+        let mut watched = WatchedPr {
+            repo: "o/r".to_string(),
+            pr_number: 1,
+            head_sha: None,
+        };
+        let mut failing = Some(HashSet::new());
+        let notes = changes(
+            &mut watched,
+            &[open_pr(1, "a", "SUCCESS")],
+            &mut failing,
+            |_| true,
+        );
+        assert!(notes.is_empty());
+        assert_eq!(watched.head_sha.as_deref(), Some("a"));
+    }
+}
@@ -0,0 +1,30 @@
+use tauri::{AppHandle, Emitter};
+
+use crate::error::PrvwError;
+use crate::types::OperationRecord;
+
+/// The registry and subprocess helpers are in `prvw_core::operations`; this module sends the
+/// progress events to the frontend and adds the commands.
+pub use prvw_core::operations::*;
+
+/// Emit each operation's progress events to the frontend.
+pub fn init(app: &AppHandle) {
+    let app = app.clone();
+    on_event(move |event, record| {
+        let _ = app.emit(event, record);
+    });
+}
+
+/// Running and recently finished operations, oldest first, for a progress history view.
+#[tauri::command]
+pub async fn list_operations() -> Result<Vec<OperationRecord>, PrvwError> {
+    Ok(list()?)
+}
+
+/// Stop a running operation: its subprocesses (gh, git, codex, ...) are killed with their
+/// children and the command fails with `op-error` (status "cancelled"); temp directories are
+/// removed as it unwinds. Returns whether the operation was running.
+#[tauri::command]
+pub async fn cancel_operation(op_id: String) -> Result<bool, PrvwError> {
+    Ok(cancel(op_id.trim())?)
+}
@@ -0,0 +1,282 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::codex::{self, AnalysisRequest};
+use crate::codex_runner;
+use crate::config;
+use crate::diff_parser;
+use crate::error::PrvwError;
+use crate::gh;
+use crate::guidelines;
+use crate::notifications;
+use crate::provider::{AnalysisProvider, ProviderRegistry};
+use crate::run_state::RunState;
+use crate::settings::{self, RunDefaults};
+use crate::time::now_secs;
+use crate::types::{PrListItem, PrefetchPhase, PrefetchRequest, PrefetchStatus, RepoContext};
+
+/// `gh pr list --search` query for the review queue.
+pub const REVIEW_QUEUE_SEARCH: &str = "review-requested:@me";
+pub const MAX_QUEUE: u32 = 30;
+/// How often a scheduled run wakes up to check whether it was cancelled.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Errors kept in the status; older ones are dropped.
+const MAX_ERRORS: usize = 20;
+/// Pause between diff fetches of `start_diff_prefetch`, keeping it well under GitHub's rate
+/// limit while the user browses.
+const DIFF_PREFETCH_INTERVAL: Duration = Duration::from_millis(1500);
+
+/// Run of `start_diff_prefetch`; a newer call replaces a running one.
+static DIFF_PREFETCH: RunState<()> = RunState::new(());
+
+/// Managed state for the background pre-analysis; each start replaces the running one.
+pub type PrefetchState = RunState<PrefetchStatus>;
+
+/// Warm the diff cache for `prs` in the background, one uncached diff at a time. A newer call
+/// (e.g. a re-listed PR page) replaces a running one, and a rate-limit error stops it.
+pub fn start_diff_prefetch(app_data_dir: PathBuf, repo: String, prs: &[PrListItem]) {
+    let run_id = DIFF_PREFETCH.begin();
+    let prs: Vec<(u32, String)> = prs
+        .iter()
+        .filter_map(|pr| Some((u32::try_from(pr.number).ok()?, pr.updated_at.clone())))
+        .collect();
+    std::thread::spawn(move || {
+        for (pr_number, updated_at) in prs {
+            if !DIFF_PREFETCH.is_current(run_id) {
+                return;
+            }
+            if gh::is_diff_cached(&app_data_dir, &repo, pr_number, &updated_at) {
+                continue;
+            }
+            match gh::fetch_pr_diff(
+                Some(&app_data_dir),
+                &repo,
+                pr_number,
+                Some(&updated_at),
+                false,
+            ) {
+                Ok(_) => {}
+                Err(e) if codex_runner::is_rate_limited(&e.message()) => {
+                    eprintln!("[prefetch] diff prefetch stopped by rate limit: {}", e);
+                    return;
+                }
+                Err(e) => eprintln!("[prefetch] diff of #{} failed: {}", pr_number, e),
+            }
+            std::thread::sleep(DIFF_PREFETCH_INTERVAL);
+        }
+    });
+}
+
+/// Fetch, parse, and analyze one PR with the same inputs the UI would use, so opening it
+/// later hits the diff and analysis caches.
+fn prefetch_pr(
+    app_data_dir: Option<&Path>,
+    provider: &dyn AnalysisProvider,
+    request: &PrefetchRequest,
+    guidelines: &Option<String>,
+    pr: &PrListItem,
+) -> Result<(), String> {
+    let pr_number =
+        u32::try_from(pr.number).map_err(|_| format!("PR number {} out of range", pr.number))?;
+    let diff = gh::fetch_pr_diff(
+        app_data_dir,
+        &request.repo,
+        pr_number,
+        Some(&pr.updated_at),
+        false,
+    )?;
+    let parsed = diff_parser::parse(diff, Some(request.repo.clone()))?;
+    if parsed.hunks.is_empty() {
+        return Ok(());
+    }
+    let hunks_json = serde_json::to_string(&parsed.hunks)
+        .map_err(|e| format!("Failed to serialize hunks: {}", e))?;
+
+    codex::run_analysis(
+        app_data_dir,
+        provider,
+        AnalysisRequest {
+            hunks_json,
+            pr_body: pr.body.clone(),
+            hunk_summaries: None,
+            guidelines: guidelines.clone(),
+            repo_context: request.use_repo_context.then(|| RepoContext {
+                repo: request.repo.clone(),
+                pr_number,
+            }),
+            cache_owner: Some(RepoContext {
+                repo: request.repo.clone(),
+                pr_number,
+            }),
+            model: request.model.clone(),
+            lang: request.lang.clone(),
+            options: request.options.clone().unwrap_or_default(),
+            force: false,
+            timeout_minutes: request.timeout_minutes,
+        },
+    )?;
+    Ok(())
+}
+
+fn run_queue(
+    app: &AppHandle,
+    run_id: u64,
+    provider: &dyn AnalysisProvider,
+    request: &PrefetchRequest,
+) {
+    let state = app.state::<PrefetchState>();
+    let app_data_dir = app.path().app_data_dir().ok();
+
+    let starts_at = now_secs() + request.delay_secs.unwrap_or(0);
+    while now_secs() < starts_at {
+        if !state.is_current(run_id) {
+            return;
+        }
+        std::thread::sleep(
+            POLL_INTERVAL.min(Duration::from_secs(starts_at.saturating_sub(now_secs()))),
+        );
+    }
+
+    state.update(run_id, |s| s.phase = PrefetchPhase::Running);
+    let queue = match gh::fetch_pr_list(
+        &request.repo,
+        MAX_QUEUE,
+        "open",
+        Some(REVIEW_QUEUE_SEARCH.to_string()),
+    ) {
+        Ok(queue) => queue,
+        Err(e) => {
+            state.update(run_id, |s| {
+                s.phase = PrefetchPhase::Done;
+                s.errors.push(e.message());
+            });
+            return;
+        }
+    };
+    // Guidelines failures only lose the extra context, as in the UI.
+    let guidelines = if request.use_guidelines {
+        guidelines::fetch_review_guidelines(&request.repo)
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+    let guidelines = config::with_prompt(
+        guidelines,
+        &settings::for_repo(Some(request.repo.as_str())),
+        "analysis",
+    );
+    state.update(run_id, |s| s.total = queue.len());
+
+    for pr in &queue {
+        if !state.is_current(run_id) {
+            return;
+        }
+        state.update(run_id, |s| s.current_pr = u32::try_from(pr.number).ok());
+        let result = prefetch_pr(app_data_dir.as_deref(), provider, request, &guidelines, pr);
+        state.update(run_id, |s| match result {
+            Ok(()) => s.completed += 1,
+            Err(e) => {
+                s.failed += 1;
+                if s.errors.len() >= MAX_ERRORS {
+                    s.errors.remove(0);
+                }
+                s.errors.push(format!("#{}: {}", pr.number, e));
+            }
+        });
+    }
+
+    state.update(run_id, |s| {
+        s.phase = PrefetchPhase::Done;
+        s.current_pr = None;
+    });
+    if state.is_current(run_id) && !queue.is_empty() {
+        let status = state.snapshot();
+        let failed = if status.failed > 0 {
+            format!(", {} failed", status.failed)
+        } else {
+            String::new()
+        };
+        notifications::send(
+            app,
+            notifications::Event::AnalysesDone,
+            &format!("Review queue of {} analyzed", request.repo),
+            &format!(
+                "{} of {} PRs ready{}",
+                status.completed, status.total, failed
+            ),
+        );
+    }
+}
+
+/// Start (or schedule, with `delay_secs`) pre-analysis of the repo's review queue in a
+/// background thread, replacing any run in progress. Poll `get_prefetch_status` for progress.
+#[tauri::command]
+pub async fn start_prefetch(
+    app: AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    state: tauri::State<'_, PrefetchState>,
+    request: PrefetchRequest,
+) -> Result<PrefetchStatus, PrvwError> {
+    let repo = request.repo.trim().to_string();
+    gh::validate_repo(&repo)?;
+    let defaults = RunDefaults::resolve(
+        Some(repo.as_str()),
+        request.model,
+        request.lang,
+        request.provider,
+        request.options,
+        request.timeout_minutes,
+    );
+    let request = PrefetchRequest {
+        model: defaults.model,
+        lang: defaults.lang,
+        provider: defaults.provider,
+        options: Some(defaults.options),
+        timeout_minutes: defaults.timeout_minutes,
+        repo,
+        ..request
+    };
+    let provider = providers.resolve(&request.provider)?;
+
+    let run_id = state.begin();
+    let delay = request.delay_secs.unwrap_or(0);
+    let status = PrefetchStatus {
+        phase: if delay > 0 {
+            PrefetchPhase::Scheduled
+        } else {
+            PrefetchPhase::Running
+        },
+        repo: Some(request.repo.clone()),
+        starts_at: Some(now_secs() + delay),
+        ..PrefetchStatus::default()
+    };
+    state.update(run_id, |s| *s = status.clone());
+
+    std::thread::spawn(move || run_queue(&app, run_id, provider.as_ref(), &request));
+    Ok(status)
+}
+
+/// Cancel the current run. The PR being analyzed finishes, but no further PRs are started.
+#[tauri::command]
+pub async fn cancel_prefetch(
+    state: tauri::State<'_, PrefetchState>,
+) -> Result<PrefetchStatus, PrvwError> {
+    let run_id = state.begin();
+    state.update(run_id, |s| {
+        if matches!(s.phase, PrefetchPhase::Scheduled | PrefetchPhase::Running) {
+            s.phase = PrefetchPhase::Cancelled;
+        }
+        s.current_pr = None;
+    });
+    Ok(state.snapshot())
+}
+
+#[tauri::command]
+pub async fn get_prefetch_status(
+    state: tauri::State<'_, PrefetchState>,
+) -> Result<PrefetchStatus, PrvwError> {
+    Ok(state.snapshot())
+}
@@ -0,0 +1,11 @@
+use crate::error::PrvwError;
+
+/// The registry is in `prvw_core::provider`; this module adds its command.
+pub use prvw_core::provider::*;
+
+#[tauri::command]
+pub async fn list_providers(
+    providers: tauri::State<'_, ProviderRegistry>,
+) -> Result<Vec<ProviderInfo>, PrvwError> {
+    Ok(providers.list())
+}
@@ -0,0 +1,26 @@
+use crate::error::PrvwError;
+use crate::types::PushHookStatus;
+
+pub use prvw_core::push_hook::*;
+
+/// Whether the repo containing `repo_path` has prvw's pre-push hook, or another one.
+#[tauri::command]
+pub async fn get_push_hook_status(repo_path: String) -> Result<PushHookStatus, PrvwError> {
+    Ok(get_status(&repo_path)?)
+}
+
+/// Install a pre-push hook that analyzes the outgoing commits and blocks the push on findings of
+/// `fail_on` risk or higher ("high" when unset). Reinstalling updates the level.
+#[tauri::command]
+pub async fn install_push_hook(
+    repo_path: String,
+    fail_on: Option<String>,
+) -> Result<PushHookStatus, PrvwError> {
+    Ok(install_in(&repo_path, fail_on.as_deref())?)
+}
+
+/// Remove prvw's pre-push hook; a hook it didn't install is never touched.
+#[tauri::command]
+pub async fn uninstall_push_hook(repo_path: String) -> Result<bool, PrvwError> {
+    Ok(uninstall_from(&repo_path)?)
+}
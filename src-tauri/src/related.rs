@@ -0,0 +1,420 @@
+use std::collections::HashSet;
+
+use crate::cache;
+use crate::codex_runner::{self, lang_suffix};
+use crate::error::PrvwError;
+use crate::gh;
+use crate::history;
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::provider::{self, Job, ProviderRegistry};
+use crate::settings::RunDefaults;
+use crate::types::{
+    AnalysisResult, Hunk, RecentPr, RelatedPr, RelatedPrAssessment, RelatedPrsResponse,
+    RelatedPrsResult, RunOptions,
+};
+use crate::usage;
+
+const RELATED_PRS_SCHEMA: &str = include_str!("../schemas/related_prs.json");
+
+/// Recently updated PRs searched for overlapping files.
+const RECENT_PR_LIMIT: u32 = 50;
+/// Overlapping PRs passed to the model, most overlapping files first.
+const MAX_RELATED: usize = 10;
+/// Characters of each candidate's description given to the model.
+const MAX_BODY_CHARS: usize = 500;
+
+const RELATIONS: &[&str] = &["conflict", "duplicate", "none"];
+
+/// Recent PRs (other than `pr_number`) touching any of `changed_files`, with the shared files,
+/// most overlap first and at most `MAX_RELATED`.
+fn find_overlaps(
+    pr_number: u32,
+    changed_files: &HashSet<&str>,
+    recent: Vec<RecentPr>,
+) -> Vec<(RecentPr, Vec<String>)> {
+    let mut overlaps: Vec<(RecentPr, Vec<String>)> = recent
+        .into_iter()
+        .filter(|pr| pr.number != u64::from(pr_number))
+        .filter_map(|pr| {
+            let mut files: Vec<String> = pr
+                .files
+                .iter()
+                .filter(|f| changed_files.contains(f.path.as_str()))
+                .map(|f| f.path.clone())
+                .collect();
+            files.sort();
+            files.dedup();
+            (!files.is_empty()).then_some((pr, files))
+        })
+        .collect();
+    // Stable sort keeps gh's most-recently-updated order among equal overlaps.
+    overlaps.sort_by_key(|(_, files)| std::cmp::Reverse(files.len()));
+    overlaps.truncate(MAX_RELATED);
+    overlaps
+}
+
+/// related.json: what the model needs to know about each candidate.
+fn candidates_json(overlaps: &[(RecentPr, Vec<String>)]) -> Result<String, String> {
+    let candidates: Vec<serde_json::Value> = overlaps
+        .iter()
+        .map(|(pr, files)| {
+            let body = pr.body.as_deref().unwrap_or("").trim();
+            serde_json::json!({
+                "number": pr.number,
+                "title": pr.title,
+                "state": pr.state,
+                "description": &body[..body.floor_char_boundary(MAX_BODY_CHARS)],
+                "overlappingFiles": files,
+            })
+        })
+        .collect();
+    serde_json::to_string(&candidates)
+        .map_err(|e| format!("Failed to serialize related PRs: {}", e))
+}
+
+fn build_related_prs_prompt(lang: &Option<String>) -> String {
+    format!(
+        "Read analysis.json, which groups the changes of the pull request under review by intent, \
+         and related.json, which lists other recent pull requests that touch some of the same files \
+         (overlappingFiles), with their title, state, and description. \
+         For each pull request in related.json, return one assessment with its number and a relation: \
+         \"conflict\" if both change the same code in ways likely to conflict when merged or to break each other, \
+         \"duplicate\" if it implements the same or largely overlapping functionality (duplicated work), \
+         or \"none\" if it merely touches the same files for an unrelated purpose. \
+         Merged or closed pull requests can still be duplicates of this one. \
+         Give a one-sentence reason naming what overlaps. Use only numbers from related.json.{}",
+        lang_suffix(lang)
+    )
+}
+
+/// Combine candidates with the model's assessments. Unknown numbers and relations are
+/// dropped; candidates without an assessment are kept as "none". Conflicts sort first.
+fn merge_assessments(
+    overlaps: Vec<(RecentPr, Vec<String>)>,
+    assessments: Vec<RelatedPrAssessment>,
+) -> (Vec<RelatedPr>, Vec<String>) {
+    let mut warnings: Vec<String> = Vec::new();
+    let numbers: HashSet<u64> = overlaps.iter().map(|(pr, _)| pr.number).collect();
+    let mut assessments: Vec<RelatedPrAssessment> = assessments
+        .into_iter()
+        .filter(|a| {
+            if !numbers.contains(&a.number) {
+                warnings.push(format!("Removed assessment of unknown PR #{}", a.number));
+                return false;
+            }
+            if !RELATIONS.contains(&a.relation.as_str()) {
+                warnings.push(format!(
+                    "Removed assessment of PR #{} with unknown relation '{}'",
+                    a.number, a.relation
+                ));
+                return false;
+            }
+            true
+        })
+        .collect();
+
+    let mut related: Vec<RelatedPr> = overlaps
+        .into_iter()
+        .map(|(pr, overlapping_files)| {
+            let (relation, reason) = match assessments.iter().position(|a| a.number == pr.number) {
+                Some(pos) => {
+                    let a = assessments.swap_remove(pos);
+                    (a.relation, a.reason)
+                }
+                None => {
+                    warnings.push(format!("PR #{} was not assessed", pr.number));
+                    ("none".to_string(), String::new())
+                }
+            };
+            RelatedPr {
+                number: pr.number,
+                title: pr.title,
+                url: pr.url,
+                state: pr.state,
+                author: pr.author.map(|a| a.login),
+                overlapping_files,
+                relation,
+                reason,
+            }
+        })
+        .collect();
+    related.sort_by_key(|r| {
+        RELATIONS
+            .iter()
+            .position(|rel| *rel == r.relation)
+            .unwrap_or(RELATIONS.len())
+    });
+    (related, warnings)
+}
+
+/// Search recent PRs (via gh) that touch the same files as this one and ask the model to flag
+/// likely merge conflicts or duplicated work. Skips the model when nothing overlaps.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn find_related_prs(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    repo: String,
+    pr_number: u32,
+    hunks_json: String,
+    analysis: AnalysisResult,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<RelatedPrsResponse, PrvwError> {
+    use tauri::Manager;
+    let op = Op::start(op_id, "find_related_prs");
+    let result: Result<RelatedPrsResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                Some(repo.as_str()),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+            let repo = repo.trim().to_string();
+
+            let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+                .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+            let changed_files: HashSet<&str> = hunks.iter().map(|h| h.file_path.as_str()).collect();
+
+            let recent = gh::fetch_recent_prs_with_files(&repo, RECENT_PR_LIMIT)?;
+            let overlaps = find_overlaps(pr_number, &changed_files, recent);
+            if overlaps.is_empty() {
+                return Ok(RelatedPrsResponse {
+                    related: Vec::new(),
+                    codex_log: format!(
+                        "[related-prs] none of the {} most recent PRs touch the same files\n",
+                        RECENT_PR_LIMIT
+                    ),
+                    from_cache: false,
+                });
+            }
+            let related_json = candidates_json(&overlaps)?;
+            let analysis_json = serde_json::to_string(&analysis)
+                .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+
+            let app_data_dir = app.path().app_data_dir().ok();
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            // The candidates are part of the key, so new or updated related PRs miss the cache.
+            let cache_key = cache::hash_key(&format!(
+                "{}\n{}\n{}\n{}\n{}\n{}",
+                repo, pr_number, analysis_json, related_json, model_str, lang_str
+            ));
+
+            if force != Some(true) {
+                if let Some(ref dir) = app_data_dir {
+                    if let Some(mut cached) = cache::read_cache_async::<RelatedPrsResponse>(
+                        dir,
+                        "cache/related_prs",
+                        &cache_key,
+                    )
+                    .await
+                    {
+                        cached.from_cache = true;
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (temp_dir, schema_path, output_path) = codex_runner::prepare_temp_dir(
+                &analysis_json,
+                RELATED_PRS_SCHEMA,
+                "related_prs.json",
+            )?;
+
+            // The input is the analysis, not hunks
+            let temp_path = temp_dir.path();
+            std::fs::rename(
+                temp_path.join("hunks.json"),
+                temp_path.join("analysis.json"),
+            )
+            .map_err(|e| format!("Failed to rename temp file: {}", e))?;
+            std::fs::write(temp_path.join("related.json"), &related_json)
+                .map_err(|e| format!("Failed to write related.json: {}", e))?;
+
+            let prompt = build_related_prs_prompt(&lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens = usage::estimate_tokens(&analysis_json)
+                + usage::estimate_tokens(&related_json)
+                + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path,
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir.as_deref(),
+                "related-prs",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read related_prs.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: RelatedPrsResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse related_prs.json: {}", e))?;
+
+            let (related, warnings) = merge_assessments(overlaps, result.assessments);
+
+            let mut log = codex_runner::build_log("related-prs", &codex_output);
+            log.push_str(&format!(
+                "[related-prs] candidates={} conflicts={} duplicates={}\n",
+                related.len(),
+                related.iter().filter(|r| r.relation == "conflict").count(),
+                related.iter().filter(|r| r.relation == "duplicate").count()
+            ));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let response = RelatedPrsResponse {
+                related,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(ref dir) = app_data_dir {
+                cache::write_cache(dir, "cache/related_prs", &cache_key, &response);
+            }
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PrFile;
+
+    fn pr(number: u64, files: &[&str]) -> RecentPr {
+        RecentPr {
+            number,
+            title: format!("PR {}", number),
+            url: format!("https://github.com/o/r/pull/{}", number),
+            state: "OPEN".to_string(),
+            updated_at: String::new(),
+            author: None,
+            body: None,
+            files: files
+                .iter()
+                .map(|p| PrFile {
+                    path: p.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn assessment(number: u64, relation: &str) -> RelatedPrAssessment {
+        RelatedPrAssessment {
+            number,
+            relation: relation.to_string(),
+            reason: "r".to_string(),
+        }
+    }
+
+    #[test]
+    fn overlaps_exclude_self_and_sort_by_shared_files() {
+        let changed: HashSet<&str> = ["src/a.rs", "src/b.rs"].into_iter().collect();
+        let overlaps = find_overlaps(
+            1,
+            &changed,
+            vec![
+                pr(1, &["src/a.rs"]),
+                pr(2, &["src/a.rs", "README.md"]),
+                pr(3, &["docs/x.md"]),
+                pr(4, &["src/b.rs", "src/a.rs"]),
+            ],
+        );
+        let got: Vec<(u64, Vec<String>)> = overlaps
+            .into_iter()
+            .map(|(pr, files)| (pr.number, files))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                (4, vec!["src/a.rs".to_string(), "src/b.rs".to_string()]),
+                (2, vec!["src/a.rs".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_drops_unknown_and_orders_conflicts_first() {
+        let overlaps = vec![
+            (pr(2, &["a"]), vec!["a".to_string()]),
+            (pr(3, &["a"]), vec!["a".to_string()]),
+            (pr(4, &["a"]), vec!["a".to_string()]),
+        ];
+        let (related, warnings) = merge_assessments(
+            overlaps,
+            vec![
+                assessment(2, "none"),
+                assessment(3, "conflict"),
+                assessment(4, "maybe"),
+                assessment(9, "duplicate"),
+            ],
+        );
+        let got: Vec<(u64, &str)> = related
+            .iter()
+            .map(|r| (r.number, r.relation.as_str()))
+            .collect();
+        assert_eq!(got, vec![(3, "conflict"), (2, "none"), (4, "none")]);
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[test]
+    fn candidates_truncate_descriptions() {
+        let mut p = pr(2, &["a"]);
+        p.body = Some("x".repeat(MAX_BODY_CHARS + 10));
+        let json = candidates_json(&[(p, vec!["a".to_string()])]).expect("json");
+        let value: serde_json::Value = serde_json::from_str(&json).expect("parse");
+        assert_eq!(
+            value[0]["description"].as_str().map(str::len),
+            Some(MAX_BODY_CHARS)
+        );
+        assert_eq!(value[0]["overlappingFiles"][0], "a");
+    }
+}
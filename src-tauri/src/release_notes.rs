@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+
+use crate::cache;
+use crate::codex_runner::{self, lang_suffix};
+use crate::error::PrvwError;
+use crate::gh;
+use crate::history;
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::provider::{self, Job, ProviderRegistry};
+use crate::report::{render_release_notes_markdown, RELEASE_NOTE_CATEGORIES};
+use crate::settings::RunDefaults;
+use crate::types::{
+    AnalysisResult, ReleaseNoteEntry, ReleaseNotesResponse, ReleaseNotesResult, RunOptions,
+};
+use crate::usage;
+
+const RELEASE_NOTES_SCHEMA: &str = include_str!("../schemas/release_notes.json");
+
+fn build_release_notes_prompt(lang: &Option<String>) -> String {
+    format!(
+        "Read analysis.json, which groups the changes of a pull request by intent. \
+         Turn it into changelog entries for the project's users, not its developers. \
+         Put each entry in one category: added (new user-visible features), \
+         changed (changes to existing behavior), or fixed (bug fixes). \
+         Write each entry as one short sentence describing the change from a user's point of view \
+         (e.g. \"Login attempts are now rate limited\"), and list in groupIds the ids of the groups it is based on. \
+         Use only existing group ids. Do not invent ids. \
+         Skip purely internal changes (refactors, tests, CI, formatting) unless users would notice them. \
+         Return an empty entries array if nothing is user-facing.{}",
+        lang_suffix(lang)
+    )
+}
+
+/// Drop entries with an unknown category or empty text, and group ids not in the analysis.
+fn validate_release_notes(
+    entries: Vec<ReleaseNoteEntry>,
+    group_ids: &HashSet<&str>,
+) -> (Vec<ReleaseNoteEntry>, Vec<String>) {
+    let mut warnings: Vec<String> = Vec::new();
+    let mut cleaned: Vec<ReleaseNoteEntry> = Vec::new();
+
+    for mut entry in entries {
+        if !RELEASE_NOTE_CATEGORIES
+            .iter()
+            .any(|(category, _)| *category == entry.category)
+        {
+            warnings.push(format!(
+                "Removed entry with unknown category '{}'",
+                entry.category
+            ));
+            continue;
+        }
+        if entry.text.trim().is_empty() {
+            warnings.push("Removed empty entry".to_string());
+            continue;
+        }
+        entry.group_ids.retain(|id| {
+            let known = group_ids.contains(id.as_str());
+            if !known {
+                warnings.push(format!("Removed non-existent group id '{}'", id));
+            }
+            known
+        });
+        cleaned.push(entry);
+    }
+    (cleaned, warnings)
+}
+
+/// Turn the analysis into categorized, user-facing changelog entries (also rendered as
+/// Markdown). Cached per PR head commit and analysis content; a caller that passes `head_sha`
+/// gets cache hits without asking GitHub for the head.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_release_notes(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    repo: String,
+    pr_number: u32,
+    head_sha: Option<String>,
+    analysis: AnalysisResult,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<ReleaseNotesResponse, PrvwError> {
+    use tauri::Manager;
+    let op = Op::start(op_id, "generate_release_notes");
+    let result: Result<ReleaseNotesResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                Some(repo.as_str()),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+            let repo = repo.trim().to_string();
+
+            if analysis.groups.is_empty() {
+                return Err("Analysis has no groups to describe.".to_string().into());
+            }
+            let head_sha = match head_sha.filter(|sha| !sha.trim().is_empty()) {
+                Some(sha) => sha.trim().to_string(),
+                None => gh::fetch_head_sha(&repo, pr_number)?,
+            };
+            let analysis_json = serde_json::to_string(&analysis)
+                .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+
+            let app_data_dir = app.path().app_data_dir().ok();
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            let cache_key = cache::hash_key(&format!(
+                "{}\n{}\n{}\n{}\n{}\n{}",
+                repo,
+                pr_number,
+                head_sha,
+                model_str,
+                lang_str,
+                cache::hash_key(&analysis_json)
+            ));
+
+            if force != Some(true) {
+                if let Some(ref dir) = app_data_dir {
+                    if let Some(mut cached) = cache::read_cache_async::<ReleaseNotesResponse>(
+                        dir,
+                        "cache/release_notes",
+                        &cache_key,
+                    )
+                    .await
+                    {
+                        cached.from_cache = true;
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (temp_dir, schema_path, output_path) = codex_runner::prepare_temp_dir(
+                &analysis_json,
+                RELEASE_NOTES_SCHEMA,
+                "release_notes.json",
+            )?;
+
+            // The input is the analysis, not hunks
+            let temp_path = temp_dir.path();
+            std::fs::rename(
+                temp_path.join("hunks.json"),
+                temp_path.join("analysis.json"),
+            )
+            .map_err(|e| format!("Failed to rename temp file: {}", e))?;
+
+            let prompt = build_release_notes_prompt(&lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens =
+                usage::estimate_tokens(&analysis_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path,
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir.as_deref(),
+                "release-notes",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read release_notes.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: ReleaseNotesResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse release_notes.json: {}", e))?;
+
+            let group_ids: HashSet<&str> = analysis.groups.iter().map(|g| g.id.as_str()).collect();
+            let (entries, warnings) = validate_release_notes(result.entries, &group_ids);
+
+            let mut log = codex_runner::build_log("release-notes", &codex_output);
+            log.push_str(&format!(
+                "[release-notes] head={} entries={}\n",
+                head_sha,
+                entries.len()
+            ));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let response = ReleaseNotesResponse {
+                markdown: render_release_notes_markdown(&entries),
+                entries,
+                head_sha,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(ref dir) = app_data_dir {
+                cache::write_cache(dir, "cache/release_notes", &cache_key, &response);
+            }
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(category: &str, text: &str, group_ids: &[&str]) -> ReleaseNoteEntry {
+        ReleaseNoteEntry {
+            category: category.to_string(),
+            text: text.to_string(),
+            group_ids: group_ids.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn validate_drops_bad_entries_and_ids() {
+        let group_ids: HashSet<&str> = ["G1"].into_iter().collect();
+        let (cleaned, warnings) = validate_release_notes(
+            vec![
+                entry("added", "Rate limiting", &["G1", "G9"]),
+                entry("removed", "Old API", &["G1"]),
+                entry("fixed", "  ", &[]),
+            ],
+            &group_ids,
+        );
+        assert_eq!(cleaned.len(), 1);
+        assert_eq!(cleaned[0].group_ids, vec!["G1"]);
+        assert_eq!(warnings.len(), 3);
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::error::PrvwError;
+use crate::gh;
+use crate::notifications;
+use crate::prefetch::{MAX_QUEUE, REVIEW_QUEUE_SEARCH};
+use crate::run_state::RunState;
+use crate::session;
+use crate::time::{now_secs, parse_timestamp, DAY_SECS};
+use crate::types::{PrListItem, ReminderRequest, ReminderStatus, ReviewSession, StaleReview};
+
+const DEFAULT_INTERVAL_MINUTES: u64 = 60;
+/// How often a running scheduler wakes up to check whether it was stopped.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Errors kept in the status; older ones are dropped.
+const MAX_ERRORS: usize = 20;
+
+/// Managed state for the reminder scheduler; each start or stop replaces the running one.
+pub type ReminderState = RunState<ReminderStatus>;
+
+/// Whether the session has any hunk or group reviewed at `head_sha` (marks without a head
+/// count, as they predate head tracking).
+fn reviewed_at(session: &ReviewSession, head_sha: Option<&str>) -> bool {
+    session
+        .reviewed_hunks
+        .values()
+        .chain(session.reviewed_groups.values())
+        .any(|mark| match (mark.head_sha.as_deref(), head_sha) {
+            (Some(mark_head), Some(head)) => mark_head == head,
+            _ => true,
+        })
+}
+
+/// Review requests open at least `stale_days` with no review progress at their current head.
+/// Drafts are left out.
+fn stale_reviews(
+    app_data_dir: &Path,
+    repo: &str,
+    queue: &[PrListItem],
+    stale_days: u32,
+    now: u64,
+) -> Vec<StaleReview> {
+    queue
+        .iter()
+        .filter(|pr| pr.is_draft != Some(true))
+        .filter_map(|pr| {
+            let pr_number = u32::try_from(pr.number).ok()?;
+            let age_days = now.saturating_sub(parse_timestamp(&pr.created_at)?) / DAY_SECS;
+            if age_days < u64::from(stale_days) {
+                return None;
+            }
+            let session = session::load(app_data_dir, repo, pr_number);
+            if reviewed_at(&session, pr.head_ref_oid.as_deref()) {
+                return None;
+            }
+            Some(StaleReview {
+                pr_number,
+                title: pr.title.clone(),
+                url: pr.url.clone(),
+                head_sha: pr.head_ref_oid.clone(),
+                age_days,
+            })
+        })
+        .collect()
+}
+
+fn check(app_data_dir: &Path, repo: &str, stale_days: u32) -> Result<Vec<StaleReview>, String> {
+    let queue = gh::fetch_pr_list(
+        repo,
+        MAX_QUEUE,
+        "open",
+        Some(REVIEW_QUEUE_SEARCH.to_string()),
+    )?;
+    Ok(stale_reviews(
+        app_data_dir,
+        repo,
+        &queue,
+        stale_days,
+        now_secs(),
+    ))
+}
+
+fn notify(app: &AppHandle, repo: &str, review: &StaleReview) {
+    notifications::show(
+        app,
+        &format!("Review waiting: {}#{}", repo, review.pr_number),
+        &format!(
+            "{} — requested {} day(s) ago",
+            review.title, review.age_days
+        ),
+    );
+}
+
+fn run_reminders(app: &AppHandle, run_id: u64, request: &ReminderRequest) {
+    let state = app.state::<ReminderState>();
+    let Ok(app_data_dir) = app.path().app_data_dir() else {
+        state.update(run_id, |s| {
+            s.active = false;
+            s.errors.push("Failed to get app data dir".to_string());
+        });
+        return;
+    };
+    let interval = request
+        .interval_minutes
+        .filter(|m| *m > 0)
+        .unwrap_or(DEFAULT_INTERVAL_MINUTES)
+        .saturating_mul(60);
+    // Each PR is reminded about once per head; a push makes it pending again.
+    let mut notified: HashSet<(u32, String)> = HashSet::new();
+
+    while state.is_current(run_id) {
+        match check(&app_data_dir, &request.repo, request.stale_days) {
+            Ok(pending) => {
+                for review in &pending {
+                    let head = review.head_sha.clone().unwrap_or_default();
+                    if notified.insert((review.pr_number, head)) {
+                        notify(app, &request.repo, review);
+                    }
+                }
+                state.update(run_id, |s| {
+                    s.last_checked_at = Some(now_secs());
+                    s.pending = pending;
+                });
+            }
+            Err(e) => state.update(run_id, |s| {
+                if s.errors.len() >= MAX_ERRORS {
+                    s.errors.remove(0);
+                }
+                s.errors.push(e);
+            }),
+        }
+
+        let next_at = now_secs().saturating_add(interval);
+        while now_secs() < next_at {
+            if !state.is_current(run_id) {
+                return;
+            }
+            std::thread::sleep(
+                POLL_INTERVAL.min(Duration::from_secs(next_at.saturating_sub(now_secs()))),
+            );
+        }
+    }
+}
+
+/// Start checking the repo's review queue in the background, replacing a running scheduler:
+/// every `interval_minutes`, review requests open `stale_days` or longer with no review
+/// progress at their current head fire a desktop notification.
+#[tauri::command]
+pub async fn start_review_reminders(
+    app: AppHandle,
+    state: tauri::State<'_, ReminderState>,
+    request: ReminderRequest,
+) -> Result<ReminderStatus, PrvwError> {
+    let repo = request.repo.trim().to_string();
+    gh::validate_repo(&repo)?;
+    let request = ReminderRequest { repo, ..request };
+
+    let run_id = state.begin();
+    let status = ReminderStatus {
+        active: true,
+        repo: Some(request.repo.clone()),
+        stale_days: request.stale_days,
+        ..ReminderStatus::default()
+    };
+    state.update(run_id, |s| *s = status.clone());
+
+    std::thread::spawn(move || run_reminders(&app, run_id, &request));
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn stop_review_reminders(
+    state: tauri::State<'_, ReminderState>,
+) -> Result<ReminderStatus, PrvwError> {
+    let run_id = state.begin();
+    state.update(run_id, |s| s.active = false);
+    Ok(state.snapshot())
+}
+
+#[tauri::command]
+pub async fn get_review_reminders(
+    state: tauri::State<'_, ReminderState>,
+) -> Result<ReminderStatus, PrvwError> {
+    Ok(state.snapshot())
+}
+
+/// One-off check of the review queue, without notifications.
+#[tauri::command]
+pub async fn check_stale_reviews(
+    app: AppHandle,
+    repo: String,
+    stale_days: u32,
+) -> Result<Vec<StaleReview>, PrvwError> {
+    let repo = repo.trim();
+    gh::validate_repo(repo)?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(check(&app_data_dir, repo, stale_days)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ReviewMark, SessionUpdate};
+
+    fn pr(number: u64, created_at: &str, head: &str) -> PrListItem {
+        PrListItem {
+            number,
+            title: format!("PR {}", number),
+            url: String::new(),
+            updated_at: String::new(),
+            created_at: created_at.to_string(),
+            author: None,
+            head_ref_name: None,
+            head_ref_oid: Some(head.to_string()),
+            base_ref_name: None,
+            head_repository_owner: None,
+            is_cross_repository: None,
+            review_decision: None,
+            is_draft: None,
+            body: None,
+        }
+    }
+
+    #[test]
+    fn picks_old_requests_without_progress_at_head() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut session = session::load(tmp.path(), "o/r", 2);
+        session::apply_update(
+            &mut session,
+            &SessionUpdate {
+                head_sha: Some("b".to_string()),
+                mark_hunks: vec!["H1".to_string()],
+                ..SessionUpdate::default()
+            },
+            1,
+        );
+        session::save(tmp.path(), &session).unwrap();
+        let mut stale_session = session::load(tmp.path(), "o/r", 3);
+        stale_session.reviewed_groups.insert(
+            "G1".to_string(),
+            ReviewMark {
+                at: 1,
+                head_sha: Some("old".to_string()),
+            },
+        );
+        session::save(tmp.path(), &stale_session).unwrap();
+
+        let now = parse_timestamp("2024-01-10T00:00:00Z").unwrap();
+        let queue = vec![
+            pr(1, "2024-01-01T00:00:00Z", "a"),
+            // reviewed at its head
+            pr(2, "2024-01-01T00:00:00Z", "b"),
+            // reviewed before the last push
+            pr(3, "2024-01-01T00:00:00Z", "c"),
+            // too recent
+            pr(4, "2024-01-09T00:00:00Z", "d"),
+        ];
+        let stale = stale_reviews(tmp.path(), "o/r", &queue, 3, now);
+        let numbers: Vec<u32> = stale.iter().map(|r| r.pr_number).collect();
+        assert_eq!(numbers, vec![1, 3]);
+        assert_eq!(stale[0].age_days, 9);
+    }
+}
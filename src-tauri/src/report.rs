@@ -0,0 +1,21 @@
+use crate::error::PrvwError;
+use crate::types::AnalysisResult;
+
+pub use prvw_core::report::*;
+
+/// With `repo` and `pr_number`, the PR's session orders the groups, ticks checked checklist
+/// items and calls out skipped groups.
+#[tauri::command]
+pub async fn render_review_summary(
+    app: tauri::AppHandle,
+    analysis: AnalysisResult,
+    repo: Option<String>,
+    pr_number: Option<u32>,
+) -> Result<String, PrvwError> {
+    render_summary(
+        &crate::app_data_dir(&app)?,
+        &analysis,
+        repo.as_deref(),
+        pr_number,
+    )
+}
@@ -0,0 +1,39 @@
+use crate::error::PrvwError;
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::types::{AnalysisResult, ReviewState};
+
+pub use prvw_core::review_state::*;
+
+/// Parse `diff_text` like `parse_diff` and keep the hunks in the backend. Later analysis and
+/// refinement calls pass the returned `sessionId` (and hunk ids) instead of the hunks. The same
+/// diff of the same repo always gets the same id.
+#[tauri::command]
+pub async fn open_review_state(
+    states: tauri::State<'_, ReviewStates>,
+    diff_text: String,
+    repo: Option<String>,
+    op_id: Option<String>,
+) -> Result<ReviewState, PrvwError> {
+    let op = Op::start(op_id, "open_review_state");
+    op.progress(t(Msg::ParsingDiff, &[]));
+    Ok(op.finish(states.open(diff_text, repo))?)
+}
+
+/// The latest analysis run on the review, if any.
+#[tauri::command]
+pub async fn get_review_state_analysis(
+    states: tauri::State<'_, ReviewStates>,
+    session_id: String,
+) -> Result<Option<AnalysisResult>, PrvwError> {
+    Ok(states.analysis(&session_id)?)
+}
+
+/// Drop the review's hunks and analysis, e.g. when its tab closes. Returns whether it was open.
+#[tauri::command]
+pub async fn close_review_state(
+    states: tauri::State<'_, ReviewStates>,
+    session_id: String,
+) -> Result<bool, PrvwError> {
+    Ok(states.close(&session_id)?)
+}
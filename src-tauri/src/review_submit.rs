@@ -0,0 +1,308 @@
+use serde_json::json;
+
+use crate::error::PrvwError;
+use crate::gh::{self, validate_repo};
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::session;
+use crate::types::{AssembledReview, BlockingItem, DraftComment, Hunk, ReviewCommentPayload};
+use crate::validation::hunk_has_line;
+
+/// GitHub review event for a verdict.
+fn review_event(verdict: &str) -> Result<&'static str, String> {
+    match verdict.trim().to_ascii_lowercase().as_str() {
+        "approve" => Ok("APPROVE"),
+        "request_changes" | "request-changes" => Ok("REQUEST_CHANGES"),
+        "comment" | "" => Ok("COMMENT"),
+        other => Err(format!("Unknown review verdict '{}'", other)),
+    }
+}
+
+/// Place each draft on the current diff: its own hunk first, then any hunk of the same file
+/// that shows the line on that side (the diff may have been re-fetched since drafting).
+/// Returns the placed comments and the drafts that fit nowhere.
+fn place_comments(
+    drafts: Vec<DraftComment>,
+    hunks: &[Hunk],
+) -> (Vec<ReviewCommentPayload>, Vec<DraftComment>) {
+    let mut placed: Vec<ReviewCommentPayload> = Vec::new();
+    let mut unplaced: Vec<DraftComment> = Vec::new();
+    for draft in drafts {
+        if draft.body.trim().is_empty() {
+            continue;
+        }
+        let side = if draft.side == "LEFT" {
+            "LEFT"
+        } else {
+            "RIGHT"
+        };
+        let fits = |h: &&Hunk| hunk_has_line(h, draft.line, side);
+        let hunk = hunks
+            .iter()
+            .filter(|h| h.id == draft.hunk_id && h.file_path == draft.file_path)
+            .find(fits)
+            .or_else(|| {
+                hunks
+                    .iter()
+                    .filter(|h| h.file_path == draft.file_path)
+                    .find(fits)
+            });
+        match hunk {
+            Some(hunk) => placed.push(ReviewCommentPayload {
+                path: hunk.file_path.clone(),
+                line: draft.line,
+                side: side.to_string(),
+                body: draft.body.trim().to_string(),
+            }),
+            None => unplaced.push(draft),
+        }
+    }
+    (placed, unplaced)
+}
+
+/// The overall body, with unplaced drafts appended as a list so nothing queued is lost.
+fn assemble_body(body: &str, unplaced: &[DraftComment]) -> String {
+    let mut out = body.trim().to_string();
+    if unplaced.is_empty() {
+        return out;
+    }
+    if !out.is_empty() {
+        out.push_str("\n\n---\n\n");
+    }
+    out.push_str("**Comments on lines outside the current diff:**\n");
+    for c in unplaced {
+        out.push_str(&format!(
+            "\n- `{}:{}` — {}",
+            c.file_path,
+            c.line,
+            c.body.trim()
+        ));
+    }
+    out
+}
+
+/// Turn an approval into "request changes" while blocking items are open, and list the open
+/// items at the top of the body of any "request changes" review. Returns the event to submit
+/// and whether an approval was blocked.
+fn gate_on_blocking(
+    event: &'static str,
+    body: String,
+    open: &[BlockingItem],
+) -> (&'static str, String, bool) {
+    if open.is_empty() || event == "COMMENT" {
+        return (event, body, false);
+    }
+    let mut out = "**Blocking items to address before approval:**\n".to_string();
+    for item in open {
+        out.push_str(&format!("\n- [ ] {}", item.text.trim()));
+    }
+    if !body.is_empty() {
+        out.push_str("\n\n");
+        out.push_str(&body);
+    }
+    ("REQUEST_CHANGES", out, event == "APPROVE")
+}
+
+/// Submit the queued draft comments and a verdict as one GitHub review (a single
+/// `POST /pulls/{n}/reviews` call, anchored to `head_sha`, the commit `hunks_json` was parsed
+/// at). Comments are placed on that diff; those that no longer fit are listed in the body. A
+/// PR whose head has moved since is refused, so comments never land on lines of another diff. With `dry_run`, returns
+/// the assembled review without submitting it. While the session has open blocking items, an
+/// approval is refused and sent as "request changes" summarizing them.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn submit_assembled_review(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    hunks_json: String,
+    head_sha: String,
+    comments: Vec<DraftComment>,
+    verdict: String,
+    body: Option<String>,
+    dry_run: Option<bool>,
+    op_id: Option<String>,
+) -> Result<AssembledReview, PrvwError> {
+    use tauri::Manager;
+    let op = Op::start(op_id, "submit_assembled_review");
+    let result: Result<AssembledReview, PrvwError> = op.scope(async {
+        let repo = repo.trim();
+        validate_repo(repo)?;
+        let hunks: Vec<Hunk> =
+            serde_json::from_str(&hunks_json).map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+        let event = review_event(&verdict)?;
+
+        let (placed, unplaced) = place_comments(comments, &hunks);
+        let body = assemble_body(body.as_deref().unwrap_or(""), &unplaced);
+        let app_data_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+        let blocking_items =
+            session::open_blocking_items(&session::load(&app_data_dir, repo, pr_number));
+        let (event, body, approval_blocked) = gate_on_blocking(event, body, &blocking_items);
+        if event != "APPROVE" && body.is_empty() && placed.is_empty() {
+            return Err(PrvwError::InvalidInput(
+                "Nothing to submit: add a review body or comments.".to_string(),
+            ));
+        }
+
+        let mut review = AssembledReview {
+            event: event.to_string(),
+            body,
+            comments: placed,
+            unplaced,
+            dry_run: dry_run == Some(true),
+            url: None,
+            blocking_items,
+            approval_blocked,
+        };
+        if review.dry_run {
+            return Ok(review);
+        }
+
+        let head_sha = head_sha.trim();
+        let current = gh::fetch_head_sha(repo, pr_number)?;
+        if current != head_sha {
+            return Err(PrvwError::InvalidInput(format!(
+                "PR #{} has new commits ({}) since its diff was loaded at {}; refresh it before submitting.",
+                pr_number, current, head_sha
+            )));
+        }
+        let payload = json!({
+            "commit_id": head_sha,
+            "event": review.event,
+            "body": review.body,
+            "comments": review.comments,
+        });
+        // Pass the payload through a file to avoid argument length limits on large reviews.
+        let payload_file = tempfile::NamedTempFile::new()
+            .map_err(|e| format!("Failed to create temp file: {}", e))?;
+        std::fs::write(payload_file.path(), payload.to_string())
+            .map_err(|e| format!("Failed to write review payload: {}", e))?;
+        let payload_path = payload_file
+            .path()
+            .to_str()
+            .ok_or_else(|| "Non-UTF-8 temp path".to_string())?;
+
+        op.progress(t(Msg::SubmittingReview, &[&repo, &pr_number]));
+        let endpoint = format!("repos/{}/pulls/{}/reviews", repo, pr_number);
+        let output = gh::run_gh(
+            &[
+                "api",
+                "--method",
+                "POST",
+                &endpoint,
+                "--input",
+                payload_path,
+            ],
+            "gh api reviews",
+        )?;
+        let response: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Failed to parse review response: {}", e))?;
+        review.url = response["html_url"].as_str().map(str::to_string);
+        Ok(review)
+    })
+    .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DiffLine;
+
+    fn hunk(id: &str, file_path: &str, new_lines: &[u32]) -> Hunk {
+        Hunk {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            subproject: None,
+            header: String::new(),
+            old_start: 1,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 0,
+            lines: new_lines
+                .iter()
+                .map(|n| DiffLine {
+                    kind: "add".to_string(),
+                    old_line: None,
+                    new_line: Some(*n),
+                    text: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    fn draft(hunk_id: &str, file_path: &str, line: u32) -> DraftComment {
+        DraftComment {
+            hunk_id: hunk_id.to_string(),
+            file_path: file_path.to_string(),
+            line,
+            side: "RIGHT".to_string(),
+            body: format!("comment on {}", line),
+            severity: "nit".to_string(),
+        }
+    }
+
+    #[test]
+    fn places_comments_on_current_diff() {
+        let hunks = vec![hunk("H1", "a.rs", &[1, 2]), hunk("H2", "a.rs", &[40, 41])];
+        let (placed, unplaced) = place_comments(
+            vec![
+                draft("H1", "a.rs", 2),
+                // drafted against an older diff: the hunk id moved, the line did not
+                draft("H9", "a.rs", 41),
+                draft("H1", "a.rs", 99),
+                draft("H1", "b.rs", 1),
+            ],
+            &hunks,
+        );
+        assert_eq!(
+            placed.iter().map(|c| c.line).collect::<Vec<_>>(),
+            vec![2, 41]
+        );
+        assert_eq!(
+            unplaced.iter().map(|c| c.line).collect::<Vec<_>>(),
+            vec![99, 1]
+        );
+    }
+
+    #[test]
+    fn body_lists_unplaced_comments() {
+        let body = assemble_body("Looks good overall.", &[draft("H1", "a.rs", 99)]);
+        assert!(body.starts_with("Looks good overall.\n\n---"));
+        assert!(body.contains("- `a.rs:99` — comment on 99"));
+        assert_eq!(assemble_body("  ", &[]), "");
+    }
+
+    #[test]
+    fn open_blocking_items_turn_approval_into_request_changes() {
+        let open = vec![BlockingItem {
+            id: "G1:checklist:0".to_string(),
+            kind: "checklist".to_string(),
+            group_id: Some("G1".to_string()),
+            hunk_id: None,
+            text: "Migration is reversible".to_string(),
+            resolved: false,
+            at: 0,
+        }];
+        let (event, body, blocked) = gate_on_blocking("APPROVE", "LGTM".to_string(), &open);
+        assert_eq!((event, blocked), ("REQUEST_CHANGES", true));
+        assert!(body.starts_with("**Blocking items to address before approval:**"));
+        assert!(body.contains("- [ ] Migration is reversible\n\nLGTM"));
+
+        let (event, body, blocked) = gate_on_blocking("COMMENT", "fyi".to_string(), &open);
+        assert_eq!((event, body.as_str(), blocked), ("COMMENT", "fyi", false));
+        let (event, _, blocked) = gate_on_blocking("APPROVE", String::new(), &[]);
+        assert_eq!((event, blocked), ("APPROVE", false));
+    }
+
+    #[test]
+    fn verdicts_map_to_review_events() {
+        assert_eq!(review_event("approve"), Ok("APPROVE"));
+        assert_eq!(review_event("request_changes"), Ok("REQUEST_CHANGES"));
+        assert_eq!(review_event(""), Ok("COMMENT"));
+        assert!(review_event("merge").is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Managed state of a background thread that a newer run replaces, with the status it reports
+/// into. Each `begin` starts a new run; a running thread stops as soon as it notices it is no
+/// longer the current run, and its late updates are dropped.
+pub struct RunState<S> {
+    run_id: AtomicU64,
+    status: Mutex<S>,
+}
+
+impl<S: Default> Default for RunState<S> {
+    fn default() -> Self {
+        RunState::new(S::default())
+    }
+}
+
+impl<S> RunState<S> {
+    pub const fn new(status: S) -> Self {
+        RunState {
+            run_id: AtomicU64::new(0),
+            status: Mutex::new(status),
+        }
+    }
+
+    /// Start a new run, making the running one stale. Returns the new run's id.
+    pub fn begin(&self) -> u64 {
+        self.run_id.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub fn is_current(&self, run_id: u64) -> bool {
+        self.run_id.load(Ordering::SeqCst) == run_id
+    }
+
+    /// Apply `f` to the status, unless a newer run has taken over.
+    pub fn update(&self, run_id: u64, f: impl FnOnce(&mut S)) {
+        if let Ok(mut status) = self.status.lock() {
+            if self.is_current(run_id) {
+                f(&mut status);
+            }
+        }
+    }
+
+    /// Apply `f` to the status whichever run is current, e.g. for a setting kept across runs.
+    pub fn modify<R>(&self, f: impl FnOnce(&mut S) -> R) -> Result<R, String> {
+        let mut status = self
+            .status
+            .lock()
+            .map_err(|_| "Run state lock poisoned".to_string())?;
+        Ok(f(&mut status))
+    }
+}
+
+impl<S: Clone + Default> RunState<S> {
+    pub fn snapshot(&self) -> S {
+        self.status.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_runs_cannot_update_status() {
+        let state = RunState::<u32>::default();
+        let (old, new) = (state.begin(), state.begin());
+        state.update(old, |s| *s = 5);
+        assert_eq!(state.snapshot(), 0);
+        state.update(new, |s| *s = 1);
+        assert_eq!(state.snapshot(), 1);
+        assert!(!state.is_current(old));
+        state.modify(|s| *s += 1).unwrap();
+        assert_eq!(state.snapshot(), 2);
+    }
+}
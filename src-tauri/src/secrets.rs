@@ -0,0 +1,28 @@
+use crate::error::PrvwError;
+use crate::types::SecretTest;
+
+pub use prvw_core::secrets::*;
+
+/// Store a secret (`openai`, `github`, `gitlab`, `gitea`, `azure-devops`, or `bitbucket` as
+/// `username:app_password`) in the OS keyring.
+#[tauri::command]
+pub async fn set_secret(name: String, value: String) -> Result<(), PrvwError> {
+    set(Secret::parse(&name)?, &value)
+}
+
+/// Whether a value is stored for the secret.
+#[tauri::command]
+pub async fn has_secret(name: String) -> Result<bool, PrvwError> {
+    Ok(get(Secret::parse(&name)?)?.is_some())
+}
+
+#[tauri::command]
+pub async fn delete_secret(name: String) -> Result<(), PrvwError> {
+    Ok(delete(Secret::parse(&name)?)?)
+}
+
+/// Check the stored secret with an authenticated request to its service.
+#[tauri::command]
+pub async fn test_secret(name: String) -> Result<SecretTest, PrvwError> {
+    test(Secret::parse(&name)?)
+}
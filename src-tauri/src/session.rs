@@ -0,0 +1,166 @@
+use crate::error::PrvwError;
+use crate::types::{
+    AnalysisResult, ChecklistItem, NextUnreviewed, ReviewProgress, ReviewSession, SessionUpdate,
+};
+
+/// Sessions are kept by `prvw_core::session`; this module adds their commands.
+pub use prvw_core::session::*;
+
+/// The saved review progress of a PR; an empty session if none was saved.
+#[tauri::command]
+pub async fn get_session(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+) -> Result<ReviewSession, PrvwError> {
+    get(&crate::app_data_dir(&app)?, &repo, pr_number)
+}
+
+/// Mark or unmark groups and hunks as reviewed and save the session. Returns the saved session.
+#[tauri::command]
+pub async fn update_session(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    update: SessionUpdate,
+) -> Result<ReviewSession, PrvwError> {
+    self::update(&crate::app_data_dir(&app)?, &repo, pr_number, &update)
+}
+
+/// The reviewer checklist of every group in the analysis, with each item's checked state.
+#[tauri::command]
+pub async fn get_checklist(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    analysis: AnalysisResult,
+) -> Result<Vec<ChecklistItem>, PrvwError> {
+    let session = get(&crate::app_data_dir(&app)?, &repo, pr_number)?;
+    Ok(checklist_items(&session, &analysis))
+}
+
+/// Check or uncheck checklist items by id and save the session. Returns the saved session.
+#[tauri::command]
+pub async fn check_checklist_items(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    ids: Vec<String>,
+    checked: bool,
+    head_sha: Option<String>,
+) -> Result<ReviewSession, PrvwError> {
+    let (check_items, uncheck_items) = if checked {
+        (ids, Vec::new())
+    } else {
+        (Vec::new(), ids)
+    };
+    let update = SessionUpdate {
+        head_sha,
+        check_items,
+        uncheck_items,
+        ..SessionUpdate::default()
+    };
+    update_session(app, repo, pr_number, update).await
+}
+
+/// Set groups aside with a reason: skipped until undone, or with `snooze` only until the next
+/// push. Either way they drop out of progress and `next_unreviewed`. Returns the saved session.
+#[tauri::command]
+pub async fn skip_groups(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    group_ids: Vec<String>,
+    reason: Option<String>,
+    snooze: Option<bool>,
+    head_sha: Option<String>,
+) -> Result<ReviewSession, PrvwError> {
+    let update = SessionUpdate {
+        head_sha,
+        skip_groups: group_ids,
+        skip_reason: reason,
+        snooze: snooze == Some(true),
+        ..SessionUpdate::default()
+    };
+    update_session(app, repo, pr_number, update).await
+}
+
+#[tauri::command]
+pub async fn unskip_groups(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    group_ids: Vec<String>,
+) -> Result<ReviewSession, PrvwError> {
+    let update = SessionUpdate {
+        unskip_groups: group_ids,
+        ..SessionUpdate::default()
+    };
+    update_session(app, repo, pr_number, update).await
+}
+
+/// Save a custom group order for the PR at `head_sha`; exports and the submitted summary follow
+/// it. An empty list restores the analysis order. Returns the saved session.
+#[tauri::command]
+pub async fn set_group_order(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    group_ids: Vec<String>,
+    head_sha: Option<String>,
+) -> Result<ReviewSession, PrvwError> {
+    let update = SessionUpdate {
+        head_sha,
+        group_order: Some(group_ids),
+        ..SessionUpdate::default()
+    };
+    update_session(app, repo, pr_number, update).await
+}
+
+/// `analysis` with its groups in the PR's saved custom order, for display.
+#[tauri::command]
+pub async fn order_analysis(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    mut analysis: AnalysisResult,
+) -> Result<AnalysisResult, PrvwError> {
+    let session = get(&crate::app_data_dir(&app)?, &repo, pr_number)?;
+    apply_group_order(&mut analysis, &session);
+    Ok(analysis)
+}
+
+/// Progress of the saved session over the PR's analysis, for progress bars and a
+/// "ready to submit" signal.
+#[tauri::command]
+pub async fn get_review_progress(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    analysis: AnalysisResult,
+) -> Result<ReviewProgress, PrvwError> {
+    let session = get(&crate::app_data_dir(&app)?, &repo, pr_number)?;
+    Ok(progress(&session, &analysis))
+}
+
+/// Where to go next in a keyboard-driven review: the next unreviewed hunk after
+/// `current_hunk_id` and its group. `None` when everything is reviewed or skipped.
+#[tauri::command]
+pub async fn next_unreviewed(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    analysis: AnalysisResult,
+    group_order: Option<Vec<String>>,
+    skip_group_ids: Option<Vec<String>>,
+    current_hunk_id: Option<String>,
+) -> Result<Option<NextUnreviewed>, PrvwError> {
+    let session = get(&crate::app_data_dir(&app)?, &repo, pr_number)?;
+    Ok(next_unreviewed_hunk(
+        &session,
+        &analysis,
+        group_order.as_deref().unwrap_or_default(),
+        skip_group_ids.as_deref().unwrap_or_default(),
+        current_hunk_id.as_deref(),
+    ))
+}
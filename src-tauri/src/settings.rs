@@ -0,0 +1,40 @@
+use crate::error::PrvwError;
+use crate::types::{AppSettings, RepoSettings, SettingsUpdate};
+
+/// Settings are kept by `prvw_core::settings`; this module adds their commands.
+pub use prvw_core::settings::*;
+
+/// The settings in effect: the saved ones with `prvw.toml` applied. `get_config_status` names
+/// the keys the file sets.
+#[tauri::command]
+pub async fn get_settings(app: tauri::AppHandle) -> Result<AppSettings, PrvwError> {
+    Ok(get(&crate::app_data_dir(&app)?))
+}
+
+/// Apply `update` to the saved settings and return the settings in effect; see `update`.
+#[tauri::command]
+pub async fn update_settings(
+    app: tauri::AppHandle,
+    update: SettingsUpdate,
+) -> Result<AppSettings, PrvwError> {
+    self::update(&crate::app_data_dir(&app)?, update)
+}
+
+/// The overrides saved for `repo`, if any.
+#[tauri::command]
+pub async fn get_repo_settings(
+    app: tauri::AppHandle,
+    repo: String,
+) -> Result<Option<RepoSettings>, PrvwError> {
+    Ok(get_repo(&crate::app_data_dir(&app)?, &repo))
+}
+
+/// Save the overrides for `repo`; None (or overrides with nothing set) removes them.
+#[tauri::command]
+pub async fn set_repo_settings(
+    app: tauri::AppHandle,
+    repo: String,
+    overrides: Option<RepoSettings>,
+) -> Result<Option<RepoSettings>, PrvwError> {
+    set_repo(&crate::app_data_dir(&app)?, &repo, overrides)
+}
@@ -0,0 +1,395 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::error::PrvwError;
+use crate::gh::{self, validate_repo};
+use crate::report::render_review_markdown;
+use crate::session;
+use crate::types::{
+    AnalysisResult, Hunk, HunkAnchor, ImportedAnalysis, ReviewSession, SharedAnalysis,
+};
+use crate::validation::validate_analysis;
+
+pub const SHARED_FORMAT: &str = "prvw-analysis";
+
+/// The exported JSON: the analysis plus anchors of the hunks it was made on.
+fn shared_analysis(
+    repo: Option<String>,
+    pr_number: Option<u32>,
+    head_sha: Option<String>,
+    analysis: AnalysisResult,
+    hunks: &[Hunk],
+) -> SharedAnalysis {
+    SharedAnalysis {
+        format: SHARED_FORMAT.to_string(),
+        repo,
+        pr_number,
+        head_sha,
+        analysis,
+        anchors: hunk_anchors(hunks),
+    }
+}
+
+/// What identifies each hunk across re-fetched diffs: its file and header.
+pub fn hunk_anchors(hunks: &[Hunk]) -> Vec<HunkAnchor> {
+    hunks
+        .iter()
+        .map(|h| HunkAnchor {
+            id: h.id.clone(),
+            file_path: h.file_path.clone(),
+            header: h.header.clone(),
+        })
+        .collect()
+}
+
+/// Gist file name, e.g. `prvw-owner-repo-42.md`.
+fn gist_file_name(repo: Option<&str>, pr_number: Option<u32>, extension: &str) -> String {
+    let mut name = String::from("prvw");
+    if let Some(repo) = repo {
+        name.push('-');
+        name.push_str(&repo.replace('/', "-"));
+    }
+    if let Some(n) = pr_number {
+        name.push_str(&format!("-{}", n));
+    }
+    format!("{}.{}", name, extension)
+}
+
+/// Upload the review report (`format` "markdown") or the analysis JSON with its hunk anchors
+/// (`format` "json", importable with `import_analysis`) as a secret gist. Returns the gist URL.
+/// With `repo` and `pr_number`, groups follow the PR's custom group order, and the report ticks
+/// checked checklist items and calls out skipped groups.
+#[tauri::command]
+pub async fn share_analysis_gist(
+    app: tauri::AppHandle,
+    repo: Option<String>,
+    pr_number: Option<u32>,
+    head_sha: Option<String>,
+    mut analysis: AnalysisResult,
+    hunks_json: Option<String>,
+    format: String,
+) -> Result<String, PrvwError> {
+    use tauri::Manager;
+    let repo = repo.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    if let Some(repo) = &repo {
+        validate_repo(repo)?;
+    }
+    let session = match (&repo, pr_number) {
+        (Some(repo), Some(pr_number)) => {
+            let app_data_dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+            session::load(&app_data_dir, repo, pr_number)
+        }
+        _ => ReviewSession::default(),
+    };
+    session::apply_group_order(&mut analysis, &session);
+    let (content, extension) = match format.as_str() {
+        "markdown" => (render_review_markdown(&analysis, &session), "md"),
+        "json" => {
+            let hunks: Vec<Hunk> = match hunks_json.as_deref() {
+                Some(json) => serde_json::from_str(json)
+                    .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?,
+                None => Vec::new(),
+            };
+            let shared = shared_analysis(repo.clone(), pr_number, head_sha, analysis, &hunks);
+            let json = serde_json::to_string_pretty(&shared)
+                .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+            (json, "json")
+        }
+        other => {
+            return Err(PrvwError::InvalidInput(format!(
+                "Unknown share format '{}'",
+                other
+            )))
+        }
+    };
+
+    // gh names the gist file after the uploaded file.
+    let temp_dir = tempfile::tempdir().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let path = temp_dir
+        .path()
+        .join(gist_file_name(repo.as_deref(), pr_number, extension));
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write gist file: {}", e))?;
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| "Non-UTF-8 temp path".to_string())?;
+    let description = match (&repo, pr_number) {
+        (Some(repo), Some(n)) => format!("prvw analysis of {}#{}", repo, n),
+        _ => "prvw analysis".to_string(),
+    };
+
+    // Gists are secret unless --public is given.
+    let output = gh::run_gh(
+        &["gist", "create", path_str, "--desc", &description],
+        "gh gist create",
+    )?;
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if url.is_empty() {
+        return Err(PrvwError::Other(
+            "gh gist create returned no URL.".to_string(),
+        ));
+    }
+    Ok(url)
+}
+
+/// Function context after the closing `@@` of a hunk header; stable across line shifts.
+fn header_context(header: &str) -> &str {
+    header
+        .splitn(3, "@@")
+        .nth(2)
+        .map(str::trim)
+        .unwrap_or_default()
+}
+
+/// Whether an anchor from an imported analysis points at a hunk of the current diff.
+type AnchorMatcher = dyn Fn(&HunkAnchor, &Hunk) -> bool;
+
+/// Map exported hunk ids to local ones: the same file and header, else the same file and
+/// function context (the lines moved), preferring the same id when several hunks match.
+/// Anchored ids without a match map to nothing; ids without an anchor keep their name.
+pub fn anchor_map(anchors: &[HunkAnchor], hunks: &[Hunk]) -> HashMap<String, Option<String>> {
+    let mut taken: HashSet<&str> = HashSet::new();
+    let mut map: HashMap<String, Option<String>> = HashMap::new();
+    let same_context = |anchor: &HunkAnchor, hunk: &Hunk| {
+        let context = header_context(&anchor.header);
+        !context.is_empty() && context == header_context(&hunk.header)
+    };
+    let matchers: [&AnchorMatcher; 2] = [
+        &|a: &HunkAnchor, h: &Hunk| a.header == h.header,
+        &same_context,
+    ];
+    for matches in matchers {
+        for anchor in anchors {
+            if map.contains_key(&anchor.id) {
+                continue;
+            }
+            let candidates: Vec<&Hunk> = hunks
+                .iter()
+                .filter(|h| {
+                    h.file_path == anchor.file_path
+                        && !taken.contains(h.id.as_str())
+                        && matches(anchor, h)
+                })
+                .collect();
+            let hunk = candidates
+                .iter()
+                .find(|h| h.id == anchor.id)
+                .or_else(|| candidates.first());
+            if let Some(hunk) = hunk {
+                taken.insert(hunk.id.as_str());
+                map.insert(anchor.id.clone(), Some(hunk.id.clone()));
+            }
+        }
+    }
+    for anchor in anchors {
+        map.entry(anchor.id.clone()).or_insert(None);
+    }
+    map
+}
+
+/// Rename the hunk ids of `analysis` through `map`; ids mapped to nothing are dropped.
+/// Returns how many ids changed name.
+pub fn reanchor(analysis: &mut AnalysisResult, map: &HashMap<String, Option<String>>) -> usize {
+    let mut remapped: HashSet<String> = HashSet::new();
+    let mut rename = |ids: &mut Vec<String>| {
+        *ids = ids
+            .drain(..)
+            .filter_map(|id| match map.get(&id) {
+                Some(Some(new_id)) => {
+                    if *new_id != id {
+                        remapped.insert(id);
+                    }
+                    Some(new_id.clone())
+                }
+                Some(None) => None,
+                None => Some(id),
+            })
+            .collect();
+    };
+    for group in &mut analysis.groups {
+        rename(&mut group.hunk_ids);
+    }
+    rename(&mut analysis.unassigned_hunk_ids);
+    rename(&mut analysis.non_substantive_hunk_ids);
+    for group in &mut analysis.groups {
+        for evidence in &mut group.risk_evidence {
+            if let Some(Some(new_id)) = map.get(&evidence.hunk_id) {
+                evidence.hunk_id.clone_from(new_id);
+            }
+        }
+    }
+    remapped.len()
+}
+
+/// A shared analysis file, or a bare analysis JSON (no anchors, ids taken as they are).
+fn parse_import(json: &str) -> Result<SharedAnalysis, String> {
+    if let Ok(shared) = serde_json::from_str::<SharedAnalysis>(json) {
+        if shared.format == SHARED_FORMAT {
+            return Ok(shared);
+        }
+    }
+    let analysis: AnalysisResult =
+        serde_json::from_str(json).map_err(|e| format!("Not an analysis file: {}", e))?;
+    Ok(SharedAnalysis {
+        format: SHARED_FORMAT.to_string(),
+        repo: None,
+        pr_number: None,
+        head_sha: None,
+        analysis,
+        anchors: Vec::new(),
+    })
+}
+
+/// Gist id from a gist URL (`https://gist.github.com/user/<id>`) or a bare id.
+fn gist_id(source: &str) -> Option<&str> {
+    let id = if source.contains("gist.github.com/") {
+        source.trim_end_matches('/').rsplit('/').next()?
+    } else {
+        source
+    };
+    let id = id.split(['#', '?']).next()?;
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit())).then_some(id)
+}
+
+/// Content of the first JSON file of a gist.
+fn fetch_gist_json(id: &str) -> Result<String, String> {
+    let output = gh::run_gh(&["api", &format!("gists/{}", id)], "gh api gists")?;
+    let gist: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse gist: {}", e))?;
+    let file = gist["files"]
+        .as_object()
+        .and_then(|files| {
+            files
+                .iter()
+                .find(|(name, _)| name.ends_with(".json"))
+                .map(|(_, file)| file)
+        })
+        .ok_or_else(|| "The gist has no JSON file.".to_string())?;
+    if file["truncated"].as_bool() == Some(true) {
+        return Err("The gist's analysis file is too large to import.".to_string());
+    }
+    file["content"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "The gist's JSON file is empty.".to_string())
+}
+
+/// Load an analysis exported by `share_analysis_gist` (or a bare analysis JSON) from a file
+/// path or a gist URL, re-anchor its hunk ids on the local diff, and validate it so it can be
+/// installed as the current analysis.
+#[tauri::command]
+pub async fn import_analysis(
+    source: String,
+    hunks_json: String,
+) -> Result<ImportedAnalysis, PrvwError> {
+    let source = source.trim();
+    let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+    if hunks.is_empty() {
+        return Err(PrvwError::InvalidInput(
+            "Load the PR diff before importing an analysis.".to_string(),
+        ));
+    }
+    let json = match gist_id(source) {
+        Some(id)
+            if source.contains("gist.github.com/") || !std::path::Path::new(source).exists() =>
+        {
+            fetch_gist_json(id)?
+        }
+        _ => std::fs::read_to_string(source)
+            .map_err(|e| format!("Failed to read {}: {}", source, e))?,
+    };
+    let SharedAnalysis {
+        repo,
+        pr_number,
+        mut analysis,
+        anchors,
+        ..
+    } = parse_import(&json)?;
+
+    let remapped = reanchor(&mut analysis, &anchor_map(&anchors, &hunks));
+    let valid_ids: HashSet<String> = hunks.iter().map(|h| h.id.clone()).collect();
+    let validation = validate_analysis(&analysis, &valid_ids);
+    Ok(ImportedAnalysis {
+        result: validation.cleaned,
+        remapped,
+        warnings: validation.warnings,
+        repo,
+        pr_number,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_gist_files_after_the_pr() {
+        assert_eq!(
+            gist_file_name(Some("owner/repo"), Some(42), "md"),
+            "prvw-owner-repo-42.md"
+        );
+        assert_eq!(gist_file_name(None, None, "json"), "prvw.json");
+    }
+
+    fn hunk(id: &str, file_path: &str, header: &str) -> Hunk {
+        Hunk {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            subproject: None,
+            header: header.to_string(),
+            old_start: 1,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 0,
+            lines: vec![],
+        }
+    }
+
+    fn anchor(id: &str, file_path: &str, header: &str) -> HunkAnchor {
+        HunkAnchor {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            header: header.to_string(),
+        }
+    }
+
+    #[test]
+    fn reanchors_by_header_then_context() {
+        let anchors = vec![
+            anchor("H1", "a.rs", "@@ -1,3 +1,4 @@ fn main()"),
+            anchor("H2", "a.rs", "@@ -20,3 +21,4 @@ fn helper()"),
+            anchor("H3", "gone.rs", "@@ -1 +1 @@"),
+        ];
+        // the local diff has an extra file first, and helper() moved down
+        let hunks = vec![
+            hunk("H1", "new.rs", "@@ -0,0 +1,9 @@"),
+            hunk("H2", "a.rs", "@@ -1,3 +1,4 @@ fn main()"),
+            hunk("H3", "a.rs", "@@ -25,3 +26,4 @@ fn helper()"),
+        ];
+        let map = anchor_map(&anchors, &hunks);
+        assert_eq!(map["H1"].as_deref(), Some("H2"));
+        assert_eq!(map["H2"].as_deref(), Some("H3"));
+        assert_eq!(map["H3"], None);
+
+        let mut analysis = parse_import(
+            r#"{"version":1,"overallSummary":"","groups":[],"unassignedHunkIds":["H1","H2","H3"],
+                "nonSubstantiveHunkIds":[],"questions":[]}"#,
+        )
+        .unwrap()
+        .analysis;
+        assert_eq!(reanchor(&mut analysis, &map), 2);
+        assert_eq!(analysis.unassigned_hunk_ids, vec!["H2", "H3"]);
+    }
+
+    #[test]
+    fn recognizes_gist_sources() {
+        assert_eq!(
+            gist_id("https://gist.github.com/someone/0a1b2c3d/"),
+            Some("0a1b2c3d")
+        );
+        assert_eq!(gist_id("0a1b2c3d"), Some("0a1b2c3d"));
+        assert_eq!(gist_id("/tmp/analysis.json"), None);
+    }
+}
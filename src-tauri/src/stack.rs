@@ -0,0 +1,225 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::cache;
+use crate::error::PrvwError;
+use crate::gh::{self, validate_repo};
+use crate::types::{PrListItem, PrStack, RepoContext, StackLayer};
+
+/// Open PRs scanned for stacks; stacks rarely span PRs outside the most recent ones.
+const STACK_SCAN_LIMIT: u32 = 100;
+
+fn layer(pr: &PrListItem, parent: Option<u64>) -> Option<StackLayer> {
+    Some(StackLayer {
+        number: pr.number,
+        title: pr.title.clone(),
+        head_ref_name: pr.head_ref_name.clone()?,
+        head_ref_oid: pr.head_ref_oid.clone(),
+        base_ref_name: pr.base_ref_name.clone()?,
+        parent,
+    })
+}
+
+/// Group PRs whose base branch is another PR's head branch into stacks. Each stack starts at a
+/// PR based on a branch no listed PR owns (usually the default branch) and lists its
+/// descendants depth-first, parents before children; lone PRs are not stacks. Heads are keyed
+/// by owner and branch, and fork PRs never parent: a fork's `main` is not the repo's `main`.
+pub fn detect_stacks(repo_owner: &str, prs: &[PrListItem]) -> Vec<PrStack> {
+    let by_head: HashMap<(&str, &str), &PrListItem> = prs
+        .iter()
+        .filter(|pr| pr.is_cross_repository != Some(true))
+        .filter_map(|pr| {
+            let owner = pr
+                .head_repository_owner
+                .as_ref()
+                .map_or(repo_owner, |o| o.login.as_str());
+            Some(((owner, pr.head_ref_name.as_deref()?), pr))
+        })
+        .collect();
+    let mut children: HashMap<u64, Vec<&PrListItem>> = HashMap::new();
+    let mut roots: Vec<&PrListItem> = Vec::new();
+    for pr in prs {
+        // A PR's base branch is always in the PR's own repository.
+        match pr
+            .base_ref_name
+            .as_deref()
+            .and_then(|base| by_head.get(&(repo_owner, base)))
+        {
+            Some(parent) if parent.number != pr.number => {
+                children.entry(parent.number).or_default().push(pr);
+            }
+            _ => roots.push(pr),
+        }
+    }
+
+    let mut stacks: Vec<PrStack> = Vec::new();
+    let mut visited: HashSet<u64> = HashSet::new();
+    for root in roots {
+        if !children.contains_key(&root.number) {
+            continue;
+        }
+        let mut layers: Vec<StackLayer> = Vec::new();
+        let mut pending: Vec<(&PrListItem, Option<u64>)> = vec![(root, None)];
+        while let Some((pr, parent)) = pending.pop() {
+            if !visited.insert(pr.number) {
+                continue;
+            }
+            layers.extend(layer(pr, parent));
+            if let Some(kids) = children.get(&pr.number) {
+                pending.extend(kids.iter().rev().map(|kid| (*kid, Some(pr.number))));
+            }
+        }
+        stacks.push(PrStack { layers });
+    }
+    stacks
+}
+
+/// Stacks of dependent open PRs in `repo`.
+#[tauri::command]
+pub async fn get_pr_stacks(repo: String) -> Result<Vec<PrStack>, PrvwError> {
+    let repo = repo.trim();
+    validate_repo(repo)?;
+    let prs = gh::fetch_pr_list(repo, STACK_SCAN_LIMIT, "open", None)?;
+    let owner = repo.split_once('/').map_or(repo, |(owner, _)| owner);
+    Ok(detect_stacks(owner, &prs))
+}
+
+/// Diff of one stack layer alone: the changes from `base_sha` (the parent layer's head) to
+/// `head_sha`, without the layers below it. Both ends are commits, so the cached diff never
+/// goes stale.
+pub fn fetch_layer_diff(
+    app_data_dir: Option<&Path>,
+    repo: &str,
+    pr_number: u32,
+    base_sha: &str,
+    head_sha: &str,
+) -> Result<String, String> {
+    validate_repo(repo)?;
+    let is_sha = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit());
+    if !is_sha(base_sha) || !is_sha(head_sha) {
+        return Err("Stack layer diffs need commit SHAs.".to_string());
+    }
+    let cache_key = format!(
+        "{}__{}_stack_{}_{}",
+        repo.replace('/', "__"),
+        pr_number,
+        base_sha,
+        head_sha
+    );
+    if let Some(dir) = app_data_dir {
+        if let Some(cached) = cache::read_cache::<String>(dir, "cache/diff", &cache_key) {
+            return Ok(cached);
+        }
+    }
+
+    let endpoint = format!("repos/{}/compare/{}...{}", repo, base_sha, head_sha);
+    let output = gh::run_gh(
+        &[
+            "api",
+            "-H",
+            "Accept: application/vnd.github.v3.diff",
+            &endpoint,
+        ],
+        "gh api compare",
+    )?;
+    let diff = String::from_utf8_lossy(&output.stdout).to_string();
+    if diff.trim().is_empty() {
+        return Err("Diff is empty. This layer may have no changes of its own.".to_string());
+    }
+
+    if let Some(dir) = app_data_dir {
+        let owner = RepoContext {
+            repo: repo.to_string(),
+            pr_number,
+        };
+        cache::write_cache_owned(dir, "cache/diff", &cache_key, &diff, Some(&owner));
+    }
+    Ok(diff)
+}
+
+/// Incremental diff of a stacked PR against its parent layer's head, for analyzing each layer
+/// on its own.
+#[tauri::command]
+pub async fn get_stack_layer_diff(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    base_sha: String,
+    head_sha: String,
+) -> Result<String, PrvwError> {
+    use tauri::Manager;
+    let app_data_dir = app.path().app_data_dir().ok();
+    Ok(fetch_layer_diff(
+        app_data_dir.as_deref(),
+        repo.trim(),
+        pr_number,
+        base_sha.trim(),
+        head_sha.trim(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RepoOwner;
+
+    fn pr(number: u64, head: &str, base: &str) -> PrListItem {
+        PrListItem {
+            number,
+            title: format!("PR {}", number),
+            url: String::new(),
+            updated_at: String::new(),
+            created_at: String::new(),
+            author: None,
+            head_ref_name: Some(head.to_string()),
+            head_ref_oid: None,
+            base_ref_name: Some(base.to_string()),
+            head_repository_owner: Some(RepoOwner {
+                login: "o".to_string(),
+            }),
+            is_cross_repository: Some(false),
+            review_decision: None,
+            is_draft: None,
+            body: None,
+        }
+    }
+
+    #[test]
+    fn detects_stacks_parents_first() {
+        let prs = vec![
+            pr(3, "feat-c", "feat-b"),
+            pr(1, "feat-a", "main"),
+            pr(2, "feat-b", "feat-a"),
+            pr(4, "feat-d", "feat-a"),
+            pr(5, "lone", "main"),
+        ];
+        let stacks = detect_stacks("o", &prs);
+        assert_eq!(stacks.len(), 1);
+        let layers: Vec<(u64, Option<u64>)> = stacks[0]
+            .layers
+            .iter()
+            .map(|l| (l.number, l.parent))
+            .collect();
+        assert_eq!(
+            layers,
+            vec![(1, None), (2, Some(1)), (3, Some(2)), (4, Some(1))]
+        );
+    }
+
+    #[test]
+    fn ignores_cycles_and_self_bases() {
+        let prs = vec![pr(1, "a", "b"), pr(2, "b", "a"), pr(3, "c", "c")];
+        assert!(detect_stacks("o", &prs).is_empty());
+    }
+
+    #[test]
+    fn fork_heads_never_parent() {
+        let mut fork = pr(9, "main", "main");
+        fork.head_repository_owner = Some(RepoOwner {
+            login: "someone".to_string(),
+        });
+        fork.is_cross_repository = Some(true);
+        let prs = vec![fork, pr(1, "feat-a", "main"), pr(2, "feat-b", "main")];
+        assert!(detect_stacks("o", &prs).is_empty());
+    }
+}
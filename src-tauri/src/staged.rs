@@ -0,0 +1,301 @@
+use std::path::Path;
+use std::time::Duration;
+
+use git2::Repository;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::cache;
+use crate::codex_runner::{self, lang_suffix};
+use crate::diff_parser;
+use crate::error::PrvwError;
+use crate::local_range;
+use crate::provider::{self, Job, ProviderRegistry};
+use crate::run_state::RunState;
+use crate::settings::RunDefaults;
+use crate::time::now_secs;
+use crate::types::{ChangedFile, RunOptions, SelfReviewResponse, SelfReviewResult, StagedStatus};
+use crate::usage;
+
+const SELF_REVIEW_SCHEMA: &str = include_str!("../schemas/self_review.json");
+
+/// Emitted with the new `StagedStatus` whenever the watched index changes.
+const STAGED_CHANGED_EVENT: &str = "staged-changes";
+/// How often the watcher looks at the index.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Checklist items kept from the model.
+const MAX_CHECKLIST: usize = 15;
+
+/// Managed state for the index watcher; each watch or stop replaces the running one.
+pub type StagedWatchState = RunState<StagedStatus>;
+
+/// The staged files and a stamp that changes whenever the staged content does. No stamp when
+/// nothing is staged.
+fn read_index(repo: &Repository) -> Result<(Option<String>, Vec<ChangedFile>), String> {
+    let diff = local_range::staged(repo)?;
+    if diff.files.is_empty() {
+        return Ok((None, Vec::new()));
+    }
+    Ok((Some(diff.stamp), diff.files))
+}
+
+fn watch(app: &AppHandle, run_id: u64, root: &Path) {
+    let state = app.state::<StagedWatchState>();
+    let repo = match local_range::open(&root.to_string_lossy()) {
+        Ok((repo, _)) => repo,
+        Err(e) => {
+            state.update(run_id, |s| {
+                s.watching = false;
+                s.error = Some(e);
+            });
+            return;
+        }
+    };
+    let mut last: Option<Result<Option<String>, String>> = None;
+    while state.is_current(run_id) {
+        let read = read_index(&repo);
+        let seen = read
+            .as_ref()
+            .map(|(stamp, _)| stamp.clone())
+            .map_err(Clone::clone);
+        if last.as_ref() != Some(&seen) {
+            last = Some(seen);
+            state.update(run_id, |s| {
+                match read {
+                    Ok((stamp, files)) => {
+                        s.stamp = stamp;
+                        s.files = files;
+                        s.error = None;
+                    }
+                    Err(e) => s.error = Some(e),
+                }
+                s.changed_at = Some(now_secs());
+            });
+            if state.is_current(run_id) {
+                let _ = app.emit(STAGED_CHANGED_EVENT, state.snapshot());
+            }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Watch the index of the local repo at `repo_path` in the background, replacing a running
+/// watcher. Each change to the staged content emits a `staged-changes` event with the status.
+#[tauri::command]
+pub async fn watch_staged_changes(
+    app: AppHandle,
+    state: tauri::State<'_, StagedWatchState>,
+    repo_path: String,
+) -> Result<StagedStatus, PrvwError> {
+    let (repo, root) = local_range::open(&repo_path)?;
+    let (stamp, files) = read_index(&repo)?;
+    let run_id = state.begin();
+    let status = StagedStatus {
+        watching: true,
+        repo_path: Some(root.display().to_string()),
+        stamp,
+        files,
+        changed_at: Some(now_secs()),
+        error: None,
+    };
+    state.update(run_id, |s| *s = status.clone());
+
+    std::thread::spawn(move || watch(&app, run_id, &root));
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn stop_watching_staged(
+    state: tauri::State<'_, StagedWatchState>,
+) -> Result<StagedStatus, PrvwError> {
+    let run_id = state.begin();
+    state.update(run_id, |s| s.watching = false);
+    Ok(state.snapshot())
+}
+
+#[tauri::command]
+pub async fn get_staged_status(
+    state: tauri::State<'_, StagedWatchState>,
+) -> Result<StagedStatus, PrvwError> {
+    Ok(state.snapshot())
+}
+
+/// The staged diff of the local repo at `repo_path`, for `parse_diff` and a regular analysis.
+#[tauri::command]
+pub async fn get_staged_diff(repo_path: String) -> Result<String, PrvwError> {
+    let (repo, _) = local_range::open(&repo_path)?;
+    Ok(local_range::staged(&repo)?.patch)
+}
+
+fn build_self_review_prompt(lang: &Option<String>) -> String {
+    format!(
+        "Read hunks.json, which contains the changes staged for the next commit of a repository. \
+         The author wants to review their own change before pushing it. \
+         Write commitMessage as a conventional git commit message: a subject line of at most 72 \
+         characters in the imperative mood, then a blank line and a short body explaining what \
+         changed and why, wrapped at 72 characters. Omit the body for trivial changes. \
+         Write checklist as short, specific things the author should verify before pushing, based on \
+         these changes (e.g. \"Update the callers of parseConfig that pass a path\"); include \
+         leftover debug code, missing tests, and unrelated changes that should be a separate commit \
+         when you see them. Do not list generic advice.{}",
+        lang_suffix(lang)
+    )
+}
+
+/// Trim the commit message and drop empty or duplicate checklist items.
+fn clean_self_review(result: SelfReviewResult) -> (String, Vec<String>) {
+    let mut checklist: Vec<String> = Vec::new();
+    for item in result.checklist {
+        let item = item.trim();
+        if !item.is_empty() && !checklist.iter().any(|c| c.eq_ignore_ascii_case(item)) {
+            checklist.push(item.to_string());
+        }
+    }
+    checklist.truncate(MAX_CHECKLIST);
+    (result.commit_message.trim().to_string(), checklist)
+}
+
+/// Pre-push self-review of the staged changes: a suggested commit message and a checklist of
+/// things to verify. Cached per staged content.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn self_review_staged(
+    app: AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    repo_path: String,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+) -> Result<SelfReviewResponse, PrvwError> {
+    let (git_repo, root) = local_range::open(&repo_path)?;
+    let repo = local_range::clone_remotes(&git_repo).repo;
+    let RunDefaults {
+        model,
+        lang,
+        provider,
+        options,
+        timeout_minutes,
+    } = RunDefaults::resolve(
+        repo.as_deref(),
+        model,
+        lang,
+        provider,
+        options,
+        timeout_minutes,
+    );
+    let provider = providers.resolve(&provider)?;
+
+    let staged = local_range::staged(&git_repo)?;
+    if staged.files.is_empty() {
+        return Err("Nothing is staged.".to_string().into());
+    }
+    let stamp = staged.stamp;
+    let parsed = diff_parser::parse(staged.patch, repo)?;
+    if parsed.hunks.is_empty() {
+        return Err("All staged changes are in ignored files."
+            .to_string()
+            .into());
+    }
+
+    let app_data_dir = app.path().app_data_dir().ok();
+    let model_str = format!(
+        "{}:{}:{}",
+        provider.name(),
+        model.as_deref().unwrap_or(""),
+        options.cache_key()
+    );
+    let cache_key = cache::hash_key(&format!(
+        "{}\n{}\n{}\n{}",
+        root.display(),
+        stamp,
+        model_str,
+        lang.as_deref().unwrap_or("")
+    ));
+    if force != Some(true) {
+        if let Some(ref dir) = app_data_dir {
+            if let Some(mut cached) =
+                cache::read_cache_async::<SelfReviewResponse>(dir, "cache/self_review", &cache_key)
+                    .await
+            {
+                cached.from_cache = true;
+                return Ok(cached);
+            }
+        }
+    }
+
+    let hunks_json = serde_json::to_string(&parsed.hunks)
+        .map_err(|e| format!("Failed to serialize hunks: {}", e))?;
+    let (temp_dir, schema_path, output_path) =
+        codex_runner::prepare_temp_dir(&hunks_json, SELF_REVIEW_SCHEMA, "self_review.json")?;
+    let prompt = build_self_review_prompt(&lang);
+    let estimated_tokens = usage::estimate_tokens(&hunks_json) + usage::estimate_tokens(&prompt);
+    let codex_output = provider::execute(
+        provider.as_ref(),
+        Job {
+            temp_path: temp_dir.path(),
+            schema_path: &schema_path,
+            output_path: &output_path,
+            repo_dir: Some(&root),
+            model: &model,
+            options: &options,
+            prompt,
+            timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+        },
+    )?;
+    usage::record(
+        app_data_dir.as_deref(),
+        "self-review",
+        provider.name(),
+        estimated_tokens,
+        &codex_output,
+    );
+
+    let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+        format!(
+            "Failed to read self_review.json: {}. Codex may not have produced output.",
+            e
+        )
+    })?;
+    let result: SelfReviewResult = serde_json::from_str(&result_str)
+        .map_err(|e| format!("Failed to parse self_review.json: {}", e))?;
+    let (commit_message, checklist) = clean_self_review(result);
+
+    let mut log = codex_runner::build_log("self-review", &codex_output);
+    log.push_str(&format!(
+        "[self-review] hunks={} checklist={}\n",
+        parsed.hunks.len(),
+        checklist.len()
+    ));
+    let response = SelfReviewResponse {
+        commit_message,
+        checklist,
+        stamp,
+        codex_log: log,
+        from_cache: false,
+    };
+    if let Some(ref dir) = app_data_dir {
+        cache::write_cache(dir, "cache/self_review", &cache_key, &response);
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleans_self_review() {
+        let (message, checklist) = clean_self_review(SelfReviewResult {
+            commit_message: "  Add parser\n\nBody\n ".to_string(),
+            checklist: vec![
+                "Remove dbg! call".to_string(),
+                " ".to_string(),
+                "remove DBG! call".to_string(),
+            ],
+        });
+        assert_eq!(message, "Add parser\n\nBody");
+        assert_eq!(checklist, vec!["Remove dbg! call"]);
+    }
+}
@@ -0,0 +1,465 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::cache;
+use crate::codex_runner::{self, lang_suffix};
+use crate::error::PrvwError;
+use crate::gh;
+use crate::history;
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::provider::{self, Job, ProviderRegistry};
+use crate::settings::RunDefaults;
+use crate::types::{
+    ReviewThread, RunOptions, ThreadComment, ThreadSummariesResponse, ThreadSummariesResult,
+    ThreadSummary,
+};
+use crate::usage;
+
+const THREAD_SUMMARIES_SCHEMA: &str = include_str!("../schemas/thread_summaries.json");
+
+/// Longest comment body given to the model; long pastes rarely matter for the status.
+const MAX_COMMENT_CHARS: usize = 1500;
+
+/// First 100 threads with their first 50 comments; enough for all but the largest PRs.
+const REVIEW_THREADS_QUERY: &str = "query($owner: String!, $name: String!, $number: Int!) {
+  repository(owner: $owner, name: $name) {
+    pullRequest(number: $number) {
+      reviewThreads(first: 100) {
+        nodes {
+          id isResolved isOutdated path line
+          comments(first: 50) { nodes { author { login } body createdAt } }
+        }
+      }
+    }
+  }
+}";
+
+#[derive(Deserialize)]
+struct GqlResponse {
+    data: GqlData,
+}
+
+#[derive(Deserialize)]
+struct GqlData {
+    repository: GqlRepository,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlRepository {
+    pull_request: GqlPullRequest,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlPullRequest {
+    review_threads: GqlNodes<GqlThread>,
+}
+
+#[derive(Deserialize)]
+struct GqlNodes<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlThread {
+    id: String,
+    is_resolved: bool,
+    is_outdated: bool,
+    path: Option<String>,
+    line: Option<u32>,
+    comments: GqlNodes<GqlComment>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlComment {
+    /// Null for deleted accounts.
+    author: Option<GqlAuthor>,
+    body: String,
+    created_at: String,
+}
+
+#[derive(Deserialize)]
+struct GqlAuthor {
+    login: String,
+}
+
+fn parse_review_threads(json: &str) -> Result<Vec<ReviewThread>, String> {
+    let response: GqlResponse =
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse review threads: {}", e))?;
+    Ok(response
+        .data
+        .repository
+        .pull_request
+        .review_threads
+        .nodes
+        .into_iter()
+        .map(|t| ReviewThread {
+            id: t.id,
+            path: t.path,
+            line: t.line,
+            is_resolved: t.is_resolved,
+            is_outdated: t.is_outdated,
+            comments: t
+                .comments
+                .nodes
+                .into_iter()
+                .map(|c| ThreadComment {
+                    author: c
+                        .author
+                        .map(|a| a.login)
+                        .unwrap_or_else(|| "ghost".to_string()),
+                    body: c.body,
+                    created_at: c.created_at,
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+/// Inline review threads of a PR with their comments and resolved/outdated state.
+#[tauri::command]
+pub async fn get_review_threads(
+    repo: String,
+    pr_number: u32,
+) -> Result<Vec<ReviewThread>, PrvwError> {
+    let repo = repo.trim();
+    gh::validate_repo(repo)?;
+    let (owner, name) = repo.split_once('/').unwrap_or_default();
+    let output = gh::run_gh(
+        &[
+            "api",
+            "graphql",
+            "-f",
+            &format!("query={}", REVIEW_THREADS_QUERY),
+            "-f",
+            &format!("owner={}", owner),
+            "-f",
+            &format!("name={}", name),
+            "-F",
+            &format!("number={}", pr_number),
+        ],
+        "gh api graphql",
+    )?;
+    Ok(parse_review_threads(&String::from_utf8_lossy(
+        &output.stdout,
+    ))?)
+}
+
+/// threads.json for the model: comment bodies truncated, threads without comments dropped.
+fn threads_json(threads: &[ReviewThread]) -> Result<String, String> {
+    let trimmed: Vec<ReviewThread> = threads
+        .iter()
+        .filter(|t| !t.comments.is_empty())
+        .map(|t| ReviewThread {
+            comments: t
+                .comments
+                .iter()
+                .map(|c| ThreadComment {
+                    body: c.body[..c.body.floor_char_boundary(MAX_COMMENT_CHARS)].to_string(),
+                    ..c.clone()
+                })
+                .collect(),
+            ..t.clone()
+        })
+        .collect();
+    serde_json::to_string(&trimmed).map_err(|e| format!("Failed to serialize threads: {}", e))
+}
+
+fn build_thread_summaries_prompt(thread_count: usize, lang: &Option<String>) -> String {
+    format!(
+        "Read threads.json, which contains {} inline review threads of a pull request, \
+         each with its comments in order, the file path and line it is attached to, \
+         and whether it is marked resolved (isResolved) or its code has changed since (isOutdated). \
+         Summarize each thread for a reviewer returning to the pull request: \
+         in asked, what the reviewer asked for or pointed out; \
+         in resolved, what has been addressed or agreed so far (empty if nothing); \
+         in open, what is still unanswered, disputed, or not yet done (empty if nothing). \
+         Set status to \"open\" if anything remains open, otherwise \"resolved\"; \
+         judge from the discussion, using isResolved only as a hint. \
+         Keep each field to one or two short sentences. \
+         Return one summary per thread, with threadId set to the thread's id.{}",
+        thread_count,
+        lang_suffix(lang)
+    )
+}
+
+/// Drop summaries of unknown or already summarized threads and those with an unknown status,
+/// and warn about threads left without a summary.
+fn validate_thread_summaries(
+    summaries: Vec<ThreadSummary>,
+    thread_ids: &HashSet<&str>,
+) -> (Vec<ThreadSummary>, Vec<String>) {
+    let mut warnings: Vec<String> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut cleaned: Vec<ThreadSummary> = Vec::new();
+
+    for summary in summaries {
+        if !thread_ids.contains(summary.thread_id.as_str()) {
+            warnings.push(format!(
+                "Removed summary of non-existent thread '{}'",
+                summary.thread_id
+            ));
+            continue;
+        }
+        if summary.status != "resolved" && summary.status != "open" {
+            warnings.push(format!(
+                "Removed summary of thread '{}' with unknown status '{}'",
+                summary.thread_id, summary.status
+            ));
+            continue;
+        }
+        if !seen.insert(summary.thread_id.clone()) {
+            warnings.push(format!(
+                "Removed duplicate summary of thread '{}'",
+                summary.thread_id
+            ));
+            continue;
+        }
+        cleaned.push(summary);
+    }
+
+    let missing = thread_ids.iter().filter(|id| !seen.contains(**id)).count();
+    if missing > 0 {
+        warnings.push(format!("{} thread(s) did not get a summary", missing));
+    }
+    (cleaned, warnings)
+}
+
+/// Per-thread status of a PR's review discussion: what was asked, what was resolved, and
+/// what is still open. Takes the threads from `get_review_threads`; open threads come first.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn summarize_review_threads(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    threads: Vec<ReviewThread>,
+    repo: Option<String>,
+    model: Option<String>,
+    lang: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<ThreadSummariesResponse, PrvwError> {
+    use tauri::Manager;
+    let op = Op::start(op_id, "summarize_review_threads");
+    let result: Result<ThreadSummariesResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                repo.as_deref(),
+                model,
+                lang,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+
+            let thread_ids: HashSet<&str> = threads
+                .iter()
+                .filter(|t| !t.comments.is_empty())
+                .map(|t| t.id.as_str())
+                .collect();
+            if thread_ids.is_empty() {
+                return Err("No review threads to summarize.".to_string().into());
+            }
+            let input_json = threads_json(&threads)?;
+
+            let app_data_dir = app.path().app_data_dir().ok();
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let lang_str = lang.as_deref().unwrap_or("");
+            let cache_key =
+                cache::hash_key(&format!("{}\n{}\n{}", input_json, model_str, lang_str));
+
+            if force != Some(true) {
+                if let Some(ref dir) = app_data_dir {
+                    if let Some(mut cached) = cache::read_cache_async::<ThreadSummariesResponse>(
+                        dir,
+                        "cache/threads",
+                        &cache_key,
+                    )
+                    .await
+                    {
+                        cached.from_cache = true;
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (temp_dir, schema_path, output_path) = codex_runner::prepare_temp_dir(
+                &input_json,
+                THREAD_SUMMARIES_SCHEMA,
+                "thread_summaries.json",
+            )?;
+
+            // The input is the review threads, not hunks
+            let temp_path = temp_dir.path();
+            std::fs::rename(temp_path.join("hunks.json"), temp_path.join("threads.json"))
+                .map_err(|e| format!("Failed to rename temp file: {}", e))?;
+
+            let prompt = build_thread_summaries_prompt(thread_ids.len(), &lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens =
+                usage::estimate_tokens(&input_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path,
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir.as_deref(),
+                "threads",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read thread_summaries.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let result: ThreadSummariesResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse thread_summaries.json: {}", e))?;
+
+            let (mut summaries, warnings) =
+                validate_thread_summaries(result.summaries, &thread_ids);
+            summaries.sort_by_key(|s| s.status != "open");
+
+            let mut log = codex_runner::build_log("threads", &codex_output);
+            log.push_str(&format!(
+                "[threads] threads={} open={}\n",
+                summaries.len(),
+                summaries.iter().filter(|s| s.status == "open").count()
+            ));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let response = ThreadSummariesResponse {
+                summaries,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(ref dir) = app_data_dir {
+                cache::write_cache(dir, "cache/threads", &cache_key, &response);
+            }
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(thread_id: &str, status: &str) -> ThreadSummary {
+        ThreadSummary {
+            thread_id: thread_id.to_string(),
+            status: status.to_string(),
+            asked: "a".to_string(),
+            resolved: String::new(),
+            open: String::new(),
+        }
+    }
+
+    #[test]
+    fn parses_graphql_threads() {
+        let json = r#"{"data":{"repository":{"pullRequest":{"reviewThreads":{"nodes":[
+            {"id":"T1","isResolved":false,"isOutdated":true,"path":"src/a.rs","line":null,
+             "comments":{"nodes":[
+                {"author":{"login":"alice"},"body":"Why?","createdAt":"2024-01-01T00:00:00Z"},
+                {"author":null,"body":"Because.","createdAt":"2024-01-02T00:00:00Z"}]}}
+        ]}}}}}"#;
+        let threads = parse_review_threads(json).expect("parse");
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].path.as_deref(), Some("src/a.rs"));
+        assert_eq!(threads[0].line, None);
+        assert!(threads[0].is_outdated);
+        let authors: Vec<&str> = threads[0]
+            .comments
+            .iter()
+            .map(|c| c.author.as_str())
+            .collect();
+        assert_eq!(authors, vec!["alice", "ghost"]);
+    }
+
+    #[test]
+    fn threads_json_truncates_bodies_and_skips_empty_threads() {
+        let comment = |body: String| ThreadComment {
+            author: "a".to_string(),
+            body,
+            created_at: String::new(),
+        };
+        let thread = |id: &str, comments: Vec<ThreadComment>| ReviewThread {
+            id: id.to_string(),
+            path: None,
+            line: None,
+            is_resolved: false,
+            is_outdated: false,
+            comments,
+        };
+        let json = threads_json(&[
+            thread("T1", vec![comment("x".repeat(MAX_COMMENT_CHARS + 5))]),
+            thread("T2", vec![]),
+        ])
+        .expect("json");
+        let parsed: Vec<ReviewThread> = serde_json::from_str(&json).expect("parse");
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].comments[0].body.len(), MAX_COMMENT_CHARS);
+    }
+
+    #[test]
+    fn validate_drops_unknown_duplicate_and_bad_status() {
+        let ids: HashSet<&str> = ["T1", "T2", "T3"].into_iter().collect();
+        let (cleaned, warnings) = validate_thread_summaries(
+            vec![
+                summary("T1", "open"),
+                summary("T1", "resolved"),
+                summary("T2", "wontfix"),
+                summary("T9", "open"),
+            ],
+            &ids,
+        );
+        assert_eq!(cleaned.len(), 1);
+        assert_eq!(cleaned[0].status, "open");
+        // duplicate, bad status, unknown id, and the missing threads (T2, T3)
+        assert_eq!(warnings.len(), 4);
+    }
+}
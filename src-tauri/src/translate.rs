@@ -0,0 +1,383 @@
+use crate::cache;
+use crate::codex_runner;
+use crate::error::PrvwError;
+use crate::history;
+use crate::i18n::{t, Msg};
+use crate::operations::Op;
+use crate::provider::{self, Job, ProviderRegistry};
+use crate::settings::RunDefaults;
+use crate::types::{AnalysisResult, RunOptions, TranslateResponse, TranslationResult};
+use crate::usage;
+
+const TRANSLATE_SCHEMA: &str = include_str!("../schemas/translate.json");
+
+fn build_translate_prompt(lang: &str) -> String {
+    format!(
+        "Read analysis.json, the result of grouping a pull request's changes by intent. \
+         Translate its human-readable text into {}: overallSummary, each group's title, rationale, \
+         the reason of every riskEvidence entry (as riskReasons, in the same order), \
+         reviewerChecklist and suggestedTests, and the questions. \
+         Return every group with its id unchanged and keep every list at its original length and order. \
+         Translate only; do not add, drop, merge, or reinterpret anything. \
+         Keep code identifiers, file paths, hunk ids, and text inside backticks as they are, \
+         and keep the Markdown structure of overallSummary (first line, blank line, \"- \" bullets).",
+        lang
+    )
+}
+
+/// Use `text` unless it is empty.
+fn replace_text(target: &mut String, text: String, what: &str, warnings: &mut Vec<String>) {
+    if text.trim().is_empty() {
+        warnings.push(format!("Kept original {}: translation is empty", what));
+    } else {
+        *target = text;
+    }
+}
+
+/// Use `items` only if it has as many entries as the original list.
+fn replace_list(
+    target: &mut Vec<String>,
+    items: Vec<String>,
+    what: &str,
+    warnings: &mut Vec<String>,
+) {
+    if items.len() == target.len() {
+        *target = items;
+    } else {
+        warnings.push(format!(
+            "Kept original {}: expected {} item(s), got {}",
+            what,
+            target.len(),
+            items.len()
+        ));
+    }
+}
+
+/// Copy the translated text into `original`. Structure (ids, hunks, risk, categories) always
+/// comes from `original`; any field whose translation is missing, empty, or has a different
+/// number of items keeps its original text and is reported as a warning.
+fn apply_translation(
+    original: &AnalysisResult,
+    translation: TranslationResult,
+) -> (AnalysisResult, Vec<String>) {
+    let mut result = original.clone();
+    let mut warnings: Vec<String> = Vec::new();
+
+    replace_text(
+        &mut result.overall_summary,
+        translation.overall_summary,
+        "overall summary",
+        &mut warnings,
+    );
+    replace_list(
+        &mut result.questions,
+        translation.questions,
+        "questions",
+        &mut warnings,
+    );
+
+    let mut translated_groups = translation.groups;
+    for group in &mut result.groups {
+        let Some(pos) = translated_groups.iter().position(|t| t.id == group.id) else {
+            warnings.push(format!(
+                "Kept original text of group {}: not translated",
+                group.id
+            ));
+            continue;
+        };
+        let t = translated_groups.swap_remove(pos);
+        replace_text(
+            &mut group.title,
+            t.title,
+            &format!("title of {}", group.id),
+            &mut warnings,
+        );
+        replace_text(
+            &mut group.rationale,
+            t.rationale,
+            &format!("rationale of {}", group.id),
+            &mut warnings,
+        );
+        let mut reasons: Vec<String> = group
+            .risk_evidence
+            .iter()
+            .map(|e| e.reason.clone())
+            .collect();
+        replace_list(
+            &mut reasons,
+            t.risk_reasons,
+            &format!("risk evidence of {}", group.id),
+            &mut warnings,
+        );
+        for (evidence, reason) in group.risk_evidence.iter_mut().zip(reasons) {
+            evidence.reason = reason;
+        }
+        replace_list(
+            &mut group.reviewer_checklist,
+            t.reviewer_checklist,
+            &format!("checklist of {}", group.id),
+            &mut warnings,
+        );
+        replace_list(
+            &mut group.suggested_tests,
+            t.suggested_tests,
+            &format!("suggested tests of {}", group.id),
+            &mut warnings,
+        );
+    }
+    for t in translated_groups {
+        warnings.push(format!(
+            "Ignored translation of non-existent group '{}'",
+            t.id
+        ));
+    }
+
+    (result, warnings)
+}
+
+/// Rewrite only the human-readable text of an existing analysis in another language, so
+/// switching the display language does not re-run the analysis. The input is the analysis
+/// alone (no hunks), which keeps the call cheap; a smaller `model` can be passed as well.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn translate_analysis(
+    app: tauri::AppHandle,
+    providers: tauri::State<'_, ProviderRegistry>,
+    analysis: AnalysisResult,
+    lang: String,
+    repo: Option<String>,
+    model: Option<String>,
+    provider: Option<String>,
+    options: Option<RunOptions>,
+    force: Option<bool>,
+    timeout_minutes: Option<u64>,
+    op_id: Option<String>,
+) -> Result<TranslateResponse, PrvwError> {
+    use tauri::Manager;
+    let op = Op::start(op_id, "translate_analysis");
+    let result: Result<TranslateResponse, PrvwError> = op
+        .scope(async {
+            let RunDefaults {
+                model,
+                lang: _,
+                provider,
+                options,
+                timeout_minutes,
+            } = RunDefaults::resolve(
+                repo.as_deref(),
+                model,
+                None,
+                provider,
+                options,
+                timeout_minutes,
+            );
+            let provider = providers.resolve(&provider)?;
+            let lang = lang.trim().to_string();
+
+            if lang.is_empty() {
+                return Err("No target language given.".to_string().into());
+            }
+
+            let analysis_json = serde_json::to_string(&analysis)
+                .map_err(|e| format!("Failed to serialize analysis: {}", e))?;
+
+            let app_data_dir = app.path().app_data_dir().ok();
+            let model_str = format!(
+                "{}:{}:{}",
+                provider.name(),
+                model.as_deref().unwrap_or(""),
+                options.cache_key()
+            );
+            let cache_key = cache::hash_key(&format!("{}\n{}\n{}", analysis_json, model_str, lang));
+
+            if force != Some(true) {
+                if let Some(ref dir) = app_data_dir {
+                    if let Some(mut cached) = cache::read_cache_async::<TranslateResponse>(
+                        dir,
+                        "cache/translate",
+                        &cache_key,
+                    )
+                    .await
+                    {
+                        cached.from_cache = true;
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let (temp_dir, schema_path, output_path) = codex_runner::prepare_temp_dir(
+                &analysis_json,
+                TRANSLATE_SCHEMA,
+                "translation.json",
+            )?;
+
+            // The input is the analysis, not hunks
+            let temp_path = temp_dir.path();
+            std::fs::rename(
+                temp_path.join("hunks.json"),
+                temp_path.join("analysis.json"),
+            )
+            .map_err(|e| format!("Failed to rename temp file: {}", e))?;
+
+            let prompt = build_translate_prompt(&lang);
+
+            op.progress(t(Msg::WaitingFor, &[&provider.name()]));
+            let estimated_tokens =
+                usage::estimate_tokens(&analysis_json) + usage::estimate_tokens(&prompt);
+            let codex_output = provider::execute(
+                provider.as_ref(),
+                Job {
+                    temp_path,
+                    schema_path: &schema_path,
+                    output_path: &output_path,
+                    repo_dir: None,
+                    model: &model,
+                    options: &options,
+                    prompt,
+                    timeout: codex_runner::timeout_from_minutes(timeout_minutes),
+                },
+            )?;
+            usage::record(
+                app_data_dir.as_deref(),
+                "translate",
+                provider.name(),
+                estimated_tokens,
+                &codex_output,
+            );
+
+            let result_str = std::fs::read_to_string(&output_path).map_err(|e| {
+                format!(
+                    "Failed to read translation.json: {}. Codex may not have produced output.",
+                    e
+                )
+            })?;
+
+            let translation: TranslationResult = serde_json::from_str(&result_str)
+                .map_err(|e| format!("Failed to parse translation.json: {}", e))?;
+
+            let (result, warnings) = apply_translation(&analysis, translation);
+
+            let mut log = codex_runner::build_log("translate", &codex_output);
+            log.push_str(&format!(
+                "[translate] lang={} groups={}\n",
+                lang,
+                result.groups.len()
+            ));
+            history::attach_warnings(codex_output.run_id.as_deref(), &warnings);
+            if !warnings.is_empty() {
+                log.push_str("--- validation warnings ---\n");
+                for w in &warnings {
+                    log.push_str(w);
+                    log.push('\n');
+                }
+            }
+
+            let response = TranslateResponse {
+                result,
+                codex_log: log,
+                from_cache: false,
+            };
+
+            if let Some(ref dir) = app_data_dir {
+                cache::write_cache(dir, "cache/translate", &cache_key, &response);
+            }
+
+            Ok(response)
+        })
+        .await;
+    op.finish(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{IntentGroup, RiskEvidence, TranslatedGroup};
+
+    fn analysis() -> AnalysisResult {
+        AnalysisResult {
+            version: 1,
+            overall_summary: "Adds login rate limiting.".to_string(),
+            groups: vec![IntentGroup {
+                id: "G1".to_string(),
+                title: "Rate limiting".to_string(),
+                category: "logic".to_string(),
+                rationale: "Throttle login attempts".to_string(),
+                risk: "medium".to_string(),
+                risk_score: 50,
+                risk_evidence: vec![RiskEvidence {
+                    hunk_id: "H1".to_string(),
+                    reason: "changes the login check".to_string(),
+                }],
+                hunk_ids: vec!["H1".to_string()],
+                reviewer_checklist: vec!["Check the limit".to_string()],
+                suggested_tests: vec!["Test lockout".to_string()],
+            }],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec!["Is 5 attempts enough?".to_string()],
+        }
+    }
+
+    fn translated_group(id: &str, checklist: &[&str]) -> TranslatedGroup {
+        TranslatedGroup {
+            id: id.to_string(),
+            title: "レート制限".to_string(),
+            rationale: "ログイン試行を制限".to_string(),
+            risk_reasons: vec!["ログイン判定を変更".to_string()],
+            reviewer_checklist: checklist.iter().map(ToString::to_string).collect(),
+            suggested_tests: vec!["ロックアウトをテスト".to_string()],
+        }
+    }
+
+    #[test]
+    fn applies_text_and_keeps_structure() {
+        let original = analysis();
+        let (result, warnings) = apply_translation(
+            &original,
+            TranslationResult {
+                overall_summary: "ログインのレート制限を追加。".to_string(),
+                groups: vec![translated_group("G1", &["上限を確認"])],
+                questions: vec!["5回で十分か?".to_string()],
+            },
+        );
+        assert!(warnings.is_empty());
+        let g = &result.groups[0];
+        assert_eq!(g.title, "レート制限");
+        assert_eq!(g.risk_evidence[0].reason, "ログイン判定を変更");
+        assert_eq!(g.risk_evidence[0].hunk_id, "H1");
+        assert_eq!(g.hunk_ids, original.groups[0].hunk_ids);
+        assert_eq!(g.risk, "medium");
+        assert_eq!(result.questions, vec!["5回で十分か?"]);
+    }
+
+    #[test]
+    fn keeps_original_on_mismatched_or_missing_translation() {
+        let original = analysis();
+        let (result, warnings) = apply_translation(
+            &original,
+            TranslationResult {
+                overall_summary: " ".to_string(),
+                groups: vec![
+                    translated_group("G1", &["a", "b"]),
+                    translated_group("G9", &[]),
+                ],
+                questions: vec![],
+            },
+        );
+        assert_eq!(result.overall_summary, original.overall_summary);
+        assert_eq!(result.questions, original.questions);
+        assert_eq!(
+            result.groups[0].reviewer_checklist,
+            original.groups[0].reviewer_checklist
+        );
+        assert_eq!(result.groups[0].title, "レート制限");
+        assert_eq!(warnings.len(), 4);
+    }
+
+    #[test]
+    fn prompt_names_target_language() {
+        let prompt = build_translate_prompt("Japanese");
+        assert!(prompt.contains("into Japanese"));
+        assert!(prompt.contains("id unchanged"));
+    }
+}
@@ -0,0 +1,395 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Manager, Window, WindowEvent};
+
+use crate::error::PrvwError;
+use crate::gh;
+use crate::prefetch::{MAX_QUEUE, REVIEW_QUEUE_SEARCH};
+use crate::run_state::RunState;
+use crate::time::now_secs;
+use crate::types::{OpenPr, PrListItem, TrayStatus};
+
+/// Repo and close behavior, kept in the app data dir across launches.
+const CONFIG_FILE: &str = "tray.json";
+const TRAY_ID: &str = "prvw";
+/// Emitted with an `OpenPr` when a PR is picked from the tray menu or a `prvw://` link.
+pub const OPEN_PR_EVENT: &str = "open-pr";
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// How often a refresh thread wakes up to check whether it was replaced.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// PRs listed in the menu; the rest are only counted.
+const MENU_PRS: usize = 10;
+const MENU_TITLE_CHARS: usize = 60;
+
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Config {
+    #[serde(default)]
+    repo: Option<String>,
+    #[serde(default)]
+    keep_running: bool,
+}
+
+fn config_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(CONFIG_FILE)
+}
+
+fn load_config(app_data_dir: &Path) -> Config {
+    fs::read_to_string(config_path(app_data_dir))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_config(app_data_dir: &Path, config: &Config) -> Result<(), String> {
+    fs::create_dir_all(app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize tray config: {}", e))?;
+    fs::write(config_path(app_data_dir), json)
+        .map_err(|e| format!("Failed to save tray config: {}", e))
+}
+
+/// Managed state for the tray; each repo change replaces the running refresh thread.
+pub type TrayState = RunState<TrayStatus>;
+
+impl TrayState {
+    fn keep_running(&self) -> bool {
+        self.snapshot().keep_running
+    }
+}
+
+/// Tooltip, also the first (disabled) menu line.
+fn summary(status: &TrayStatus) -> String {
+    match (&status.repo, &status.error) {
+        (None, _) => "prvw — list a repo's PRs to follow its review queue".to_string(),
+        (Some(repo), Some(_)) if status.last_checked_at.is_none() => {
+            format!("prvw — couldn't check the review queue of {}", repo)
+        }
+        (Some(repo), _) => match status.pending.len() {
+            1 => format!("prvw — 1 review request in {}", repo),
+            n => format!("prvw — {} review requests in {}", n, repo),
+        },
+    }
+}
+
+/// Menu line of a PR, with long titles cut.
+fn pr_label(pr: &PrListItem) -> String {
+    let title: String = if pr.title.chars().count() > MENU_TITLE_CHARS {
+        let cut: String = pr.title.chars().take(MENU_TITLE_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        pr.title.clone()
+    };
+    format!("#{} {}", pr.number, title)
+}
+
+/// Menu id of a PR item (`pr:<number>`).
+fn pr_id(pr: &PrListItem) -> String {
+    format!("pr:{}", pr.number)
+}
+
+fn parse_pr_id(id: &str) -> Option<u64> {
+    id.strip_prefix("pr:")?.parse().ok()
+}
+
+fn build_menu(app: &AppHandle, status: &TrayStatus) -> tauri::Result<Menu<tauri::Wry>> {
+    let menu = Menu::new(app)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "summary",
+        summary(status),
+        false,
+        None::<&str>,
+    )?)?;
+    if !status.pending.is_empty() {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+        for pr in status.pending.iter().take(MENU_PRS) {
+            menu.append(&MenuItem::with_id(
+                app,
+                pr_id(pr),
+                pr_label(pr),
+                true,
+                None::<&str>,
+            )?)?;
+        }
+        if status.pending.len() > MENU_PRS {
+            let more = format!("{} more in prvw…", status.pending.len() - MENU_PRS);
+            menu.append(&MenuItem::with_id(
+                app,
+                "show-more",
+                more,
+                true,
+                None::<&str>,
+            )?)?;
+        }
+    }
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "show",
+        "Open prvw",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "refresh",
+        "Refresh",
+        status.repo.is_some(),
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+/// Redraw the tray from the current status: menu, tooltip, and the count as the title (shown
+/// next to the icon on macOS).
+fn render(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let status = app.state::<TrayState>().snapshot();
+    let count = status.pending.len();
+    let result = build_menu(app, &status).and_then(|menu| {
+        tray.set_menu(Some(menu))?;
+        tray.set_tooltip(Some(summary(&status)))?;
+        tray.set_title((status.repo.is_some() && count > 0).then(|| count.to_string()))
+    });
+    if let Err(e) = result {
+        eprintln!("[tray] failed to update the tray: {}", e);
+    }
+}
+
+pub fn show_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn on_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        "show" | "show-more" => show_window(app),
+        "refresh" => {
+            let repo = app.state::<TrayState>().snapshot().repo;
+            start_refresh(app, repo);
+        }
+        "quit" => app.exit(0),
+        id => {
+            let Some(number) = parse_pr_id(id) else {
+                return;
+            };
+            let status = app.state::<TrayState>().snapshot();
+            let (Some(repo), Some(pr)) = (
+                status.repo,
+                status.pending.into_iter().find(|pr| pr.number == number),
+            ) else {
+                return;
+            };
+            show_window(app);
+            let _ = app.emit(OPEN_PR_EVENT, OpenPr { repo, pr });
+        }
+    }
+}
+
+fn run_refresh(app: &AppHandle, run_id: u64, repo: &str) {
+    let state = app.state::<TrayState>();
+    while state.is_current(run_id) {
+        let queue = gh::fetch_pr_list(
+            repo,
+            MAX_QUEUE,
+            "open",
+            Some(REVIEW_QUEUE_SEARCH.to_string()),
+        );
+        state.update(run_id, |s| match queue {
+            Ok(queue) => {
+                s.pending = queue;
+                s.last_checked_at = Some(now_secs());
+                s.error = None;
+            }
+            // The last known queue stays listed.
+            Err(e) => s.error = Some(e.message()),
+        });
+        if state.is_current(run_id) {
+            render(app);
+        }
+
+        let next_at = Instant::now() + REFRESH_INTERVAL;
+        while Instant::now() < next_at {
+            if !state.is_current(run_id) {
+                return;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Follow `repo`'s review queue from now on (checked right away, then every 5 minutes),
+/// replacing the previous refresh thread; None stops following.
+fn start_refresh(app: &AppHandle, repo: Option<String>) {
+    let state = app.state::<TrayState>();
+    let run_id = state.begin();
+    let changed = state.snapshot().repo != repo;
+    state.update(run_id, |s| {
+        if changed {
+            s.pending.clear();
+            s.last_checked_at = None;
+            s.error = None;
+        }
+        s.repo.clone_from(&repo);
+    });
+    render(app);
+    if let Some(repo) = repo {
+        let app = app.clone();
+        std::thread::spawn(move || run_refresh(&app, run_id, &repo));
+    }
+}
+
+/// Create the tray icon at launch and resume following the saved repo.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let config = app
+        .path()
+        .app_data_dir()
+        .map(|dir| load_config(&dir))
+        .unwrap_or_default();
+    let state = app.state::<TrayState>();
+    let _ = state.modify(|s| s.keep_running = config.keep_running);
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&build_menu(app, &state.snapshot())?)
+        .tooltip(summary(&state.snapshot()))
+        .on_menu_event(|app, event| on_menu_event(app, event.id().as_ref()));
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    start_refresh(app, config.repo);
+    Ok(())
+}
+
+/// With `keep_running`, closing the window hides it; the app keeps going in the tray until
+/// Quit is picked from its menu.
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::CloseRequested { api, .. } = event {
+        if window.app_handle().state::<TrayState>().keep_running() {
+            api.prevent_close();
+            let _ = window.hide();
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_tray_status(state: tauri::State<'_, TrayState>) -> Result<TrayStatus, PrvwError> {
+    Ok(state.snapshot())
+}
+
+/// Show `repo`'s review queue in the tray (None clears it). The app calls this whenever a repo's
+/// PRs are listed; the choice is kept across launches.
+#[tauri::command]
+pub async fn set_tray_repo(
+    app: AppHandle,
+    state: tauri::State<'_, TrayState>,
+    repo: Option<String>,
+) -> Result<TrayStatus, PrvwError> {
+    let repo = repo.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    if let Some(repo) = &repo {
+        gh::validate_repo(repo)?;
+    }
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let mut config = load_config(&app_data_dir);
+    config.repo.clone_from(&repo);
+    save_config(&app_data_dir, &config)?;
+    start_refresh(&app, repo);
+    Ok(state.snapshot())
+}
+
+/// Whether closing the window hides it to the tray instead of quitting.
+#[tauri::command]
+pub async fn set_keep_running(
+    app: AppHandle,
+    state: tauri::State<'_, TrayState>,
+    enabled: bool,
+) -> Result<TrayStatus, PrvwError> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let mut config = load_config(&app_data_dir);
+    config.keep_running = enabled;
+    save_config(&app_data_dir, &config)?;
+    state.modify(|s| s.keep_running = enabled)?;
+    Ok(state.snapshot())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pr(number: u64, title: &str) -> PrListItem {
+        PrListItem {
+            number,
+            title: title.to_string(),
+            url: String::new(),
+            updated_at: String::new(),
+            created_at: String::new(),
+            author: None,
+            head_ref_name: None,
+            head_ref_oid: None,
+            base_ref_name: None,
+            head_repository_owner: None,
+            is_cross_repository: None,
+            review_decision: None,
+            is_draft: None,
+            body: None,
+        }
+    }
+
+    #[test]
+    fn summarizes_the_queue() {
+        let mut status = TrayStatus::default();
+        assert!(summary(&status).contains("list a repo"));
+        status.repo = Some("o/r".to_string());
+        status.error = Some("gh failed".to_string());
+        assert_eq!(
+            summary(&status),
+            "prvw — couldn't check the review queue of o/r"
+        );
+        status.last_checked_at = Some(1);
+        status.pending = vec![pr(1, "a")];
+        assert_eq!(summary(&status), "prvw — 1 review request in o/r");
+        status.pending.push(pr(2, "b"));
+        assert_eq!(summary(&status), "prvw — 2 review requests in o/r");
+    }
+
+    #[test]
+    fn labels_and_ids_of_prs() {
+        assert_eq!(pr_label(&pr(7, "Fix it")), "#7 Fix it");
+        let long = pr_label(&pr(8, &"word ".repeat(20)));
+        assert!(long.ends_with('…'));
+        assert_eq!(long.chars().count(), "#8 ".len() + MENU_TITLE_CHARS);
+        assert_eq!(parse_pr_id(&pr_id(&pr(42, "x"))), Some(42));
+        assert_eq!(parse_pr_id("quit"), None);
+    }
+
+    #[test]
+    fn config_round_trips() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(load_config(tmp.path()), Config::default());
+        let config = Config {
+            repo: Some("o/r".to_string()),
+            keep_running: true,
+        };
+        save_config(tmp.path(), &config).unwrap();
+        assert_eq!(load_config(tmp.path()), config);
+    }
+}
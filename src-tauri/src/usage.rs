@@ -0,0 +1,22 @@
+use crate::error::PrvwError;
+use crate::types::{TokenEstimate, UsageEntry};
+
+pub use prvw_core::usage::*;
+
+/// Estimate the input tokens of an analysis run from its hunks JSON and compare with `budget`.
+#[tauri::command]
+pub async fn estimate_tokens_for_hunks(
+    hunks_json: String,
+    budget: Option<u64>,
+) -> Result<TokenEstimate, PrvwError> {
+    Ok(estimate(&hunks_json, budget))
+}
+
+/// Recorded runs, newest first (at most `limit`, default 500).
+#[tauri::command]
+pub async fn get_usage_ledger(
+    app: tauri::AppHandle,
+    limit: Option<usize>,
+) -> Result<Vec<UsageEntry>, PrvwError> {
+    Ok(recent(&crate::app_data_dir(&app)?, limit.unwrap_or(500)))
+}
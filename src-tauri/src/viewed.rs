@@ -0,0 +1,318 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Deserialize;
+
+use crate::error::PrvwError;
+use crate::gh;
+use crate::session;
+use crate::time::now_secs;
+use crate::types::{AnalysisResult, Hunk, ReviewMark, ReviewSession, SessionUpdate, ViewedSync};
+
+/// Node id, head and per-file viewed state of a PR; the first 100 files cover all but the
+/// largest PRs.
+const VIEWED_FILES_QUERY: &str = "query($owner: String!, $name: String!, $number: Int!) {
+  repository(owner: $owner, name: $name) {
+    pullRequest(number: $number) {
+      id headRefOid
+      files(first: 100) { nodes { path viewerViewedState } }
+    }
+  }
+}";
+
+const MARK_VIEWED_MUTATION: &str = "mutation($id: ID!, $path: String!) {
+  markFileAsViewed(input: { pullRequestId: $id, path: $path }) { clientMutationId }
+}";
+
+#[derive(Deserialize)]
+struct GqlResponse {
+    data: GqlData,
+}
+
+#[derive(Deserialize)]
+struct GqlData {
+    repository: GqlRepository,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlRepository {
+    pull_request: GqlPullRequest,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlPullRequest {
+    id: String,
+    head_ref_oid: String,
+    files: GqlNodes<GqlFile>,
+}
+
+#[derive(Deserialize)]
+struct GqlNodes<T> {
+    nodes: Vec<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlFile {
+    path: String,
+    /// VIEWED, UNVIEWED, or DISMISSED (viewed, then changed by a later push).
+    viewer_viewed_state: String,
+}
+
+struct RemoteState {
+    pr_id: String,
+    head_sha: String,
+    viewed: BTreeSet<String>,
+}
+
+fn parse_viewed_files(json: &str) -> Result<RemoteState, String> {
+    let response: GqlResponse =
+        serde_json::from_str(json).map_err(|e| format!("Failed to parse viewed files: {}", e))?;
+    let pr = response.data.repository.pull_request;
+    Ok(RemoteState {
+        pr_id: pr.id,
+        head_sha: pr.head_ref_oid,
+        viewed: pr
+            .files
+            .nodes
+            .into_iter()
+            .filter(|f| f.viewer_viewed_state == "VIEWED")
+            .map(|f| f.path)
+            .collect(),
+    })
+}
+
+fn fetch_viewed_files(repo: &str, pr_number: u32) -> Result<RemoteState, String> {
+    let (owner, name) = repo.split_once('/').unwrap_or_default();
+    let output = gh::run_gh(
+        &[
+            "api",
+            "graphql",
+            "-f",
+            &format!("query={}", VIEWED_FILES_QUERY),
+            "-f",
+            &format!("owner={}", owner),
+            "-f",
+            &format!("name={}", name),
+            "-F",
+            &format!("number={}", pr_number),
+        ],
+        "gh api graphql",
+    )?;
+    parse_viewed_files(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn mark_file_viewed(pr_id: &str, path: &str) -> Result<(), String> {
+    gh::run_gh(
+        &[
+            "api",
+            "graphql",
+            "-f",
+            &format!("query={}", MARK_VIEWED_MUTATION),
+            "-f",
+            &format!("id={}", pr_id),
+            "-f",
+            &format!("path={}", path),
+        ],
+        "gh api graphql markFileAsViewed",
+    )
+    .map(|_| ())
+    .map_err(String::from)
+}
+
+/// Hunk ids per file, in diff order.
+fn hunks_by_file(hunks: &[Hunk]) -> BTreeMap<&str, Vec<&str>> {
+    let mut files: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for hunk in hunks {
+        files
+            .entry(hunk.file_path.as_str())
+            .or_default()
+            .push(hunk.id.as_str());
+    }
+    files
+}
+
+/// Whether `hunk_id` is marked reviewed at the session's head, directly or through a group.
+fn hunk_reviewed(
+    session: &ReviewSession,
+    analysis: Option<&AnalysisResult>,
+    hunk_id: &str,
+) -> bool {
+    let current = |mark: &ReviewMark| session::is_current(mark, session);
+    session.reviewed_hunks.get(hunk_id).is_some_and(current)
+        || analysis.is_some_and(|a| {
+            a.groups.iter().any(|g| {
+                g.hunk_ids.iter().any(|h| h == hunk_id)
+                    && session.reviewed_groups.get(&g.id).is_some_and(current)
+            })
+        })
+}
+
+/// Files to mark viewed on GitHub (every hunk reviewed locally, not yet viewed there) and
+/// files viewed on GitHub with hunks not yet reviewed locally. Markers are only ever added:
+/// unviewing on one side does not clear the other.
+fn plan_sync<'a>(
+    session: &ReviewSession,
+    analysis: Option<&AnalysisResult>,
+    hunks: &'a [Hunk],
+    viewed: &BTreeSet<String>,
+) -> (Vec<&'a str>, Vec<&'a str>) {
+    let mut to_github: Vec<&str> = Vec::new();
+    let mut to_local: Vec<&str> = Vec::new();
+    for (path, ids) in hunks_by_file(hunks) {
+        let reviewed = ids.iter().all(|id| hunk_reviewed(session, analysis, id));
+        match (reviewed, viewed.contains(path)) {
+            (true, false) => to_github.push(path),
+            (false, true) => to_local.push(path),
+            _ => {}
+        }
+    }
+    (to_github, to_local)
+}
+
+/// Mirror local "reviewed" markers and GitHub's per-file viewed state both ways: files whose
+/// hunks are all reviewed locally are marked viewed on GitHub, and files viewed on GitHub have
+/// their hunks marked reviewed in the session. Marks from an older head are ignored, matching
+/// GitHub, which dismisses viewed state when a file changes.
+#[tauri::command]
+pub async fn sync_viewed_state(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+    hunks_json: String,
+    analysis: Option<AnalysisResult>,
+) -> Result<ViewedSync, PrvwError> {
+    use tauri::Manager;
+    let repo = repo.trim();
+    gh::validate_repo(repo)?;
+    let hunks: Vec<Hunk> = serde_json::from_str(&hunks_json)
+        .map_err(|e| PrvwError::InvalidInput(format!("Invalid hunks JSON: {}", e)))?;
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    let remote = fetch_viewed_files(repo, pr_number)?;
+
+    let (to_github, to_local, session) = {
+        let _guard = session::SESSION_LOCK
+            .lock()
+            .map_err(|_| "Session lock poisoned".to_string())?;
+        let mut session = session::load(&app_data_dir, repo, pr_number);
+        session.head_sha = Some(remote.head_sha.clone());
+        let (to_github, to_local) = plan_sync(&session, analysis.as_ref(), &hunks, &remote.viewed);
+        let mark_hunks: Vec<String> = hunks
+            .iter()
+            .filter(|h| to_local.contains(&h.file_path.as_str()))
+            .map(|h| h.id.clone())
+            .collect();
+        if !mark_hunks.is_empty() {
+            session::apply_update(
+                &mut session,
+                &SessionUpdate {
+                    head_sha: Some(remote.head_sha.clone()),
+                    mark_hunks,
+                    ..SessionUpdate::default()
+                },
+                now_secs(),
+            );
+            session::save(&app_data_dir, &session)?;
+        }
+        (to_github, to_local, session)
+    };
+
+    for path in &to_github {
+        mark_file_viewed(&remote.pr_id, path)?;
+    }
+    Ok(ViewedSync {
+        marked_on_github: to_github.into_iter().map(str::to_string).collect(),
+        marked_locally: to_local.into_iter().map(str::to_string).collect(),
+        session,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::IntentGroup;
+
+    fn hunk(id: &str, file_path: &str) -> Hunk {
+        Hunk {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            subproject: None,
+            header: String::new(),
+            old_start: 1,
+            old_lines: 0,
+            new_start: 1,
+            new_lines: 0,
+            lines: vec![],
+        }
+    }
+
+    fn mark(head_sha: &str) -> ReviewMark {
+        ReviewMark {
+            at: 1,
+            head_sha: Some(head_sha.to_string()),
+        }
+    }
+
+    #[test]
+    fn plans_sync_both_ways() {
+        let hunks = vec![
+            hunk("H1", "a.rs"),
+            hunk("H2", "a.rs"),
+            hunk("H3", "b.rs"),
+            hunk("H4", "c.rs"),
+            hunk("H5", "d.rs"),
+        ];
+        let analysis = AnalysisResult {
+            version: 1,
+            overall_summary: String::new(),
+            groups: vec![IntentGroup {
+                id: "G1".to_string(),
+                title: String::new(),
+                category: String::new(),
+                rationale: String::new(),
+                risk: "low".to_string(),
+                risk_score: 0,
+                risk_evidence: vec![],
+                hunk_ids: vec!["H2".to_string()],
+                reviewer_checklist: vec![],
+                suggested_tests: vec![],
+            }],
+            unassigned_hunk_ids: vec![],
+            non_substantive_hunk_ids: vec![],
+            questions: vec![],
+        };
+        let mut session = ReviewSession {
+            head_sha: Some("new".to_string()),
+            ..ReviewSession::default()
+        };
+        session.reviewed_hunks.insert("H1".to_string(), mark("new"));
+        session
+            .reviewed_groups
+            .insert("G1".to_string(), mark("new"));
+        // reviewed before the last push: no longer counts
+        session.reviewed_hunks.insert("H4".to_string(), mark("old"));
+        session.reviewed_hunks.insert("H5".to_string(), mark("new"));
+
+        let viewed: BTreeSet<String> = ["b.rs", "d.rs"].iter().map(ToString::to_string).collect();
+        let (to_github, to_local) = plan_sync(&session, Some(&analysis), &hunks, &viewed);
+        assert_eq!(to_github, vec!["a.rs"]);
+        assert_eq!(to_local, vec!["b.rs"]);
+    }
+
+    #[test]
+    fn parses_viewed_files() {
+        let json = r#"{"data":{"repository":{"pullRequest":{"id":"PR_1","headRefOid":"abc",
+            "files":{"nodes":[
+                {"path":"a.rs","viewerViewedState":"VIEWED"},
+                {"path":"b.rs","viewerViewedState":"DISMISSED"},
+                {"path":"c.rs","viewerViewedState":"UNVIEWED"}]}}}}}"#;
+        let remote = parse_viewed_files(json).unwrap();
+        assert_eq!(remote.pr_id, "PR_1");
+        assert_eq!(remote.head_sha, "abc");
+        assert_eq!(remote.viewed.into_iter().collect::<Vec<_>>(), vec!["a.rs"]);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::error::PrvwError;
+use crate::types::{PrWorkspace, WorkspaceInfo};
+
+pub use prvw_core::workspace::*;
+
+/// Check out the head of a PR in its managed worktree (fetched like `gh pr checkout` does, from
+/// `refs/pull/N/head`, so forks work too), for tools that need a real tree. Reuses and resets an
+/// existing checkout.
+#[tauri::command]
+pub async fn checkout_pr_workspace(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+) -> Result<PrWorkspace, PrvwError> {
+    checkout(&crate::app_data_dir(&app)?, &repo, pr_number)
+}
+
+/// Free the disk space of managed checkouts: one PR's (`repo` and `pr_number`), a whole repo's
+/// (`repo` only), or all of them. Returns the number of PR checkouts removed.
+#[tauri::command]
+pub async fn cleanup_workspaces(
+    app: tauri::AppHandle,
+    repo: Option<String>,
+    pr_number: Option<u32>,
+) -> Result<u32, PrvwError> {
+    remove(&crate::app_data_dir(&app)?, repo.as_deref(), pr_number)
+}
+
+/// PR checkouts on disk with their size, for the workspace list in Settings.
+#[tauri::command]
+pub async fn list_workspaces(app: tauri::AppHandle) -> Result<Vec<WorkspaceInfo>, PrvwError> {
+    Ok(list(&crate::app_data_dir(&app)?))
+}
+
+/// Remove one PR's checkout (unregistering the worktree from the user's clone when it is one of
+/// its worktrees). Returns whether there was one.
+#[tauri::command]
+pub async fn remove_workspace(
+    app: tauri::AppHandle,
+    repo: String,
+    pr_number: u32,
+) -> Result<bool, PrvwError> {
+    Ok(remove(&crate::app_data_dir(&app)?, Some(&repo), Some(pr_number))? > 0)
+}
+
+/// Disk usage of all workspaces (checkouts and managed base clones), human-readable.
+#[tauri::command]
+pub async fn get_workspace_size(app: tauri::AppHandle) -> Result<String, PrvwError> {
+    Ok(size(&crate::app_data_dir(&app)?))
+}
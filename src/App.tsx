@@ -1,18 +1,31 @@
 import { openUrl } from "@tauri-apps/plugin-opener";
-import { useMemo, useState } from "react";
+import { useEffect, useMemo, useState } from "react";
 import { DiffPane } from "./components/DiffPane";
 import { GroupsPane } from "./components/GroupsPane";
 import { Header } from "./components/Header";
+import { PostSummaryModal } from "./components/PostSummaryModal";
+import { PrefetchBar } from "./components/PrefetchBar";
 import { PrList } from "./components/PrList";
 import { SettingsModal } from "./components/SettingsModal";
 import { SummaryPane } from "./components/SummaryPane";
 import { useAnalysis } from "./hooks/useAnalysis";
 import { useAutoRunAnalysis } from "./hooks/useAutoRunAnalysis";
+import { useCacheWriteErrors } from "./hooks/useCacheManager";
 import { useGroupFiltering } from "./hooks/useGroupFiltering";
+import { useHeadWatch } from "./hooks/useHeadWatch";
+import { useHunkSummaries } from "./hooks/useHunkSummaries";
+import { useDeepLinkErrors, useOpenPrEvents } from "./hooks/useOpenPr";
+import { cancelOperationApi, useOperations } from "./hooks/useOperations";
+import { usePrefetch } from "./hooks/usePrefetch";
 import { usePrDiff } from "./hooks/usePrDiff";
 import { usePrList } from "./hooks/usePrList";
+import { usePrWatch } from "./hooks/usePrWatch";
 import { useRepoHistory } from "./hooks/useRepoHistory";
+import { useRepoSettings } from "./hooks/useRepoSettings";
 import { useSettings } from "./hooks/useSettings";
+import { setTrayRepoApi } from "./hooks/useTray";
+import type { OpenPr } from "./types";
+import { errorMessage } from "./utils/prvwError";
 import "./App.css";
 
 function App() {
@@ -21,20 +34,40 @@ function App() {
   const [error, setError] = useState<string | null>(null);
   const [loading, setLoading] = useState<string | null>(null);
   const [settingsOpen, setSettingsOpen] = useState(false);
+  const [postSummaryOpen, setPostSummaryOpen] = useState(false);
 
   // ── Settings ──
-  const { codexModel, lang, hasSettings, saveSettings } = useSettings();
+  const {
+    codexModel,
+    lang,
+    codexTimeout,
+    provider,
+    useGuidelines,
+    useRepoContext,
+    runOptions,
+    tokenBudget,
+    costPerMTokens,
+    hasSettings,
+    saveSettings,
+  } = useSettings();
 
   // ── Hooks ──
+  useCacheWriteErrors((message) => console.warn(`Cache write failed: ${message}`));
   const { repo, setRepo, repoHistory, persistRepo } = useRepoHistory();
+  // Repo overrides win over the app-wide model and language while that repo is open.
+  const { repoSettings } = useRepoSettings(repo);
+  const repoModel = repoSettings?.model ?? codexModel;
+  const repoLang = repoSettings?.lang ?? lang;
 
   const { prs, fetchPrs, fetchMore, hasMore, loadingMore } = usePrList({
     repo,
     search,
     onFetched: (r) => {
       persistRepo(r);
+      setTrayRepoApi(r).catch(() => {});
       clearSelection();
       resetAnalysis();
+      resetSummaries();
     },
     setError,
     setLoading,
@@ -43,19 +76,44 @@ function App() {
   const {
     selectedPr,
     hunks,
+    sessionId,
     selectPr: rawSelectPr,
+    refreshHead,
     clearSelection,
   } = usePrDiff({
     repo,
     setError,
     setLoading,
   });
+  usePrWatch(repo, selectedPr);
 
-  const { analysis, codexLog, fromCache, runAnalysis, refineGroup, resetAnalysis } = useAnalysis({
+  const { hunkSummaries, summarizing, runSummaries, resetSummaries } = useHunkSummaries({
     hunks,
+    repo,
+    codexModel: repoModel,
+    lang: repoLang,
+    codexTimeout,
+    provider,
+    runOptions,
+    setError,
+  });
+
+  const { analysis, codexLog, fromCache, runAnalysis, refineGroup, installAnalysis, resetAnalysis } = useAnalysis({
+    hunks,
+    sessionId,
+    repo,
+    prNumber: selectedPr?.number ?? null,
     prBody: selectedPr?.body ?? null,
-    codexModel,
-    lang,
+    hunkSummaries,
+    codexModel: repoModel,
+    lang: repoLang,
+    codexTimeout,
+    provider,
+    useGuidelines,
+    useRepoContext,
+    runOptions,
+    tokenBudget,
+    costPerMTokens,
     setError,
     setLoading,
   });
@@ -67,8 +125,19 @@ function App() {
     displayedHunks,
     reviewedGroups,
     toggleReviewed,
+    checkedItems,
+    toggleChecklistItem,
     resetFiltering,
-  } = useGroupFiltering(hunks, analysis);
+  } = useGroupFiltering(
+    hunks,
+    analysis,
+    selectedPr && repo.trim()
+      ? { repo: repo.trim(), prNumber: selectedPr.number, headSha: selectedPr.headRefOid ?? null }
+      : null,
+  );
+
+  const prefetch = usePrefetch(setError);
+  const { current: currentOp } = useOperations();
 
   const nonSubstantiveHunkIds = useMemo(() => new Set(analysis?.nonSubstantiveHunkIds ?? []), [analysis]);
 
@@ -85,17 +154,58 @@ function App() {
   function handleSelectPr(pr: (typeof prs)[number]) {
     resetAutoRun();
     resetAnalysis();
+    resetSummaries();
     resetFiltering();
     rawSelectPr(pr);
   }
 
+  // A PR picked from the tray or a prvw:// link opens once its repo is the current one.
+  const [pendingPr, setPendingPr] = useState<OpenPr | null>(null);
+  useOpenPrEvents((open) => {
+    setRepo(open.repo);
+    setPendingPr(open);
+  });
+  useDeepLinkErrors(setError);
+  // biome-ignore lint/correctness/useExhaustiveDependencies: runs when the pending PR or repo changes
+  useEffect(() => {
+    if (pendingPr && repo.trim() === pendingPr.repo) {
+      setPendingPr(null);
+      handleSelectPr(pendingPr.pr);
+    }
+  }, [pendingPr, repo]);
+
+  // New commits on the open PR: offer to load them, keeping the review and the analysis.
+  useHeadWatch(repo, selectedPr?.number ?? null, sessionId ? (selectedPr?.headRefOid ?? null) : null, (change) => {
+    if (!selectedPr || change.repo !== repo.trim() || change.prNumber !== selectedPr.number) return;
+    const load = window.confirm(`New commits were pushed to #${change.prNumber}. Load them and keep your review?`);
+    if (!load) return;
+    refreshHead(change.headSha).then((refresh) => {
+      if (refresh?.analysis) installAnalysis(refresh.analysis);
+    });
+  });
+
   function goBackToList() {
     resetAutoRun();
     clearSelection();
     resetAnalysis();
+    resetSummaries();
     resetFiltering();
   }
 
+  function startPrefetch(delaySecs: number) {
+    prefetch.start({
+      repo,
+      model: repoModel.trim() || null,
+      lang: repoLang.trim() || null,
+      provider,
+      options: runOptions,
+      useGuidelines,
+      useRepoContext,
+      timeoutMinutes: codexTimeout,
+      delaySecs,
+    });
+  }
+
   // ── Render ──
   return (
     <div className="app">
@@ -119,7 +229,31 @@ function App() {
           </button>
         </div>
       )}
-      {loading && <div className="loading-bar">{loading}</div>}
+      {loading && (
+        <div className="loading-bar">
+          {loading}
+          {currentOp?.message && <span className="loading-step"> — {currentOp.message}</span>}
+          {currentOp && (
+            <button
+              type="button"
+              className="btn btn-ghost loading-cancel"
+              onClick={() => cancelOperationApi(currentOp.opId).catch(() => {})}
+            >
+              Cancel
+            </button>
+          )}
+        </div>
+      )}
+
+      {/* Background pre-analysis of the review queue */}
+      {!selectedPr && repo.trim() && (
+        <PrefetchBar
+          status={prefetch.status}
+          active={prefetch.active}
+          onStart={startPrefetch}
+          onCancel={prefetch.cancel}
+        />
+      )}
 
       {/* PR List (shown when no PR selected) */}
       {!selectedPr && prs.length > 0 && (
@@ -150,32 +284,61 @@ function App() {
             onBack={goBackToList}
           />
           <DiffPane
+            repo={repo}
+            prNumber={selectedPr?.number ?? null}
             hunks={displayedHunks}
             selectedGroup={selectedGroup}
             selectedGroupId={selectedGroupId}
             nonSubstantiveHunkIds={nonSubstantiveHunkIds}
-            model={codexModel}
-            lang={lang}
+            model={repoModel}
+            lang={repoLang}
+            timeoutMinutes={codexTimeout}
+            provider={provider}
+            runOptions={runOptions}
+            hunkSummaries={hunkSummaries}
+            summarizing={summarizing}
+            onSummarize={() => runSummaries()}
           />
           <SummaryPane
             selectedPr={selectedPr}
             analysis={analysis}
             selectedGroup={selectedGroup}
+            checkedItems={checkedItems}
+            onToggleChecklistItem={toggleChecklistItem}
             codexLog={codexLog}
             onOpenPr={() => {
               if (selectedPr?.url) {
-                openUrl(selectedPr.url).catch((e) => setError(String(e)));
+                openUrl(selectedPr.url).catch((e) => setError(errorMessage(e)));
               }
             }}
+            onPostSummary={() => setPostSummaryOpen(true)}
           />
         </div>
       )}
 
+      {/* Post review summary (preview + edit before posting) */}
+      {postSummaryOpen && selectedPr && analysis && (
+        <PostSummaryModal
+          repo={repo}
+          prNumber={selectedPr.number}
+          analysis={analysis}
+          onPosted={() => setPostSummaryOpen(false)}
+          onClose={() => setPostSummaryOpen(false)}
+        />
+      )}
+
       {/* Settings Modal — force on first launch */}
       {!hasSettings && (
         <SettingsModal
           initialModel={codexModel}
           initialLang={lang}
+          initialTimeout={codexTimeout}
+          initialProvider={provider}
+          initialUseGuidelines={useGuidelines}
+          initialUseRepoContext={useRepoContext}
+          initialRunOptions={runOptions}
+          initialTokenBudget={tokenBudget}
+          initialCostPerMTokens={costPerMTokens}
           force={true}
           onSave={(s) => {
             saveSettings(s);
@@ -189,6 +352,13 @@ function App() {
         <SettingsModal
           initialModel={codexModel}
           initialLang={lang}
+          initialTimeout={codexTimeout}
+          initialProvider={provider}
+          initialUseGuidelines={useGuidelines}
+          initialUseRepoContext={useRepoContext}
+          initialRunOptions={runOptions}
+          initialTokenBudget={tokenBudget}
+          initialCostPerMTokens={costPerMTokens}
           force={false}
           onSave={(s) => {
             saveSettings(s);
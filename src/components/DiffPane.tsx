@@ -1,20 +1,45 @@
 import { useMemo, useState } from "react";
 import { UNASSIGNED_GROUP_ID } from "../constants";
-import type { Hunk, IntentGroup } from "../types";
+import { openInEditorApi } from "../hooks/useReviewApi";
+import type { Hunk, HunkSummary, IntentGroup, RunOptions } from "../types";
 import { classifyFile } from "../utils/classifyFile";
 import { getFileExtension } from "../utils/fileExtension";
 import { HunkDetailModal } from "./HunkDetailModal";
+import { errorMessage } from "../utils/prvwError";
 
 interface Props {
+  repo: string;
+  prNumber: number | null;
   hunks: Hunk[];
   selectedGroup: IntentGroup | null;
   selectedGroupId: string | null;
   nonSubstantiveHunkIds: Set<string>;
   model: string;
   lang: string;
+  timeoutMinutes: number;
+  provider: string;
+  runOptions: RunOptions;
+  hunkSummaries: HunkSummary[];
+  summarizing: boolean;
+  onSummarize: () => void;
 }
 
-export function DiffPane({ hunks, selectedGroup, selectedGroupId, nonSubstantiveHunkIds, model, lang }: Props) {
+export function DiffPane({
+  repo,
+  prNumber,
+  hunks,
+  selectedGroup,
+  selectedGroupId,
+  nonSubstantiveHunkIds,
+  model,
+  lang,
+  timeoutMinutes,
+  provider,
+  runOptions,
+  hunkSummaries,
+  summarizing,
+  onSummarize,
+}: Props) {
   const [hideTests, setHideTests] = useState(false);
   const [hiddenExts, setHiddenExts] = useState<Set<string>>(new Set());
   const [collapseCosmetic, setCollapseCosmetic] = useState(true);
@@ -38,8 +63,15 @@ export function DiffPane({ hunks, selectedGroup, selectedGroupId, nonSubstantive
     });
   }
 
+  const summaryById = useMemo(() => new Map(hunkSummaries.map((s) => [s.hunkId, s.summary])), [hunkSummaries]);
+
   const hasNonSubstantive = nonSubstantiveHunkIds.size > 0;
 
+  function openInEditor(hunk: Hunk) {
+    const line = hunk.lines.find((l) => l.kind === "add")?.newLine ?? hunk.newStart;
+    openInEditorApi(repo, prNumber, hunk.filePath, line).catch((e) => alert(errorMessage(e)));
+  }
+
   const filteredHunks = useMemo(() => {
     return hunks.filter((h) => {
       if (hideTests && classifyFile(h.filePath) === "test") return false;
@@ -92,6 +124,9 @@ export function DiffPane({ hunks, selectedGroup, selectedGroupId, nonSubstantive
           <button type="button" className="btn-mini" onClick={collapseAll}>
             Collapse all
           </button>
+          <button type="button" className="btn-mini" onClick={onSummarize} disabled={summarizing || hunks.length === 0}>
+            {summarizing ? "Summarizing..." : "Summarize hunks"}
+          </button>
           <span className="filter-sep" />
           <label className={`filter-toggle ${hideTests ? "off" : ""}`}>
             <input type="checkbox" checked={!hideTests} onChange={() => setHideTests((v) => !v)} />
@@ -144,7 +179,12 @@ export function DiffPane({ hunks, selectedGroup, selectedGroupId, nonSubstantive
                   return (
                     <div key={hunk.id} className="hunk-block" style={cosmeticCollapsed ? { opacity: 0.5 } : undefined}>
                       <div className="hunk-header">
-                        <span className={`hunk-id${isCosmetic ? " hunk-id-cosmetic" : ""}`}>{hunk.id}</span>
+                        <span
+                          className={`hunk-id${isCosmetic ? " hunk-id-cosmetic" : ""}`}
+                          title={summaryById.get(hunk.id)}
+                        >
+                          {hunk.id}
+                        </span>
                         <span className="hunk-range">{hunk.header}</span>
                         {cosmeticCollapsed && <span className="cosmetic-badge">cosmetic</span>}
                         {hasAdds && !cosmeticCollapsed && (
@@ -152,6 +192,14 @@ export function DiffPane({ hunks, selectedGroup, selectedGroupId, nonSubstantive
                             Deep Dive
                           </button>
                         )}
+                        <button
+                          type="button"
+                          className="btn-mini"
+                          onClick={() => openInEditor(hunk)}
+                          title="Open in editor (PR checkout or the repo's clone)"
+                        >
+                          Open
+                        </button>
                       </div>
                       {!cosmeticCollapsed && (
                         <pre className="hunk-code">
@@ -176,7 +224,16 @@ export function DiffPane({ hunks, selectedGroup, selectedGroupId, nonSubstantive
         {hunks.length === 0 && <p className="hint">No diff loaded.</p>}
       </div>
       {detailHunk && (
-        <HunkDetailModal hunk={detailHunk} model={model} lang={lang} onClose={() => setDetailHunk(null)} />
+        <HunkDetailModal
+          hunk={detailHunk}
+          repo={repo}
+          model={model}
+          lang={lang}
+          timeoutMinutes={timeoutMinutes}
+          provider={provider}
+          runOptions={runOptions}
+          onClose={() => setDetailHunk(null)}
+        />
       )}
     </div>
   );
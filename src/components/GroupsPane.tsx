@@ -1,6 +1,6 @@
 import { useEffect, useMemo, useState } from "react";
-import { UNASSIGNED_GROUP_ID } from "../constants";
-import type { AnalysisResult, Hunk, IntentGroup } from "../types";
+import { REASONING_EFFORTS, UNASSIGNED_GROUP_ID } from "../constants";
+import type { AnalysisResult, Hunk, IntentGroup, RunOptions } from "../types";
 import { GroupListItem } from "./GroupListItem";
 
 const SPINNER_FRAMES = ["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"];
@@ -25,7 +25,7 @@ interface Props {
   fromCache: boolean;
   onSelectGroup: (id: string | null) => void;
   onToggleReviewed: (id: string) => void;
-  onRunAnalysis: (force?: boolean) => void;
+  onRunAnalysis: (force?: boolean, overrides?: RunOptions) => void;
   onRefineGroup: (group: IntentGroup) => void;
   onBack: () => void;
 }
@@ -46,6 +46,9 @@ export function GroupsPane({
 }: Props) {
   const loading = !!loadingMessage;
   const spinner = useSpinner(loading);
+  // Per-run override of the reasoning effort from Settings; empty keeps the setting.
+  const [effort, setEffort] = useState("");
+  const overrides: RunOptions | undefined = effort ? { reasoningEffort: effort } : undefined;
 
   const reviewProgress = useMemo(() => {
     if (!analysis) return null;
@@ -68,7 +71,7 @@ export function GroupsPane({
               <button
                 type="button"
                 className="cache-badge-btn"
-                onClick={() => onRunAnalysis(true)}
+                onClick={() => onRunAnalysis(true, overrides)}
                 disabled={loading}
                 title="Re-run analysis (bypass cache)"
               >
@@ -76,10 +79,24 @@ export function GroupsPane({
                 <span className="cache-badge-reload">reload</span>
               </button>
             )}
+            <select
+              className="input"
+              value={effort}
+              onChange={(e) => setEffort(e.target.value)}
+              disabled={loading}
+              title="Reasoning effort for this run"
+            >
+              <option value="">effort: default</option>
+              {REASONING_EFFORTS.map((e) => (
+                <option key={e} value={e}>
+                  effort: {e}
+                </option>
+              ))}
+            </select>
             <button
               type="button"
               className="btn btn-accent"
-              onClick={() => onRunAnalysis(!!analysis)}
+              onClick={() => onRunAnalysis(!!analysis, overrides)}
               disabled={loading || hunks.length === 0}
             >
               {analysis ? "Re-run" : "Run"}
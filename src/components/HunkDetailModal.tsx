@@ -2,8 +2,10 @@ import { Highlight, themes } from "prism-react-renderer";
 import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 import Markdown from "react-markdown";
 import { askAboutHunkApi, explainHunkApi } from "../hooks/useCodexApi";
-import type { Hunk } from "../types";
+import { newOpId } from "../hooks/useOperations";
+import type { Hunk, RunOptions } from "../types";
 import { getFileExtension } from "../utils/fileExtension";
+import { errorMessage } from "../utils/prvwError";
 
 const EXT_TO_LANG: Record<string, string> = {
   ".js": "javascript",
@@ -53,12 +55,16 @@ interface Message {
 
 interface Props {
   hunk: Hunk;
+  repo: string;
   model: string;
   lang: string;
+  timeoutMinutes: number;
+  provider: string;
+  runOptions: RunOptions;
   onClose: () => void;
 }
 
-export function HunkDetailModal({ hunk, model, lang, onClose }: Props) {
+export function HunkDetailModal({ hunk, repo, model, lang, timeoutMinutes, provider, runOptions, onClose }: Props) {
   const [messages, setMessages] = useState<Message[]>([]);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
@@ -81,17 +87,17 @@ export function HunkDetailModal({ hunk, model, lang, onClose }: Props) {
       setLoading(true);
       setError(null);
       try {
-        const res = await explainHunkApi(hunk, model, lang, force);
+        const res = await explainHunkApi(hunk, model, lang, force, timeoutMinutes, provider, runOptions, repo, newOpId("explain"));
         setMessages([{ role: "assistant", content: res.explanation }]);
         setFromCache(res.fromCache);
         scrollToBottom();
       } catch (e) {
-        setError(String(e));
+        setError(errorMessage(e));
       } finally {
         setLoading(false);
       }
     },
-    [hunk, model, lang, scrollToBottom],
+    [hunk, repo, model, lang, timeoutMinutes, provider, runOptions, scrollToBottom],
   );
 
   useEffect(() => {
@@ -127,11 +133,11 @@ export function HunkDetailModal({ hunk, model, lang, onClose }: Props) {
     setError(null);
 
     try {
-      const res = await askAboutHunkApi(hunk, q, ctx, model, lang);
+      const res = await askAboutHunkApi(hunk, q, ctx, model, lang, timeoutMinutes, provider, runOptions, repo, newOpId("ask"));
       setMessages((prev) => [...prev, { role: "assistant", content: res.explanation }]);
       scrollToBottom();
     } catch (e) {
-      setError(String(e));
+      setError(errorMessage(e));
     } finally {
       setAsking(false);
     }
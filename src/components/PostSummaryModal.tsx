@@ -0,0 +1,72 @@
+import { useEffect, useState } from "react";
+import { newOpId } from "../hooks/useOperations";
+import { postReviewSummaryApi, renderReviewSummaryApi } from "../hooks/useReviewApi";
+import type { AnalysisResult } from "../types";
+import { errorMessage } from "../utils/prvwError";
+
+interface Props {
+  repo: string;
+  prNumber: number;
+  analysis: AnalysisResult;
+  onPosted: (url: string) => void;
+  onClose: () => void;
+}
+
+export function PostSummaryModal({ repo, prNumber, analysis, onPosted, onClose }: Props) {
+  const [body, setBody] = useState("");
+  const [posting, setPosting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    renderReviewSummaryApi(analysis, { repo, prNumber })
+      .then(setBody)
+      .catch((e) => setError(errorMessage(e)));
+  }, [analysis, repo, prNumber]);
+
+  async function post(asReview: boolean) {
+    setPosting(true);
+    setError(null);
+    try {
+      const url = await postReviewSummaryApi(repo, prNumber, body, asReview, newOpId("post"));
+      onPosted(url);
+    } catch (e) {
+      setError(errorMessage(e));
+    } finally {
+      setPosting(false);
+    }
+  }
+
+  return (
+    <div className="modal-overlay">
+      <div className="modal">
+        <div className="modal-header">
+          <h3>Post Review Summary — #{prNumber}</h3>
+          <button type="button" className="btn-close" onClick={onClose}>
+            ×
+          </button>
+        </div>
+        <div className="modal-body">
+          {error && <p className="hint">{error}</p>}
+          <textarea
+            className="input"
+            value={body}
+            onChange={(e) => setBody(e.target.value)}
+            rows={20}
+            style={{ width: "100%", fontFamily: "monospace" }}
+          />
+        </div>
+        <div className="modal-footer">
+          <button type="button" className="btn btn-ghost" onClick={onClose}>
+            Cancel
+          </button>
+          <button type="button" className="btn btn-accent" onClick={() => post(false)} disabled={posting || !body}>
+            Post as comment
+          </button>
+          <button type="button" className="btn btn-primary" onClick={() => post(true)} disabled={posting || !body}>
+            {posting ? "Posting..." : "Post as review"}
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,56 @@
+import { secondsUntilHour } from "../hooks/usePrefetch";
+import type { PrefetchStatus } from "../types";
+
+/** Local hour at which a "tonight" pre-analysis starts. */
+const NIGHT_HOUR = 2;
+
+interface Props {
+  status: PrefetchStatus | null;
+  active: boolean;
+  onStart: (delaySecs: number) => void;
+  onCancel: () => void;
+}
+
+function describe(status: PrefetchStatus): string {
+  switch (status.phase) {
+    case "scheduled":
+      return `Scheduled for ${new Date((status.startsAt ?? 0) * 1000).toLocaleString()}`;
+    case "running":
+      return `Pre-analyzing ${status.completed + status.failed}/${status.total || "?"}${
+        status.currentPr != null ? ` (#${status.currentPr})` : ""
+      }`;
+    case "done":
+      return `Pre-analyzed ${status.completed}/${status.total}${status.failed ? `, ${status.failed} failed` : ""}`;
+    case "cancelled":
+      return "Pre-analysis cancelled";
+    default:
+      return "";
+  }
+}
+
+export function PrefetchBar({ status, active, onStart, onCancel }: Props) {
+  return (
+    <div className="prefetch-bar">
+      <span className="prefetch-label">Review queue</span>
+      {active ? (
+        <button type="button" className="btn btn-ghost" onClick={onCancel}>
+          Cancel
+        </button>
+      ) : (
+        <>
+          <button type="button" className="btn btn-accent" onClick={() => onStart(0)}>
+            Pre-analyze now
+          </button>
+          <button type="button" className="btn btn-ghost" onClick={() => onStart(secondsUntilHour(NIGHT_HOUR))}>
+            Tonight ({String(NIGHT_HOUR).padStart(2, "0")}:00)
+          </button>
+        </>
+      )}
+      {status && (
+        <span className="prefetch-status" title={status.errors.join("\n")}>
+          {describe(status)}
+        </span>
+      )}
+    </div>
+  );
+}
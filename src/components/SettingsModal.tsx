@@ -1,21 +1,197 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
+import { REASONING_EFFORTS } from "../constants";
+import { useApiServer } from "../hooks/useApiServer";
 import { useCacheManager } from "../hooks/useCacheManager";
+import { useConfigFile } from "../hooks/useConfigFile";
+import { useErrorReports } from "../hooks/useErrorReports";
+import { useSecret } from "../hooks/useSecrets";
+import { useTray } from "../hooks/useTray";
+import { useUsageLedger } from "../hooks/useUsageLedger";
+import { useWorkspaces } from "../hooks/useWorkspaces";
+import {
+  getSettingsApi,
+  loadPrefetchDiffCount,
+  savePrefetchDiffCount,
+  type Settings,
+  updateSettingsApi,
+} from "../hooks/useSettings";
+import type { NotificationSettings, RunOptions } from "../types";
+import { errorMessage } from "../utils/prvwError";
 
 interface Props {
   initialModel: string;
   initialLang: string;
+  initialTimeout: number;
+  initialProvider: string;
+  initialUseGuidelines: boolean;
+  initialUseRepoContext: boolean;
+  initialRunOptions: RunOptions;
+  initialTokenBudget: number;
+  initialCostPerMTokens: number;
   force: boolean;
-  onSave: (settings: { codexModel: string; lang: string }) => void;
+  onSave: (settings: Settings) => void;
   onClose: () => void;
 }
 
-export function SettingsModal({ initialModel, initialLang, force, onSave, onClose }: Props) {
+export function SettingsModal({
+  initialModel,
+  initialLang,
+  initialTimeout,
+  initialProvider,
+  initialUseGuidelines,
+  initialUseRepoContext,
+  initialRunOptions,
+  initialTokenBudget,
+  initialCostPerMTokens,
+  force,
+  onSave,
+  onClose,
+}: Props) {
   const [model, setModel] = useState(initialModel);
   const [lang, setLang] = useState(initialLang || "ja");
-  const { cacheSize, clearing, clearCache } = useCacheManager();
+  const [provider, setProvider] = useState(initialProvider || "codex");
+  const [useGuidelines, setUseGuidelines] = useState(initialUseGuidelines);
+  const [useRepoContext, setUseRepoContext] = useState(initialUseRepoContext);
+  const [runOptions, setRunOptions] = useState<RunOptions>(initialRunOptions);
+  const [timeoutMinutes, setTimeoutMinutes] = useState(String(initialTimeout || 10));
+  const [tokenBudget, setTokenBudget] = useState(initialTokenBudget > 0 ? String(initialTokenBudget) : "");
+  const [costPerMTokens, setCostPerMTokens] = useState(initialCostPerMTokens > 0 ? String(initialCostPerMTokens) : "");
+  const [apiKey, setApiKey] = useState("");
+  const { cacheSize, clearing, clearCache, cacheLimitMb, saveCacheLimit, encryptCache, saveCacheEncryption } =
+    useCacheManager();
+  const [cacheLimit, setCacheLimit] = useState<string | null>(null);
+  const [encrypt, setEncrypt] = useState<boolean | null>(null);
+  const [prefetchDiffs, setPrefetchDiffs] = useState(() => {
+    const n = loadPrefetchDiffCount();
+    return n > 0 ? String(n) : "";
+  });
+  const openAiSecret = useSecret("openai");
+  const githubSecret = useSecret("github");
+  const [githubToken, setGithubToken] = useState("");
+  const usage = useUsageLedger();
+  const { workspaces, workspaceSize, removing, removeWorkspace, removeAllWorkspaces } = useWorkspaces();
+  const apiServer = useApiServer();
+  const tray = useTray();
+  const [keepRunning, setKeepRunning] = useState<boolean | null>(null);
+  const [apiPort, setApiPort] = useState("");
+  const [savedEditorCommand, setSavedEditorCommand] = useState("");
+  const [editorCommand, setEditorCommand] = useState<string | null>(null);
+  const [savedNotifications, setSavedNotifications] = useState<NotificationSettings | null>(null);
+  const [notifications, setNotifications] = useState<NotificationSettings | null>(null);
+  const [savedErrorReporting, setSavedErrorReporting] = useState(false);
+  const [errorReporting, setErrorReporting] = useState<boolean | null>(null);
+  const [savedUiLang, setSavedUiLang] = useState("");
+  const [uiLang, setUiLang] = useState<string | null>(null);
+  const [reportsPath, setReportsPath] = useState("");
+  const errorReports = useErrorReports();
+  const configFile = useConfigFile();
 
-  function handleSave() {
-    onSave({ codexModel: model, lang });
+  useEffect(() => {
+    getSettingsApi()
+      .then((s) => {
+        setSavedEditorCommand(s.editorCommand ?? "");
+        setSavedNotifications(s.notifications);
+        setSavedErrorReporting(s.errorReports);
+        setSavedUiLang(s.uiLang ?? "");
+      })
+      .catch(() => {});
+  }, []);
+
+  function toggleNotification(key: keyof NotificationSettings, checked: boolean) {
+    const current = notifications ?? savedNotifications;
+    if (current) setNotifications({ ...current, [key]: checked });
+  }
+
+  async function handleSave() {
+    if (apiKey.trim()) {
+      try {
+        await openAiSecret.save(apiKey);
+      } catch (e) {
+        alert(`Failed to save API key: ${errorMessage(e)}`);
+        return;
+      }
+    }
+    if (githubToken.trim()) {
+      try {
+        await githubSecret.save(githubToken);
+      } catch (e) {
+        alert(`Failed to save GitHub token: ${errorMessage(e)}`);
+        return;
+      }
+    }
+    if (cacheLimit != null) {
+      const limit = Number.parseInt(cacheLimit, 10);
+      try {
+        await saveCacheLimit(limit > 0 ? limit : 0);
+      } catch (e) {
+        alert(`Failed to save cache limit: ${errorMessage(e)}`);
+        return;
+      }
+    }
+    if (encrypt != null && encrypt !== encryptCache) {
+      try {
+        await saveCacheEncryption(encrypt);
+      } catch (e) {
+        alert(`Failed to change cache encryption: ${errorMessage(e)}`);
+        return;
+      }
+    }
+    if (editorCommand != null && editorCommand.trim() !== savedEditorCommand) {
+      try {
+        await updateSettingsApi({ editorCommand });
+      } catch (e) {
+        alert(`Failed to save editor command: ${errorMessage(e)}`);
+        return;
+      }
+    }
+    if (notifications != null) {
+      try {
+        await updateSettingsApi({ notifications });
+      } catch (e) {
+        alert(`Failed to save notification settings: ${errorMessage(e)}`);
+        return;
+      }
+    }
+    if (errorReporting != null && errorReporting !== savedErrorReporting) {
+      try {
+        await updateSettingsApi({ errorReports: errorReporting });
+      } catch (e) {
+        alert(`Failed to save error reporting: ${errorMessage(e)}`);
+        return;
+      }
+    }
+    if (uiLang != null && uiLang !== savedUiLang) {
+      try {
+        await updateSettingsApi({ uiLang });
+      } catch (e) {
+        alert(`Failed to save interface language: ${errorMessage(e)}`);
+        return;
+      }
+    }
+    if (keepRunning != null && keepRunning !== tray.status?.keepRunning) {
+      try {
+        await tray.saveKeepRunning(keepRunning);
+      } catch (e) {
+        alert(`Failed to save tray setting: ${errorMessage(e)}`);
+        return;
+      }
+    }
+    const prefetchCount = Number.parseInt(prefetchDiffs, 10);
+    savePrefetchDiffCount(prefetchCount > 0 ? prefetchCount : 0);
+    const minutes = Number.parseInt(timeoutMinutes, 10);
+    const budget = Number.parseInt(tokenBudget, 10);
+    const cost = Number.parseFloat(costPerMTokens);
+    onSave({
+      codexModel: model,
+      lang,
+      codexTimeout: minutes > 0 ? minutes : 10,
+      provider,
+      useGuidelines,
+      useRepoContext,
+      runOptions,
+      tokenBudget: budget > 0 ? budget : 0,
+      costPerMTokens: cost > 0 ? cost : 0,
+    });
   }
 
   return (
@@ -30,8 +206,87 @@ export function SettingsModal({ initialModel, initialLang, force, onSave, onClos
           )}
         </div>
         <div className="modal-body">
+          {configFile?.exists && (
+            <div className="modal-field">
+              <span className="modal-hint">
+                Values set in {configFile.path} override these settings
+                {configFile.overrides.length > 0 ? `: ${configFile.overrides.join(", ")}` : "."}
+              </span>
+              {configFile.error && (
+                <span className="modal-hint">The last change was not applied: {configFile.error}</span>
+              )}
+            </div>
+          )}
+          <div className="modal-field">
+            <label className="modal-label">Analysis Backend</label>
+            <select
+              className="input"
+              value={provider}
+              onChange={(e) => setProvider(e.target.value)}
+              style={{ width: "100%" }}
+            >
+              <option value="codex">Codex CLI</option>
+              <option value="claude">Claude Code CLI</option>
+              <option value="openai">OpenAI API</option>
+            </select>
+          </div>
+          {provider === "openai" && (
+            <div className="modal-field">
+              <label className="modal-label">OpenAI API Key{openAiSecret.stored ? " (stored in keyring)" : ""}</label>
+              <input
+                className="input"
+                type="password"
+                placeholder={openAiSecret.stored ? "leave empty to keep current key" : "sk-..."}
+                value={apiKey}
+                onChange={(e) => setApiKey(e.target.value)}
+                style={{ width: "100%" }}
+              />
+              {openAiSecret.stored && (
+                <div style={{ display: "flex", gap: 8 }}>
+                  <button type="button" className="btn btn-ghost" onClick={openAiSecret.remove}>
+                    Remove Key
+                  </button>
+                  <button
+                    type="button"
+                    className="btn btn-ghost"
+                    onClick={openAiSecret.test}
+                    disabled={openAiSecret.testing}
+                  >
+                    {openAiSecret.testing ? "Testing..." : "Test Key"}
+                  </button>
+                </div>
+              )}
+              {openAiSecret.lastTest && <span className="modal-hint">{openAiSecret.lastTest.message}</span>}
+            </div>
+          )}
+          <div className="modal-field">
+            <label className="modal-label">GitHub Token (optional, stored in keyring)</label>
+            <input
+              className="input"
+              type="password"
+              placeholder="empty = keep the stored token, or use gh auth login"
+              value={githubToken}
+              onChange={(e) => setGithubToken(e.target.value)}
+              style={{ width: "100%" }}
+            />
+            <span className="modal-hint">Passed to gh in place of its own login.</span>
+            <div style={{ display: "flex", gap: 8 }}>
+              <button type="button" className="btn btn-ghost" onClick={githubSecret.remove}>
+                Remove Token
+              </button>
+              <button
+                type="button"
+                className="btn btn-ghost"
+                onClick={githubSecret.test}
+                disabled={githubSecret.testing}
+              >
+                {githubSecret.testing ? "Testing..." : "Test Token"}
+              </button>
+            </div>
+            {githubSecret.lastTest && <span className="modal-hint">{githubSecret.lastTest.message}</span>}
+          </div>
           <div className="modal-field">
-            <label className="modal-label">Codex Model</label>
+            <label className="modal-label">Model</label>
             <input
               className="input"
               placeholder="empty = use config default"
@@ -40,6 +295,51 @@ export function SettingsModal({ initialModel, initialLang, force, onSave, onClos
               style={{ width: "100%" }}
             />
           </div>
+          {provider === "codex" && (
+            <>
+              <div className="modal-field">
+                <label className="modal-label">Codex Profile</label>
+                <input
+                  className="input"
+                  placeholder="empty = none"
+                  value={runOptions.profile ?? ""}
+                  onChange={(e) => setRunOptions({ ...runOptions, profile: e.target.value })}
+                  style={{ width: "100%" }}
+                />
+              </div>
+              <div className="modal-field">
+                <label className="modal-label">Reasoning Effort</label>
+                <select
+                  className="input"
+                  value={runOptions.reasoningEffort ?? ""}
+                  onChange={(e) => setRunOptions({ ...runOptions, reasoningEffort: e.target.value })}
+                  style={{ width: "100%" }}
+                >
+                  <option value="">config default</option>
+                  {REASONING_EFFORTS.map((effort) => (
+                    <option key={effort} value={effort}>
+                      {effort}
+                    </option>
+                  ))}
+                </select>
+              </div>
+              <div className="modal-field">
+                <label className="modal-label">Approval Mode</label>
+                <select
+                  className="input"
+                  value={runOptions.approvalMode ?? ""}
+                  onChange={(e) => setRunOptions({ ...runOptions, approvalMode: e.target.value })}
+                  style={{ width: "100%" }}
+                >
+                  <option value="">full-auto (default)</option>
+                  <option value="untrusted">untrusted</option>
+                  <option value="on-failure">on-failure</option>
+                  <option value="on-request">on-request</option>
+                  <option value="never">never</option>
+                </select>
+              </div>
+            </>
+          )}
           <div className="modal-field">
             <label className="modal-label">Language</label>
             <input
@@ -50,6 +350,103 @@ export function SettingsModal({ initialModel, initialLang, force, onSave, onClos
               style={{ width: "100%" }}
             />
           </div>
+          <div className="modal-field">
+            <label className="modal-label">Message Language</label>
+            <select
+              className="input"
+              value={uiLang ?? savedUiLang}
+              onChange={(e) => setUiLang(e.target.value)}
+              style={{ width: "100%" }}
+            >
+              <option value="">Same as Language (English if unavailable)</option>
+              <option value="en">English</option>
+              <option value="ja">日本語</option>
+            </select>
+            <span className="modal-hint">Language of error and progress messages from the app.</span>
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">Timeout (minutes)</label>
+            <input
+              className="input"
+              type="number"
+              min={1}
+              placeholder="10"
+              value={timeoutMinutes}
+              onChange={(e) => setTimeoutMinutes(e.target.value)}
+              style={{ width: "100%" }}
+            />
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">
+              <input
+                type="checkbox"
+                checked={useGuidelines}
+                onChange={(e) => setUseGuidelines(e.target.checked)}
+              />{" "}
+              Use repo review guidelines (REVIEW.md / CONTRIBUTING.md)
+            </label>
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">
+              <input
+                type="checkbox"
+                checked={useRepoContext}
+                onChange={(e) => setUseRepoContext(e.target.checked)}
+              />{" "}
+              Full-repo context (checks out the PR so analysis can read surrounding code; slower)
+            </label>
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">Token Budget (estimated input tokens per analysis)</label>
+            <input
+              className="input"
+              type="number"
+              min={0}
+              placeholder="empty = no warning"
+              value={tokenBudget}
+              onChange={(e) => setTokenBudget(e.target.value)}
+              style={{ width: "100%" }}
+            />
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">Cost per 1M tokens (USD, for estimates)</label>
+            <input
+              className="input"
+              type="number"
+              min={0}
+              step="0.01"
+              placeholder="empty = don't show cost"
+              value={costPerMTokens}
+              onChange={(e) => setCostPerMTokens(e.target.value)}
+              style={{ width: "100%" }}
+            />
+            {usage.entries.length > 0 && (
+              <span className="modal-hint">
+                Usage: {usage.entries.length} run(s), ~{usage.totalTokens.toLocaleString()} tokens
+                {Number.parseFloat(costPerMTokens) > 0 &&
+                  ` (~$${((usage.totalTokens / 1e6) * Number.parseFloat(costPerMTokens)).toFixed(2)})`}
+                {usage.unreported > 0 && `, ${usage.unreported} estimated`}
+              </span>
+            )}
+          </div>
+          {usage.stats && (
+            <div className="modal-field">
+              <label className="modal-label">Usage stats (recorded on this machine only)</label>
+              <span className="modal-hint">
+                {usage.stats.analysesRun} analyses run
+                {usage.stats.cacheHitRate != null &&
+                  `, ${Math.round(usage.stats.cacheHitRate * 100)}% of cache lookups were hits`}
+                {usage.stats.reviews.reviewedPrs > 0 &&
+                  `; ${usage.stats.reviews.reviewedPrs} PR(s) reviewed, ` +
+                    `median ${Math.round(usage.stats.reviews.medianSecs / 60)} min of active review`}
+              </span>
+              {usage.stats.models.map((m) => (
+                <span key={`${m.provider}:${m.model}`} className="modal-hint">
+                  {m.provider} {m.model || "(default model)"}: {m.runs} run(s), {m.averageSecs.toFixed(1)}s on average
+                </span>
+              ))}
+            </div>
+          )}
           <div className="modal-field">
             <label className="modal-label">Cache{cacheSize != null ? ` (${cacheSize})` : ""}</label>
             <button
@@ -62,6 +459,219 @@ export function SettingsModal({ initialModel, initialLang, force, onSave, onClos
               {clearing ? "Clearing..." : "Clear Cache"}
             </button>
           </div>
+          <div className="modal-field">
+            <label className="modal-label">
+              PR checkouts{workspaceSize != null ? ` (${workspaceSize})` : ""}
+            </label>
+            {workspaces.map((w) => (
+              <span key={w.path} className="modal-hint">
+                {w.repo}#{w.prNumber} ({(w.sizeBytes / (1024 * 1024)).toFixed(1)} MB
+                {w.clonePath ? `, worktree of ${w.clonePath}` : ""}){" "}
+                <button
+                  type="button"
+                  className="btn"
+                  onClick={() => removeWorkspace(w.repo, w.prNumber)}
+                  disabled={removing}
+                >
+                  Remove
+                </button>
+              </span>
+            ))}
+            <button
+              type="button"
+              className="btn btn-accent"
+              onClick={removeAllWorkspaces}
+              disabled={removing || workspaces.length === 0}
+              style={{ alignSelf: "flex-start" }}
+            >
+              {removing ? "Removing..." : "Remove All Checkouts"}
+            </button>
+            <span className="modal-hint">
+              Set a repo's local clone in its repo settings to check PRs out as worktrees of it.
+            </span>
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">Editor command</label>
+            <input
+              className="input"
+              type="text"
+              placeholder="code -g {file}:{line}"
+              value={editorCommand ?? savedEditorCommand}
+              onChange={(e) => setEditorCommand(e.target.value)}
+              style={{ width: "100%" }}
+            />
+            <span className="modal-hint">
+              Used by Open on a hunk, e.g. <code>nvim +{"{line}"} {"{file}"}</code> or{" "}
+              <code>zed {"{file}:{line}"}</code>. Files open from the PR's checkout, or the repo's clone when it has
+              none.
+            </span>
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">Prefetch diffs of the first N listed PRs</label>
+            <input
+              className="input"
+              type="number"
+              min={0}
+              placeholder="empty = don't prefetch"
+              value={prefetchDiffs}
+              onChange={(e) => setPrefetchDiffs(e.target.value)}
+              style={{ width: "100%" }}
+            />
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">Max cache size (MB)</label>
+            <input
+              className="input"
+              type="number"
+              min={0}
+              placeholder="empty = unlimited"
+              value={cacheLimit ?? (cacheLimitMb ? String(cacheLimitMb) : "")}
+              onChange={(e) => setCacheLimit(e.target.value)}
+              style={{ width: "100%" }}
+            />
+            <span className="modal-hint">Least recently used entries are evicted above this size.</span>
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">
+              <input
+                type="checkbox"
+                checked={encrypt ?? encryptCache}
+                onChange={(e) => setEncrypt(e.target.checked)}
+              />{" "}
+              Encrypt cached diffs and analyses at rest (key stored in the OS keyring)
+            </label>
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">
+              <input
+                type="checkbox"
+                checked={keepRunning ?? tray.status?.keepRunning ?? false}
+                onChange={(e) => setKeepRunning(e.target.checked)}
+                disabled={tray.status == null}
+              />{" "}
+              Keep running in the tray when the window is closed
+            </label>
+            <span className="modal-hint">
+              The tray shows how many reviews are requested of you in the last repo whose PRs you listed
+              {tray.status?.repo ? ` (${tray.status.repo}: ${tray.status.pending.length})` : ""}; pick one to open it.
+            </span>
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">Desktop notifications</label>
+            {(
+              [
+                ["analysesDone", "Background analyses of the review queue finish"],
+                ["newPushes", "New commits land on the open PR"],
+                ["ciFailures", "CI turns red on a PR you have started reviewing"],
+                ["onlyWhenUnfocused", "Only while prvw is in the background"],
+              ] as const
+            ).map(([key, label]) => (
+              <label key={key} className="modal-label">
+                <input
+                  type="checkbox"
+                  checked={(notifications ?? savedNotifications)?.[key] ?? true}
+                  onChange={(e) => toggleNotification(key, e.target.checked)}
+                  disabled={savedNotifications == null}
+                />{" "}
+                {label}
+              </label>
+            ))}
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">
+              <input
+                type="checkbox"
+                checked={errorReporting ?? savedErrorReporting}
+                onChange={(e) => setErrorReporting(e.target.checked)}
+              />{" "}
+              Save crash and error reports on this machine ({errorReports.reports.length} saved)
+            </label>
+            <input
+              className="input"
+              type="text"
+              placeholder="/path/to/prvw-reports.json"
+              value={reportsPath}
+              onChange={(e) => setReportsPath(e.target.value)}
+              style={{ width: "100%" }}
+            />
+            <div style={{ display: "flex", gap: 8 }}>
+              <button
+                type="button"
+                className="btn btn-ghost"
+                onClick={() =>
+                  errorReports
+                    .exportTo(reportsPath)
+                    .then((n) => alert(`Exported ${n} report(s).`))
+                    .catch((e) => alert(`Failed to export reports: ${errorMessage(e)}`))
+                }
+                disabled={errorReports.reports.length === 0 || !reportsPath.trim()}
+              >
+                Export
+              </button>
+              <button
+                type="button"
+                className="btn btn-ghost"
+                onClick={() =>
+                  errorReports
+                    .submit(errorReports.reports[0].id)
+                    .catch((e) => alert(`Failed to open the report: ${errorMessage(e)}`))
+                }
+                disabled={errorReports.reports.length === 0}
+              >
+                Report Latest on GitHub
+              </button>
+              <button
+                type="button"
+                className="btn btn-ghost"
+                onClick={() => errorReports.clear().catch(() => {})}
+                disabled={errorReports.reports.length === 0}
+              >
+                Delete All
+              </button>
+            </div>
+            <span className="modal-hint">
+              Off by default. Reports hold the error message (without diff lines), app version and OS; nothing leaves
+              this machine unless you export one or submit the prefilled GitHub issue.
+            </span>
+          </div>
+          <div className="modal-field">
+            <label className="modal-label">
+              Editor API{apiServer.status?.running ? ` (http://127.0.0.1:${apiServer.status.port})` : ""}
+            </label>
+            <input
+              className="input"
+              type="number"
+              min={1}
+              max={65535}
+              placeholder={`port (default ${apiServer.status?.port ?? 7733})`}
+              value={apiPort}
+              onChange={(e) => setApiPort(e.target.value)}
+              disabled={apiServer.status?.running}
+              style={{ width: "100%" }}
+            />
+            <button
+              type="button"
+              className="btn btn-accent"
+              onClick={() => {
+                const port = Number.parseInt(apiPort, 10);
+                if (apiServer.status?.running) apiServer.stop();
+                else apiServer.start(port > 0 ? port : null);
+              }}
+              disabled={apiServer.busy || apiServer.status == null}
+              style={{ alignSelf: "flex-start" }}
+            >
+              {apiServer.status?.running ? "Stop" : "Start"}
+            </button>
+            {apiServer.status?.running && (
+              <span className="modal-hint">
+                Token: <code>{apiServer.status.token}</code>
+              </span>
+            )}
+            <span className="modal-hint">
+              Lets editor extensions on this machine list PRs, read intent groups and mark them reviewed. Only
+              127.0.0.1 is served and requests need the token; it stays on across launches until stopped.
+            </span>
+          </div>
         </div>
         <div className="modal-footer">
           {!force && (
@@ -1,6 +1,7 @@
 import { useState } from "react";
 import { createPortal } from "react-dom";
 import Markdown from "react-markdown";
+import { checklistItemId } from "../constants";
 import { useTruncationTooltip } from "../hooks/useTruncationTooltip";
 import type { AnalysisResult, IntentGroup, PrListItem } from "../types";
 
@@ -8,11 +9,24 @@ interface Props {
   selectedPr: PrListItem | null;
   analysis: AnalysisResult | null;
   selectedGroup: IntentGroup | null;
+  /** Checked checklist item ids. */
+  checkedItems: Set<string>;
+  onToggleChecklistItem: (itemId: string) => void;
   codexLog: string;
   onOpenPr: () => void;
+  onPostSummary: () => void;
 }
 
-export function SummaryPane({ selectedPr, analysis, selectedGroup, codexLog, onOpenPr }: Props) {
+export function SummaryPane({
+  selectedPr,
+  analysis,
+  selectedGroup,
+  checkedItems,
+  onToggleChecklistItem,
+  codexLog,
+  onOpenPr,
+  onPostSummary,
+}: Props) {
   const [logOpen, setLogOpen] = useState(false);
   const { tooltip, ref: titleRef, onMouseEnter, onMouseLeave } = useTruncationTooltip();
 
@@ -39,7 +53,12 @@ export function SummaryPane({ selectedPr, analysis, selectedGroup, codexLog, onO
         <div className="summary-content">
           {/* ── Summary ── */}
           <div className="summary-card">
-            <div className="summary-card-header">Summary</div>
+            <div className="summary-card-header">
+              Summary
+              <button type="button" className="btn-mini" onClick={onPostSummary} style={{ float: "right" }}>
+                Post to PR
+              </button>
+            </div>
             <div className="summary-card-body">
               <Markdown>{analysis.overallSummary}</Markdown>
             </div>
@@ -55,13 +74,41 @@ export function SummaryPane({ selectedPr, analysis, selectedGroup, codexLog, onO
                     <h4>Rationale</h4>
                     <Markdown>{selectedGroup.rationale}</Markdown>
                   </section>
+                  <section>
+                    <h4>
+                      Risk — {selectedGroup.risk} ({selectedGroup.riskScore ?? 0}/100)
+                    </h4>
+                    {selectedGroup.riskEvidence?.length > 0 ? (
+                      <ul>
+                        {selectedGroup.riskEvidence.map((ev, i) => (
+                          <li key={i}>
+                            <code>{ev.hunkId}</code> {ev.reason}
+                          </li>
+                        ))}
+                      </ul>
+                    ) : (
+                      <p className="hint">No evidence provided.</p>
+                    )}
+                  </section>
                   {selectedGroup.reviewerChecklist.length > 0 && (
                     <section>
                       <h4>Reviewer Checklist</h4>
-                      <ul>
-                        {selectedGroup.reviewerChecklist.map((c, i) => (
-                          <li key={i}>{c}</li>
-                        ))}
+                      <ul className="reviewer-checklist">
+                        {selectedGroup.reviewerChecklist.map((c) => {
+                          const id = checklistItemId(selectedGroup.id, c);
+                          return (
+                            <li key={id}>
+                              <label>
+                                <input
+                                  type="checkbox"
+                                  checked={checkedItems.has(id)}
+                                  onChange={() => onToggleChecklistItem(id)}
+                                />{" "}
+                                {c}
+                              </label>
+                            </li>
+                          );
+                        })}
                       </ul>
                     </section>
                   )}
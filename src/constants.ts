@@ -1 +1,13 @@
 export const UNASSIGNED_GROUP_ID = "__unassigned";
+
+/** Id of a reviewer checklist item of a group, as used by the review session: 32-bit FNV-1a of the trimmed text, like `session::checklist_item_id`. */
+export function checklistItemId(groupId: string, text: string): string {
+  let hash = 0x811c9dc5;
+  for (const byte of new TextEncoder().encode(text.trim())) {
+    hash = Math.imul(hash ^ byte, 0x01000193);
+  }
+  return `${groupId}:checklist:${(hash >>> 0).toString(16).padStart(8, "0")}`;
+}
+
+/** Values accepted by Codex for `model_reasoning_effort`. */
+export const REASONING_EFFORTS = ["minimal", "low", "medium", "high"];
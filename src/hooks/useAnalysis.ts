@@ -1,23 +1,83 @@
 import { useRef, useState } from "react";
-import type { AnalysisResult, Hunk, IntentGroup } from "../types";
+import type { AnalysisResult, Hunk, HunkSummary, IntentGroup, RepoContext, RunOptions } from "../types";
 import { analyzeIntents, refineGroupApi } from "./useCodexApi";
+import { getReviewGuidelinesApi } from "./useReviewApi";
+import { newOpId } from "./useOperations";
+import { estimateTokensApi } from "./useUsageLedger";
+import { errorMessage } from "../utils/prvwError";
 
 interface UseAnalysisOptions {
   hunks: Hunk[];
+  /** Backend review state holding `hunks`, sent instead of the hunks when set. */
+  sessionId: string | null;
+  repo: string;
+  prNumber: number | null;
   prBody: string | null;
+  hunkSummaries: HunkSummary[];
   codexModel: string;
   lang: string;
+  codexTimeout: number;
+  provider: string;
+  useGuidelines: boolean;
+  useRepoContext: boolean;
+  runOptions: RunOptions;
+  tokenBudget: number;
+  costPerMTokens: number;
   setError: (error: string | null) => void;
   setLoading: (msg: string | null) => void;
 }
 
-export function useAnalysis({ hunks, prBody, codexModel, lang, setError, setLoading }: UseAnalysisOptions) {
+export function useAnalysis({
+  hunks,
+  sessionId,
+  repo,
+  prNumber,
+  prBody,
+  hunkSummaries,
+  codexModel,
+  lang,
+  codexTimeout,
+  provider,
+  useGuidelines,
+  useRepoContext,
+  runOptions,
+  tokenBudget,
+  costPerMTokens,
+  setError,
+  setLoading,
+}: UseAnalysisOptions) {
   const [analysis, setAnalysis] = useState<AnalysisResult | null>(null);
   const [codexLog, setCodexLog] = useState<string>("");
   const [fromCache, setFromCache] = useState(false);
   const requestIdRef = useRef(0);
+  const guidelinesRef = useRef<{ repo: string; text: string | null } | null>(null);
 
-  async function runAnalysis(force?: boolean) {
+  const repoContext: RepoContext | null =
+    useRepoContext && repo.trim() && prNumber != null ? { repo: repo.trim(), prNumber } : null;
+  const cacheOwner: RepoContext | null = repo.trim() && prNumber != null ? { repo: repo.trim(), prNumber } : null;
+
+  // Review guidelines are fetched once per repo and reused for analysis and refine runs.
+  async function loadGuidelines(): Promise<string | null> {
+    if (!useGuidelines || !repo.trim()) return null;
+    if (guidelinesRef.current?.repo === repo) return guidelinesRef.current.text;
+    const text = await getReviewGuidelinesApi(repo);
+    guidelinesRef.current = { repo, text };
+    return text;
+  }
+
+  // Asks before sending a payload whose estimate exceeds the budget; true means go ahead.
+  async function confirmBudget(): Promise<boolean> {
+    if (tokenBudget <= 0) return true;
+    const estimate = await estimateTokensApi(hunks, tokenBudget);
+    if (!estimate.overBudget) return true;
+    const cost = costPerMTokens > 0 ? ` (~$${((estimate.estimatedTokens / 1e6) * costPerMTokens).toFixed(2)})` : "";
+    return window.confirm(
+      `This PR is estimated at ~${estimate.estimatedTokens.toLocaleString()} input tokens${cost}, ` +
+        `over your budget of ${tokenBudget.toLocaleString()}. Run the analysis anyway?`,
+    );
+  }
+
+  async function runAnalysis(force?: boolean, overrides?: RunOptions) {
     const id = ++requestIdRef.current;
     setError(null);
     setCodexLog("");
@@ -26,6 +86,12 @@ export function useAnalysis({ hunks, prBody, codexModel, lang, setError, setLoad
       setError("No hunks to analyze. Select a PR first.");
       return;
     }
+    try {
+      if (!(await confirmBudget())) return;
+    } catch (e) {
+      setError(errorMessage(e));
+      return;
+    }
 
     setLoading(
       force
@@ -33,14 +99,30 @@ export function useAnalysis({ hunks, prBody, codexModel, lang, setError, setLoad
         : "Running intent analysis with Codex... (this may take a minute)",
     );
     try {
-      const res = await analyzeIntents(hunks, codexModel, lang, force, prBody);
+      const guidelines = await loadGuidelines();
+      const res = await analyzeIntents(
+        hunks,
+        codexModel,
+        lang,
+        force,
+        prBody,
+        codexTimeout,
+        hunkSummaries,
+        provider,
+        guidelines,
+        repoContext,
+        { ...runOptions, ...overrides },
+        cacheOwner,
+        sessionId,
+        newOpId("analyze"),
+      );
       if (id !== requestIdRef.current) return;
       setAnalysis(res.result);
       setCodexLog(res.codexLog);
       setFromCache(res.fromCache);
     } catch (e) {
       if (id !== requestIdRef.current) return;
-      setError(String(e));
+      setError(errorMessage(e));
     } finally {
       if (id === requestIdRef.current) {
         setLoading(null);
@@ -52,7 +134,22 @@ export function useAnalysis({ hunks, prBody, codexModel, lang, setError, setLoad
     setError(null);
     setLoading(`Refining "${group.title}"...`);
     try {
-      const res = await refineGroupApi(hunks, group, codexModel, lang, force);
+      const guidelines = await loadGuidelines();
+      const res = await refineGroupApi(
+        hunks,
+        group,
+        codexModel,
+        lang,
+        force,
+        codexTimeout,
+        provider,
+        guidelines,
+        repoContext,
+        runOptions,
+        cacheOwner,
+        sessionId,
+        newOpId("refine"),
+      );
 
       // Replace the refined group with its sub-groups using functional setState
       setAnalysis((prev) => {
@@ -70,12 +167,20 @@ export function useAnalysis({ hunks, prBody, codexModel, lang, setError, setLoad
       setCodexLog((prev) => `${prev}\n${res.codexLog}`);
       if (!res.fromCache) setFromCache(false);
     } catch (e) {
-      setError(String(e));
+      setError(errorMessage(e));
     } finally {
       setLoading(null);
     }
   }
 
+  /** Install an analysis obtained elsewhere (e.g. imported from a teammate) as the current one. */
+  function installAnalysis(result: AnalysisResult, log = "") {
+    requestIdRef.current++;
+    setAnalysis(result);
+    setCodexLog(log);
+    setFromCache(false);
+  }
+
   function resetAnalysis() {
     requestIdRef.current++;
     setAnalysis(null);
@@ -83,5 +188,5 @@ export function useAnalysis({ hunks, prBody, codexModel, lang, setError, setLoad
     setFromCache(false);
   }
 
-  return { analysis, codexLog, fromCache, runAnalysis, refineGroup, resetAnalysis };
+  return { analysis, codexLog, fromCache, runAnalysis, refineGroup, installAnalysis, resetAnalysis };
 }
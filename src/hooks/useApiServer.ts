@@ -0,0 +1,47 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import type { ApiServerStatus } from "../types";
+import { errorMessage } from "../utils/prvwError";
+
+export async function getApiServerStatusApi(): Promise<ApiServerStatus> {
+  return invoke<ApiServerStatus>("get_api_server_status");
+}
+
+/** Serve the editor API on 127.0.0.1; it stays enabled across launches until stopped. */
+export async function startApiServerApi(port: number | null): Promise<ApiServerStatus> {
+  return invoke<ApiServerStatus>("start_api_server", { port });
+}
+
+export async function stopApiServerApi(): Promise<ApiServerStatus> {
+  return invoke<ApiServerStatus>("stop_api_server");
+}
+
+/** The localhost API for editor extensions, for turning it on and off from Settings. */
+export function useApiServer() {
+  const [status, setStatus] = useState<ApiServerStatus | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    getApiServerStatusApi()
+      .then(setStatus)
+      .catch(() => {});
+  }, []);
+
+  async function run(action: () => Promise<ApiServerStatus>) {
+    setBusy(true);
+    try {
+      setStatus(await action());
+    } catch (e) {
+      alert(`Editor API: ${errorMessage(e)}`);
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  return {
+    status,
+    busy,
+    start: (port: number | null) => run(() => startApiServerApi(port)),
+    stop: () => run(stopApiServerApi),
+  };
+}
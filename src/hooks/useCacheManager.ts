@@ -1,27 +1,76 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useEffect, useState } from "react";
+import { errorMessage } from "../utils/prvwError";
+
+/** Cache writes happen in the background; failures arrive as `cache-write-failed` events. */
+export function useCacheWriteErrors(onError: (message: string) => void) {
+  // biome-ignore lint/correctness/useExhaustiveDependencies: subscribe once on mount
+  useEffect(() => {
+    const unlisten = listen<string>("cache-write-failed", (e) => onError(e.payload));
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+}
 
 export function useCacheManager() {
   const [cacheSize, setCacheSize] = useState<string | null>(null);
   const [clearing, setClearing] = useState(false);
+  /** Maximum cache size in MB; 0 means unlimited. */
+  const [cacheLimitMb, setCacheLimitMb] = useState<number | null>(null);
+  const [encryptCache, setEncryptCache] = useState(false);
 
   useEffect(() => {
     invoke<string>("get_cache_size")
       .then(setCacheSize)
       .catch(() => {});
+    invoke<number>("get_cache_limit")
+      .then(setCacheLimitMb)
+      .catch(() => {});
+    invoke<boolean>("get_cache_encryption")
+      .then(setEncryptCache)
+      .catch(() => {});
   }, []);
 
+  /** Drop the cached diffs, analyses, and refinements of one PR, or of the whole repo. */
+  async function invalidateCache(repo: string, prNumber?: number | null): Promise<number> {
+    const removed = await invoke<number>("invalidate_cache", { repo, prNumber: prNumber ?? null });
+    setCacheSize(await invoke<string>("get_cache_size"));
+    return removed;
+  }
+
+  async function saveCacheEncryption(enabled: boolean) {
+    await invoke("set_cache_encryption", { enabled });
+    setEncryptCache(enabled);
+  }
+
+  async function saveCacheLimit(maxMb: number) {
+    await invoke("set_cache_limit", { maxMb });
+    setCacheLimitMb(maxMb);
+    setCacheSize(await invoke<string>("get_cache_size"));
+  }
+
   async function clearCache() {
     setClearing(true);
     try {
       await invoke("clear_cache");
       setCacheSize("0 B");
     } catch (e) {
-      alert(`Failed to clear cache: ${e}`);
+      alert(`Failed to clear cache: ${errorMessage(e)}`);
     } finally {
       setClearing(false);
     }
   }
 
-  return { cacheSize, clearing, clearCache };
+  return {
+    cacheSize,
+    clearing,
+    clearCache,
+    invalidateCache,
+    cacheLimitMb,
+    saveCacheLimit,
+    encryptCache,
+    saveCacheEncryption,
+  };
 }
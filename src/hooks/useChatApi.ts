@@ -0,0 +1,36 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { AskPrResponse, ChatMessage, Hunk, RunOptions } from "../types";
+
+export async function askAboutPrApi(
+  repo: string,
+  prNumber: number,
+  hunks: Hunk[],
+  question: string,
+  model: string,
+  lang: string,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  opId?: string,
+): Promise<AskPrResponse> {
+  return invoke<AskPrResponse>("ask_about_pr", {
+    repo: repo.trim(),
+    prNumber,
+    hunksJson: JSON.stringify(hunks),
+    question,
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    opId: opId ?? null,
+  });
+}
+
+export async function getPrChatApi(repo: string, prNumber: number): Promise<ChatMessage[]> {
+  return invoke<ChatMessage[]>("get_pr_chat", { repo: repo.trim(), prNumber });
+}
+
+export async function clearPrChatApi(repo: string, prNumber: number): Promise<void> {
+  return invoke("clear_pr_chat", { repo: repo.trim(), prNumber });
+}
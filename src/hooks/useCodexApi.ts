@@ -1,5 +1,33 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { AnalysisResponse, ExplainResponse, Hunk, IntentGroup, RefineResponse } from "../types";
+import type {
+  AnalysisConstraints,
+  AnalysisDelta,
+  AnalysisPromptPreview,
+  AnalysisResponse,
+  AnalysisResult,
+  BreakingChangesResponse,
+  CodexRun,
+  CodexRunSummary,
+  ConsistencyResponse,
+  DraftCommentsResponse,
+  ExplainResponse,
+  GuidedRefineResponse,
+  Hunk,
+  HunkSummary,
+  IntentGroup,
+  ProviderInfo,
+  RepoContext,
+  RunOptions,
+  RefineResponse,
+  SplitPlanResponse,
+  SummarizeResponse,
+  TranslateResponse,
+} from "../types";
+
+/** Hunk arguments of an AI command: the backend review state when one is open, else the hunks. */
+function hunksArgs(hunks: Hunk[], sessionId?: string | null) {
+  return sessionId ? { sessionId, hunksJson: null } : { sessionId: null, hunksJson: JSON.stringify(hunks) };
+}
 
 export async function analyzeIntents(
   hunks: Hunk[],
@@ -7,13 +35,104 @@ export async function analyzeIntents(
   lang: string,
   force?: boolean,
   prBody?: string | null,
+  timeoutMinutes?: number,
+  hunkSummaries?: HunkSummary[],
+  provider?: string,
+  guidelines?: string | null,
+  repoContext?: RepoContext | null,
+  options?: RunOptions,
+  cacheOwner?: RepoContext | null,
+  sessionId?: string | null,
+  opId?: string,
 ): Promise<AnalysisResponse> {
   return invoke<AnalysisResponse>("analyze_intents_with_codex", {
+    ...hunksArgs(hunks, sessionId),
+    prBody: prBody || null,
+    hunkSummaries: hunkSummaries?.length ? hunkSummaries : null,
+    guidelines: guidelines || null,
+    repoContext: repoContext ?? null,
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    cacheOwner: cacheOwner ?? null,
+    opId: opId ?? null,
+  });
+}
+
+export async function adjustAnalysisApi(
+  hunks: Hunk[],
+  analysis: AnalysisResult,
+  constraints: AnalysisConstraints,
+  model: string,
+  lang: string,
+  force?: boolean,
+  prBody?: string | null,
+  timeoutMinutes?: number,
+  provider?: string,
+  guidelines?: string | null,
+  options?: RunOptions,
+  repo?: string | null,
+  opId?: string,
+): Promise<AnalysisResponse> {
+  return invoke<AnalysisResponse>("adjust_analysis", {
     hunksJson: JSON.stringify(hunks),
+    analysis,
+    constraints,
     prBody: prBody || null,
+    guidelines: guidelines || null,
     model: model.trim() || null,
     lang: lang.trim() || null,
     force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    repo: repo?.trim() || null,
+    opId: opId ?? null,
+  });
+}
+
+export async function previewAnalysisPromptApi(
+  hunks: Hunk[],
+  lang: string,
+  prBody?: string | null,
+  hunkSummaries?: HunkSummary[],
+  guidelines?: string | null,
+  repo?: string | null,
+): Promise<AnalysisPromptPreview> {
+  return invoke<AnalysisPromptPreview>("preview_analysis_prompt", {
+    hunksJson: JSON.stringify(hunks),
+    prBody: prBody || null,
+    hunkSummaries: hunkSummaries?.length ? hunkSummaries : null,
+    guidelines: guidelines || null,
+    lang: lang.trim() || null,
+    repo: repo?.trim() || null,
+  });
+}
+
+export async function translateAnalysisApi(
+  analysis: AnalysisResult,
+  lang: string,
+  model: string,
+  force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  repo?: string | null,
+  opId?: string,
+): Promise<TranslateResponse> {
+  return invoke<TranslateResponse>("translate_analysis", {
+    analysis,
+    lang: lang.trim(),
+    model: model.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    repo: repo?.trim() || null,
+    opId: opId ?? null,
   });
 }
 
@@ -23,8 +142,78 @@ export async function refineGroupApi(
   model: string,
   lang: string,
   force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  guidelines?: string | null,
+  repoContext?: RepoContext | null,
+  options?: RunOptions,
+  cacheOwner?: RepoContext | null,
+  sessionId?: string | null,
+  opId?: string,
 ): Promise<RefineResponse> {
   return invoke<RefineResponse>("refine_group", {
+    ...hunksArgs(hunks, sessionId),
+    groupId: group.id,
+    groupTitle: group.title,
+    hunkIds: group.hunkIds,
+    guidelines: guidelines || null,
+    repoContext: repoContext ?? null,
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    cacheOwner: cacheOwner ?? null,
+    opId: opId ?? null,
+  });
+}
+
+export async function refineGroupWithGuidanceApi(
+  hunks: Hunk[],
+  analysis: AnalysisResult,
+  groupId: string,
+  guidance: string,
+  model: string,
+  lang: string,
+  force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  guidelines?: string | null,
+  repoContext?: RepoContext | null,
+  options?: RunOptions,
+  opId?: string,
+): Promise<GuidedRefineResponse> {
+  return invoke<GuidedRefineResponse>("refine_group_with_guidance", {
+    hunksJson: JSON.stringify(hunks),
+    analysis,
+    groupId,
+    guidance,
+    guidelines: guidelines || null,
+    repoContext: repoContext ?? null,
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    opId: opId ?? null,
+  });
+}
+
+export async function draftCommentsApi(
+  hunks: Hunk[],
+  group: IntentGroup,
+  model: string,
+  lang: string,
+  force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  repo?: string | null,
+  opId?: string,
+): Promise<DraftCommentsResponse> {
+  return invoke<DraftCommentsResponse>("draft_comments_for_group", {
     hunksJson: JSON.stringify(hunks),
     groupId: group.id,
     groupTitle: group.title,
@@ -32,6 +221,117 @@ export async function refineGroupApi(
     model: model.trim() || null,
     lang: lang.trim() || null,
     force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    repo: repo?.trim() || null,
+    opId: opId ?? null,
+  });
+}
+
+export async function suggestSplitPlanApi(
+  hunks: Hunk[],
+  model: string,
+  lang: string,
+  force?: boolean,
+  prBody?: string | null,
+  analysis?: AnalysisResult | null,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  repo?: string | null,
+  opId?: string,
+): Promise<SplitPlanResponse> {
+  return invoke<SplitPlanResponse>("suggest_split_plan", {
+    hunksJson: JSON.stringify(hunks),
+    prBody: prBody || null,
+    analysis: analysis ?? null,
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    repo: repo?.trim() || null,
+    opId: opId ?? null,
+  });
+}
+
+export async function detectBreakingChangesApi(
+  hunks: Hunk[],
+  analysis: AnalysisResult,
+  model: string,
+  lang: string,
+  force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  repo?: string | null,
+  opId?: string,
+): Promise<BreakingChangesResponse> {
+  return invoke<BreakingChangesResponse>("detect_breaking_changes", {
+    hunksJson: JSON.stringify(hunks),
+    analysis,
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    repo: repo?.trim() || null,
+    opId: opId ?? null,
+  });
+}
+
+export async function checkPrConsistencyApi(
+  prTitle: string,
+  prBody: string | undefined,
+  analysis: AnalysisResult,
+  model: string,
+  lang: string,
+  force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  repo?: string | null,
+  opId?: string,
+): Promise<ConsistencyResponse> {
+  return invoke<ConsistencyResponse>("check_pr_consistency", {
+    prTitle,
+    prBody: prBody ?? null,
+    analysis,
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    repo: repo?.trim() || null,
+    opId: opId ?? null,
+  });
+}
+
+export async function summarizeHunksApi(
+  hunks: Hunk[],
+  model: string,
+  lang: string,
+  force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  repo?: string | null,
+  opId?: string,
+): Promise<SummarizeResponse> {
+  return invoke<SummarizeResponse>("summarize_hunks", {
+    hunksJson: JSON.stringify(hunks),
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    repo: repo?.trim() || null,
+    opId: opId ?? null,
   });
 }
 
@@ -40,6 +340,11 @@ export async function explainHunkApi(
   model: string,
   lang: string,
   force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  repo?: string | null,
+  opId?: string,
 ): Promise<ExplainResponse> {
   return invoke<ExplainResponse>("explain_hunk", {
     hunkJson: JSON.stringify(hunk),
@@ -47,6 +352,11 @@ export async function explainHunkApi(
     model: model.trim() || null,
     lang: lang.trim() || null,
     force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    repo: repo?.trim() || null,
+    opId: opId ?? null,
   });
 }
 
@@ -56,6 +366,11 @@ export async function askAboutHunkApi(
   context: string,
   model: string,
   lang: string,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  repo?: string | null,
+  opId?: string,
 ): Promise<ExplainResponse> {
   return invoke<ExplainResponse>("ask_about_hunk", {
     hunkJson: JSON.stringify(hunk),
@@ -64,5 +379,36 @@ export async function askAboutHunkApi(
     context,
     model: model.trim() || null,
     lang: lang.trim() || null,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    repo: repo?.trim() || null,
+    opId: opId ?? null,
+  });
+}
+
+export async function diffAnalysesApi(
+  oldHunks: Hunk[],
+  oldAnalysis: AnalysisResult,
+  newHunks: Hunk[],
+  newAnalysis: AnalysisResult,
+): Promise<AnalysisDelta> {
+  return invoke<AnalysisDelta>("diff_analyses", {
+    oldHunksJson: JSON.stringify(oldHunks),
+    oldAnalysis,
+    newHunksJson: JSON.stringify(newHunks),
+    newAnalysis,
   });
 }
+
+export async function listProvidersApi(): Promise<ProviderInfo[]> {
+  return invoke<ProviderInfo[]>("list_providers");
+}
+
+export async function listCodexRunsApi(limit?: number): Promise<CodexRunSummary[]> {
+  return invoke<CodexRunSummary[]>("list_codex_runs", { limit: limit ?? null });
+}
+
+export async function getCodexRunApi(id: string): Promise<CodexRun> {
+  return invoke<CodexRun>("get_codex_run", { id });
+}
@@ -0,0 +1,26 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import type { ConfigStatus } from "../types";
+
+/** Where `prvw.toml` is read from and whether it loaded; its values override these settings. */
+export async function getConfigStatusApi(): Promise<ConfigStatus> {
+  return invoke<ConfigStatus>("get_config_status");
+}
+
+/** Status of `prvw.toml`, updated when the file changes on disk (`config-reloaded`). */
+export function useConfigFile() {
+  const [status, setStatus] = useState<ConfigStatus | null>(null);
+
+  useEffect(() => {
+    getConfigStatusApi()
+      .then(setStatus)
+      .catch(() => {});
+    const unlisten = listen<ConfigStatus>("config-reloaded", (e) => setStatus(e.payload));
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  return status;
+}
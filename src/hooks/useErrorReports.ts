@@ -0,0 +1,52 @@
+import { invoke } from "@tauri-apps/api/core";
+import { openUrl } from "@tauri-apps/plugin-opener";
+import { useEffect, useState } from "react";
+import type { ErrorReport } from "../types";
+
+export async function listErrorReportsApi(): Promise<ErrorReport[]> {
+  return invoke<ErrorReport[]>("list_error_reports");
+}
+
+/** Write the reports (or those in `ids`) to `path` as JSON; returns how many. */
+export async function exportErrorReportsApi(path: string, ids?: string[]): Promise<number> {
+  return invoke<number>("export_error_reports", { path, ids: ids ?? null });
+}
+
+/** URL of a GitHub issue prefilled with the report; nothing is sent until the user submits it there. */
+export async function submitErrorReportApi(id: string): Promise<string> {
+  return invoke<string>("submit_error_report", { id });
+}
+
+export async function deleteErrorReportsApi(ids?: string[]): Promise<number> {
+  return invoke<number>("delete_error_reports", { ids: ids ?? null });
+}
+
+/** Locally saved crash and error reports, for Settings. */
+export function useErrorReports() {
+  const [reports, setReports] = useState<ErrorReport[]>([]);
+
+  async function refresh() {
+    setReports(await listErrorReportsApi());
+  }
+
+  useEffect(() => {
+    listErrorReportsApi()
+      .then(setReports)
+      .catch(() => {});
+  }, []);
+
+  async function exportTo(path: string): Promise<number> {
+    return exportErrorReportsApi(path.trim());
+  }
+
+  async function submit(id: string) {
+    await openUrl(await submitErrorReportApi(id));
+  }
+
+  async function clear() {
+    await deleteErrorReportsApi();
+    await refresh();
+  }
+
+  return { reports, refresh, exportTo, submit, clear };
+}
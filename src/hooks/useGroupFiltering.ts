@@ -1,10 +1,49 @@
+import { listen } from "@tauri-apps/api/event";
 import { useEffect, useMemo, useState } from "react";
 import { UNASSIGNED_GROUP_ID } from "../constants";
-import type { AnalysisResult, Hunk, IntentGroup } from "../types";
+import type { AnalysisResult, Hunk, IntentGroup, ReviewMark, ReviewSession } from "../types";
+import { checkChecklistItemsApi, getSessionApi, updateSessionApi } from "./useReviewApi";
+import { errorMessage } from "../utils/prvwError";
 
-export function useGroupFiltering(hunks: Hunk[], analysis: AnalysisResult | null) {
+/** The PR whose review progress is persisted as a session. */
+export interface SessionPr {
+  repo: string;
+  prNumber: number;
+  headSha: string | null;
+}
+
+export function useGroupFiltering(hunks: Hunk[], analysis: AnalysisResult | null, sessionPr: SessionPr | null) {
   const [selectedGroupId, setSelectedGroupId] = useState<string | null>(null);
   const [reviewedGroups, setReviewedGroups] = useState<Set<string>>(new Set());
+  const [checkedItems, setCheckedItems] = useState<Set<string>>(new Set());
+
+  // Restore groups marked reviewed at the same head; marks from an older push no longer apply.
+  // Marks made from an editor through the local API arrive as `session-updated` events.
+  // biome-ignore lint/correctness/useExhaustiveDependencies: keyed on the PR identity, not the object
+  useEffect(() => {
+    if (!sessionPr) return;
+    let cancelled = false;
+    const apply = (session: ReviewSession) => {
+      const current = (marks: Record<string, ReviewMark>) =>
+        Object.entries(marks ?? {})
+          .filter(([, mark]) => !mark.headSha || !sessionPr.headSha || mark.headSha === sessionPr.headSha)
+          .map(([id]) => id);
+      setReviewedGroups(new Set(current(session.reviewedGroups)));
+      setCheckedItems(new Set(current(session.checkedItems)));
+    };
+    getSessionApi(sessionPr.repo, sessionPr.prNumber)
+      .then((session) => {
+        if (!cancelled) apply(session);
+      })
+      .catch(() => {});
+    const unlisten = listen<ReviewSession>("session-updated", (e) => {
+      if (e.payload.repo === sessionPr.repo && e.payload.prNumber === sessionPr.prNumber) apply(e.payload);
+    });
+    return () => {
+      cancelled = true;
+      unlisten.then((fn) => fn());
+    };
+  }, [sessionPr?.repo, sessionPr?.prNumber, sessionPr?.headSha]);
 
   // biome-ignore lint/correctness/useExhaustiveDependencies: selectedGroupId intentionally excluded to avoid overwriting user selection
   useEffect(() => {
@@ -34,17 +73,41 @@ export function useGroupFiltering(hunks: Hunk[], analysis: AnalysisResult | null
   }, [hunks, selectedGroup, selectedGroupId, analysis]);
 
   function toggleReviewed(groupId: string) {
+    const reviewed = !reviewedGroups.has(groupId);
     setReviewedGroups((prev) => {
       const next = new Set(prev);
-      if (next.has(groupId)) next.delete(groupId);
-      else next.add(groupId);
+      if (reviewed) next.add(groupId);
+      else next.delete(groupId);
+      return next;
+    });
+    if (sessionPr) {
+      updateSessionApi(sessionPr.repo, sessionPr.prNumber, {
+        headSha: sessionPr.headSha,
+        markGroups: reviewed ? [groupId] : [],
+        unmarkGroups: reviewed ? [] : [groupId],
+      }).catch((e) => console.warn(`Failed to save review session: ${errorMessage(e)}`));
+    }
+  }
+
+  function toggleChecklistItem(itemId: string) {
+    const checked = !checkedItems.has(itemId);
+    setCheckedItems((prev) => {
+      const next = new Set(prev);
+      if (checked) next.add(itemId);
+      else next.delete(itemId);
       return next;
     });
+    if (sessionPr) {
+      checkChecklistItemsApi(sessionPr.repo, sessionPr.prNumber, [itemId], checked, sessionPr.headSha).catch((e) =>
+        console.warn(`Failed to save review session: ${errorMessage(e)}`),
+      );
+    }
   }
 
   function resetFiltering() {
     setSelectedGroupId(null);
     setReviewedGroups(new Set());
+    setCheckedItems(new Set());
   }
 
   return {
@@ -54,6 +117,8 @@ export function useGroupFiltering(hunks: Hunk[], analysis: AnalysisResult | null
     displayedHunks,
     reviewedGroups,
     toggleReviewed,
+    checkedItems,
+    toggleChecklistItem,
     resetFiltering,
   };
 }
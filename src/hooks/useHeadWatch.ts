@@ -0,0 +1,57 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useRef } from "react";
+import type { HeadChange, HeadRefresh } from "../types";
+
+/** Poll the open PR's head commit; new pushes arrive as `pr-head-changed` events. */
+export async function watchPrHeadApi(repo: string, prNumber: number, headSha: string): Promise<void> {
+  return invoke<void>("watch_pr_head", { repo: repo.trim(), prNumber, headSha });
+}
+
+export async function unwatchPrHeadApi(): Promise<void> {
+  return invoke<void>("unwatch_pr_head");
+}
+
+/** Re-fetch the PR at `headSha` and carry the review of `sessionId` over to the new diff. */
+export async function refreshPrHeadApi(
+  repo: string,
+  prNumber: number,
+  sessionId: string,
+  headSha: string | null,
+  opId?: string,
+): Promise<HeadRefresh> {
+  return invoke<HeadRefresh>("refresh_pr_head", {
+    repo: repo.trim(),
+    prNumber,
+    sessionId,
+    headSha,
+    opId: opId ?? null,
+  });
+}
+
+/** Watches the head of the PR whose diff is loaded and reports pushes to it. */
+export function useHeadWatch(
+  repo: string,
+  prNumber: number | null,
+  headSha: string | null,
+  onChange: (change: HeadChange) => void,
+) {
+  const watching = prNumber != null && headSha != null && repo.trim() !== "";
+  const onChangeRef = useRef(onChange);
+  onChangeRef.current = onChange;
+
+  useEffect(() => {
+    if (!watching || prNumber == null || headSha == null) return;
+    watchPrHeadApi(repo, prNumber, headSha).catch(() => {});
+    return () => {
+      unwatchPrHeadApi().catch(() => {});
+    };
+  }, [watching, repo, prNumber, headSha]);
+
+  useEffect(() => {
+    const unlisten = listen<HeadChange>("pr-head-changed", (e) => onChangeRef.current(e.payload));
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+}
@@ -0,0 +1,57 @@
+import { useRef, useState } from "react";
+import type { Hunk, HunkSummary, RunOptions } from "../types";
+import { summarizeHunksApi } from "./useCodexApi";
+import { newOpId } from "./useOperations";
+import { errorMessage } from "../utils/prvwError";
+
+interface UseHunkSummariesOptions {
+  hunks: Hunk[];
+  repo: string;
+  codexModel: string;
+  lang: string;
+  codexTimeout: number;
+  provider: string;
+  runOptions: RunOptions;
+  setError: (error: string | null) => void;
+}
+
+export function useHunkSummaries({
+  hunks,
+  repo,
+  codexModel,
+  lang,
+  codexTimeout,
+  provider,
+  runOptions,
+  setError,
+}: UseHunkSummariesOptions) {
+  const [hunkSummaries, setHunkSummaries] = useState<HunkSummary[]>([]);
+  const [summarizing, setSummarizing] = useState(false);
+  const requestIdRef = useRef(0);
+
+  async function runSummaries(force?: boolean) {
+    if (hunks.length === 0) return;
+    const id = ++requestIdRef.current;
+    setSummarizing(true);
+    try {
+      const res = await summarizeHunksApi(hunks, codexModel, lang, force, codexTimeout, provider, runOptions, repo, newOpId("summarize"));
+      if (id !== requestIdRef.current) return;
+      setHunkSummaries(res.summaries);
+    } catch (e) {
+      if (id !== requestIdRef.current) return;
+      setError(errorMessage(e));
+    } finally {
+      if (id === requestIdRef.current) {
+        setSummarizing(false);
+      }
+    }
+  }
+
+  function resetSummaries() {
+    requestIdRef.current++;
+    setHunkSummaries([]);
+    setSummarizing(false);
+  }
+
+  return { hunkSummaries, summarizing, runSummaries, resetSummaries };
+}
@@ -0,0 +1,35 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect } from "react";
+import type { OpenPr } from "../types";
+
+/**
+ * PRs picked from the tray menu or opened through prvw:// links arrive as `open-pr` events; links
+ * that came in before the window was listening are handed over once it is.
+ */
+export function useOpenPrEvents(onOpen: (open: OpenPr) => void) {
+  // biome-ignore lint/correctness/useExhaustiveDependencies: subscribe once on mount
+  useEffect(() => {
+    const unlisten = listen<OpenPr>("open-pr", (e) => onOpen(e.payload));
+    unlisten
+      .then(() => invoke<OpenPr[]>("deep_links_ready"))
+      .then((pending) => {
+        for (const open of pending) onOpen(open);
+      })
+      .catch(() => {});
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+}
+
+/** Problems opening a prvw:// link (malformed, or the PR couldn't be fetched). */
+export function useDeepLinkErrors(onError: (message: string) => void) {
+  // biome-ignore lint/correctness/useExhaustiveDependencies: subscribe once on mount
+  useEffect(() => {
+    const unlisten = listen<string>("deep-link-failed", (e) => onError(e.payload));
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+}
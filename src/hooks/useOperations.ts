@@ -0,0 +1,53 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import type { OperationRecord } from "../types";
+
+/** Same cap as the backend's history. */
+const MAX_OPERATIONS = 50;
+
+/** Id to pass as `opId` to a long-running command so its progress events can be followed. */
+export function newOpId(kind: string): string {
+  return `${kind}-${crypto.randomUUID()}`;
+}
+
+export async function listOperationsApi(): Promise<OperationRecord[]> {
+  return invoke<OperationRecord[]>("list_operations");
+}
+
+/** Kill a running operation's subprocesses; it then fails with status "cancelled". */
+export async function cancelOperationApi(opId: string): Promise<boolean> {
+  return invoke<boolean>("cancel_operation", { opId });
+}
+
+/** Long-running commands of this session, kept current from `op-progress`/`op-done`/`op-error` events. */
+export function useOperations() {
+  const [operations, setOperations] = useState<OperationRecord[]>([]);
+
+  useEffect(() => {
+    let disposed = false;
+    const update = (record: OperationRecord) =>
+      setOperations((prev) => {
+        const index = prev.findIndex((op) => op.opId === record.opId);
+        if (index < 0) return [...prev, record].slice(-MAX_OPERATIONS);
+        return prev.map((op, i) => (i === index ? record : op));
+      });
+    const unlisteners = ["op-progress", "op-done", "op-error"].map((event) =>
+      listen<OperationRecord>(event, (e) => update(e.payload)),
+    );
+    listOperationsApi()
+      .then((list) => {
+        if (disposed) return;
+        // Events that arrived meanwhile are newer than the listed records.
+        setOperations((prev) => [...list.filter((op) => !prev.some((p) => p.opId === op.opId)), ...prev]);
+      })
+      .catch(() => {});
+    return () => {
+      disposed = true;
+      for (const unlisten of unlisteners) unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const running = operations.filter((op) => op.status === "running");
+  return { operations, running, current: running.at(-1) ?? null };
+}
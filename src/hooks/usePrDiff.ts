@@ -1,6 +1,10 @@
 import { invoke } from "@tauri-apps/api/core";
 import { useRef, useState } from "react";
-import type { Hunk, ParsedDiff, PrListItem } from "../types";
+import type { HeadRefresh, Hunk, PrListItem } from "../types";
+import { refreshPrHeadApi } from "./useHeadWatch";
+import { newOpId } from "./useOperations";
+import { closeReviewStateApi, openReviewStateApi } from "./useReviewApi";
+import { errorMessage } from "../utils/prvwError";
 
 interface UsePrDiffOptions {
   repo: string;
@@ -11,28 +15,48 @@ interface UsePrDiffOptions {
 export function usePrDiff({ repo, setError, setLoading }: UsePrDiffOptions) {
   const [selectedPr, setSelectedPr] = useState<PrListItem | null>(null);
   const [hunks, setHunks] = useState<Hunk[]>([]);
+  // Backend review state of the loaded diff; AI commands take its id instead of the hunks.
+  const [sessionId, setSessionId] = useState<string | null>(null);
   const requestIdRef = useRef(0);
 
-  async function selectPr(pr: PrListItem) {
+  function closeSession() {
+    if (sessionId) closeReviewStateApi(sessionId).catch(() => {});
+    setSessionId(null);
+  }
+
+  /** With `stackBaseSha` (the parent layer's head), only this stack layer's own changes are loaded. */
+  async function selectPr(pr: PrListItem, stackBaseSha?: string | null) {
     const id = ++requestIdRef.current;
     setError(null);
     setSelectedPr(pr);
+    closeSession();
     setLoading("Fetching diff...");
     try {
-      const diff = await invoke<string>("get_pr_diff", {
-        repo: repo.trim(),
-        prNumber: pr.number,
-        updatedAt: pr.updatedAt,
-      });
-      if (id !== requestIdRef.current) return;
-      const parsed = await invoke<ParsedDiff>("parse_diff", {
-        diffText: diff,
-      });
+      const diff =
+        stackBaseSha && pr.headRefOid
+          ? await invoke<string>("get_stack_layer_diff", {
+              repo: repo.trim(),
+              prNumber: pr.number,
+              baseSha: stackBaseSha,
+              headSha: pr.headRefOid,
+            })
+          : await invoke<string>("get_pr_diff", {
+              repo: repo.trim(),
+              prNumber: pr.number,
+              updatedAt: pr.updatedAt,
+              opId: newOpId("diff"),
+            });
       if (id !== requestIdRef.current) return;
+      const parsed = await openReviewStateApi(diff, repo.trim(), newOpId("parse"));
+      if (id !== requestIdRef.current) {
+        closeReviewStateApi(parsed.sessionId).catch(() => {});
+        return;
+      }
       setHunks(parsed.hunks);
+      setSessionId(parsed.sessionId);
     } catch (e) {
       if (id !== requestIdRef.current) return;
-      setError(String(e));
+      setError(errorMessage(e));
       setHunks([]);
     } finally {
       if (id === requestIdRef.current) {
@@ -41,11 +65,39 @@ export function usePrDiff({ repo, setError, setLoading }: UsePrDiffOptions) {
     }
   }
 
+  /** Load the PR at `headSha` after a push, carrying the review over; null when it failed or was superseded. */
+  async function refreshHead(headSha: string): Promise<HeadRefresh | null> {
+    if (!selectedPr || !sessionId) return null;
+    const id = ++requestIdRef.current;
+    setError(null);
+    setLoading("Loading new commits...");
+    try {
+      const refresh = await refreshPrHeadApi(repo, selectedPr.number, sessionId, headSha, newOpId("refresh"));
+      if (id !== requestIdRef.current) {
+        closeReviewStateApi(refresh.review.sessionId).catch(() => {});
+        return null;
+      }
+      if (refresh.review.sessionId !== sessionId) closeReviewStateApi(sessionId).catch(() => {});
+      setSelectedPr({ ...selectedPr, headRefOid: refresh.headSha });
+      setHunks(refresh.review.hunks);
+      setSessionId(refresh.review.sessionId);
+      return refresh;
+    } catch (e) {
+      if (id === requestIdRef.current) setError(errorMessage(e));
+      return null;
+    } finally {
+      if (id === requestIdRef.current) {
+        setLoading(null);
+      }
+    }
+  }
+
   function clearSelection() {
     requestIdRef.current++;
     setSelectedPr(null);
     setHunks([]);
+    closeSession();
   }
 
-  return { selectedPr, hunks, selectPr, clearSelection };
+  return { selectedPr, hunks, sessionId, selectPr, refreshHead, clearSelection };
 }
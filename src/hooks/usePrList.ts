@@ -1,25 +1,29 @@
 import { invoke } from "@tauri-apps/api/core";
 import { useEffect, useRef, useState } from "react";
 import type { PrListItem } from "../types";
+import { loadPrefetchDiffCount } from "./useSettings";
+import { errorMessage } from "../utils/prvwError";
 
 const PAGE_SIZE = 30;
 
 interface UsePrListOptions {
   repo: string;
   search: string;
+  /** Only list PRs touching a file under this subproject root. */
+  subproject?: string;
   onFetched: (repo: string) => void;
   setError: (error: string | null) => void;
   setLoading: (msg: string | null) => void;
 }
 
-export function usePrList({ repo, search, onFetched, setError, setLoading }: UsePrListOptions) {
+export function usePrList({ repo, search, subproject, onFetched, setError, setLoading }: UsePrListOptions) {
   const [prs, setPrs] = useState<PrListItem[]>([]);
   const [hasMore, setHasMore] = useState(true);
   const [loadingMore, setLoadingMore] = useState(false);
   const requestIdRef = useRef(0);
   const limitRef = useRef(PAGE_SIZE);
   const loadingMoreRef = useRef(false);
-  const activeQueryRef = useRef({ repo: "", search: "" });
+  const activeQueryRef = useRef({ repo: "", search: "", subproject: "" });
 
   async function fetchPrs() {
     limitRef.current = PAGE_SIZE;
@@ -32,7 +36,8 @@ export function usePrList({ repo, search, onFetched, setError, setLoading }: Use
     }
     const trimmedRepo = repo.trim();
     const trimmedSearch = search.trim() || null;
-    activeQueryRef.current = { repo: trimmedRepo, search: trimmedSearch ?? "" };
+    const trimmedSubproject = subproject?.trim() || null;
+    activeQueryRef.current = { repo: trimmedRepo, search: trimmedSearch ?? "", subproject: trimmedSubproject ?? "" };
     setLoading("Fetching PRs...");
     try {
       const items = await invoke<PrListItem[]>("list_prs", {
@@ -40,11 +45,14 @@ export function usePrList({ repo, search, onFetched, setError, setLoading }: Use
         limit: PAGE_SIZE,
         state: "open",
         search: trimmedSearch,
+        prefetchDiffs: loadPrefetchDiffCount() || null,
+        subproject: trimmedSubproject,
       });
       if (id !== requestIdRef.current) return;
       setPrs(items);
       onFetched(trimmedRepo);
-      if (items.length < PAGE_SIZE) {
+      // A subproject filter drops PRs from each page, so a short page isn't the end.
+      if (items.length < PAGE_SIZE && !trimmedSubproject) {
         setHasMore(false);
       }
       if (items.length === 0) {
@@ -52,7 +60,7 @@ export function usePrList({ repo, search, onFetched, setError, setLoading }: Use
       }
     } catch (e) {
       if (id !== requestIdRef.current) return;
-      setError(String(e));
+      setError(errorMessage(e));
     } finally {
       if (id === requestIdRef.current) {
         setLoading(null);
@@ -66,22 +74,25 @@ export function usePrList({ repo, search, onFetched, setError, setLoading }: Use
     setLoadingMore(true);
     const id = ++requestIdRef.current;
     limitRef.current += PAGE_SIZE;
-    const { repo: activeRepo, search: activeSearch } = activeQueryRef.current;
+    const { repo: activeRepo, search: activeSearch, subproject: activeSubproject } = activeQueryRef.current;
     try {
       const items = await invoke<PrListItem[]>("list_prs", {
         repo: activeRepo,
         limit: limitRef.current,
         state: "open",
         search: activeSearch || null,
+        prefetchDiffs: null,
+        subproject: activeSubproject || null,
       });
       if (id !== requestIdRef.current) return;
-      setPrs(items);
-      if (items.length < limitRef.current) {
+      // With a subproject filter, a page that found nothing new is the end.
+      if (items.length < limitRef.current && (!activeSubproject || items.length === prs.length)) {
         setHasMore(false);
       }
+      setPrs(items);
     } catch (e) {
       if (id !== requestIdRef.current) return;
-      setError(String(e));
+      setError(errorMessage(e));
     } finally {
       if (id === requestIdRef.current) {
         loadingMoreRef.current = false;
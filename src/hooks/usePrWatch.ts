@@ -0,0 +1,33 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect } from "react";
+import type { PrListItem } from "../types";
+
+/** Watch the open PR for new pushes and its repo's reviewed PRs for CI failures (desktop notifications). */
+export async function watchPrApi(repo: string, prNumber: number, headSha: string | null): Promise<void> {
+  return invoke<void>("watch_pr", { repo: repo.trim(), prNumber, headSha });
+}
+
+export async function unwatchPrApi(): Promise<void> {
+  return invoke<void>("unwatch_pr");
+}
+
+/** Keeps the backend watching whichever PR is open; nothing is watched from the PR list. */
+export function usePrWatch(repo: string, pr: PrListItem | null) {
+  const prNumber = pr?.number ?? null;
+  const headSha = pr?.headRefOid ?? null;
+
+  const watching = prNumber != null && headSha != null && repo.trim() !== "";
+
+  useEffect(() => {
+    // Diffs without a PR head (local ranges, patches) have nothing to watch. Switching PRs just
+    // retargets the watch, so the repo's CI state carries over.
+    if (watching && prNumber != null) watchPrApi(repo, prNumber, headSha).catch(() => {});
+  }, [watching, repo, prNumber, headSha]);
+
+  useEffect(() => {
+    if (!watching) return;
+    return () => {
+      unwatchPrApi().catch(() => {});
+    };
+  }, [watching]);
+}
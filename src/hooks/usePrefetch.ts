@@ -0,0 +1,67 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import type { PrefetchRequest, PrefetchStatus } from "../types";
+import { errorMessage } from "../utils/prvwError";
+
+const POLL_MS = 5000;
+
+export async function startPrefetchApi(request: PrefetchRequest): Promise<PrefetchStatus> {
+  return invoke<PrefetchStatus>("start_prefetch", { request: { ...request, repo: request.repo.trim() } });
+}
+
+export async function cancelPrefetchApi(): Promise<PrefetchStatus> {
+  return invoke<PrefetchStatus>("cancel_prefetch");
+}
+
+export async function getPrefetchStatusApi(): Promise<PrefetchStatus> {
+  return invoke<PrefetchStatus>("get_prefetch_status");
+}
+
+/** Seconds from now until the next occurrence of `hour`:00 local time. */
+export function secondsUntilHour(hour: number): number {
+  const now = new Date();
+  const target = new Date(now);
+  target.setHours(hour, 0, 0, 0);
+  if (target <= now) target.setDate(target.getDate() + 1);
+  return Math.round((target.getTime() - now.getTime()) / 1000);
+}
+
+/** Background pre-analysis of the review queue; status is polled while a run is pending. */
+export function usePrefetch(setError: (error: string | null) => void) {
+  const [status, setStatus] = useState<PrefetchStatus | null>(null);
+  const active = status?.phase === "scheduled" || status?.phase === "running";
+
+  useEffect(() => {
+    getPrefetchStatusApi()
+      .then(setStatus)
+      .catch(() => {});
+  }, []);
+
+  useEffect(() => {
+    if (!active) return;
+    const timer = setInterval(() => {
+      getPrefetchStatusApi()
+        .then(setStatus)
+        .catch(() => {});
+    }, POLL_MS);
+    return () => clearInterval(timer);
+  }, [active]);
+
+  async function start(request: PrefetchRequest) {
+    try {
+      setStatus(await startPrefetchApi(request));
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  }
+
+  async function cancel() {
+    try {
+      setStatus(await cancelPrefetchApi());
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  }
+
+  return { status, active, start, cancel };
+}
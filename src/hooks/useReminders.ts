@@ -0,0 +1,63 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import type { ReminderRequest, ReminderStatus, StaleReview } from "../types";
+import { errorMessage } from "../utils/prvwError";
+
+const POLL_MS = 60_000;
+
+export async function startReviewRemindersApi(request: ReminderRequest): Promise<ReminderStatus> {
+  return invoke<ReminderStatus>("start_review_reminders", { request: { ...request, repo: request.repo.trim() } });
+}
+
+export async function stopReviewRemindersApi(): Promise<ReminderStatus> {
+  return invoke<ReminderStatus>("stop_review_reminders");
+}
+
+export async function getReviewRemindersApi(): Promise<ReminderStatus> {
+  return invoke<ReminderStatus>("get_review_reminders");
+}
+
+/** One-off check of the review queue, without notifications. */
+export async function checkStaleReviewsApi(repo: string, staleDays: number): Promise<StaleReview[]> {
+  return invoke<StaleReview[]>("check_stale_reviews", { repo: repo.trim(), staleDays });
+}
+
+/** Desktop reminders for stale review requests; status is polled while the scheduler runs. */
+export function useReminders(setError: (error: string | null) => void) {
+  const [status, setStatus] = useState<ReminderStatus | null>(null);
+  const active = status?.active ?? false;
+
+  useEffect(() => {
+    getReviewRemindersApi()
+      .then(setStatus)
+      .catch(() => {});
+  }, []);
+
+  useEffect(() => {
+    if (!active) return;
+    const timer = setInterval(() => {
+      getReviewRemindersApi()
+        .then(setStatus)
+        .catch(() => {});
+    }, POLL_MS);
+    return () => clearInterval(timer);
+  }, [active]);
+
+  async function start(request: ReminderRequest) {
+    try {
+      setStatus(await startReviewRemindersApi(request));
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  }
+
+  async function stop() {
+    try {
+      setStatus(await stopReviewRemindersApi());
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  }
+
+  return { status, active, start, stop };
+}
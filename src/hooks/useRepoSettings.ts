@@ -0,0 +1,42 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import type { RepoSettings } from "../types";
+
+export async function getRepoSettingsApi(repo: string): Promise<RepoSettings | null> {
+  return invoke<RepoSettings | null>("get_repo_settings", { repo: repo.trim() });
+}
+
+/** Save overrides for `repo`; null (or nothing set) removes them. */
+export async function setRepoSettingsApi(repo: string, overrides: RepoSettings | null): Promise<RepoSettings | null> {
+  return invoke<RepoSettings | null>("set_repo_settings", { repo: repo.trim(), overrides });
+}
+
+/** Overrides saved for the open repo, reloaded whenever it changes. */
+export function useRepoSettings(repo: string) {
+  const [repoSettings, setRepoSettings] = useState<RepoSettings | null>(null);
+
+  useEffect(() => {
+    const trimmed = repo.trim();
+    if (!/^[^/\s]+\/[^/\s]+$/.test(trimmed)) {
+      setRepoSettings(null);
+      return;
+    }
+    let stale = false;
+    getRepoSettingsApi(trimmed)
+      .then((s) => {
+        if (!stale) setRepoSettings(s);
+      })
+      .catch(() => {
+        if (!stale) setRepoSettings(null);
+      });
+    return () => {
+      stale = true;
+    };
+  }, [repo]);
+
+  async function saveRepoSettings(overrides: RepoSettings | null) {
+    setRepoSettings(await setRepoSettingsApi(repo, overrides));
+  }
+
+  return { repoSettings, saveRepoSettings };
+}
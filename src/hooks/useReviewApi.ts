@@ -0,0 +1,761 @@
+import { invoke } from "@tauri-apps/api/core";
+import type {
+  AdhocReview,
+  AnalysisEdit,
+  AnalysisEditState,
+  AnalysisResult,
+  AssembledReview,
+  AssignmentTable,
+  BranchComparison,
+  ChecklistItem,
+  ChecklistTemplate,
+  CloneRemotes,
+  CommentTemplate,
+  CompareDiff,
+  DependencyChange,
+  DraftComment,
+  FollowUpIssue,
+  FollowUpItem,
+  FullFileView,
+  HeadMigration,
+  HeadState,
+  Hunk,
+  ImpactResponse,
+  ImportedAnalysis,
+  LabelResult,
+  LeftoverFinding,
+  NextUnreviewed,
+  PrListItem,
+  PrStack,
+  PrWorkspace,
+  QueuedComment,
+  QueuedCommentEdit,
+  RangeDiff,
+  RelatedPrsResponse,
+  ReleaseNotesResponse,
+  ReviewProgress,
+  ReviewSession,
+  ReviewState,
+  ReviewThread,
+  ReviewVerdict,
+  RiskyPatternHit,
+  RunOptions,
+  SessionUpdate,
+  ThreadSummariesResponse,
+  ViewedSync,
+  WorkspaceInfo,
+} from "../types";
+
+/** With `pr`, checklist items checked in the PR's session are rendered ticked. */
+export async function renderReviewSummaryApi(
+  analysis: AnalysisResult,
+  pr?: { repo: string; prNumber: number },
+): Promise<string> {
+  return invoke<string>("render_review_summary", {
+    analysis,
+    repo: pr?.repo.trim() || null,
+    prNumber: pr?.prNumber ?? null,
+  });
+}
+
+/** Upload the report ("markdown") or the importable analysis JSON ("json") as a secret gist; returns its URL. */
+export async function shareAnalysisGistApi(
+  analysis: AnalysisResult,
+  format: "markdown" | "json",
+  pr?: { repo: string; prNumber: number; headSha?: string | null },
+  hunks?: Hunk[],
+): Promise<string> {
+  return invoke<string>("share_analysis_gist", {
+    repo: pr?.repo.trim() || null,
+    prNumber: pr?.prNumber ?? null,
+    headSha: pr?.headSha ?? null,
+    analysis,
+    hunksJson: hunks ? JSON.stringify(hunks) : null,
+    format,
+  });
+}
+
+/** Load an analysis from a file path or gist URL, re-anchored on the local `hunks`. */
+export async function importAnalysisApi(source: string, hunks: Hunk[]): Promise<ImportedAnalysis> {
+  return invoke<ImportedAnalysis>("import_analysis", { source: source.trim(), hunksJson: JSON.stringify(hunks) });
+}
+
+/** Save the current session, comment queue, and analysis as the PR's state at `headSha`. */
+export async function saveHeadStateApi(
+  repo: string,
+  prNumber: number,
+  headSha: string,
+  hunks: Hunk[],
+  analysis?: AnalysisResult | null,
+): Promise<void> {
+  return invoke<void>("save_head_state", {
+    repo: repo.trim(),
+    prNumber,
+    headSha,
+    hunksJson: JSON.stringify(hunks),
+    analysis: analysis ?? null,
+  });
+}
+
+export async function getHeadStateApi(repo: string, prNumber: number, headSha: string): Promise<HeadState | null> {
+  return invoke<HeadState | null>("get_head_state", { repo: repo.trim(), prNumber, headSha });
+}
+
+/** Carry the review state saved at the session's previous head over to the diff at `headSha`. */
+export async function migrateHeadStateApi(
+  repo: string,
+  prNumber: number,
+  headSha: string,
+  hunks: Hunk[],
+): Promise<HeadMigration> {
+  return invoke<HeadMigration>("migrate_head_state", {
+    repo: repo.trim(),
+    prNumber,
+    headSha,
+    hunksJson: JSON.stringify(hunks),
+  });
+}
+
+/** Base and head versions of `filePath` aligned on the PR's hunks, for a full-file split view. */
+export async function getFullFileApi(
+  repo: string,
+  prNumber: number,
+  filePath: string,
+  hunks: Hunk[],
+  shas?: { baseSha: string; headSha: string },
+  oldPath?: string,
+): Promise<FullFileView> {
+  return invoke<FullFileView>("get_full_file", {
+    repo: repo.trim(),
+    prNumber,
+    filePath,
+    hunksJson: JSON.stringify(hunks),
+    oldPath: oldPath ?? null,
+    baseSha: shas?.baseSha ?? null,
+    headSha: shas?.headSha ?? null,
+  });
+}
+
+/** Apply a manual grouping edit to `analysis`, record it in the PR's undo log, and save the result. */
+export async function editAnalysisApi(
+  repo: string,
+  prNumber: number,
+  analysis: AnalysisResult,
+  edit: AnalysisEdit,
+  headSha?: string | null,
+): Promise<AnalysisEditState> {
+  return invoke<AnalysisEditState>("edit_analysis", {
+    repo: repo.trim(),
+    prNumber,
+    analysis,
+    edit,
+    headSha: headSha ?? null,
+  });
+}
+
+export async function undoAnalysisEditApi(
+  repo: string,
+  prNumber: number,
+  headSha?: string | null,
+): Promise<AnalysisEditState> {
+  return invoke<AnalysisEditState>("undo_analysis_edit", { repo: repo.trim(), prNumber, headSha: headSha ?? null });
+}
+
+export async function redoAnalysisEditApi(
+  repo: string,
+  prNumber: number,
+  headSha?: string | null,
+): Promise<AnalysisEditState> {
+  return invoke<AnalysisEditState>("redo_analysis_edit", { repo: repo.trim(), prNumber, headSha: headSha ?? null });
+}
+
+/** The PR's saved manual edits, or null when there are none (or they were made at another head). */
+export async function getEditedAnalysisApi(
+  repo: string,
+  prNumber: number,
+  headSha?: string | null,
+): Promise<AnalysisResult | null> {
+  return invoke<AnalysisResult | null>("get_edited_analysis", {
+    repo: repo.trim(),
+    prNumber,
+    headSha: headSha ?? null,
+  });
+}
+
+/** Drop the PR's undo log and saved edits, e.g. after a re-run replaced the edited analysis. */
+export async function clearAnalysisEditsApi(repo: string, prNumber: number): Promise<void> {
+  return invoke<void>("clear_analysis_edits", { repo: repo.trim(), prNumber });
+}
+
+export async function getDependencyChangesApi(hunks: Hunk[]): Promise<DependencyChange[]> {
+  return invoke<DependencyChange[]>("get_dependency_changes", { hunksJson: JSON.stringify(hunks) });
+}
+
+export async function scanLeftoversApi(hunks: Hunk[]): Promise<LeftoverFinding[]> {
+  return invoke<LeftoverFinding[]>("scan_leftovers", { hunksJson: JSON.stringify(hunks) });
+}
+
+export async function scanRiskyPatternsApi(hunks: Hunk[]): Promise<RiskyPatternHit[]> {
+  return invoke<RiskyPatternHit[]>("scan_risky_patterns", { hunksJson: JSON.stringify(hunks) });
+}
+
+/** Stacks of dependent open PRs (a PR based on another PR's head branch). */
+export async function getPrStacksApi(repo: string): Promise<PrStack[]> {
+  return invoke<PrStack[]>("get_pr_stacks", { repo: repo.trim() });
+}
+
+export async function postReviewSummaryApi(
+  repo: string,
+  prNumber: number,
+  body: string,
+  asReview: boolean,
+  opId?: string,
+): Promise<string> {
+  return invoke<string>("post_review_summary", {
+    repo: repo.trim(),
+    prNumber,
+    body,
+    asReview,
+    opId: opId ?? null,
+  });
+}
+
+/** Submit queued draft comments and a verdict as one review on `headSha`, the commit `hunks` were parsed at; `dryRun` only assembles it. */
+export async function submitAssembledReviewApi(
+  repo: string,
+  prNumber: number,
+  hunks: Hunk[],
+  headSha: string,
+  comments: DraftComment[],
+  verdict: ReviewVerdict,
+  body?: string,
+  dryRun?: boolean,
+  opId?: string,
+): Promise<AssembledReview> {
+  return invoke<AssembledReview>("submit_assembled_review", {
+    repo: repo.trim(),
+    prNumber,
+    hunksJson: JSON.stringify(hunks),
+    headSha,
+    comments,
+    verdict,
+    body,
+    dryRun,
+    opId: opId ?? null,
+  });
+}
+
+/** The PR's queued draft comments, in submission order. Nothing here is posted until the review is submitted. */
+export async function getCommentQueueApi(repo: string, prNumber: number): Promise<QueuedComment[]> {
+  return invoke<QueuedComment[]>("get_comment_queue", { repo: repo.trim(), prNumber });
+}
+
+export async function queueCommentsApi(
+  repo: string,
+  prNumber: number,
+  comments: DraftComment[],
+  source: "ai" | "manual" = "manual",
+): Promise<QueuedComment[]> {
+  return invoke<QueuedComment[]>("queue_comments", { repo: repo.trim(), prNumber, comments, source });
+}
+
+export async function editQueuedCommentApi(
+  repo: string,
+  prNumber: number,
+  id: string,
+  changes: QueuedCommentEdit,
+): Promise<QueuedComment[]> {
+  return invoke<QueuedComment[]>("edit_queued_comment", { repo: repo.trim(), prNumber, id, changes });
+}
+
+/** Remove the given queued comments, or all of them when `ids` is omitted. */
+export async function deleteQueuedCommentsApi(
+  repo: string,
+  prNumber: number,
+  ids?: string[],
+): Promise<QueuedComment[]> {
+  return invoke<QueuedComment[]>("delete_queued_comments", { repo: repo.trim(), prNumber, ids: ids ?? null });
+}
+
+/** Move the listed comments to the front in the given order. */
+export async function reorderCommentQueueApi(repo: string, prNumber: number, ids: string[]): Promise<QueuedComment[]> {
+  return invoke<QueuedComment[]>("reorder_comment_queue", { repo: repo.trim(), prNumber, ids });
+}
+
+export async function getReviewGuidelinesApi(repo: string): Promise<string | null> {
+  return invoke<string | null>("get_review_guidelines", { repo: repo.trim() });
+}
+
+/** Templates applying to `repo` (global ones included), or all templates without it. */
+export async function listChecklistTemplatesApi(repo?: string): Promise<ChecklistTemplate[]> {
+  return invoke<ChecklistTemplate[]>("list_checklist_templates", { repo: repo?.trim() || null });
+}
+
+/** Creates the template when its id is empty, otherwise replaces it. */
+export async function saveChecklistTemplateApi(template: ChecklistTemplate): Promise<ChecklistTemplate> {
+  return invoke<ChecklistTemplate>("save_checklist_template", { template });
+}
+
+export async function deleteChecklistTemplateApi(id: string): Promise<void> {
+  return invoke<void>("delete_checklist_template", { id });
+}
+
+/** Templates offered in `repo` (global ones included), or all templates without it. */
+export async function listCommentTemplatesApi(repo?: string): Promise<CommentTemplate[]> {
+  return invoke<CommentTemplate[]>("list_comment_templates", { repo: repo?.trim() || null });
+}
+
+/** Creates the template when its id is empty, otherwise replaces it. */
+export async function saveCommentTemplateApi(template: CommentTemplate): Promise<CommentTemplate> {
+  return invoke<CommentTemplate>("save_comment_template", { template });
+}
+
+export async function deleteCommentTemplateApi(id: string): Promise<void> {
+  return invoke<void>("delete_comment_template", { id });
+}
+
+/** Fill in a template for a diff line and append it to the PR's comment queue. Returns the saved queue. */
+export async function insertCommentTemplateApi(
+  repo: string,
+  prNumber: number,
+  templateId: string,
+  target: { hunkId: string; filePath: string; line: number; side?: "RIGHT" | "LEFT"; groupTitle?: string | null },
+): Promise<QueuedComment[]> {
+  return invoke<QueuedComment[]>("insert_comment_template", {
+    repo: repo.trim(),
+    prNumber,
+    templateId,
+    hunkId: target.hunkId,
+    filePath: target.filePath,
+    line: target.line,
+    side: target.side ?? null,
+    groupTitle: target.groupTitle ?? null,
+  });
+}
+
+/**
+ * `mapping` is category -> label; without it the repo's saved mapping is used, or else each category maps to the
+ * label of the same name.
+ */
+export async function applyCategoryLabelsApi(
+  repo: string,
+  prNumber: number,
+  analysis: AnalysisResult,
+  mapping?: Record<string, string>,
+  dryRun?: boolean,
+): Promise<LabelResult> {
+  return invoke<LabelResult>("apply_category_labels", {
+    repo: repo.trim(),
+    prNumber,
+    analysis,
+    mapping: mapping && Object.keys(mapping).length > 0 ? mapping : null,
+    dryRun: dryRun ?? false,
+  });
+}
+
+/** Open a GitHub issue per item, linking back to the PR and files; `dryRun` only drafts them. */
+export async function createFollowUpIssuesApi(
+  repo: string,
+  prNumber: number,
+  items: FollowUpItem[],
+  headSha?: string | null,
+  labels?: string[],
+  dryRun?: boolean,
+  opId?: string,
+): Promise<FollowUpIssue[]> {
+  return invoke<FollowUpIssue[]>("create_follow_up_issues", {
+    repo: repo.trim(),
+    prNumber,
+    items,
+    headSha: headSha ?? null,
+    labels: labels ?? null,
+    dryRun: dryRun ?? false,
+    opId: opId ?? null,
+  });
+}
+
+export async function findRelatedPrsApi(
+  repo: string,
+  prNumber: number,
+  hunks: Hunk[],
+  analysis: AnalysisResult,
+  model: string,
+  lang: string,
+  force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  opId?: string,
+): Promise<RelatedPrsResponse> {
+  return invoke<RelatedPrsResponse>("find_related_prs", {
+    repo: repo.trim(),
+    prNumber,
+    hunksJson: JSON.stringify(hunks),
+    analysis,
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    opId: opId ?? null,
+  });
+}
+
+export async function generateReleaseNotesApi(
+  repo: string,
+  prNumber: number,
+  analysis: AnalysisResult,
+  model: string,
+  lang: string,
+  force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  headSha?: string,
+  opId?: string,
+): Promise<ReleaseNotesResponse> {
+  return invoke<ReleaseNotesResponse>("generate_release_notes", {
+    repo: repo.trim(),
+    prNumber,
+    headSha: headSha ?? null,
+    analysis,
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    opId: opId ?? null,
+  });
+}
+
+export async function analyzeImpactApi(
+  repo: string,
+  prNumber: number,
+  hunks: Hunk[],
+  analysis: AnalysisResult,
+): Promise<ImpactResponse> {
+  return invoke<ImpactResponse>("analyze_impact", {
+    repo: repo.trim(),
+    prNumber,
+    hunksJson: JSON.stringify(hunks),
+    analysis,
+  });
+}
+
+export async function getReviewThreadsApi(repo: string, prNumber: number): Promise<ReviewThread[]> {
+  return invoke<ReviewThread[]>("get_review_threads", { repo: repo.trim(), prNumber });
+}
+
+export async function summarizeReviewThreadsApi(
+  threads: ReviewThread[],
+  model: string,
+  lang: string,
+  force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+  repo?: string | null,
+  opId?: string,
+): Promise<ThreadSummariesResponse> {
+  return invoke<ThreadSummariesResponse>("summarize_review_threads", {
+    threads,
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+    repo: repo?.trim() || null,
+    opId: opId ?? null,
+  });
+}
+
+export async function getSessionApi(repo: string, prNumber: number): Promise<ReviewSession> {
+  return invoke<ReviewSession>("get_session", { repo, prNumber });
+}
+
+export async function updateSessionApi(repo: string, prNumber: number, update: SessionUpdate): Promise<ReviewSession> {
+  return invoke<ReviewSession>("update_session", { repo, prNumber, update });
+}
+
+export async function getChecklistApi(
+  repo: string,
+  prNumber: number,
+  analysis: AnalysisResult,
+): Promise<ChecklistItem[]> {
+  return invoke<ChecklistItem[]>("get_checklist", { repo, prNumber, analysis });
+}
+
+export async function checkChecklistItemsApi(
+  repo: string,
+  prNumber: number,
+  ids: string[],
+  checked: boolean,
+  headSha?: string | null,
+): Promise<ReviewSession> {
+  return invoke<ReviewSession>("check_checklist_items", { repo, prNumber, ids, checked, headSha: headSha ?? null });
+}
+
+/** Assign groups to reviewers (group id -> GitHub login, "@" optional) and/or unassign groups. */
+export async function assignGroupsApi(
+  repo: string,
+  prNumber: number,
+  assignments: Record<string, string>,
+  unassign?: string[],
+): Promise<ReviewSession> {
+  return invoke<ReviewSession>("assign_groups", { repo, prNumber, assignments, unassign: unassign ?? null });
+}
+
+export async function getAssignmentsApi(
+  repo: string,
+  prNumber: number,
+  analysis: AnalysisResult,
+): Promise<AssignmentTable> {
+  return invoke<AssignmentTable>("get_assignments", { repo, prNumber, analysis });
+}
+
+/** Post the assignment table as a PR comment; returns its URL. */
+export async function postAssignmentsApi(repo: string, prNumber: number, analysis: AnalysisResult, opId?: string): Promise<string> {
+  return invoke<string>("post_assignments", { repo, prNumber, analysis, opId: opId ?? null });
+}
+
+/** Skip groups with a reason, or with `snooze` only until the next push; they drop out of progress. */
+export async function skipGroupsApi(
+  repo: string,
+  prNumber: number,
+  groupIds: string[],
+  reason: string,
+  snooze?: boolean,
+  headSha?: string | null,
+): Promise<ReviewSession> {
+  return invoke<ReviewSession>("skip_groups", {
+    repo,
+    prNumber,
+    groupIds,
+    reason: reason.trim() || null,
+    snooze: snooze ?? false,
+    headSha: headSha ?? null,
+  });
+}
+
+export async function unskipGroupsApi(repo: string, prNumber: number, groupIds: string[]): Promise<ReviewSession> {
+  return invoke<ReviewSession>("unskip_groups", { repo, prNumber, groupIds });
+}
+
+/** Save a custom group order at `headSha`; exports and the submitted summary follow it. Empty resets it. */
+export async function setGroupOrderApi(
+  repo: string,
+  prNumber: number,
+  groupIds: string[],
+  headSha?: string | null,
+): Promise<ReviewSession> {
+  return invoke<ReviewSession>("set_group_order", { repo, prNumber, groupIds, headSha: headSha ?? null });
+}
+
+/** `analysis` with its groups in the PR's saved custom order. */
+export async function orderAnalysisApi(
+  repo: string,
+  prNumber: number,
+  analysis: AnalysisResult,
+): Promise<AnalysisResult> {
+  return invoke<AnalysisResult>("order_analysis", { repo, prNumber, analysis });
+}
+
+export async function getReviewProgressApi(
+  repo: string,
+  prNumber: number,
+  analysis: AnalysisResult,
+): Promise<ReviewProgress> {
+  return invoke<ReviewProgress>("get_review_progress", { repo, prNumber, analysis });
+}
+
+export async function nextUnreviewedApi(
+  repo: string,
+  prNumber: number,
+  analysis: AnalysisResult,
+  currentHunkId: string | null,
+  groupOrder?: string[],
+  skipGroupIds?: string[],
+): Promise<NextUnreviewed | null> {
+  return invoke<NextUnreviewed | null>("next_unreviewed", {
+    repo,
+    prNumber,
+    analysis,
+    groupOrder,
+    skipGroupIds,
+    currentHunkId,
+  });
+}
+
+/** Mirror reviewed markers and GitHub's per-file "viewed" state both ways. */
+export async function syncViewedStateApi(
+  repo: string,
+  prNumber: number,
+  hunks: Hunk[],
+  analysis: AnalysisResult | null,
+): Promise<ViewedSync> {
+  return invoke<ViewedSync>("sync_viewed_state", {
+    repo,
+    prNumber,
+    hunksJson: JSON.stringify(hunks),
+    analysis,
+  });
+}
+
+/** The diff of `range` (`main..feature`, `HEAD~5..HEAD`, or one commit) in a local clone, for `parse_diff`. */
+export async function getRangeDiffApi(repoPath: string, range: string): Promise<RangeDiff> {
+  return invoke<RangeDiff>("get_range_diff", { repoPath: repoPath.trim(), range: range.trim() });
+}
+
+/** The diff of a Jujutsu change or revset (`@` by default) in a jj-managed repo, like `getRangeDiffApi`. */
+export async function getJjDiffApi(repoPath: string, revset: string | null = null): Promise<RangeDiff> {
+  return invoke<RangeDiff>("get_jj_diff", { repoPath: repoPath.trim(), revset: revset?.trim() || null });
+}
+
+/** The repo a local clone's PRs live in (upstream over origin) and its fork, to fill in the repo slug. */
+export async function getCloneRemotesApi(repoPath: string): Promise<CloneRemotes> {
+  return invoke<CloneRemotes>("get_clone_remotes", { repoPath: repoPath.trim() });
+}
+
+/** Diff `head` against its merge base with `base` (`git diff base...head`), with ahead/behind counts. */
+export async function compareBranchesApi(repoPath: string, base: string, head: string): Promise<BranchComparison> {
+  return invoke<BranchComparison>("compare_branches", {
+    repoPath: repoPath.trim(),
+    base: base.trim(),
+    head: head.trim(),
+  });
+}
+
+/** Check out a PR's head in its managed worktree (reused and reset when it exists). */
+export async function checkoutPrWorkspaceApi(repo: string, prNumber: number): Promise<PrWorkspace> {
+  return invoke<PrWorkspace>("checkout_pr_workspace", { repo: repo.trim(), prNumber });
+}
+
+/** Remove one PR's checkout, a repo's checkouts, or all of them; resolves to the number removed. */
+export async function cleanupWorkspacesApi(
+  repo: string | null = null,
+  prNumber: number | null = null,
+): Promise<number> {
+  return invoke<number>("cleanup_workspaces", { repo, prNumber });
+}
+
+/** PR checkouts on disk with their size. */
+export async function listWorkspacesApi(): Promise<WorkspaceInfo[]> {
+  return invoke<WorkspaceInfo[]>("list_workspaces");
+}
+
+/** Remove one PR's checkout; resolves to whether there was one. */
+export async function removeWorkspaceApi(repo: string, prNumber: number): Promise<boolean> {
+  return invoke<boolean>("remove_workspace", { repo: repo.trim(), prNumber });
+}
+
+/**
+ * Open a diff file in the user's editor at `line`, from the PR's checkout or else the repo's clone;
+ * resolves to the opened path.
+ */
+export async function openInEditorApi(
+  repo: string,
+  prNumber: number | null,
+  path: string,
+  line: number | null,
+): Promise<string> {
+  return invoke<string>("open_in_editor", { repo: repo.trim(), prNumber, path, line });
+}
+
+/** Disk usage of all workspaces, human-readable. */
+export async function getWorkspaceSizeApi(): Promise<string> {
+  return invoke<string>("get_workspace_size");
+}
+
+/** Review a local .patch/.diff file (a format-patch series too) in its own session. */
+export async function openPatchFileApi(path: string): Promise<AdhocReview> {
+  return invoke<AdhocReview>("open_patch_file", { path: path.trim() });
+}
+
+/** Review diff text pasted from the clipboard in its own session (keyed by `name`, "pasted" by default). */
+export async function openDiffTextApi(text: string, name: string | null = null): Promise<AdhocReview> {
+  return invoke<AdhocReview>("open_diff_text", { text, name });
+}
+
+/** Resolve a pasted github.com compare/base...head URL and fetch its diff, for `parse_diff` with its repo. */
+export async function getCompareDiffApi(url: string): Promise<CompareDiff> {
+  return invoke<CompareDiff>("get_compare_diff", { url: url.trim() });
+}
+
+/** Download a .diff/.patch (a CI artifact, a github.com pull/N.diff) and review it in its own session. */
+export async function openDiffUrlApi(url: string): Promise<AdhocReview> {
+  return invoke<AdhocReview>("open_diff_url", { url: url.trim() });
+}
+
+/** Pull requests of a Bitbucket Cloud repository (`workspace/repo_slug`), shaped like `list_prs` results. */
+export async function listBitbucketPrsApi(
+  repo: string,
+  limit: number,
+  state: "open" | "closed" | "merged" | "all" = "open",
+): Promise<PrListItem[]> {
+  return invoke<PrListItem[]>("list_bitbucket_prs", { repo: repo.trim(), limit, state });
+}
+
+/** The diff of a Bitbucket Cloud pull request, for `parse_diff`. */
+export async function getBitbucketPrDiffApi(
+  repo: string,
+  prNumber: number,
+  updatedAt: string | null,
+  force = false,
+): Promise<string> {
+  return invoke<string>("get_bitbucket_pr_diff", { repo: repo.trim(), prNumber, updatedAt, force });
+}
+
+/** Pull requests on the Gitea/Forgejo instance set in Settings, shaped like `list_prs` results. */
+export async function listGiteaPrsApi(
+  repo: string,
+  limit: number,
+  state: "open" | "closed" | "merged" | "all" = "open",
+): Promise<PrListItem[]> {
+  return invoke<PrListItem[]>("list_gitea_prs", { repo: repo.trim(), limit, state });
+}
+
+/** The diff of a Gitea/Forgejo pull request, for `parse_diff`. */
+export async function getGiteaPrDiffApi(
+  repo: string,
+  prNumber: number,
+  updatedAt: string | null,
+  force = false,
+): Promise<string> {
+  return invoke<string>("get_gitea_pr_diff", { repo: repo.trim(), prNumber, updatedAt, force });
+}
+
+/** Post a Markdown comment to a Gitea/Forgejo pull request; resolves to the comment URL. */
+export async function postGiteaCommentApi(repo: string, prNumber: number, body: string, opId?: string): Promise<string> {
+  return invoke<string>("post_gitea_comment", { repo: repo.trim(), prNumber, body, opId: opId ?? null });
+}
+
+/** Pull requests of an Azure DevOps repository (`project/repository`), shaped like `list_prs` results. */
+export async function listAzureDevopsPrsApi(
+  repo: string,
+  limit: number,
+  state: "open" | "closed" | "merged" | "all" = "open",
+): Promise<PrListItem[]> {
+  return invoke<PrListItem[]>("list_azure_devops_prs", { repo: repo.trim(), limit, state });
+}
+
+/** The diff of an Azure DevOps pull request's latest iteration, for `parse_diff`. */
+export async function getAzureDevopsPrDiffApi(repo: string, prNumber: number, force = false): Promise<string> {
+  return invoke<string>("get_azure_devops_pr_diff", { repo: repo.trim(), prNumber, force });
+}
+
+/** Parse a diff into backend review state; analysis and refine runs then take its `sessionId`. */
+export async function openReviewStateApi(diffText: string, repo: string | null, opId?: string): Promise<ReviewState> {
+  return invoke<ReviewState>("open_review_state", { diffText, repo: repo?.trim() || null, opId: opId ?? null });
+}
+
+/** The latest analysis run on an open review state. */
+export async function getReviewStateAnalysisApi(sessionId: string): Promise<AnalysisResult | null> {
+  return invoke<AnalysisResult | null>("get_review_state_analysis", { sessionId });
+}
+
+export async function closeReviewStateApi(sessionId: string): Promise<boolean> {
+  return invoke<boolean>("close_review_state", { sessionId });
+}
@@ -0,0 +1,65 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import type { SecretTest } from "../types";
+import { errorMessage } from "../utils/prvwError";
+
+/** Tokens kept in the OS keyring, never in the settings files. */
+export type SecretName = "openai" | "github" | "gitlab" | "gitea" | "azure-devops" | "bitbucket";
+
+export async function setSecretApi(name: SecretName, value: string): Promise<void> {
+  return invoke<void>("set_secret", { name, value });
+}
+
+export async function hasSecretApi(name: SecretName): Promise<boolean> {
+  return invoke<boolean>("has_secret", { name });
+}
+
+export async function deleteSecretApi(name: SecretName): Promise<void> {
+  return invoke<void>("delete_secret", { name });
+}
+
+/** Check the stored token with an authenticated request to its service. */
+export async function testSecretApi(name: SecretName): Promise<SecretTest> {
+  return invoke<SecretTest>("test_secret", { name });
+}
+
+export function useSecret(name: SecretName) {
+  const [stored, setStored] = useState(false);
+  const [testing, setTesting] = useState(false);
+  const [lastTest, setLastTest] = useState<SecretTest | null>(null);
+
+  useEffect(() => {
+    hasSecretApi(name)
+      .then(setStored)
+      .catch(() => {});
+  }, [name]);
+
+  async function save(value: string) {
+    await setSecretApi(name, value);
+    setStored(true);
+    setLastTest(null);
+  }
+
+  async function remove() {
+    try {
+      await deleteSecretApi(name);
+      setStored(false);
+      setLastTest(null);
+    } catch (e) {
+      alert(`Failed to delete the token: ${errorMessage(e)}`);
+    }
+  }
+
+  async function test() {
+    setTesting(true);
+    try {
+      setLastTest(await testSecretApi(name));
+    } catch (e) {
+      setLastTest({ name, stored: false, ok: false, message: errorMessage(e) });
+    } finally {
+      setTesting(false);
+    }
+  }
+
+  return { stored, testing, lastTest, save, remove, test };
+}
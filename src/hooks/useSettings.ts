@@ -1,17 +1,171 @@
-import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import type { AppSettings, RunOptions, SettingsUpdate } from "../types";
+import { errorMessage } from "../utils/prvwError";
+
+export interface Settings {
+  codexModel: string;
+  lang: string;
+  codexTimeout: number;
+  provider: string;
+  useGuidelines: boolean;
+  useRepoContext: boolean;
+  runOptions: RunOptions;
+  /** Estimated input tokens above which analysis asks for confirmation; 0 disables the check. */
+  tokenBudget: number;
+  /** USD per million tokens, used only to show cost estimates. */
+  costPerMTokens: number;
+}
+
+const RUN_OPTIONS_KEY = "prvw:codexOptions";
+
+function loadRunOptions(): RunOptions {
+  try {
+    const raw = localStorage.getItem(RUN_OPTIONS_KEY);
+    return raw ? JSON.parse(raw) : {};
+  } catch {
+    return {};
+  }
+}
+
+const LABEL_MAPPING_KEY = "prvw:labelMapping";
+
+/** Category -> repository label mapping used when labeling PRs from their analysis. */
+export function loadLabelMapping(): Record<string, string> {
+  try {
+    const raw = localStorage.getItem(LABEL_MAPPING_KEY);
+    return raw ? JSON.parse(raw) : {};
+  } catch {
+    return {};
+  }
+}
+
+export function saveLabelMapping(mapping: Record<string, string>) {
+  localStorage.setItem(LABEL_MAPPING_KEY, JSON.stringify(mapping));
+}
+
+const PREFETCH_DIFFS_KEY = "prvw:prefetchDiffs";
+
+/** How many PR diffs to prefetch in the background after listing PRs; 0 disables it. */
+export function loadPrefetchDiffCount(): number {
+  const n = Number(localStorage.getItem(PREFETCH_DIFFS_KEY) ?? "0");
+  return n > 0 ? n : 0;
+}
+
+export function savePrefetchDiffCount(count: number) {
+  localStorage.setItem(PREFETCH_DIFFS_KEY, String(count));
+}
+
+/** Saved in the app data dir; commands fall back to these when a call leaves an option unset. */
+export async function getSettingsApi(): Promise<AppSettings> {
+  return invoke<AppSettings>("get_settings");
+}
+
+export async function updateSettingsApi(update: SettingsUpdate): Promise<AppSettings> {
+  return invoke<AppSettings>("update_settings", { update });
+}
+
+const SETTINGS_MIGRATED_KEY = "prvw:settingsMigrated";
+
+function toUpdate(s: Settings): SettingsUpdate {
+  return {
+    model: s.codexModel,
+    lang: s.lang,
+    provider: s.provider,
+    runOptions: s.runOptions,
+    timeoutMinutes: s.codexTimeout,
+    useGuidelines: s.useGuidelines,
+    useRepoContext: s.useRepoContext,
+    tokenBudget: s.tokenBudget,
+    costPerMTokens: s.costPerMTokens,
+  };
+}
 
 export function useSettings() {
+  // Seeded from the values this hook used to keep in localStorage, until the store answers.
   const [codexModel, setCodexModel] = useState(() => localStorage.getItem("prvw:codexModel") ?? "");
   const [lang, setLang] = useState(() => localStorage.getItem("prvw:lang") ?? "ja");
+  const [codexTimeout, setCodexTimeout] = useState(() => Number(localStorage.getItem("prvw:codexTimeout") ?? "10"));
+  const [provider, setProvider] = useState(() => localStorage.getItem("prvw:provider") ?? "codex");
+  const [useGuidelines, setUseGuidelines] = useState(() => localStorage.getItem("prvw:useGuidelines") === "true");
+  const [useRepoContext, setUseRepoContext] = useState(() => localStorage.getItem("prvw:repoContext") === "true");
+  const [runOptions, setRunOptions] = useState<RunOptions>(loadRunOptions);
+  const [tokenBudget, setTokenBudget] = useState(() => Number(localStorage.getItem("prvw:tokenBudget") ?? "0"));
+  const [costPerMTokens, setCostPerMTokens] = useState(() =>
+    Number(localStorage.getItem("prvw:costPerMTokens") ?? "0"),
+  );
   const [hasSettings, setHasSettings] = useState(() => localStorage.getItem("prvw:lang") !== null);
 
-  function saveSettings(s: { codexModel: string; lang: string }) {
-    localStorage.setItem("prvw:codexModel", s.codexModel);
+  function apply(s: AppSettings) {
+    setCodexModel(s.model ?? "");
+    setLang(s.lang ?? "");
+    setCodexTimeout(s.timeoutMinutes ?? 10);
+    setProvider(s.provider ?? "codex");
+    setUseGuidelines(s.useGuidelines);
+    setUseRepoContext(s.useRepoContext);
+    setRunOptions(s.runOptions);
+    setTokenBudget(s.tokenBudget);
+    setCostPerMTokens(s.costPerMTokens);
+  }
+
+  // biome-ignore lint/correctness/useExhaustiveDependencies: load once on mount
+  useEffect(() => {
+    // Settings saved before the store existed are moved into it once.
+    const migrate = hasSettings && localStorage.getItem(SETTINGS_MIGRATED_KEY) === null;
+    const load = migrate
+      ? updateSettingsApi(
+          toUpdate({
+            codexModel,
+            lang,
+            codexTimeout,
+            provider,
+            useGuidelines,
+            useRepoContext,
+            runOptions,
+            tokenBudget,
+            costPerMTokens,
+          }),
+        )
+      : getSettingsApi();
+    load
+      .then((s) => {
+        localStorage.setItem(SETTINGS_MIGRATED_KEY, "true");
+        if (hasSettings) apply(s);
+      })
+      .catch((e) => console.warn(`Failed to load settings: ${errorMessage(e)}`));
+  }, []);
+
+  async function saveSettings(s: Settings) {
     localStorage.setItem("prvw:lang", s.lang);
-    setCodexModel(s.codexModel);
-    setLang(s.lang);
+    localStorage.setItem(SETTINGS_MIGRATED_KEY, "true");
+    try {
+      apply(await updateSettingsApi(toUpdate(s)));
+    } catch (e) {
+      console.warn(`Failed to save settings: ${errorMessage(e)}`);
+      setCodexModel(s.codexModel);
+      setLang(s.lang);
+      setCodexTimeout(s.codexTimeout);
+      setProvider(s.provider);
+      setUseGuidelines(s.useGuidelines);
+      setUseRepoContext(s.useRepoContext);
+      setRunOptions(s.runOptions);
+      setTokenBudget(s.tokenBudget);
+      setCostPerMTokens(s.costPerMTokens);
+    }
     setHasSettings(true);
   }
 
-  return { codexModel, lang, hasSettings, saveSettings };
+  return {
+    codexModel,
+    lang,
+    codexTimeout,
+    provider,
+    useGuidelines,
+    useRepoContext,
+    runOptions,
+    tokenBudget,
+    costPerMTokens,
+    hasSettings,
+    saveSettings,
+  };
 }
@@ -0,0 +1,92 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { useEffect, useState } from "react";
+import type { PushHookStatus, RunOptions, SelfReviewResponse, StagedStatus } from "../types";
+import { errorMessage } from "../utils/prvwError";
+
+export async function watchStagedChangesApi(repoPath: string): Promise<StagedStatus> {
+  return invoke<StagedStatus>("watch_staged_changes", { repoPath: repoPath.trim() });
+}
+
+export async function stopWatchingStagedApi(): Promise<StagedStatus> {
+  return invoke<StagedStatus>("stop_watching_staged");
+}
+
+export async function getStagedStatusApi(): Promise<StagedStatus> {
+  return invoke<StagedStatus>("get_staged_status");
+}
+
+/** The staged diff, for `parse_diff` and a regular analysis. */
+export async function getStagedDiffApi(repoPath: string): Promise<string> {
+  return invoke<string>("get_staged_diff", { repoPath: repoPath.trim() });
+}
+
+export async function getPushHookStatusApi(repoPath: string): Promise<PushHookStatus> {
+  return invoke<PushHookStatus>("get_push_hook_status", { repoPath: repoPath.trim() });
+}
+
+/** Install the pre-push hook that blocks pushes with findings of `failOn` risk or higher (default "high"). */
+export async function installPushHookApi(
+  repoPath: string,
+  failOn: "low" | "medium" | "high" | null = null,
+): Promise<PushHookStatus> {
+  return invoke<PushHookStatus>("install_push_hook", { repoPath: repoPath.trim(), failOn });
+}
+
+/** Remove prvw's pre-push hook; resolves to whether it was installed. */
+export async function uninstallPushHookApi(repoPath: string): Promise<boolean> {
+  return invoke<boolean>("uninstall_push_hook", { repoPath: repoPath.trim() });
+}
+
+export async function selfReviewStagedApi(
+  repoPath: string,
+  model: string,
+  lang: string,
+  force?: boolean,
+  timeoutMinutes?: number,
+  provider?: string,
+  options?: RunOptions,
+): Promise<SelfReviewResponse> {
+  return invoke<SelfReviewResponse>("self_review_staged", {
+    repoPath: repoPath.trim(),
+    model: model.trim() || null,
+    lang: lang.trim() || null,
+    force: force ?? false,
+    timeoutMinutes: timeoutMinutes ?? null,
+    provider: provider?.trim() || null,
+    options: options ?? null,
+  });
+}
+
+/** Pre-push self-review mode: the watched index's status, updated by `staged-changes` events. */
+export function useStagedReview(setError: (error: string | null) => void) {
+  const [status, setStatus] = useState<StagedStatus | null>(null);
+
+  useEffect(() => {
+    getStagedStatusApi()
+      .then(setStatus)
+      .catch(() => {});
+    const unlisten = listen<StagedStatus>("staged-changes", (e) => setStatus(e.payload));
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  async function watch(repoPath: string) {
+    try {
+      setStatus(await watchStagedChangesApi(repoPath));
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  }
+
+  async function stop() {
+    try {
+      setStatus(await stopWatchingStagedApi());
+    } catch (e) {
+      setError(errorMessage(e));
+    }
+  }
+
+  return { status, watching: status?.watching ?? false, watch, stop };
+}
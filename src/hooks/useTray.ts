@@ -0,0 +1,33 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import type { TrayStatus } from "../types";
+
+export async function getTrayStatusApi(): Promise<TrayStatus> {
+  return invoke<TrayStatus>("get_tray_status");
+}
+
+/** Follow a repo's review queue in the tray (null stops); kept across launches. */
+export async function setTrayRepoApi(repo: string | null): Promise<TrayStatus> {
+  return invoke<TrayStatus>("set_tray_repo", { repo: repo?.trim() || null });
+}
+
+export async function setKeepRunningApi(enabled: boolean): Promise<TrayStatus> {
+  return invoke<TrayStatus>("set_keep_running", { enabled });
+}
+
+/** The tray's close behavior, for Settings. */
+export function useTray() {
+  const [status, setStatus] = useState<TrayStatus | null>(null);
+
+  useEffect(() => {
+    getTrayStatusApi()
+      .then(setStatus)
+      .catch(() => {});
+  }, []);
+
+  async function saveKeepRunning(enabled: boolean) {
+    setStatus(await setKeepRunningApi(enabled));
+  }
+
+  return { status, saveKeepRunning };
+}
@@ -0,0 +1,43 @@
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import type { Hunk, TokenEstimate, UsageEntry, UsageStats } from "../types";
+
+export async function estimateTokensApi(hunks: Hunk[], budget: number): Promise<TokenEstimate> {
+  return invoke<TokenEstimate>("estimate_tokens_for_hunks", {
+    hunksJson: JSON.stringify(hunks),
+    budget: budget > 0 ? budget : null,
+  });
+}
+
+export async function getUsageLedgerApi(limit?: number): Promise<UsageEntry[]> {
+  return invoke<UsageEntry[]>("get_usage_ledger", { limit: limit ?? null });
+}
+
+/** Analyses run, cache hit rates, latency per model and review durations; computed locally. */
+export async function getUsageStatsApi(): Promise<UsageStats> {
+  return invoke<UsageStats>("get_usage_stats");
+}
+
+/** Tokens a ledger entry counts toward totals: reported usage, else the pre-run estimate. */
+export function entryTokens(entry: UsageEntry): number {
+  return entry.usage?.totalTokens ?? entry.estimatedInputTokens;
+}
+
+export function useUsageLedger() {
+  const [entries, setEntries] = useState<UsageEntry[]>([]);
+  const [stats, setStats] = useState<UsageStats | null>(null);
+
+  useEffect(() => {
+    getUsageLedgerApi()
+      .then(setEntries)
+      .catch(() => {});
+    getUsageStatsApi()
+      .then(setStats)
+      .catch(() => {});
+  }, []);
+
+  const totalTokens = entries.reduce((sum, e) => sum + entryTokens(e), 0);
+  const unreported = entries.filter((e) => !e.usage).length;
+
+  return { entries, stats, totalTokens, unreported };
+}
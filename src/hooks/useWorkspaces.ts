@@ -0,0 +1,47 @@
+import { useEffect, useState } from "react";
+import type { WorkspaceInfo } from "../types";
+import { cleanupWorkspacesApi, getWorkspaceSizeApi, listWorkspacesApi, removeWorkspaceApi } from "./useReviewApi";
+import { errorMessage } from "../utils/prvwError";
+
+/** PR checkouts on disk and their total size, for cleaning them up from Settings. */
+export function useWorkspaces() {
+  const [workspaces, setWorkspaces] = useState<WorkspaceInfo[]>([]);
+  const [workspaceSize, setWorkspaceSize] = useState<string | null>(null);
+  const [removing, setRemoving] = useState(false);
+
+  async function refresh() {
+    const [list, size] = await Promise.all([listWorkspacesApi(), getWorkspaceSizeApi()]);
+    setWorkspaces(list);
+    setWorkspaceSize(size);
+  }
+
+  useEffect(() => {
+    refresh().catch(() => {});
+  }, []);
+
+  async function removeWorkspace(repo: string, prNumber: number) {
+    setRemoving(true);
+    try {
+      await removeWorkspaceApi(repo, prNumber);
+      await refresh();
+    } catch (e) {
+      alert(`Failed to remove checkout: ${errorMessage(e)}`);
+    } finally {
+      setRemoving(false);
+    }
+  }
+
+  async function removeAllWorkspaces() {
+    setRemoving(true);
+    try {
+      await cleanupWorkspacesApi();
+      await refresh();
+    } catch (e) {
+      alert(`Failed to remove checkouts: ${errorMessage(e)}`);
+    } finally {
+      setRemoving(false);
+    }
+  }
+
+  return { workspaces, workspaceSize, removing, removeWorkspace, removeAllWorkspaces };
+}
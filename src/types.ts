@@ -7,14 +7,34 @@ export interface PrListItem {
   title: string;
   url: string;
   updatedAt: string;
+  createdAt?: string;
   author: PrAuthor | null;
   headRefName: string | null;
+  headRefOid?: string | null;
   baseRefName: string | null;
+  headRepositoryOwner?: { login: string } | null;
+  /** The head branch is in a fork rather than the PR's own repository. */
+  isCrossRepository?: boolean | null;
   reviewDecision: string | null;
   isDraft: boolean | null;
   body: string | null;
 }
 
+export interface StackLayer {
+  number: number;
+  title: string;
+  headRefName: string;
+  headRefOid: string | null;
+  baseRefName: string;
+  /** The PR this one is stacked on; null for the bottom layer. */
+  parent: number | null;
+}
+
+/** Dependent PRs chained by base branch, parents before children. */
+export interface PrStack {
+  layers: StackLayer[];
+}
+
 export interface DiffLine {
   kind: "add" | "remove" | "context";
   oldLine: number | null;
@@ -25,6 +45,8 @@ export interface DiffLine {
 export interface Hunk {
   id: string;
   filePath: string;
+  /** Root of the subproject (from the repo settings) the file belongs to. */
+  subproject?: string | null;
   header: string;
   oldStart: number;
   oldLines: number;
@@ -38,14 +60,160 @@ export interface ParsedDiff {
   raw: string;
 }
 
+/** A diff parsed into backend review state (`open_review_state`). */
+export interface ReviewState extends ParsedDiff {
+  sessionId: string;
+}
+
+export interface RangeCommit {
+  sha: string;
+  subject: string;
+}
+
+/** A file in a local diff. */
+export interface ChangedFile {
+  path: string;
+  /** Previous path of a renamed or copied file. */
+  oldPath: string | null;
+  status: "added" | "deleted" | "modified" | "renamed" | "copied" | "typechange";
+  /** Binary files have no hunks in the diff. */
+  binary: boolean;
+}
+
+/** A PR head checked out in its managed worktree. */
+export interface PrWorkspace {
+  repo: string;
+  prNumber: number;
+  path: string;
+  headSha: string;
+}
+
+/** A PR checkout on disk, for listing and cleaning up workspaces. */
+export interface WorkspaceInfo {
+  repo: string;
+  prNumber: number;
+  path: string;
+  /** Null when the checkout is broken (e.g. its base clone was deleted). */
+  headSha: string | null;
+  /** The user's clone the checkout is a worktree of; null for the managed clone. */
+  clonePath: string | null;
+  sizeBytes: number;
+  /** Seconds since the epoch. */
+  modifiedAt: number | null;
+}
+
+/**
+ * A diff reviewed without a forge (a patch file or pasted text). `repo` (`local/<name>`) and `prNumber`
+ * address its session in the regular session commands.
+ */
+export interface AdhocReview {
+  repo: string;
+  prNumber: number;
+  title: string;
+  /** Commit subjects of a format-patch series, standing in for the PR description. */
+  description: string;
+  commits: RangeCommit[];
+  diff: ParsedDiff;
+  session: ReviewSession;
+}
+
+/** The diff of a revision range in a local clone, analyzed like a PR diff. */
+export interface RangeDiff {
+  repoPath: string;
+  range: string;
+  /** "owner/name" PRs are listed from: the upstream remote, else origin, when on GitHub. */
+  repo: string | null;
+  /** The user's fork (origin) when `repo` comes from upstream. */
+  fork: string | null;
+  /** Commit subjects, standing in for the PR description. */
+  description: string;
+  commits: RangeCommit[];
+  files: ChangedFile[];
+  diff: string;
+}
+
+/** A GitHub branch comparison (`compare/base...head`) without a PR, analyzed like a PR diff. */
+export interface CompareDiff {
+  repo: string;
+  base: string;
+  head: string;
+  mergeBase: string;
+  /** Null when the comparison has more commits than the API lists. */
+  headSha: string | null;
+  /** Commits on `head` since the merge base. */
+  ahead: number;
+  /** Commits on `base` since the merge base, not part of the diff. */
+  behind: number;
+  /** The comparison on github.com. */
+  url: string;
+  /** Commit subjects, standing in for the PR description. */
+  description: string;
+  commits: RangeCommit[];
+  files: ChangedFile[];
+  diff: string;
+}
+
+/** GitHub repos behind a local clone's remotes. */
+export interface CloneRemotes {
+  /** Where PRs live: the upstream remote when it is on GitHub, otherwise origin. */
+  repo: string | null;
+  /** Name of the remote `repo` came from. */
+  remote: string | null;
+  /** The user's fork (origin) in a triangular workflow, where branches are pushed. */
+  fork: string | null;
+}
+
+/** Three-dot comparison of two local refs: the diff of `head` since its merge base with `base`. */
+export interface BranchComparison extends RangeDiff {
+  base: string;
+  head: string;
+  mergeBase: string;
+  /** Commits on `head` since the merge base (those in the diff). */
+  ahead: number;
+  /** Commits on `base` since the merge base, not part of the diff. */
+  behind: number;
+}
+
+export interface SideLine {
+  number: number;
+  text: string;
+}
+
+/** One row of a full-file split view: base on the left, head on the right. */
+export interface SideBySideRow {
+  kind: "context" | "remove" | "add" | "change";
+  left: SideLine | null;
+  right: SideLine | null;
+  /** Null for unchanged lines outside the hunks. */
+  hunkId: string | null;
+}
+
+export interface FullFileView {
+  filePath: string;
+  baseSha: string;
+  headSha: string;
+  /** False when the PR adds (no base) or deletes (no head) the file. */
+  baseExists: boolean;
+  headExists: boolean;
+  rows: SideBySideRow[];
+  warnings: string[];
+}
+
 export type GroupCategory = "schema" | "logic" | "api" | "ui" | "test" | "config" | "docs" | "refactor" | "other";
 
+export interface RiskEvidence {
+  hunkId: string;
+  reason: string;
+}
+
 export interface IntentGroup {
   id: string;
   title: string;
   category: GroupCategory;
   rationale: string;
   risk: "low" | "medium" | "high";
+  riskScore: number;
+  riskEvidence: RiskEvidence[];
   hunkIds: string[];
   reviewerChecklist: string[];
   suggestedTests: string[];
@@ -60,7 +228,63 @@ export interface AnalysisResult {
   questions: string[];
 }
 
+export interface LeftoverFinding {
+  hunkId: string;
+  filePath: string;
+  line: number | null;
+  kind: "debug" | "todo" | "commented-code";
+  text: string;
+}
+
+export interface RiskyPatternHit {
+  hunkId: string;
+  filePath: string;
+  line: number | null;
+  kind: "raw-sql" | "unsafe" | "eval" | "tls-disabled" | "swallowed-exception";
+  text: string;
+}
+
 export interface AnalysisResponse {
+  result: AnalysisResult;
+  leftovers?: LeftoverFinding[];
+  riskyPatterns?: RiskyPatternHit[];
+  codexLog: string;
+  fromCache: boolean;
+}
+
+/** Manual corrections for adjustAnalysisApi. Each merge lists group ids kept under the first one. */
+export interface AnalysisConstraints {
+  pins: { hunkId: string; groupId: string }[];
+  merges: string[][];
+}
+
+/** A manual grouping change, undoable with undoAnalysisEditApi. */
+export type AnalysisEdit =
+  /** `groupId` null moves the hunks to the unassigned list. */
+  | { op: "moveHunks"; hunkIds: string[]; groupId: string | null }
+  /** Merged into the first group listed. */
+  | { op: "mergeGroups"; groupIds: string[] }
+  | { op: "renameGroup"; groupId: string; title: string }
+  | { op: "createGroup"; title: string; hunkIds: string[]; category?: string | null };
+
+export interface AnalysisEditState {
+  analysis: AnalysisResult;
+  /** What undo/redo would revert or reapply, e.g. "Merge G1, G2"; null when there is nothing. */
+  undoLabel: string | null;
+  redoLabel: string | null;
+}
+
+/** What an analysis would send to the provider (dry run, nothing is executed). */
+export interface AnalysisPromptPreview {
+  prompt: string;
+  schema: string;
+  hunksJson: string;
+  summariesJson: string | null;
+  estimatedTokens: number;
+}
+
+/** An analysis with its text translated; ids, hunks, and risk are unchanged. */
+export interface TranslateResponse {
   result: AnalysisResult;
   codexLog: string;
   fromCache: boolean;
@@ -72,8 +296,871 @@ export interface RefineResponse {
   fromCache: boolean;
 }
 
+/** Analysis with one group replaced by the sub-groups of a guided refinement. */
+export interface GuidedRefineResponse {
+  result: AnalysisResult;
+  subGroups: IntentGroup[];
+  codexLog: string;
+  fromCache: boolean;
+}
+
 export interface ExplainResponse {
   explanation: string;
   codexLog: string;
   fromCache: boolean;
 }
+
+export interface HunkSummary {
+  hunkId: string;
+  summary: string;
+}
+
+export interface SummarizeResponse {
+  summaries: HunkSummary[];
+  codexLog: string;
+  fromCache: boolean;
+}
+
+export type CommentSeverity = "nit" | "suggestion" | "issue" | "blocker";
+
+export interface DraftComment {
+  hunkId: string;
+  filePath: string;
+  line: number;
+  side: "RIGHT" | "LEFT";
+  body: string;
+  severity: CommentSeverity;
+}
+
+/** A draft comment held in a PR's queue until the review is submitted. */
+export interface QueuedComment extends DraftComment {
+  id: string;
+  source: "ai" | "manual";
+  /** Unix seconds. */
+  createdAt: number;
+}
+
+/** Changes to a queued comment; omitted fields are kept. */
+export interface QueuedCommentEdit {
+  body?: string;
+  severity?: CommentSeverity;
+  line?: number;
+  side?: "RIGHT" | "LEFT";
+}
+
+/** An inline comment as sent in a GitHub review. */
+export interface ReviewCommentPayload {
+  path: string;
+  line: number;
+  side: "RIGHT" | "LEFT";
+  body: string;
+}
+
+export type ReviewVerdict = "approve" | "request_changes" | "comment";
+
+export interface AssembledReview {
+  event: string;
+  body: string;
+  comments: ReviewCommentPayload[];
+  /** Drafts whose line is no longer in the diff; they are listed in `body` instead. */
+  unplaced: DraftComment[];
+  dryRun: boolean;
+  url: string | null;
+  /** Open blocking items, listed in `body` of a "request changes" review. */
+  blockingItems: BlockingItem[];
+  /** The requested approval was sent as "request changes" because of open blocking items. */
+  approvalBlocked: boolean;
+}
+
+export interface DraftCommentsResponse {
+  comments: DraftComment[];
+  codexLog: string;
+  fromCache: boolean;
+}
+
+export interface HunkAnchor {
+  id: string;
+  filePath: string;
+  header: string;
+}
+
+/** An analysis exported for a co-reviewer (format "prvw-analysis"). */
+export interface SharedAnalysis {
+  format: string;
+  repo: string | null;
+  prNumber: number | null;
+  headSha: string | null;
+  analysis: AnalysisResult;
+  anchors: HunkAnchor[];
+}
+
+export interface ImportedAnalysis {
+  result: AnalysisResult;
+  /** Hunk ids renamed to match the local diff. */
+  remapped: number;
+  warnings: string[];
+  repo: string | null;
+  prNumber: number | null;
+}
+
+/** Review state of a PR as saved at one head commit. */
+export interface HeadState {
+  repo: string;
+  prNumber: number;
+  headSha: string;
+  anchors: HunkAnchor[];
+  fingerprints: Record<string, string>;
+  analysis: AnalysisResult | null;
+  session: ReviewSession;
+  comments: QueuedComment[];
+  savedAt: number;
+}
+
+/** Review state carried over to a new head commit. */
+export interface HeadMigration {
+  fromHeadSha: string;
+  headSha: string;
+  /** The analysis re-anchored on the new diff; null when none was saved for the old head. */
+  analysis: AnalysisResult | null;
+  session: ReviewSession;
+  comments: QueuedComment[];
+  /** Hunk ids renamed to match the new diff. */
+  remapped: number;
+  /** Review marks still valid at the new head. */
+  carriedMarks: number;
+  /** State that could not be carried over, one line each. */
+  notCarried: string[];
+  warnings: string[];
+}
+
+/** A panic or failed command saved by the opt-in error reporting, without diff content. */
+export interface ErrorReport {
+  id: string;
+  kind: "panic" | "command";
+  /** Operation kind of the failed command, or where the panic happened. */
+  source: string;
+  message: string;
+  backtrace: string | null;
+  appVersion: string;
+  os: string;
+  at: number;
+}
+
+/** New commits detected on the PR open in the window (`pr-head-changed`). */
+export interface HeadChange {
+  repo: string;
+  prNumber: number;
+  fromHeadSha: string;
+  headSha: string;
+}
+
+export interface HeadRefresh {
+  headSha: string;
+  /** The new diff, held as review state. */
+  review: ReviewState;
+  /** The previous analysis re-anchored on the new diff. */
+  analysis: AnalysisResult | null;
+  /** Review state carried over; null when the session had no progress at an older head. */
+  migration: HeadMigration | null;
+  /** What the push changed per group; `newHunkIds` are left to analyze. */
+  delta: AnalysisDelta | null;
+}
+
+/** Reusable checklist items merged into every group's reviewerChecklist. */
+export interface ChecklistTemplate {
+  /** Empty for a template not saved yet. */
+  id: string;
+  name: string;
+  /** "owner/name"; null applies the template to every repo. */
+  repo: string | null;
+  items: string[];
+}
+
+/** A reusable review comment; `{file}`, `{line}` and `{group}` are filled in when it is inserted. */
+export interface CommentTemplate {
+  /** Empty for a template not saved yet. */
+  id: string;
+  name: string;
+  body: string;
+  /** Empty saves as "suggestion". */
+  severity: CommentSeverity | "";
+  /** "owner/name"; null offers the template in every repo. */
+  repo: string | null;
+}
+
+export interface ReviewMark {
+  /** Unix seconds. */
+  at: number;
+  headSha: string | null;
+}
+
+export interface ReviewSession {
+  repo: string;
+  prNumber: number;
+  headSha: string | null;
+  reviewedGroups: Record<string, ReviewMark>;
+  reviewedHunks: Record<string, ReviewMark>;
+  blockingItems: BlockingItem[];
+  /** Checked reviewer checklist items, by ChecklistItem id. */
+  checkedItems: Record<string, ReviewMark>;
+  /** Groups set aside without review, by group id. */
+  skippedGroups: Record<string, GroupSkip>;
+  /** Reviewer (GitHub login) each intent group is assigned to, by group id. */
+  assignments: Record<string, string>;
+  /** The reviewer's own group order; only applies at its `headSha`. */
+  groupOrder: GroupOrder | null;
+  updatedAt: number;
+}
+
+/** A custom group order; groups it does not list follow in analysis order. */
+export interface GroupOrder {
+  groupIds: string[];
+  at: number;
+  headSha: string | null;
+}
+
+export interface GroupAssignment {
+  groupId: string;
+  title: string;
+  risk: string;
+  riskScore: number;
+  hunkCount: number;
+  /** Null while the group is unassigned. */
+  reviewer: string | null;
+}
+
+/** Assignment rows plus the same table as a Markdown comment body. */
+export interface AssignmentTable {
+  rows: GroupAssignment[];
+  markdown: string;
+}
+
+/** A reviewer checklist item of a group. */
+export interface ChecklistItem {
+  /** `<groupId>:checklist:<hash of the text>`, the id a blocking checklist item uses too. */
+  id: string;
+  groupId: string;
+  text: string;
+  /** Checked at the current head. */
+  checked: boolean;
+}
+
+/** A skip lasts until undone; a snooze only until the next push. */
+export interface GroupSkip {
+  reason: string;
+  snoozed: boolean;
+  at: number;
+  headSha: string | null;
+}
+
+/** A finding or checklist item that must be addressed before the PR can be approved. */
+export interface BlockingItem {
+  /** Stable per item, e.g. "G1:checklist:5d41402a". */
+  id: string;
+  kind: "checklist" | "test" | "question" | "comment" | "finding";
+  groupId?: string | null;
+  hunkId?: string | null;
+  text: string;
+  resolved?: boolean;
+  at?: number;
+}
+
+export interface SessionUpdate {
+  headSha?: string | null;
+  markGroups?: string[];
+  unmarkGroups?: string[];
+  markHunks?: string[];
+  unmarkHunks?: string[];
+  /** Marks items as blocking; re-marking a resolved item reopens it. */
+  blockItems?: BlockingItem[];
+  resolveItems?: string[];
+  unblockItems?: string[];
+  checkItems?: string[];
+  uncheckItems?: string[];
+  skipGroups?: string[];
+  skipReason?: string | null;
+  /** Skip `skipGroups` only until the next push. */
+  snooze?: boolean;
+  unskipGroups?: string[];
+  /** New custom group order; an empty list restores the analysis order. */
+  groupOrder?: string[];
+}
+
+export interface NextUnreviewed {
+  /** Null for unassigned hunks. */
+  groupId: string | null;
+  hunkId: string;
+  /** Unreviewed hunks left, this one included. */
+  remaining: number;
+}
+
+export interface ViewedSync {
+  /** Files fully reviewed locally that were marked viewed on GitHub. */
+  markedOnGithub: string[];
+  /** Files viewed on GitHub whose hunks were marked reviewed locally. */
+  markedLocally: string[];
+  session: ReviewSession;
+}
+
+export interface GroupProgress {
+  groupId: string;
+  title: string;
+  reviewedHunks: number;
+  totalHunks: number;
+  complete: boolean;
+  checkedItems: number;
+  totalChecklistItems: number;
+  /** Set while the group is skipped or snoozed; it then does not count towards progress. */
+  skip: GroupSkip | null;
+}
+
+export interface ReviewProgress {
+  totalHunks: number;
+  reviewedHunks: number;
+  /** Non-substantive hunks, counted as done without review. */
+  autoSkippedHunks: number;
+  /** Unreviewed hunks only in skipped or snoozed groups. */
+  skippedHunks: number;
+  groups: GroupProgress[];
+  timeSpentSecs: number;
+  readyToSubmit: boolean;
+  /** Unresolved blocking items; the PR cannot be approved while any are open. */
+  openBlockingItems: number;
+  checkedItems: number;
+  totalChecklistItems: number;
+}
+
+export interface ChatMessage {
+  role: "user" | "assistant";
+  content: string;
+  hunkIds: string[];
+}
+
+export interface AskPrResponse {
+  answer: ChatMessage;
+  codexLog: string;
+}
+
+export interface GroupDelta {
+  groupId: string;
+  title: string;
+  status: "new" | "changed" | "unchanged" | "removed";
+  matchedGroupId: string | null;
+  gainedHunkIds: string[];
+  lostHunkIds: string[];
+}
+
+export interface AnalysisDelta {
+  groups: GroupDelta[];
+  removedGroups: GroupDelta[];
+  newHunkIds: string[];
+}
+
+export interface ProviderInfo {
+  name: string;
+  capabilities: {
+    readsFiles: boolean;
+    nativeSchema: boolean;
+    cli: string | null;
+  };
+}
+
+export interface RepoContext {
+  repo: string;
+  prNumber: number;
+}
+
+/** Codex pass-through options; empty fields keep the Codex config default. */
+export interface RunOptions {
+  profile?: string;
+  reasoningEffort?: string;
+  approvalMode?: string;
+}
+
+/** App-wide defaults from `get_settings`; AI commands use them for options a call leaves unset. */
+export interface AppSettings {
+  model: string | null;
+  /** Null for English. */
+  lang: string | null;
+  /** Language of backend error and status messages ("en" or "ja"); null follows lang. */
+  uiLang: string | null;
+  provider: string | null;
+  runOptions: RunOptions;
+  timeoutMinutes: number | null;
+  /** 0 means unlimited. */
+  cacheLimitMb: number;
+  /** Files whose hunks are dropped from parsed diffs, e.g. "*.lock" or "generated/**". */
+  ignoreGlobs: string[];
+  /** GitHub Enterprise host for gh; null for github.com. */
+  ghHost: string | null;
+  /** Base URL of a Gitea or Forgejo instance, e.g. https://codeberg.org. */
+  giteaUrl: string | null;
+  /** Azure DevOps organization (or Server collection) URL, e.g. https://dev.azure.com/contoso. */
+  azureDevopsUrl: string | null;
+  useGuidelines: boolean;
+  useRepoContext: boolean;
+  tokenBudget: number;
+  costPerMTokens: number;
+  /** Opens a file at a line, with {file} and {line} placeholders; null for VS Code (`code -g {file}:{line}`). */
+  editorCommand: string | null;
+  notifications: NotificationSettings;
+  /** Save panics and failed commands as local error reports; off unless opted in. */
+  errorReports: boolean;
+  /** Extra prompt instructions per kind of run ("analysis", "refine"), from prvw.toml. */
+  prompts?: Record<string, string>;
+  /** Overrides per repo, keyed by lowercase owner/repo. */
+  repos: Record<string, RepoSettings>;
+}
+
+/** Which events fire a desktop notification; all on by default, only while the window is in the background. */
+export interface NotificationSettings {
+  /** Background pre-analysis of the review queue finished. */
+  analysesDone: boolean;
+  /** New commits were pushed to the open PR. */
+  newPushes: boolean;
+  /** CI started failing on a PR with review progress. */
+  ciFailures: boolean;
+  onlyWhenUnfocused: boolean;
+}
+
+/** Overrides of the app-wide settings while a repo is open; unset fields fall back to them. */
+export interface RepoSettings {
+  model?: string | null;
+  lang?: string | null;
+  /** Replaces the app-wide ignore globs for this repo. */
+  ignoreGlobs?: string[] | null;
+  /** Category -> label mapping used when labeling PRs without an explicit mapping. */
+  labelMapping?: Record<string, string> | null;
+  /** Checklist template ids applied to this repo besides those scoped to it. */
+  checklistTemplates?: string[];
+  /** The user's clone of the repo; PR checkouts become worktrees of it instead of a managed clone. */
+  clonePath?: string | null;
+  /** Subproject roots of a monorepo (e.g. "services/api"); hunks are tagged with the one they are in. */
+  subprojects?: string[];
+}
+
+/** Partial update for `update_settings`; an empty string clears a value. */
+export type SettingsUpdate = Partial<{
+  model: string;
+  lang: string;
+  uiLang: string;
+  provider: string;
+  runOptions: RunOptions;
+  /** 0 restores the default. */
+  timeoutMinutes: number;
+  cacheLimitMb: number;
+  ignoreGlobs: string[];
+  ghHost: string;
+  giteaUrl: string;
+  azureDevopsUrl: string;
+  useGuidelines: boolean;
+  useRepoContext: boolean;
+  tokenBudget: number;
+  costPerMTokens: number;
+  editorCommand: string;
+  notifications: NotificationSettings;
+  errorReports: boolean;
+}>;
+
+/** Result of `test_secret`: whether a stored token is accepted by its service. */
+export interface SecretTest {
+  name: string;
+  stored: boolean;
+  ok: boolean;
+  message: string;
+}
+
+/** The prvw.toml config file as last (re)loaded (`config-reloaded`). */
+export interface ConfigStatus {
+  path: string;
+  exists: boolean;
+  /** Why the file was rejected; the previous config stays in effect. */
+  error: string | null;
+  /** 0 before the first load. */
+  loadedAt: number;
+  /** Settings keys the file sets, e.g. "model" or "runOptions.profile"; Settings can't change them. */
+  overrides: string[];
+}
+
+/** Provider-reported token counts; Codex only reports a total. */
+export interface TokenUsage {
+  inputTokens: number | null;
+  outputTokens: number | null;
+  totalTokens: number;
+}
+
+/** Local usage metrics from `get_usage_stats`; nothing is sent anywhere. */
+export interface UsageStats {
+  /** Intent analyses that ran a provider (cache hits not included). */
+  analysesRun: number;
+  /** Provider runs by log label (analysis, refine, summarize, ...). */
+  runsByKind: Record<string, number>;
+  models: ModelLatency[];
+  cache: CacheStats[];
+  /** Hits over lookups across all namespaces; null before the first lookup. */
+  cacheHitRate: number | null;
+  reviews: ReviewDurations;
+}
+
+export interface ModelLatency {
+  provider: string;
+  model: string;
+  runs: number;
+  averageSecs: number;
+}
+
+/** Cache lookups of one namespace, e.g. "cache/analysis". */
+export interface CacheStats {
+  namespace: string;
+  hits: number;
+  misses: number;
+}
+
+/** Active review time per PR with review marks. */
+export interface ReviewDurations {
+  reviewedPrs: number;
+  totalSecs: number;
+  averageSecs: number;
+  medianSecs: number;
+}
+
+export interface UsageEntry {
+  /** Unix seconds. */
+  timestamp: number;
+  kind: string;
+  provider: string;
+  model: string;
+  estimatedInputTokens: number;
+  usage: TokenUsage | null;
+  elapsedSecs: number;
+}
+
+/** A persisted provider invocation (see `get_codex_run`). */
+export interface CodexRun {
+  id: string;
+  timestamp: number;
+  kind: string;
+  provider: string;
+  model: string;
+  args: string[];
+  prompt: string;
+  elapsedSecs: number;
+  warnings: string[];
+  stdout: string;
+  stderr: string;
+  outputTruncated: boolean;
+  error: string | null;
+}
+
+export interface CodexRunSummary {
+  id: string;
+  timestamp: number;
+  kind: string;
+  provider: string;
+  model: string;
+  elapsedSecs: number;
+  warningCount: number;
+  error: string | null;
+}
+
+export interface TokenEstimate {
+  estimatedTokens: number;
+  budget: number | null;
+  overBudget: boolean;
+}
+
+export interface CallSite {
+  filePath: string;
+  line: number;
+  text: string;
+  inDiff: boolean;
+}
+
+/** Call sites in the PR head of one function changed by an intent group. */
+export interface SymbolImpact {
+  groupId: string;
+  symbol: string;
+  hunkIds: string[];
+  callSites: CallSite[];
+  truncated: boolean;
+}
+
+export interface ImpactResponse {
+  headSha: string;
+  impacts: SymbolImpact[];
+}
+
+export interface DependencyChange {
+  manifest: string;
+  name: string;
+  change: "added" | "removed" | "bumped";
+  from: string | null;
+  to: string | null;
+}
+
+export interface PrefetchRequest {
+  repo: string;
+  model: string | null;
+  lang: string | null;
+  provider: string | null;
+  options: RunOptions;
+  useGuidelines: boolean;
+  useRepoContext: boolean;
+  timeoutMinutes: number | null;
+  /** Seconds to wait before starting; 0 starts now. */
+  delaySecs: number;
+}
+
+export interface PrefetchStatus {
+  phase: "idle" | "scheduled" | "running" | "done" | "cancelled";
+  repo: string | null;
+  /** Unix seconds. */
+  startsAt: number | null;
+  total: number;
+  completed: number;
+  failed: number;
+  currentPr: number | null;
+  errors: string[];
+}
+
+export interface ReminderRequest {
+  repo: string;
+  /** Review requests open at least this many days are reminded about. */
+  staleDays: number;
+  /** Minutes between checks; null checks hourly. */
+  intervalMinutes: number | null;
+}
+
+/** A PR awaiting the user's review with no review progress at its current head. */
+export interface StaleReview {
+  prNumber: number;
+  title: string;
+  url: string;
+  headSha: string | null;
+  ageDays: number;
+}
+
+export interface ReminderStatus {
+  active: boolean;
+  repo: string | null;
+  staleDays: number;
+  /** Unix seconds. */
+  lastCheckedAt: number | null;
+  pending: StaleReview[];
+  errors: string[];
+}
+
+/** One proposed PR of a split plan; `dependsOn` holds 1-based positions of earlier proposed PRs. */
+export interface SplitPr {
+  title: string;
+  description: string;
+  hunkIds: string[];
+  dependsOn: number[];
+}
+
+export interface SplitPlan {
+  rationale: string;
+  prs: SplitPr[];
+  unassignedHunkIds: string[];
+}
+
+export interface SplitPlanResponse {
+  plan: SplitPlan;
+  codexLog: string;
+  fromCache: boolean;
+}
+
+export interface ReleaseNoteEntry {
+  category: "added" | "changed" | "fixed";
+  text: string;
+  groupIds: string[];
+}
+
+export interface ThreadComment {
+  author: string;
+  body: string;
+  createdAt: string;
+}
+
+/** An inline review thread, as returned by getReviewThreadsApi. */
+export interface ReviewThread {
+  id: string;
+  path: string | null;
+  line: number | null;
+  isResolved: boolean;
+  isOutdated: boolean;
+  comments: ThreadComment[];
+}
+
+export interface ThreadSummary {
+  threadId: string;
+  status: "resolved" | "open";
+  asked: string;
+  resolved: string;
+  open: string;
+}
+
+export interface ThreadSummariesResponse {
+  /** Open threads first. */
+  summaries: ThreadSummary[];
+  codexLog: string;
+  fromCache: boolean;
+}
+
+export interface LabelResult {
+  /** Labels added to the PR (or that would be, on a dry run). */
+  applied: string[];
+  /** Mapped labels that do not exist in the repository. */
+  missing: string[];
+  dryRun: boolean;
+}
+
+/** A question or finding to track as a follow-up issue. */
+export interface FollowUpItem {
+  kind: "question" | "finding";
+  text: string;
+  /** Defaults to the first line of `text`. */
+  title?: string | null;
+  groupId?: string | null;
+  filePath?: string | null;
+  line?: number | null;
+}
+
+export interface FollowUpIssue {
+  title: string;
+  body: string;
+  /** Null on a dry run or when creating the issue failed. */
+  url: string | null;
+  error: string | null;
+}
+
+/** A recent PR touching some of the same files as the reviewed one. */
+export interface RelatedPr {
+  number: number;
+  title: string;
+  url: string;
+  state: "OPEN" | "CLOSED" | "MERGED";
+  author: string | null;
+  overlappingFiles: string[];
+  relation: "conflict" | "duplicate" | "none";
+  reason: string;
+}
+
+export interface RelatedPrsResponse {
+  /** Likely conflicts first, then duplicates. */
+  related: RelatedPr[];
+  codexLog: string;
+  fromCache: boolean;
+}
+
+export interface ReleaseNotesResponse {
+  entries: ReleaseNoteEntry[];
+  /** Entries rendered as a Markdown changelog section. */
+  markdown: string;
+  headSha: string;
+  codexLog: string;
+  fromCache: boolean;
+}
+
+/** State of the watched index of a local repo. */
+export interface StagedStatus {
+  watching: boolean;
+  repoPath: string | null;
+  /** Changes whenever the staged content does; null when nothing is staged. */
+  stamp: string | null;
+  files: ChangedFile[];
+  /** Unix seconds. */
+  changedAt: number | null;
+  error: string | null;
+}
+
+/** State of the pre-push hook of a local repo. */
+/** State of the localhost API for editor extensions. */
+export interface ApiServerStatus {
+  /** Started now and again at each launch. */
+  enabled: boolean;
+  running: boolean;
+  port: number;
+  /** Bearer token clients must send in `Authorization`. */
+  token: string;
+}
+
+/** The tray icon's view of a repo's review queue. */
+export interface TrayStatus {
+  /** The last repo whose PRs were listed. */
+  repo: string | null;
+  pending: PrListItem[];
+  lastCheckedAt: number | null;
+  error: string | null;
+  /** Closing the window hides it to the tray instead of quitting. */
+  keepRunning: boolean;
+}
+
+/** Payload of `open-pr`: a PR picked from the tray menu or opened through a prvw:// link. */
+export interface OpenPr {
+  repo: string;
+  pr: PrListItem;
+}
+
+export interface PushHookStatus {
+  hookPath: string;
+  /** prvw's hook is installed. */
+  installed: boolean;
+  /** Another pre-push hook exists; prvw won't overwrite it. */
+  foreign: boolean;
+  /** Lowest risk level that blocks a push, when installed. */
+  failOn: "low" | "medium" | "high" | null;
+}
+
+export interface SelfReviewResponse {
+  commitMessage: string;
+  /** Things to verify before pushing. */
+  checklist: string[];
+  stamp: string;
+  codexLog: string;
+  fromCache: boolean;
+}
+
+/** A change to a public surface that may break callers, tied to one intent group. */
+export interface BreakingChange {
+  groupId: string;
+  hunkIds: string[];
+  kind: "symbol" | "endpoint" | "schema" | "config" | "cli" | "other";
+  subject: string;
+  description: string;
+  severity: "breaking" | "possibly-breaking";
+}
+
+export interface BreakingChangesResponse {
+  findings: BreakingChange[];
+  codexLog: string;
+  fromCache: boolean;
+}
+
+/** A gap between the PR title/description and the analyzed changes. */
+export interface ConsistencyMismatch {
+  kind: "undisclosed" | "unfulfilled" | "contradicted";
+  claim: string;
+  groupIds: string[];
+  description: string;
+  severity: "low" | "medium" | "high";
+}
+
+export interface ConsistencyResponse {
+  consistent: boolean;
+  summary: string;
+  mismatches: ConsistencyMismatch[];
+  codexLog: string;
+  fromCache: boolean;
+}
+
+/** A long-running command as reported by its `op-progress`/`op-done`/`op-error` events. */
+export interface OperationRecord {
+  opId: string;
+  kind: string;
+  status: "running" | "done" | "error" | "cancelled";
+  message: string | null;
+  error: string | null;
+  startedAt: number;
+  elapsedMs: number;
+}
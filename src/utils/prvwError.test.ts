@@ -0,0 +1,38 @@
+import { describe, expect, test } from "vitest";
+import { asPrvwError, errorMessage, isErrorKind } from "./prvwError";
+
+describe("errorMessage", () => {
+  test("non-backend errors are shown as-is", () => {
+    expect(errorMessage("Failed to open URL")).toBe("Failed to open URL");
+    expect(asPrvwError("Failed to open URL")).toBeNull();
+  });
+
+  test("backend errors append their remediation", () => {
+    const e = {
+      kind: "rateLimited",
+      program: "codex",
+      detail: "429",
+      message: "codex rate limit exceeded: 429",
+      remediation: "Wait a minute before retrying, or switch provider in Settings.",
+      retryable: true,
+    };
+    expect(asPrvwError(e)?.kind).toBe("rateLimited");
+    expect(errorMessage(e)).toBe(
+      "codex rate limit exceeded: 429 Wait a minute before retrying, or switch provider in Settings.",
+    );
+  });
+
+  test("backend errors without a remediation show only the message", () => {
+    const e = { kind: "notFound", message: "Run 7 not found", remediation: null, retryable: false };
+    expect(errorMessage(e)).toBe("Run 7 not found");
+  });
+});
+
+describe("isErrorKind", () => {
+  test("matches any of the given kinds", () => {
+    const e = { kind: "notConfigured", message: "Gitea token is not set.", remediation: null, retryable: false };
+    expect(isErrorKind(e, "notConfigured")).toBe(true);
+    expect(isErrorKind(e, "notInstalled", "notAuthenticated")).toBe(false);
+    expect(isErrorKind("notConfigured", "notConfigured")).toBe(false);
+  });
+});
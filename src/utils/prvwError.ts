@@ -0,0 +1,41 @@
+/** Typed provider failure (the backend's `RunError`), carried by `PrvwError` under its own kinds. */
+export type RunError =
+  | { kind: "notInstalled"; program: string; hint: string }
+  | { kind: "notAuthenticated"; program: string; hint: string }
+  | { kind: "timeout"; program: string; minutes: number }
+  | { kind: "schemaViolation"; program: string; detail: string }
+  | { kind: "emptyOutput"; program: string; detail: string }
+  | { kind: "rateLimited"; program: string; detail: string }
+  | { kind: "cancelled"; program: string }
+  | { kind: "unknown"; stderr: string };
+
+/** Error of every backend command, as serialized by `PrvwError`. */
+export type PrvwError = {
+  message: string;
+  /** What the user can do about it, when `message` does not already say so. */
+  remediation: string | null;
+  /** Whether running the same command again may succeed. */
+  retryable: boolean;
+} & (RunError | { kind: "notConfigured" | "invalidInput" | "notFound" | "network" | "other" });
+
+export type PrvwErrorKind = PrvwError["kind"];
+
+export function asPrvwError(e: unknown): PrvwError | null {
+  if (typeof e === "object" && e !== null && "kind" in e && "message" in e) {
+    return e as PrvwError;
+  }
+  return null;
+}
+
+/** Whether `e` is a backend error of one of `kinds`. */
+export function isErrorKind(e: unknown, ...kinds: PrvwErrorKind[]): boolean {
+  const error = asPrvwError(e);
+  return error !== null && kinds.includes(error.kind);
+}
+
+/** Error text for display: backend errors with their remediation, anything else as-is. */
+export function errorMessage(e: unknown): string {
+  const error = asPrvwError(e);
+  if (!error) return String(e);
+  return error.remediation ? `${error.message} ${error.remediation}` : error.message;
+}